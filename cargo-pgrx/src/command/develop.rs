@@ -0,0 +1,346 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+use crate::command::get::get_property;
+use crate::command::install::{
+    build_extension, dylib_extension, find_library_file, install_extension,
+};
+use crate::command::start::start_postgres;
+use crate::manifest::{get_package_manifest, pg_config_and_version};
+use crate::profile::CargoProfile;
+use crate::CommandExecute;
+use cargo_toml::Manifest;
+use eyre::{eyre, WrapErr};
+use owo_colors::OwoColorize;
+use pgrx_pg_config::{createdb, get_target_dir, PgConfig, Pgrx};
+use std::collections::BTreeMap;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime};
+
+/// Rebuild the extension as its sources change and hot-reload its functions into a running
+/// pgrx-managed Postgres, without restarting it
+#[derive(clap::Args, Debug)]
+#[clap(author)]
+pub(crate) struct Develop {
+    /// Do you want to run against Postgres `pg11`, `pg12`, `pg13`, `pg14`, `pg15`?
+    #[clap(env = "PG_VERSION")]
+    pg_version: Option<String>,
+    /// The database to reload functions into (and create if the first time).  Defaults to a database with the same name as the current extension name
+    dbname: Option<String>,
+    /// Package to build (see `cargo help pkgid`)
+    #[clap(long, short)]
+    package: Option<String>,
+    /// Path to Cargo.toml
+    #[clap(long, value_parser)]
+    manifest_path: Option<PathBuf>,
+    /// Compile for release mode (default is debug)
+    #[clap(long, short)]
+    release: bool,
+    /// Specific profile to use (conflicts with `--release`)
+    #[clap(long)]
+    profile: Option<String>,
+    #[clap(flatten)]
+    features: clap_cargo::Features,
+    #[clap(from_global, action = ArgAction::Count)]
+    verbose: u8,
+    /// How often, in milliseconds, to check the sources for changes
+    #[clap(long, default_value_t = 500)]
+    poll_interval: u64,
+}
+
+impl CommandExecute for Develop {
+    #[tracing::instrument(level = "error", skip(self))]
+    fn execute(mut self) -> eyre::Result<()> {
+        let pgrx = Pgrx::from_config()?;
+        let (package_manifest, package_manifest_path) = get_package_manifest(
+            &self.features,
+            self.package.as_ref(),
+            self.manifest_path.as_ref(),
+        )?;
+        let (pg_config, _pg_version) = pg_config_and_version(
+            &pgrx,
+            &package_manifest,
+            self.pg_version.clone(),
+            Some(&mut self.features),
+            true,
+        )?;
+
+        let extname = get_property(&package_manifest_path, "extname")?
+            .ok_or(eyre!("could not determine extension name"))?;
+        let dbname = self.dbname.clone().unwrap_or_else(|| extname.clone());
+        let profile = CargoProfile::from_flags(
+            self.profile.as_deref(),
+            self.release.then_some(CargoProfile::Release).unwrap_or(CargoProfile::Dev),
+        )?;
+
+        develop(
+            &pg_config,
+            self.manifest_path.as_ref(),
+            self.package.as_ref(),
+            &package_manifest_path,
+            &package_manifest,
+            &extname,
+            &dbname,
+            &profile,
+            &self.features,
+            Duration::from_millis(self.poll_interval),
+        )
+    }
+}
+
+#[tracing::instrument(level = "error", skip_all, fields(
+    pg_version = %pg_config.version()?,
+    dbname,
+    profile = ?profile,
+))]
+fn develop(
+    pg_config: &PgConfig,
+    user_manifest_path: Option<&PathBuf>,
+    user_package: Option<&String>,
+    package_manifest_path: &Path,
+    package_manifest: &Manifest,
+    extname: &str,
+    dbname: &str,
+    profile: &CargoProfile,
+    features: &clap_cargo::Features,
+    poll_interval: Duration,
+) -> eyre::Result<()> {
+    // do a regular install first so that `CREATE EXTENSION` works, and so the functions we later
+    // replace already exist as members of the extension
+    install_extension(
+        user_manifest_path,
+        user_package,
+        package_manifest_path,
+        pg_config,
+        profile,
+        false,
+        None,
+        features,
+    )?;
+    start_postgres(pg_config)?;
+    if !createdb(pg_config, dbname, false, true)? {
+        println!("{} existing database {}", "    Re-using".bold().cyan(), dbname);
+    }
+    run_sql(pg_config, dbname, &format!("CREATE EXTENSION IF NOT EXISTS \"{extname}\";"))?;
+
+    let package_dir = package_manifest_path
+        .parent()
+        .ok_or(eyre!("could not determine package directory"))?
+        .to_path_buf();
+    let mut snapshot = source_snapshot(&package_dir)?;
+    let mut previous_so: Option<PathBuf> = None;
+
+    println!(
+        "{} for changes in {}",
+        "    Watching".bold().green(),
+        package_dir.display().to_string().cyan()
+    );
+
+    loop {
+        std::thread::sleep(poll_interval);
+
+        let current = source_snapshot(&package_dir)?;
+        if current == snapshot {
+            continue;
+        }
+        snapshot = current;
+
+        match reload(
+            pg_config,
+            user_manifest_path,
+            user_package,
+            package_manifest_path,
+            package_manifest,
+            extname,
+            dbname,
+            profile,
+            features,
+        ) {
+            Ok(new_so) => {
+                // backends that already loaded the previous library keep it mapped, so it's
+                // safe to remove it out from under them
+                if let Some(previous_so) = previous_so.replace(new_so) {
+                    let _ = std::fs::remove_file(previous_so);
+                }
+            }
+            Err(e) => eprintln!("{} {e:#}", "       Error".bold().red()),
+        }
+    }
+}
+
+/// Rebuild the extension, install the new shared library under a unique name, and re-point its
+/// functions at it.  Returns the path of the installed shared library
+fn reload(
+    pg_config: &PgConfig,
+    user_manifest_path: Option<&PathBuf>,
+    user_package: Option<&String>,
+    package_manifest_path: &Path,
+    package_manifest: &Manifest,
+    extname: &str,
+    dbname: &str,
+    profile: &CargoProfile,
+    features: &clap_cargo::Features,
+) -> eyre::Result<PathBuf> {
+    let build_output =
+        build_extension(user_manifest_path, user_package, package_manifest, profile, features)?;
+    let messages = cargo_metadata::Message::parse_stream(BufReader::new(&*build_output.stdout))
+        .collect::<Result<Vec<_>, std::io::Error>>()?;
+    let shlibpath = find_library_file(package_manifest, &messages)?;
+
+    // Postgres never loads the same library path twice, so each reload gets a fresh name.  The
+    // functions name the whole file, so Postgres doesn't append its own idea of the suffix
+    let stamp = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?.as_millis();
+    let so_file = format!("{extname}-develop-{stamp}.{}", dylib_extension());
+    let mut dest = pg_config.pkglibdir()?;
+    dest.push(&so_file);
+    std::fs::copy(&shlibpath, &dest).wrap_err_with(|| {
+        format!("failed copying `{}` to `{}`", shlibpath.display(), dest.display())
+    })?;
+
+    // `cargo pgrx schema` loads the extension library into its own process, so generate the
+    // statements from a fresh process each time to avoid seeing the first build's entities forever
+    let mut reload_file = get_target_dir()?;
+    reload_file.push("pgrx-develop");
+    std::fs::create_dir_all(&reload_file)?;
+    reload_file.push(format!("{extname}.reload.sql"));
+    generate_function_replacements(
+        pg_config,
+        user_manifest_path,
+        user_package,
+        profile,
+        features,
+        &format!("$libdir/{so_file}"),
+        &reload_file,
+    )?;
+
+    let functions = std::fs::read_to_string(&reload_file)
+        .wrap_err_with(|| format!("failed to read `{}`", reload_file.display()))?;
+    if functions.trim().is_empty() {
+        eyre::bail!("the extension has no functions to reload");
+    }
+    run_sql_file(pg_config, dbname, &reload_file)?;
+
+    println!(
+        "{} functions from {}",
+        "    Reloaded".bold().green(),
+        dest.display().to_string().cyan()
+    );
+    Ok(dest)
+}
+
+/// Invoke `cargo pgrx schema` in a child process, writing `CREATE OR REPLACE FUNCTION` statements
+/// for the extension's functions, loading them from `module_pathname`, to `out`
+fn generate_function_replacements(
+    pg_config: &PgConfig,
+    user_manifest_path: Option<&PathBuf>,
+    user_package: Option<&String>,
+    profile: &CargoProfile,
+    features: &clap_cargo::Features,
+    module_pathname: &str,
+    out: &Path,
+) -> eyre::Result<()> {
+    let mut command = Command::new(std::env::current_exe()?);
+    command.args(["pgrx", "schema", "--skip-build", "--out"]).arg(out);
+    command.arg("--replace-functions").arg(module_pathname);
+    command.arg("--pg-config").arg(pg_config.path().ok_or(eyre!("no pg_config path"))?);
+    if let Some(user_manifest_path) = user_manifest_path {
+        command.arg("--manifest-path").arg(user_manifest_path);
+    }
+    if let Some(user_package) = user_package {
+        command.arg("--package").arg(user_package);
+    }
+    match profile {
+        CargoProfile::Dev => {}
+        CargoProfile::Release => {
+            command.arg("--release");
+        }
+        CargoProfile::Profile(name) => {
+            command.arg("--profile").arg(name);
+        }
+    }
+    let features_arg = features.features.join(" ");
+    if !features_arg.trim().is_empty() {
+        command.arg("--features").arg(&features_arg);
+    }
+    if features.no_default_features {
+        command.arg("--no-default-features");
+    }
+    if features.all_features {
+        command.arg("--all-features");
+    }
+
+    let status = command.stdout(Stdio::null()).status()?;
+    if !status.success() {
+        eyre::bail!("failed to generate schema");
+    }
+    Ok(())
+}
+
+/// The modification times of every file under the package's `src/` directory, plus its `Cargo.toml`
+fn source_snapshot(package_dir: &Path) -> eyre::Result<BTreeMap<PathBuf, SystemTime>> {
+    fn visit(dir: &Path, snapshot: &mut BTreeMap<PathBuf, SystemTime>) -> eyre::Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if entry.file_type()?.is_dir() {
+                visit(&path, snapshot)?;
+            } else {
+                snapshot.insert(path, entry.metadata()?.modified()?);
+            }
+        }
+        Ok(())
+    }
+
+    let mut snapshot = BTreeMap::new();
+    visit(&package_dir.join("src"), &mut snapshot)?;
+    let cargo_toml = package_dir.join("Cargo.toml");
+    snapshot.insert(cargo_toml.clone(), std::fs::metadata(&cargo_toml)?.modified()?);
+    Ok(snapshot)
+}
+
+fn psql(pg_config: &PgConfig, dbname: &str) -> eyre::Result<Command> {
+    let mut command = Command::new(pg_config.psql_path()?);
+    command
+        .env_remove("PGDATABASE")
+        .env_remove("PGHOST")
+        .env_remove("PGPORT")
+        .env_remove("PGUSER")
+        .arg("-h")
+        .arg(pg_config.host())
+        .arg("-p")
+        .arg(pg_config.port()?.to_string())
+        .arg("-v")
+        .arg("ON_ERROR_STOP=1")
+        .arg("--quiet")
+        .arg(dbname);
+    Ok(command)
+}
+
+fn run_sql(pg_config: &PgConfig, dbname: &str, sql: &str) -> eyre::Result<()> {
+    let status = psql(pg_config, dbname)?.arg("-c").arg(sql).status()?;
+    if !status.success() {
+        eyre::bail!("failed to execute `{sql}`");
+    }
+    Ok(())
+}
+
+fn run_sql_file(pg_config: &PgConfig, dbname: &str, file: &Path) -> eyre::Result<()> {
+    // run everything in a single transaction so a function whose signature can't be replaced
+    // in-place doesn't leave the others pointing at different libraries
+    let status =
+        psql(pg_config, dbname)?.arg("--single-transaction").arg("-f").arg(file).status()?;
+    if !status.success() {
+        eyre::bail!(
+            "failed to reload functions.  Changing a function's signature requires `cargo pgrx run`"
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,337 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+use crate::CommandExecute;
+use cargo_toml::Manifest;
+use owo_colors::OwoColorize;
+use pgrx_pg_config::{PgConfig, PgConfigSelector, Pgrx, SUPPORTED_VERSIONS};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Check the build environment for problems that commonly break building `pgrx-pg-sys`
+#[derive(clap::Args, Debug)]
+#[clap(author)]
+pub(crate) struct Doctor {
+    /// Package whose feature flags should be checked (see `cargo help pkgid`)
+    #[clap(long, short)]
+    package: Option<String>,
+    /// Path to Cargo.toml
+    #[clap(long, value_parser)]
+    manifest_path: Option<PathBuf>,
+    #[clap(from_global, action = ArgAction::Count)]
+    verbose: u8,
+}
+
+impl CommandExecute for Doctor {
+    #[tracing::instrument(level = "error", skip(self))]
+    fn execute(self) -> eyre::Result<()> {
+        let mut report = Report::default();
+        check_libclang(&mut report);
+        check_tool(&mut report, "rustfmt", "RUSTFMT", Severity::Warning, RUSTFMT_FIX);
+        check_tool(&mut report, "make", "MAKE", Severity::Error, MAKE_FIX);
+        check_postgres(&mut report);
+        self.check_features(&mut report);
+
+        if report.errors > 0 {
+            println!(
+                "\n{} problem(s) found that will likely break the build, and {} warning(s)",
+                report.errors.bold().red(),
+                report.warnings.bold().yellow()
+            );
+            // We explicitly do not want to return a spantraced error here.
+            std::process::exit(1)
+        }
+        println!(
+            "\nNo problems found that should break the build, and {} warning(s)",
+            report.warnings.bold().yellow()
+        );
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Default)]
+struct Report {
+    errors: usize,
+    warnings: usize,
+}
+
+impl Report {
+    fn ok(&mut self, check: &str, detail: impl AsRef<str>) {
+        println!("{} {check}: {}", "     Ok".bold().green(), detail.as_ref());
+    }
+
+    fn problem(&mut self, severity: Severity, check: &str, detail: impl AsRef<str>, fix: &str) {
+        match severity {
+            Severity::Warning => {
+                self.warnings += 1;
+                println!("{} {check}: {}", "   Warn".bold().yellow(), detail.as_ref());
+            }
+            Severity::Error => {
+                self.errors += 1;
+                println!("{} {check}: {}", "  Error".bold().red(), detail.as_ref());
+            }
+        }
+        for line in fix.lines() {
+            println!("        {} {line}", "fix:".cyan());
+        }
+    }
+}
+
+const LIBCLANG_FIX: &str = "\
+install libclang, for example with `apt install libclang-dev`, `dnf install clang-devel`, or `brew install llvm`
+or set LIBCLANG_PATH to the directory containing it";
+
+const RUSTFMT_FIX: &str = "\
+install it with `rustup component add rustfmt`; it's only needed when generating bindings for a release";
+
+const MAKE_FIX: &str = "\
+install it, for example with `apt install build-essential`, `dnf install make`, or `xcode-select --install`
+or set MAKE to the `make` to use; it's needed to compile the `cshim` feature";
+
+const POSTGRES_FIX: &str = "\
+run `cargo pgrx init` to have pgrx build and manage its own Postgres installations
+or install your distribution's Postgres server development package, such as `postgresql-server-dev-15`";
+
+const MACOS_SDK_FIX: &str = "\
+install or update the Xcode command line tools with `xcode-select --install`
+then rerun `cargo pgrx init` so Postgres is configured against an SDK that exists";
+
+fn check_libclang(report: &mut Report) {
+    const CHECK: &str = "libclang";
+    let is_libclang = |path: &Path| {
+        path.file_name()
+            .and_then(|name| name.to_str())
+            .map(|name| name.starts_with("libclang") && !name.ends_with(".a"))
+            .unwrap_or(false)
+    };
+    let find_in = |dir: &Path| {
+        std::fs::read_dir(dir)
+            .ok()?
+            .flatten()
+            .map(|entry| entry.path())
+            .find(|path| is_libclang(path))
+    };
+
+    if let Some(dir) = std::env::var_os("LIBCLANG_PATH") {
+        let dir = PathBuf::from(dir);
+        match find_in(&dir) {
+            Some(found) => report.ok(CHECK, format!("found {} (LIBCLANG_PATH)", found.display())),
+            None => report.problem(
+                Severity::Error,
+                CHECK,
+                format!("LIBCLANG_PATH is set to {}, which has no libclang", dir.display()),
+                LIBCLANG_FIX,
+            ),
+        }
+        return;
+    }
+
+    let mut dirs = Vec::new();
+    if let Some(libdir) = stdout(Command::new("llvm-config").arg("--libdir")) {
+        dirs.push(PathBuf::from(libdir));
+    }
+    for dir in [
+        "/usr/lib",
+        "/usr/lib64",
+        "/usr/local/lib",
+        "/usr/lib/x86_64-linux-gnu",
+        "/usr/lib/aarch64-linux-gnu",
+        "/opt/homebrew/opt/llvm/lib",
+        "/usr/local/opt/llvm/lib",
+        "/Library/Developer/CommandLineTools/usr/lib",
+        "/Applications/Xcode.app/Contents/Developer/Toolchains/XcodeDefault.xctoolchain/usr/lib",
+    ] {
+        dirs.push(PathBuf::from(dir));
+    }
+    // distributions often install versioned LLVMs side by side, such as /usr/lib/llvm-15/lib
+    for parent in ["/usr/lib", "/usr/lib64"] {
+        if let Ok(entries) = std::fs::read_dir(parent) {
+            for entry in entries.flatten() {
+                if entry.file_name().to_string_lossy().starts_with("llvm") {
+                    dirs.push(entry.path().join("lib"));
+                }
+            }
+        }
+    }
+
+    match dirs.iter().find_map(|dir| find_in(dir)) {
+        Some(found) => report.ok(CHECK, format!("found {}", found.display())),
+        None => report.problem(
+            Severity::Error,
+            CHECK,
+            "not found, so bindgen won't be able to generate bindings",
+            LIBCLANG_FIX,
+        ),
+    }
+}
+
+fn check_tool(report: &mut Report, tool: &str, envar: &str, severity: Severity, fix: &str) {
+    let program = std::env::var(envar).unwrap_or_else(|_| tool.to_string());
+    match stdout(Command::new(&program).arg("--version")) {
+        Some(version) => report.ok(tool, version.lines().next().unwrap_or_default().to_string()),
+        None => report.problem(severity, tool, format!("`{program} --version` failed"), fix),
+    }
+}
+
+fn check_postgres(report: &mut Report) {
+    const CHECK: &str = "Postgres headers";
+    let pg_configs = match Pgrx::from_config() {
+        Ok(pgrx) => pgrx.iter(PgConfigSelector::All).collect::<Vec<_>>(),
+        Err(e) => {
+            report.problem(
+                Severity::Error,
+                CHECK,
+                format!("no pgrx configuration: {e}"),
+                POSTGRES_FIX,
+            );
+            return;
+        }
+    };
+    if pg_configs.is_empty() {
+        report.problem(Severity::Error, CHECK, "no Postgres versions are configured", POSTGRES_FIX);
+    }
+
+    for pg_config in pg_configs {
+        let pg_config = match pg_config {
+            Ok(pg_config) => pg_config,
+            Err(e) => {
+                report.problem(Severity::Error, CHECK, e.to_string(), POSTGRES_FIX);
+                continue;
+            }
+        };
+        let label = pg_config.label().unwrap_or_else(|_| "pg?".to_string());
+        let check = format!("{CHECK} ({label})");
+        match pg_config.includedir_server() {
+            Ok(dir) if dir.join("postgres.h").is_file() => {
+                report.ok(&check, format!("found {}", dir.display()))
+            }
+            Ok(dir) => report.problem(
+                Severity::Error,
+                &check,
+                format!("{} has no postgres.h", dir.display()),
+                POSTGRES_FIX,
+            ),
+            Err(e) => report.problem(
+                Severity::Error,
+                &check,
+                format!("couldn't run pg_config: {e}"),
+                POSTGRES_FIX,
+            ),
+        }
+        if cfg!(target_os = "macos") {
+            check_macos_sdk(report, &label, &pg_config);
+        }
+    }
+}
+
+/// Mirrors how `pgrx-pg-sys`'s build script finds the SDK Postgres was configured with
+fn check_macos_sdk(report: &mut Report, label: &str, pg_config: &PgConfig) {
+    let check = format!("macOS SDK ({label})");
+    let Ok(flags) = pg_config.cppflags() else { return };
+    let flags = shlex::split(&flags.to_string_lossy()).unwrap_or_default();
+    for pair in flags.windows(2) {
+        if pair[0] == "-isysroot" {
+            if Path::new(&pair[1]).exists() {
+                report.ok(&check, format!("found {}", pair[1]));
+            } else {
+                report.problem(
+                    Severity::Error,
+                    &check,
+                    format!(
+                        "Postgres was configured with the SDK {}, which no longer exists",
+                        pair[1]
+                    ),
+                    MACOS_SDK_FIX,
+                );
+            }
+        }
+    }
+}
+
+impl Doctor {
+    fn check_features(&self, report: &mut Report) {
+        const CHECK: &str = "feature flags";
+        let features = clap_cargo::Features::default();
+        let manifest = crate::manifest::get_package_manifest(
+            &features,
+            self.package.as_ref(),
+            self.manifest_path.as_ref(),
+        );
+        let (manifest, path) = match manifest {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                println!("{} {CHECK}: not in a pgrx extension ({e})", "   Skip".bold().white());
+                return;
+            }
+        };
+        let path = path.display();
+
+        let defaults = manifest
+            .features
+            .get("default")
+            .map(|default| default.iter().filter(|f| is_pg_feature(f)).cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+        match defaults.as_slice() {
+            [] => report.problem(
+                Severity::Warning,
+                CHECK,
+                format!("{path} has no default `pgXX` feature"),
+                "pass a Postgres version to cargo pgrx commands, such as `cargo pgrx run pg15`, or add one to `default`",
+            ),
+            [default] => report.ok(CHECK, format!("{path} defaults to `{default}`")),
+            _ => report.problem(
+                Severity::Error,
+                CHECK,
+                format!("{path} enables several Postgres versions by default: {}", defaults.join(", ")),
+                "`pgrx-pg-sys` only builds for one at a time, so keep exactly one `pgXX` in `default`",
+            ),
+        }
+
+        let missing = SUPPORTED_VERSIONS()
+            .iter()
+            .map(|pgver| format!("pg{}", pgver.major))
+            .filter(|feature| !manifest.features.contains_key(feature))
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            report.problem(
+                Severity::Warning,
+                CHECK,
+                format!("{path} doesn't declare the features {}", missing.join(", ")),
+                "add them to `[features]`, for example `pg15 = [\"pgrx/pg15\", \"pgrx-tests/pg15\"]`, to build for those versions",
+            );
+        }
+        if !manifest.features.contains_key("pg_test") {
+            report.problem(
+                Severity::Warning,
+                CHECK,
+                format!("{path} doesn't declare a `pg_test` feature"),
+                "add `pg_test = []` to `[features]`, which `cargo pgrx test` needs",
+            );
+        }
+    }
+}
+
+fn is_pg_feature(feature: &str) -> bool {
+    SUPPORTED_VERSIONS().iter().any(|pgver| feature == format!("pg{}", pgver.major))
+}
+
+fn stdout(command: &mut Command) -> Option<String> {
+    command
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|stdout| stdout.trim().to_string())
+}
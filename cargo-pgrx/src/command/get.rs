@@ -7,19 +7,41 @@
 //LICENSE All rights reserved.
 //LICENSE
 //LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+use crate::command::install::get_version;
+use crate::manifest::{get_package_manifest, pg_config_and_version};
+use crate::profile::CargoProfile;
 use crate::CommandExecute;
 use eyre::{eyre, WrapErr};
+use pgrx_pg_config::{cargo::PgrxManifestExt, get_target_dir, Pgrx};
+use serde_json::{Map, Value};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-/// Get a property from the extension control file
+/// Get a property from the extension control file, or a value `cargo pgrx` resolves for it
+///
+/// Besides the control file's properties, `<name>` can be any of the keys printed by `--json`.
 #[derive(clap::Args, Debug)]
 #[clap(author)]
 pub(crate) struct Get {
-    /// One of the properties from `$EXTENSION.control`
-    name: String,
+    /// One of the properties from `$EXTENSION.control`, or a resolved value such as `pg_config`,
+    /// `port`, `data_dir` or `lib_path`
+    #[clap(required_unless_present = "json")]
+    name: Option<String>,
+    /// Print the control file's properties and every resolved value as a JSON object
+    #[clap(long, conflicts_with = "name")]
+    json: bool,
+    /// The Postgres version to resolve values for (`pg11`, `pg12`, ...), rather than the crate's
+    /// default feature
+    #[clap(long, env = "PG_VERSION")]
+    pg_version: Option<String>,
+    /// Resolve the library path of the release build
+    #[clap(long, short)]
+    release: bool,
+    /// Resolve the library path of the build with this Cargo profile
+    #[clap(long)]
+    profile: Option<String>,
     #[clap(from_global, action = ArgAction::Count)]
     verbose: u8,
     /// Package to determine default `pg_version` with (see `cargo help pkgid`)
@@ -40,13 +62,146 @@ impl CommandExecute for Get {
             crate::manifest::manifest_path(&metadata, self.package.as_ref())
                 .wrap_err("Couldn't get manifest path")?;
 
-        if let Some(value) = get_property(&package_manifest_path, &self.name)? {
+        let Some(name) = &self.name else {
+            let mut values = get_properties(&package_manifest_path)?;
+            values.extend(self.resolved_values(&package_manifest_path)?);
+            println!("{}", serde_json::to_string_pretty(&Value::Object(values))?);
+            return Ok(());
+        };
+
+        if let Some(value) = get_property(&package_manifest_path, name)? {
             println!("{}", value);
+        } else if let Some(value) = self.resolved_values(&package_manifest_path)?.remove(name) {
+            match value {
+                Value::Null => {}
+                Value::String(value) => println!("{}", value),
+                value => println!("{}", value),
+            }
         }
         Ok(())
     }
 }
 
+impl Get {
+    /// Everything `cargo pgrx` works out about the extension and the Postgres it targets, which
+    /// isn't spelled out in the control file.
+    ///
+    /// Values that depend on a Postgres version are `null` if one can't be determined.
+    fn resolved_values(&self, package_manifest_path: &Path) -> eyre::Result<Map<String, Value>> {
+        let (manifest, _) = get_package_manifest(
+            &clap_cargo::Features::default(),
+            self.package.as_ref(),
+            self.manifest_path.as_ref(),
+        )?;
+        let (control_file, extname) = find_control_file(package_manifest_path)?;
+        let profile = CargoProfile::from_flags(
+            self.profile.as_deref(),
+            self.release.then_some(CargoProfile::Release).unwrap_or(CargoProfile::Dev),
+        )?;
+        let mut lib_path = get_target_dir()?;
+        lib_path.push(profile.target_subdir());
+        lib_path.push(manifest.lib_filename()?);
+
+        let path = |path: &Path| Value::String(path.display().to_string());
+        let mut values = Map::new();
+        values.insert("extname".into(), Value::String(extname));
+        values.insert(
+            "extversion".into(),
+            get_version(package_manifest_path).map(Value::String).unwrap_or(Value::Null),
+        );
+        values.insert("control_file".into(), path(&control_file));
+        values.insert("manifest_path".into(), path(package_manifest_path));
+        values.insert("profile".into(), Value::String(profile.name().into()));
+        values.insert("lib_path".into(), path(&lib_path));
+        values.insert(
+            "pgrx_home".into(),
+            Pgrx::home().map(|home| path(&home)).unwrap_or(Value::Null),
+        );
+
+        // `cargo pgrx init` may not have been run yet, which leaves no Postgres to resolve against
+        let pg_config = Pgrx::from_config()
+            .and_then(|pgrx| {
+                pg_config_and_version(&pgrx, &manifest, self.pg_version.clone(), None, false)
+            })
+            .map(|(pg_config, pg_version)| (pg_config, pg_version.label().clone()));
+        let (pg_config, pg_version) = match pg_config {
+            Ok(resolved) => resolved,
+            Err(e) if self.pg_version.is_none() => {
+                tracing::debug!("no Postgres version to resolve values for: {e}");
+                for key in PG_CONFIG_KEYS {
+                    values.insert(key.to_string(), Value::Null);
+                }
+                return Ok(values);
+            }
+            Err(e) => return Err(e),
+        };
+
+        values.insert("pg_version".into(), Value::String(pg_version));
+        values
+            .insert("pg_config".into(), pg_config.path().map(|p| path(&p)).unwrap_or(Value::Null));
+        values.insert("pg_major_version".into(), pg_config.major_version()?.into());
+        values.insert("pg_full_version".into(), Value::String(pg_config.version()?));
+        values.insert("host".into(), Value::String(pg_config.host().into()));
+        values.insert("port".into(), pg_config.port()?.into());
+        values.insert("test_port".into(), pg_config.test_port()?.into());
+        values.insert("bin_dir".into(), path(&pg_config.bin_dir()?));
+        values.insert("pkglibdir".into(), path(&pg_config.pkglibdir()?));
+        values.insert("sharedir".into(), path(&pg_config.sharedir()?));
+        values.insert("extension_dir".into(), path(&pg_config.extension_dir()?));
+        values.insert("includedir_server".into(), path(&pg_config.includedir_server()?));
+        values.insert("data_dir".into(), path(&pg_config.data_dir()?));
+        values.insert("log_file".into(), path(&pg_config.log_file()?));
+        Ok(values)
+    }
+}
+
+/// The resolved values which need a Postgres version
+const PG_CONFIG_KEYS: &[&str] = &[
+    "pg_version",
+    "pg_config",
+    "pg_major_version",
+    "pg_full_version",
+    "host",
+    "port",
+    "test_port",
+    "bin_dir",
+    "pkglibdir",
+    "sharedir",
+    "extension_dir",
+    "includedir_server",
+    "data_dir",
+    "log_file",
+];
+
+/// All of the properties from the extension's control file
+fn get_properties(manifest_path: impl AsRef<Path>) -> eyre::Result<Map<String, Value>> {
+    let (control_file, _) = find_control_file(manifest_path)?;
+    let control_file = File::open(&control_file)
+        .wrap_err_with(|| eyre!("could not find control file `{}`", control_file.display()))?;
+    let mut properties = Map::new();
+    for line in BufReader::new(control_file).lines() {
+        let line = line?;
+        if let Some((k, v)) = parse_property(&line) {
+            properties.insert(k.to_string(), Value::String(v));
+        }
+    }
+    Ok(properties)
+}
+
+/// A `name = 'value'` line of a control file
+fn parse_property(line: &str) -> Option<(&str, String)> {
+    let parts: Vec<&str> = line.split('=').collect();
+
+    if parts.len() != 2 {
+        return None;
+    }
+
+    let (k, v) = (parts.get(0).unwrap().trim(), parts.get(1).unwrap().trim());
+    let v = v.trim_start_matches('\'');
+    let v = v.trim_end_matches('\'');
+    Some((k, v.trim().to_string()))
+}
+
 #[tracing::instrument(level = "error", skip_all, fields(
     %name,
     manifest_path = %manifest_path.as_ref().display(),
@@ -66,18 +221,10 @@ pub fn get_property(manifest_path: impl AsRef<Path>, name: &str) -> eyre::Result
 
     for line in reader.lines() {
         let line = line.unwrap();
-        let parts: Vec<&str> = line.split('=').collect();
-
-        if parts.len() != 2 {
-            continue;
-        }
-
-        let (k, v) = (parts.get(0).unwrap().trim(), parts.get(1).unwrap().trim());
-
-        if k == name {
-            let v = v.trim_start_matches('\'');
-            let v = v.trim_end_matches('\'');
-            return Ok(Some(v.trim().to_string()));
+        if let Some((k, v)) = parse_property(&line) {
+            if k == name {
+                return Ok(Some(v));
+            }
         }
     }
 
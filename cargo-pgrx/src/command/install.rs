@@ -126,7 +126,7 @@ pub(crate) fn install_extension(
     let versioned_so = get_property(&package_manifest_path, "module_pathname")?.is_none();
 
     let build_command_output =
-        build_extension(user_manifest_path.as_ref(), user_package, &profile, &features)?;
+        build_extension(user_manifest_path.as_ref(), user_package, &manifest, &profile, &features)?;
     let build_command_bytes = build_command_output.stdout;
     let build_command_reader = BufReader::new(build_command_bytes.as_slice());
     let build_command_stream = cargo_metadata::Message::parse_stream(build_command_reader);
@@ -235,6 +235,7 @@ fn copy_file(
 pub(crate) fn build_extension(
     user_manifest_path: Option<impl AsRef<Path>>,
     user_package: Option<&String>,
+    package_manifest: &Manifest,
     profile: &CargoProfile,
     features: &clap_cargo::Features,
 ) -> eyre::Result<std::process::Output> {
@@ -269,6 +270,8 @@ pub(crate) fn build_extension(
     }
 
     command.arg("--message-format=json-render-diagnostics");
+    crate::env::set_build_info(&mut command, features);
+    crate::env::set_bindgen_config(&mut command, package_manifest)?;
 
     for arg in flags.split_ascii_whitespace() {
         command.arg(arg);
@@ -329,6 +332,7 @@ fn copy_sql_files(
         Some(&dest),
         Option::<String>::None,
         None,
+        None,
         skip_build,
     )?;
 
@@ -357,6 +361,15 @@ fn copy_sql_files(
     Ok(())
 }
 
+/// The extension of the shared libraries cargo builds for the platform
+pub(crate) fn dylib_extension() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "dylib"
+    } else {
+        "so"
+    }
+}
+
 #[tracing::instrument(level = "error", skip_all)]
 pub(crate) fn find_library_file(
     manifest: &cargo_toml::Manifest,
@@ -372,8 +385,7 @@ pub(crate) fn find_library_file(
                     continue;
                 }
                 for filename in &artifact.filenames {
-                    let so_extension = if cfg!(target_os = "macos") { "dylib" } else { "so" };
-                    if filename.extension() == Some(so_extension) {
+                    if filename.extension() == Some(dylib_extension()) {
                         library_file = Some(filename.to_string());
                         break;
                     }
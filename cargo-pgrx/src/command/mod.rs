@@ -12,6 +12,8 @@ use ureq::{Agent, AgentBuilder, Proxy};
 
 pub(crate) mod connect;
 pub(crate) mod cross;
+pub(crate) mod develop;
+pub(crate) mod doctor;
 pub(crate) mod get;
 pub(crate) mod info;
 pub(crate) mod init;
@@ -42,6 +42,8 @@ enum CargoPgrxSubCommands {
     Test(super::test::Test),
     Get(super::get::Get),
     Cross(super::cross::Cross),
+    Develop(super::develop::Develop),
+    Doctor(super::doctor::Doctor),
 }
 
 impl CommandExecute for CargoPgrxSubCommands {
@@ -63,6 +65,8 @@ impl CommandExecute for CargoPgrxSubCommands {
             Test(c) => c.execute(),
             Get(c) => c.execute(),
             Cross(c) => c.execute(),
+            Develop(c) => c.execute(),
+            Doctor(c) => c.execute(),
         }
     }
 }
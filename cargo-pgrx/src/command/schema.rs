@@ -66,7 +66,7 @@ pub(crate) struct Schema {
     /// A path to output a produced SQL file (default is `stdout`)
     #[clap(long, short, value_parser)]
     out: Option<PathBuf>,
-    /// A path to output a produced GraphViz DOT file
+    /// A path to output the SQL entity graph to, as GraphViz DOT (or D2, if the path ends in `.d2`)
     #[clap(long, short, value_parser)]
     dot: Option<PathBuf>,
     #[clap(from_global, action = ArgAction::Count)]
@@ -74,6 +74,10 @@ pub(crate) struct Schema {
     /// Skip building a fresh extension shared object.
     #[clap(long)]
     skip_build: bool,
+    /// Instead of the schema, output `CREATE OR REPLACE FUNCTION` statements loading the
+    /// extension's functions from this library, for `cargo pgrx develop`
+    #[clap(long, hide = true)]
+    replace_functions: Option<String>,
 }
 
 impl CommandExecute for Schema {
@@ -119,6 +123,7 @@ impl CommandExecute for Schema {
             &self.features,
             self.out.as_ref(),
             self.dot,
+            self.replace_functions.as_deref(),
             log_level,
             self.skip_build,
         )
@@ -190,6 +195,7 @@ pub(crate) fn generate_schema(
     features: &clap_cargo::Features,
     path: Option<impl AsRef<std::path::Path>>,
     dot: Option<impl AsRef<std::path::Path>>,
+    replace_functions: Option<&str>,
     log_level: Option<String>,
     skip_build: bool,
 ) -> eyre::Result<()> {
@@ -209,6 +215,9 @@ pub(crate) fn generate_schema(
     let flags = std::env::var("PGRX_BUILD_FLAGS").unwrap_or_default();
 
     let mut target_dir_with_profile = get_target_dir()?;
+    if let Ok(target) = std::env::var("CARGO_BUILD_TARGET") {
+        target_dir_with_profile.push(target);
+    }
     target_dir_with_profile.push(profile.target_subdir());
 
     // First, build the SQL generator so we can get a look at the symbol table
@@ -252,6 +261,8 @@ pub(crate) fn generate_schema(
         if features.all_features {
             command.arg("--all-features");
         }
+        crate::env::set_build_info(&mut command, features);
+        crate::env::set_bindgen_config(&mut command, &manifest)?;
 
         for arg in flags.split_ascii_whitespace() {
             command.arg(arg);
@@ -427,9 +438,34 @@ pub(crate) fn generate_schema(
         entities.into_iter(),
         manifest.lib_name()?,
         versioned_so,
+        resolved_features(features, package_manifest_path.as_ref())?,
     )
     .wrap_err("SQL generation error")?;
 
+    // write the graph before the SQL, as it's most useful when the SQL can't be ordered
+    if let Some(dot_path) = dot {
+        let dot_path = dot_path.as_ref();
+        if dot_path.extension().map_or(false, |ext| ext == "d2") {
+            tracing::info!(d2 = %dot_path.display(), "Writing D2");
+            pgrx_sql.to_d2(dot_path)?;
+        } else {
+            tracing::info!(dot = %dot_path.display(), "Writing Graphviz DOT");
+            pgrx_sql.to_dot(dot_path)?;
+        }
+    }
+
+    if let Some(module_pathname) = replace_functions {
+        let statements = pgrx_sql.function_replacements(module_pathname)?;
+        let sql = statements.join("\n\n");
+        match path {
+            Some(out_path) => std::fs::write(out_path.as_ref(), sql).wrap_err_with(|| {
+                eyre!("Could not write SQL to {}", out_path.as_ref().display())
+            })?,
+            None => println!("{sql}"),
+        }
+        return Ok(());
+    }
+
     if let Some(out_path) = path {
         let out_path = out_path.as_ref();
 
@@ -451,15 +487,31 @@ pub(crate) fn generate_schema(
             .write(&mut std::io::stdout())
             .wrap_err_with(|| eyre!("Could not write SQL to stdout"))?;
     }
-
-    if let Some(dot_path) = dot {
-        let dot_path = dot_path.as_ref();
-        tracing::info!(dot = %dot_path.display(), "Writing Graphviz DOT");
-        pgrx_sql.to_dot(dot_path)?;
-    }
     Ok(())
 }
 
+/// The features cargo resolves for the package when built with `features`, which is what decides
+/// which `#[cfg(feature = "...")]` items end up in the shared object and so in the schema
+fn resolved_features(
+    features: &clap_cargo::Features,
+    package_manifest_path: &Path,
+) -> eyre::Result<Vec<String>> {
+    let metadata = crate::metadata::metadata(features, Some(package_manifest_path))?;
+    let package = metadata
+        .packages
+        .iter()
+        .find(|package| package.manifest_path.as_std_path() == package_manifest_path)
+        .ok_or_else(|| eyre!("could not find package for `{}`", package_manifest_path.display()))?;
+    let mut resolved = metadata
+        .resolve
+        .as_ref()
+        .and_then(|resolve| resolve.nodes.iter().find(|node| node.id == package.id))
+        .map(|node| node.features.clone())
+        .unwrap_or_default();
+    resolved.sort();
+    Ok(resolved)
+}
+
 #[tracing::instrument(level = "error", skip_all, fields(
     postmaster_path = %format_display_path(postmaster_path.as_ref())?,
     postmaster_stub_dir = %format_display_path(postmaster_stub_dir.as_ref())?,
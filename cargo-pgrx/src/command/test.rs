@@ -7,6 +7,7 @@
 //LICENSE All rights reserved.
 //LICENSE
 //LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+use cargo_toml::Manifest;
 use eyre::Context;
 use pgrx_pg_config::{get_target_dir, PgConfig, PgConfigSelector, Pgrx};
 use std::path::{Path, PathBuf};
@@ -40,6 +41,20 @@ pub(crate) struct Test {
     /// Don't regenerate the schema
     #[clap(long, short)]
     no_schema: bool,
+    /// Also check that each `sql/<extname>--<from>--<to>.sql` upgrade script produces the same
+    /// catalog state as a fresh install of `<to>`
+    #[clap(long)]
+    upgrade_paths: bool,
+    /// Skip upgrade paths whose starting or target version has no install script, instead of
+    /// failing
+    #[clap(long, requires = "upgrade_paths")]
+    skip_missing_versions: bool,
+    /// Build the extension with a sanitizer and run Postgres under its runtime (requires nightly Rust)
+    #[clap(long, value_enum)]
+    sanitizer: Option<Sanitizer>,
+    /// Also build the C shim with the sanitizer given by `--sanitizer`
+    #[clap(long, requires = "sanitizer")]
+    sanitize_cshim: bool,
     #[clap(flatten)]
     features: clap_cargo::Features,
     #[clap(from_global, action = clap::ArgAction::Count)]
@@ -71,8 +86,12 @@ impl CommandExecute for Test {
                 &pg_config,
                 me.manifest_path.as_ref(),
                 me.package.as_ref(),
+                &package_manifest,
                 &profile,
                 me.no_schema,
+                me.upgrade_paths,
+                me.skip_missing_versions,
+                me.sanitizer.map(|sanitizer| (sanitizer, me.sanitize_cshim)),
                 &features,
                 me.testname,
             )?;
@@ -106,8 +125,12 @@ pub fn test_extension(
     pg_config: &PgConfig,
     user_manifest_path: Option<impl AsRef<Path>>,
     user_package: Option<&String>,
+    package_manifest: &Manifest,
     profile: &CargoProfile,
     no_schema: bool,
+    upgrade_paths: bool,
+    skip_missing_versions: bool,
+    sanitizer: Option<(Sanitizer, bool)>,
     features: &clap_cargo::Features,
     testname: Option<impl AsRef<str>>,
 ) -> eyre::Result<()> {
@@ -133,11 +156,24 @@ pub fn test_extension(
         .env("PGRX_NO_DEFAULT_FEATURES", if no_default_features_arg { "true" } else { "false" })
         .env("PGRX_ALL_FEATURES", if features.all_features { "true" } else { "false" })
         .env("PGRX_BUILD_PROFILE", profile.name())
-        .env("PGRX_NO_SCHEMA", if no_schema { "true" } else { "false" });
+        .env("PGRX_NO_SCHEMA", if no_schema { "true" } else { "false" })
+        .env("PGRX_TEST_UPGRADE_PATHS", if upgrade_paths { "true" } else { "false" })
+        .env(
+            "PGRX_TEST_SKIP_MISSING_VERSIONS",
+            if skip_missing_versions { "true" } else { "false" },
+        );
 
     if let Ok(rust_log) = std::env::var("RUST_LOG") {
         command.env("RUST_LOG", rust_log);
     }
+    crate::env::set_bindgen_config(&mut command, package_manifest)?;
+
+    let sanitizer_log_dir = match sanitizer {
+        Some((sanitizer, sanitize_cshim)) => {
+            Some(sanitizer.configure(&mut command, &target_dir, sanitize_cshim)?)
+        }
+        None => None,
+    };
 
     if !features_arg.trim().is_empty() {
         command.arg("--features");
@@ -173,12 +209,137 @@ pub fn test_extension(
     tracing::debug!(command = ?command, "Running");
     let status = command.status().wrap_err("failed to run cargo test")?;
     tracing::trace!(status_code = %status, command = ?command, "Finished");
-    if !status.success() {
-        if !status.success() {
-            // We explicitly do not want to return a spantraced error here.
-            std::process::exit(1)
-        }
+    let sanitizer_reported = match sanitizer_log_dir {
+        Some(log_dir) => report_sanitizer_logs(&log_dir)?,
+        None => false,
+    };
+    if !status.success() || sanitizer_reported {
+        // We explicitly do not want to return a spantraced error here.
+        std::process::exit(1)
     }
 
     Ok(())
 }
+
+/// A sanitizer to build the extension with, via `-Zsanitizer`
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Sanitizer {
+    Address,
+}
+
+impl Sanitizer {
+    /// Configure `command` (a `cargo test`) to build with this sanitizer and to tell the test
+    /// framework how to run Postgres under it, returning the directory its reports are written to
+    fn configure(
+        self,
+        command: &mut std::process::Command,
+        target_dir: &Path,
+        sanitize_cshim: bool,
+    ) -> eyre::Result<PathBuf> {
+        match self {
+            Sanitizer::Address => {
+                // Build for an explicit target, so `RUSTFLAGS` doesn't apply to build scripts
+                // and proc macros, which can't be loaded into an uninstrumented `rustc`.
+                if std::env::var_os("CARGO_BUILD_TARGET").is_none() {
+                    command.env("CARGO_BUILD_TARGET", host_target()?);
+                }
+                let mut rustflags = std::env::var("RUSTFLAGS").unwrap_or_default();
+                rustflags.push_str(" -Zsanitizer=address");
+                command.env("RUSTFLAGS", rustflags.trim());
+                if sanitize_cshim {
+                    command.env("PGRX_CSHIM_COPT", "-fsanitize=address -fno-omit-frame-pointer");
+                }
+
+                // Postgres itself isn't instrumented, so the ASan runtime has to be preloaded
+                // into it before the extension is loaded.
+                let runtime = match std::env::var("PGRX_ASAN_RUNTIME") {
+                    Ok(runtime) => runtime,
+                    Err(_) => asan_runtime()?,
+                };
+
+                let log_dir = target_dir.join("pgrx-sanitizer");
+                if log_dir.exists() {
+                    std::fs::remove_dir_all(&log_dir)
+                        .wrap_err_with(|| format!("couldn't clear `{}`", log_dir.display()))?;
+                }
+                std::fs::create_dir_all(&log_dir)
+                    .wrap_err_with(|| format!("couldn't create `{}`", log_dir.display()))?;
+
+                // Postgres leaks by design, relying on memory contexts and process exit
+                let mut options = format!(
+                    "detect_leaks=0:halt_on_error=1:log_path={}",
+                    log_dir.join("asan").display()
+                );
+                if let Some(symbolizer) = find_on_path("llvm-symbolizer") {
+                    options.push_str(":symbolize=1");
+                    command.env("ASAN_SYMBOLIZER_PATH", symbolizer);
+                }
+
+                command
+                    .env("PGRX_SANITIZER", "address")
+                    .env("PGRX_SANITIZER_PRELOAD", runtime)
+                    .env("PGRX_SANITIZER_OPTIONS", options);
+                Ok(log_dir)
+            }
+        }
+    }
+}
+
+/// The target triple of the host, as `rustc` reports it
+fn host_target() -> eyre::Result<String> {
+    let output = crate::env::rustc().arg("-vV").output().wrap_err("failed to run `rustc -vV`")?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix("host: "))
+        .map(|host| host.trim().to_string())
+        .ok_or_else(|| eyre::eyre!("couldn't determine the host target from `rustc -vV`"))
+}
+
+/// Find the shared ASan runtime through the C compiler, preferring `clang`'s since `rustc`'s
+/// instrumentation comes from LLVM
+fn asan_runtime() -> eyre::Result<String> {
+    let arch = std::env::consts::ARCH;
+    let candidates = [
+        ("clang", format!("libclang_rt.asan-{arch}.so")),
+        ("clang", format!("linux/libclang_rt.asan-{arch}.so")),
+        ("gcc", "libasan.so".to_string()),
+    ];
+    for (compiler, library) in candidates {
+        let Ok(output) = std::process::Command::new(compiler)
+            .arg(format!("-print-file-name={library}"))
+            .output()
+        else {
+            continue;
+        };
+        let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        // compilers echo the name back when they can't find the file
+        if output.status.success() && Path::new(&path).is_absolute() && Path::new(&path).exists() {
+            return Ok(path);
+        }
+    }
+    Err(eyre::eyre!(
+        "couldn't find a shared ASan runtime through `clang` or `gcc`, set `PGRX_ASAN_RUNTIME` to its path"
+    ))
+}
+
+fn find_on_path(name: &str) -> Option<PathBuf> {
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Print any reports the sanitizer wrote, returning whether there were any
+fn report_sanitizer_logs(log_dir: &Path) -> eyre::Result<bool> {
+    let mut reports = std::fs::read_dir(log_dir)
+        .wrap_err_with(|| format!("couldn't read `{}`", log_dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect::<Vec<_>>();
+    reports.sort();
+    for report in &reports {
+        let contents = std::fs::read_to_string(report)
+            .wrap_err_with(|| format!("couldn't read `{}`", report.display()))?;
+        eprintln!("{}", report.display());
+        eprintln!("{contents}");
+    }
+    Ok(!reports.is_empty())
+}
@@ -7,6 +7,8 @@
 //LICENSE All rights reserved.
 //LICENSE
 //LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+use eyre::WrapErr;
+
 pub(crate) fn cargo() -> std::process::Command {
     let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
     std::process::Command::new(cargo)
@@ -44,3 +46,83 @@ pub(crate) fn initialize() {
         (_, Err(_)) => {}
     }
 }
+
+/// Set the environment variables `pgrx::extension_introspection!()` records the build with.
+///
+/// These are only informational, so anything that can't be determined is left unset.
+pub(crate) fn set_build_info(command: &mut std::process::Command, features: &clap_cargo::Features) {
+    let stdout = |mut command: std::process::Command| {
+        command
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+            .map(|stdout| stdout.trim().to_string())
+    };
+
+    let mut git = std::process::Command::new("git");
+    git.args(["rev-parse", "HEAD"]);
+    if let Some(git_hash) = stdout(git) {
+        command.env("PGRX_BUILD_GIT_HASH", git_hash);
+    }
+
+    let mut rustc = rustc();
+    rustc.arg("--version");
+    if let Some(rustc_version) = stdout(rustc) {
+        command.env("PGRX_BUILD_RUSTC_VERSION", rustc_version);
+    }
+
+    let mut requested = Vec::new();
+    if features.all_features {
+        requested.push("all".to_string());
+    } else if !features.no_default_features {
+        requested.push("default".to_string());
+    }
+    requested.extend(
+        features
+            .features
+            .iter()
+            .flat_map(|f| f.split([' ', ',']))
+            .filter(|f| !f.is_empty())
+            .map(String::from),
+    );
+    command.env("PGRX_BUILD_FEATURES", requested.join(","));
+}
+
+/// Point `pgrx-pg-sys`' build script at the package's `[package.metadata.pgrx.bindgen]` table
+/// (or the older `[package.metadata.pgx.bindgen]`), as it can't read the manifest of the crate
+/// depending on it.  Does nothing if the package has no such table, or `PGRX_BINDGEN_CONFIG` is
+/// already set.
+///
+/// The table is copied to a file under the target directory, which is only rewritten when the
+/// table changes, as the build script regenerates the bindings whenever the file does.
+pub(crate) fn set_bindgen_config(
+    command: &mut std::process::Command,
+    package_manifest: &cargo_toml::Manifest,
+) -> eyre::Result<()> {
+    if std::env::var_os("PGRX_BINDGEN_CONFIG").is_some() {
+        return Ok(());
+    }
+    let Some(package) = &package_manifest.package else {
+        return Ok(());
+    };
+    let table = package.metadata.as_ref().and_then(|metadata| {
+        ["pgrx", "pgx"].iter().find_map(|key| metadata.get(key)?.get("bindgen"))
+    });
+    let Some(table) = table else {
+        return Ok(());
+    };
+
+    let contents =
+        toml::to_string(table).wrap_err("couldn't serialize `[package.metadata.pgrx.bindgen]`")?;
+    let mut path = pgrx_pg_config::get_target_dir()?;
+    path.push("pgrx-bindgen");
+    path.push(format!("{}.toml", package.name));
+    if std::fs::read_to_string(&path).ok().as_deref() != Some(contents.as_str()) {
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        std::fs::write(&path, contents)
+            .wrap_err_with(|| format!("couldn't write `{}`", path.display()))?;
+    }
+    command.env("PGRX_BINDGEN_CONFIG", path);
+    Ok(())
+}
@@ -0,0 +1,84 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Submodule of `build`
+//!
+//! Reads the TOML file named by `PGRX_BINDGEN_CONFIG`, which downstream crates use to adjust the
+//! bindings, as described in `pgrx-pg-sys`'s README.  `cargo pgrx` sets it to a copy of the
+//! extension's `[package.metadata.pgrx.bindgen]` (or `[package.metadata.pgx.bindgen]`) table.
+use eyre::WrapErr;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct BindgenConfig {
+    pub(crate) headers: Vec<String>,
+    pub(crate) clang_args: Vec<String>,
+    pub(crate) blocklist_functions: Vec<String>,
+    pub(crate) blocklist_types: Vec<String>,
+    pub(crate) blocklist_items: Vec<String>,
+    pub(crate) allowlist: Vec<String>,
+    #[serde(skip)]
+    allowlist_regexes: Vec<regex::Regex>,
+}
+
+impl BindgenConfig {
+    /// Read the file named by `PGRX_BINDGEN_CONFIG`, if it's set
+    pub(crate) fn load() -> eyre::Result<Self> {
+        let Some(path) = super::env_tracked("PGRX_BINDGEN_CONFIG") else {
+            return Ok(Self::default());
+        };
+        let path = PathBuf::from(path);
+        // build scripts run in `pgrx-pg-sys`'s directory, not the one the user had in mind
+        if !path.is_absolute() {
+            return Err(eyre::eyre!(
+                "`PGRX_BINDGEN_CONFIG` must be an absolute path, but is `{}`",
+                path.display()
+            ));
+        }
+        println!("cargo:rerun-if-changed={}", path.display());
+        Self::from_file(&path)
+    }
+
+    /// Read a bindgen config file
+    pub fn from_file(path: &Path) -> eyre::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("failed to read `{}`", path.display()))?;
+        let mut config: BindgenConfig = toml::from_str(&contents)
+            .wrap_err_with(|| format!("invalid bindgen config `{}`", path.display()))?;
+        config.allowlist_regexes = config
+            .allowlist
+            .iter()
+            .map(|pattern| anchored(pattern))
+            .collect::<Result<_, _>>()
+            .wrap_err_with(|| format!("invalid regex in the allowlist of `{}`", path.display()))?;
+        Ok(config)
+    }
+
+    /// Did the user ask for the symbol `name`, even if pgrx would leave it out?
+    pub(crate) fn allows(&self, name: &str) -> bool {
+        self.allowlist_regexes.iter().any(|allowed| allowed.is_match(name))
+    }
+
+    /// Should pgrx's own blocklist `pattern` be lifted from bindgen, because it may block symbols
+    /// the user asked for?  That's the case if an allowlist entry names something `pattern`
+    /// matches, matches `pattern` itself, or is simply the same pattern.  What a lifted pattern
+    /// blocks but the allowlist doesn't name is removed from the bindings afterwards instead.
+    pub(crate) fn lifts(&self, pattern: &str) -> bool {
+        let Ok(blocklisted) = anchored(pattern) else { return false };
+        self.allowlist.iter().zip(&self.allowlist_regexes).any(|(allowed, allowed_regex)| {
+            allowed == pattern || blocklisted.is_match(allowed) || allowed_regex.is_match(pattern)
+        })
+    }
+}
+
+/// Bindgen matches its patterns against entire names
+pub(crate) fn anchored(pattern: &str) -> Result<regex::Regex, regex::Error> {
+    regex::Regex::new(&format!("^(?:{pattern})$"))
+}
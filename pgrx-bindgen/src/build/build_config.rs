@@ -0,0 +1,156 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Submodule of `build`
+//!
+//! Everything binding generation and cshim compilation take from outside of `pgrx-pg-sys`'s
+//! sources.  The build script reads it from the environment cargo runs it in, and the standalone
+//! `pgrx-pg-sys-gen` tool from its command line.
+use super::{env_tracked, target_env_tracked};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone)]
+pub struct BuildConfig {
+    /// The target triple being built for, `TARGET`
+    pub target: String,
+    /// The target's operating system, `CARGO_CFG_TARGET_OS`
+    pub target_os: String,
+    /// Postgres' server include directory for a major version, instead of asking `pg_config`, or
+    /// empty to pass none.  `PGRX_INCLUDEDIR_SERVER[_PG$VERSION]`
+    pub includedir_server: BTreeMap<u16, String>,
+    /// Whether bindgen should detect the system include paths, unless
+    /// `PGRX_BINDGEN_NO_DETECT_INCLUDES` is set
+    pub detect_includes: bool,
+    /// A directory holding a pregenerated `pg$VERSION_raw_bindings.rs` for a major version, to use
+    /// instead of running bindgen.  `PGRX_TARGET_INFO_PATH_PG$VERSION`
+    pub raw_bindings_dir: BTreeMap<u16, PathBuf>,
+    /// Where to also write bindgen's output before pgrx rewrites it,
+    /// `PGRX_PG_SYS_EXTRA_OUTPUT_PATH`
+    pub extra_output_path: Option<PathBuf>,
+    /// Whether to also write the bindings into `pgrx-pg-sys/src`, as for a release,
+    /// `PGRX_PG_SYS_GENERATE_BINDINGS_FOR_RELEASE`
+    pub for_release: bool,
+    /// The libclang bindgen loads, `LIBCLANG_PATH`
+    pub libclang_path: Option<PathBuf>,
+    /// Extra arguments to clang, `BINDGEN_EXTRA_CLANG_ARGS[_$TARGET]`
+    pub extra_clang_args: Vec<String>,
+    /// The `rustfmt` to format the bindings with, `RUSTFMT`
+    pub rustfmt: PathBuf,
+    /// The `make` to build the cshim with, `MAKE`
+    pub make: PathBuf,
+    /// Extra compiler flags for the cshim, `PGRX_CSHIM_COPT`
+    pub cshim_copt: Option<String>,
+    /// Whether to print cargo's directives, as only a build script should
+    pub cargo: bool,
+}
+
+impl BuildConfig {
+    /// The defaults for building for `target`, outside of cargo
+    pub fn new(target: String) -> Self {
+        // the bindings only distinguish macOS
+        let target_os = if target.contains("-apple-") {
+            "macos".to_string()
+        } else {
+            target.split('-').nth(2).unwrap_or_default().to_string()
+        };
+        Self {
+            target,
+            target_os,
+            includedir_server: BTreeMap::new(),
+            detect_includes: true,
+            raw_bindings_dir: BTreeMap::new(),
+            extra_output_path: None,
+            for_release: false,
+            libclang_path: None,
+            extra_clang_args: Vec::new(),
+            rustfmt: "rustfmt".into(),
+            make: "make".into(),
+            cshim_copt: None,
+            cargo: false,
+        }
+    }
+
+    /// Read the environment cargo runs the build script in, for building each of `major_versions`
+    pub(crate) fn from_env(major_versions: &[u16]) -> Self {
+        // Cargo guarantees this is provided, so unwrap is fine.
+        let target = env_tracked("TARGET").unwrap();
+        // `emit_rerun_if_changed()` tracks these on bindgen's behalf, following bindgen's logic
+        // https://github.com/rust-lang/rust-bindgen/blob/e6dd2c636/bindgen/lib.rs#L2918
+        let extra_clang_args = [
+            format!("BINDGEN_EXTRA_CLANG_ARGS_{target}"),
+            format!("BINDGEN_EXTRA_CLANG_ARGS_{}", target.replace('-', "_")),
+            "BINDGEN_EXTRA_CLANG_ARGS".to_string(),
+        ]
+        .iter()
+        .find_map(|var| std::env::var(var).ok())
+        .map(|args| shlex::split(&args).unwrap_or_default())
+        .unwrap_or_default();
+
+        let includedir_server = major_versions
+            .iter()
+            .filter_map(|&major_version| {
+                let var = "PGRX_INCLUDEDIR_SERVER";
+                target_env_tracked(&format!("{var}_PG{major_version}"))
+                    .or_else(|| target_env_tracked(var))
+                    .map(|dir| (major_version, dir))
+            })
+            .collect();
+        let raw_bindings_dir = major_versions
+            .iter()
+            .filter_map(|&major_version| {
+                target_env_tracked(&format!("PGRX_TARGET_INFO_PATH_PG{major_version}"))
+                    .map(|dir| (major_version, dir.into()))
+            })
+            .collect();
+
+        Self {
+            target_os: env_tracked("CARGO_CFG_TARGET_OS").unwrap_or_default(),
+            includedir_server,
+            detect_includes: target_env_tracked("PGRX_BINDGEN_NO_DETECT_INCLUDES").is_none(),
+            raw_bindings_dir,
+            extra_output_path: env_tracked("PGRX_PG_SYS_EXTRA_OUTPUT_PATH").map(PathBuf::from),
+            for_release: env_tracked("PGRX_PG_SYS_GENERATE_BINDINGS_FOR_RELEASE").as_deref()
+                == Some("1"),
+            libclang_path: std::env::var_os("LIBCLANG_PATH").map(PathBuf::from),
+            extra_clang_args,
+            // We shouldn't hit this path in a case where we care about it, but... just
+            // in case we probably should respect RUSTFMT.
+            rustfmt: env_tracked("RUSTFMT").unwrap_or_else(|| "rustfmt".into()).into(),
+            make: env_tracked("MAKE").unwrap_or_else(|| "make".into()).into(),
+            cshim_copt: env_tracked("PGRX_CSHIM_COPT"),
+            cargo: true,
+            target,
+        }
+    }
+
+    /// Hand `libclang_path` to clang-sys and take the extra clang args away from bindgen, which
+    /// only look for them in the environment.  `run_bindgen()` passes the args itself.
+    ///
+    /// This changes the process' environment, so must be called before generating bindings on
+    /// any other thread.
+    pub fn set_bindgen_env(&self) {
+        match &self.libclang_path {
+            Some(path) => std::env::set_var("LIBCLANG_PATH", path),
+            None => std::env::remove_var("LIBCLANG_PATH"),
+        }
+        std::env::remove_var(format!("BINDGEN_EXTRA_CLANG_ARGS_{}", self.target));
+        std::env::remove_var(format!("BINDGEN_EXTRA_CLANG_ARGS_{}", self.target.replace('-', "_")));
+        std::env::remove_var("BINDGEN_EXTRA_CLANG_ARGS");
+    }
+
+    /// Report something which may explain a failure later on
+    pub(crate) fn warning(&self, message: &str) {
+        if self.cargo {
+            println!("cargo:warning={message}");
+        } else {
+            eprintln!("warning: {message}");
+        }
+    }
+}
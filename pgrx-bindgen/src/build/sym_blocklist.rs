@@ -7,7 +7,7 @@
 //LICENSE All rights reserved.
 //LICENSE
 //LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
-//! Submodule of `build.rs`
+//! Submodule of `build`
 
 /// Hardcoded list of that are known to be from system libraries and other
 /// non-postgres sources, which we exclude from wrappers to avoid symbol
@@ -0,0 +1,13 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Binding generation and cshim compilation for `pgrx-pg-sys`, shared by its build script and by
+//! the standalone `pgrx-pg-sys-gen` tool.  This is an implementation detail of pgrx and has no
+//! stable API.
+pub mod build;
@@ -0,0 +1,13 @@
+--LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+--LICENSE
+--LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+--LICENSE
+--LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+--LICENSE
+--LICENSE All rights reserved.
+--LICENSE
+--LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+ 
+
+CREATE TABLE @extschema@.extension_sql_template (version TEXT, pg_test BOOLEAN);
+INSERT INTO @extschema@.extension_sql_template VALUES ('@CARGO_VERSION@', @feature:pg_test@);
@@ -79,6 +79,10 @@ extension_sql_file!(
 );
 extension_sql_file!("../sql/finalizer.sql", finalize);
 
+// `template` substitutes `@CARGO_VERSION@`, `@feature:name@` (for the listed `features`), and,
+// in relocatable extensions, `@extschema@` into the SQL.
+extension_sql_file!("../sql/templated.sql", template, features = ["pg_test"]);
+
 #[cfg(any(test, feature = "pg_test"))]
 #[pg_schema]
 mod tests {
@@ -112,6 +116,15 @@ mod tests {
         );
         Ok(())
     }
+
+    #[pg_test]
+    fn test_template() -> Result<(), spi::Error> {
+        let (version, pg_test) =
+            Spi::get_two::<String, bool>("SELECT version, pg_test FROM extension_sql_template")?;
+        assert_eq!(version.as_deref(), Some(env!("CARGO_PKG_VERSION")));
+        assert_eq!(pg_test, Some(true));
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -62,7 +62,7 @@ fn spi_return_query() -> Result<
     .map(|results| TableIterator::new(results))
 }
 
-#[pg_extern(immutable, parallel_safe)]
+#[pg_extern(volatile, parallel_restricted)]
 fn spi_query_random_id() -> Result<Option<i64>, pgrx::spi::Error> {
     Spi::get_one("SELECT id FROM spi.spi_example ORDER BY random() LIMIT 1")
 }
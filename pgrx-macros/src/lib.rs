@@ -23,10 +23,14 @@ use pgrx_sql_entity_graph::{
     PgAggregate, PgExtern, PostgresEnum, PostgresType, Schema,
 };
 
+use crate::pg_version::{Comparison, PgVersionBranch};
 use crate::rewriter::PgGuardRewriter;
+use crate::test_presets::TestPresets;
 
 mod operators;
+mod pg_version;
 mod rewriter;
+mod test_presets;
 
 /// Declare a function as `#[pg_guard]` to indicate that it is called from a Postgres `extern "C"`
 /// function so that Rust `panic!()`s (and Postgres `elog(ERROR)`s) will be properly handled by `pgrx`
@@ -54,10 +58,27 @@ pub fn pg_guard(_attr: TokenStream, item: TokenStream) -> TokenStream {
 
 /// `#[pg_test]` functions are test functions (akin to `#[test]`), but they run in-process inside
 /// Postgres during `cargo pgrx test`.
+///
+/// Besides the `#[pg_extern]` arguments, such as `error = "..."`, the test's transaction can be
+/// set up before the test function is called:
+///
+/// * `isolation = "serializable"` -- the transaction isolation level
+/// * `search_path = "my_schema, public"`
+/// * `role = "some_role"` -- run as this role, which is switched to after the other settings
+/// * `set(work_mem = "64MB", "my_extension.setting" = "on")` -- any other settings.  Names
+///   containing a `.` need to be quoted
+/// * `fake_clock = "2023-01-01 00:00:00+00"` -- pin the current time, as seen by pgrx and by
+///   SQL's `now()` and friends.  See `pgrx::fake_clock`
+///
+/// All of them only last until the end of the test's transaction.
 #[proc_macro_attribute]
 pub fn pg_test(attr: TokenStream, item: TokenStream) -> TokenStream {
     let mut stream = proc_macro2::TokenStream::new();
-    let args = parse_extern_attributes(proc_macro2::TokenStream::from(attr.clone()));
+    let (presets, attr) = match TestPresets::extract(attr.into()) {
+        Ok(split) => split,
+        Err(e) => return e.into_compile_error().into(),
+    };
+    let args = parse_extern_attributes(attr.clone());
 
     let mut expected_error = None;
     args.into_iter().for_each(|v| {
@@ -92,7 +113,7 @@ pub fn pg_test(attr: TokenStream, item: TokenStream) -> TokenStream {
             func.attrs = non_test_attributes;
 
             stream.extend(proc_macro2::TokenStream::from(pg_extern(
-                attr,
+                attr.into(),
                 Item::Fn(func.clone()).to_token_stream().into(),
             )));
 
@@ -123,7 +144,7 @@ pub fn pg_test(attr: TokenStream, item: TokenStream) -> TokenStream {
                     #att_stream
 
                     crate::pg_test::setup(options);
-                    let res = pgrx_tests::run_test(#sql_funcname, #expected_error, crate::pg_test::postgresql_conf_options());
+                    let res = pgrx_tests::run_test_with_presets(#sql_funcname, #expected_error, crate::pg_test::postgresql_conf_options(), &#presets);
                     match res {
                         Ok(()) => (),
                         Err(e) => panic!("{:?}", e)
@@ -225,17 +246,41 @@ mod dsl {
 }
 ```
 
+To name an inner schema after the schema it's nested in too, declare it with
+`#[pg_schema(nested)]`.  Its name is then made of both identifiers, outermost first, joined with a
+dot.  Since that name contains a dot it must be quoted in SQL.  Types and functions referenced
+from other schemas are always written fully qualified in the generated SQL.
+
+In this example, the created `example` function is in the `"dsl.dsl_filters"` schema, and is
+called with `SELECT "dsl.dsl_filters".example();`.
+
+```rust,ignore
+use pgrx::*;
+
+#[pg_schema]
+mod dsl {
+    use pgrx::*;
+    #[pg_schema(nested)]
+    mod dsl_filters {
+        use pgrx::*;
+        #[pg_extern]
+        fn example() { todo!() }
+    }
+}
+```
+
 File modules (like `mod name;`) aren't able to be supported due to [`rust/#54725`](https://github.com/rust-lang/rust/issues/54725).
 
 */
 #[proc_macro_attribute]
-pub fn pg_schema(_attr: TokenStream, input: TokenStream) -> TokenStream {
-    fn wrapped(input: TokenStream) -> Result<TokenStream, syn::Error> {
-        let pgrx_schema: Schema = syn::parse(input)?;
+pub fn pg_schema(attr: TokenStream, input: TokenStream) -> TokenStream {
+    fn wrapped(attr: TokenStream, input: TokenStream) -> Result<TokenStream, syn::Error> {
+        let mut pgrx_schema: Schema = syn::parse(input)?;
+        pgrx_schema.parse_args(attr.into())?;
         Ok(pgrx_schema.to_token_stream().into())
     }
 
-    match wrapped(input) {
+    match wrapped(attr, input) {
         Ok(tokens) => tokens,
         Err(e) => {
             let msg = e.to_string();
@@ -257,6 +302,11 @@ Accepts a String literal, a `name` attribute, and optionally others:
   Please note it **does not** create matching Rust types.
 * `bootstrap` (**Unique**): Communicates that this is SQL intended to go before all other generated SQL.
 * `finalize` (**Unique**): Communicates that this is SQL intended to go after all other generated SQL.
+* `template`: Substitute placeholders in the SQL when generating the schema:
+  * `@CARGO_VERSION@` becomes the crate version.
+  * `@feature:name@` becomes `true` or `false`, depending on if the `name` feature is enabled.
+  * `@extschema@` is left for Postgres to substitute, or has its qualifications removed if the extension is relocatable.
+* `features = ["name", "other"]`: The features which `@feature:name@` placeholders may refer to.
 
 You can declare some SQL without any positioning information, meaning it can end up anywhere in the generated SQL:
 
@@ -414,6 +464,18 @@ extension_sql_file!(
 );
 ```
 
+To substitute the crate version, enabled features, and extension schema into the SQL:
+
+```rust,ignore
+use pgrx_macros::extension_sql_file;
+
+extension_sql_file!(
+    "../static/demo.sql",
+    template,
+    features = ["demo"],
+);
+```
+
 For all other options, and examples of them, see [`macro@extension_sql`].
 */
 #[proc_macro]
@@ -434,6 +496,53 @@ pub fn extension_sql_file(input: TokenStream) -> TokenStream {
     }
 }
 
+/**
+Compile one piece of code when building for a Postgres major version at least as new as the one
+given, and an optional other piece otherwise.
+
+```rust,ignore
+use pgrx::prelude::*;
+
+pg_version_at_least!(14, {
+    // multiranges are new in Postgres 14, so on older versions this function is neither compiled
+    // nor part of the extension's schema
+    #[pg_extern]
+    fn has_multiranges() -> bool {
+        true
+    }
+});
+
+#[pg_extern]
+fn range_kinds() -> &'static str {
+    pg_version_at_least!(14, { "range and multirange" } else { "range" })
+}
+```
+
+When both branches contain only items, such as functions, types or `extension_sql!()`, each item is
+compiled, and so appears in the generated SQL, only for the chosen versions.  Otherwise the branches
+are expressions and the macro evaluates to the chosen one.
+
+The choice is made with the `pg11` ... `pg16` features of the crate using the macro, which every
+pgrx extension declares.  See also [`macro@pg_version_below`] and `pgrx::pg_version`.
+*/
+#[proc_macro]
+pub fn pg_version_at_least(input: TokenStream) -> TokenStream {
+    let branch = parse_macro_input!(input as PgVersionBranch);
+    branch.expand(Comparison::AtLeast).into()
+}
+
+/// The opposite of [`macro@pg_version_at_least`]: compile one piece of code when building for a
+/// Postgres major version older than the one given, and an optional other piece otherwise.
+///
+/// ```rust,ignore
+/// let value = pg_version_below!(13, { 11 } else { 13 });
+/// ```
+#[proc_macro]
+pub fn pg_version_below(input: TokenStream) -> TokenStream {
+    let branch = parse_macro_input!(input as PgVersionBranch);
+    branch.expand(Comparison::Below).into()
+}
+
 /// Associated macro for `#[pg_extern]` or `#[macro@pg_operator]`.  Used to set the `SEARCH_PATH` option
 /// on the `CREATE FUNCTION` statement.
 #[proc_macro_attribute]
@@ -460,6 +569,32 @@ Optionally accepts the following attributes:
 * `no_guard`: Do not use `#[pg_guard]` with the function.
 * `sql`: Same arguments as [`#[pgrx(sql = ..)]`](macro@pgrx).
 * `name`: Specifies target function name. Defaults to Rust function name.
+* `deny_lints`: Check the function's markings against it, as below, and fail to compile if they look wrong.
+* `memoize`: Cache results by argument, for the query (`memoize` or `memoize = "query"`) or for the
+  session (`memoize = "session"`). Only for functions returning a single value, see `pgrx::memoize`.
+* `revoke_from_public`: Follow `CREATE FUNCTION` with [`REVOKE ALL ... FROM PUBLIC`](https://www.postgresql.org/docs/current/sql-revoke.html),
+  so that only the roles it's granted to can call it.
+* `grant = "role_name"`: Follow `CREATE FUNCTION` with [`GRANT EXECUTE ... TO role_name`](https://www.postgresql.org/docs/current/sql-grant.html).
+  May be given more than once.  `"PUBLIC"`, `"CURRENT_USER"` and the like are passed through as keywords.
+* `security_label = "label"` or `security_label(provider = "selinux", label = "label")`: Follow
+  `CREATE FUNCTION` with [`SECURITY LABEL`](https://www.postgresql.org/docs/current/sql-security-label.html).
+  The label provider must be loaded when the extension is created.
+
+With `deny_lints`, `#[pg_extern]` checks its markings against what the function names, and reports
+an error when they look wrong.  These are hints rather than proof, so the checks are opt-in:
+
+* `immutable` on a function which uses SPI, since it reads the database.
+* `immutable` or `stable` on a function which uses the clock or randomness.
+* More than one of `immutable`, `stable`, and `volatile`.
+* `strict` on a function taking `Option<T>` or `Nullable<T>` arguments, which will never be NULL.
+* `memoize` on a function which isn't `immutable`, or `stable` when memoizing for the query.
+* More than one of `parallel_safe`, `parallel_restricted`, and `parallel_unsafe`.
+* `parallel_safe` or `parallel_restricted` on a function which writes to the database through SPI,
+  which only `parallel_unsafe` functions may.
+* `parallel_safe` on a function which uses cursors, prepared statements or backend-local state,
+  which should be `parallel_restricted`.
+* An `immutable` function with no parallel marking which looks `parallel_safe`, as functions are
+  `PARALLEL UNSAFE` unless marked otherwise.
 
 Functions can accept and return any type which `pgrx` supports. `pgrx` supports many PostgreSQL types by default.
 New types can be defined via [`macro@PostgresType`] or [`macro@PostgresEnum`].
@@ -704,13 +839,34 @@ enum Animal {
 }
 ```
 
+As with `Animal` above, fields may be other `#[derive(PostgresType)]` types, or any type which
+implements `Serialize` and `Deserialize`, such as pgrx's `Date` or `AnyNumeric`.  The generated SQL
+creates a type after the custom types it's made of.
+
 Optionally accepts the following attributes:
 
 * `inoutfuncs(some_in_fn, some_out_fn)`: Define custom in/out functions for the type.
 * `pgvarlena_inoutfuncs(some_in_fn, some_out_fn)`: Define custom in/out functions for the `PgVarlena` of this type.
+* `sendrecvfuncs`: Give the type binary send/receive functions, for the binary wire protocol and binary `COPY`.
+  The type must implement `pgrx::inoutfuncs::SendRecvFuncs`.
 * `sql`: Same arguments as [`#[pgrx(sql = ..)]`](macro@pgrx).
+* `storage = "..."`: The type's TOAST strategy, `plain`, `external`, `extended` (the default), or `main`.
+* `compression = "..."`: Compress values with `pglz` or `lz4` as they're converted into Datums.
+* `alignment = "..."`: The type's storage alignment, `char`, `int2`, `int4` (Postgres' default), or `double`.
 */
-#[proc_macro_derive(PostgresType, attributes(inoutfuncs, pgvarlena_inoutfuncs, requires, pgrx))]
+#[proc_macro_derive(
+    PostgresType,
+    attributes(
+        inoutfuncs,
+        pgvarlena_inoutfuncs,
+        sendrecvfuncs,
+        requires,
+        pgrx,
+        storage,
+        compression,
+        alignment
+    )
+)]
 pub fn postgres_type(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as syn::DeriveInput);
 
@@ -723,7 +879,12 @@ fn impl_postgres_type(ast: DeriveInput) -> syn::Result<proc_macro2::TokenStream>
     let has_lifetimes = generics.lifetimes().next();
     let funcname_in = Ident::new(&format!("{}_in", name).to_lowercase(), name.span());
     let funcname_out = Ident::new(&format!("{}_out", name).to_lowercase(), name.span());
+    let funcname_recv = Ident::new(&format!("{}_recv", name).to_lowercase(), name.span());
+    let funcname_send = Ident::new(&format!("{}_send", name).to_lowercase(), name.span());
     let mut args = parse_postgres_type_args(&ast.attrs);
+    let sendrecvfuncs = args.remove(&PostgresTypeAttribute::SendRecvFuncs);
+    // the functions generated for the type share its `#[cfg]`, so the schema leaves them out with it
+    let cfgs = ast.attrs.iter().filter(|attr| attr.path.is_ident("cfg")).collect::<Vec<_>>();
     let mut stream = proc_macro2::TokenStream::new();
 
     // validate that we're only operating on a struct
@@ -751,10 +912,34 @@ fn impl_postgres_type(ast: DeriveInput) -> syn::Result<proc_macro2::TokenStream>
         Some(lifetime) => quote! {#lifetime},
         None => quote! {'static},
     };
+    // the input only lives as long as the call's memory context
+    let input_lifetime = match has_lifetimes {
+        Some(lifetime) => quote! {#lifetime},
+        None => quote! {'_},
+    };
+
+    let mut compression = None;
+    for att in ast.attrs.iter().filter(|att| att.path.is_ident("compression")) {
+        compression = match string_attribute(att, "compression")?.as_str() {
+            "pglz" => Some(quote! { ::pgrx::varlena::ToastCompression::Pglz }),
+            "lz4" => Some(quote! { ::pgrx::varlena::ToastCompression::Lz4 }),
+            _ => {
+                return Err(syn::Error::new(
+                    att.span(),
+                    "expected `#[compression = \"pglz\"]` or `#[compression = \"lz4\"]`",
+                ))
+            }
+        };
+    }
+    let compression = compression.map(|compression| {
+        quote! {
+            const COMPRESSION: Option<::pgrx::varlena::ToastCompression> = Some(#compression);
+        }
+    });
 
     // all #[derive(PostgresType)] need to implement that trait
     stream.extend(quote! {
-        impl #generics ::pgrx::PostgresType for #name #generics { }
+        impl #generics ::pgrx::PostgresType for #name #generics { #compression }
     });
 
     // and if we don't have custom inout/funcs, we use the JsonInOutFuncs trait
@@ -769,9 +954,10 @@ fn impl_postgres_type(ast: DeriveInput) -> syn::Result<proc_macro2::TokenStream>
         stream.extend(quote! {
             impl #generics ::pgrx::inoutfuncs::JsonInOutFuncs #inout_generics for #name #generics {}
 
+            #(#cfgs)*
             #[doc(hidden)]
             #[::pgrx::pgrx_macros::pg_extern(immutable,parallel_safe)]
-            pub fn #funcname_in #generics(input: Option<&#lifetime ::core::ffi::CStr>) -> Option<#name #generics> {
+            pub fn #funcname_in #generics(input: Option<&#input_lifetime ::core::ffi::CStr>) -> Option<#name #generics> {
                 input.map_or_else(|| {
                     for m in <#name as ::pgrx::inoutfuncs::JsonInOutFuncs>::NULL_ERROR_MESSAGE {
                         ::pgrx::pg_sys::error!("{}", m);
@@ -780,6 +966,7 @@ fn impl_postgres_type(ast: DeriveInput) -> syn::Result<proc_macro2::TokenStream>
                 }, |i| Some(<#name as ::pgrx::inoutfuncs::JsonInOutFuncs>::input(i)))
             }
 
+            #(#cfgs)*
             #[doc(hidden)]
             #[::pgrx::pgrx_macros::pg_extern(immutable,parallel_safe)]
             pub fn #funcname_out #generics(input: #name #generics) -> &#lifetime ::core::ffi::CStr {
@@ -792,9 +979,10 @@ fn impl_postgres_type(ast: DeriveInput) -> syn::Result<proc_macro2::TokenStream>
     } else if args.contains(&PostgresTypeAttribute::InOutFuncs) {
         // otherwise if it's InOutFuncs our _in/_out functions use an owned type instance
         stream.extend(quote! {
+            #(#cfgs)*
             #[doc(hidden)]
             #[::pgrx::pgrx_macros::pg_extern(immutable,parallel_safe)]
-            pub fn #funcname_in #generics(input: Option<&#lifetime ::core::ffi::CStr>) -> Option<#name #generics> {
+            pub fn #funcname_in #generics(input: Option<&#input_lifetime ::core::ffi::CStr>) -> Option<#name #generics> {
                 input.map_or_else(|| {
                     for m in <#name as ::pgrx::inoutfuncs::InOutFuncs>::NULL_ERROR_MESSAGE {
                         ::pgrx::pg_sys::error!("{}", m);
@@ -803,6 +991,7 @@ fn impl_postgres_type(ast: DeriveInput) -> syn::Result<proc_macro2::TokenStream>
                 }, |i| Some(<#name as ::pgrx::inoutfuncs::InOutFuncs>::input(i)))
             }
 
+            #(#cfgs)*
             #[doc(hidden)]
             #[::pgrx::pgrx_macros::pg_extern(immutable,parallel_safe)]
             pub fn #funcname_out #generics(input: #name #generics) -> &#lifetime ::core::ffi::CStr {
@@ -814,9 +1003,10 @@ fn impl_postgres_type(ast: DeriveInput) -> syn::Result<proc_macro2::TokenStream>
     } else if args.contains(&PostgresTypeAttribute::PgVarlenaInOutFuncs) {
         // otherwise if it's PgVarlenaInOutFuncs our _in/_out functions use a PgVarlena
         stream.extend(quote! {
+            #(#cfgs)*
             #[doc(hidden)]
             #[::pgrx::pgrx_macros::pg_extern(immutable,parallel_safe)]
-            pub fn #funcname_in #generics(input: Option<&#lifetime ::core::ffi::CStr>) -> Option<::pgrx::datum::PgVarlena<#name #generics>> {
+            pub fn #funcname_in #generics(input: Option<&#input_lifetime ::core::ffi::CStr>) -> Option<::pgrx::datum::PgVarlena<#name #generics>> {
                 input.map_or_else(|| {
                     for m in <#name as ::pgrx::inoutfuncs::PgVarlenaInOutFuncs>::NULL_ERROR_MESSAGE {
                         ::pgrx::pg_sys::error!("{}", m);
@@ -825,6 +1015,7 @@ fn impl_postgres_type(ast: DeriveInput) -> syn::Result<proc_macro2::TokenStream>
                 }, |i| Some(<#name as ::pgrx::inoutfuncs::PgVarlenaInOutFuncs>::input(i)))
             }
 
+            #(#cfgs)*
             #[doc(hidden)]
             #[::pgrx::pgrx_macros::pg_extern(immutable,parallel_safe)]
             pub fn #funcname_out #generics(input: ::pgrx::datum::PgVarlena<#name #generics>) -> &#lifetime ::core::ffi::CStr {
@@ -835,6 +1026,45 @@ fn impl_postgres_type(ast: DeriveInput) -> syn::Result<proc_macro2::TokenStream>
         });
     }
 
+    if sendrecvfuncs {
+        // the binary functions work on the same owned or `PgVarlena` form as the _in/_out functions
+        let (ty, recv_value, send_value) =
+            if args.contains(&PostgresTypeAttribute::PgVarlenaInOutFuncs) {
+                (
+                    quote! { ::pgrx::datum::PgVarlena<#name #generics> },
+                    quote! {{
+                        let mut varlena = ::pgrx::datum::PgVarlena::<#name #generics>::new();
+                        *varlena = value;
+                        varlena
+                    }},
+                    quote! { &*input },
+                )
+            } else {
+                (quote! { #name #generics }, quote! { value }, quote! { &input })
+            };
+
+        stream.extend(quote! {
+            #(#cfgs)*
+            #[doc(hidden)]
+            #[::pgrx::pgrx_macros::pg_extern(immutable,parallel_safe,strict)]
+            pub fn #funcname_recv #generics(mut internal: ::pgrx::datum::Internal) -> #ty {
+                let mut buffer = unsafe {
+                    // SAFETY:  Postgres passes a `receive` function the message buffer as `internal`
+                    internal.as_receive_buffer()
+                }.expect("receive buffer is null");
+                let value = <#name as ::pgrx::inoutfuncs::SendRecvFuncs>::recv(&mut buffer);
+                #recv_value
+            }
+
+            #(#cfgs)*
+            #[doc(hidden)]
+            #[::pgrx::pgrx_macros::pg_extern(immutable,parallel_safe)]
+            pub fn #funcname_send #generics(input: #ty) -> Vec<u8> {
+                ::pgrx::inoutfuncs::SendRecvFuncs::send(#send_value)
+            }
+        });
+    }
+
     let sql_graph_entity_item = PostgresType::from_derive_input(ast)?;
     sql_graph_entity_item.to_tokens(&mut stream);
 
@@ -933,6 +1163,7 @@ fn impl_guc_enum(ast: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
 enum PostgresTypeAttribute {
     InOutFuncs,
     PgVarlenaInOutFuncs,
+    SendRecvFuncs,
     Default,
 }
 
@@ -951,6 +1182,10 @@ fn parse_postgres_type_args(attributes: &[Attribute]) -> HashSet<PostgresTypeAtt
                 categorized_attributes.insert(PostgresTypeAttribute::PgVarlenaInOutFuncs);
             }
 
+            "sendrecvfuncs" => {
+                categorized_attributes.insert(PostgresTypeAttribute::SendRecvFuncs);
+            }
+
             _ => {
                 // we can just ignore attributes we don't understand
             }
@@ -1032,22 +1267,691 @@ pub fn postgres_hash(input: TokenStream) -> TokenStream {
     impl_postgres_hash(ast).unwrap_or_else(syn::Error::into_compile_error).into()
 }
 
+/**
+Derives the `pgrx::aggregate::AggregateSerial` trait, so that the type can be kept in an aggregate's
+`pgrx::Internal` state and passed between parallel workers.  Name the type in
+[`#[pg_aggregate(serial = ...)]`](macro@pg_aggregate) to generate the aggregate's `SERIALFUNC` and
+`DESERIALFUNC`.
+
+The value is encoded as CBOR, so the type must also implement `serde::Serialize` and
+`serde::Deserialize`.
+
+```rust,ignore
+use pgrx::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Serialize, Deserialize, AggregateSerial)]
+struct SumState {
+    sum: i64,
+}
+```
+*/
+#[proc_macro_derive(AggregateSerial)]
+pub fn aggregate_serial(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as syn::DeriveInput);
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    TokenStream::from(quote! {
+        impl #impl_generics ::pgrx::aggregate::AggregateSerial for #name #ty_generics #where_clause {
+            fn serialize_state(&self) -> Vec<u8> {
+                ::pgrx::aggregate::serialize_state_cbor(self)
+            }
+
+            fn deserialize_state(bytes: &[u8]) -> Self {
+                ::pgrx::aggregate::deserialize_state_cbor(bytes)
+            }
+        }
+    })
+}
+
+/**
+Derives the `pgrx::table_writer::TableRow` trait, so that a struct can be written to a table with a
+`pgrx::table_writer::BufferedTableWriter`.
+
+Each named field is mapped onto a column of the same name, in declaration order.  Every field's
+type must implement `pgrx::IntoDatum`.
+
+```rust,ignore
+use pgrx::prelude::*;
+
+#[derive(Clone, TableRow)]
+struct Event {
+    id: i64,
+    #[column = "event_kind"]
+    kind: String,
+}
+```
+Optionally accepts the following attributes on fields:
+
+* `column`: The name of the column the field is written to, if it differs from the field's name.
+*/
+#[proc_macro_derive(TableRow, attributes(column))]
+pub fn table_row(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as syn::DeriveInput);
+    impl_table_row(ast).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+fn impl_table_row(ast: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = match &ast.data {
+        Data::Struct(syn::DataStruct { fields: syn::Fields::Named(fields), .. }) => &fields.named,
+        _ => {
+            return Err(syn::Error::new(
+                ast.span(),
+                "#[derive(TableRow)] can only be applied to structs with named fields",
+            ))
+        }
+    };
+
+    let mut columns = Vec::new();
+    let mut args = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named fields have an ident");
+        let ty = &field.ty;
+        let mut column = ident.to_string();
+        for att in field.attrs.iter().filter(|att| att.path.is_ident("column")) {
+            match att.parse_meta()? {
+                syn::Meta::NameValue(syn::MetaNameValue { lit: syn::Lit::Str(name), .. }) => {
+                    column = name.value()
+                }
+                other => {
+                    return Err(syn::Error::new(other.span(), "expected `#[column = \"name\"]`"))
+                }
+            }
+        }
+        columns.push(column);
+        args.push(quote! {
+            (
+                ::pgrx::PgOid::from_untagged(<#ty as ::pgrx::IntoDatum>::type_oid()),
+                ::pgrx::IntoDatum::into_datum(self.#ident),
+            )
+        });
+    }
+
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::pgrx::table_writer::TableRow for #name #ty_generics #where_clause {
+            const COLUMNS: &'static [&'static str] = &[#(#columns),*];
+
+            fn into_args(self) -> ::std::vec::Vec<(::pgrx::PgOid, ::core::option::Option<::pgrx::pg_sys::Datum>)> {
+                ::std::vec![#(#args),*]
+            }
+        }
+    })
+}
+
+/**
+Derives `pgrx::heap_tuple::PostgresComposite`, `FromDatum`, `IntoDatum` and `SqlTranslatable` for a
+struct, so it can be converted to and from an existing composite type.
+
+Each named field is mapped onto the composite type's attribute of the same name.  Fields whose type
+is an `Option<T>` may be NULL, and other fields raise an ERROR if they are.  Every field's type must
+implement `pgrx::FromDatum` and `pgrx::IntoDatum`.
+
+```rust,ignore
+use pgrx::prelude::*;
+
+extension_sql!(
+    "CREATE TYPE Dog AS (name text, scritches int);",
+    name = "create_dog",
+    creates = [Type(Dog)]
+);
+
+#[derive(PostgresComposite)]
+struct Dog {
+    name: String,
+    #[column = "scritches"]
+    pets: Option<i32>,
+}
+```
+Optionally accepts the following attributes:
+
+* `composite_type`: The SQL name of the composite type, if it differs from the struct's name, as in
+  `#[composite_type = "animals.dog"]`.
+* `column`: The name of the attribute a field is mapped onto, if it differs from the field's name.
+*/
+#[proc_macro_derive(PostgresComposite, attributes(composite_type, column))]
+pub fn postgres_composite(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as syn::DeriveInput);
+    impl_postgres_composite(ast).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+fn impl_postgres_composite(ast: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = match &ast.data {
+        Data::Struct(syn::DataStruct { fields: syn::Fields::Named(fields), .. }) => &fields.named,
+        _ => {
+            return Err(syn::Error::new(
+                ast.span(),
+                "#[derive(PostgresComposite)] can only be applied to structs with named fields",
+            ))
+        }
+    };
+
+    let name = &ast.ident;
+    let mut composite_type = name.to_string();
+    for att in ast.attrs.iter().filter(|att| att.path.is_ident("composite_type")) {
+        composite_type = string_attribute(att, "composite_type")?;
+    }
+
+    let mut getters = Vec::new();
+    let mut setters = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named fields have an ident");
+        let mut column = ident.to_string();
+        for att in field.attrs.iter().filter(|att| att.path.is_ident("column")) {
+            column = string_attribute(att, "column")?;
+        }
+
+        getters.push(match option_inner_type(&field.ty) {
+            Some(ty) => quote! {
+                #ident: tuple.get_by_name::<#ty>(#column)?
+            },
+            None => {
+                let ty = &field.ty;
+                quote! {
+                    #ident: tuple.get_by_name::<#ty>(#column)?.ok_or_else(|| {
+                        ::pgrx::heap_tuple::PgHeapTupleError::NullAttribute(#column.to_string())
+                    })?
+                }
+            }
+        });
+        setters.push(quote! {
+            tuple.set_by_name(#column, self.#ident)?;
+        });
+    }
+
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::pgrx::heap_tuple::PostgresComposite for #name #ty_generics #where_clause {
+            const COMPOSITE_TYPE: &'static str = #composite_type;
+
+            fn from_heap_tuple<AllocatedBy: ::pgrx::WhoAllocated>(
+                tuple: &::pgrx::heap_tuple::PgHeapTuple<'_, AllocatedBy>,
+            ) -> ::core::result::Result<Self, ::pgrx::heap_tuple::PgHeapTupleError> {
+                Ok(Self { #(#getters),* })
+            }
+
+            fn into_heap_tuple(
+                self,
+            ) -> ::core::result::Result<
+                ::pgrx::heap_tuple::PgHeapTuple<'static, ::pgrx::AllocatedByRust>,
+                ::pgrx::heap_tuple::PgHeapTupleError,
+            > {
+                let mut tuple = ::pgrx::heap_tuple::PgHeapTuple::new_composite_type(
+                    <Self as ::pgrx::heap_tuple::PostgresComposite>::COMPOSITE_TYPE,
+                )?;
+                #(#setters)*
+                Ok(tuple)
+            }
+        }
+
+        impl #impl_generics ::pgrx::FromDatum for #name #ty_generics #where_clause {
+            unsafe fn from_polymorphic_datum(
+                datum: ::pgrx::pg_sys::Datum,
+                is_null: bool,
+                _typoid: ::pgrx::pg_sys::Oid,
+            ) -> ::core::option::Option<Self> {
+                if is_null {
+                    None
+                } else {
+                    let tuple = ::pgrx::heap_tuple::PgHeapTuple::from_composite_datum(datum);
+                    match <Self as ::pgrx::heap_tuple::PostgresComposite>::from_heap_tuple(&tuple) {
+                        Ok(value) => Some(value),
+                        Err(e) => panic!("{}", e),
+                    }
+                }
+            }
+        }
+
+        impl #impl_generics ::pgrx::IntoDatum for #name #ty_generics #where_clause {
+            fn into_datum(self) -> ::core::option::Option<::pgrx::pg_sys::Datum> {
+                match <Self as ::pgrx::heap_tuple::PostgresComposite>::into_heap_tuple(self) {
+                    Ok(tuple) => tuple.into_composite_datum(),
+                    Err(e) => panic!("{}", e),
+                }
+            }
+
+            fn type_oid() -> ::pgrx::pg_sys::Oid {
+                <Self as ::pgrx::heap_tuple::PostgresComposite>::composite_type_oid()
+            }
+
+            fn composite_type_oid(&self) -> ::core::option::Option<::pgrx::pg_sys::Oid> {
+                Some(<Self as ::pgrx::heap_tuple::PostgresComposite>::composite_type_oid())
+            }
+        }
+
+        unsafe impl #impl_generics ::pgrx::pgrx_sql_entity_graph::metadata::SqlTranslatable for #name #ty_generics #where_clause {
+            fn argument_sql() -> core::result::Result<::pgrx::pgrx_sql_entity_graph::metadata::SqlMapping, ::pgrx::pgrx_sql_entity_graph::metadata::ArgumentError> {
+                Ok(::pgrx::pgrx_sql_entity_graph::metadata::SqlMapping::As(String::from(#composite_type)))
+            }
+
+            fn return_sql() -> core::result::Result<::pgrx::pgrx_sql_entity_graph::metadata::Returns, ::pgrx::pgrx_sql_entity_graph::metadata::ReturnsError> {
+                Ok(::pgrx::pgrx_sql_entity_graph::metadata::Returns::One(::pgrx::pgrx_sql_entity_graph::metadata::SqlMapping::As(String::from(#composite_type))))
+            }
+        }
+    })
+}
+
+/**
+Derives `pgrx::fdw::FdwOptions` for a struct, so the `OPTIONS (...)` of a foreign data wrapper,
+server, user mapping, table or column can be validated and parsed into it.
+
+Each named field is mapped onto the option of the same name, and parsed with
+`pgrx::fdw::FdwOptionValue`.  Fields whose type is an `Option<T>` are optional, fields with a
+`#[fdw_option(default = "...")]` are parsed from that value when the option isn't given, and all
+others are required.
+
+```rust,ignore
+use pgrx::prelude::*;
+
+#[derive(FdwOptionValue)]
+enum Mode {
+    ReadOnly,
+    #[fdw_option(name = "rw")]
+    ReadWrite,
+}
+
+#[derive(FdwOptions)]
+struct TableOptions {
+    #[fdw_option(name = "table_name")]
+    name: String,
+    #[fdw_option(default = "100")]
+    fetch_size: i32,
+    #[fdw_option(default = "read_only")]
+    mode: Mode,
+    schema: Option<String>,
+}
+```
+Optionally accepts the following arguments in a field's `#[fdw_option(...)]` attribute:
+
+* `name`: The name of the option the field is mapped onto, if it differs from the field's name.
+* `default`: The value of the field's option when it isn't given.
+*/
+#[proc_macro_derive(FdwOptions, attributes(fdw_option))]
+pub fn fdw_options(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as syn::DeriveInput);
+    impl_fdw_options(ast).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+/**
+Derives `pgrx::fdw::FdwOptionValue` for an enum whose variants have no fields, so it can be the
+type of a field of a `#[derive(FdwOptions)]` struct.  Its values are its variants' names in
+`snake_case`, or the name given with `#[fdw_option(name = "...")]`.
+
+```rust,ignore
+use pgrx::prelude::*;
+
+#[derive(FdwOptionValue)]
+enum Mode {
+    ReadOnly,
+    #[fdw_option(name = "rw")]
+    ReadWrite,
+}
+```
+*/
+#[proc_macro_derive(FdwOptionValue, attributes(fdw_option))]
+pub fn fdw_option_value(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as syn::DeriveInput);
+    impl_fdw_option_value(ast).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+fn impl_fdw_option_value(ast: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let Data::Enum(data) = &ast.data else {
+        return Err(syn::Error::new(
+            ast.span(),
+            "#[derive(FdwOptionValue)] can only be applied to enums",
+        ));
+    };
+
+    let mut values = Vec::new();
+    let mut arms = Vec::new();
+    for variant in &data.variants {
+        if !variant.fields.is_empty() {
+            return Err(syn::Error::new(
+                variant.span(),
+                "#[derive(FdwOptionValue)] can only be applied to enums whose variants have no fields",
+            ));
+        }
+        let ident = &variant.ident;
+        let args = fdw_option_args(&variant.attrs, &["name"])?;
+        let value = args.name.unwrap_or_else(|| snake_case(&ident.to_string()));
+        arms.push(quote! { #value => Ok(Self::#ident), });
+        values.push(value);
+    }
+    let expected = format!("expected one of: {}", values.join(", "));
+    Ok(quote! {
+        impl #impl_generics ::pgrx::fdw::FdwOptionValue for #name #ty_generics #where_clause {
+            fn parse_option(value: &str) -> ::core::result::Result<Self, ::std::string::String> {
+                match value {
+                    #(#arms)*
+                    _ => Err(::std::string::String::from(#expected)),
+                }
+            }
+        }
+    })
+}
+
+fn impl_fdw_options(ast: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let fields = match &ast.data {
+        Data::Struct(syn::DataStruct { fields: syn::Fields::Named(fields), .. }) => &fields.named,
+        Data::Enum(_) => {
+            return Err(syn::Error::new(
+                ast.span(),
+                "#[derive(FdwOptions)] can only be applied to structs with named fields.  Use #[derive(FdwOptionValue)] for an enum which is the type of an option",
+            ))
+        }
+        _ => {
+            return Err(syn::Error::new(
+                ast.span(),
+                "#[derive(FdwOptions)] can only be applied to structs with named fields",
+            ))
+        }
+    };
+
+    let mut options = Vec::new();
+    let mut locals = Vec::new();
+    let mut arms = Vec::new();
+    let mut initializers = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named fields have an ident");
+        let FdwOptionArgs { name: option, default } =
+            fdw_option_args(&field.attrs, &["name", "default"])?;
+        let option = option.unwrap_or_else(|| ident.to_string());
+
+        let (ty, optional) = match option_inner_type(&field.ty) {
+            Some(ty) => (ty, true),
+            None => (&field.ty, false),
+        };
+        if optional && default.is_some() {
+            return Err(syn::Error::new(
+                field.span(),
+                "an `Option<T>` field can't also have a `#[fdw_option(default = \"...\")]`",
+            ));
+        }
+
+        locals.push(quote! {
+            let mut #ident: ::core::option::Option<#ty> = None;
+        });
+        arms.push(quote! {
+            #option => #ident = Some(<#ty as ::pgrx::fdw::FdwOptionValue>::from_option(#option, value)?),
+        });
+        initializers.push(match (optional, default) {
+            (true, _) => quote! { #ident },
+            (false, Some(default)) => quote! {
+                #ident: match #ident {
+                    Some(value) => value,
+                    None => <#ty as ::pgrx::fdw::FdwOptionValue>::from_option(#option, #default)?,
+                }
+            },
+            (false, None) => quote! {
+                #ident: #ident.ok_or(::pgrx::fdw::FdwOptionsError::Missing(#option))?
+            },
+        });
+        options.push(option);
+    }
+
+    Ok(quote! {
+        impl #impl_generics ::pgrx::fdw::FdwOptions for #name #ty_generics #where_clause {
+            const OPTIONS: &'static [&'static str] = &[#(#options),*];
+
+            fn from_options(
+                options: &[(&str, &str)],
+            ) -> ::core::result::Result<Self, ::pgrx::fdw::FdwOptionsError> {
+                #(#locals)*
+                for &(name, value) in options {
+                    match name {
+                        #(#arms)*
+                        _ => {
+                            return Err(::pgrx::fdw::FdwOptionsError::Unknown {
+                                name: ::std::string::String::from(name),
+                                valid: <Self as ::pgrx::fdw::FdwOptions>::OPTIONS,
+                            })
+                        }
+                    }
+                }
+                Ok(Self { #(#initializers),* })
+            }
+        }
+    })
+}
+
+/// The arguments of the `#[fdw_option(...)]` attributes of a field or variant
+#[derive(Default)]
+struct FdwOptionArgs {
+    name: Option<String>,
+    default: Option<String>,
+}
+
+fn fdw_option_args(attrs: &[Attribute], allowed: &[&str]) -> syn::Result<FdwOptionArgs> {
+    let mut args = FdwOptionArgs::default();
+    for att in attrs.iter().filter(|att| att.path.is_ident("fdw_option")) {
+        let syn::Meta::List(list) = att.parse_meta()? else {
+            return Err(syn::Error::new(att.span(), "expected `#[fdw_option(...)]`"));
+        };
+        for nested in &list.nested {
+            let expected = || {
+                let expected = allowed
+                    .iter()
+                    .map(|arg| format!("`{arg} = \"...\"`"))
+                    .collect::<Vec<_>>()
+                    .join(" or ");
+                syn::Error::new(nested.span(), format!("expected {expected}"))
+            };
+            let syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                path,
+                lit: syn::Lit::Str(value),
+                ..
+            })) = nested
+            else {
+                return Err(expected());
+            };
+            let slot = match path.get_ident().map(|ident| ident.to_string()).as_deref() {
+                Some(arg) if !allowed.contains(&arg) => return Err(expected()),
+                Some("name") => &mut args.name,
+                Some("default") => &mut args.default,
+                _ => return Err(expected()),
+            };
+            *slot = Some(value.value());
+        }
+    }
+    Ok(args)
+}
+
+/// `ReadWrite` as `read_write`
+fn snake_case(ident: &str) -> String {
+    let mut snake = String::with_capacity(ident.len());
+    for (i, c) in ident.char_indices() {
+        if c.is_uppercase() {
+            if i > 0 {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+/**
+Derives `FromDatum`, `IntoDatum` and `SqlTranslatable` for a fieldless enum, so it can be converted
+to and from an enum type which already exists in the database, rather than one created by
+`#[derive(PostgresEnum)]`.
+
+Each variant is mapped onto the label of the same name.  The labels' Oids are looked up at runtime,
+and cached, with `pgrx::PgEnumType`.
+
+```rust,ignore
+use pgrx::prelude::*;
+
+#[derive(PostgresExistingEnum)]
+#[enum_type = "app.mood"]
+enum Mood {
+    Happy,
+    #[label = "so-so"]
+    SoSo,
+}
+```
+Optionally accepts the following attributes:
+
+* `enum_type`: The SQL name of the enum type, if it differs from the enum's name.
+* `label`: The label a variant is mapped onto, if it differs from the variant's name.
+*/
+#[proc_macro_derive(PostgresExistingEnum, attributes(enum_type, label))]
+pub fn postgres_existing_enum(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as syn::DeriveInput);
+    impl_postgres_existing_enum(ast).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+fn impl_postgres_existing_enum(ast: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let variants = match &ast.data {
+        Data::Enum(e) => &e.variants,
+        _ => {
+            return Err(syn::Error::new(
+                ast.span(),
+                "#[derive(PostgresExistingEnum)] can only be applied to enums",
+            ))
+        }
+    };
+
+    let name = &ast.ident;
+    let mut enum_type = name.to_string();
+    for att in ast.attrs.iter().filter(|att| att.path.is_ident("enum_type")) {
+        enum_type = string_attribute(att, "enum_type")?;
+    }
+
+    let mut idents = Vec::new();
+    let mut labels = Vec::new();
+    for variant in variants {
+        if !matches!(variant.fields, syn::Fields::Unit) {
+            return Err(syn::Error::new(
+                variant.span(),
+                "#[derive(PostgresExistingEnum)] variants can't have fields",
+            ));
+        }
+        let ident = &variant.ident;
+        let mut label = ident.to_string();
+        for att in variant.attrs.iter().filter(|att| att.path.is_ident("label")) {
+            label = string_attribute(att, "label")?;
+        }
+        idents.push(ident);
+        labels.push(label);
+    }
+
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::pgrx::FromDatum for #name #ty_generics #where_clause {
+            unsafe fn from_polymorphic_datum(
+                datum: ::pgrx::pg_sys::Datum,
+                is_null: bool,
+                _typoid: ::pgrx::pg_sys::Oid,
+            ) -> ::core::option::Option<Self> {
+                if is_null {
+                    None
+                } else {
+                    let enum_type = ::pgrx::PgEnumType::lookup(#enum_type).unwrap_or_else(|e| panic!("{}", e));
+                    let oid = unsafe { <::pgrx::pg_sys::Oid as ::pgrx::FromDatum>::from_datum(datum, is_null)? };
+                    match enum_type.label_of(oid) {
+                        #(Some(#labels) => Some(#name::#idents),)*
+                        Some(label) => panic!("`{}` has no variant for the label `{}`", stringify!(#name), label),
+                        None => panic!("invalid internal value for enum `{}`: {:?}", #enum_type, oid),
+                    }
+                }
+            }
+        }
+
+        impl #impl_generics ::pgrx::IntoDatum for #name #ty_generics #where_clause {
+            fn into_datum(self) -> ::core::option::Option<::pgrx::pg_sys::Datum> {
+                let label = match self {
+                    #(#name::#idents => #labels,)*
+                };
+                let enum_type = ::pgrx::PgEnumType::lookup(#enum_type).unwrap_or_else(|e| panic!("{}", e));
+                match enum_type.label_oid(label) {
+                    Some(oid) => Some(::pgrx::pg_sys::Datum::from(oid)),
+                    None => panic!("enum type `{}` has no label `{}`", #enum_type, label),
+                }
+            }
+
+            fn type_oid() -> ::pgrx::pg_sys::Oid {
+                ::pgrx::PgEnumType::lookup(#enum_type).unwrap_or_else(|e| panic!("{}", e)).oid()
+            }
+        }
+
+        unsafe impl #impl_generics ::pgrx::pgrx_sql_entity_graph::metadata::SqlTranslatable for #name #ty_generics #where_clause {
+            fn argument_sql() -> core::result::Result<::pgrx::pgrx_sql_entity_graph::metadata::SqlMapping, ::pgrx::pgrx_sql_entity_graph::metadata::ArgumentError> {
+                Ok(::pgrx::pgrx_sql_entity_graph::metadata::SqlMapping::As(String::from(#enum_type)))
+            }
+
+            fn return_sql() -> core::result::Result<::pgrx::pgrx_sql_entity_graph::metadata::Returns, ::pgrx::pgrx_sql_entity_graph::metadata::ReturnsError> {
+                Ok(::pgrx::pgrx_sql_entity_graph::metadata::Returns::One(::pgrx::pgrx_sql_entity_graph::metadata::SqlMapping::As(String::from(#enum_type))))
+            }
+        }
+    })
+}
+
+/// The value of a `#[name = "value"]` attribute
+fn string_attribute(att: &Attribute, name: &str) -> syn::Result<String> {
+    match att.parse_meta()? {
+        syn::Meta::NameValue(syn::MetaNameValue { lit: syn::Lit::Str(value), .. }) => {
+            Ok(value.value())
+        }
+        other => Err(syn::Error::new(other.span(), format!("expected `#[{name} = \"...\"]`"))),
+    }
+}
+
+/// `T`, if `ty` is spelled `Option<T>`
+fn option_inner_type(ty: &syn::Type) -> Option<&syn::Type> {
+    let syn::Type::Path(path) = ty else { return None };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            syn::GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
 /**
 Declare a `pgrx::Aggregate` implementation on a type as able to used by Postgres as an aggregate.
 
 Functions inside the `impl` may use the [`#[pgrx]`](macro@pgrx) attribute.
+
+For an aggregate whose `State` is `pgrx::Internal`, `#[pg_aggregate(serial = StateType)]` generates
+the `SERIALFUNC` and `DESERIALFUNC` needed to run it in parallel, where `StateType` is the type kept
+in the `Internal` and derives [`AggregateSerial`](macro@AggregateSerial).
 */
 #[proc_macro_attribute]
-pub fn pg_aggregate(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    // We don't care about `_attr` as we can find it in the `ItemMod`.
-    fn wrapped(item_impl: ItemImpl) -> Result<TokenStream, syn::Error> {
-        let sql_graph_entity_item = PgAggregate::new(item_impl.into())?;
+pub fn pg_aggregate(attr: TokenStream, item: TokenStream) -> TokenStream {
+    fn wrapped(attr: TokenStream, item_impl: ItemImpl) -> Result<TokenStream, syn::Error> {
+        // the only argument is `serial = StateType`, naming the `AggregateSerial` state type
+        let serial_state = if attr.is_empty() {
+            None
+        } else {
+            let parser = |input: syn::parse::ParseStream| {
+                let ident: syn::Ident = input.parse()?;
+                if ident != "serial" {
+                    return Err(syn::Error::new(ident.span(), "expected `serial = StateType`"));
+                }
+                input.parse::<syn::Token![=]>()?;
+                input.parse::<syn::Type>()
+            };
+            Some(syn::parse::Parser::parse(parser, attr)?)
+        };
+        let sql_graph_entity_item = PgAggregate::new_with_serial_state(item_impl, serial_state)?;
 
         Ok(sql_graph_entity_item.to_token_stream().into())
     }
 
     let parsed_base = parse_macro_input!(item as syn::ItemImpl);
-    match wrapped(parsed_base) {
+    match wrapped(attr, parsed_base) {
         Ok(tokens) => tokens,
         Err(e) => {
             let msg = e.to_string();
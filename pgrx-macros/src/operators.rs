@@ -14,6 +14,13 @@ use proc_macro2::Ident;
 use quote::{quote, ToTokens};
 use syn::DeriveInput;
 
+/// The type's `#[cfg]` attributes, to repeat on the functions generated for it, so the schema
+/// leaves them out along with the type
+fn cfgs(ast: &DeriveInput) -> proc_macro2::TokenStream {
+    let cfgs = ast.attrs.iter().filter(|attr| attr.path.is_ident("cfg"));
+    quote! { #(#cfgs)* }
+}
+
 fn ident_and_type_path(ast: &DeriveInput) -> (&Ident, proc_macro2::TokenStream) {
     let ident = &ast.ident;
     let args = parse_postgres_type_args(&ast.attrs);
@@ -28,8 +35,10 @@ fn ident_and_type_path(ast: &DeriveInput) -> (&Ident, proc_macro2::TokenStream)
 pub(crate) fn impl_postgres_eq(ast: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
     let mut stream = proc_macro2::TokenStream::new();
     let (ident, type_path) = ident_and_type_path(&ast);
-    stream.extend(eq(ident, &type_path));
-    stream.extend(ne(ident, &type_path));
+    let cfgs = cfgs(&ast);
+    for func in [eq(ident, &type_path), ne(ident, &type_path)] {
+        stream.extend(quote! { #cfgs #func });
+    }
 
     Ok(stream)
 }
@@ -37,12 +46,17 @@ pub(crate) fn impl_postgres_eq(ast: DeriveInput) -> syn::Result<proc_macro2::Tok
 pub(crate) fn impl_postgres_ord(ast: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
     let mut stream = proc_macro2::TokenStream::new();
     let (ident, type_path) = ident_and_type_path(&ast);
-
-    stream.extend(lt(ident, &type_path));
-    stream.extend(gt(ident, &type_path));
-    stream.extend(le(ident, &type_path));
-    stream.extend(ge(ident, &type_path));
-    stream.extend(cmp(ident, &type_path));
+    let cfgs = cfgs(&ast);
+
+    for func in [
+        lt(ident, &type_path),
+        gt(ident, &type_path),
+        le(ident, &type_path),
+        ge(ident, &type_path),
+        cmp(ident, &type_path),
+    ] {
+        stream.extend(quote! { #cfgs #func });
+    }
 
     let sql_graph_entity_item = PostgresOrd::from_derive_input(ast)?;
     sql_graph_entity_item.to_tokens(&mut stream);
@@ -53,8 +67,10 @@ pub(crate) fn impl_postgres_ord(ast: DeriveInput) -> syn::Result<proc_macro2::To
 pub(crate) fn impl_postgres_hash(ast: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
     let mut stream = proc_macro2::TokenStream::new();
     let (ident, type_path) = ident_and_type_path(&ast);
+    let cfgs = cfgs(&ast);
 
-    stream.extend(hash(ident, &type_path));
+    let func = hash(ident, &type_path);
+    stream.extend(quote! { #cfgs #func });
 
     let sql_graph_entity_item = PostgresHash::from_derive_input(ast)?;
     sql_graph_entity_item.to_tokens(&mut stream);
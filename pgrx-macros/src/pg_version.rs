@@ -0,0 +1,111 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! `pg_version_at_least!()` and `pg_version_below!()`, which choose between two pieces of code by
+//! the Postgres version being compiled for.
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::{braced, Item, LitInt, Token};
+
+/// The Postgres major versions pgrx supports, each of which is a `pgNN` feature of every
+/// extension crate
+const SUPPORTED_VERSIONS: std::ops::RangeInclusive<u16> = 11..=16;
+
+pub(crate) enum Comparison {
+    AtLeast,
+    Below,
+}
+
+/// `<major>, { ... }` with an optional `else { ... }`
+pub(crate) struct PgVersionBranch {
+    major: u16,
+    then: TokenStream,
+    otherwise: Option<TokenStream>,
+}
+
+impl Parse for PgVersionBranch {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let major: LitInt = input.parse()?;
+        let major = major.base10_parse()?;
+        input.parse::<Token![,]>()?;
+
+        let content;
+        braced!(content in input);
+        let then = content.parse()?;
+
+        let otherwise = if input.parse::<Option<Token![else]>>()?.is_some() {
+            let content;
+            braced!(content in input);
+            Some(content.parse()?)
+        } else {
+            None
+        };
+        input.parse::<Option<Token![,]>>()?;
+
+        Ok(Self { major, then, otherwise })
+    }
+}
+
+/// A body made up entirely of items
+struct Items(Vec<Item>);
+
+impl Parse for Items {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut items = Vec::new();
+        while !input.is_empty() {
+            items.push(input.parse()?);
+        }
+        Ok(Self(items))
+    }
+}
+
+impl PgVersionBranch {
+    /// If both branches are only items (functions, types, `extension_sql!()`, ...), each item is
+    /// given a `#[cfg]` of its own so that the entity graph, and so the generated SQL, only has
+    /// those of the version being compiled for.  Otherwise the branches are expressions and the
+    /// result is the value of the chosen one.
+    pub(crate) fn expand(self, comparison: Comparison) -> TokenStream {
+        let versions = SUPPORTED_VERSIONS
+            .filter(|version| match comparison {
+                Comparison::AtLeast => *version >= self.major,
+                Comparison::Below => *version < self.major,
+            })
+            .map(|version| format!("pg{version}"));
+        let predicate = quote! { any(#(feature = #versions),*) };
+
+        let then_items = syn::parse2::<Items>(self.then.clone());
+        let otherwise_items =
+            self.otherwise.clone().map(syn::parse2::<Items>).unwrap_or(Ok(Items(Vec::new())));
+        if let (Ok(Items(then_items)), Ok(Items(otherwise_items))) = (then_items, otherwise_items) {
+            return quote! {
+                #(
+                    #[cfg(#predicate)]
+                    #then_items
+                )*
+                #(
+                    #[cfg(not(#predicate))]
+                    #otherwise_items
+                )*
+            };
+        }
+
+        let then = self.then;
+        let otherwise = self.otherwise.unwrap_or_default();
+        quote! {
+            {
+                #[cfg(#predicate)]
+                let __pgrx_pg_version_branch = { #then };
+                #[cfg(not(#predicate))]
+                let __pgrx_pg_version_branch = { #otherwise };
+                __pgrx_pg_version_branch
+            }
+        }
+    }
+}
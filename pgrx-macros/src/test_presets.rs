@@ -0,0 +1,159 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! The `#[pg_test]` arguments which set up the test's transaction, rather than describe the
+//! `#[pg_extern]` function the test runs as.
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+use syn::parse::{Parse, ParseStream, Parser};
+use syn::punctuated::Punctuated;
+use syn::{LitStr, Token};
+
+const ISOLATION_LEVELS: &[&str] =
+    &["READ UNCOMMITTED", "READ COMMITTED", "REPEATABLE READ", "SERIALIZABLE"];
+
+#[derive(Default)]
+pub(crate) struct TestPresets {
+    isolation_level: Option<String>,
+    search_path: Option<String>,
+    role: Option<String>,
+    fake_clock: Option<String>,
+    gucs: Vec<(String, String)>,
+}
+
+impl TestPresets {
+    /// Take the preset arguments out of a `#[pg_test]` attribute, returning the presets along with
+    /// the rest of the arguments, which are meant for `#[pg_extern]`.
+    pub(crate) fn extract(attr: TokenStream) -> syn::Result<(Self, TokenStream)> {
+        let mut presets = TestPresets::default();
+        let mut remaining = Vec::new();
+
+        for arg in split_args(attr) {
+            let name = match arg.first() {
+                Some(TokenTree::Ident(ident)) => ident.to_string(),
+                _ => {
+                    remaining.push(arg);
+                    continue;
+                }
+            };
+            let value = || -> syn::Result<LitStr> {
+                let tokens = arg[1..].iter().cloned().collect::<TokenStream>();
+                let NameValue { value, .. } = syn::parse2(tokens)?;
+                Ok(value)
+            };
+
+            match name.as_str() {
+                "isolation" => {
+                    let value = value()?;
+                    let level = value.value().to_uppercase();
+                    if !ISOLATION_LEVELS.contains(&level.as_str()) {
+                        return Err(syn::Error::new(
+                            value.span(),
+                            format!("`isolation` must be one of {}", ISOLATION_LEVELS.join(", ")),
+                        ));
+                    }
+                    presets.isolation_level = Some(level);
+                }
+                "search_path" => presets.search_path = Some(value()?.value()),
+                "role" => presets.role = Some(value()?.value()),
+                "fake_clock" => presets.fake_clock = Some(value()?.value()),
+                "set" => {
+                    let group = match arg.get(1) {
+                        Some(TokenTree::Group(group)) if arg.len() == 2 => group.stream(),
+                        _ => {
+                            return Err(syn::Error::new(
+                                arg[0].span(),
+                                "expected `set(name = \"value\", ...)`",
+                            ))
+                        }
+                    };
+                    let gucs = Punctuated::<Guc, Token![,]>::parse_terminated.parse2(group)?;
+                    presets.gucs.extend(gucs.into_iter().map(|guc| (guc.name, guc.value.value())));
+                }
+                _ => remaining.push(arg),
+            }
+        }
+
+        let remaining = remaining.into_iter().map(|arg| arg.into_iter().collect::<TokenStream>());
+        Ok((presets, quote! { #(#remaining),* }))
+    }
+}
+
+impl ToTokens for TestPresets {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let isolation_level = match &self.isolation_level {
+            Some(level) => quote! { Some(#level) },
+            None => quote! { None },
+        };
+        let fake_clock = match &self.fake_clock {
+            Some(now) => quote! { Some(#now) },
+            None => quote! { None },
+        };
+
+        // the role is switched to last, in case it isn't allowed to change the other settings
+        let gucs = self
+            .search_path
+            .iter()
+            .map(|path| ("search_path".to_string(), path.clone()))
+            .chain(self.gucs.iter().cloned())
+            .chain(self.role.iter().map(|role| ("role".to_string(), role.clone())))
+            .map(|(name, value)| quote! { (#name, #value) });
+
+        tokens.extend(quote! {
+            pgrx_tests::TestPresets {
+                isolation_level: #isolation_level,
+                gucs: &[#(#gucs),*],
+                fake_clock: #fake_clock,
+            }
+        });
+    }
+}
+
+/// Split an attribute's arguments on its top-level commas
+fn split_args(attr: TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut args = vec![Vec::new()];
+    for token in attr {
+        match token {
+            TokenTree::Punct(punct) if punct.as_char() == ',' => args.push(Vec::new()),
+            token => args.last_mut().unwrap().push(token),
+        }
+    }
+    args.retain(|arg| !arg.is_empty());
+    args
+}
+
+/// The `= "value"` part of a `name = "value"` argument
+struct NameValue {
+    value: LitStr,
+}
+
+impl Parse for NameValue {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let _eq: Token![=] = input.parse()?;
+        Ok(NameValue { value: input.parse()? })
+    }
+}
+
+/// One `name = "value"` in `set(...)`.  Names containing a `.` have to be quoted.
+struct Guc {
+    name: String,
+    value: LitStr,
+}
+
+impl Parse for Guc {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = if input.peek(LitStr) {
+            input.parse::<LitStr>()?.value()
+        } else {
+            input.parse::<syn::Ident>()?.to_string()
+        };
+        let _eq: Token![=] = input.parse()?;
+        Ok(Guc { name, value: input.parse()? })
+    }
+}
@@ -0,0 +1,159 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Runs `pgrx-pg-sys`'s binding generation and cshim compilation as standalone steps, for build
+//! systems such as Bazel or Buck which want every input and output of an action spelled out.
+//!
+//! This runs the same `pgrx-bindgen` code as `pgrx-pg-sys`'s build script, so the output is
+//! identical to what `cargo` would produce.  Unlike the build script, nothing is read from the
+//! ambient environment: everything is passed to `pgrx-bindgen` in a [`BuildConfig`] filled in from
+//! the command line.
+use clap::{Args, Parser, Subcommand};
+use pgrx_bindgen::build::{self as build_rs, BuildConfig};
+use pgrx_pg_config::PgConfig;
+use std::path::PathBuf;
+
+#[derive(Parser, Debug)]
+#[clap(name = "pgrx-pg-sys-gen", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Generate `pg$VERSION.rs` and `pg$VERSION_oids.rs` into `--out-dir`
+    Bindings(BindingsArgs),
+    /// Compile `libpgrx-cshim-$VERSION.a` into `--out-dir/cshim`
+    Cshim(CshimArgs),
+}
+
+#[derive(Args, Debug)]
+struct CommonArgs {
+    /// The `pg_config` of the Postgres to build against
+    #[clap(long)]
+    pg_config: PathBuf,
+    /// Directory to write outputs to.  Point `pgrx-pg-sys`'s `OUT_DIR` here when compiling it
+    #[clap(long)]
+    out_dir: PathBuf,
+    /// The target triple being built for
+    #[clap(long)]
+    target: String,
+    /// The `pgrx-pg-sys` source directory, holding `include/` and `cshim/`
+    #[clap(long)]
+    pg_sys_dir: PathBuf,
+}
+
+#[derive(Args, Debug)]
+struct BindingsArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    /// A bindgen config file to apply, like the one `PGRX_BINDGEN_CONFIG` names
+    #[clap(long)]
+    bindgen_config: Option<PathBuf>,
+    /// Postgres' server include directory, instead of asking `pg_config`.  Empty to pass none
+    #[clap(long)]
+    includedir_server: Option<String>,
+    /// Don't let bindgen detect the system include paths
+    #[clap(long)]
+    no_detect_includes: bool,
+    /// The libclang for bindgen to load, instead of searching for one
+    #[clap(long)]
+    libclang_path: Option<PathBuf>,
+    /// An extra argument to pass to clang.  May be given more than once
+    #[clap(long = "clang-arg", allow_hyphen_values = true)]
+    clang_args: Vec<String>,
+    /// A directory holding a pregenerated `pg$VERSION_raw_bindings.rs` to use instead of running
+    /// bindgen, as when cross compiling
+    #[clap(long)]
+    raw_bindings_dir: Option<PathBuf>,
+    /// The `rustfmt` to format the bindings with, instead of the one on `PATH`.  The bindings are
+    /// left unformatted if it can't be run
+    #[clap(long)]
+    rustfmt: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct CshimArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    /// The `make` to run
+    #[clap(long, default_value = "make")]
+    make: PathBuf,
+    /// Extra compiler flags for the shim
+    #[clap(long)]
+    copt: Option<String>,
+}
+
+fn main() -> eyre::Result<()> {
+    let cli = Cli::parse();
+    match cli.command {
+        Command::Bindings(args) => bindings(args),
+        Command::Cshim(args) => cshim(args),
+    }
+}
+
+fn bindings(args: BindingsArgs) -> eyre::Result<()> {
+    let (major_version, pg_config) = setup(&args.common)?;
+
+    let mut build_config = BuildConfig::new(args.common.target);
+    if let Some(includedir_server) = args.includedir_server {
+        build_config.includedir_server.insert(major_version, includedir_server);
+    }
+    build_config.detect_includes = !args.no_detect_includes;
+    if let Some(raw_bindings_dir) = args.raw_bindings_dir {
+        build_config.raw_bindings_dir.insert(major_version, raw_bindings_dir);
+    }
+    build_config.libclang_path = args.libclang_path;
+    build_config.extra_clang_args = args.clang_args;
+    if let Some(rustfmt) = args.rustfmt {
+        build_config.rustfmt = rustfmt;
+    }
+    build_config.set_bindgen_env();
+
+    let bindgen_config = match args.bindgen_config {
+        Some(path) => build_rs::BindgenConfig::from_file(&path)?,
+        None => Default::default(),
+    };
+    let build_paths = build_rs::BuildPaths::new(args.common.pg_sys_dir, args.common.out_dir);
+    std::fs::create_dir_all(&build_paths.out_dir)?;
+    build_rs::generate_bindings(
+        major_version,
+        &pg_config,
+        &build_paths,
+        &bindgen_config,
+        &build_config,
+    )
+}
+
+fn cshim(args: CshimArgs) -> eyre::Result<()> {
+    let (_, pg_config) = setup(&args.common)?;
+
+    let mut build_config = BuildConfig::new(args.common.target);
+    build_config.make = args.make;
+    build_config.cshim_copt = args.copt;
+
+    let build_paths = build_rs::BuildPaths::new(args.common.pg_sys_dir, args.common.out_dir);
+    build_rs::build_shim_for_version(
+        &build_paths.shim_src,
+        &build_paths.shim_dst,
+        &pg_config,
+        &build_config,
+    )
+}
+
+/// Resolve `--pg-config`
+fn setup(common: &CommonArgs) -> eyre::Result<(u16, PgConfig)> {
+    let pg_config = PgConfig::new_with_defaults(common.pg_config.clone());
+    let major_version = pg_config.major_version()?;
+    if !pgrx_pg_config::is_supported_major_version(major_version) {
+        return Err(eyre::eyre!("pg{major_version} is not supported by pgrx"));
+    }
+    Ok((major_version, pg_config))
+}
@@ -7,1060 +7,6 @@
 //LICENSE All rights reserved.
 //LICENSE
 //LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
-use bindgen::callbacks::{DeriveTrait, ImplementsTrait, MacroParsingBehavior};
-use eyre::{eyre, WrapErr};
-use once_cell::sync::Lazy;
-use pgrx_pg_config::{
-    is_supported_major_version, prefix_path, PgConfig, PgConfigSelector, Pgrx, SUPPORTED_VERSIONS,
-};
-use quote::{quote, ToTokens};
-use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
-use std::fs;
-use std::path::{self, PathBuf}; // disambiguate path::Path and syn::Type::Path
-use std::process::{Command, Output};
-use syn::{ForeignItem, Item, ItemConst};
-
-const BLOCKLISTED_TYPES: [&str; 3] = ["Datum", "NullableDatum", "Oid"];
-
-mod build {
-    pub(super) mod sym_blocklist;
-}
-
-#[derive(Debug)]
-struct PgrxOverrides(HashSet<String>);
-
-fn is_nightly() -> bool {
-    if env_tracked("CARGO_CFG_PLRUSTC").is_some() {
-        return false;
-    }
-    let rustc = env_tracked("RUSTC").map(PathBuf::from).unwrap_or_else(|| "rustc".into());
-    let output = match std::process::Command::new(rustc).arg("--version").output() {
-        Ok(out) if out.status.success() => String::from_utf8_lossy(&out.stdout).trim().to_owned(),
-        _ => return false,
-    };
-    // Output looks like:
-    // - for nightly: `"rustc 1.66.0-nightly (0ca356586 2022-10-06)"`
-    // - for dev (locally built rust toolchain): `"rustc 1.66.0-dev"`
-    output.starts_with("rustc ") && (output.contains("-nightly") || output.contains("-dev"))
-}
-
-impl PgrxOverrides {
-    fn default() -> Self {
-        // these cause duplicate definition problems on linux
-        // see: https://github.com/rust-lang/rust-bindgen/issues/687
-        PgrxOverrides(
-            vec![
-                "FP_INFINITE".into(),
-                "FP_NAN".into(),
-                "FP_NORMAL".into(),
-                "FP_SUBNORMAL".into(),
-                "FP_ZERO".into(),
-                "IPPORT_RESERVED".into(),
-            ]
-            .into_iter()
-            .collect(),
-        )
-    }
-}
-
-impl bindgen::callbacks::ParseCallbacks for PgrxOverrides {
-    fn will_parse_macro(&self, name: &str) -> MacroParsingBehavior {
-        if self.0.contains(name) {
-            bindgen::callbacks::MacroParsingBehavior::Ignore
-        } else {
-            bindgen::callbacks::MacroParsingBehavior::Default
-        }
-    }
-
-    fn blocklisted_type_implements_trait(
-        &self,
-        name: &str,
-        derive_trait: DeriveTrait,
-    ) -> Option<ImplementsTrait> {
-        if !BLOCKLISTED_TYPES.contains(&name) {
-            return None;
-        }
-
-        let implements_trait = match derive_trait {
-            DeriveTrait::Copy => ImplementsTrait::Yes,
-            DeriveTrait::Debug => ImplementsTrait::Yes,
-            _ => ImplementsTrait::No,
-        };
-        Some(implements_trait)
-    }
-}
-
 fn main() -> eyre::Result<()> {
-    if env_tracked("DOCS_RS").as_deref() == Some("1") {
-        return Ok(());
-    }
-
-    // dump the environment for debugging if asked
-    if env_tracked("PGRX_BUILD_VERBOSE").as_deref() == Some("true") {
-        for (k, v) in std::env::vars() {
-            eprintln!("{}={}", k, v);
-        }
-    }
-
-    let compile_cshim = env_tracked("CARGO_FEATURE_CSHIM").as_deref() == Some("1");
-
-    let is_for_release =
-        env_tracked("PGRX_PG_SYS_GENERATE_BINDINGS_FOR_RELEASE").as_deref() == Some("1");
-
-    // Do nightly detection to suppress silly warnings.
-    if is_nightly() {
-        println!("cargo:rustc-cfg=nightly")
-    };
-
-    let build_paths = BuildPaths::from_env();
-
-    eprintln!("build_paths={build_paths:?}");
-
-    emit_rerun_if_changed();
-
-    let pg_configs: Vec<(u16, PgConfig)> = if env_tracked(
-        "PGRX_PG_SYS_GENERATE_BINDINGS_FOR_RELEASE",
-    )
-    .as_deref()
-        == Some("1")
-    {
-        Pgrx::from_config()?.iter(PgConfigSelector::All)
-            .map(|r| r.expect("invalid pg_config"))
-            .map(|c| (c.major_version().expect("invalid major version"), c))
-            .filter_map(|t| {
-                if is_supported_major_version(t.0) {
-                    Some(t)
-                } else {
-                    println!(
-                        "cargo:warning={} contains a configuration for pg{}, which pgrx does not support.",
-                        Pgrx::config_toml()
-                            .expect("Could not get PGRX configuration TOML")
-                            .to_string_lossy(),
-                        t.0
-                    );
-                    None
-                }
-            })
-            .collect()
-    } else {
-        let mut found = None;
-        for pgver in SUPPORTED_VERSIONS() {
-            if env_tracked(&format!("CARGO_FEATURE_PG{}", pgver.major)).is_none() {
-                continue;
-            }
-            if found.is_some() {
-                return Err(eyre!("Multiple `pg$VERSION` features found, `--no-default-features` may be required."));
-            }
-            let major = pgver.major;
-            found = Some((pgver, format!("pg{}", major)));
-        }
-        let (found_ver, found_feat) = found.ok_or_else(|| {
-            eyre!(
-                "Did not find `pg$VERSION` feature. `pgrx-pg-sys` requires one of {} to be set",
-                SUPPORTED_VERSIONS()
-                    .iter()
-                    .map(|pgver| format!("`pg{}`", pgver.major))
-                    .collect::<Vec<_>>()
-                    .join(", ")
-            )
-        })?;
-
-        if let Ok(pg_config) = PgConfig::from_env() {
-            let major_version = pg_config.major_version()?;
-
-            if major_version != found_ver.major {
-                panic!("Feature flag `pg{found_ver}` does not match version from the environment-described PgConfig (`{major_version}`)")
-            }
-            vec![(major_version, pg_config)]
-        } else {
-            let specific = Pgrx::from_config()?.get(&found_feat)?;
-            vec![(found_ver.major, specific)]
-        }
-    };
-    std::thread::scope(|scope| {
-        // This is pretty much either always 1 (normally) or 5 (for releases),
-        // but in the future if we ever have way more, we should consider
-        // chunking `pg_configs` based on `thread::available_parallelism()`.
-        let threads = pg_configs
-            .iter()
-            .map(|(pg_major_ver, pg_config)| {
-                scope.spawn(|| {
-                    generate_bindings(*pg_major_ver, pg_config, &build_paths, is_for_release)
-                })
-            })
-            .collect::<Vec<_>>();
-        // Most of the rest of this is just for better error handling --
-        // `thread::scope` already joins the threads for us before it returns.
-        let results = threads
-            .into_iter()
-            .map(|thread| thread.join().expect("thread panicked while generating bindings"))
-            .collect::<Vec<eyre::Result<_>>>();
-        results.into_iter().try_for_each(|r| r)
-    })?;
-
-    if compile_cshim {
-        // compile the cshim for each binding
-        for (_version, pg_config) in pg_configs {
-            build_shim(&build_paths.shim_src, &build_paths.shim_dst, &pg_config)?;
-        }
-    }
-
-    Ok(())
-}
-
-fn emit_rerun_if_changed() {
-    // `pgrx-pg-config` doesn't emit one for this.
-    println!("cargo:rerun-if-env-changed=PGRX_PG_CONFIG_PATH");
-    println!("cargo:rerun-if-env-changed=PGRX_PG_CONFIG_AS_ENV");
-    // Bindgen's behavior depends on these vars, but it doesn't emit them
-    // directly because the output would cause issue with `bindgen-cli`. Do it
-    // on bindgen's behalf.
-    println!("cargo:rerun-if-env-changed=LLVM_CONFIG_PATH");
-    println!("cargo:rerun-if-env-changed=LIBCLANG_PATH");
-    println!("cargo:rerun-if-env-changed=LIBCLANG_STATIC_PATH");
-    // Follows the logic bindgen uses here, more or less.
-    // https://github.com/rust-lang/rust-bindgen/blob/e6dd2c636/bindgen/lib.rs#L2918
-    println!("cargo:rerun-if-env-changed=BINDGEN_EXTRA_CLANG_ARGS");
-    if let Some(target) = env_tracked("TARGET") {
-        println!("cargo:rerun-if-env-changed=BINDGEN_EXTRA_CLANG_ARGS_{target}");
-        println!(
-            "cargo:rerun-if-env-changed=BINDGEN_EXTRA_CLANG_ARGS_{}",
-            target.replace('-', "_"),
-        );
-    }
-
-    // don't want to get stuck always generating bindings
-    println!("cargo:rerun-if-env-changed=PGRX_PG_SYS_GENERATE_BINDINGS_FOR_RELEASE");
-
-    println!("cargo:rerun-if-changed=include");
-    println!("cargo:rerun-if-changed=cshim");
-
-    if let Ok(pgrx_config) = Pgrx::config_toml() {
-        println!("cargo:rerun-if-changed={}", pgrx_config.display().to_string());
-    }
-}
-
-fn generate_bindings(
-    major_version: u16,
-    pg_config: &PgConfig,
-    build_paths: &BuildPaths,
-    is_for_release: bool,
-) -> eyre::Result<()> {
-    let mut include_h = build_paths.manifest_dir.clone();
-    include_h.push("include");
-    include_h.push(format!("pg{}.h", major_version));
-
-    let bindgen_output = get_bindings(major_version, &pg_config, &include_h)
-        .wrap_err_with(|| format!("bindgen failed for pg{}", major_version))?;
-
-    let oids = extract_oids(&bindgen_output);
-    let rewritten_items = rewrite_items(&bindgen_output, &oids, is_for_release)
-        .wrap_err_with(|| format!("failed to rewrite items for pg{}", major_version))?;
-    let oids = format_builtin_oid_impl(oids);
-
-    let dest_dirs =
-        if env_tracked("PGRX_PG_SYS_GENERATE_BINDINGS_FOR_RELEASE").as_deref() == Some("1") {
-            vec![build_paths.out_dir.clone(), build_paths.src_dir.clone()]
-        } else {
-            vec![build_paths.out_dir.clone()]
-        };
-    for dest_dir in dest_dirs {
-        let mut bindings_file = dest_dir.clone();
-        bindings_file.push(&format!("pg{}.rs", major_version));
-        write_rs_file(
-            rewritten_items.clone(),
-            &bindings_file,
-            quote! {
-                use crate as pg_sys;
-                #[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14", feature = "pg15", feature = "pg16"))]
-                use crate::NullableDatum;
-                use crate::{Datum, Oid, PgNode};
-            },
-        )
-        .wrap_err_with(|| {
-            format!(
-                "Unable to write bindings file for pg{} to `{}`",
-                major_version,
-                bindings_file.display()
-            )
-        })?;
-
-        let mut oids_file = dest_dir.clone();
-        oids_file.push(&format!("pg{}_oids.rs", major_version));
-        write_rs_file(oids.clone(), &oids_file, quote! {}).wrap_err_with(|| {
-            format!(
-                "Unable to write oids file for pg{} to `{}`",
-                major_version,
-                oids_file.display()
-            )
-        })?;
-    }
-    Ok(())
-}
-
-#[derive(Debug, Clone)]
-struct BuildPaths {
-    /// CARGO_MANIFEST_DIR
-    manifest_dir: PathBuf,
-    /// OUT_DIR
-    out_dir: PathBuf,
-    /// {manifest_dir}/src
-    src_dir: PathBuf,
-    /// {manifest_dir}/cshim
-    shim_src: PathBuf,
-    /// {out_dir}/cshim
-    shim_dst: PathBuf,
-}
-
-impl BuildPaths {
-    fn from_env() -> Self {
-        // Cargo guarantees these are provided, so unwrap is fine.
-        let manifest_dir = env_tracked("CARGO_MANIFEST_DIR").map(PathBuf::from).unwrap();
-        let out_dir = env_tracked("OUT_DIR").map(PathBuf::from).unwrap();
-        Self {
-            src_dir: manifest_dir.join("src"),
-            shim_src: manifest_dir.join("cshim"),
-            shim_dst: out_dir.join("cshim"),
-            out_dir,
-            manifest_dir,
-        }
-    }
-}
-
-fn write_rs_file(
-    code: proc_macro2::TokenStream,
-    file: &PathBuf,
-    header: proc_macro2::TokenStream,
-) -> eyre::Result<()> {
-    let mut contents = header;
-    contents.extend(code);
-
-    std::fs::write(&file, contents.to_string())?;
-    rust_fmt(&file)
-}
-
-/// Given a token stream representing a file, apply a series of transformations to munge
-/// the bindgen generated code with some postgres specific enhancements
-fn rewrite_items(
-    file: &syn::File,
-    oids: &BTreeMap<syn::Ident, Box<syn::Expr>>,
-    is_for_release: bool,
-) -> eyre::Result<proc_macro2::TokenStream> {
-    let items_vec = rewrite_oid_consts(&file.items, oids);
-    let mut items = apply_pg_guard(&items_vec)?;
-    let pgnode_impls = impl_pg_node(&items_vec, is_for_release)?;
-
-    // append the pgnodes to the set of items
-    items.extend(pgnode_impls);
-
-    Ok(items)
-}
-
-/// Find all the constants that represent Postgres type OID values.
-///
-/// These are constants of type `u32` whose name ends in the string "OID"
-fn extract_oids(code: &syn::File) -> BTreeMap<syn::Ident, Box<syn::Expr>> {
-    let mut oids = BTreeMap::new(); // we would like to have a nice sorted set
-    for item in &code.items {
-        match item {
-            Item::Const(ItemConst { ident, ty, expr, .. }) => {
-                // Retype as strings for easy comparison
-                let name = ident.to_string();
-                let ty_str = ty.to_token_stream().to_string();
-
-                // This heuristic identifies "OIDs"
-                // We're going to warp the const declarations to be our newtype Oid
-                if ty_str == "u32" && is_builtin_oid(&name) {
-                    oids.insert(ident.clone(), expr.clone());
-                }
-            }
-            _ => {}
-        }
-    }
-    oids
-}
-
-fn is_builtin_oid(name: &str) -> bool {
-    if name.ends_with("OID") && name != "HEAP_HASOID" {
-        true
-    } else if name.ends_with("RelationId") {
-        true
-    } else if name == "TemplateDbOid" {
-        true
-    } else {
-        false
-    }
-}
-
-fn rewrite_oid_consts(
-    items: &Vec<syn::Item>,
-    oids: &BTreeMap<syn::Ident, Box<syn::Expr>>,
-) -> Vec<syn::Item> {
-    items
-        .into_iter()
-        .map(|item| match item {
-            Item::Const(ItemConst { ident, ty, expr, .. })
-                if ty.to_token_stream().to_string() == "u32" && oids.get(ident) == Some(expr) =>
-            {
-                syn::parse2(quote! { pub const #ident : Oid = Oid(#expr); }).unwrap()
-            }
-            item => item.clone(),
-        })
-        .collect()
-}
-
-fn format_builtin_oid_impl<'a>(
-    oids: BTreeMap<syn::Ident, Box<syn::Expr>>,
-) -> proc_macro2::TokenStream {
-    let enum_variants: proc_macro2::TokenStream;
-    let from_impl: proc_macro2::TokenStream;
-    (enum_variants, from_impl) = oids
-        .iter()
-        .map(|(ident, expr)| {
-            (quote! { #ident = #expr, }, quote! { #expr => Ok(BuiltinOid::#ident), })
-        })
-        .unzip();
-
-    quote! {
-        use crate::{NotBuiltinOid};
-
-        #[derive(Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd, Debug)]
-        pub enum BuiltinOid {
-            #enum_variants
-        }
-
-        impl BuiltinOid {
-            pub const fn from_u32(uint: u32) -> Result<BuiltinOid, NotBuiltinOid> {
-                match uint {
-                    0 => Err(NotBuiltinOid::Invalid),
-                    #from_impl
-                    _ => Err(NotBuiltinOid::Ambiguous),
-                }
-            }
-        }
-    }
-}
-
-/// Implement our `PgNode` marker trait for `pg_sys::Node` and its "subclasses"
-fn impl_pg_node(
-    items: &Vec<syn::Item>,
-    is_for_release: bool,
-) -> eyre::Result<proc_macro2::TokenStream> {
-    let mut pgnode_impls = proc_macro2::TokenStream::new();
-
-    // we scope must of the computation so we can borrow `items` and then
-    // extend it at the very end.
-    let struct_graph: StructGraph = StructGraph::from(&items[..]);
-
-    // collect all the structs with `NodeTag` as their first member,
-    // these will serve as roots in our forest of `Node`s
-    let mut root_node_structs = Vec::new();
-    for descriptor in struct_graph.descriptors.iter() {
-        // grab the first field, if any
-        let first_field = match &descriptor.struct_.fields {
-            syn::Fields::Named(fields) => {
-                if let Some(first_field) = fields.named.first() {
-                    first_field
-                } else {
-                    continue;
-                }
-            }
-            syn::Fields::Unnamed(fields) => {
-                if let Some(first_field) = fields.unnamed.first() {
-                    first_field
-                } else {
-                    continue;
-                }
-            }
-            _ => continue,
-        };
-
-        // grab the type name of the first field
-        let ty_name = if let syn::Type::Path(p) = &first_field.ty {
-            if let Some(last_segment) = p.path.segments.last() {
-                last_segment.ident.to_string()
-            } else {
-                continue;
-            }
-        } else {
-            continue;
-        };
-
-        if ty_name == "NodeTag" {
-            root_node_structs.push(descriptor);
-        }
-    }
-
-    // the set of types which subclass `Node` according to postgres' object system
-    let mut node_set = HashSet::new();
-    // fill in any children of the roots with a recursive DFS
-    // (we are not operating on user input, so it is ok to just
-    //  use direct recursion rather than an explicit stack).
-    for root in root_node_structs.into_iter() {
-        dfs_find_nodes(root, &struct_graph, &mut node_set);
-    }
-
-    let nodes: Box<dyn std::iter::Iterator<Item = StructDescriptor>> = if is_for_release {
-        // if it's for release we want to sort by struct name to avoid diff churn
-        let mut set = node_set.into_iter().collect::<Vec<_>>();
-        set.sort_by(|a, b| a.struct_.ident.cmp(&b.struct_.ident));
-        Box::new(set.into_iter())
-    } else {
-        // otherwise we don't care and want to avoid the CPU overhead of sorting
-        Box::new(node_set.into_iter())
-    };
-
-    // now we can finally iterate the Nodes and emit out Display impl
-    for node_struct in nodes {
-        let struct_name = &node_struct.struct_.ident;
-
-        // impl the PgNode trait for all nodes
-        pgnode_impls.extend(quote! {
-            impl pg_sys::seal::Sealed for #struct_name {}
-            impl pg_sys::PgNode for #struct_name {}
-        });
-
-        // impl Rust's Display trait for all nodes
-        pgnode_impls.extend(quote! {
-            impl std::fmt::Display for #struct_name {
-                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                    write!(f, "{}", self.display_node() )
-                }
-            }
-        });
-    }
-
-    Ok(pgnode_impls)
-}
-
-/// Given a root node, dfs_find_nodes adds all its children nodes to `node_set`.
-fn dfs_find_nodes<'graph>(
-    node: &'graph StructDescriptor<'graph>,
-    graph: &'graph StructGraph<'graph>,
-    node_set: &mut HashSet<StructDescriptor<'graph>>,
-) {
-    node_set.insert(node.clone());
-
-    for child in node.children(graph) {
-        if node_set.contains(child) {
-            continue;
-        }
-        dfs_find_nodes(child, graph, node_set);
-    }
-}
-
-/// A graph describing the inheritance relationships between different nodes
-/// according to postgres' object system.
-///
-/// NOTE: the borrowed lifetime on a StructGraph should also ensure that the offsets
-///       it stores into the underlying items struct are always correct.
-#[derive(Clone, Debug)]
-struct StructGraph<'a> {
-    #[allow(dead_code)]
-    /// A table mapping struct names to their offset in the descriptor table
-    name_tab: HashMap<String, usize>,
-    #[allow(dead_code)]
-    /// A table mapping offsets into the underlying items table to offsets in the descriptor table
-    item_offset_tab: Vec<Option<usize>>,
-    /// A table of struct descriptors
-    descriptors: Vec<StructDescriptor<'a>>,
-}
-
-impl<'a> From<&'a [syn::Item]> for StructGraph<'a> {
-    fn from(items: &'a [syn::Item]) -> StructGraph<'a> {
-        let mut descriptors = Vec::new();
-
-        // a table mapping struct names to their offset in `descriptors`
-        let mut name_tab: HashMap<String, usize> = HashMap::new();
-        let mut item_offset_tab: Vec<Option<usize>> = vec![None; items.len()];
-        for (i, item) in items.iter().enumerate() {
-            if let &syn::Item::Struct(struct_) = &item {
-                let next_offset = descriptors.len();
-                descriptors.push(StructDescriptor {
-                    struct_,
-                    items_offset: i,
-                    parent: None,
-                    children: Vec::new(),
-                });
-                name_tab.insert(struct_.ident.to_string(), next_offset);
-                item_offset_tab[i] = Some(next_offset);
-            }
-        }
-
-        for item in items.iter() {
-            // grab the first field if it is struct
-            let (id, first_field) = match &item {
-                &syn::Item::Struct(syn::ItemStruct {
-                    ident: id,
-                    fields: syn::Fields::Named(fields),
-                    ..
-                }) => {
-                    if let Some(first_field) = fields.named.first() {
-                        (id.to_string(), first_field)
-                    } else {
-                        continue;
-                    }
-                }
-                &syn::Item::Struct(syn::ItemStruct {
-                    ident: id,
-                    fields: syn::Fields::Unnamed(fields),
-                    ..
-                }) => {
-                    if let Some(first_field) = fields.unnamed.first() {
-                        (id.to_string(), first_field)
-                    } else {
-                        continue;
-                    }
-                }
-                _ => continue,
-            };
-
-            if let syn::Type::Path(p) = &first_field.ty {
-                // We should be guaranteed that just extracting the last path
-                // segment is ok because these structs are all from the same module.
-                // (also, they are all generated from C code, so collisions should be
-                //  impossible anyway thanks to C's single shared namespace).
-                if let Some(last_segment) = p.path.segments.last() {
-                    if let Some(parent_offset) = name_tab.get(&last_segment.ident.to_string()) {
-                        // establish the 2-way link
-                        let child_offset = name_tab[&id];
-                        descriptors[child_offset].parent = Some(*parent_offset);
-                        descriptors[*parent_offset].children.push(child_offset);
-                    }
-                }
-            }
-        }
-
-        StructGraph { name_tab, item_offset_tab, descriptors }
-    }
-}
-
-impl<'a> StructDescriptor<'a> {
-    /// children returns an iterator over the children of this node in the graph
-    fn children(&'a self, graph: &'a StructGraph) -> StructDescriptorChildren {
-        StructDescriptorChildren { offset: 0, descriptor: self, graph }
-    }
-}
-
-/// An iterator over a StructDescriptor's children
-struct StructDescriptorChildren<'a> {
-    offset: usize,
-    descriptor: &'a StructDescriptor<'a>,
-    graph: &'a StructGraph<'a>,
-}
-
-impl<'a> std::iter::Iterator for StructDescriptorChildren<'a> {
-    type Item = &'a StructDescriptor<'a>;
-    fn next(&mut self) -> Option<&'a StructDescriptor<'a>> {
-        if self.offset >= self.descriptor.children.len() {
-            None
-        } else {
-            let ret = Some(&self.graph.descriptors[self.descriptor.children[self.offset]]);
-            self.offset += 1;
-            ret
-        }
-    }
-}
-
-/// A node a StructGraph
-#[derive(Clone, Debug, Hash, Eq, PartialEq)]
-struct StructDescriptor<'a> {
-    /// A reference to the underlying struct syntax node
-    struct_: &'a syn::ItemStruct,
-    /// An offset into the items slice that was used to construct the struct graph that
-    /// this StructDescriptor is a part of
-    items_offset: usize,
-    /// The offset of the "parent" (first member) struct (if any).
-    parent: Option<usize>,
-    /// The offsets of the "children" structs (if any).
-    children: Vec<usize>,
-}
-
-fn get_bindings(
-    major_version: u16,
-    pg_config: &PgConfig,
-    include_h: &PathBuf,
-) -> eyre::Result<syn::File> {
-    let bindings = if let Some(info_dir) =
-        target_env_tracked(&format!("PGRX_TARGET_INFO_PATH_PG{major_version}"))
-    {
-        let bindings_file = format!("{info_dir}/pg{major_version}_raw_bindings.rs");
-        std::fs::read_to_string(&bindings_file)
-            .wrap_err_with(|| format!("failed to read raw bindings from {bindings_file}"))?
-    } else {
-        let bindings = run_bindgen(major_version, pg_config, include_h)?;
-        if let Some(path) = env_tracked("PGRX_PG_SYS_EXTRA_OUTPUT_PATH") {
-            std::fs::write(&path, &bindings)?;
-        }
-        bindings
-    };
-    syn::parse_file(bindings.as_str()).wrap_err_with(|| "failed to parse generated bindings")
-}
-
-/// Given a specific postgres version, `run_bindgen` generates bindings for the given
-/// postgres version and returns them as a token stream.
-fn run_bindgen(
-    major_version: u16,
-    pg_config: &PgConfig,
-    include_h: &PathBuf,
-) -> eyre::Result<String> {
-    eprintln!("Generating bindings for pg{major_version}");
-    let bindings = bindgen::Builder::default()
-        .header(include_h.display().to_string())
-        .clang_args(&extra_bindgen_clang_args(pg_config)?)
-        .clang_args(pg_target_include_flags(major_version, pg_config)?)
-        .detect_include_paths(target_env_tracked("PGRX_BINDGEN_NO_DETECT_INCLUDES").is_none())
-        .parse_callbacks(Box::new(PgrxOverrides::default()))
-        .blocklist_type("(Nullable)?Datum") // manually wrapping datum types for correctness
-        .blocklist_type("Oid") // "Oid" is not just any u32
-        .blocklist_function("varsize_any") // pgrx converts the VARSIZE_ANY macro, so we don't want to also have this function, which is in heaptuple.c
-        .blocklist_function("(?:query|expression)_tree_walker")
-        .blocklist_function(".*(?:set|long)jmp")
-        .blocklist_function("pg_re_throw")
-        .blocklist_function("errstart")
-        .blocklist_function("errcode")
-        .blocklist_function("errmsg")
-        .blocklist_function("errdetail")
-        .blocklist_function("errcontext_msg")
-        .blocklist_function("errhint")
-        .blocklist_function("errfinish")
-        .blocklist_item("CONFIGURE_ARGS") // configuration during build is hopefully irrelevant
-        .blocklist_item("_*(?:HAVE|have)_.*") // header tracking metadata
-        .blocklist_item("_[A-Z_]+_H") // more header metadata
-        .blocklist_item("__[A-Z].*") // these are reserved and unused by Postgres
-        .blocklist_item("__darwin.*") // this should always be Apple's names
-        .blocklist_function("pq(?:Strerror|Get.*)") // wrappers around platform functions: user can call those themselves
-        .blocklist_function("log")
-        .blocklist_item(".*pthread.*)") // shims for pthreads on non-pthread systems, just use std::thread
-        .blocklist_item(".*(?i:va)_(?i:list|start|end|copy).*") // do not need va_list anything!
-        .blocklist_function("(?:pg_|p)v(?:sn?|f)?printf")
-        .blocklist_function("appendStringInfoVA")
-        .blocklist_file("stdarg.h")
-        // these cause cause warnings, errors, or deprecations on some systems,
-        // and are not useful for us.
-        .blocklist_function("(?:sigstack|sigreturn|siggetmask|gets|vfork|te?mpnam(?:_r)?|mktemp)")
-        // Missing on some systems, despite being in their headers.
-        .blocklist_function("inet_net_pton.*")
-        .size_t_is_usize(true)
-        .formatter(bindgen::Formatter::None)
-        .derive_debug(true)
-        .derive_copy(true) // necessary to avoid __BindgenUnionField usages -- I don't understand why?
-        .derive_default(true)
-        .derive_eq(false)
-        .derive_partialeq(false)
-        .derive_hash(false)
-        .derive_ord(false)
-        .derive_partialord(false)
-        .layout_tests(false)
-        .generate()
-        .wrap_err_with(|| format!("Unable to generate bindings for pg{}", major_version))?;
-
-    Ok(bindings.to_string())
-}
-
-fn env_tracked(s: &str) -> Option<String> {
-    // a **sorted** list of environment variable keys that cargo might set that we don't need to track
-    // these were picked out, by hand, from: https://doc.rust-lang.org/cargo/reference/environment-variables.html
-    const CARGO_KEYS: &[&str] = &[
-        "BROWSER",
-        "DEBUG",
-        "DOCS_RS",
-        "HOST",
-        "HTTP_PROXY",
-        "HTTP_TIMEOUT",
-        "NUM_JOBS",
-        "OPT_LEVEL",
-        "OUT_DIR",
-        "PATH",
-        "PROFILE",
-        "TARGET",
-        "TERM",
-    ];
-
-    let is_cargo_key =
-        s.starts_with("CARGO") || s.starts_with("RUST") || CARGO_KEYS.binary_search(&s).is_ok();
-
-    if !is_cargo_key {
-        // if it's an envar that cargo gives us, we don't want to ask it to rerun build.rs if it changes
-        // we'll let cargo figure that out for itself, and doing so, depending on the key, seems to
-        // cause cargo to rerun build.rs every time, which is terrible
-        println!("cargo:rerun-if-env-changed={s}");
-    }
-    std::env::var(s).ok()
-}
-
-fn target_env_tracked(s: &str) -> Option<String> {
-    let target = env_tracked("TARGET").unwrap();
-    env_tracked(&format!("{s}_{target}")).or_else(|| env_tracked(s))
-}
-
-/// Returns `Err` if `pg_config` errored, `None` if we should
-fn pg_target_include_flags(pg_version: u16, pg_config: &PgConfig) -> eyre::Result<Option<String>> {
-    let var = "PGRX_INCLUDEDIR_SERVER";
-    let value =
-        target_env_tracked(&format!("{var}_PG{pg_version}")).or_else(|| target_env_tracked(var));
-    match value {
-        // No configured value: ask `pg_config`.
-        None => Ok(Some(format!("-I{}", pg_config.includedir_server()?.display()))),
-        // Configured to empty string: assume bindgen is getting it some other
-        // way, pass nothing.
-        Some(overridden) if overridden.is_empty() => Ok(None),
-        // Configured to non-empty string: pass to bindgen
-        Some(overridden) => Ok(Some(format!("-I{overridden}"))),
-    }
-}
-
-fn build_shim(shim_src: &PathBuf, shim_dst: &PathBuf, pg_config: &PgConfig) -> eyre::Result<()> {
-    let major_version = pg_config.major_version()?;
-    let mut libpgrx_cshim: PathBuf = shim_dst.clone();
-
-    libpgrx_cshim.push(format!("libpgrx-cshim-{}.a", major_version));
-
-    eprintln!("libpgrx_cshim={}", libpgrx_cshim.display());
-    // then build the shim for the version feature currently being built
-    build_shim_for_version(&shim_src, &shim_dst, pg_config)?;
-
-    // no matter what, tell rustc to link to the library that was built for the feature we're currently building
-    let envvar_name = format!("CARGO_FEATURE_PG{}", major_version);
-    if env_tracked(&envvar_name).is_some() {
-        println!("cargo:rustc-link-search={}", shim_dst.display());
-        println!("cargo:rustc-link-lib=static=pgrx-cshim-{}", major_version);
-    }
-
-    Ok(())
-}
-
-fn build_shim_for_version(
-    shim_src: &PathBuf,
-    shim_dst: &PathBuf,
-    pg_config: &PgConfig,
-) -> eyre::Result<()> {
-    let path_env = prefix_path(pg_config.parent_path());
-    let major_version = pg_config.major_version()?;
-
-    eprintln!("PATH for build_shim={}", path_env);
-    eprintln!("shim_src={}", shim_src.display());
-    eprintln!("shim_dst={}", shim_dst.display());
-
-    fs::create_dir_all(shim_dst).unwrap();
-
-    let makefile_dst = path::Path::join(shim_dst, "./Makefile");
-    if !makefile_dst.try_exists()? {
-        fs::copy(path::Path::join(shim_src, "./Makefile"), makefile_dst).unwrap();
-    }
-
-    let cshim_dst = path::Path::join(shim_dst, "./pgrx-cshim.c");
-    if !cshim_dst.try_exists()? {
-        fs::copy(path::Path::join(shim_src, "./pgrx-cshim.c"), cshim_dst).unwrap();
-    }
-
-    let make = env_tracked("MAKE").unwrap_or_else(|| "make".to_string());
-    let rc = run_command(
-        Command::new(make)
-            .arg("clean")
-            .arg(&format!("libpgrx-cshim-{}.a", major_version))
-            .env("PG_TARGET_VERSION", format!("{}", major_version))
-            .env("PATH", path_env)
-            .env_remove("TARGET")
-            .env_remove("HOST")
-            .current_dir(shim_dst),
-        &format!("shim for PG v{}", major_version),
-    )?;
-
-    if rc.status.code().unwrap() != 0 {
-        return Err(eyre!("failed to make pgrx-cshim for v{}", major_version));
-    }
-
-    Ok(())
-}
-
-fn extra_bindgen_clang_args(pg_config: &PgConfig) -> eyre::Result<Vec<String>> {
-    let mut out = vec![];
-    if env_tracked("CARGO_CFG_TARGET_OS").as_deref() == Some("macos") {
-        // On macOS, find the `-isysroot` arg out of the c preprocessor flags,
-        // to handle the case where bindgen uses a libclang isn't provided by
-        // the system.
-        let flags = pg_config.cppflags()?;
-        // In practice this will always be valid UTF-8 because of how the
-        // `pgrx-pg-config` crate is implemented, but even if it were not, the
-        // problem won't be with flags we are interested in.
-        let flags = shlex::split(&flags.to_string_lossy()).unwrap_or_default();
-        // Find the `-isysroot` flags -- The rest are `-I` flags that don't seem
-        // to be needed inside the code (and feel likely to cause bindgen to
-        // emit bindings for unrelated libraries)
-        for pair in flags.windows(2) {
-            if pair[0] == "-isysroot" {
-                if std::path::Path::new(&pair[1]).exists() {
-                    out.extend(pair.into_iter().cloned());
-                } else {
-                    // The SDK path doesn't exist. Emit a warning, which they'll
-                    // see if the build ends up failing (it may not fail in all
-                    // cases, so we don't panic here).
-                    //
-                    // There's a bunch of smarter things we can try here, but
-                    // most of them either break things that currently work, or
-                    // are very difficult to get right. If you try to fix this,
-                    // be sure to consider cases like:
-                    //
-                    // - User may have CommandLineTools and not Xcode, vice
-                    //   versa, or both installed.
-                    // - User may using a newer SDK than their OS, or vice
-                    //   versa.
-                    // - User may be using a newer SDK than their XCode (updated
-                    //   Command line tools, not OS), or vice versa.
-                    // - And so on.
-                    //
-                    // These are all actually fairly common. Note that the code
-                    // as-is is *not* broken in these cases (except on OS/SDK
-                    // updates), so care should be taken to avoid changing that
-                    // if possible.
-                    //
-                    // The logic we'd like ideally is for `cargo pgrx init` to
-                    // choose a good SDK in the first place, and force postgres
-                    // to use it. Then, the logic in this build script would
-                    // Just Work without changes (since we are using its
-                    // sysroot verbatim).
-                    //
-                    // The value of "Good" here is tricky, but the logic should
-                    // probably:
-                    //
-                    // - prefer SDKs from the CLI tools to ones from XCode
-                    //   (since they're guaranteed compatible with the user's OS
-                    //   version)
-                    //
-                    // - prefer SDKs that specify only the major SDK version
-                    //   (e.g. MacOSX12.sdk and not MacOSX12.4.sdk or
-                    //   MacOSX.sdk), to avoid breaking too frequently (if we
-                    //   have a minor version) or being totally unable to detect
-                    //   what version of the SDK was used to build postgres (if
-                    //   we have neither).
-                    //
-                    // - Avoid choosing an SDK newer than the user's OS version,
-                    //   since postgres fails to detect that they are missing if
-                    //   you do.
-                    //
-                    // This is surprisingly hard to implement, as the
-                    // information is scattered across a dozen ini files.
-                    // Presumably Apple assumes you'll use
-                    // `MACOSX_DEPLOYMENT_TARGET`, rather than basing it off the
-                    // SDK version, but it's not an option for postgres.
-                    let major_version = pg_config.major_version()?;
-                    println!(
-                        "cargo:warning=postgres v{major_version} was compiled against an \
-                         SDK Root which does not seem to exist on this machine ({}). You may \
-                         need to re-run `cargo pgrx init` and/or update your command line tools.",
-                        pair[1],
-                    );
-                };
-                // Either way, we stop here.
-                break;
-            }
-        }
-    }
-    Ok(out)
-}
-
-fn run_command(mut command: &mut Command, version: &str) -> eyre::Result<Output> {
-    let mut dbg = String::new();
-
-    command = command
-        .env_remove("DEBUG")
-        .env_remove("MAKEFLAGS")
-        .env_remove("MAKELEVEL")
-        .env_remove("MFLAGS")
-        .env_remove("DYLD_FALLBACK_LIBRARY_PATH")
-        .env_remove("OPT_LEVEL")
-        .env_remove("PROFILE")
-        .env_remove("OUT_DIR")
-        .env_remove("NUM_JOBS");
-
-    eprintln!("[{}] {:?}", version, command);
-    dbg.push_str(&format!("[{}] -------- {:?} -------- \n", version, command));
-
-    let output = command.output()?;
-    let rc = output.clone();
-
-    if !output.stdout.is_empty() {
-        for line in String::from_utf8(output.stdout).unwrap().lines() {
-            if line.starts_with("cargo:") {
-                dbg.push_str(&format!("{}\n", line));
-            } else {
-                dbg.push_str(&format!("[{}] [stdout] {}\n", version, line));
-            }
-        }
-    }
-
-    if !output.stderr.is_empty() {
-        for line in String::from_utf8(output.stderr).unwrap().lines() {
-            dbg.push_str(&format!("[{}] [stderr] {}\n", version, line));
-        }
-    }
-    dbg.push_str(&format!("[{}] /----------------------------------------\n", version));
-
-    eprintln!("{}", dbg);
-    Ok(rc)
-}
-
-// Plausibly it would be better to generate a regex to pass to bindgen for this,
-// but this is less error-prone for now.
-static BLOCKLISTED: Lazy<BTreeSet<&'static str>> =
-    Lazy::new(|| build::sym_blocklist::SYMBOLS.iter().copied().collect::<BTreeSet<&str>>());
-fn is_blocklisted_item(item: &ForeignItem) -> bool {
-    let sym_name = match item {
-        ForeignItem::Fn(f) => &f.sig.ident,
-        // We don't *need* to filter statics too (only functions), but it
-        // doesn't hurt.
-        ForeignItem::Static(s) => &s.ident,
-        _ => return false,
-    };
-    BLOCKLISTED.contains(sym_name.to_string().as_str())
-}
-
-fn apply_pg_guard(items: &Vec<syn::Item>) -> eyre::Result<proc_macro2::TokenStream> {
-    let mut out = proc_macro2::TokenStream::new();
-    for item in items {
-        match item {
-            Item::ForeignMod(block) => {
-                let abi = &block.abi;
-                for item in &block.items {
-                    if is_blocklisted_item(item) {
-                        continue;
-                    }
-                    match item {
-                        ForeignItem::Fn(func) => {
-                            out.extend(quote! {
-                                #[pgrx_macros::pg_guard]
-                                #abi { #func }
-                            });
-                        }
-                        other => out.extend(quote! { #abi { #other } }),
-                    }
-                }
-            }
-            _ => {
-                out.extend(item.into_token_stream());
-            }
-        }
-    }
-
-    Ok(out)
-}
-
-fn rust_fmt(path: &PathBuf) -> eyre::Result<()> {
-    // We shouldn't hit this path in a case where we care about it, but... just
-    // in case we probably should respect RUSTFMT.
-    let rustfmt = env_tracked("RUSTFMT").unwrap_or_else(|| "rustfmt".into());
-    let out = run_command(Command::new(rustfmt).arg(path).current_dir("."), "[bindings_diff]");
-    match out {
-        Ok(_) => Ok(()),
-        Err(e)
-            if e.downcast_ref::<std::io::Error>()
-                .ok_or(eyre!("Couldn't downcast error ref"))?
-                .kind()
-                == std::io::ErrorKind::NotFound =>
-        {
-            Err(e).wrap_err("Failed to run `rustfmt`, is it installed?")
-        }
-        Err(e) => Err(e.into()),
-    }
+    pgrx_bindgen::build::main()
 }
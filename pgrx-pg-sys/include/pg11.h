@@ -20,6 +20,7 @@
 #include "access/generic_xlog.h"
 #include "access/gin.h"
 #include "access/gist.h"
+#include "access/hash.h"
 #include "access/heapam.h"
 #include "access/htup.h"
 #include "access/htup_details.h"
@@ -27,6 +28,7 @@
 #include "access/relscan.h"
 #include "access/skey.h"
 #include "access/sysattr.h"
+#include "access/tuptoaster.h"
 #include "access/xact.h"
 #include "catalog/dependency.h"
 #include "catalog/index.h"
@@ -39,12 +41,17 @@
 #include "catalog/pg_database.h"
 #include "catalog/pg_enum.h"
 #include "catalog/pg_extension.h"
+#include "catalog/pg_foreign_data_wrapper.h"
+#include "catalog/pg_foreign_server.h"
+#include "catalog/pg_foreign_table.h"
 #include "catalog/pg_operator.h"
 #include "catalog/pg_proc.h"
 #include "catalog/pg_namespace.h"
 #include "catalog/pg_tablespace.h"
 #include "catalog/pg_trigger.h"
 #include "catalog/pg_type.h"
+#include "catalog/pg_user_mapping.h"
+#include "commands/async.h"
 #include "commands/comment.h"
 #include "commands/dbcommands.h"
 #include "commands/defrem.h"
@@ -106,6 +113,7 @@
 #include "storage/bufmgr.h"
 #include "storage/buffile.h"
 #include "storage/bufpage.h"
+#include "storage/checksum.h"
 #include "storage/ipc.h"
 #include "storage/itemptr.h"
 #include "storage/lmgr.h"
@@ -127,6 +135,7 @@
 #include "utils/elog.h"
 #include "utils/fmgrprotos.h"
 #include "utils/guc.h"
+#include "utils/inval.h"
 #include "utils/json.h"
 #include "utils/jsonapi.h"
 #include "utils/jsonb.h"
@@ -134,6 +143,7 @@
 #include "utils/memutils.h"
 #include "utils/numeric.h"
 #include "utils/palloc.h"
+#include "utils/regproc.h"
 #include "utils/rel.h"
 #include "utils/relcache.h"
 #include "utils/sampling.h"
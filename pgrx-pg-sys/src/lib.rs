@@ -196,6 +196,26 @@ pub trait PgNode: seal::Sealed {
         // this is only implemented for things known to be "Nodes"
         unsafe { display_node_impl(NonNull::from(self).cast()) }
     }
+
+    /// Format this node for humans, rather than as the raw `nodeToString()` dump used by `Display`
+    ///
+    /// The returned [`PrettyNode`] prints one field per line, indented by depth, with well-known
+    /// oids annotated with the names of the objects they refer to.
+    ///
+    /// ```rust,no_run
+    /// use pgrx_pg_sys::PgNode;
+    /// # fn planner_hook(query: &pgrx_pg_sys::Query) {
+    /// eprintln!("{}", query.pretty());
+    /// eprintln!("{}", query.pretty().resolve_oids(false));
+    /// # }
+    /// ```
+    #[inline]
+    fn pretty(&self) -> PrettyNode<'_, Self>
+    where
+        Self: Sized,
+    {
+        PrettyNode::new(self)
+    }
 }
 
 mod seal {
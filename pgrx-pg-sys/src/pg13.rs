@@ -2787,6 +2787,10 @@ pub const Anum_pg_extension_extversion: u32 = 6;
 pub const Anum_pg_extension_extconfig: u32 = 7;
 pub const Anum_pg_extension_extcondition: u32 = 8;
 pub const Natts_pg_extension: u32 = 8;
+pub const ForeignDataWrapperRelationId: Oid = Oid(2328);
+pub const ForeignServerRelationId: Oid = Oid(1417);
+pub const ForeignTableRelationId: Oid = Oid(3118);
+pub const UserMappingRelationId: Oid = Oid(1418);
 pub const OperatorRelationId: Oid = Oid(2617);
 pub const Anum_pg_operator_oid: u32 = 1;
 pub const Anum_pg_operator_oprname: u32 = 2;
@@ -9025,6 +9029,18 @@ extern "C" {
     pub fn pg_detoast_datum_slice(datum: *mut varlena, first: int32, count: int32) -> *mut varlena;
 }
 #[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn toast_raw_datum_size(value: Datum) -> Size;
+}
+#[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn toast_compress_datum(value: Datum) -> Datum;
+}
+#[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn toast_datum_size(value: Datum) -> Size;
+}
+#[pgrx_macros::pg_guard]
 extern "C" {
     pub fn pg_detoast_datum_packed(datum: *mut varlena) -> *mut varlena;
 }
@@ -22287,6 +22303,26 @@ extern "C" {
         len: usize,
     ) -> pg_crc32c;
 }
+#[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn pg_checksum_page(page: *mut ::std::os::raw::c_char, blkno: BlockNumber) -> uint16;
+}
+#[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn hash_bytes(k: *const ::std::os::raw::c_uchar, keylen: ::std::os::raw::c_int) -> uint32;
+}
+#[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn hash_bytes_extended(
+        k: *const ::std::os::raw::c_uchar,
+        keylen: ::std::os::raw::c_int,
+        seed: uint64,
+    ) -> uint64;
+}
+#[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn hash_bytes_uint32(k: uint32) -> uint32;
+}
 pub const ForkNumber_InvalidForkNumber: ForkNumber = -1;
 pub const ForkNumber_MAIN_FORKNUM: ForkNumber = 0;
 pub const ForkNumber_FSM_FORKNUM: ForkNumber = 1;
@@ -28524,6 +28560,13 @@ extern "C" {
     pub fn ProcessConfigFile(context: GucContext);
 }
 #[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn Async_Notify(
+        channel: *const ::std::os::raw::c_char,
+        payload: *const ::std::os::raw::c_char,
+    );
+}
+#[pgrx_macros::pg_guard]
 extern "C" {
     pub fn InitializeGUCOptions();
 }
@@ -32036,6 +32079,10 @@ extern "C" {
     pub fn makeRangeVarFromNameList(names: *mut List) -> *mut RangeVar;
 }
 #[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn stringToQualifiedNameList(string: *const ::std::os::raw::c_char) -> *mut List;
+}
+#[pgrx_macros::pg_guard]
 extern "C" {
     pub fn NameListToString(names: *mut List) -> *mut ::std::os::raw::c_char;
 }
@@ -60598,6 +60645,17 @@ extern "C" {
 extern "C" {
     pub fn InitCatalogCachePhase2();
 }
+pub type SyscacheCallbackFunction = ::std::option::Option<
+    unsafe extern "C" fn(arg: Datum, cacheid: ::std::os::raw::c_int, hashvalue: uint32),
+>;
+#[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn CacheRegisterSyscacheCallback(
+        cacheid: ::std::os::raw::c_int,
+        func: SyscacheCallbackFunction,
+        arg: Datum,
+    );
+}
 #[pgrx_macros::pg_guard]
 extern "C" {
     pub fn SearchSysCache(
@@ -1488,6 +1488,12 @@ pub const TYPSTORAGE_PLAIN: u8 = 112u8;
 pub const TYPSTORAGE_EXTERNAL: u8 = 101u8;
 pub const TYPSTORAGE_EXTENDED: u8 = 120u8;
 pub const TYPSTORAGE_MAIN: u8 = 109u8;
+pub const TOAST_PGLZ_COMPRESSION: u8 = 112u8;
+pub const TOAST_LZ4_COMPRESSION: u8 = 108u8;
+pub const ToastCompressionId_TOAST_PGLZ_COMPRESSION_ID: ToastCompressionId = 0;
+pub const ToastCompressionId_TOAST_LZ4_COMPRESSION_ID: ToastCompressionId = 1;
+pub const ToastCompressionId_TOAST_INVALID_COMPRESSION_ID: ToastCompressionId = 2;
+pub type ToastCompressionId = ::std::os::raw::c_uint;
 pub const BOOLOID: Oid = Oid(16);
 pub const BYTEAOID: Oid = Oid(17);
 pub const CHAROID: Oid = Oid(18);
@@ -2598,6 +2604,10 @@ pub const Anum_pg_extension_extversion: u32 = 6;
 pub const Anum_pg_extension_extconfig: u32 = 7;
 pub const Anum_pg_extension_extcondition: u32 = 8;
 pub const Natts_pg_extension: u32 = 8;
+pub const ForeignDataWrapperRelationId: Oid = Oid(2328);
+pub const ForeignServerRelationId: Oid = Oid(1417);
+pub const ForeignTableRelationId: Oid = Oid(3118);
+pub const UserMappingRelationId: Oid = Oid(1418);
 pub const ExtensionOidIndexId: u32 = 3080;
 pub const ExtensionNameIndexId: u32 = 3081;
 pub const OperatorRelationId: Oid = Oid(2617);
@@ -9128,6 +9138,22 @@ extern "C" {
     pub fn pg_detoast_datum_slice(datum: *mut varlena, first: int32, count: int32) -> *mut varlena;
 }
 #[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn toast_raw_datum_size(value: Datum) -> Size;
+}
+#[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn toast_compress_datum(value: Datum, cmethod: ::std::os::raw::c_char) -> Datum;
+}
+#[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn toast_datum_size(value: Datum) -> Size;
+}
+#[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn toast_get_compression_id(attr: *mut varlena) -> ToastCompressionId;
+}
+#[pgrx_macros::pg_guard]
 extern "C" {
     pub fn pg_detoast_datum_packed(datum: *mut varlena) -> *mut varlena;
 }
@@ -20855,6 +20881,14 @@ extern "C" {
     pub fn EvalPlanQualEnd(epqstate: *mut EPQState);
 }
 #[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn ExecAsyncRequestDone(areq: *mut AsyncRequest, result: *mut TupleTableSlot);
+}
+#[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn ExecAsyncRequestPending(areq: *mut AsyncRequest);
+}
+#[pgrx_macros::pg_guard]
 extern "C" {
     pub fn ExecInitNode(
         node: *mut Plan,
@@ -25194,6 +25228,26 @@ extern "C" {
         len: usize,
     ) -> pg_crc32c;
 }
+#[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn pg_checksum_page(page: *mut ::std::os::raw::c_char, blkno: BlockNumber) -> uint16;
+}
+#[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn hash_bytes(k: *const ::std::os::raw::c_uchar, keylen: ::std::os::raw::c_int) -> uint32;
+}
+#[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn hash_bytes_extended(
+        k: *const ::std::os::raw::c_uchar,
+        keylen: ::std::os::raw::c_int,
+        seed: uint64,
+    ) -> uint64;
+}
+#[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn hash_bytes_uint32(k: uint32) -> uint32;
+}
 #[repr(C)]
 #[derive(Debug, Default, Copy, Clone)]
 pub struct XLogRecord {
@@ -28829,6 +28883,13 @@ extern "C" {
     pub fn ProcessConfigFile(context: GucContext);
 }
 #[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn Async_Notify(
+        channel: *const ::std::os::raw::c_char,
+        payload: *const ::std::os::raw::c_char,
+    );
+}
+#[pgrx_macros::pg_guard]
 extern "C" {
     pub fn InitializeGUCOptions();
 }
@@ -32196,6 +32257,10 @@ extern "C" {
     pub fn makeRangeVarFromNameList(names: *mut List) -> *mut RangeVar;
 }
 #[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn stringToQualifiedNameList(string: *const ::std::os::raw::c_char) -> *mut List;
+}
+#[pgrx_macros::pg_guard]
 extern "C" {
     pub fn NameListToString(names: *mut List) -> *mut ::std::os::raw::c_char;
 }
@@ -63561,6 +63626,17 @@ extern "C" {
 extern "C" {
     pub fn InitCatalogCachePhase2();
 }
+pub type SyscacheCallbackFunction = ::std::option::Option<
+    unsafe extern "C" fn(arg: Datum, cacheid: ::std::os::raw::c_int, hashvalue: uint32),
+>;
+#[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn CacheRegisterSyscacheCallback(
+        cacheid: ::std::os::raw::c_int,
+        func: SyscacheCallbackFunction,
+        arg: Datum,
+    );
+}
 #[pgrx_macros::pg_guard]
 extern "C" {
     pub fn SearchSysCache(
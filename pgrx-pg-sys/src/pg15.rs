@@ -1087,6 +1087,12 @@ pub const TYPSTORAGE_PLAIN: u8 = 112u8;
 pub const TYPSTORAGE_EXTERNAL: u8 = 101u8;
 pub const TYPSTORAGE_EXTENDED: u8 = 120u8;
 pub const TYPSTORAGE_MAIN: u8 = 109u8;
+pub const TOAST_PGLZ_COMPRESSION: u8 = 112u8;
+pub const TOAST_LZ4_COMPRESSION: u8 = 108u8;
+pub const ToastCompressionId_TOAST_PGLZ_COMPRESSION_ID: ToastCompressionId = 0;
+pub const ToastCompressionId_TOAST_LZ4_COMPRESSION_ID: ToastCompressionId = 1;
+pub const ToastCompressionId_TOAST_INVALID_COMPRESSION_ID: ToastCompressionId = 2;
+pub type ToastCompressionId = ::std::os::raw::c_uint;
 pub const BOOLOID: Oid = Oid(16);
 pub const BYTEAOID: Oid = Oid(17);
 pub const CHAROID: Oid = Oid(18);
@@ -2633,6 +2639,10 @@ pub const Anum_pg_extension_extversion: u32 = 6;
 pub const Anum_pg_extension_extconfig: u32 = 7;
 pub const Anum_pg_extension_extcondition: u32 = 8;
 pub const Natts_pg_extension: u32 = 8;
+pub const ForeignDataWrapperRelationId: Oid = Oid(2328);
+pub const ForeignServerRelationId: Oid = Oid(1417);
+pub const ForeignTableRelationId: Oid = Oid(3118);
+pub const UserMappingRelationId: Oid = Oid(1418);
 pub const OperatorRelationId: Oid = Oid(2617);
 pub const OperatorOidIndexId: u32 = 2688;
 pub const OperatorNameNspIndexId: u32 = 2689;
@@ -8692,6 +8702,22 @@ extern "C" {
     pub fn pg_detoast_datum_slice(datum: *mut varlena, first: int32, count: int32) -> *mut varlena;
 }
 #[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn toast_raw_datum_size(value: Datum) -> Size;
+}
+#[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn toast_compress_datum(value: Datum, cmethod: ::std::os::raw::c_char) -> Datum;
+}
+#[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn toast_datum_size(value: Datum) -> Size;
+}
+#[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn toast_get_compression_id(attr: *mut varlena) -> ToastCompressionId;
+}
+#[pgrx_macros::pg_guard]
 extern "C" {
     pub fn pg_detoast_datum_packed(datum: *mut varlena) -> *mut varlena;
 }
@@ -20758,6 +20784,14 @@ extern "C" {
     pub fn EvalPlanQualEnd(epqstate: *mut EPQState);
 }
 #[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn ExecAsyncRequestDone(areq: *mut AsyncRequest, result: *mut TupleTableSlot);
+}
+#[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn ExecAsyncRequestPending(areq: *mut AsyncRequest);
+}
+#[pgrx_macros::pg_guard]
 extern "C" {
     pub fn ExecInitNode(
         node: *mut Plan,
@@ -24981,6 +25015,26 @@ extern "C" {
         len: usize,
     ) -> pg_crc32c;
 }
+#[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn pg_checksum_page(page: *mut ::std::os::raw::c_char, blkno: BlockNumber) -> uint16;
+}
+#[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn hash_bytes(k: *const ::std::os::raw::c_uchar, keylen: ::std::os::raw::c_int) -> uint32;
+}
+#[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn hash_bytes_extended(
+        k: *const ::std::os::raw::c_uchar,
+        keylen: ::std::os::raw::c_int,
+        seed: uint64,
+    ) -> uint64;
+}
+#[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn hash_bytes_uint32(k: uint32) -> uint32;
+}
 pub const ForkNumber_InvalidForkNumber: ForkNumber = -1;
 pub const ForkNumber_MAIN_FORKNUM: ForkNumber = 0;
 pub const ForkNumber_FSM_FORKNUM: ForkNumber = 1;
@@ -28923,6 +28977,13 @@ extern "C" {
     pub fn ProcessConfigFile(context: GucContext);
 }
 #[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn Async_Notify(
+        channel: *const ::std::os::raw::c_char,
+        payload: *const ::std::os::raw::c_char,
+    );
+}
+#[pgrx_macros::pg_guard]
 extern "C" {
     pub fn convert_GUC_name_for_parameter_acl(
         name: *const ::std::os::raw::c_char,
@@ -32330,6 +32391,10 @@ extern "C" {
     pub fn makeRangeVarFromNameList(names: *mut List) -> *mut RangeVar;
 }
 #[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn stringToQualifiedNameList(string: *const ::std::os::raw::c_char) -> *mut List;
+}
+#[pgrx_macros::pg_guard]
 extern "C" {
     pub fn NameListToString(names: *mut List) -> *mut ::std::os::raw::c_char;
 }
@@ -64432,6 +64497,17 @@ extern "C" {
 extern "C" {
     pub fn InitCatalogCachePhase2();
 }
+pub type SyscacheCallbackFunction = ::std::option::Option<
+    unsafe extern "C" fn(arg: Datum, cacheid: ::std::os::raw::c_int, hashvalue: uint32),
+>;
+#[pgrx_macros::pg_guard]
+extern "C" {
+    pub fn CacheRegisterSyscacheCallback(
+        cacheid: ::std::os::raw::c_int,
+        func: SyscacheCallbackFunction,
+        arg: Datum,
+    );
+}
 #[pgrx_macros::pg_guard]
 extern "C" {
     pub fn SearchSysCache(
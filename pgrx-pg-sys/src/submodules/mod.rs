@@ -14,6 +14,7 @@ pub mod cmp;
 pub mod errcodes;
 pub mod ffi;
 pub mod htup;
+pub mod node_fmt;
 pub mod oids;
 pub mod panic;
 pub mod pg_try;
@@ -38,6 +39,7 @@ pub use datum::Datum;
 ))]
 pub use datum::NullableDatum;
 
+pub use node_fmt::PrettyNode;
 pub use oids::*;
 pub use pg_try::*;
 pub use polyfill::*;
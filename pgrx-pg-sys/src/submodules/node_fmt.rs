@@ -0,0 +1,320 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Human-readable formatting for Postgres `Node`s
+//!
+//! The [`Display`] impl on node types prints the raw `nodeToString()` dump, which is compact
+//! but hard to read.  [`PrettyNode`], created by [`PgNode::pretty()`], prints the same dump
+//! with one field per line, indented by depth, and annotates well-known relation, type,
+//! function, and operator oids with their names.
+//!
+//! ```rust,no_run
+//! use pgrx_pg_sys::PgNode;
+//! # fn log_query(query: &pgrx_pg_sys::Query) {
+//! eprintln!("{}", query.pretty());
+//! # }
+//! ```
+use crate as pg_sys;
+use crate::PgNode;
+use std::ffi::CStr;
+use std::fmt::{self, Display, Formatter};
+
+/// Indentation used per level of nesting, matching Postgres' own `debug_pretty_print` output
+const INDENT: &str = "   ";
+
+/// A formatting wrapper around a [`PgNode`], created by [`PgNode::pretty()`]
+pub struct PrettyNode<'a, N> {
+    node: &'a N,
+    resolve_oids: bool,
+}
+
+impl<'a, N: PgNode> PrettyNode<'a, N> {
+    pub(crate) fn new(node: &'a N) -> Self {
+        Self { node, resolve_oids: true }
+    }
+
+    /// Should relation, type, function, and operator oids be annotated with their names?
+    ///
+    /// This is the default.  Resolving names requires catalog access, so it's silently skipped
+    /// outside of a transaction.
+    pub fn resolve_oids(mut self, resolve_oids: bool) -> Self {
+        self.resolve_oids = resolve_oids;
+        self
+    }
+}
+
+impl<N: PgNode> Display for PrettyNode<'_, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let dump = self.node.display_node();
+        // SAFETY: IsTransactionState() only inspects the backend's transaction state
+        let resolve_oids = self.resolve_oids && unsafe { pg_sys::IsTransactionState() };
+        if resolve_oids {
+            f.write_str(&format_dump(&dump, &|kind, oid| unsafe {
+                // SAFETY: we're in a transaction, so the catalogs may be consulted
+                resolve_oid(kind, oid)
+            }))
+        } else {
+            f.write_str(&format_dump(&dump, &|_, _| None))
+        }
+    }
+}
+
+/// The kinds of catalog objects whose oids we know how to name
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum OidKind {
+    Relation,
+    Type,
+    Function,
+    Operator,
+}
+
+impl OidKind {
+    /// Determine what kind of oid, if any, a `nodeToString()` field holds
+    fn of_field(field: &str) -> Option<OidKind> {
+        match field {
+            "relid" | "indexid" => Some(OidKind::Relation),
+            "vartype" | "consttype" | "paramtype" | "resulttype" | "funcresulttype"
+            | "opresulttype" | "aggtype" | "wintype" | "casetype" | "coalescetype"
+            | "minmaxtype" | "row_typeid" | "array_typeid" | "element_typeid"
+            | "refcontainertype" | "refelemtype" => Some(OidKind::Type),
+            "funcid" | "aggfnoid" | "winfnoid" | "opfuncid" => Some(OidKind::Function),
+            "opno" => Some(OidKind::Operator),
+            _ => None,
+        }
+    }
+}
+
+/// Look up the name of a catalog object
+///
+/// # Safety
+///
+/// Must be called within a transaction
+unsafe fn resolve_oid(kind: OidKind, oid: u32) -> Option<String> {
+    let oid = pg_sys::Oid::from_u32_unchecked(oid);
+    let name = match kind {
+        OidKind::Relation => pg_sys::get_rel_name(oid),
+        OidKind::Type => pg_sys::format_type_extended(
+            oid,
+            -1,
+            pg_sys::FORMAT_TYPE_ALLOW_INVALID as pg_sys::bits16,
+        ),
+        OidKind::Function => pg_sys::get_func_name(oid),
+        OidKind::Operator => pg_sys::get_opname(oid),
+    };
+    if name.is_null() {
+        return None;
+    }
+
+    let resolved = CStr::from_ptr(name).to_string_lossy().into_owned();
+    pg_sys::pfree(name.cast());
+    // `format_type_extended()` names types it can't find "???"
+    (resolved != "???").then_some(resolved)
+}
+
+/// What kind of `nodeToString()` structure we're inside of
+enum Frame {
+    /// A `{NODE :field value ...}`
+    Node,
+    /// A `(...)`, which is laid out one element per line if it contains nodes
+    List { has_nodes: bool },
+}
+
+/// Lay out a `nodeToString()` dump with one field per line, annotating oids named by `resolve`
+fn format_dump(dump: &str, resolve: &dyn Fn(OidKind, u32) -> Option<String>) -> String {
+    fn newline(out: &mut String, depth: usize) {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        for _ in 0..depth {
+            out.push_str(INDENT);
+        }
+    }
+
+    fn push_word(out: &mut String, word: &str) {
+        if !(out.is_empty() || out.ends_with(['(', ' ', '\n'])) {
+            out.push(' ');
+        }
+        out.push_str(word);
+    }
+
+    let mut out = String::with_capacity(dump.len() * 2);
+    let mut stack = Vec::new();
+    let mut field = None;
+    let mut tokens = Tokens(dump);
+    while let Some(token) = tokens.next() {
+        match token {
+            "{" => {
+                match stack.last_mut() {
+                    // a node that's the value of a field stays on the field's line
+                    _ if field.take().is_some() => out.push(' '),
+                    Some(Frame::List { has_nodes }) => {
+                        *has_nodes = true;
+                        newline(&mut out, stack.len());
+                    }
+                    _ => newline(&mut out, stack.len()),
+                }
+                out.push('{');
+                if let Some(name) = tokens.next() {
+                    out.push_str(name);
+                }
+                stack.push(Frame::Node);
+            }
+            "}" => {
+                stack.pop();
+                newline(&mut out, stack.len());
+                out.push('}');
+            }
+            "(" => {
+                push_word(&mut out, "(");
+                field = None;
+                stack.push(Frame::List { has_nodes: false });
+            }
+            ")" => {
+                if let Some(Frame::List { has_nodes: true }) = stack.pop() {
+                    newline(&mut out, stack.len());
+                }
+                out.push(')');
+            }
+            _ if token.starts_with(':') && matches!(stack.last(), Some(Frame::Node)) => {
+                newline(&mut out, stack.len());
+                out.push_str(token);
+                field = Some(&token[1..]);
+            }
+            _ => {
+                push_word(&mut out, token);
+                let kind = field.take().and_then(OidKind::of_field);
+                let name = match (kind, token.parse::<u32>()) {
+                    (Some(kind), Ok(oid)) if oid != 0 => resolve(kind, oid),
+                    _ => None,
+                };
+                if let Some(name) = name {
+                    out.push_str(" /* ");
+                    out.push_str(&name);
+                    out.push_str(" */");
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Splits a `nodeToString()` dump into tokens the same way Postgres' `pg_strtok()` does
+struct Tokens<'a>(&'a str);
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let s = self.0.trim_start_matches([' ', '\n', '\t']);
+        let mut chars = s.char_indices();
+        let end = match chars.next()? {
+            (_, '(' | ')' | '{' | '}') => 1,
+            (_, first) => {
+                let mut escaped = first == '\\';
+                chars
+                    .find(|&(_, c)| {
+                        let is_end =
+                            !escaped && matches!(c, ' ' | '\n' | '\t' | '(' | ')' | '{' | '}');
+                        escaped = !escaped && c == '\\';
+                        is_end
+                    })
+                    .map(|(i, _)| i)
+                    .unwrap_or(s.len())
+            }
+        };
+        let (token, rest) = s.split_at(end);
+        self.0 = rest;
+        Some(token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(kind: OidKind, oid: u32) -> Option<String> {
+        match (kind, oid) {
+            (OidKind::Type, 23) => Some("integer".into()),
+            (OidKind::Relation, 16384) => Some("things".into()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn tokens_follow_pg_strtok() {
+        let tokens = Tokens(r#"{CONST :constvalue 4 [ 42 0 ] :name a\ b\(c\) :list (i 1 2)}"#)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            [
+                "{",
+                "CONST",
+                ":constvalue",
+                "4",
+                "[",
+                "42",
+                "0",
+                "]",
+                ":name",
+                r"a\ b\(c\)",
+                ":list",
+                "(",
+                "i",
+                "1",
+                "2",
+                ")",
+                "}"
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_nodes_are_indented() {
+        let dump = "{QUERY :commandType 1 :rtable ({RANGETBLENTRY :relid 16384 :relkind r}) \
+                    :jointree {FROMEXPR :fromlist ({RANGETBLREF :rtindex 1}) :quals <>} \
+                    :targetList <> :rowMarks (i 1 2)}";
+        let expected = "\
+{QUERY
+   :commandType 1
+   :rtable (
+      {RANGETBLENTRY
+         :relid 16384 /* things */
+         :relkind r
+      }
+   )
+   :jointree {FROMEXPR
+      :fromlist (
+         {RANGETBLREF
+            :rtindex 1
+         }
+      )
+      :quals <>
+   }
+   :targetList <>
+   :rowMarks (i 1 2)
+}";
+        assert_eq!(format_dump(dump, &resolve), expected);
+    }
+
+    #[test]
+    fn only_known_oid_fields_are_resolved() {
+        let dump = "{CONST :consttype 23 :consttypmod -1 :constcollid 23 :constlen 4 \
+                    :constvalue 4 [ 42 0 0 0 0 0 0 0 ]}";
+        let expected = "\
+{CONST
+   :consttype 23 /* integer */
+   :consttypmod -1
+   :constcollid 23
+   :constlen 4
+   :constvalue 4 [ 42 0 0 0 0 0 0 0 ]
+}";
+        assert_eq!(format_dump(dump, &resolve), expected);
+        assert_eq!(format_dump(dump, &|_, _| None), expected.replace(" /* integer */", ""));
+    }
+}
@@ -18,6 +18,7 @@ use std::hint::unreachable_unchecked;
 use std::panic::{
     catch_unwind, panic_any, resume_unwind, Location, PanicInfo, RefUnwindSafe, UnwindSafe,
 };
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 
 use crate::elog::PgLogLevel;
 use crate::errcodes::PgSqlErrorCode;
@@ -304,6 +305,69 @@ fn take_panic_location() -> ErrorReportLocation {
     PANIC_LOCATION.with(|p| p.take().unwrap_or_default())
 }
 
+/// The level a Rust `panic!()` is raised at, once it reaches a `#[pg_guard]` boundary and is
+/// reported to Postgres.  The default is [`PanicPolicy::Error`].
+///
+/// `pgrx::error!()` and `ereport!()` keep the level they were raised with.  A panic caught with
+/// [`PgTryBuilder`](crate::pg_try::PgTryBuilder) is only affected if it's rethrown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum PanicPolicy {
+    /// Abort the current transaction, leaving the backend running
+    #[default]
+    Error = 0,
+    /// Terminate the backend
+    Fatal = 1,
+    /// Take down the whole cluster, which then goes through crash recovery
+    Panic = 2,
+}
+
+impl PanicPolicy {
+    /// The policy's log level
+    pub fn level(&self) -> PgLogLevel {
+        match self {
+            PanicPolicy::Error => PgLogLevel::ERROR,
+            PanicPolicy::Fatal => PgLogLevel::FATAL,
+            PanicPolicy::Panic => PgLogLevel::PANIC,
+        }
+    }
+}
+
+static PANIC_POLICY: AtomicU8 = AtomicU8::new(PanicPolicy::Error as u8);
+static ABORT_ON_DOUBLE_PANIC: AtomicBool = AtomicBool::new(false);
+
+/// Set the level Rust panics are raised at, for the rest of this process' life.  Calling this from
+/// `_PG_init()` sets it for every backend that loads the extension.
+pub fn set_panic_policy(policy: PanicPolicy) {
+    PANIC_POLICY.store(policy as u8, Ordering::Relaxed)
+}
+
+/// The current [`PanicPolicy`]
+pub fn panic_policy() -> PanicPolicy {
+    match PANIC_POLICY.load(Ordering::Relaxed) {
+        1 => PanicPolicy::Fatal,
+        2 => PanicPolicy::Panic,
+        _ => PanicPolicy::Error,
+    }
+}
+
+/// Abort the process if a Rust panic happens while the thread is already unwinding from another,
+/// such as a panic in a `Drop` impl.
+///
+/// Rust only aborts once the second panic escapes the destructor, so by default it can still be
+/// caught by a `#[pg_guard]` function the destructor calls, and raised as an ERROR midway through
+/// the first unwind.  With this set, that `#[pg_guard]` function raises it as a PANIC instead, so
+/// Postgres logs it and aborts, and the postmaster treats it as a crash.  Postgres ERRORs raised
+/// there count as panics too.
+pub fn set_abort_on_double_panic(abort: bool) {
+    ABORT_ON_DOUBLE_PANIC.store(abort, Ordering::Relaxed)
+}
+
+/// Is [`set_abort_on_double_panic`] enabled?
+pub fn abort_on_double_panic() -> bool {
+    ABORT_ON_DOUBLE_PANIC.load(Ordering::Relaxed)
+}
+
 pub fn register_pg_guard_panic_hook() {
     std::panic::set_hook(Box::new(|info| {
         PANIC_LOCATION.with(|thread_local| {
@@ -405,15 +469,32 @@ fn run_guarded<F, R: Copy>(f: F) -> GuardAction<R>
 where
     F: FnOnce() -> R + UnwindSafe + RefUnwindSafe,
 {
+    // if we're already unwinding, we were called from a destructor
+    let unwinding = std::thread::panicking();
     match catch_unwind(f) {
         Ok(v) => GuardAction::Return(v),
+        Err(e) if unwinding && abort_on_double_panic() => {
+            let mut ereport = match downcast_panic_payload(e) {
+                CaughtError::PostgresError(ereport)
+                | CaughtError::ErrorReport(ereport)
+                | CaughtError::RustPanic { ereport, .. } => ereport,
+            };
+            ereport.level = PgLogLevel::PANIC;
+            ereport.inner.message =
+                format!("panicked while unwinding from another panic: {}", ereport.inner.message);
+            // Postgres aborts the process once the PANIC is logged
+            do_ereport(ereport);
+            std::process::abort();
+        }
         Err(e) => match downcast_panic_payload(e) {
             CaughtError::PostgresError(_) => {
                 // Return to the caller to rethrow -- we can't do it here
                 // since we this function's has non-POF frames.
                 GuardAction::ReThrow
             }
-            CaughtError::ErrorReport(ereport) | CaughtError::RustPanic { ereport, .. } => {
+            CaughtError::ErrorReport(ereport) => GuardAction::Report(ereport),
+            CaughtError::RustPanic { mut ereport, .. } => {
+                ereport.level = ereport.level.max(panic_policy().level());
                 GuardAction::Report(ereport)
             }
         },
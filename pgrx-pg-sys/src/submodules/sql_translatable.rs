@@ -81,6 +81,24 @@ unsafe impl SqlTranslatable for crate::Point {
     }
 }
 
+unsafe impl SqlTranslatable for crate::LSEG {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        Ok(SqlMapping::literal("lseg"))
+    }
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        Ok(Returns::One(SqlMapping::literal("lseg")))
+    }
+}
+
+unsafe impl SqlTranslatable for crate::CIRCLE {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        Ok(SqlMapping::literal("circle"))
+    }
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        Ok(Returns::One(SqlMapping::literal("circle")))
+    }
+}
+
 unsafe impl SqlTranslatable for crate::ItemPointerData {
     fn argument_sql() -> Result<SqlMapping, ArgumentError> {
         Ok(SqlMapping::literal("tid"))
@@ -9,6 +9,9 @@
 //LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
 //! General utility functions
 use crate as pg_sys;
+use core::cmp::Ordering;
+use core::ffi::CStr;
+use core::hash::{Hash, Hasher};
 
 /// Converts a `pg_sys::NameData` struct into a `&str`.  
 ///
@@ -16,5 +19,149 @@ use crate as pg_sys;
 /// of the provided `pg_sys::NameData`
 #[inline]
 pub fn name_data_to_str(name_data: &pg_sys::NameData) -> &str {
-    unsafe { core::ffi::CStr::from_ptr(name_data.data.as_ptr()) }.to_str().unwrap()
+    name_data.to_str().unwrap()
+}
+
+/// The error returned when a `&str` cannot be represented as a `pg_sys::NameData` because
+/// it contains an interior NUL byte at the contained position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NameDataNulError(pub usize);
+
+impl core::fmt::Display for NameDataNulError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "NameData cannot contain a NUL byte (found at position {})", self.0)
+    }
+}
+
+impl std::error::Error for NameDataNulError {}
+
+impl pg_sys::NameData {
+    /// The maximum number of bytes a `NameData` can hold, not including the trailing NUL
+    pub const MAX_LEN: usize = pg_sys::NAMEDATALEN as usize - 1;
+
+    /// Build a `NameData` from a `&str`, truncating it the same way Postgres truncates
+    /// identifiers.
+    ///
+    /// Postgres silently truncates names that are longer than `NAMEDATALEN - 1` bytes, taking
+    /// care to never split a multibyte character.  As a Rust `&str` is always UTF-8, this
+    /// truncates on the last character boundary that fits.
+    ///
+    /// Returns an error if `s` contains an interior NUL byte.
+    pub fn try_from_str(s: &str) -> Result<Self, NameDataNulError> {
+        if let Some(pos) = s.bytes().position(|b| b == 0) {
+            return Err(NameDataNulError(pos));
+        }
+
+        let s = Self::truncate(s);
+        let mut name = pg_sys::NameData::default();
+        for (dst, src) in name.data.iter_mut().zip(s.bytes()) {
+            *dst = src as _;
+        }
+        Ok(name)
+    }
+
+    /// Returns the prefix of `s` that Postgres would keep when storing it as a `name`
+    #[inline]
+    pub fn truncate(s: &str) -> &str {
+        if s.len() <= Self::MAX_LEN {
+            return s;
+        }
+        let mut end = Self::MAX_LEN;
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        &s[..end]
+    }
+
+    /// Returns this `NameData` as a `&CStr`, not including any trailing padding
+    #[inline]
+    pub fn as_c_str(&self) -> &CStr {
+        // SAFETY: a NameData is always NUL-padded, and `try_from_str()` never fills the final byte
+        unsafe { CStr::from_ptr(self.data.as_ptr()) }
+    }
+
+    /// Returns this `NameData` as a `&[u8]`, not including the trailing NUL
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        self.as_c_str().to_bytes()
+    }
+
+    /// Returns this `NameData` as a `&str`, or an error if it isn't valid UTF-8.
+    ///
+    /// The bytes are in the database's encoding, which needn't be UTF-8.  `pgrx::name_to_str()`
+    /// transcodes them instead.
+    #[inline]
+    pub fn to_str(&self) -> Result<&str, core::str::Utf8Error> {
+        self.as_c_str().to_str()
+    }
+}
+
+impl core::str::FromStr for pg_sys::NameData {
+    type Err = NameDataNulError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        pg_sys::NameData::try_from_str(s)
+    }
+}
+
+impl TryFrom<&str> for pg_sys::NameData {
+    type Error = NameDataNulError;
+
+    #[inline]
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        pg_sys::NameData::try_from_str(s)
+    }
+}
+
+impl core::fmt::Display for pg_sys::NameData {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.as_c_str().to_string_lossy())
+    }
+}
+
+/// `NameData` compares like Postgres' `namecmp()` under the "C" collation:  bytewise, up to the
+/// first NUL
+impl PartialEq for pg_sys::NameData {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl Eq for pg_sys::NameData {}
+
+impl PartialEq<str> for pg_sys::NameData {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl PartialEq<&str> for pg_sys::NameData {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl PartialOrd for pg_sys::NameData {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for pg_sys::NameData {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_bytes().cmp(other.as_bytes())
+    }
+}
+
+impl Hash for pg_sys::NameData {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state)
+    }
 }
@@ -108,9 +108,19 @@ pub struct PgAggregate {
 }
 
 impl PgAggregate {
-    pub fn new(mut item_impl: ItemImpl) -> Result<CodeEnrichment<Self>, syn::Error> {
-        let to_sql_config =
-            ToSqlConfig::from_attributes(item_impl.attrs.as_slice())?.unwrap_or_default();
+    pub fn new(item_impl: ItemImpl) -> Result<CodeEnrichment<Self>, syn::Error> {
+        Self::new_with_serial_state(item_impl, None)
+    }
+
+    /// As [`PgAggregate::new`], also generating the `SERIALFUNC` and `DESERIALFUNC` from the
+    /// `AggregateSerial` impl of `serial_state`, the type kept in the aggregate's `Internal` state
+    pub fn new_with_serial_state(
+        mut item_impl: ItemImpl,
+        serial_state: Option<syn::Type>,
+    ) -> Result<CodeEnrichment<Self>, syn::Error> {
+        let to_sql_config = ToSqlConfig::from_attributes(item_impl.attrs.as_slice())?
+            .unwrap_or_default()
+            .with_cfg(&item_impl.attrs);
         let target_path = get_target_path(&item_impl)?;
         let target_ident = get_target_ident(&target_path)?;
 
@@ -380,55 +390,122 @@ impl PgAggregate {
         };
 
         let fn_serial = get_impl_func_by_name(&item_impl_snapshot, "serial");
-        let fn_serial_name = if let Some(found) = fn_serial {
-            let fn_name =
-                Ident::new(&format!("{}_serial", snake_case_target_ident), found.sig.ident.span());
-            let pg_extern_attr = pg_extern_attr(found);
+        let fn_deserial = get_impl_func_by_name(&item_impl_snapshot, "deserial");
+        let (fn_serial_name, fn_deserial_name) = if let Some(serial_state) = serial_state {
+            // the generated functions read the state out of an `Internal`
+            let state_is_internal = match &type_state_without_self {
+                syn::Type::Path(ty) => {
+                    ty.path.segments.last().map_or(false, |segment| segment.ident == "Internal")
+                }
+                _ => false,
+            };
+            if !state_is_internal {
+                return Err(syn::Error::new(
+                    serial_state.span(),
+                    "`#[pg_aggregate(serial = ..)]` requires the aggregate's `State` to be `Internal`.",
+                ));
+            }
+            if let Some(found) = fn_serial.or(fn_deserial) {
+                return Err(syn::Error::new(
+                    found.sig.ident.span(),
+                    "`#[pg_aggregate(serial = ..)]` generates `serial` and `deserial`, so they must not be implemented too.",
+                ));
+            }
+            let fn_serial_name =
+                Ident::new(&format!("{}_serial", snake_case_target_ident), serial_state.span());
+            let fn_deserial_name =
+                Ident::new(&format!("{}_deserial", snake_case_target_ident), serial_state.span());
             pg_externs.push(parse_quote! {
                 #[allow(non_snake_case, clippy::too_many_arguments)]
-                #pg_extern_attr
-                fn #fn_name(this: #type_state_without_self, fcinfo: ::pgrx::pg_sys::FunctionCallInfo) -> Vec<u8> {
+                #[::pgrx::pg_extern(immutable, parallel_safe)]
+                fn #fn_serial_name(this: ::pgrx::datum::Internal, fcinfo: ::pgrx::pg_sys::FunctionCallInfo) -> Vec<u8> {
                     <#target_path as ::pgrx::aggregate::Aggregate>::in_memory_context(
                         fcinfo,
-                        move |_context| <#target_path as ::pgrx::aggregate::Aggregate>::serial(this, fcinfo)
+                        move |_context| {
+                            // SAFETY:  the aggregate keeps a `#serial_state` in its `Internal` state
+                            let state = unsafe { this.get::<#serial_state>() }
+                                .expect("aggregate state is not initialized");
+                            <#serial_state as ::pgrx::aggregate::AggregateSerial>::serialize_state(state)
+                        }
                     )
                 }
             });
-            Some(fn_name)
-        } else {
-            item_impl.items.push(parse_quote! {
-                fn serial(current: #type_state_without_self, _fcinfo: ::pgrx::pg_sys::FunctionCallInfo) -> Vec<u8> {
-                    unimplemented!("Call to serial on an aggregate which does not support it.")
-                }
-            });
-            None
-        };
-
-        let fn_deserial = get_impl_func_by_name(&item_impl_snapshot, "deserial");
-        let fn_deserial_name = if let Some(found) = fn_deserial {
-            let fn_name = Ident::new(
-                &format!("{}_deserial", snake_case_target_ident),
-                found.sig.ident.span(),
-            );
-            let pg_extern_attr = pg_extern_attr(found);
             pg_externs.push(parse_quote! {
                 #[allow(non_snake_case, clippy::too_many_arguments)]
-                #pg_extern_attr
-                fn #fn_name(this: #type_state_without_self, buf: Vec<u8>, internal: ::pgrx::pgbox::PgBox<#type_state_without_self>, fcinfo: ::pgrx::pg_sys::FunctionCallInfo) -> ::pgrx::pgbox::PgBox<#type_state_without_self> {
+                #[::pgrx::pg_extern(immutable, parallel_safe)]
+                fn #fn_deserial_name(bytes: &[u8], _internal: ::pgrx::datum::Internal, fcinfo: ::pgrx::pg_sys::FunctionCallInfo) -> ::pgrx::datum::Internal {
                     <#target_path as ::pgrx::aggregate::Aggregate>::in_memory_context(
                         fcinfo,
-                        move |_context| <#target_path as ::pgrx::aggregate::Aggregate>::deserial(this, buf, internal, fcinfo)
+                        move |_context| ::pgrx::datum::Internal::new(
+                            <#serial_state as ::pgrx::aggregate::AggregateSerial>::deserialize_state(bytes)
+                        )
                     )
                 }
             });
-            Some(fn_name)
-        } else {
+            item_impl.items.push(parse_quote! {
+                fn serial(current: #type_state_without_self, _fcinfo: ::pgrx::pg_sys::FunctionCallInfo) -> Vec<u8> {
+                    unimplemented!("Call to serial on an aggregate which generates it from `AggregateSerial`.")
+                }
+            });
             item_impl.items.push(parse_quote! {
                 fn deserial(current: #type_state_without_self, _buf: Vec<u8>, _internal: ::pgrx::pgbox::PgBox<#type_state_without_self>, _fcinfo: ::pgrx::pg_sys::FunctionCallInfo) -> ::pgrx::pgbox::PgBox<#type_state_without_self> {
-                    unimplemented!("Call to deserial on an aggregate which does not support it.")
+                    unimplemented!("Call to deserial on an aggregate which generates it from `AggregateSerial`.")
                 }
             });
-            None
+            (Some(fn_serial_name), Some(fn_deserial_name))
+        } else {
+            let fn_serial_name = if let Some(found) = fn_serial {
+                let fn_name = Ident::new(
+                    &format!("{}_serial", snake_case_target_ident),
+                    found.sig.ident.span(),
+                );
+                let pg_extern_attr = pg_extern_attr(found);
+                pg_externs.push(parse_quote! {
+                    #[allow(non_snake_case, clippy::too_many_arguments)]
+                    #pg_extern_attr
+                    fn #fn_name(this: #type_state_without_self, fcinfo: ::pgrx::pg_sys::FunctionCallInfo) -> Vec<u8> {
+                        <#target_path as ::pgrx::aggregate::Aggregate>::in_memory_context(
+                            fcinfo,
+                            move |_context| <#target_path as ::pgrx::aggregate::Aggregate>::serial(this, fcinfo)
+                        )
+                    }
+                });
+                Some(fn_name)
+            } else {
+                item_impl.items.push(parse_quote! {
+                    fn serial(current: #type_state_without_self, _fcinfo: ::pgrx::pg_sys::FunctionCallInfo) -> Vec<u8> {
+                        unimplemented!("Call to serial on an aggregate which does not support it.")
+                    }
+                });
+                None
+            };
+
+            let fn_deserial_name = if let Some(found) = fn_deserial {
+                let fn_name = Ident::new(
+                    &format!("{}_deserial", snake_case_target_ident),
+                    found.sig.ident.span(),
+                );
+                let pg_extern_attr = pg_extern_attr(found);
+                pg_externs.push(parse_quote! {
+                    #[allow(non_snake_case, clippy::too_many_arguments)]
+                    #pg_extern_attr
+                    fn #fn_name(this: #type_state_without_self, buf: Vec<u8>, internal: ::pgrx::pgbox::PgBox<#type_state_without_self>, fcinfo: ::pgrx::pg_sys::FunctionCallInfo) -> ::pgrx::pgbox::PgBox<#type_state_without_self> {
+                        <#target_path as ::pgrx::aggregate::Aggregate>::in_memory_context(
+                            fcinfo,
+                            move |_context| <#target_path as ::pgrx::aggregate::Aggregate>::deserial(this, buf, internal, fcinfo)
+                        )
+                    }
+                });
+                Some(fn_name)
+            } else {
+                item_impl.items.push(parse_quote! {
+                    fn deserial(current: #type_state_without_self, _buf: Vec<u8>, _internal: ::pgrx::pgbox::PgBox<#type_state_without_self>, _fcinfo: ::pgrx::pg_sys::FunctionCallInfo) -> ::pgrx::pgbox::PgBox<#type_state_without_self> {
+                        unimplemented!("Call to deserial on an aggregate which does not support it.")
+                    }
+                });
+                None
+            };
+            (fn_serial_name, fn_deserial_name)
         };
 
         let fn_moving_state = get_impl_func_by_name(&item_impl_snapshot, "moving_state");
@@ -752,12 +829,16 @@ fn pg_extern_attr(item: &ImplItemMethod) -> syn::Attribute {
             _ => (),
         };
     }
-    match found {
-        Some(args) => parse_quote! {
-            #[::pgrx::pg_extern #args]
-        },
+    // `Aggregate::Args` can't borrow for only as long as the call
+    match found.and_then(|args| syn::parse2::<proc_macro2::Group>(args).ok()) {
+        Some(args) => {
+            let args = args.stream();
+            parse_quote! {
+                #[::pgrx::pg_extern(static_args, #args)]
+            }
+        }
         None => parse_quote! {
-            #[::pgrx::pg_extern]
+            #[::pgrx::pg_extern(static_args)]
         },
     }
 }
@@ -124,13 +124,20 @@ impl TryFrom<&str> for ControlFile {
 }
 
 impl ToSql for ControlFile {
-    fn to_sql(&self, _context: &super::PgrxSql) -> eyre::Result<String> {
+    fn to_sql(&self, context: &super::PgrxSql) -> eyre::Result<String> {
+        let features = if context.features.is_empty() {
+            String::from("(none)")
+        } else {
+            context.features.join(", ")
+        };
         let sql = format!(
             "\
             /* \n\
             This file is auto generated by pgrx.\n\
             \n\
             The ordering of items is not stable, it is driven by a dependency graph.\n\
+            \n\
+            Generated with features: {features}\n\
             */\
         "
         );
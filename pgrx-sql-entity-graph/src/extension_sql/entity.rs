@@ -37,12 +37,66 @@ pub struct ExtensionSqlEntity {
     pub finalize: bool,
     pub requires: Vec<PositioningRef>,
     pub creates: Vec<SqlDeclaredEntity>,
+    /// Whether `@CARGO_VERSION@`, `@feature:name@`, and `@extschema@` should be substituted
+    pub template: bool,
+    /// The version of the crate which declared this SQL
+    pub version: &'static str,
+    /// The features named in `features = [...]`, and whether each was enabled
+    pub features: Vec<(&'static str, bool)>,
 }
 
 impl ExtensionSqlEntity {
     pub fn has_sql_declared_entity(&self, identifier: &SqlDeclared) -> Option<&SqlDeclaredEntity> {
         self.creates.iter().find(|created| created.has_sql_declared_entity(identifier))
     }
+
+    /// The SQL with any template placeholders substituted.
+    ///
+    /// * `@CARGO_VERSION@` becomes the crate version.
+    /// * `@feature:name@` becomes `true` or `false`, for features listed in `features = [...]`.
+    /// * `@extschema@` is left for Postgres to substitute when the extension is not relocatable.
+    ///   Postgres doesn't substitute it for relocatable extensions, so `@extschema@.` qualifications
+    ///   are removed instead, leaving objects to be created in whatever schema the extension is
+    ///   installed into.
+    pub fn render(&self, relocatable: bool) -> eyre::Result<String> {
+        if !self.template {
+            return Ok(self.sql.to_string());
+        }
+
+        let mut sql = self.sql.replace("@CARGO_VERSION@", self.version);
+
+        const FEATURE: &str = "@feature:";
+        while let Some(start) = sql.find(FEATURE) {
+            let name_start = start + FEATURE.len();
+            let name_end =
+                sql[name_start..].find('@').map(|end| name_start + end).ok_or_else(|| {
+                    eyre::eyre!("Unterminated `{FEATURE}` placeholder in `{}`", self.name)
+                })?;
+            let name = &sql[name_start..name_end];
+            let enabled = self
+                .features
+                .iter()
+                .find_map(|(feature, enabled)| (*feature == name).then_some(*enabled))
+                .ok_or_else(|| {
+                    eyre::eyre!(
+                        "Feature `{name}` is used in `{}` but not listed in its `features = [...]`",
+                        self.name
+                    )
+                })?;
+            sql.replace_range(start..=name_end, if enabled { "true" } else { "false" });
+        }
+
+        if relocatable {
+            sql = sql.replace("@extschema@.", "");
+            if sql.contains("@extschema@") {
+                return Err(eyre::eyre!(
+                    "`{}` uses `@extschema@` outside of a qualified name, which is not possible in a relocatable extension",
+                    self.name
+                ));
+            }
+        }
+        Ok(sql)
+    }
 }
 
 impl From<ExtensionSqlEntity> for SqlGraphEntity {
@@ -69,7 +123,7 @@ impl SqlGraphIdentifier for ExtensionSqlEntity {
 }
 
 impl ToSql for ExtensionSqlEntity {
-    fn to_sql(&self, _context: &PgrxSql) -> eyre::Result<String> {
+    fn to_sql(&self, context: &PgrxSql) -> eyre::Result<String> {
         let sql = format!(
             "\n\
                 -- {file}:{line}\n\
@@ -113,7 +167,7 @@ impl ToSql for ExtensionSqlEntity {
                 "".to_string()
             },
             finalize = if self.finalize { "-- finalize\n" } else { "" },
-            sql = self.sql,
+            sql = self.render(context.control.relocatable)?,
         );
         Ok(sql)
     }
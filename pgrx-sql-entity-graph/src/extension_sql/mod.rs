@@ -63,6 +63,8 @@ impl ToEntityGraphTokens for ExtensionSqlFile {
         let mut name = None;
         let mut bootstrap = false;
         let mut finalize = false;
+        let mut template = false;
+        let mut features = vec![];
         let mut requires = vec![];
         let mut creates = vec![];
         for attr in &self.attrs {
@@ -82,6 +84,12 @@ impl ToEntityGraphTokens for ExtensionSqlFile {
                 ExtensionSqlAttribute::Name(found_name) => {
                     name = Some(found_name.value());
                 }
+                ExtensionSqlAttribute::Template => {
+                    template = true;
+                }
+                ExtensionSqlAttribute::Features(items) => {
+                    features.extend(items.iter().cloned());
+                }
             }
         }
         let name = name.unwrap_or(
@@ -94,6 +102,8 @@ impl ToEntityGraphTokens for ExtensionSqlFile {
         );
         let requires_iter = requires.iter();
         let creates_iter = creates.iter();
+        let features_iter =
+            features.iter().map(|feature| quote! { (#feature, cfg!(feature = #feature)) });
         let sql_graph_entity_fn_name =
             syn::Ident::new(&format!("__pgrx_internals_sql_{}", name.clone()), Span::call_site());
         quote! {
@@ -115,6 +125,9 @@ impl ToEntityGraphTokens for ExtensionSqlFile {
                     finalize: #finalize,
                     requires: vec![#(#requires_iter),*],
                     creates: vec![#(#creates_iter),*],
+                    template: #template,
+                    version: env!("CARGO_PKG_VERSION"),
+                    features: vec![#(#features_iter),*],
                 };
                 ::pgrx::pgrx_sql_entity_graph::SqlGraphEntity::CustomSql(submission)
             }
@@ -169,6 +182,8 @@ impl ToEntityGraphTokens for ExtensionSql {
         let sql = &self.sql;
         let mut bootstrap = false;
         let mut finalize = false;
+        let mut template = false;
+        let mut features = vec![];
         let mut creates = vec![];
         let mut requires = vec![];
         for attr in &self.attrs {
@@ -186,10 +201,18 @@ impl ToEntityGraphTokens for ExtensionSql {
                     finalize = true;
                 }
                 ExtensionSqlAttribute::Name(_found_name) => (), // Already done
+                ExtensionSqlAttribute::Template => {
+                    template = true;
+                }
+                ExtensionSqlAttribute::Features(items) => {
+                    features.extend(items.iter().cloned());
+                }
             }
         }
         let requires_iter = requires.iter();
         let creates_iter = creates.iter();
+        let features_iter =
+            features.iter().map(|feature| quote! { (#feature, cfg!(feature = #feature)) });
         let name = &self.name;
 
         let sql_graph_entity_fn_name =
@@ -212,6 +235,9 @@ impl ToEntityGraphTokens for ExtensionSql {
                     finalize: #finalize,
                     requires: vec![#(#requires_iter),*],
                     creates: vec![#(#creates_iter),*],
+                    template: #template,
+                    version: env!("CARGO_PKG_VERSION"),
+                    features: vec![#(#features_iter),*],
                 };
                 ::pgrx::pgrx_sql_entity_graph::SqlGraphEntity::CustomSql(submission)
             }
@@ -254,6 +280,8 @@ pub enum ExtensionSqlAttribute {
     Bootstrap,
     Finalize,
     Name(LitStr),
+    Template,
+    Features(Punctuated<LitStr, Token![,]>),
 }
 
 impl Parse for ExtensionSqlAttribute {
@@ -274,6 +302,13 @@ impl Parse for ExtensionSqlAttribute {
             }
             "bootstrap" => Self::Bootstrap,
             "finalize" => Self::Finalize,
+            "template" => Self::Template,
+            "features" => {
+                let _eq: syn::token::Eq = input.parse()?;
+                let content;
+                let _bracket = syn::bracketed!(content in input);
+                Self::Features(content.parse_terminated(|input| input.parse::<LitStr>())?)
+            }
             "name" => {
                 let _eq: syn::token::Eq = input.parse()?;
                 Self::Name(input.parse()?)
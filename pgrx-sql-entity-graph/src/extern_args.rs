@@ -31,6 +31,9 @@ pub enum ExternArgs {
     Name(String),
     Cost(String),
     Requires(Vec<PositioningRef>),
+    RevokeFromPublic,
+    Grant(String),
+    SecurityLabel { provider: Option<String>, label: String },
 }
 
 impl core::fmt::Display for ExternArgs {
@@ -53,6 +56,9 @@ impl core::fmt::Display for ExternArgs {
             ExternArgs::Name(_) => Ok(()),
             ExternArgs::Cost(cost) => write!(f, "COST {}", cost),
             ExternArgs::Requires(_) => Ok(()),
+            ExternArgs::Grant(_) => Ok(()),
+            ExternArgs::RevokeFromPublic => Ok(()),
+            ExternArgs::SecurityLabel { .. } => Ok(()),
         }
     }
 }
@@ -112,10 +118,69 @@ impl ToTokens for ExternArgs {
                     .to_token_stream(),
                 );
             }
+            ExternArgs::Grant(role) => {
+                tokens.append_all(quote! { Grant(String::from(#role)) });
+            }
+            ExternArgs::RevokeFromPublic => tokens.append(format_ident!("RevokeFromPublic")),
+            ExternArgs::SecurityLabel { provider, label } => {
+                let provider = match provider {
+                    Some(provider) => quote! { Some(String::from(#provider)) },
+                    None => quote! { None },
+                };
+                tokens.append_all(quote! {
+                    SecurityLabel { provider: #provider, label: String::from(#label) }
+                });
+            }
+        }
+    }
+}
+
+impl ExternArgs {
+    /// Does this argument set the function's privileges or security label, rather than being part
+    /// of `CREATE FUNCTION`?
+    pub fn is_privilege(&self) -> bool {
+        matches!(
+            self,
+            ExternArgs::Grant(_) | ExternArgs::RevokeFromPublic | ExternArgs::SecurityLabel { .. }
+        )
+    }
+
+    /// The statement this argument adds after `CREATE FUNCTION`, for the function named, with its
+    /// argument types, by `function`, such as `"tests"."answer"(integer)`.
+    pub fn privilege_sql(&self, function: &str) -> Option<String> {
+        match self {
+            ExternArgs::RevokeFromPublic => {
+                Some(format!("REVOKE ALL ON FUNCTION {function} FROM PUBLIC;"))
+            }
+            ExternArgs::Grant(role) => Some(format!(
+                "GRANT EXECUTE ON FUNCTION {function} TO {role};",
+                role = quote_role(role)
+            )),
+            ExternArgs::SecurityLabel { provider, label } => Some(format!(
+                "SECURITY LABEL {provider}ON FUNCTION {function} IS '{label}';",
+                provider = provider
+                    .as_ref()
+                    .map(|provider| format!("FOR \"{}\" ", provider.replace('"', "\"\"")))
+                    .unwrap_or_default(),
+                label = label.replace('\'', "''"),
+            )),
+            _ => None,
         }
     }
 }
 
+/// `PUBLIC` and the `CURRENT_USER`-like role specifications are keywords, everything else is a
+/// role name.
+fn quote_role(role: &str) -> String {
+    const KEYWORDS: &[&str] = &["PUBLIC", "CURRENT_ROLE", "CURRENT_USER", "SESSION_USER"];
+    let upper = role.to_uppercase();
+    if KEYWORDS.contains(&upper.as_str()) {
+        upper
+    } else {
+        format!("\"{}\"", role.replace('"', "\"\""))
+    }
+}
+
 pub fn parse_extern_attributes(attr: TokenStream) -> HashSet<ExternArgs> {
     let mut args = HashSet::<ExternArgs>::new();
     let mut itr = attr.into_iter();
@@ -201,4 +266,38 @@ mod tests {
         let args = parse_extern_attributes(ts);
         assert!(args.contains(&ExternArgs::Error("syntax error at or near \"THIS\"".to_string())));
     }
+
+    #[test]
+    fn privilege_sql() {
+        let function = "\"tests\".\"answer\"(integer)";
+        assert_eq!(
+            ExternArgs::RevokeFromPublic.privilege_sql(function).unwrap(),
+            "REVOKE ALL ON FUNCTION \"tests\".\"answer\"(integer) FROM PUBLIC;"
+        );
+        assert_eq!(
+            ExternArgs::Grant("App \"Users\"".into()).privilege_sql(function).unwrap(),
+            "GRANT EXECUTE ON FUNCTION \"tests\".\"answer\"(integer) TO \"App \"\"Users\"\"\";"
+        );
+        assert_eq!(
+            ExternArgs::Grant("public".into()).privilege_sql(function).unwrap(),
+            "GRANT EXECUTE ON FUNCTION \"tests\".\"answer\"(integer) TO PUBLIC;"
+        );
+        assert_eq!(
+            ExternArgs::SecurityLabel {
+                provider: Some("selinux".into()),
+                label: "system_u:object_r:sepgsql_proc_exec_t:s0".into()
+            }
+            .privilege_sql(function)
+            .unwrap(),
+            "SECURITY LABEL FOR \"selinux\" ON FUNCTION \"tests\".\"answer\"(integer) \
+             IS 'system_u:object_r:sepgsql_proc_exec_t:s0';"
+        );
+        assert_eq!(
+            ExternArgs::SecurityLabel { provider: None, label: "it's".into() }
+                .privilege_sql(function)
+                .unwrap(),
+            "SECURITY LABEL ON FUNCTION \"tests\".\"answer\"(integer) IS 'it''s';"
+        );
+        assert_eq!(ExternArgs::Strict.privilege_sql(function), None);
+    }
 }
@@ -122,6 +122,23 @@ impl SqlGraphEntity {
             rust_identifier = self.rust_identifier(),
         )
     }
+
+    /// The entity's [`ToSqlConfigEntity`], for those which have one
+    pub fn to_sql_config(&self) -> Option<&ToSqlConfigEntity> {
+        match self {
+            SqlGraphEntity::Function(item) => Some(&item.to_sql_config),
+            SqlGraphEntity::Type(item) => Some(&item.to_sql_config),
+            SqlGraphEntity::Enum(item) => Some(&item.to_sql_config),
+            SqlGraphEntity::Ord(item) => Some(&item.to_sql_config),
+            SqlGraphEntity::Hash(item) => Some(&item.to_sql_config),
+            SqlGraphEntity::Aggregate(item) => Some(&item.to_sql_config),
+            SqlGraphEntity::Trigger(item) => Some(&item.to_sql_config),
+            SqlGraphEntity::Schema(_)
+            | SqlGraphEntity::CustomSql(_)
+            | SqlGraphEntity::BuiltinType(_)
+            | SqlGraphEntity::ExtensionRoot(_) => None,
+        }
+    }
 }
 
 impl SqlGraphIdentifier for SqlGraphEntity {
@@ -211,19 +228,7 @@ impl ToSql for SqlGraphEntity {
                     .any(|neighbor| {
                         let neighbor_item = &context.graph[neighbor];
                         match neighbor_item {
-                            SqlGraphEntity::Type(PostgresTypeEntity {
-                                in_fn,
-                                in_fn_module_path,
-                                out_fn,
-                                out_fn_module_path,
-                                ..
-                            }) => {
-                                let is_in_fn = item.full_path.starts_with(in_fn_module_path)
-                                    && item.full_path.ends_with(in_fn);
-                                let is_out_fn = item.full_path.starts_with(out_fn_module_path)
-                                    && item.full_path.ends_with(out_fn);
-                                is_in_fn || is_out_fn
-                            }
+                            SqlGraphEntity::Type(ty) => ty.is_io_fn(item.full_path),
                             _ => false,
                         }
                     })
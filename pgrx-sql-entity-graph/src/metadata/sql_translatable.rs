@@ -16,6 +16,7 @@ to the `pgrx` framework and very subject to change between versions. While you m
 
 */
 use std::any::Any;
+use std::borrow::Cow;
 use std::error::Error;
 use std::fmt::Display;
 
@@ -288,6 +289,24 @@ unsafe impl<'a> SqlTranslatable for &'a [u8] {
     }
 }
 
+unsafe impl<'a> SqlTranslatable for Cow<'a, str> {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        Ok(SqlMapping::literal("TEXT"))
+    }
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        Ok(Returns::One(SqlMapping::literal("TEXT")))
+    }
+}
+
+unsafe impl<'a> SqlTranslatable for Cow<'a, [u8]> {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        Ok(SqlMapping::literal("bytea"))
+    }
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        Ok(Returns::One(SqlMapping::literal("bytea")))
+    }
+}
+
 unsafe impl SqlTranslatable for i8 {
     fn argument_sql() -> Result<SqlMapping, ArgumentError> {
         Ok(SqlMapping::As(String::from("\"char\"")))
@@ -315,6 +334,24 @@ unsafe impl SqlTranslatable for i64 {
     }
 }
 
+unsafe impl SqlTranslatable for i128 {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        Ok(SqlMapping::literal("numeric"))
+    }
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        Ok(Returns::One(SqlMapping::literal("numeric")))
+    }
+}
+
+unsafe impl SqlTranslatable for u128 {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        Ok(SqlMapping::literal("numeric"))
+    }
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        Ok(Returns::One(SqlMapping::literal("numeric")))
+    }
+}
+
 unsafe impl SqlTranslatable for bool {
     fn argument_sql() -> Result<SqlMapping, ArgumentError> {
         Ok(SqlMapping::literal("bool"))
@@ -43,6 +43,34 @@ pub enum Attribute {
     Cost(syn::Expr),
     Requires(Punctuated<PositioningRef, Token![,]>),
     Sql(ToSqlConfig),
+    DenyLints,
+    /// Converts borrowed arguments with `FromDatum` rather than tying them to the call's memory
+    /// context, for `#[pg_aggregate]`, whose `Args` can't name that lifetime
+    StaticArgs,
+    Memoize(MemoizeScope),
+    Grant(syn::LitStr),
+    RevokeFromPublic,
+    SecurityLabel {
+        provider: Option<syn::LitStr>,
+        label: syn::LitStr,
+    },
+}
+
+/// How long `#[pg_extern(memoize)]` keeps a function's results
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub enum MemoizeScope {
+    Query,
+    Session,
+}
+
+impl ToTokens for MemoizeScope {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let quoted = match self {
+            MemoizeScope::Query => quote! { ::pgrx::memoize::MemoizeScope::Query },
+            MemoizeScope::Session => quote! { ::pgrx::memoize::MemoizeScope::Session },
+        };
+        tokens.append_all(quoted);
+    }
 }
 
 impl Attribute {
@@ -92,8 +120,29 @@ impl Attribute {
                 let items_iter = items.iter().map(|x| x.to_token_stream()).collect::<Vec<_>>();
                 quote! { ::pgrx::pgrx_sql_entity_graph::ExternArgs::Requires(vec![#(#items_iter),*],) }
             }
-            // This attribute is handled separately
-            Attribute::Sql(_) => {
+            Attribute::Grant(role) => {
+                quote! { ::pgrx::pgrx_sql_entity_graph::ExternArgs::Grant(String::from(#role)) }
+            }
+            Attribute::RevokeFromPublic => {
+                quote! { ::pgrx::pgrx_sql_entity_graph::ExternArgs::RevokeFromPublic }
+            }
+            Attribute::SecurityLabel { provider, label } => {
+                let provider = match provider {
+                    Some(provider) => quote! { Some(String::from(#provider)) },
+                    None => quote! { None },
+                };
+                quote! {
+                    ::pgrx::pgrx_sql_entity_graph::ExternArgs::SecurityLabel {
+                        provider: #provider,
+                        label: String::from(#label),
+                    }
+                }
+            }
+            // These attributes are handled separately
+            Attribute::Sql(_)
+            | Attribute::DenyLints
+            | Attribute::StaticArgs
+            | Attribute::Memoize(_) => {
                 quote! {}
             }
         }
@@ -145,6 +194,18 @@ impl ToTokens for Attribute {
             Attribute::Sql(to_sql_config) => {
                 quote! { sql = #to_sql_config }
             }
+            Attribute::DenyLints => quote! { deny_lints },
+            Attribute::StaticArgs => quote! { static_args },
+            Attribute::Memoize(MemoizeScope::Query) => quote! { memoize = "query" },
+            Attribute::Memoize(MemoizeScope::Session) => quote! { memoize = "session" },
+            Attribute::Grant(role) => quote! { grant = #role },
+            Attribute::RevokeFromPublic => quote! { revoke_from_public },
+            Attribute::SecurityLabel { provider: Some(provider), label } => {
+                quote! { security_label(provider = #provider, label = #label) }
+            }
+            Attribute::SecurityLabel { provider: None, label } => {
+                quote! { security_label = #label }
+            }
         };
         tokens.append_all(quoted);
     }
@@ -166,6 +227,66 @@ impl Parse for Attribute {
             "parallel_safe" => Self::ParallelSafe,
             "parallel_unsafe" => Self::ParallelUnsafe,
             "parallel_restricted" => Self::ParallelRestricted,
+            "deny_lints" => Self::DenyLints,
+            "static_args" => Self::StaticArgs,
+            "revoke_from_public" => Self::RevokeFromPublic,
+            "grant" => {
+                let _eq: Token![=] = input.parse()?;
+                let literal: syn::LitStr = input.parse()?;
+                Self::Grant(literal)
+            }
+            "security_label" => {
+                if input.peek(Token![=]) {
+                    let _eq: Token![=] = input.parse()?;
+                    let label: syn::LitStr = input.parse()?;
+                    Self::SecurityLabel { provider: None, label }
+                } else {
+                    let content;
+                    let _paren = syn::parenthesized!(content in input);
+                    let mut provider = None;
+                    let mut label = None;
+                    while !content.is_empty() {
+                        let key: syn::Ident = content.parse()?;
+                        let _eq: Token![=] = content.parse()?;
+                        let value: syn::LitStr = content.parse()?;
+                        match key.to_string().as_str() {
+                            "provider" => provider = Some(value),
+                            "label" => label = Some(value),
+                            _ => {
+                                return Err(syn::Error::new(
+                                    key.span(),
+                                    "expected `provider = \"...\"` or `label = \"...\"`",
+                                ))
+                            }
+                        }
+                        if !content.is_empty() {
+                            let _comma: Token![,] = content.parse()?;
+                        }
+                    }
+                    let label = label.ok_or_else(|| {
+                        syn::Error::new(ident.span(), "`security_label(...)` needs a `label`")
+                    })?;
+                    Self::SecurityLabel { provider, label }
+                }
+            }
+            "memoize" => {
+                if input.peek(Token![=]) {
+                    let _eq: Token![=] = input.parse()?;
+                    let literal: syn::LitStr = input.parse()?;
+                    match literal.value().as_str() {
+                        "query" => Self::Memoize(MemoizeScope::Query),
+                        "session" => Self::Memoize(MemoizeScope::Session),
+                        _ => {
+                            return Err(syn::Error::new(
+                                literal.span(),
+                                "expected `memoize = \"query\"` or `memoize = \"session\"`",
+                            ))
+                        }
+                    }
+                } else {
+                    Self::Memoize(MemoizeScope::Query)
+                }
+            }
             "error" => {
                 let _eq: Token![=] = input.parse()?;
                 let literal: syn::LitStr = input.parse()?;
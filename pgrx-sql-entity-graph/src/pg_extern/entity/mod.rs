@@ -74,9 +74,20 @@ impl SqlGraphIdentifier for PgExternEntity {
     }
 }
 
-impl ToSql for PgExternEntity {
-    fn to_sql(&self, context: &PgrxSql) -> eyre::Result<String> {
-        let self_index = context.externs[self];
+impl PgExternEntity {
+    /// A `CREATE OR REPLACE FUNCTION` statement loading this function from `module_pathname`
+    /// rather than the extension's library, as `cargo pgrx develop` re-points it at a rebuilt one
+    pub fn replacement_sql(
+        &self,
+        context: &PgrxSql,
+        module_pathname: &str,
+    ) -> eyre::Result<String> {
+        let (fn_sql, _) = self.create_function_sql(context, module_pathname, true)?;
+        Ok(fn_sql)
+    }
+
+    /// The `STRICT` marker this function gets, if it can, and its other attributes, sorted
+    fn resolved_extern_attrs(&self) -> Vec<ExternArgs> {
         let mut extern_attrs = self.extern_attrs.clone();
         // if we already have a STRICT marker we do not need to add it
         // presume we can upgrade, then disprove it
@@ -96,8 +107,27 @@ impl ToSql for PgExternEntity {
         }
         extern_attrs.sort();
         extern_attrs.dedup();
+        extern_attrs
+    }
 
-        let module_pathname = &context.get_module_pathname();
+    fn schema_prefix(&self, context: &PgrxSql) -> String {
+        self.schema
+            .map(|schema| format!("{}.", crate::pgrx_sql::quote_schema_name(schema)))
+            .unwrap_or_else(|| context.schema_prefix_for(&context.externs[self]))
+    }
+
+    /// The `CREATE FUNCTION` statement alone, and the function's argument types, for naming it in
+    /// the statements following its creation
+    fn create_function_sql(
+        &self,
+        context: &PgrxSql,
+        module_pathname: &str,
+        or_replace: bool,
+    ) -> eyre::Result<(String, Vec<String>)> {
+        let self_index = context.externs[self];
+        let extern_attrs = self.resolved_extern_attrs();
+        let schema_prefix = self.schema_prefix(context);
+        let mut signature_types = Vec::new();
 
         let fn_sql = format!(
             "\
@@ -107,12 +137,12 @@ impl ToSql for PgExternEntity {
                 LANGUAGE c /* Rust */\n\
                 AS '{module_pathname}', '{unaliased_name}_wrapper';\
             ",
-            or_replace =
-                if extern_attrs.contains(&ExternArgs::CreateOrReplace) { "OR REPLACE" } else { "" },
-            schema = self
-                .schema
-                .map(|schema| format!("{}.", schema))
-                .unwrap_or_else(|| context.schema_prefix_for(&self_index)),
+            or_replace = if or_replace || extern_attrs.contains(&ExternArgs::CreateOrReplace) {
+                "OR REPLACE"
+            } else {
+                ""
+            },
+            schema = schema_prefix,
             name = self.name,
             module_pathname = module_pathname,
             arguments = if !self.fn_args.is_empty() {
@@ -138,6 +168,25 @@ impl ToSql for PgExternEntity {
                         .ok_or_else(|| eyre!("Could not find arg type in graph. Got: {:?}", arg))?;
                     let needs_comma = idx < (metadata_without_arg_skips.len().saturating_sub(1));
                     let metadata_argument = &self.metadata.arguments[idx];
+                    if let Some(domain) = arg.used_ty.domain {
+                        // Domains are declared by the user, so they're referenced exactly as written.
+                        let buf = format!("\
+                            \t\"{pattern}\" {variadic}{sql_type}{default}{maybe_comma}/* {type_name} */\
+                        ",
+                            pattern = arg.pattern,
+                            sql_type = domain,
+                            default = if let Some(def) = arg.used_ty.default { format!(" DEFAULT {}", def) } else { String::from("") },
+                            variadic = if metadata_argument.variadic { "VARIADIC " } else { "" },
+                            maybe_comma = if needs_comma { ", " } else { " " },
+                            type_name = metadata_argument.type_name,
+                        );
+                        args.push(buf);
+                        signature_types.push(format!(
+                            "{variadic}{domain}",
+                            variadic = if metadata_argument.variadic { "VARIADIC " } else { "" },
+                        ));
+                        continue;
+                    }
                     match metadata_argument.argument_sql {
                         Ok(SqlMapping::As(ref argument_sql)) => {
                             let buf = format!("\
@@ -153,6 +202,12 @@ impl ToSql for PgExternEntity {
                                                 type_name = metadata_argument.type_name,
                                         );
                             args.push(buf);
+                            signature_types.push(format!(
+                                "{variadic}{schema_prefix}{argument_sql}",
+                                variadic =
+                                    if metadata_argument.variadic { "VARIADIC " } else { "" },
+                                schema_prefix = context.schema_prefix_for(&graph_index),
+                            ));
                         }
                         Ok(SqlMapping::Composite { array_brackets }) => {
                             let sql =
@@ -184,6 +239,12 @@ impl ToSql for PgExternEntity {
                                 type_name = metadata_argument.type_name,
                         );
                             args.push(buf);
+                            signature_types.push(format!(
+                                "{variadic}{schema_prefix}{sql}",
+                                variadic =
+                                    if metadata_argument.variadic { "VARIADIC " } else { "" },
+                                schema_prefix = context.schema_prefix_for(&graph_index),
+                            ));
                         }
                         Ok(SqlMapping::Source { array_brackets }) => {
                             let sql =
@@ -214,6 +275,12 @@ impl ToSql for PgExternEntity {
                                 type_name = metadata_argument.type_name,
                         );
                             args.push(buf);
+                            signature_types.push(format!(
+                                "{variadic}{schema_prefix}{sql}",
+                                variadic =
+                                    if metadata_argument.variadic { "VARIADIC " } else { "" },
+                                schema_prefix = context.schema_prefix_for(&graph_index),
+                            ));
                         }
                         Ok(SqlMapping::Skip) => (),
                         Err(err) => {
@@ -232,6 +299,15 @@ impl ToSql for PgExternEntity {
                                             type_name = metadata_argument.type_name,
                                     );
                                     args.push(buf);
+                                    signature_types.push(format!(
+                                        "{variadic}{schema_prefix}{source_only_mapping}",
+                                        variadic = if metadata_argument.variadic {
+                                            "VARIADIC "
+                                        } else {
+                                            ""
+                                        },
+                                        schema_prefix = context.schema_prefix_for(&graph_index),
+                                    ));
                                 }
                                 None => return Err(err).wrap_err("While mapping argument"),
                             }
@@ -257,6 +333,7 @@ impl ToSql for PgExternEntity {
                         .ok_or_else(|| eyre!("Could not find return type in graph."))?;
                     let metadata_retval = self.metadata.retval.clone().ok_or_else(|| eyre!("Macro expansion time and SQL resolution time had differing opinions about the return value existing"))?;
                     let metadata_retval_sql = match metadata_retval.return_sql {
+                        _ if ty.domain.is_some() => ty.domain.unwrap().to_string(),
                         Ok(Returns::One(SqlMapping::As(ref sql))) => sql.clone(),
                         Ok(Returns::One(SqlMapping::Composite { array_brackets })) => ty.composite_type.unwrap().to_string()
                         + if array_brackets {
@@ -281,7 +358,12 @@ impl ToSql for PgExternEntity {
                     format!(
                         "RETURNS {schema_prefix}{sql_type} /* {full_path} */",
                         sql_type = metadata_retval_sql,
-                        schema_prefix = context.schema_prefix_for(&graph_index),
+                        // Domains are declared by the user, so they're referenced exactly as written.
+                        schema_prefix = if ty.domain.is_some() {
+                            String::new()
+                        } else {
+                            context.schema_prefix_for(&graph_index)
+                        },
                         full_path = ty.full_path
                     )
                 }
@@ -394,12 +476,12 @@ impl ToSql for PgExternEntity {
             } else {
                 Default::default()
             },
-            extern_attrs = if extern_attrs.is_empty() {
+            extern_attrs = if extern_attrs.iter().all(|attr| attr.is_privilege()) {
                 String::default()
             } else {
                 let mut retval = extern_attrs
                     .iter()
-                    .filter(|attr| **attr != ExternArgs::CreateOrReplace)
+                    .filter(|attr| **attr != ExternArgs::CreateOrReplace && !attr.is_privilege())
                     .map(|attr| format!("{}", attr).to_uppercase())
                     .collect::<Vec<_>>()
                     .join(" ");
@@ -408,6 +490,29 @@ impl ToSql for PgExternEntity {
             },
             unaliased_name = self.unaliased_name,
         );
+        Ok((fn_sql, signature_types))
+    }
+}
+
+impl ToSql for PgExternEntity {
+    fn to_sql(&self, context: &PgrxSql) -> eyre::Result<String> {
+        let self_index = context.externs[self];
+        let extern_attrs = self.resolved_extern_attrs();
+        let schema_prefix = self.schema_prefix(context);
+        let (fn_sql, signature_types) =
+            self.create_function_sql(context, &context.get_module_pathname(), false)?;
+
+        let function = format!(
+            "{schema_prefix}\"{name}\"({types})",
+            name = self.name,
+            types = signature_types.join(", ")
+        );
+        // `extern_attrs` is sorted, which puts `REVOKE` before `GRANT` so granting to `PUBLIC` sticks
+        let privileges = extern_attrs
+            .iter()
+            .filter_map(|attr| attr.privilege_sql(&function))
+            .map(|sql| format!("\n{sql}"))
+            .collect::<String>();
 
         let ext_sql = format!(
             "\n\
@@ -415,12 +520,14 @@ impl ToSql for PgExternEntity {
                                 -- {module_path}::{name}\n\
                                 {requires}\
                                 {fn_sql}\
+                                {privileges}\
                             ",
             name = self.name,
             module_path = self.module_path,
             file = self.file,
             line = self.line,
             fn_sql = fn_sql,
+            privileges = privileges,
             requires = {
                 let requires_attrs = self
                     .extern_attrs
@@ -577,7 +684,7 @@ impl ToSql for PgExternEntity {
                                                     -- {file}:{line}\n\
                                                     -- {module_path}::{name}\n\
                                                     CREATE OPERATOR {opname} (\n\
-                                                        \tPROCEDURE=\"{name}\",\n\
+                                                        \tPROCEDURE={schema_prefix}\"{name}\",\n\
                                                         \tLEFTARG={schema_prefix_left}{left_arg}, /* {left_name} */\n\
                                                         \tRIGHTARG={schema_prefix_right}{right_arg}{maybe_comma} /* {right_name} */\n\
                                                         {optionals}\
@@ -587,6 +694,7 @@ impl ToSql for PgExternEntity {
                                                     file = self.file,
                                                     line = self.line,
                                                     name = self.name,
+                                                    schema_prefix = schema_prefix,
                                                     module_path = self.module_path,
                                                     left_name = left_arg.type_name,
                                                     right_name = right_arg.type_name,
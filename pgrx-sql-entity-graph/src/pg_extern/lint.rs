@@ -0,0 +1,285 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+/*!
+
+Checks of `#[pg_extern]` markings against the function they're applied to
+
+> Like all of the [`sql_entity_graph`][crate::pgrx_sql_entity_graph] APIs, this is considered **internal**
+to the `pgrx` framework and very subject to change between versions. While you may use this, please do it with caution.
+
+*/
+use super::attribute::{Attribute, MemoizeScope};
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
+use quote::ToTokens;
+
+/// Identifiers which show a function reads the database through SPI
+const SPI_HINTS: &[&str] = &["Spi", "SpiClient", "spi"];
+
+/// Identifiers which, along with SPI, show a function writes to the database
+const SPI_WRITE_HINTS: &[&str] = &["update", "run", "run_with_args"];
+
+/// Identifiers which show a function's result can change even within a single statement
+const VOLATILE_HINTS: &[&str] =
+    &["GetCurrentTimestamp", "random", "rand", "thread_rng", "SystemTime", "Instant"];
+
+/// Identifiers which show a function uses cursors, prepared statements, or other state of the
+/// backend it runs in, which a parallel worker doesn't share
+const BACKEND_STATE_HINTS: &[&str] = &[
+    "open_cursor",
+    "find_cursor",
+    "SpiCursor",
+    "prepare",
+    "OwnedPreparedStatement",
+    "thread_local",
+];
+
+/// Check the markings given to a `#[pg_extern]` against its signature and body, returning a
+/// description of each likely mistake.
+///
+/// These are hints from what the function names, not proof, so they're only reported for
+/// functions which ask for them with `deny_lints`.
+pub(crate) fn lints(
+    func: &syn::ItemFn,
+    attrs: &[Attribute],
+    memoize: Option<MemoizeScope>,
+) -> Vec<String> {
+    let name = &func.sig.ident;
+    let has = |attr: &Attribute| attrs.contains(attr);
+    let mut lints = Vec::new();
+
+    let volatilities = [Attribute::Immutable, Attribute::Stable, Attribute::Volatile]
+        .into_iter()
+        .filter(|attr| has(attr))
+        .count();
+    if volatilities > 1 {
+        lints.push(format!(
+            "`{name}` is marked with more than one of `immutable`, `stable`, and `volatile`"
+        ));
+    }
+
+    let body = func.block.to_token_stream();
+    if has(&Attribute::Immutable) && mentions(&body, SPI_HINTS) {
+        lints.push(format!(
+            "`{name}` uses SPI, so it reads the database and should be `stable` or `volatile` rather than `immutable`"
+        ));
+    }
+    if (has(&Attribute::Immutable) || has(&Attribute::Stable)) && mentions(&body, VOLATILE_HINTS) {
+        lints.push(format!(
+            "`{name}` uses the clock or randomness, so it should be `volatile` rather than `immutable` or `stable`"
+        ));
+    }
+
+    match memoize {
+        Some(MemoizeScope::Session) if !has(&Attribute::Immutable) => lints.push(format!(
+            "`{name}` is memoized for the session, which is only correct if it's `immutable`"
+        )),
+        Some(MemoizeScope::Query) if !has(&Attribute::Immutable) && !has(&Attribute::Stable) => {
+            lints.push(format!(
+                "`{name}` is memoized for the query, which is only correct if it's `immutable` or `stable`"
+            ))
+        }
+        _ => {}
+    }
+
+    let parallels =
+        [Attribute::ParallelSafe, Attribute::ParallelRestricted, Attribute::ParallelUnsafe]
+            .into_iter()
+            .filter(|attr| has(attr))
+            .count();
+    if parallels > 1 {
+        lints.push(format!(
+            "`{name}` is marked with more than one of `parallel_safe`, `parallel_restricted`, and `parallel_unsafe`"
+        ));
+    }
+
+    let uses_spi = mentions(&body, SPI_HINTS);
+    let writes = uses_spi && mentions(&body, SPI_WRITE_HINTS);
+    let backend_state = mentions(&body, BACKEND_STATE_HINTS);
+    if writes && (has(&Attribute::ParallelSafe) || has(&Attribute::ParallelRestricted)) {
+        lints.push(format!(
+            "`{name}` writes to the database through SPI, which a parallel query can't, so it should be `parallel_unsafe`"
+        ));
+    } else if backend_state && has(&Attribute::ParallelSafe) {
+        lints.push(format!(
+            "`{name}` uses cursors, prepared statements or backend-local state, which parallel workers don't share, so it should be `parallel_restricted` rather than `parallel_safe`"
+        ));
+    }
+    // functions are `PARALLEL UNSAFE` unless marked otherwise, which keeps every query calling
+    // them from being parallelized
+    if has(&Attribute::Immutable) && parallels == 0 && !uses_spi && !backend_state {
+        lints.push(format!(
+            "`{name}` is `immutable` and looks parallel safe, so it should be marked `parallel_safe` for queries calling it to be parallelized"
+        ));
+    }
+
+    if has(&Attribute::Strict) {
+        let nullable = func.sig.inputs.iter().any(|arg| match arg {
+            syn::FnArg::Typed(pat_ty) => is_nullable(&pat_ty.ty),
+            syn::FnArg::Receiver(_) => false,
+        });
+        if nullable {
+            lints.push(format!(
+                "`{name}` is `strict`, so its `Option` or `Nullable` arguments will never be NULL"
+            ));
+        }
+    }
+
+    lints
+}
+
+fn mentions(tokens: &TokenStream2, idents: &[&str]) -> bool {
+    tokens.clone().into_iter().any(|tree| match tree {
+        TokenTree::Ident(ident) => idents.iter().any(|hint| ident == hint),
+        TokenTree::Group(group) => mentions(&group.stream(), idents),
+        TokenTree::Punct(_) | TokenTree::Literal(_) => false,
+    })
+}
+
+fn is_nullable(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident == "Option" || segment.ident == "Nullable")
+            .unwrap_or_default(),
+        // `default!(Option<T>, ..)` and the like
+        syn::Type::Macro(mac) => {
+            mac.mac.path.is_ident("default") && mentions(&mac.mac.tokens, &["Option", "Nullable"])
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pg_extern::PgExtern;
+    use quote::quote;
+    use syn::parse_quote;
+
+    #[test]
+    fn spi_in_immutable() {
+        let func: syn::ItemFn = parse_quote! {
+            fn lookup() -> Option<i64> {
+                Spi::get_one("SELECT 1").unwrap()
+            }
+        };
+        assert_eq!(lints(&func, &[Attribute::Immutable], None).len(), 1);
+        assert!(lints(&func, &[Attribute::Stable], None).is_empty());
+    }
+
+    #[test]
+    fn clock_in_stable() {
+        let func: syn::ItemFn = parse_quote! {
+            fn now() -> i64 {
+                unsafe { pg_sys::GetCurrentTimestamp() }
+            }
+        };
+        assert_eq!(lints(&func, &[Attribute::Stable], None).len(), 1);
+        assert!(lints(&func, &[Attribute::Volatile], None).is_empty());
+    }
+
+    #[test]
+    fn conflicting_volatility() {
+        let func: syn::ItemFn = parse_quote! {
+            fn one() -> i32 {
+                1
+            }
+        };
+        let attrs = [Attribute::Stable, Attribute::Volatile];
+        assert_eq!(lints(&func, &attrs, None).len(), 1);
+    }
+
+    #[test]
+    fn memoize_volatility() {
+        let func: syn::ItemFn = parse_quote! {
+            fn one() -> i32 {
+                1
+            }
+        };
+        assert_eq!(lints(&func, &[Attribute::Stable], Some(MemoizeScope::Session)).len(), 1);
+        assert!(lints(&func, &[Attribute::Stable], Some(MemoizeScope::Query)).is_empty());
+        assert_eq!(lints(&func, &[Attribute::Volatile], Some(MemoizeScope::Query)).len(), 1);
+    }
+
+    #[test]
+    fn strict_with_option() {
+        let func: syn::ItemFn = parse_quote! {
+            fn maybe(value: Option<i32>) -> i32 {
+                value.unwrap_or_default()
+            }
+        };
+        assert_eq!(lints(&func, &[Attribute::Strict], None).len(), 1);
+        assert!(lints(&func, &[], None).is_empty());
+    }
+
+    #[test]
+    fn conflicting_parallel() {
+        let func: syn::ItemFn = parse_quote! {
+            fn one() -> i32 {
+                1
+            }
+        };
+        let attrs = [Attribute::ParallelSafe, Attribute::ParallelUnsafe];
+        assert_eq!(lints(&func, &attrs, None).len(), 1);
+    }
+
+    #[test]
+    fn spi_write_in_parallel() {
+        let func: syn::ItemFn = parse_quote! {
+            fn record(value: i32) {
+                Spi::run("INSERT INTO log VALUES (1)").unwrap()
+            }
+        };
+        assert_eq!(lints(&func, &[Attribute::ParallelSafe], None).len(), 1);
+        assert_eq!(lints(&func, &[Attribute::ParallelRestricted], None).len(), 1);
+        assert!(lints(&func, &[Attribute::ParallelUnsafe], None).is_empty());
+    }
+
+    #[test]
+    fn backend_state_in_parallel_safe() {
+        let func: syn::ItemFn = parse_quote! {
+            fn first() -> Option<i64> {
+                Spi::connect(|client| client.open_cursor("SELECT 1", None).fetch(1).ok()?.first().get(1).ok()?)
+            }
+        };
+        assert_eq!(lints(&func, &[Attribute::ParallelSafe], None).len(), 1);
+        assert!(lints(&func, &[Attribute::ParallelRestricted], None).is_empty());
+    }
+
+    #[test]
+    fn immutable_could_be_parallel_safe() {
+        let func: syn::ItemFn = parse_quote! {
+            fn double(value: i32) -> i32 {
+                value * 2
+            }
+        };
+        assert_eq!(lints(&func, &[Attribute::Immutable], None).len(), 1);
+        let attrs = [Attribute::Immutable, Attribute::ParallelSafe];
+        assert!(lints(&func, &attrs, None).is_empty());
+        let attrs = [Attribute::Immutable, Attribute::ParallelRestricted];
+        assert!(lints(&func, &attrs, None).is_empty());
+    }
+
+    #[test]
+    fn reported_only_when_denied() {
+        let func = quote! {
+            fn double(value: i32) -> i32 {
+                value * 2
+            }
+        };
+        assert!(PgExtern::new(quote!(immutable), func.clone()).is_ok());
+        assert!(PgExtern::new(quote!(immutable, parallel_safe, deny_lints), func.clone()).is_ok());
+        let err = PgExtern::new(quote!(immutable, deny_lints), func).err();
+        let err = err.expect("the lint should be an error");
+        assert!(err.to_string().contains("parallel_safe"), "{err}");
+    }
+}
@@ -18,6 +18,7 @@ to the `pgrx` framework and very subject to change between versions. While you m
 mod argument;
 mod attribute;
 pub mod entity;
+mod lint;
 mod operator;
 mod returning;
 mod search_path;
@@ -27,14 +28,14 @@ pub use operator::PgOperator;
 pub use returning::NameMacro;
 
 use crate::ToSqlConfig;
-use attribute::Attribute;
+use attribute::{Attribute, MemoizeScope};
 use operator::{PgrxOperatorAttributeWithIdent, PgrxOperatorOpName};
 use search_path::SearchPathList;
 
 use crate::enrich::CodeEnrichment;
 use crate::enrich::ToEntityGraphTokens;
 use crate::enrich::ToRustCodeTokens;
-use crate::lifetimes::staticize_lifetimes;
+use crate::lifetimes::{anonymize_lifetimes, staticize_lifetimes};
 use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
 use quote::{quote, quote_spanned, ToTokens};
 use syn::parse::{Parse, ParseStream, Parser};
@@ -78,12 +79,17 @@ pub struct PgExtern {
     inputs: Vec<PgExternArgument>,
     input_types: Vec<syn::Type>,
     returns: Returning,
+    memoize: Option<MemoizeScope>,
+    static_args: bool,
 }
 
 impl PgExtern {
     pub fn new(attr: TokenStream2, item: TokenStream2) -> Result<CodeEnrichment<Self>, syn::Error> {
         let mut attrs = Vec::new();
         let mut to_sql_config: Option<ToSqlConfig> = None;
+        let mut deny_lints = false;
+        let mut static_args = false;
+        let mut memoize = None;
 
         let parser = Punctuated::<Attribute, Token![,]>::parse_terminated;
         let punctuated_attrs = parser.parse2(attr)?;
@@ -92,16 +98,25 @@ impl PgExtern {
                 Attribute::Sql(config) => {
                     to_sql_config.get_or_insert(config);
                 }
+                Attribute::DenyLints => {
+                    deny_lints = true;
+                }
+                Attribute::StaticArgs => {
+                    static_args = true;
+                }
+                Attribute::Memoize(scope) => {
+                    memoize.get_or_insert(scope);
+                }
                 attr => {
                     attrs.push(attr);
                 }
             }
         }
 
-        let mut to_sql_config = to_sql_config.unwrap_or_default();
-
         let func = syn::parse2::<syn::ItemFn>(item)?;
 
+        let mut to_sql_config = to_sql_config.unwrap_or_default().with_cfg(&func.attrs);
+
         if let Some(ref mut content) = to_sql_config.content {
             let value = content.value();
             let updated_value = value
@@ -118,6 +133,20 @@ impl PgExtern {
         let inputs = Self::inputs(&func)?;
         let input_types = Self::input_types(&func)?;
         let returns = Returning::try_from(&func.sig.output)?;
+        if memoize.is_some() && !matches!(returns, Returning::Type(_)) {
+            return Err(syn::Error::new(
+                func.sig.output.span(),
+                "`memoize` is only supported for functions returning a single value",
+            ));
+        }
+        // there's no stable way for a proc macro to warn, so the lints are only reported as
+        // errors, for functions which opt into them
+        if deny_lints {
+            let lints = lint::lints(&func, &attrs, memoize);
+            if !lints.is_empty() {
+                return Err(syn::Error::new(func.sig.ident.span(), lints.join("\n")));
+            }
+        }
         Ok(CodeEnrichment(Self {
             attrs,
             func,
@@ -127,6 +156,8 @@ impl PgExtern {
             inputs,
             input_types,
             returns,
+            memoize,
+            static_args,
         }))
     }
 
@@ -370,50 +401,119 @@ impl PgExtern {
             .iter()
             .map(|v| syn::Ident::new(&format!("{}_", &v.pat), self.func.sig.span()))
             .collect::<Vec<_>>();
-        let arg_fetches = args.iter().enumerate().map(|(idx, arg)| {
-            let pat = &arg_pats[idx];
-            let resolved_ty = &arg.used_ty.resolved_ty;
-            if arg.used_ty.resolved_ty.to_token_stream().to_string() == quote!(pgrx::pg_sys::FunctionCallInfo).to_token_stream().to_string()
-                || arg.used_ty.resolved_ty.to_token_stream().to_string() == quote!(pg_sys::FunctionCallInfo).to_token_stream().to_string()
-                || arg.used_ty.resolved_ty.to_token_stream().to_string() == quote!(::pgrx::pg_sys::FunctionCallInfo).to_token_stream().to_string()
-            {
-                quote_spanned! {pat.span()=>
-                    let #pat = #fcinfo_ident;
-                }
-            } else if arg.used_ty.resolved_ty.to_token_stream().to_string() == quote!(()).to_token_stream().to_string() {
-                quote_spanned! {pat.span()=>
-                    debug_assert!(unsafe { ::pgrx::fcinfo::pg_getarg::<()>(#fcinfo_ident, #idx).is_none() }, "A `()` argument should always receive `NULL`");
-                    let #pat = ();
-                }
-            } else {
-                match (is_raw, &arg.used_ty.optional) {
-                    (true, None) | (true, Some(_)) => quote_spanned! { pat.span() =>
-                        let #pat = unsafe { ::pgrx::fcinfo::pg_getarg_datum_raw(#fcinfo_ident, #idx) as #resolved_ty };
-                    },
-                    (false, None) => quote_spanned! { pat.span() =>
-                        let #pat = unsafe { ::pgrx::fcinfo::pg_getarg::<#resolved_ty>(#fcinfo_ident, #idx).unwrap_or_else(|| panic!("{} is null", stringify!{#pat})) };
-                    },
-                    (false, Some(inner)) => quote_spanned! { pat.span() =>
-                        let #pat = unsafe { ::pgrx::fcinfo::pg_getarg::<#inner>(#fcinfo_ident, #idx) };
-                    },
-                }
+        let is_fcinfo = |arg: &PgExternArgument| {
+            arg.used_ty.resolved_ty.to_token_stream().to_string()
+                == quote!(pgrx::pg_sys::FunctionCallInfo).to_token_stream().to_string()
+                || arg.used_ty.resolved_ty.to_token_stream().to_string()
+                    == quote!(pg_sys::FunctionCallInfo).to_token_stream().to_string()
+                || arg.used_ty.resolved_ty.to_token_stream().to_string()
+                    == quote!(::pgrx::pg_sys::FunctionCallInfo).to_token_stream().to_string()
+        };
+        let is_unit = |arg: &PgExternArgument| {
+            arg.used_ty.resolved_ty.to_token_stream().to_string()
+                == quote!(()).to_token_stream().to_string()
+        };
+        let is_nullable = |arg: &PgExternArgument| match &arg.used_ty.resolved_ty {
+            syn::Type::Path(path) => {
+                path.path.segments.last().map_or(false, |segment| segment.ident == "Nullable")
             }
-        });
+            _ => false,
+        };
+        // Outside of set-returning functions, whose iterators outlive the call, arguments are
+        // converted inside `current_context()` so those which borrow can't outlive it
+        let binds_mcx =
+            !is_raw && !self.static_args && args.iter().any(|arg| !is_fcinfo(arg) && !is_unit(arg));
+        let arg_fetch = |bind_mcx: bool| {
+            args.iter().enumerate().map(|(idx, arg)| {
+                let pat = &arg_pats[idx];
+                let resolved_ty = &arg.used_ty.resolved_ty;
+                if is_fcinfo(arg) {
+                    quote_spanned! {pat.span()=>
+                        let #pat = #fcinfo_ident;
+                    }
+                } else if is_unit(arg) {
+                    quote_spanned! {pat.span()=>
+                        debug_assert!(unsafe { ::pgrx::fcinfo::pg_getarg::<()>(#fcinfo_ident, #idx).is_none() }, "A `()` argument should always receive `NULL`");
+                        let #pat = ();
+                    }
+                } else {
+                    let fetched_ty = arg.used_ty.optional.as_ref().unwrap_or(resolved_ty);
+                    let fetch = if bind_mcx {
+                        // the argument's lifetimes are the `MemCx`'s, not whatever the function names
+                        let mut fetched_ty = fetched_ty.clone();
+                        anonymize_lifetimes(&mut fetched_ty);
+                        quote_spanned! { pat.span() =>
+                            #[allow(unused_imports)]
+                            use ::pgrx::datum::ArgumentFallback as _;
+                            unsafe { ::pgrx::datum::Argument::<#fetched_ty>::get(#fcinfo_ident, #idx, mcx) }
+                        }
+                    } else {
+                        quote_spanned! { pat.span() =>
+                            unsafe { ::pgrx::fcinfo::pg_getarg::<#fetched_ty>(#fcinfo_ident, #idx) }
+                        }
+                    };
+                    match (is_raw, &arg.used_ty.optional) {
+                        (true, None) | (true, Some(_)) => quote_spanned! { pat.span() =>
+                            let #pat = unsafe { ::pgrx::fcinfo::pg_getarg_datum_raw(#fcinfo_ident, #idx) as #resolved_ty };
+                        },
+                        // a `Nullable` the caller didn't pass at all, as when calling through a
+                        // shorter SQL declaration or directly from C, is `Nullable::Absent`
+                        (false, None) if is_nullable(arg) => quote_spanned! { pat.span() =>
+                            let #pat = if (#idx as i16) < unsafe { (*#fcinfo_ident).nargs } {
+                                #[allow(unused_imports)]
+                                use ::pgrx::datum_diagnostics::ArgumentCheckFallback as _;
+                                unsafe { ::pgrx::datum_diagnostics::ArgumentCheck::<#resolved_ty>::check(#fcinfo_ident, #idx) };
+                                { #fetch }.unwrap_or_else(|| panic!("{} is null", stringify!{#pat}))
+                            } else {
+                                ::pgrx::Nullable::Absent
+                            };
+                        },
+                        (false, None) => quote_spanned! { pat.span() =>
+                            #[allow(unused_imports)]
+                            use ::pgrx::datum_diagnostics::ArgumentCheckFallback as _;
+                            unsafe { ::pgrx::datum_diagnostics::ArgumentCheck::<#resolved_ty>::check(#fcinfo_ident, #idx) };
+                            let #pat = { #fetch }.unwrap_or_else(|| panic!("{} is null", stringify!{#pat}));
+                        },
+                        (false, Some(inner)) => quote_spanned! { pat.span() =>
+                            #[allow(unused_imports)]
+                            use ::pgrx::datum_diagnostics::ArgumentCheckFallback as _;
+                            unsafe { ::pgrx::datum_diagnostics::ArgumentCheck::<#inner>::check(#fcinfo_ident, #idx) };
+                            let #pat = { #fetch };
+                        },
+                    }
+                }
+            }).collect::<Vec<_>>()
+        };
+        let arg_fetches = arg_fetch(false);
 
         match &self.returns {
-            Returning::None => quote_spanned! { self.func.sig.span() =>
-                  #[no_mangle]
-                  #[doc(hidden)]
-                  #[::pgrx::pgrx_macros::pg_guard]
-                  pub unsafe extern "C" fn #func_name_wrapper #func_generics(#fcinfo_ident: ::pgrx::pg_sys::FunctionCallInfo) {
-                      #(
-                          #arg_fetches
-                      )*
+            Returning::None => {
+                let arg_fetches = arg_fetch(binds_mcx);
+                let body = quote_spanned! { self.func.sig.span() =>
+                    #(
+                        #arg_fetches
+                    )*
 
                     #[allow(unused_unsafe)] // unwrapped fn might be unsafe
                     unsafe { #func_name(#(#arg_pats),*) }
+                };
+                let body = if binds_mcx {
+                    quote_spanned! { self.func.sig.span() =>
+                        ::pgrx::current_context(|mcx| { #body })
+                    }
+                } else {
+                    body
+                };
+
+                quote_spanned! { self.func.sig.span() =>
+                    #[no_mangle]
+                    #[doc(hidden)]
+                    #[::pgrx::pgrx_macros::pg_guard]
+                    pub unsafe extern "C" fn #func_name_wrapper #func_generics(#fcinfo_ident: ::pgrx::pg_sys::FunctionCallInfo) {
+                        #body
+                    }
                 }
-            },
+            }
             Returning::Type(retval_ty) => {
                 let result_ident = syn::Ident::new("result", self.func.sig.span());
                 let retval_transform = if retval_ty.resolved_ty == syn::parse_quote!(()) {
@@ -458,20 +558,53 @@ impl PgExtern {
                         ::pgrx::datum::IntoDatum::into_datum(#result_ident).unwrap_or_else(|| panic!("returned Datum was NULL"))
                     }
                 };
+                // Postgres doesn't enforce domain constraints on the results of C functions.  The
+                // domain's constraints are cached in `fn_extra`, unless memoizing keeps its cache
+                // there, or the function may use it through its `fcinfo`
+                let fn_extra_taken = self.memoize.is_some() || args.iter().any(is_fcinfo);
+                let retval_transform = if retval_ty.domain.is_some() && fn_extra_taken {
+                    quote_spanned! { self.func.sig.output.span() =>
+                        unsafe { ::pgrx::fcinfo::pg_return_domain_checked_uncached(#fcinfo_ident, #retval_transform) }
+                    }
+                } else if retval_ty.domain.is_some() {
+                    quote_spanned! { self.func.sig.output.span() =>
+                        unsafe { ::pgrx::fcinfo::pg_return_domain_checked(#fcinfo_ident, #retval_transform) }
+                    }
+                } else {
+                    retval_transform
+                };
+
+                let arg_fetches = arg_fetch(binds_mcx);
+                let body = quote_spanned! { self.func.sig.span() =>
+                    #(
+                        #arg_fetches
+                    )*
+
+                    #[allow(unused_unsafe)] // unwrapped fn might be unsafe
+                    let #result_ident = unsafe { #func_name(#(#arg_pats),*) };
+
+                    #retval_transform
+                };
+                let body = if binds_mcx {
+                    quote_spanned! { self.func.sig.span() =>
+                        ::pgrx::current_context(|mcx| { #body })
+                    }
+                } else {
+                    body
+                };
+                let body = match self.memoize {
+                    Some(scope) => quote_spanned! { self.func.sig.span() =>
+                        ::pgrx::memoize::memoized(#fcinfo_ident, #scope, |#fcinfo_ident| { #body })
+                    },
+                    None => body,
+                };
 
                 quote_spanned! { self.func.sig.span() =>
                     #[no_mangle]
                     #[doc(hidden)]
                     #[::pgrx::pgrx_macros::pg_guard]
                     pub unsafe extern "C" fn #func_name_wrapper #func_generics(#fcinfo_ident: ::pgrx::pg_sys::FunctionCallInfo) -> ::pgrx::pg_sys::Datum {
-                        #(
-                            #arg_fetches
-                        )*
-
-                        #[allow(unused_unsafe)] // unwrapped fn might be unsafe
-                        let #result_ident = unsafe { #func_name(#(#arg_pats),*) };
-
-                        #retval_transform
+                        #body
                     }
                 }
             }
@@ -45,12 +45,12 @@ impl Returning {
         let mac = &type_macro.mac;
         let opt_archetype = mac.path.segments.last().map(|archetype| archetype.ident.to_string());
         match opt_archetype.as_deref() {
-            Some("composite_type") => {
+            Some("composite_type") | Some("domain") => {
                 Ok(Returning::Type(UsedType::new(syn::Type::Macro(type_macro.clone()))?))
             }
             _ => Err(syn::Error::new(
                 type_macro.span(),
-                "type macros other than `composite_type!` are not yet implemented",
+                "type macros other than `composite_type!` and `domain!` are not yet implemented",
             )),
         }
     }
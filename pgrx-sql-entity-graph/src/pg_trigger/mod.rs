@@ -55,7 +55,8 @@ impl PgTrigger {
                 };
                 config
             })
-            .unwrap_or_default();
+            .unwrap_or_default()
+            .with_cfg(&func.attrs);
 
         if !to_sql_config.overrides_default() {
             crate::ident_is_acceptable_to_postgres(&func.sig.ident)?;
@@ -79,6 +79,9 @@ pub struct PgrxSql {
     pub triggers: HashMap<PgTriggerEntity, NodeIndex>,
     pub extension_name: String,
     pub versioned_so: bool,
+    /// The cargo features the schema is generated for.  Entities whose `#[cfg(feature = "...")]`
+    /// attributes don't hold for them are left out
+    pub features: Vec<String>,
 }
 
 impl PgrxSql {
@@ -86,11 +89,17 @@ impl PgrxSql {
         entities: impl Iterator<Item = SqlGraphEntity>,
         extension_name: String,
         versioned_so: bool,
+        features: Vec<String>,
     ) -> eyre::Result<Self> {
         let mut graph = StableGraph::new();
 
         let mut entities = entities.collect::<Vec<_>>();
         entities.sort();
+        // only what's compiled under `features` belongs in the schema, even if the shared object
+        // is left over from a build with others
+        entities.retain(|entity| {
+            entity.to_sql_config().map_or(true, |config| config.cfg_holds(&features))
+        });
         // Split up things into their specific types:
         let mut control: Option<ControlFile> = None;
         let mut schemas: Vec<SchemaEntity> = Vec::default();
@@ -189,7 +198,7 @@ impl PgrxSql {
             &mapped_triggers,
         )?;
         connect_enums(&mut graph, &mapped_enums, &mapped_schemas);
-        connect_types(&mut graph, &mapped_types, &mapped_schemas);
+        connect_types(&mut graph, &mapped_types, &mapped_enums, &mapped_schemas);
         connect_externs(
             &mut graph,
             &mapped_externs,
@@ -246,6 +255,7 @@ impl PgrxSql {
             graph_finalize: finalize,
             extension_name: extension_name,
             versioned_so,
+            features,
         };
         Ok(this)
     }
@@ -393,11 +403,72 @@ impl PgrxSql {
         Ok(())
     }
 
+    /// Write the entity graph as a [D2](https://d2lang.com/) diagram, with the same coloring as
+    /// [`PgrxSql::to_dot`].
+    pub fn to_d2(&self, file: impl AsRef<Path> + Debug) -> eyre::Result<()> {
+        use std::fs::{create_dir_all, File};
+        use std::io::Write;
+        let mut generated = String::from("direction: right\n\n");
+        for index in self.graph.node_indices() {
+            let node = &self.graph[index];
+            let style = match node {
+                SqlGraphEntity::Schema(_) => "shape: package",
+                SqlGraphEntity::Function(_) => "style.fill: \"#ADC7C6\"",
+                SqlGraphEntity::Type(_) => "shape: oval; style.fill: \"#AE9BBD\"",
+                SqlGraphEntity::BuiltinType(_) => "shape: text",
+                SqlGraphEntity::Enum(_) => "shape: oval; style.fill: \"#C9A7C8\"",
+                SqlGraphEntity::Ord(_) => "shape: diamond; style.fill: \"#FFCFD3\"",
+                SqlGraphEntity::Hash(_)
+                | SqlGraphEntity::Aggregate(_)
+                | SqlGraphEntity::Trigger(_) => "shape: diamond; style.fill: \"#FFE4E0\"",
+                SqlGraphEntity::CustomSql(_) => "shape: page",
+                SqlGraphEntity::ExtensionRoot(_) => "shape: cylinder",
+            };
+            let label = node.dot_identifier().replace('\\', "\\\\").replace('"', "\\\"");
+            generated.push_str(&format!("n{}: \"{label}\" {{ {style} }}\n", index.index()));
+        }
+        generated.push('\n');
+        for edge in self.graph.edge_indices() {
+            let (source, target) =
+                self.graph.edge_endpoints(edge).expect("edge index came from the graph");
+            let style = match self.graph[edge] {
+                SqlGraphRelationship::RequiredBy => "style.stroke: gray",
+                SqlGraphRelationship::RequiredByArg => "style.stroke: black",
+                SqlGraphRelationship::RequiredByReturn => {
+                    "style.stroke: black; style.stroke-dash: 3"
+                }
+            };
+            generated.push_str(&format!(
+                "n{} -> n{}: {{ {style} }}\n",
+                source.index(),
+                target.index()
+            ));
+        }
+
+        let path = Path::new(file.as_ref());
+
+        let parent = path.parent();
+        if let Some(parent) = parent {
+            create_dir_all(parent)?;
+        }
+        let mut out = File::create(path)?;
+        write!(out, "{}", generated)?;
+        Ok(())
+    }
+
+    /// The entities which depend on each other, directly or indirectly, including `node`.
+    fn cycle_containing(&self, node: NodeIndex) -> Vec<NodeIndex> {
+        petgraph::algo::tarjan_scc(&self.graph)
+            .into_iter()
+            .find(|component| component.contains(&node))
+            .unwrap_or_else(|| vec![node])
+    }
+
     pub fn schema_alias_of(&self, item_index: &NodeIndex) -> Option<String> {
         self.graph
             .neighbors_undirected(*item_index)
             .flat_map(|neighbor_index| match &self.graph[neighbor_index] {
-                SqlGraphEntity::Schema(s) => Some(String::from(s.name)),
+                SqlGraphEntity::Schema(s) => Some(self.schema_name(s)),
                 SqlGraphEntity::ExtensionRoot(control) => {
                     if !control.relocatable {
                         control.schema.clone()
@@ -410,6 +481,11 @@ impl PgrxSql {
             .next()
     }
 
+    /// The name of `schema` in SQL, quoted if it's nested in other schemas
+    pub fn schema_name(&self, schema: &SchemaEntity) -> String {
+        quote_schema_name(&nested_schema_name(&self.schemas, schema))
+    }
+
     pub fn schema_prefix_for(&self, target: &NodeIndex) -> String {
         self.schema_alias_of(target)
             .map(|v| (v + ".").to_string())
@@ -419,7 +495,15 @@ impl PgrxSql {
     pub fn to_sql(&self) -> eyre::Result<String> {
         let mut full_sql = String::new();
         for step_id in petgraph::algo::toposort(&self.graph, None).map_err(|e| {
-            eyre!("Failed to toposort SQL entities, node with cycle: {:?}", self.graph[e.node_id()])
+            let cycle = self
+                .cycle_containing(e.node_id())
+                .into_iter()
+                .map(|index| format!("  {}", self.graph[index].dot_identifier()))
+                .collect::<Vec<_>>()
+                .join("\n");
+            eyre!(
+                "Failed to toposort SQL entities, these entities form a dependency cycle (use `--dot` to inspect the graph):\n{cycle}"
+            )
         })? {
             let step = &self.graph[step_id];
 
@@ -462,6 +546,21 @@ impl PgrxSql {
             String::from("MODULE_PATHNAME")
         };
     }
+
+    /// `CREATE OR REPLACE FUNCTION` statements for every `#[pg_extern]` function which pgrx
+    /// generates the SQL for, loading them from `module_pathname` instead
+    pub fn function_replacements(&self, module_pathname: &str) -> eyre::Result<Vec<String>> {
+        let mut externs = self
+            .externs
+            .iter()
+            .filter(|(item, _)| {
+                let config = &item.to_sql_config;
+                config.enabled && config.content.is_none() && config.callback.is_none()
+            })
+            .collect::<Vec<_>>();
+        externs.sort_by_key(|(_, index)| **index);
+        externs.into_iter().map(|(item, _)| item.replacement_sql(self, module_pathname)).collect()
+    }
 }
 
 fn build_base_edges(
@@ -667,8 +766,46 @@ fn connect_schemas(
     schemas: &HashMap<SchemaEntity, NodeIndex>,
     root: NodeIndex,
 ) {
-    for (_item, &index) in schemas {
+    for (item, &index) in schemas {
         graph.add_edge(root, index, SqlGraphRelationship::RequiredBy);
+
+        // create schemas after those they're nested in, so they're listed together
+        for (parent, &parent_index) in schemas {
+            if is_nested_in(item, parent) {
+                graph.add_edge(parent_index, index, SqlGraphRelationship::RequiredBy);
+            }
+        }
+    }
+}
+
+/// Is `schema`'s module somewhere inside `parent`'s module?
+fn is_nested_in(schema: &SchemaEntity, parent: &SchemaEntity) -> bool {
+    schema.module_path.strip_prefix(parent.module_path).map_or(false, |rest| rest.starts_with("::"))
+}
+
+/// The unquoted name of `schema`.  A `#[pg_schema(nested)]` module names the schema made of its
+/// enclosing schema's name and its own, joined with a dot, so `a::b` is the schema `"a.b"`.  Any
+/// other `#[pg_schema]` module names the schema after itself alone.
+fn nested_schema_name(schemas: &HashMap<SchemaEntity, NodeIndex>, schema: &SchemaEntity) -> String {
+    let parent = schemas
+        .keys()
+        .filter(|parent| is_nested_in(schema, parent))
+        .max_by_key(|parent| parent.module_path.len());
+    match parent {
+        Some(parent) if schema.nested => {
+            format!("{}.{}", nested_schema_name(schemas, parent), schema.name)
+        }
+        _ => schema.name.to_string(),
+    }
+}
+
+/// Quote a schema name made of nested schemas' names, whose dots would otherwise be read as
+/// qualifying one name with another
+pub(crate) fn quote_schema_name(name: &str) -> String {
+    if name.contains('.') {
+        format!("\"{name}\"")
+    } else {
+        name.to_string()
     }
 }
 
@@ -726,6 +863,7 @@ fn initialize_types(
 fn connect_types(
     graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
     types: &HashMap<PostgresTypeEntity, NodeIndex>,
+    enums: &HashMap<PostgresEnumEntity, NodeIndex>,
     schemas: &HashMap<SchemaEntity, NodeIndex>,
 ) {
     for (item, &index) in types {
@@ -737,6 +875,20 @@ fn connect_types(
             item.module_path,
             schemas,
         );
+
+        // a type made of other custom types comes after them
+        for field_type in &item.field_types {
+            for (other, &other_index) in types {
+                if other_index != index && other.id_matches(field_type) {
+                    graph.add_edge(other_index, index, SqlGraphRelationship::RequiredBy);
+                }
+            }
+            for (other, &other_index) in enums {
+                if other.id_matches(field_type) {
+                    graph.add_edge(other_index, index, SqlGraphRelationship::RequiredBy);
+                }
+            }
+        }
     }
 }
 
@@ -873,7 +1025,7 @@ fn connect_externs(
                 }
                 crate::ExternArgs::Schema(declared_schema_name) => {
                     for (schema, schema_index) in schemas {
-                        if schema.name == declared_schema_name {
+                        if nested_schema_name(schemas, schema) == *declared_schema_name {
                             graph.add_edge(*schema_index, index, SqlGraphRelationship::RequiredBy);
                             found_schema_declaration = true;
                         }
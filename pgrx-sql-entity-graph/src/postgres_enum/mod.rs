@@ -73,8 +73,9 @@ impl PostgresEnum {
     pub fn from_derive_input(
         derive_input: DeriveInput,
     ) -> Result<CodeEnrichment<Self>, syn::Error> {
-        let to_sql_config =
-            ToSqlConfig::from_attributes(derive_input.attrs.as_slice())?.unwrap_or_default();
+        let to_sql_config = ToSqlConfig::from_attributes(derive_input.attrs.as_slice())?
+            .unwrap_or_default()
+            .with_cfg(&derive_input.attrs);
         let data_enum = match derive_input.data {
             syn::Data::Enum(data_enum) => data_enum,
             syn::Data::Union(_) | syn::Data::Struct(_) => {
@@ -174,8 +175,9 @@ impl ToRustCodeTokens for PostgresEnum {}
 impl Parse for CodeEnrichment<PostgresEnum> {
     fn parse(input: ParseStream) -> Result<Self, syn::Error> {
         let parsed: ItemEnum = input.parse()?;
-        let to_sql_config =
-            ToSqlConfig::from_attributes(parsed.attrs.as_slice())?.unwrap_or_default();
+        let to_sql_config = ToSqlConfig::from_attributes(parsed.attrs.as_slice())?
+            .unwrap_or_default()
+            .with_cfg(&parsed.attrs);
         PostgresEnum::new(parsed.ident, parsed.generics, parsed.variants, to_sql_config)
     }
 }
@@ -82,20 +82,23 @@ impl SqlGraphIdentifier for PostgresOrdEntity {
 }
 
 impl ToSql for PostgresOrdEntity {
-    fn to_sql(&self, _context: &PgrxSql) -> eyre::Result<String> {
+    fn to_sql(&self, context: &PgrxSql) -> eyre::Result<String> {
+        // the type, its comparison function and its operator class all live in the schema of the derive
+        let schema = context.schema_prefix_for(&context.ords[self]);
         let sql = format!("\n\
                             -- {file}:{line}\n\
                             -- {full_path}\n\
-                            CREATE OPERATOR FAMILY {name}_btree_ops USING btree;\n\
-                            CREATE OPERATOR CLASS {name}_btree_ops DEFAULT FOR TYPE {name} USING btree FAMILY {name}_btree_ops AS\n\
+                            CREATE OPERATOR FAMILY {schema}{name}_btree_ops USING btree;\n\
+                            CREATE OPERATOR CLASS {schema}{name}_btree_ops DEFAULT FOR TYPE {schema}{name} USING btree FAMILY {schema}{name}_btree_ops AS\n\
                                   \tOPERATOR 1 <,\n\
                                   \tOPERATOR 2 <=,\n\
                                   \tOPERATOR 3 =,\n\
                                   \tOPERATOR 4 >=,\n\
                                   \tOPERATOR 5 >,\n\
-                                  \tFUNCTION 1 {cmp_fn_name}({name}, {name});\
+                                  \tFUNCTION 1 {schema}{cmp_fn_name}({schema}{name}, {schema}{name});\
                             ",
                           name = self.name,
+                          schema = schema,
                           full_path = self.full_path,
                           file = self.file,
                           line = self.line,
@@ -91,8 +91,9 @@ impl PostgresOrd {
     pub fn from_derive_input(
         derive_input: DeriveInput,
     ) -> Result<CodeEnrichment<Self>, syn::Error> {
-        let to_sql_config =
-            ToSqlConfig::from_attributes(derive_input.attrs.as_slice())?.unwrap_or_default();
+        let to_sql_config = ToSqlConfig::from_attributes(derive_input.attrs.as_slice())?
+            .unwrap_or_default()
+            .with_cfg(&derive_input.attrs);
         Self::new(derive_input.ident, to_sql_config)
     }
 }
@@ -139,7 +140,8 @@ impl Parse for CodeEnrichment<PostgresOrd> {
             Item::Struct(item) => (item.ident.clone(), item.attrs.as_slice()),
             _ => return Err(syn::Error::new(input.span(), "expected enum or struct")),
         };
-        let to_sql_config = ToSqlConfig::from_attributes(attrs)?.unwrap_or_default();
+        let to_sql_config =
+            ToSqlConfig::from_attributes(attrs)?.unwrap_or_default().with_cfg(attrs);
         PostgresOrd::new(ident, to_sql_config)
     }
 }
@@ -36,13 +36,38 @@ pub struct PostgresTypeEntity {
     pub in_fn_module_path: String,
     pub out_fn: &'static str,
     pub out_fn_module_path: String,
+    /// The binary output function `#[sendrecvfuncs]` generates, in the type's own module
+    pub send_fn: Option<&'static str>,
+    /// The binary input function `#[sendrecvfuncs]` generates, in the type's own module
+    pub recv_fn: Option<&'static str>,
     pub to_sql_config: ToSqlConfigEntity,
+    /// The type's TOAST strategy: `plain`, `external`, `extended`, or `main`
+    pub storage: &'static str,
+    /// The type's storage alignment, `char`, `int2`, `int4`, or `double`, if not Postgres' default
+    pub alignment: Option<&'static str>,
+    /// The `TypeId` of each of the type's fields, so that any custom types it's made of are
+    /// created before it
+    pub field_types: Vec<core::any::TypeId>,
 }
 
 impl PostgresTypeEntity {
     pub fn id_matches(&self, candidate: &core::any::TypeId) -> bool {
         self.mappings.iter().any(|tester| *candidate == tester.id)
     }
+
+    /// Is the function at `full_path` one of this type's I/O functions, which are created along
+    /// with the type rather than on their own?
+    pub fn is_io_fn(&self, full_path: &str) -> bool {
+        let is_in_fn =
+            full_path.starts_with(&self.in_fn_module_path) && full_path.ends_with(self.in_fn);
+        let is_out_fn =
+            full_path.starts_with(&self.out_fn_module_path) && full_path.ends_with(self.out_fn);
+        let is_send_recv_fn = [self.send_fn, self.recv_fn]
+            .into_iter()
+            .flatten()
+            .any(|f| full_path == format!("{}::{f}", self.module_path));
+        is_in_fn || is_out_fn || is_send_recv_fn
+    }
 }
 
 impl From<PostgresTypeEntity> for SqlGraphEntity {
@@ -139,6 +164,36 @@ impl ToSql for PostgresTypeEntity {
             .ok_or_else(|| eyre!("Could not find out_fn graph entity."))?;
         let out_fn_sql = out_fn.to_sql(context)?;
 
+        // the binary send/recv functions, which #[sendrecvfuncs] generates beside the type
+        let mut send_recv_fns_sql = String::new();
+        let mut send_recv_clauses = String::new();
+        if let (Some(send_fn), Some(recv_fn)) = (item.send_fn, item.recv_fn) {
+            for (clause, fn_name) in [("RECEIVE", recv_fn), ("SEND", send_fn)] {
+                let fn_path = format!("{}::{fn_name}", item.module_path);
+                let (fn_graph_index, func) = context
+                    .graph
+                    .neighbors_undirected(self_index)
+                    .find_map(|neighbor| match &context.graph[neighbor] {
+                        SqlGraphEntity::Function(func) if func.full_path == fn_path => {
+                            Some((neighbor, func))
+                        }
+                        _ => None,
+                    })
+                    .ok_or_else(|| eyre!("Could not find `{fn_path}` graph entity."))?;
+                send_recv_fns_sql.push('\n');
+                send_recv_fns_sql.push_str(&func.to_sql(context)?);
+                send_recv_clauses.push_str(&format!(
+                    "\t{clause} = {schema_prefix}{fn_name}, /* {fn_path} */\n",
+                    schema_prefix = context.schema_prefix_for(&fn_graph_index),
+                ));
+            }
+        }
+
+        let alignment_clause = item
+            .alignment
+            .map(|alignment| format!("\tALIGNMENT = {alignment},\n"))
+            .unwrap_or_default();
+
         let shell_type = format!(
             "\n\
                 -- {file}:{line}\n\
@@ -160,7 +215,9 @@ impl ToSql for PostgresTypeEntity {
                     \tINTERNALLENGTH = variable,\n\
                     \tINPUT = {schema_prefix_in_fn}{in_fn}, /* {in_fn_path} */\n\
                     \tOUTPUT = {schema_prefix_out_fn}{out_fn}, /* {out_fn_path} */\n\
-                    \tSTORAGE = extended\n\
+                    {send_recv_clauses}\
+                    {alignment_clause}\
+                    \tSTORAGE = {storage}\n\
                 );\
             ",
             full_path = item.full_path,
@@ -174,8 +231,18 @@ impl ToSql for PostgresTypeEntity {
             schema_prefix_out_fn = context.schema_prefix_for(&out_fn_graph_index),
             out_fn = item.out_fn,
             out_fn_path = out_fn_path,
+            send_recv_clauses = send_recv_clauses,
+            alignment_clause = alignment_clause,
+            storage = item.storage,
         };
 
-        Ok(shell_type + "\n" + &in_fn_sql + "\n" + &out_fn_sql + "\n" + &materialized_type)
+        Ok(shell_type
+            + "\n"
+            + &in_fn_sql
+            + "\n"
+            + &out_fn_sql
+            + &send_recv_fns_sql
+            + "\n"
+            + &materialized_type)
     }
 }
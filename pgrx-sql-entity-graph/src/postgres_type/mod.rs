@@ -21,6 +21,7 @@ use crate::enrich::{ToEntityGraphTokens, ToRustCodeTokens};
 use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
 use quote::quote;
 use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
 use syn::{DeriveInput, Generics, ItemStruct};
 
 use crate::{CodeEnrichment, ToSqlConfig};
@@ -55,6 +56,10 @@ pub struct PostgresType {
     in_fn: Ident,
     out_fn: Ident,
     to_sql_config: ToSqlConfig,
+    storage: Option<String>,
+    alignment: Option<String>,
+    send_recv_fns: Option<(Ident, Ident)>,
+    field_types: Vec<syn::Type>,
 }
 
 impl PostgresType {
@@ -68,20 +73,106 @@ impl PostgresType {
         if !to_sql_config.overrides_default() {
             crate::ident_is_acceptable_to_postgres(&name)?;
         }
-        Ok(CodeEnrichment(Self { generics, name, in_fn, out_fn, to_sql_config }))
+        Ok(CodeEnrichment(Self {
+            generics,
+            name,
+            in_fn,
+            out_fn,
+            to_sql_config,
+            storage: None,
+            alignment: None,
+            send_recv_fns: None,
+            field_types: Vec::new(),
+        }))
+    }
+
+    /// Use the `{name}_send` and `{name}_recv` functions `#[sendrecvfuncs]` generates
+    fn with_send_recv(mut self, attrs: &[syn::Attribute]) -> Self {
+        if attrs.iter().any(|attr| attr.path.is_ident("sendrecvfuncs")) {
+            let name = self.name.to_string().to_lowercase();
+            self.send_recv_fns = Some((
+                Ident::new(&format!("{name}_send"), self.name.span()),
+                Ident::new(&format!("{name}_recv"), self.name.span()),
+            ));
+        }
+        self
+    }
+
+    /// Set the type's TOAST strategy, from a `#[storage = "..."]` attribute
+    fn with_storage(mut self, attrs: &[syn::Attribute]) -> Result<Self, syn::Error> {
+        for attr in attrs.iter().filter(|attr| attr.path.is_ident("storage")) {
+            let storage = match attr.parse_meta()? {
+                syn::Meta::NameValue(syn::MetaNameValue { lit: syn::Lit::Str(value), .. }) => {
+                    value.value()
+                }
+                other => {
+                    return Err(syn::Error::new(other.span(), "expected `#[storage = \"...\"]`"))
+                }
+            };
+            if !matches!(storage.as_str(), "plain" | "external" | "extended" | "main") {
+                return Err(syn::Error::new(
+                    attr.span(),
+                    "`storage` must be one of `plain`, `external`, `extended`, or `main`",
+                ));
+            }
+            self.storage = Some(storage);
+        }
+        Ok(self)
+    }
+
+    /// Set the type's storage alignment, from an `#[alignment = "..."]` attribute
+    fn with_alignment(mut self, attrs: &[syn::Attribute]) -> Result<Self, syn::Error> {
+        for attr in attrs.iter().filter(|attr| attr.path.is_ident("alignment")) {
+            let alignment = match attr.parse_meta()? {
+                syn::Meta::NameValue(syn::MetaNameValue { lit: syn::Lit::Str(value), .. }) => {
+                    value.value()
+                }
+                other => {
+                    return Err(syn::Error::new(other.span(), "expected `#[alignment = \"...\"]`"))
+                }
+            };
+            if !matches!(alignment.as_str(), "char" | "int2" | "int4" | "double") {
+                return Err(syn::Error::new(
+                    attr.span(),
+                    "`alignment` must be one of `char`, `int2`, `int4`, or `double`",
+                ));
+            }
+            self.alignment = Some(alignment);
+        }
+        Ok(self)
+    }
+
+    /// Remember the types of the type's fields, so the SQL for any custom types among them comes
+    /// first.  Generic types are left alone, as their fields' types aren't known here.
+    fn with_field_types<'a>(mut self, fields: impl IntoIterator<Item = &'a syn::Field>) -> Self {
+        if self.generics.type_params().next().is_none() {
+            self.field_types = fields
+                .into_iter()
+                .map(|field| {
+                    let mut ty = field.ty.clone();
+                    crate::lifetimes::staticize_lifetimes(&mut ty);
+                    ty
+                })
+                .collect();
+        }
+        self
     }
 
     pub fn from_derive_input(
         derive_input: DeriveInput,
     ) -> Result<CodeEnrichment<Self>, syn::Error> {
-        match derive_input.data {
-            syn::Data::Struct(_) | syn::Data::Enum(_) => {}
+        let fields: Vec<syn::Field> = match derive_input.data {
+            syn::Data::Struct(data) => data.fields.into_iter().collect(),
+            syn::Data::Enum(data) => {
+                data.variants.into_iter().flat_map(|variant| variant.fields).collect()
+            }
             syn::Data::Union(_) => {
                 return Err(syn::Error::new(derive_input.ident.span(), "expected struct or enum"))
             }
         };
-        let to_sql_config =
-            ToSqlConfig::from_attributes(derive_input.attrs.as_slice())?.unwrap_or_default();
+        let to_sql_config = ToSqlConfig::from_attributes(derive_input.attrs.as_slice())?
+            .unwrap_or_default()
+            .with_cfg(&derive_input.attrs);
         let funcname_in = Ident::new(
             &format!("{}_in", derive_input.ident).to_lowercase(),
             derive_input.ident.span(),
@@ -90,13 +181,20 @@ impl PostgresType {
             &format!("{}_out", derive_input.ident).to_lowercase(),
             derive_input.ident.span(),
         );
-        Self::new(
+        let CodeEnrichment(postgres_type) = Self::new(
             derive_input.ident,
             derive_input.generics,
             funcname_in,
             funcname_out,
             to_sql_config,
-        )
+        )?;
+        Ok(CodeEnrichment(
+            postgres_type
+                .with_storage(&derive_input.attrs)?
+                .with_alignment(&derive_input.attrs)?
+                .with_send_recv(&derive_input.attrs)
+                .with_field_types(&fields),
+        ))
     }
 }
 
@@ -142,6 +240,19 @@ impl ToEntityGraphTokens for PostgresType {
             syn::Ident::new(&format!("__pgrx_internals_type_{}", self.name), Span::call_site());
 
         let to_sql_config = &self.to_sql_config;
+        let storage = self.storage.as_deref().unwrap_or("extended");
+        let alignment = match &self.alignment {
+            Some(alignment) => quote! { Some(#alignment) },
+            None => quote! { None },
+        };
+        let (send_fn, recv_fn) = match &self.send_recv_fns {
+            Some((send_fn, recv_fn)) => {
+                (quote! { Some(stringify!(#send_fn)) }, quote! { Some(stringify!(#recv_fn)) })
+            }
+            None => (quote! { None }, quote! { None }),
+        };
+
+        let field_types = &self.field_types;
 
         quote! {
             unsafe impl #staticless_impl_generics ::pgrx::pgrx_sql_entity_graph::metadata::SqlTranslatable for #name #static_ty_generics #static_where_clauses {
@@ -182,6 +293,10 @@ impl ToEntityGraphTokens for PostgresType {
                     &mut mappings,
                     stringify!(#name).to_string()
                 );
+                ::pgrx::datum::WithVarlenaTypeIds::<#name #static_ty_generics>::register_toasted(
+                    &mut mappings,
+                    stringify!(#name).to_string()
+                );
                 let submission = ::pgrx::pgrx_sql_entity_graph::PostgresTypeEntity {
                     name: stringify!(#name),
                     file: file!(),
@@ -203,7 +318,12 @@ impl ToEntityGraphTokens for PostgresType {
                         let _ = path_items.pop(); // Drop the one we don't want.
                         path_items.join("::")
                     },
+                    send_fn: #send_fn,
+                    recv_fn: #recv_fn,
                     to_sql_config: #to_sql_config,
+                    storage: #storage,
+                    alignment: #alignment,
+                    field_types: vec![#(core::any::TypeId::of::<#field_types>()),*],
                 };
                 ::pgrx::pgrx_sql_entity_graph::SqlGraphEntity::Type(submission)
             }
@@ -216,12 +336,26 @@ impl ToRustCodeTokens for PostgresType {}
 impl Parse for CodeEnrichment<PostgresType> {
     fn parse(input: ParseStream) -> Result<Self, syn::Error> {
         let parsed: ItemStruct = input.parse()?;
-        let to_sql_config =
-            ToSqlConfig::from_attributes(parsed.attrs.as_slice())?.unwrap_or_default();
+        let to_sql_config = ToSqlConfig::from_attributes(parsed.attrs.as_slice())?
+            .unwrap_or_default()
+            .with_cfg(&parsed.attrs);
         let funcname_in =
             Ident::new(&format!("{}_in", parsed.ident).to_lowercase(), parsed.ident.span());
         let funcname_out =
             Ident::new(&format!("{}_out", parsed.ident).to_lowercase(), parsed.ident.span());
-        PostgresType::new(parsed.ident, parsed.generics, funcname_in, funcname_out, to_sql_config)
+        let CodeEnrichment(postgres_type) = PostgresType::new(
+            parsed.ident,
+            parsed.generics,
+            funcname_in,
+            funcname_out,
+            to_sql_config,
+        )?;
+        Ok(CodeEnrichment(
+            postgres_type
+                .with_storage(&parsed.attrs)?
+                .with_alignment(&parsed.attrs)?
+                .with_send_recv(&parsed.attrs)
+                .with_field_types(&parsed.fields),
+        ))
     }
 }
@@ -24,6 +24,8 @@ use crate::{SqlGraphEntity, SqlGraphIdentifier};
 pub struct SchemaEntity {
     pub module_path: &'static str,
     pub name: &'static str,
+    /// Declared with `#[pg_schema(nested)]`, so it's named after the schema it's nested in too
+    pub nested: bool,
     pub file: &'static str,
     pub line: u32,
 }
@@ -52,13 +54,13 @@ impl SqlGraphIdentifier for SchemaEntity {
 }
 
 impl ToSql for SchemaEntity {
-    fn to_sql(&self, _context: &PgrxSql) -> eyre::Result<String> {
+    fn to_sql(&self, context: &PgrxSql) -> eyre::Result<String> {
         let sql = format!(
             "\n\
                 -- {file}:{line}\n\
                 CREATE SCHEMA IF NOT EXISTS {name}; /* {module_path} */\
             ",
-            name = self.name,
+            name = context.schema_name(self),
             file = self.file,
             line = self.line,
             module_path = self.module_path,
@@ -44,9 +44,24 @@ use syn::ItemMod;
 #[derive(Debug, Clone)]
 pub struct Schema {
     pub module: ItemMod,
+    /// Declared with `#[pg_schema(nested)]`
+    pub nested: bool,
 }
 
 impl Schema {
+    /// Parse the arguments of `#[pg_schema(...)]`, of which the only one is `nested`
+    pub fn parse_args(&mut self, args: TokenStream2) -> Result<(), syn::Error> {
+        if args.is_empty() {
+            return Ok(());
+        }
+        let arg: syn::Ident = syn::parse2(args)?;
+        if arg != "nested" {
+            return Err(syn::Error::new(arg.span(), "expected `#[pg_schema(nested)]`"));
+        }
+        self.nested = true;
+        Ok(())
+    }
+
     /*
        It's necessary for `Schema` to handle the full `impl ToTokens` generation itself as the sql
        entity graph code has to be inside the same `mod {}` that the `#[pg_schema]` macro is
@@ -64,6 +79,7 @@ impl Schema {
     #[cfg(not(feature = "no-schema-generation"))]
     fn entity_tokens(&self) -> TokenStream2 {
         let ident = &self.module.ident;
+        let nested = self.nested;
         let postfix = {
             use std::hash::{Hash, Hasher};
 
@@ -92,6 +108,7 @@ impl Schema {
                 let submission = ::pgrx::pgrx_sql_entity_graph::SchemaEntity {
                         module_path: module_path!(),
                         name: stringify!(#ident),
+                        nested: #nested,
                         file: file!(),
                         line: line!(),
                     };
@@ -129,6 +146,6 @@ impl Parse for Schema {
     fn parse(input: ParseStream) -> Result<Self, syn::Error> {
         let module: ItemMod = input.parse()?;
         crate::ident_is_acceptable_to_postgres(&module.ident)?;
-        Ok(Self { module })
+        Ok(Self { module, nested: false })
     }
 }
@@ -33,19 +33,75 @@ use crate::SqlGraphEntity;
 ///
 /// When `callback` has a value, the corresponding `ToSql` implementation should invoke the
 /// callback instead of performing their default behavior.
+///
+/// `cfg` holds the item's own `#[cfg(...)]` attributes, and the item is left out of the schema
+/// entirely when they don't hold for the features it's generated with.
 #[derive(Default, Clone)]
 pub struct ToSqlConfigEntity {
     pub enabled: bool,
     pub callback: Option<ToSqlFn>,
     pub content: Option<&'static str>,
+    pub cfg: Option<&'static str>,
 }
 impl ToSqlConfigEntity {
     /// Helper used to implement traits (`Eq`, `Ord`, etc) despite `ToSqlFn` not
     /// having an implementation for them.
     #[inline]
-    fn fields(&self) -> (bool, Option<&str>, Option<usize>) {
-        (self.enabled, self.content, self.callback.map(|f| f as usize))
+    fn fields(&self) -> (bool, Option<&str>, Option<usize>, Option<&str>) {
+        (self.enabled, self.content, self.callback.map(|f| f as usize), self.cfg)
+    }
+
+    /// Whether the item's `#[cfg(...)]` attributes hold for `features`.
+    ///
+    /// Only `feature = "..."` predicates are decided here.  Anything else, like `test` or
+    /// `target_os = "..."`, held when the item was compiled, so whatever depends on it is given
+    /// the benefit of the doubt.
+    pub fn cfg_holds(&self, features: &[String]) -> bool {
+        // `None` for a predicate which can't be decided from the features alone
+        fn decide(meta: &syn::Meta, features: &[String]) -> Option<bool> {
+            let nested = |list: &syn::MetaList| {
+                list.nested
+                    .iter()
+                    .map(|nested| match nested {
+                        syn::NestedMeta::Meta(meta) => decide(meta, features),
+                        syn::NestedMeta::Lit(_) => None,
+                    })
+                    .collect::<Vec<_>>()
+            };
+            match meta {
+                syn::Meta::NameValue(nv) if nv.path.is_ident("feature") => match &nv.lit {
+                    syn::Lit::Str(feature) => Some(features.contains(&feature.value())),
+                    _ => None,
+                },
+                syn::Meta::List(list) if list.path.is_ident("all") => {
+                    let nested = nested(list);
+                    if nested.contains(&Some(false)) {
+                        Some(false)
+                    } else {
+                        nested.into_iter().all(|decided| decided.is_some()).then_some(true)
+                    }
+                }
+                syn::Meta::List(list) if list.path.is_ident("any") => {
+                    let nested = nested(list);
+                    if nested.contains(&Some(true)) {
+                        Some(true)
+                    } else {
+                        nested.into_iter().all(|decided| decided.is_some()).then_some(false)
+                    }
+                }
+                syn::Meta::List(list) if list.path.is_ident("not") => {
+                    nested(list).first().copied().flatten().map(|decided| !decided)
+                }
+                _ => None,
+            }
+        }
+
+        match self.cfg.map(syn::parse_str::<syn::Meta>) {
+            Some(Ok(meta)) => decide(&meta, features).unwrap_or(true),
+            _ => true,
+        }
     }
+
     /// Given a SqlGraphEntity, this function converts it to SQL based on the current configuration.
     ///
     /// If the config overrides the default behavior (i.e. using the `ToSql` trait), then `Some(eyre::Result)`
@@ -136,11 +192,67 @@ impl std::hash::Hash for ToSqlConfigEntity {
 }
 impl std::fmt::Debug for ToSqlConfigEntity {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let (enabled, content, callback) = self.fields();
+        let (enabled, content, callback, cfg) = self.fields();
         f.debug_struct("ToSqlConfigEntity")
             .field("enabled", &enabled)
             .field("callback", &callback)
             .field("content", &content)
+            .field("cfg", &cfg)
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{ToSqlConfig, ToSqlConfigEntity};
+
+    fn cfg_holds(item: syn::ItemFn, features: &[&str]) -> bool {
+        let cfg = ToSqlConfig::default().with_cfg(&item.attrs).cfg;
+        let config = ToSqlConfigEntity {
+            enabled: true,
+            callback: None,
+            content: None,
+            cfg: cfg.map(|cfg| &*Box::leak(cfg.into_boxed_str())),
+        };
+        config.cfg_holds(&features.iter().map(|f| f.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn cfg_features() {
+        let item: syn::ItemFn = syn::parse_quote! { fn f() {} };
+        assert!(cfg_holds(item, &[]));
+
+        let item: syn::ItemFn = syn::parse_quote! {
+            #[cfg(feature = "foo")]
+            fn f() {}
+        };
+        assert!(cfg_holds(item.clone(), &["foo"]));
+        assert!(!cfg_holds(item, &["bar"]));
+
+        let item: syn::ItemFn = syn::parse_quote! {
+            #[cfg(any(feature = "foo", feature = "bar"))]
+            #[cfg(not(feature = "baz"))]
+            fn f() {}
+        };
+        assert!(cfg_holds(item.clone(), &["bar"]));
+        assert!(!cfg_holds(item.clone(), &["bar", "baz"]));
+        assert!(!cfg_holds(item, &[]));
+    }
+
+    #[test]
+    fn cfg_undecided() {
+        // whatever isn't a feature held when the item was compiled
+        let item: syn::ItemFn = syn::parse_quote! {
+            #[cfg(any(test, feature = "pg_test"))]
+            fn f() {}
+        };
+        assert!(cfg_holds(item, &[]));
+
+        let item: syn::ItemFn = syn::parse_quote! {
+            #[cfg(all(not(target_os = "windows"), feature = "foo"))]
+            fn f() {}
+        };
+        assert!(cfg_holds(item.clone(), &["foo"]));
+        assert!(!cfg_holds(item, &[]));
+    }
+}
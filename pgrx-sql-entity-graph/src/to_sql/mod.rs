@@ -57,25 +57,27 @@ pub struct ToSqlConfig {
     pub enabled: bool,
     pub callback: Option<syn::Path>,
     pub content: Option<syn::LitStr>,
+    /// The item's `#[cfg(...)]` predicates, `all()`-ed together
+    pub cfg: Option<String>,
 }
 impl From<bool> for ToSqlConfig {
     fn from(enabled: bool) -> Self {
-        Self { enabled, callback: None, content: None }
+        Self { enabled, callback: None, content: None, cfg: None }
     }
 }
 impl From<syn::Path> for ToSqlConfig {
     fn from(path: syn::Path) -> Self {
-        Self { enabled: true, callback: Some(path), content: None }
+        Self { enabled: true, callback: Some(path), content: None, cfg: None }
     }
 }
 impl From<syn::LitStr> for ToSqlConfig {
     fn from(content: syn::LitStr) -> Self {
-        Self { enabled: true, callback: None, content: Some(content) }
+        Self { enabled: true, callback: None, content: Some(content), cfg: None }
     }
 }
 impl Default for ToSqlConfig {
     fn default() -> Self {
-        Self { enabled: true, callback: None, content: None }
+        Self { enabled: true, callback: None, content: None, cfg: None }
     }
 }
 
@@ -105,16 +107,23 @@ impl ToSqlConfig {
                             enabled: true,
                             callback: Some(callback_path.clone()),
                             content: None,
+                            cfg: None,
                         }));
                     }
                     ArgValue::Lit(Lit::Bool(ref b)) => {
-                        return Ok(Some(Self { enabled: b.value, callback: None, content: None }));
+                        return Ok(Some(Self {
+                            enabled: b.value,
+                            callback: None,
+                            content: None,
+                            cfg: None,
+                        }));
                     }
                     ArgValue::Lit(Lit::Str(ref s)) => {
                         return Ok(Some(Self {
                             enabled: true,
                             callback: None,
                             content: Some(s.clone()),
+                            cfg: None,
                         }));
                     }
                     ArgValue::Lit(ref other) => {
@@ -136,6 +145,24 @@ impl ToSqlConfig {
         }
     }
 
+    /// Record the `#[cfg(...)]` attributes among the item's `attrs`, so the schema leaves the item
+    /// out when generated with features they don't hold for.  Attributes on enclosing modules
+    /// can't be seen
+    pub fn with_cfg(mut self, attrs: &[Attribute]) -> Self {
+        let predicates = attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident("cfg"))
+            .filter_map(|attr| attr.parse_args::<syn::Meta>().ok())
+            .map(|meta| meta.to_token_stream().to_string())
+            .collect::<Vec<_>>();
+        self.cfg = match predicates.len() {
+            0 => None,
+            1 => predicates.into_iter().next(),
+            _ => Some(format!("all({})", predicates.join(", "))),
+        };
+        self
+    }
+
     pub fn overrides_default(&self) -> bool {
         self.enabled == false || self.callback.is_some() || self.content.is_some()
     }
@@ -146,12 +173,17 @@ impl ToTokens for ToSqlConfig {
         let enabled = self.enabled;
         let callback = &self.callback;
         let content = &self.content;
+        let cfg = match &self.cfg {
+            Some(cfg) => quote! { Some(#cfg) },
+            None => quote! { None },
+        };
         if let Some(callback_path) = callback {
             tokens.append_all(quote! {
                 ::pgrx::pgrx_sql_entity_graph::ToSqlConfigEntity {
                     enabled: #enabled,
                     callback: Some(#callback_path),
                     content: None,
+                    cfg: #cfg,
                 }
             });
             return;
@@ -162,6 +194,7 @@ impl ToTokens for ToSqlConfig {
                     enabled: #enabled,
                     callback: None,
                     content: Some(#sql),
+                    cfg: #cfg,
                 }
             });
             return;
@@ -171,6 +204,7 @@ impl ToTokens for ToSqlConfig {
                 enabled: #enabled,
                 callback: None,
                 content: None,
+                cfg: #cfg,
             }
         });
     }
@@ -33,6 +33,8 @@ pub struct UsedType {
     pub resolved_ty: syn::Type,
     /// Set via `composite_type!()`
     pub composite_type: Option<CompositeTypeMacro>,
+    /// Set via `domain!()`
+    pub domain: Option<String>,
     /// Set via `VariadicArray` or `variadic!()`
     pub variadic: bool,
     pub default: Option<String>,
@@ -47,6 +49,7 @@ impl UsedType {
         let original_ty = ty.clone();
         // There are several steps:
         // * Resolve the `default!()` macro
+        // * Resolve the `domain!()` macro
         // * Resolve the `variadic!()` macro
         // * Resolve `composite_type!()`
         // * Anonymize any lifetimes
@@ -70,6 +73,10 @@ impl UsedType {
             original => (original, None),
         };
 
+        // Resolve any `domain` macro
+        // This may be wrapped in an `Option`, but not any deeper.
+        let (resolved_ty, domain) = resolve_domain(resolved_ty)?;
+
         // Resolve any `variadic` macro
         // We do this first as it's **always** in the first position. It's not valid deeper in the type.
         let resolved_ty = match resolved_ty {
@@ -283,7 +290,16 @@ impl UsedType {
             original => (original, false, None, false),
         };
 
-        Ok(Self { original_ty, resolved_ty, optional, result, variadic, default, composite_type })
+        Ok(Self {
+            original_ty,
+            resolved_ty,
+            optional,
+            result,
+            variadic,
+            default,
+            composite_type,
+            domain,
+        })
     }
 
     pub fn entity_tokens(&self) -> syn::Expr {
@@ -295,6 +311,7 @@ impl UsedType {
         let variadic = &self.variadic;
         let optional = &self.optional.is_some();
         let default = (&self.default).iter();
+        let domain = (&self.domain).iter();
 
         syn::parse_quote! {
             ::pgrx::pgrx_sql_entity_graph::UsedTypeEntity {
@@ -308,6 +325,7 @@ impl UsedType {
                     path_items.join("::")
                 },
                 composite_type: None #( .unwrap_or(Some(#composite_type_iter)) )*,
+                domain: None #( .unwrap_or(Some(#domain)) )*,
                 variadic: #variadic,
                 default:  None #( .unwrap_or(Some(#default)) )*,
                 /// Set via the type being an `Option`.
@@ -329,6 +347,8 @@ pub struct UsedTypeEntity {
     pub full_path: &'static str,
     pub module_path: String,
     pub composite_type: Option<&'static str>,
+    /// Set via `domain!()`
+    pub domain: Option<&'static str>,
     pub variadic: bool,
     pub default: Option<&'static str>,
     /// Set via the type being an `Option`.
@@ -590,6 +610,46 @@ fn resolve_option_inner(
     }
 }
 
+fn resolve_domain(ty: syn::Type) -> syn::Result<(syn::Type, Option<String>)> {
+    match ty {
+        // domain!(..)
+        syn::Type::Macro(macro_pat) => {
+            let mac = &macro_pat.mac;
+            let archetype = mac.path.segments.last().expect("No last segment");
+            match archetype.ident.to_string().as_str() {
+                "domain" => {
+                    let out: DomainMacro = mac.parse_body()?;
+                    Ok((out.ty, Some(out.name.value())))
+                }
+                _ => Ok((syn::Type::Macro(macro_pat), None)),
+            }
+        }
+        // Option<domain!(..)>
+        syn::Type::Path(mut path) => {
+            let path_span = path.span();
+            let last = path
+                .path
+                .segments
+                .last_mut()
+                .ok_or(syn::Error::new(path_span, "Could not read last segment of path"))?;
+            let mut domain = None;
+            if last.ident == "Option" {
+                if let syn::PathArguments::AngleBracketed(path_arg) = &mut last.arguments {
+                    if let Some(syn::GenericArgument::Type(inner @ syn::Type::Macro(_))) =
+                        path_arg.args.first_mut()
+                    {
+                        let (inner_ty, inner_domain) = resolve_domain(inner.clone())?;
+                        *inner = inner_ty;
+                        domain = inner_domain;
+                    }
+                }
+            }
+            Ok((syn::Type::Path(path), domain))
+        }
+        original => Ok((original, None)),
+    }
+}
+
 fn handle_composite_type_macro(mac: &syn::Macro) -> syn::Result<CompositeTypeMacro> {
     let out: CompositeTypeMacro = mac.parse_body()?;
     Ok(out)
@@ -692,6 +752,21 @@ impl Parse for DefaultMacro {
     }
 }
 
+#[derive(Debug, Clone)]
+pub(crate) struct DomainMacro {
+    ty: syn::Type,
+    name: syn::LitStr,
+}
+
+impl Parse for DomainMacro {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        let ty = input.parse()?;
+        let _comma: Token![,] = input.parse()?;
+        let name = input.parse()?;
+        Ok(Self { ty, name })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CompositeTypeMacro {
     #[allow(dead_code)]
@@ -0,0 +1,7 @@
+-- pgrx_tests::tests::schema_snapshot_tests::tests::snapshot_add_one
+CREATE  FUNCTION tests."snapshot_add_one"(
+	"value" INT /* i32 */
+) RETURNS INT /* i32 */
+STRICT
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'snapshot_add_one_wrapper';
@@ -25,7 +25,10 @@ use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use sysinfo::{Pid, ProcessExt, System, SystemExt};
 
+mod schema_snapshot;
 mod shutdown;
+mod upgrade;
+pub use schema_snapshot::check_schema_snapshot;
 pub use shutdown::add_shutdown_hook;
 
 type LogLines = Arc<Mutex<HashMap<String, Vec<String>>>>;
@@ -109,10 +112,30 @@ where
     }
 }
 
+/// How a `#[pg_test]`'s transaction is set up before the test function is called
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TestPresets {
+    /// Such as `"SERIALIZABLE"`
+    pub isolation_level: Option<&'static str>,
+    /// `(name, value)` pairs, applied in order with `set_config(name, value, true)`
+    pub gucs: &'static [(&'static str, &'static str)],
+    /// A `timestamptz` to pin the clock at, see [`pgrx::fake_clock`]
+    pub fake_clock: Option<&'static str>,
+}
+
 pub fn run_test(
     sql_funcname: &str,
     expected_error: Option<&str>,
     postgresql_conf: Vec<&'static str>,
+) -> eyre::Result<()> {
+    run_test_with_presets(sql_funcname, expected_error, postgresql_conf, &TestPresets::default())
+}
+
+pub fn run_test_with_presets(
+    sql_funcname: &str,
+    expected_error: Option<&str>,
+    postgresql_conf: Vec<&'static str>,
+    presets: &TestPresets,
 ) -> eyre::Result<()> {
     let (loglines, system_session_id) = initialize_test_framework(postgresql_conf)?;
 
@@ -120,7 +143,8 @@ pub fn run_test(
 
     let result = client.transaction().map(|mut tx| {
         let schema = "tests"; // get_extension_schema();
-        let result = tx.simple_query(&format!("SELECT \"{schema}\".\"{sql_funcname}\"();"));
+        let result = apply_presets(&mut tx, presets)
+            .and_then(|()| tx.simple_query(&format!("SELECT \"{schema}\".\"{sql_funcname}\"();")));
 
         if result.is_ok() {
             // and abort the transaction when complete
@@ -179,6 +203,27 @@ pub fn run_test(
     }
 }
 
+fn apply_presets(
+    tx: &mut postgres::Transaction,
+    presets: &TestPresets,
+) -> Result<(), postgres::Error> {
+    // this has to come before anything which takes a snapshot, including `SELECT set_config()`
+    if let Some(isolation_level) = presets.isolation_level {
+        tx.batch_execute(&format!("SET TRANSACTION ISOLATION LEVEL {isolation_level};"))?;
+    }
+    for (name, value) in presets.gucs {
+        tx.execute("SELECT set_config($1, $2, true);", &[name, value])?;
+    }
+    if let Some(now) = presets.fake_clock {
+        tx.execute("SELECT set_config($1, $2, true);", &[&pgrx::fake_clock::FAKE_CLOCK_GUC, &now])?;
+        // an explicit `pg_catalog` is searched in the given position rather than first
+        tx.batch_execute(&format!(
+            "SELECT set_config('search_path', '{FAKE_CLOCK_SCHEMA}, pg_catalog, ' || current_setting('search_path'), true);"
+        ))?;
+    }
+    Ok(())
+}
+
 fn format_loglines(session_id: &str, loglines: &LogLines) -> String {
     let mut result = String::new();
 
@@ -214,6 +259,13 @@ fn initialize_test_framework(
         create_extension()?;
         state.installed = true;
         state.system_session_id = system_session_id;
+
+        if std::env::var("PGRX_TEST_UPGRADE_PATHS").unwrap_or("false".into()) == "true" {
+            let skip_missing_versions = std::env::var("PGRX_TEST_SKIP_MISSING_VERSIONS")
+                .unwrap_or("false".into())
+                == "true";
+            upgrade::check_upgrade_paths(skip_missing_versions)?;
+        }
     }
 
     Ok((state.loglines.clone(), state.system_session_id.clone()))
@@ -275,10 +327,10 @@ pub fn client() -> eyre::Result<(postgres::Client, String)> {
     Ok((client, session_id))
 }
 
-fn install_extension() -> eyre::Result<()> {
-    eprintln!("installing extension");
+/// A `cargo pgrx <subcommand>` for the extension under test, built the way `cargo pgrx test` asked
+/// for, with the `pg_test` feature
+fn cargo_pgrx_for_extension(subcommand: &str) -> eyre::Result<Command> {
     let profile = std::env::var("PGRX_BUILD_PROFILE").unwrap_or("debug".into());
-    let no_schema = std::env::var("PGRX_NO_SCHEMA").unwrap_or("false".into()) == "true";
     let mut features = std::env::var("PGRX_FEATURES")
         .unwrap_or("".to_string())
         .split_ascii_whitespace()
@@ -300,12 +352,10 @@ fn install_extension() -> eyre::Result<()> {
 
     let mut command = cargo_pgrx();
     command
-        .arg("install")
+        .arg(subcommand)
         .arg("--test")
         .arg("--pg-config")
         .arg(pg_config.path().ok_or(eyre!("No pg_config found"))?)
-        .stdout(Stdio::inherit())
-        .stderr(Stdio::piped())
         .env("CARGO_TARGET_DIR", get_target_dir()?);
 
     if let Ok(manifest_path) = std::env::var("PGRX_MANIFEST_PATH") {
@@ -317,6 +367,9 @@ fn install_extension() -> eyre::Result<()> {
         command.env("RUST_LOG", rust_log);
     }
 
+    // schema generation loads the instrumented extension into `cargo pgrx`
+    configure_sanitizer(&mut command);
+
     if !features.is_empty() {
         command.arg("--features");
         command.arg(features.into_iter().collect::<Vec<_>>().join(" "));
@@ -342,6 +395,16 @@ fn install_extension() -> eyre::Result<()> {
         }
     }
 
+    Ok(command)
+}
+
+fn install_extension() -> eyre::Result<()> {
+    eprintln!("installing extension");
+    let no_schema = std::env::var("PGRX_NO_SCHEMA").unwrap_or("false".into()) == "true";
+
+    let mut command = cargo_pgrx_for_extension("install")?;
+    command.stdout(Stdio::inherit()).stderr(Stdio::piped());
+
     if no_schema {
         command.arg("--no-schema");
     }
@@ -459,6 +522,7 @@ fn start_pg(loglines: LogLines) -> eyre::Result<String> {
         .args(["-c", "log_destination=stderr", "-c", "logging_collector=off"])
         .stdout(Stdio::inherit())
         .stderr(Stdio::piped());
+    configure_sanitizer(&mut command);
 
     let command_str = format!("{command:?}");
 
@@ -469,6 +533,20 @@ fn start_pg(loglines: LogLines) -> eyre::Result<String> {
     Ok(session_id)
 }
 
+/// When `cargo pgrx test --sanitizer` built the extension, run `command` under the sanitizer's
+/// runtime, which has to be preloaded since Postgres itself isn't instrumented
+fn configure_sanitizer(command: &mut Command) {
+    if std::env::var_os("PGRX_SANITIZER").is_none() {
+        return;
+    }
+    if let Some(preload) = std::env::var_os("PGRX_SANITIZER_PRELOAD") {
+        command.env("LD_PRELOAD", preload);
+    }
+    if let Some(options) = std::env::var_os("PGRX_SANITIZER_OPTIONS") {
+        command.env("ASAN_OPTIONS", options);
+    }
+}
+
 fn wait_for_pidfile() -> Result<(), eyre::Report> {
     const MAX_PIDFILE_RETRIES: usize = 10;
 
@@ -613,9 +691,39 @@ fn create_extension() -> eyre::Result<()> {
         &extension_name
     ))?;
 
+    query_wrapper(Some(fake_clock_sql()), None, |query, _| {
+        client.simple_query(query.unwrap().as_str())
+    })
+    .wrap_err("There was an issue creating the fake clock functions in Postgres: ")?;
+
     Ok(())
 }
 
+const FAKE_CLOCK_SCHEMA: &str = "pgrx_fake_clock";
+
+/// Versions of the SQL functions reporting the current time which follow the `pgrx.fake_clock`
+/// setting, for `#[pg_test(fake_clock = "...")]` to put ahead of `pg_catalog` in the `search_path`
+fn fake_clock_sql() -> String {
+    let guc = pgrx::fake_clock::FAKE_CLOCK_GUC;
+    let mut sql = format!(
+        "CREATE SCHEMA IF NOT EXISTS {FAKE_CLOCK_SCHEMA};\n\
+         GRANT USAGE ON SCHEMA {FAKE_CLOCK_SCHEMA} TO PUBLIC;\n"
+    );
+    for (func, volatility) in [
+        ("now", "STABLE"),
+        ("transaction_timestamp", "STABLE"),
+        ("statement_timestamp", "STABLE"),
+        ("clock_timestamp", "VOLATILE"),
+    ] {
+        sql.push_str(&format!(
+            "CREATE OR REPLACE FUNCTION {FAKE_CLOCK_SCHEMA}.{func}() RETURNS timestamptz \
+             {volatility} LANGUAGE sql \
+             AS $$ SELECT coalesce(nullif(current_setting('{guc}', true), '')::timestamptz, pg_catalog.{func}()) $$;\n"
+        ));
+    }
+    sql
+}
+
 fn get_extension_name() -> eyre::Result<String> {
     // We could replace this with the following if cargo adds the lib name on env var on tests/runs.
     // https://github.com/rust-lang/cargo/issues/11966
@@ -0,0 +1,371 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Golden-file checks of the SQL generated for the extension under test.
+//!
+//! The generated schema is rendered with `cargo pgrx schema`, normalized so that it doesn't change
+//! with source line numbers, and compared against a snapshot checked in next to the crate.  Items
+//! keep the order they're emitted in, since that order is what `CREATE EXTENSION` runs.  Set
+//! `PGRX_UPDATE_SNAPSHOTS=1` to accept the current output.
+use std::path::Path;
+
+use eyre::{eyre, WrapErr};
+use once_cell::sync::Lazy;
+use owo_colors::OwoColorize;
+use regex::Regex;
+
+use super::{cargo_pgrx_for_extension, get_target_dir};
+
+const UPDATE_SNAPSHOTS_ENV: &str = "PGRX_UPDATE_SNAPSHOTS";
+
+/// Lines of unchanged SQL shown around each change
+const DIFF_CONTEXT: usize = 3;
+
+/// When the changed lines of the snapshot times those of the generated SQL (after trimming the
+/// common prefix and suffix) exceed this, they're shown as one block rather than computing a
+/// minimal diff, which needs a table of that size
+const DIFF_MAX_CELLS: usize = 1 << 20;
+
+/// The `-- src/lib.rs:42` line which starts every item of the generated schema
+static ITEM_MARKER: Lazy<Regex> = Lazy::new(|| Regex::new(r"^-- \S+:\d+$").unwrap());
+
+/// Compare the generated SQL schema of the extension under test against the snapshot at `path`,
+/// relative to the crate's `Cargo.toml`.
+///
+/// This is meant for a plain `#[test]`, not a `#[pg_test]`:
+///
+/// ```rust,ignore
+/// #[test]
+/// fn sql_schema() {
+///     pgrx_tests::schema_snapshot!("tests/schema.sql");
+/// }
+/// ```
+///
+/// Passing `module = "my_ext::some::module"` limits the snapshot to the items declared in that
+/// module and its children, which keeps it stable when unrelated parts of the extension change.
+///
+/// When the snapshot doesn't match, the test panics with a diff and the new output is written
+/// next to the snapshot as `<path>.new`.  Running the test with `PGRX_UPDATE_SNAPSHOTS=1`
+/// overwrites the snapshot instead.
+#[macro_export]
+macro_rules! schema_snapshot {
+    ($path:literal) => {
+        $crate::check_schema_snapshot(
+            ::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join($path),
+            None,
+        )
+    };
+    ($path:literal, module = $module:literal) => {
+        $crate::check_schema_snapshot(
+            ::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join($path),
+            Some($module),
+        )
+    };
+}
+
+/// The function behind [`schema_snapshot!`], for a snapshot path that isn't a literal.
+pub fn check_schema_snapshot(path: impl AsRef<Path>, module: Option<&str>) {
+    if let Err(e) = try_check_schema_snapshot(path.as_ref(), module) {
+        panic!("{e:?}")
+    }
+}
+
+fn try_check_schema_snapshot(path: &Path, module: Option<&str>) -> eyre::Result<()> {
+    let actual = normalize(&generate_schema()?, module);
+    let update = std::env::var_os(UPDATE_SNAPSHOTS_ENV).map(|v| v != "0").unwrap_or(false);
+    let new_path = path.with_extension(match path.extension() {
+        Some(ext) => format!("{}.new", ext.to_string_lossy()),
+        None => "new".to_string(),
+    });
+
+    let expected = match std::fs::read_to_string(path) {
+        Ok(expected) => Some(expected),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e).wrap_err_with(|| format!("Unable to read `{}`", path.display())),
+    };
+
+    if expected.as_deref() == Some(actual.as_str()) {
+        let _ = std::fs::remove_file(&new_path);
+        return Ok(());
+    }
+
+    if update {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, &actual)
+            .wrap_err_with(|| format!("Unable to write `{}`", path.display()))?;
+        let _ = std::fs::remove_file(&new_path);
+        eprintln!("{} `{}`", "updated schema snapshot".bold().green(), path.display());
+        return Ok(());
+    }
+
+    std::fs::write(&new_path, &actual)
+        .wrap_err_with(|| format!("Unable to write `{}`", new_path.display()))?;
+
+    match expected {
+        None => Err(eyre!(
+            "No schema snapshot at `{}`.  The generated SQL was written to `{}`; rerun with \
+             `{UPDATE_SNAPSHOTS_ENV}=1` to accept it.",
+            path.display(),
+            new_path.display()
+        )),
+        Some(expected) => Err(eyre!(
+            "The generated SQL schema differs from the snapshot at `{}`:\n\n{}\n\nThe generated \
+             SQL was written to `{}`; rerun with `{UPDATE_SNAPSHOTS_ENV}=1` to accept it.",
+            path.display(),
+            diff(&expected, &actual),
+            new_path.display()
+        )),
+    }
+}
+
+fn generate_schema() -> eyre::Result<String> {
+    let out = get_target_dir()?.join("pgrx-schema-snapshot.sql");
+    let mut command = cargo_pgrx_for_extension("schema")?;
+    command.arg("--out").arg(&out);
+
+    let command_str = format!("{:?}", command);
+    let output = command.output().wrap_err_with(|| {
+        format!("Failed to spawn process for generating the schema using command: '{command_str}'")
+    })?;
+
+    if !output.status.success() {
+        return Err(eyre!(
+            "Failure generating the schema using command: {}\n\n{}{}",
+            command_str,
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    std::fs::read_to_string(&out).wrap_err_with(|| format!("Unable to read `{}`", out.display()))
+}
+
+/// Split the generated SQL into its items and drop the header and each item's source location, so
+/// the result only changes when the SQL does.  With a `module`, only the items declared in it are
+/// kept.
+fn normalize(sql: &str, module: Option<&str>) -> String {
+    let mut items = Vec::new();
+    let mut current: Option<Vec<&str>> = None;
+
+    for line in sql.lines().map(str::trim_end) {
+        if ITEM_MARKER.is_match(line) {
+            items.extend(current.take().map(|lines| finish_item(&lines)));
+            current = Some(Vec::new());
+        } else if let Some(lines) = current.as_mut() {
+            lines.push(line);
+        }
+    }
+    items.extend(current.map(|lines| finish_item(&lines)));
+    items.retain(|item| !item.is_empty());
+    if let Some(module) = module {
+        // each item's first line, after its location, is `-- path::to::item`
+        let prefix = format!("-- {module}::");
+        items.retain(|item| item.starts_with(&prefix));
+    }
+
+    let mut normalized = items.join("\n\n");
+    normalized.push('\n');
+    normalized
+}
+
+fn finish_item(lines: &[&str]) -> String {
+    let start = lines.iter().position(|line| !line.is_empty()).unwrap_or(lines.len());
+    let end = lines.iter().rposition(|line| !line.is_empty()).map(|i| i + 1).unwrap_or(start);
+    lines[start..end].join("\n")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Change<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// A unified-style line diff, `-` for the snapshot and `+` for the generated SQL.
+fn diff(expected: &str, actual: &str) -> String {
+    let changes = line_changes(expected, actual);
+    let mut shown = vec![false; changes.len()];
+    for (i, change) in changes.iter().enumerate() {
+        if !matches!(change, Change::Same(_)) {
+            let from = i.saturating_sub(DIFF_CONTEXT);
+            let to = (i + DIFF_CONTEXT + 1).min(changes.len());
+            shown[from..to].iter_mut().for_each(|s| *s = true);
+        }
+    }
+
+    let mut out = Vec::new();
+    for (i, change) in changes.iter().enumerate() {
+        if !shown[i] {
+            continue;
+        }
+        if i > 0 && !shown[i - 1] {
+            out.push("...".dimmed().to_string());
+        }
+        out.push(match change {
+            Change::Same(line) => format!("  {line}"),
+            Change::Removed(line) => format!("- {line}").red().to_string(),
+            Change::Added(line) => format!("+ {line}").green().to_string(),
+        });
+    }
+    out.join("\n")
+}
+
+fn line_changes<'a>(expected: &'a str, actual: &'a str) -> Vec<Change<'a>> {
+    let expected = expected.lines().collect::<Vec<_>>();
+    let actual = actual.lines().collect::<Vec<_>>();
+
+    let prefix = expected.iter().zip(&actual).take_while(|(e, a)| e == a).count();
+    let suffix = expected[prefix..]
+        .iter()
+        .rev()
+        .zip(actual[prefix..].iter().rev())
+        .take_while(|(e, a)| e == a)
+        .count();
+    let old = &expected[prefix..expected.len() - suffix];
+    let new = &actual[prefix..actual.len() - suffix];
+
+    let mut changes = expected[..prefix].iter().map(|line| Change::Same(line)).collect::<Vec<_>>();
+    if old.len().saturating_mul(new.len()) > DIFF_MAX_CELLS {
+        changes.extend(old.iter().map(|line| Change::Removed(line)));
+        changes.extend(new.iter().map(|line| Change::Added(line)));
+    } else {
+        // lcs[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+        let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+        for i in (0..old.len()).rev() {
+            for j in (0..new.len()).rev() {
+                lcs[i][j] = if old[i] == new[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < old.len() || j < new.len() {
+            if i < old.len() && j < new.len() && old[i] == new[j] {
+                changes.push(Change::Same(old[i]));
+                i += 1;
+                j += 1;
+            } else if j == new.len() || (i < old.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+                changes.push(Change::Removed(old[i]));
+                i += 1;
+            } else {
+                changes.push(Change::Added(new[j]));
+                j += 1;
+            }
+        }
+    }
+    changes.extend(expected[expected.len() - suffix..].iter().map(|line| Change::Same(line)));
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_drops_header_and_locations() {
+        let sql = "\
+/*
+This file is auto generated by pgrx.
+
+Generated with features: [\"pg15\"]
+*/
+
+-- src/lib.rs:20
+-- ext::zebra
+CREATE FUNCTION \"zebra\"() RETURNS INT\x20\x20
+STRICT;
+
+
+-- src/lib.rs:10
+-- ext::aardvark
+CREATE FUNCTION \"aardvark\"() RETURNS INT;
+";
+        assert_eq!(
+            normalize(sql, None),
+            "\
+-- ext::zebra
+CREATE FUNCTION \"zebra\"() RETURNS INT
+STRICT;
+
+-- ext::aardvark
+CREATE FUNCTION \"aardvark\"() RETURNS INT;
+"
+        );
+    }
+
+    #[test]
+    fn normalize_ignores_moved_lines_but_not_reordering() {
+        let before = "-- src/lib.rs:1\nCREATE TYPE a;\n-- src/lib.rs:2\nCREATE TYPE b;\n";
+        let moved = "-- src/lib.rs:7\nCREATE TYPE a;\n-- src/other.rs:3\nCREATE TYPE b;\n";
+        let reordered = "-- src/lib.rs:1\nCREATE TYPE b;\n-- src/lib.rs:2\nCREATE TYPE a;\n";
+        assert_eq!(normalize(before, None), normalize(moved, None));
+        assert_ne!(normalize(before, None), normalize(reordered, None));
+    }
+
+    #[test]
+    fn normalize_filters_by_module() {
+        let sql = "\
+-- src/lib.rs:1
+CREATE SCHEMA IF NOT EXISTS sub; /* ext::sub */
+-- src/lib.rs:2
+-- ext::sub::inner::a
+CREATE FUNCTION sub.\"a\"() RETURNS INT;
+-- src/lib.rs:3
+-- ext::subway::b
+CREATE FUNCTION \"b\"() RETURNS INT;
+";
+        assert_eq!(
+            normalize(sql, Some("ext::sub")),
+            "-- ext::sub::inner::a\nCREATE FUNCTION sub.\"a\"() RETURNS INT;\n"
+        );
+    }
+
+    #[test]
+    fn line_changes_bounds_the_table() {
+        let expected = (0..2000).map(|i| format!("old {i}\n")).collect::<String>();
+        let actual = (0..2000).map(|i| format!("new {i}\n")).collect::<String>();
+        let changes = line_changes(&expected, &actual);
+        assert_eq!(changes.len(), 4000);
+        assert!(matches!(changes[1999], Change::Removed("old 1999")));
+        assert!(matches!(changes[2000], Change::Added("new 0")));
+    }
+
+    #[test]
+    fn line_changes_finds_minimal_edit() {
+        let changes = line_changes("a\nb\nc\nd\n", "a\nc\nx\nd\n");
+        assert_eq!(
+            changes,
+            vec![
+                Change::Same("a"),
+                Change::Removed("b"),
+                Change::Same("c"),
+                Change::Added("x"),
+                Change::Same("d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_elides_unchanged_lines() {
+        let expected = (0..20).map(|i| format!("line {i}\n")).collect::<String>();
+        let actual = expected.replace("line 10\n", "line ten\n");
+        let diff = diff(&expected, &actual);
+        assert!(!diff.contains("line 3\n"));
+        assert!(diff.contains("line 7"));
+        assert!(diff.contains("line 10"));
+        assert!(diff.contains("line ten"));
+        assert!(diff.contains("line 13"));
+        assert!(!diff.contains("line 14"));
+    }
+}
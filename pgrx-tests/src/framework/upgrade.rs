@@ -0,0 +1,345 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Sanity checks for the extension's version upgrade scripts.
+//!
+//! For every `<extname>--<from>--<to>.sql` script installed alongside the extension, a scratch
+//! database is given `CREATE EXTENSION ... VERSION '<from>'` followed by
+//! `ALTER EXTENSION ... UPDATE TO '<to>'`, and a second scratch database is given a fresh
+//! `CREATE EXTENSION ... VERSION '<to>'`.  The catalog objects belonging to the extension are then
+//! compared between the two, so that an upgrade script which has drifted from the generated
+//! schema is caught before release.
+//!
+//! Both versions must be installable, so extensions keep the install script of each released
+//! version in `sql/` alongside the upgrade scripts.
+use std::collections::BTreeSet;
+
+use eyre::{eyre, WrapErr};
+use owo_colors::OwoColorize;
+
+use super::{get_extension_name, get_pg_config, get_pg_dbname, get_pg_user, query_wrapper};
+
+const UPGRADED_DBNAME: &str = "pgrx_tests_upgraded";
+const FRESH_DBNAME: &str = "pgrx_tests_fresh";
+
+/// Everything about an extension member object that an upgrade script is expected to reproduce.
+///
+/// Each query returns one text column and is run with the extension name as its only parameter.
+const CATALOG_QUERIES: &[(&str, &str)] = &[
+    (
+        "member",
+        "SELECT pg_describe_object(d.classid, d.objid, d.objsubid)
+           FROM pg_depend d
+           JOIN pg_extension e ON e.oid = d.refobjid
+          WHERE d.refclassid = 'pg_extension'::regclass
+            AND d.deptype = 'e'
+            AND e.extname = $1",
+    ),
+    (
+        "function",
+        "SELECT format('%s RETURNS %s kind=%s volatile=%s strict=%s parallel=%s secdef=%s cost=%s rows=%s src=%s bin=%s',
+                       p.oid::regprocedure, pg_get_function_result(p.oid), p.prokind, p.provolatile,
+                       p.proisstrict, p.proparallel, p.prosecdef, p.procost, p.prorows, p.prosrc, p.probin)
+           FROM pg_proc p
+           JOIN pg_depend d ON d.classid = 'pg_proc'::regclass AND d.objid = p.oid
+           JOIN pg_extension e ON e.oid = d.refobjid
+          WHERE d.refclassid = 'pg_extension'::regclass
+            AND d.deptype = 'e'
+            AND e.extname = $1",
+    ),
+    (
+        "type",
+        "SELECT format('%s len=%s byval=%s type=%s align=%s storage=%s input=%s output=%s receive=%s send=%s',
+                       t.oid::regtype, t.typlen, t.typbyval, t.typtype, t.typalign, t.typstorage,
+                       t.typinput, t.typoutput, t.typreceive, t.typsend)
+           FROM pg_type t
+           JOIN pg_depend d ON d.classid = 'pg_type'::regclass AND d.objid = t.oid
+           JOIN pg_extension e ON e.oid = d.refobjid
+          WHERE d.refclassid = 'pg_extension'::regclass
+            AND d.deptype = 'e'
+            AND e.extname = $1",
+    ),
+    (
+        "enum label",
+        "SELECT format('%s.%s = %s', t.oid::regtype, en.enumlabel, en.enumsortorder)
+           FROM pg_enum en
+           JOIN pg_type t ON t.oid = en.enumtypid
+           JOIN pg_depend d ON d.classid = 'pg_type'::regclass AND d.objid = t.oid
+           JOIN pg_extension e ON e.oid = d.refobjid
+          WHERE d.refclassid = 'pg_extension'::regclass
+            AND d.deptype = 'e'
+            AND e.extname = $1",
+    ),
+    (
+        "column",
+        "SELECT format('%s.%s %s not_null=%s', a.attrelid::regclass, a.attname,
+                       format_type(a.atttypid, a.atttypmod), a.attnotnull)
+           FROM pg_attribute a
+           JOIN pg_depend d ON d.classid = 'pg_class'::regclass AND d.objid = a.attrelid
+           JOIN pg_extension e ON e.oid = d.refobjid
+          WHERE d.refclassid = 'pg_extension'::regclass
+            AND d.deptype = 'e'
+            AND e.extname = $1
+            AND a.attnum > 0
+            AND NOT a.attisdropped",
+    ),
+    (
+        "operator",
+        "SELECT format('%s commutator=%s negator=%s restrict=%s join=%s hashes=%s merges=%s',
+                       o.oid::regoperator, o.oprcom::regoperator, o.oprnegate::regoperator,
+                       o.oprrest, o.oprjoin, o.oprcanhash, o.oprcanmerge)
+           FROM pg_operator o
+           JOIN pg_depend d ON d.classid = 'pg_operator'::regclass AND d.objid = o.oid
+           JOIN pg_extension e ON e.oid = d.refobjid
+          WHERE d.refclassid = 'pg_extension'::regclass
+            AND d.deptype = 'e'
+            AND e.extname = $1",
+    ),
+    (
+        "cast",
+        "SELECT format('%s context=%s method=%s', pg_describe_object(d.classid, d.objid, 0),
+                       c.castcontext, c.castmethod)
+           FROM pg_cast c
+           JOIN pg_depend d ON d.classid = 'pg_cast'::regclass AND d.objid = c.oid
+           JOIN pg_extension e ON e.oid = d.refobjid
+          WHERE d.refclassid = 'pg_extension'::regclass
+            AND d.deptype = 'e'
+            AND e.extname = $1",
+    ),
+];
+
+/// An upgrade path, as named by an `<extname>--<from>--<to>.sql` script.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct UpgradePath {
+    from: String,
+    to: String,
+}
+
+/// Check every installed upgrade script of the extension under test, returning an error
+/// describing each path whose result differs from a fresh install of its target version.
+///
+/// A path whose starting or target version can't be installed, because neither an install script
+/// nor an update path to it exists, is an error too, unless `skip_missing_versions` is set, in
+/// which case it's reported and skipped.
+pub(super) fn check_upgrade_paths(skip_missing_versions: bool) -> eyre::Result<()> {
+    let extname = get_extension_name()?;
+    let paths = find_upgrade_paths(&extname)?;
+    let mut failures = Vec::new();
+
+    for path in &paths {
+        eprintln!("checking upgrade path {extname} {} -> {}", path.from, path.to);
+        match check_upgrade_path(&extname, path, skip_missing_versions) {
+            Ok(Some(diff)) => failures.push(format!(
+                "{} {} -> {}:\n{diff}",
+                extname.bold(),
+                path.from.bold(),
+                path.to.bold()
+            )),
+            Ok(None) => {}
+            Err(e) => failures.push(format!("{extname} {} -> {}: {e:#}", path.from, path.to)),
+        }
+    }
+
+    drop_scratch_database(UPGRADED_DBNAME)?;
+    drop_scratch_database(FRESH_DBNAME)?;
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(eyre!(
+            "Upgrade scripts do not match a fresh install:\n\n{}",
+            failures.join("\n\n").red()
+        ))
+    }
+}
+
+fn find_upgrade_paths(extname: &str) -> eyre::Result<Vec<UpgradePath>> {
+    let extension_dir = get_pg_config()?.extension_dir()?;
+    let dir = std::fs::read_dir(&extension_dir)
+        .wrap_err_with(|| format!("Unable to read `{}`", extension_dir.display()))?;
+    let filenames = dir.map(|entry| Ok(entry?.file_name())).collect::<std::io::Result<Vec<_>>>()?;
+    Ok(parse_upgrade_paths(extname, filenames.iter().filter_map(|filename| filename.to_str())))
+}
+
+/// The upgrade paths named by the extension's scripts among `filenames`, in order
+fn parse_upgrade_paths<'a>(
+    extname: &str,
+    filenames: impl IntoIterator<Item = &'a str>,
+) -> Vec<UpgradePath> {
+    let prefix = format!("{extname}--");
+    let mut paths = BTreeSet::new();
+
+    for filename in filenames {
+        let Some(versions) =
+            filename.strip_prefix(&prefix).and_then(|rest| rest.strip_suffix(".sql"))
+        else {
+            continue;
+        };
+
+        // install scripts are named `<extname>--<version>.sql` and have no second separator
+        if let Some((from, to)) = versions.split_once("--") {
+            paths.insert(UpgradePath { from: from.to_string(), to: to.to_string() });
+        }
+    }
+
+    paths.into_iter().collect()
+}
+
+/// Returns a description of the differences between the upgraded and fresh catalogs, if any.
+fn check_upgrade_path(
+    extname: &str,
+    path: &UpgradePath,
+    skip_missing_versions: bool,
+) -> eyre::Result<Option<String>> {
+    let mut upgraded = scratch_database(UPGRADED_DBNAME)?;
+    let create = format!("CREATE EXTENSION \"{extname}\" VERSION '{}' CASCADE;", path.from);
+    if !try_create_extension(&mut upgraded, &create)? {
+        return missing_version(extname, &path.from, skip_missing_versions);
+    }
+    let update = format!("ALTER EXTENSION \"{extname}\" UPDATE TO '{}';", path.to);
+    query_wrapper(Some(update), None, |query, _| upgraded.simple_query(&query.unwrap()))?;
+
+    let mut fresh = scratch_database(FRESH_DBNAME)?;
+    let create = format!("CREATE EXTENSION \"{extname}\" VERSION '{}' CASCADE;", path.to);
+    if !try_create_extension(&mut fresh, &create)? {
+        return missing_version(extname, &path.to, skip_missing_versions);
+    }
+
+    let upgraded = catalog_snapshot(&mut upgraded, extname)?;
+    let fresh = catalog_snapshot(&mut fresh, extname)?;
+
+    let missing = fresh.difference(&upgraded).map(|line| format!("  - {line}"));
+    let extra = upgraded.difference(&fresh).map(|line| format!("  + {line}"));
+    let diff = missing.chain(extra).collect::<Vec<_>>();
+
+    Ok((!diff.is_empty()).then(|| {
+        format!(
+            "  (`-` is only in a fresh install, `+` is only after the upgrade)\n{}",
+            diff.join("\n")
+        )
+    }))
+}
+
+fn missing_version(
+    extname: &str,
+    version: &str,
+    skip_missing_versions: bool,
+) -> eyre::Result<Option<String>> {
+    if skip_missing_versions {
+        eprintln!("  {}", format!("skipped: version {version} can't be installed").yellow());
+        Ok(None)
+    } else {
+        Err(eyre!(
+            "version {version} can't be installed.  Add its install script to the extension's \
+             `sql/{extname}--{version}.sql`, or pass `--skip-missing-versions`"
+        ))
+    }
+}
+
+/// Returns `false` if Postgres has no way to install the requested version.
+fn try_create_extension(client: &mut postgres::Client, create: &str) -> eyre::Result<bool> {
+    match client.simple_query(create) {
+        Ok(_) => Ok(true),
+        Err(e)
+            if e.as_db_error()
+                .map(|dberror| dberror.message().contains("no installation script"))
+                .unwrap_or(false) =>
+        {
+            Ok(false)
+        }
+        Err(e) => Err(e).wrap_err_with(|| format!("`{create}` failed")),
+    }
+}
+
+fn catalog_snapshot(
+    client: &mut postgres::Client,
+    extname: &str,
+) -> eyre::Result<BTreeSet<String>> {
+    let params: &[&(dyn postgres::types::ToSql + Sync)] = &[&extname];
+    let mut snapshot = BTreeSet::new();
+    for (kind, query) in CATALOG_QUERIES {
+        let rows = query_wrapper(Some(query.to_string()), Some(params), |query, params| {
+            client.query(&query.unwrap(), params.unwrap())
+        })?;
+        snapshot.extend(rows.iter().map(|row| format!("{kind}: {}", row.get::<_, String>(0))));
+    }
+    Ok(snapshot)
+}
+
+/// (Re)create an empty database and connect to it.
+fn scratch_database(dbname: &str) -> eyre::Result<postgres::Client> {
+    drop_scratch_database(dbname)?;
+    let (mut client, _) = super::client()?;
+    let create = format!("CREATE DATABASE \"{dbname}\";");
+    query_wrapper(Some(create), None, |query, _| client.simple_query(&query.unwrap()))?;
+    connect(dbname)
+}
+
+fn drop_scratch_database(dbname: &str) -> eyre::Result<()> {
+    debug_assert_ne!(dbname, get_pg_dbname());
+    let (mut client, _) = super::client()?;
+    let drop = format!("DROP DATABASE IF EXISTS \"{dbname}\";");
+    query_wrapper(Some(drop), None, |query, _| client.simple_query(&query.unwrap()))?;
+    Ok(())
+}
+
+fn connect(dbname: &str) -> eyre::Result<postgres::Client> {
+    let pg_config = get_pg_config()?;
+    postgres::Config::new()
+        .host(pg_config.host())
+        .port(pg_config.test_port()?)
+        .user(&get_pg_user())
+        .dbname(dbname)
+        .connect(postgres::NoTls)
+        .wrap_err_with(|| format!("Error connecting to database `{dbname}`"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(from: &str, to: &str) -> UpgradePath {
+        UpgradePath { from: from.to_string(), to: to.to_string() }
+    }
+
+    #[test]
+    fn finds_upgrade_scripts_only() {
+        let filenames = [
+            "ext--1.0.sql",
+            "ext--1.0--1.1.sql",
+            "ext--1.1.sql",
+            "ext.control",
+            "ext--1.1--1.2.sql.orig",
+            "other--1.0--1.1.sql",
+            "ext_other--1.0--1.1.sql",
+        ];
+        assert_eq!(parse_upgrade_paths("ext", filenames), vec![path("1.0", "1.1")]);
+    }
+
+    #[test]
+    fn sorts_and_dedups_upgrade_paths() {
+        let filenames = ["ext--1.1--1.2.sql", "ext--1.0--1.1.sql", "ext--1.0--1.1.sql"];
+        assert_eq!(
+            parse_upgrade_paths("ext", filenames),
+            vec![path("1.0", "1.1"), path("1.1", "1.2")]
+        );
+    }
+
+    #[test]
+    fn keeps_dashes_within_versions() {
+        let filenames = ["ext--1.0-beta--1.0.sql", "my-ext--0.1--0.2.sql"];
+        assert_eq!(parse_upgrade_paths("ext", filenames), vec![path("1.0-beta", "1.0")]);
+        assert_eq!(parse_upgrade_paths("my-ext", filenames), vec![path("0.1", "0.2")]);
+    }
+
+    #[test]
+    fn finds_nothing_without_scripts() {
+        assert_eq!(parse_upgrade_paths("ext", []), vec![]);
+    }
+}
@@ -8,7 +8,7 @@
 //LICENSE
 //LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
 use pgrx::prelude::*;
-use pgrx::{Aggregate, Internal, ParallelOption};
+use pgrx::{Aggregate, AggregateSerial, Internal, ParallelOption};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
@@ -187,6 +187,56 @@ impl Aggregate for DemoCustomState {
     }
 }
 
+#[derive(Default, Debug, PartialEq, Serialize, Deserialize, AggregateSerial)]
+pub struct DemoParallelSumState {
+    sum: i64,
+    count: i64,
+}
+
+#[derive(Copy, Clone, Default, Debug)]
+pub struct DemoParallelAvg;
+
+#[pg_aggregate(serial = DemoParallelSumState)]
+impl Aggregate for DemoParallelAvg {
+    type Args = i32;
+    type State = Internal;
+    type Finalize = Option<f64>;
+    const PARALLEL: Option<ParallelOption> = Some(pgrx::aggregate::ParallelOption::Safe);
+
+    fn state(
+        mut current: Self::State,
+        arg: Self::Args,
+        _fcinfo: pg_sys::FunctionCallInfo,
+    ) -> Self::State {
+        let inner = unsafe { current.get_or_insert_default::<DemoParallelSumState>() };
+        inner.sum += arg as i64;
+        inner.count += 1;
+        current
+    }
+
+    fn combine(
+        mut first: Self::State,
+        mut second: Self::State,
+        _fcinfo: pg_sys::FunctionCallInfo,
+    ) -> Self::State {
+        let second_inner = unsafe { second.get_or_insert_default::<DemoParallelSumState>() };
+        let (sum, count) = (second_inner.sum, second_inner.count);
+        let first_inner = unsafe { first.get_or_insert_default::<DemoParallelSumState>() };
+        first_inner.sum += sum;
+        first_inner.count += count;
+        first
+    }
+
+    fn finalize(
+        mut current: Self::State,
+        _direct_args: Self::OrderedSetArgs,
+        _fcinfo: pg_sys::FunctionCallInfo,
+    ) -> Self::Finalize {
+        let inner = unsafe { current.get_or_insert_default::<DemoParallelSumState>() };
+        (inner.count > 0).then(|| inner.sum as f64 / inner.count as f64)
+    }
+}
+
 #[cfg(any(test, feature = "pg_test"))]
 #[pgrx::pg_schema]
 mod tests {
@@ -242,4 +292,67 @@ mod tests {
         );
         assert_eq!(retval, Ok(Some(4)));
     }
+
+    #[pg_test]
+    fn aggregate_demo_parallel_avg() -> Result<(), spi::Error> {
+        let retval = Spi::get_one::<f64>(
+            "SELECT DemoParallelAvg(value) FROM UNNEST(ARRAY [1, 2, 3, 6]) as value;",
+        )?;
+        assert_eq!(retval, Some(3.0));
+
+        let funcs = Spi::get_two::<String, String>(
+            "SELECT aggserialfn::text, aggdeserialfn::text FROM pg_aggregate \
+             WHERE aggfnoid = 'DemoParallelAvg'::regproc",
+        )?;
+        assert_eq!(
+            funcs,
+            (
+                Some("demo_parallel_avg_serial".to_string()),
+                Some("demo_parallel_avg_deserial".to_string())
+            )
+        );
+        Ok(())
+    }
+
+    #[pg_test]
+    fn aggregate_demo_parallel_avg_in_parallel() -> Result<(), spi::Error> {
+        Spi::run("CREATE TABLE parallel_avg AS SELECT generate_series(1, 100000) AS value")?;
+        Spi::run("ANALYZE parallel_avg")?;
+        Spi::run("SET LOCAL parallel_setup_cost = 0")?;
+        Spi::run("SET LOCAL parallel_tuple_cost = 0")?;
+        Spi::run("SET LOCAL min_parallel_table_scan_size = 0")?;
+        Spi::run("SET LOCAL max_parallel_workers_per_gather = 2")?;
+        #[cfg(feature = "pg16")]
+        Spi::run("SET LOCAL debug_parallel_query = on")?;
+        #[cfg(not(feature = "pg16"))]
+        Spi::run("SET LOCAL force_parallel_mode = on")?;
+
+        let query = "SELECT DemoParallelAvg(value) FROM parallel_avg";
+        let plan = Spi::connect(|client| {
+            client
+                .select(&format!("EXPLAIN (ANALYZE, COSTS OFF, TIMING OFF) {query}"), None, None)?
+                .map(|row| row.get::<String>(1))
+                .collect::<Result<Vec<_>, spi::Error>>()
+        })?;
+        let plan = plan.into_iter().flatten().collect::<Vec<_>>().join("\n");
+        assert!(plan.contains("Partial Aggregate"), "not a partial aggregate:\n{plan}");
+        assert!(
+            plan.lines().any(|line| line.trim().starts_with("Workers Launched: ")
+                && !line.trim().ends_with(": 0")),
+            "no workers launched:\n{plan}"
+        );
+
+        let retval = Spi::get_one::<f64>(query)?;
+        assert_eq!(retval, Some(50000.5));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn aggregate_serial_roundtrip() {
+        use pgrx::AggregateSerial;
+
+        let state = super::DemoParallelSumState { sum: 42, count: 7 };
+        let bytes = state.serialize_state();
+        assert_eq!(super::DemoParallelSumState::deserialize_state(&bytes), state);
+    }
 }
@@ -0,0 +1,131 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+    use pgrx::AnyNonArray;
+
+    #[pg_extern]
+    fn anynonarray_type(x: AnyNonArray) -> pg_sys::Oid {
+        x.oid()
+    }
+
+    #[pg_test]
+    fn test_anynonarray_type() {
+        let oid = Spi::get_one::<pg_sys::Oid>("SELECT tests.anynonarray_type(42::bigint)");
+        assert_eq!(oid, Ok(Some(pg_sys::INT8OID)));
+    }
+
+    #[pg_test(error = "function tests.anynonarray_type(integer[]) does not exist")]
+    fn test_anynonarray_array_arg() {
+        Spi::run("SELECT tests.anynonarray_type(ARRAY[1, 2, 3])").unwrap();
+    }
+
+    #[cfg(any(feature = "pg13", feature = "pg14", feature = "pg15", feature = "pg16"))]
+    /// Returns the common type Postgres resolved for both arguments
+    #[pg_extern]
+    fn anycompatible_common_type(a: pgrx::AnyCompatible, _b: pgrx::AnyCompatible) -> pg_sys::Oid {
+        a.oid()
+    }
+
+    #[cfg(any(feature = "pg13", feature = "pg14", feature = "pg15", feature = "pg16"))]
+    #[pg_extern]
+    fn anycompatible_array_type(a: pgrx::AnyCompatible) -> Option<pg_sys::Oid> {
+        a.array_oid()
+    }
+
+    #[cfg(any(feature = "pg13", feature = "pg14", feature = "pg15", feature = "pg16"))]
+    #[pg_extern]
+    fn anycompatiblearray_element_type(
+        a: pgrx::AnyCompatibleArray,
+        _b: pgrx::AnyCompatible,
+    ) -> pg_sys::Oid {
+        a.element_oid()
+    }
+
+    #[cfg(any(feature = "pg13", feature = "pg14", feature = "pg15", feature = "pg16"))]
+    #[pg_test]
+    fn test_anycompatible_resolves_common_type() {
+        let oid = Spi::get_one::<pg_sys::Oid>(
+            "SELECT tests.anycompatible_common_type(1::integer, 2.5::numeric)",
+        );
+        assert_eq!(oid, Ok(Some(pg_sys::NUMERICOID)));
+    }
+
+    #[cfg(any(feature = "pg13", feature = "pg14", feature = "pg15", feature = "pg16"))]
+    #[pg_test]
+    fn test_anycompatible_array_type() {
+        let oid = Spi::get_one::<pg_sys::Oid>("SELECT tests.anycompatible_array_type(1::bigint)");
+        assert_eq!(oid, Ok(Some(pg_sys::INT8ARRAYOID)));
+    }
+
+    #[cfg(any(feature = "pg13", feature = "pg14", feature = "pg15", feature = "pg16"))]
+    #[pg_test]
+    fn test_anycompatiblearray_element_type() {
+        let oid = Spi::get_one::<pg_sys::Oid>(
+            "SELECT tests.anycompatiblearray_element_type(ARRAY[1, 2]::integer[], 3::bigint)",
+        );
+        assert_eq!(oid, Ok(Some(pg_sys::INT8OID)));
+    }
+
+    #[cfg(any(feature = "pg13", feature = "pg14", feature = "pg15", feature = "pg16"))]
+    #[pg_extern]
+    fn anycompatiblenonarray_type(
+        a: pgrx::AnyCompatibleNonArray,
+        _b: pgrx::AnyCompatible,
+    ) -> pg_sys::Oid {
+        a.oid()
+    }
+
+    #[cfg(any(feature = "pg13", feature = "pg14", feature = "pg15", feature = "pg16"))]
+    #[pg_extern]
+    fn anycompatible_element_types(
+        _a: pgrx::AnyCompatibleArray,
+        _b: pgrx::AnyCompatible,
+        fcinfo: pg_sys::FunctionCallInfo,
+    ) -> Vec<Option<pg_sys::Oid>> {
+        unsafe {
+            vec![
+                pgrx::fcinfo::pg_getarg_element_type(fcinfo, 0),
+                pgrx::fcinfo::pg_getarg_element_type(fcinfo, 1),
+            ]
+        }
+    }
+
+    #[cfg(any(feature = "pg13", feature = "pg14", feature = "pg15", feature = "pg16"))]
+    #[pg_test]
+    fn test_anycompatiblenonarray_type() {
+        let oid = Spi::get_one::<pg_sys::Oid>(
+            "SELECT tests.anycompatiblenonarray_type(1::smallint, 2::integer)",
+        );
+        assert_eq!(oid, Ok(Some(pg_sys::INT4OID)));
+    }
+
+    #[cfg(any(feature = "pg13", feature = "pg14", feature = "pg15", feature = "pg16"))]
+    #[pg_test(
+        error = "function tests.anycompatiblenonarray_type(integer[], integer[]) does not exist"
+    )]
+    fn test_anycompatiblenonarray_array_arg() {
+        Spi::run("SELECT tests.anycompatiblenonarray_type(ARRAY[1], ARRAY[2])").unwrap();
+    }
+
+    #[cfg(any(feature = "pg13", feature = "pg14", feature = "pg15", feature = "pg16"))]
+    #[pg_test]
+    fn test_pg_getarg_element_type() {
+        let oids = Spi::get_one::<Vec<Option<pg_sys::Oid>>>(
+            "SELECT tests.anycompatible_element_types(ARRAY[1, 2]::integer[], 3.5::numeric)",
+        );
+        assert_eq!(oids, Ok(Some(vec![Some(pg_sys::NUMERICOID), Some(pg_sys::NUMERICOID)])));
+    }
+}
@@ -86,6 +86,61 @@ pub extern "C" fn bgworker_return_value(arg: pg_sys::Datum) {
     .expect("bgworker transaction failed");
 }
 
+#[derive(Clone, pgrx::TableRow)]
+struct WriterRow {
+    id: i32,
+    #[column = "label"]
+    name: String,
+}
+
+#[pg_guard]
+#[no_mangle]
+/// Here we test that a `BufferedTableWriter` writes every row it buffers, across several batches
+pub extern "C" fn bgworker_table_writer(_arg: pg_sys::Datum) {
+    use pgrx::bgworkers::*;
+    use pgrx::table_writer::BufferedTableWriter;
+    BackgroundWorker::attach_signal_handlers(SignalWakeFlags::SIGHUP | SignalWakeFlags::SIGTERM);
+    BackgroundWorker::connect_worker_to_spi(
+        Some(crate::framework::get_pg_dbname()),
+        Some(crate::framework::get_pg_user().as_str()),
+    );
+
+    BackgroundWorker::transaction(|| {
+        Spi::run("CREATE TABLE tests.bgworker_table_writer (id INTEGER, label TEXT);")
+    })
+    .expect("bgworker transaction failed");
+
+    let mut writer =
+        BufferedTableWriter::<WriterRow>::with_schema("tests", "bgworker_table_writer")
+            .batch_size(10)
+            .max_buffered(25);
+    for id in 1..=25 {
+        assert!(writer.push(WriterRow { id, name: format!("row {id}") }).is_ok());
+    }
+    assert!(writer.push(WriterRow { id: 26, name: "overflow".into() }).is_err());
+    assert!(writer.should_flush());
+    assert_eq!(writer.flush(), Ok(25));
+    assert!(writer.is_empty());
+}
+
+#[pg_guard]
+#[no_mangle]
+/// Here we test that a standby worker takes over once the leader gives up its role, and that
+/// its own leadership is released when it exits
+pub extern "C" fn bgworker_leader(_arg: pg_sys::Datum) {
+    use crate::tests::shmem_tests::WORKER_LEADER;
+    use pgrx::bgworkers::*;
+    use std::time::Duration;
+    BackgroundWorker::attach_signal_handlers(SignalWakeFlags::SIGHUP | SignalWakeFlags::SIGTERM);
+
+    if let Some(leadership) =
+        BackgroundWorker::wait_for_leadership(&WORKER_LEADER, Duration::from_millis(10))
+    {
+        // leave releasing the role to the exit callback
+        std::mem::forget(leadership);
+    }
+}
+
 #[cfg(any(test, feature = "pg_test"))]
 #[pgrx::pg_schema]
 mod tests {
@@ -159,4 +214,68 @@ mod tests {
 
         assert_eq!(Ok(Some(123)), Spi::get_one::<i32>("SELECT v FROM tests.bgworker_test_return;"));
     }
+
+    #[pg_test]
+    fn test_leader_election() {
+        use crate::tests::shmem_tests::LEADER;
+
+        let leadership = BackgroundWorker::try_become_leader(&LEADER).expect("role is vacant");
+        assert_eq!(LEADER.share().leader_pid(), Some(unsafe { pg_sys::MyProcPid }));
+        assert!(leadership.is_current());
+        assert!(BackgroundWorker::try_become_leader(&LEADER).is_none());
+
+        let term = leadership.term();
+        drop(leadership);
+        assert_eq!(LEADER.share().leader_pid(), None);
+
+        let leadership = BackgroundWorker::try_become_leader(&LEADER).expect("role is vacant");
+        assert_eq!(leadership.term(), term + 1);
+    }
+
+    #[pg_test]
+    fn test_leader_standby_takeover() {
+        use crate::tests::shmem_tests::WORKER_LEADER;
+
+        let leadership =
+            BackgroundWorker::try_become_leader(&WORKER_LEADER).expect("role is vacant");
+        let worker = BackgroundWorkerBuilder::new("dynamic_bgworker")
+            .set_library("pgrx_tests")
+            .set_function("bgworker_leader")
+            .enable_shmem_access(None)
+            .set_notify_pid(unsafe { pg_sys::MyProcPid })
+            .load_dynamic();
+        let pid = worker.wait_for_startup().expect("no PID from the worker");
+        assert!(pid > 0);
+        assert_eq!(WORKER_LEADER.share().leader_pid(), Some(unsafe { pg_sys::MyProcPid }));
+
+        let term = leadership.term();
+        drop(leadership);
+        worker.wait_for_shutdown().expect("aborted shutdown");
+
+        let leader = *WORKER_LEADER.share();
+        assert_eq!(leader.term(), term + 1);
+        assert_eq!(leader.leader_pid(), None);
+    }
+
+    #[pg_test]
+    fn test_background_worker_table_writer() {
+        let worker = BackgroundWorkerBuilder::new("dynamic_bgworker")
+            .set_library("pgrx_tests")
+            .set_function("bgworker_table_writer")
+            .enable_spi_access()
+            .set_notify_pid(unsafe { pg_sys::MyProcPid })
+            .load_dynamic();
+        let pid = worker.wait_for_startup().expect("no PID from the worker");
+        assert!(pid > 0);
+        worker.wait_for_shutdown().expect("aborted shutdown");
+
+        assert_eq!(
+            Ok(Some(25)),
+            Spi::get_one::<i64>("SELECT count(*) FROM tests.bgworker_table_writer;")
+        );
+        assert_eq!(
+            Ok(Some("row 7".to_string())),
+            Spi::get_one::<String>("SELECT label FROM tests.bgworker_table_writer WHERE id = 7;")
+        );
+    }
 }
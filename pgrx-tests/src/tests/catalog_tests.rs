@@ -0,0 +1,85 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::catalog::{DatabaseInfo, RoleInfo};
+    use pgrx::prelude::*;
+
+    #[pg_test]
+    fn test_current_role() -> Result<(), pgrx::spi::Error> {
+        let role = RoleInfo::current();
+        assert_eq!(Some(role.name.clone()), Spi::get_one::<String>("SELECT current_user::text")?);
+        assert_eq!(
+            Some(role.superuser),
+            Spi::get_one::<bool>("SELECT rolsuper FROM pg_roles WHERE rolname = current_user")?
+        );
+        assert_eq!(RoleInfo::lookup_by_name(&role.name), Some(role));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_role_membership() -> Result<(), pgrx::spi::Error> {
+        Spi::run("CREATE ROLE pgrx_catalog_group NOLOGIN")?;
+        Spi::run("CREATE ROLE pgrx_catalog_member NOLOGIN CONNECTION LIMIT 3")?;
+        Spi::run("CREATE ROLE pgrx_catalog_outsider NOLOGIN")?;
+        Spi::run("GRANT pgrx_catalog_group TO pgrx_catalog_member")?;
+
+        let group = RoleInfo::lookup_by_name("pgrx_catalog_group").expect("no group role");
+        let member = RoleInfo::lookup_by_name("pgrx_catalog_member").expect("no member role");
+        let outsider = RoleInfo::lookup_by_name("pgrx_catalog_outsider").expect("no outsider");
+
+        assert!(!member.superuser);
+        assert!(!member.can_login);
+        assert_eq!(member.connection_limit, Some(3));
+        assert_eq!(group.connection_limit, None);
+        assert!(member.is_member_of(group.oid));
+        assert!(member.has_privs_of(group.oid));
+        assert!(!outsider.is_member_of(group.oid));
+        assert!(!outsider.has_privs_of(group.oid));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_unknown_role() {
+        assert_eq!(RoleInfo::lookup_by_name("pgrx_catalog_no_such_role"), None);
+        assert_eq!(RoleInfo::lookup(pg_sys::InvalidOid), None);
+    }
+
+    #[pg_test]
+    fn test_current_database() -> Result<(), pgrx::spi::Error> {
+        let database = DatabaseInfo::current();
+        assert_eq!(
+            Some(database.name.clone()),
+            Spi::get_one::<String>("SELECT current_database()::text")?
+        );
+        assert_eq!(
+            Some(database.encoding_name().to_string()),
+            Spi::get_one::<String>("SELECT pg_encoding_to_char(encoding)::text FROM pg_database WHERE datname = current_database()")?
+        );
+        assert_eq!(
+            Some(database.collate.clone()),
+            Spi::get_one::<String>(
+                "SELECT datcollate::text FROM pg_database WHERE datname = current_database()"
+            )?
+        );
+        assert_eq!(
+            database.tablespace_name(),
+            Spi::get_one::<String>("SELECT spcname::text FROM pg_tablespace t JOIN pg_database d ON d.dattablespace = t.oid WHERE datname = current_database()")?
+        );
+        assert_eq!(DatabaseInfo::lookup_by_name(&database.name), Some(database));
+        assert_eq!(DatabaseInfo::lookup_by_name("pgrx_catalog_no_such_database"), None);
+        Ok(())
+    }
+}
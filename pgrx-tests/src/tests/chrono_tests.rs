@@ -0,0 +1,136 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+    use pgrx::prelude::*;
+
+    #[pg_extern]
+    fn chrono_next_day(date: NaiveDate) -> NaiveDate {
+        date.succ_opt().unwrap()
+    }
+
+    #[pg_extern]
+    fn chrono_naive_echo(ts: NaiveDateTime) -> NaiveDateTime {
+        ts
+    }
+
+    #[pg_extern]
+    fn chrono_utc_echo(ts: DateTime<Utc>) -> DateTime<Utc> {
+        ts
+    }
+
+    #[pg_extern]
+    fn chrono_double_duration(d: Duration) -> Duration {
+        d * 2
+    }
+
+    #[pg_test]
+    fn test_chrono_date_roundtrip() -> Result<(), pgrx::spi::Error> {
+        let date = Spi::get_one::<NaiveDate>("SELECT tests.chrono_next_day('2023-02-28')")?;
+        assert_eq!(date, NaiveDate::from_ymd_opt(2023, 3, 1));
+
+        let date = Spi::get_one::<NaiveDate>("SELECT tests.chrono_next_day('1970-01-01')")?;
+        assert_eq!(date, NaiveDate::from_ymd_opt(1970, 1, 2));
+
+        let text = Spi::get_one::<String>("SELECT tests.chrono_next_day('1999-12-31')::text")?;
+        assert_eq!(text.as_deref(), Some("2000-01-01"));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_chrono_timestamp_roundtrip() -> Result<(), pgrx::spi::Error> {
+        let ts = Spi::get_one::<NaiveDateTime>(
+            "SELECT tests.chrono_naive_echo('1969-07-20 20:17:40.123456')",
+        )?;
+        let expected = NaiveDate::from_ymd_opt(1969, 7, 20)
+            .unwrap()
+            .and_hms_micro_opt(20, 17, 40, 123456)
+            .unwrap();
+        assert_eq!(ts, Some(expected));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_chrono_timestamptz_is_utc() -> Result<(), pgrx::spi::Error> {
+        Spi::run("SET LOCAL TIME ZONE 'America/New_York'")?;
+        let ts = Spi::get_one::<DateTime<Utc>>(
+            "SELECT tests.chrono_utc_echo('2023-06-01 12:00:00-04')",
+        )?;
+        assert_eq!(ts, Some(Utc.with_ymd_and_hms(2023, 6, 1, 16, 0, 0).unwrap()));
+
+        let text = Spi::get_one_with_args::<String>(
+            "SELECT $1::text",
+            vec![(
+                PgBuiltInOids::TIMESTAMPTZOID.oid(),
+                Utc.with_ymd_and_hms(2023, 6, 1, 16, 0, 0).unwrap().into_datum(),
+            )],
+        )?;
+        assert_eq!(text.as_deref(), Some("2023-06-01 12:00:00-04"));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_chrono_infinity_conversions() {
+        assert!(NaiveDate::try_from(pgrx::Date::positive_infinity()).is_err());
+        assert!(NaiveDateTime::try_from(pgrx::Timestamp::negative_infinity()).is_err());
+        assert!(
+            DateTime::<Utc>::try_from(pgrx::TimestampWithTimeZone::positive_infinity()).is_err()
+        );
+    }
+
+    #[pg_test]
+    fn test_chrono_date_out_of_range() {
+        let too_early = NaiveDate::from_ymd_opt(-5000, 1, 1).unwrap();
+        assert!(pgrx::Date::try_from(too_early).is_err());
+        let earliest = NaiveDate::from_ymd_opt(-4713, 11, 24).unwrap();
+        let date = pgrx::Date::try_from(earliest).unwrap();
+        assert_eq!(NaiveDate::try_from(date).ok(), Some(earliest));
+    }
+
+    #[pg_test]
+    fn test_chrono_duration_roundtrip() -> Result<(), pgrx::spi::Error> {
+        let text = Spi::get_one::<String>(
+            "SELECT tests.chrono_double_duration('1 hour 30 minutes 0.000001 seconds')::text",
+        )?;
+        assert_eq!(text.as_deref(), Some("03:00:00.000002"));
+
+        let negative = Duration::microseconds(-1_500_000);
+        let interval = pgrx::Interval::try_from(negative).unwrap();
+        assert_eq!(interval.micros(), -1_500_000);
+        assert_eq!(Duration::try_from(interval), Ok(negative));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_chrono_duration_inexact_interval() {
+        use pgrx::datum::datetime_support::IntervalConversionError;
+
+        let month = pgrx::Interval::from_months(1);
+        assert_eq!(Duration::try_from(month), Err(IntervalConversionError::InexactInterval));
+        let day = pgrx::Interval::from_days(1);
+        assert_eq!(Duration::try_from(day), Err(IntervalConversionError::InexactInterval));
+        assert_eq!(
+            pgrx::Interval::try_from(Duration::max_value()).map(|i| i.micros()),
+            Err(IntervalConversionError::DurationTooLarge)
+        );
+    }
+
+    #[pg_test(
+        error = "cannot convert `interval` to `Duration`: Intervals with months or days have no fixed length, and cannot be converted into Durations"
+    )]
+    fn test_chrono_duration_from_inexact_interval() -> Result<(), pgrx::spi::Error> {
+        Spi::get_one::<Duration>("SELECT tests.chrono_double_duration('1 day')").map(|_| ())
+    }
+}
@@ -0,0 +1,54 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::config_file::*;
+    use pgrx::prelude::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestConfig {
+        name: String,
+        workers: i32,
+    }
+
+    static CONFIG: ExtensionConfigFile<TestConfig> =
+        ExtensionConfigFile::new("pgrx_tests_config.json", ConfigFileLocation::DataDir);
+
+    #[pg_test]
+    fn test_config_file_reload() {
+        let path = CONFIG.path();
+        assert_eq!(path, data_dir().join("pgrx_tests_config.json"));
+
+        std::fs::write(&path, r#"{"name": "first", "workers": 1}"#).unwrap();
+        let config = CONFIG.get().unwrap();
+        assert_eq!(*config, TestConfig { name: "first".into(), workers: 1 });
+
+        std::fs::write(&path, r#"{"name": "second", "workers": 2}"#).unwrap();
+        let config = CONFIG.reload().unwrap();
+        assert_eq!(*config, TestConfig { name: "second".into(), workers: 2 });
+
+        std::fs::write(&path, r#"{"name": "#).unwrap();
+        assert!(matches!(CONFIG.reload(), Err(ConfigFileError::Parse(..))));
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(CONFIG.get(), Err(ConfigFileError::Io(..))));
+    }
+
+    #[pg_test]
+    fn test_share_dir() {
+        assert!(share_dir().join("extension").join("plpgsql.control").exists());
+    }
+}
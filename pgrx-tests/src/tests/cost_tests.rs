@@ -0,0 +1,63 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::cost::{clamp_row_est, PathCost, PlannerCosts, ScanCost};
+    use pgrx::prelude::*;
+
+    #[pg_test]
+    fn test_planner_costs_follow_settings() {
+        Spi::run("SET LOCAL seq_page_cost = 2; SET LOCAL cpu_tuple_cost = 0.5").unwrap();
+        let costs = PlannerCosts::current();
+        assert_eq!(costs.seq_page_cost, 2.0);
+        assert_eq!(costs.cpu_tuple_cost, 0.5);
+    }
+
+    #[pg_test]
+    fn test_clamp_row_est() {
+        assert_eq!(clamp_row_est(0.0), 1.0);
+        assert_eq!(clamp_row_est(41.6), 42.0);
+    }
+
+    #[pg_test]
+    fn test_scan_cost() {
+        Spi::run(
+            "SET LOCAL seq_page_cost = 1; SET LOCAL random_page_cost = 4; \
+             SET LOCAL cpu_tuple_cost = 0.01; SET LOCAL cpu_operator_cost = 0.0025",
+        )
+        .unwrap();
+        let qual = pg_sys::QualCost { startup: 1.0, per_tuple: 0.0025 };
+        let cost = ScanCost::new(50.0)
+            .startup(100.0)
+            .qual(qual)
+            .seq_pages(10.0)
+            .random_pages(2.0)
+            .tuples(1000.0)
+            .operators(4.0)
+            .finish();
+
+        assert_eq!(cost.startup, 101.0);
+        // 100 + 1 startup, 10 * 1 + 2 * 4 disk, 1000 * (0.01 + 0.0025) + 4 * 0.0025 cpu
+        assert!((cost.total - 131.51).abs() < 1e-9);
+        assert_eq!(cost.rows, 50.0);
+        assert_eq!(cost.run(), cost.total - cost.startup);
+    }
+
+    #[pg_test]
+    fn test_path_cost_apply_to() {
+        let mut path = pg_sys::Path::default();
+        PathCost { startup: 1.0, total: 2.0, rows: 3.0 }.apply_to(&mut path);
+        assert_eq!((path.startup_cost, path.total_cost, path.rows), (1.0, 2.0, 3.0));
+    }
+}
@@ -0,0 +1,90 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+    use std::borrow::Cow;
+
+    #[pg_extern]
+    fn cow_shout(s: Cow<str>) -> Cow<str> {
+        if s.ends_with('!') {
+            s
+        } else {
+            Cow::Owned(format!("{s}!"))
+        }
+    }
+
+    #[pg_extern]
+    fn cow_bytes_len(bytes: Cow<[u8]>) -> i32 {
+        bytes.len() as i32
+    }
+
+    #[pg_test]
+    fn test_cow_arguments() -> Result<(), spi::Error> {
+        assert_eq!(Spi::get_one::<&str>("SELECT tests.cow_shout('hi')")?, Some("hi!"));
+        assert_eq!(Spi::get_one::<&str>("SELECT tests.cow_shout('hi!')")?, Some("hi!"));
+        assert_eq!(Spi::get_one::<i32>("SELECT tests.cow_bytes_len('abc'::bytea)")?, Some(3));
+        assert_eq!(
+            Spi::get_one::<i32>(
+                "SELECT tests.cow_bytes_len(convert_to(repeat('a', 100000), 'UTF8'))"
+            )?,
+            Some(100000)
+        );
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_cow_borrows_unless_toasted() -> Result<(), spi::Error> {
+        Spi::run(
+            "CREATE TABLE tests.cow (id int, v text, b bytea);
+             ALTER TABLE tests.cow ALTER COLUMN v SET STORAGE EXTERNAL;
+             INSERT INTO tests.cow VALUES
+                 (1, 'short', 'short'),
+                 (2, repeat('abc', 100000), convert_to(repeat('abc', 100000), 'UTF8'));",
+        )?;
+        Spi::connect(|client| {
+            let row = |id: i32| -> Result<(pg_sys::Datum, pg_sys::Datum), spi::Error> {
+                let table = client
+                    .select(&format!("SELECT v, b FROM tests.cow WHERE id = {id}"), None, None)?
+                    .first();
+                Ok((
+                    table.get_datum_by_ordinal(1)?.unwrap(),
+                    table.get_datum_by_ordinal(2)?.unwrap(),
+                ))
+            };
+
+            let (v, b) = row(1)?;
+            let text = unsafe { Cow::<str>::from_datum(v, false) }.unwrap();
+            let bytes = unsafe { Cow::<[u8]>::from_datum(b, false) }.unwrap();
+            assert!(matches!(text, Cow::Borrowed("short")));
+            assert!(matches!(bytes, Cow::Borrowed(b"short")));
+
+            let (v, b) = row(2)?;
+            let text = unsafe { Cow::<str>::from_datum(v, false) }.unwrap();
+            let bytes = unsafe { Cow::<[u8]>::from_datum(b, false) }.unwrap();
+            assert!(matches!(text, Cow::Owned(ref s) if s.len() == 300000));
+            assert!(matches!(bytes, Cow::Owned(ref b) if b.len() == 300000));
+            Ok(())
+        })
+    }
+
+    #[pg_test]
+    fn test_cow_try_from_datum() {
+        let datum = "hello".into_datum();
+        let text = unsafe { Cow::<str>::try_from_datum(datum.unwrap(), false, pg_sys::TEXTOID) };
+        assert_eq!(text, Ok(Some(Cow::Borrowed("hello"))));
+        let text = unsafe { Cow::<str>::try_from_datum(datum.unwrap(), false, pg_sys::INT4OID) };
+        assert!(text.is_err());
+    }
+}
@@ -93,7 +93,7 @@ mod tests {
 
     use pgrx::datum::datetime_support::IntervalConversionError;
     use pgrx::prelude::*;
-    use pgrx::{get_timezone_offset, DateTimeConversionError};
+    use pgrx::{get_timezone_offset, DateTimeConversionError, TimeZone};
     use serde_json::*;
     use std::result::Result;
     use std::str::FromStr;
@@ -401,6 +401,97 @@ mod tests {
         Ok(())
     }
 
+    #[pg_test]
+    fn test_date_infinity() -> Result<(), pgrx::spi::Error> {
+        let date = Spi::get_one::<Date>("SELECT 'infinity'::date")?.expect("datum was null");
+        assert!(date.is_infinity());
+        assert!(date.is_infinite());
+        assert!(!date.is_finite());
+        assert_eq!(date.to_julian_days(), i32::MAX);
+        assert_eq!(date.to_unix_epoch_days(), i32::MAX);
+        assert_eq!(date.to_posix_time(), libc::time_t::MAX);
+        assert_eq!(date.to_string(), "infinity");
+
+        let date = Spi::get_one::<Date>("SELECT '-infinity'::date")?.expect("datum was null");
+        assert!(date.is_neg_infinity());
+        assert!(date.is_infinite());
+        assert_eq!(date.to_julian_days(), i32::MIN);
+        assert_eq!(date.to_posix_time(), libc::time_t::MIN);
+        assert_eq!(date.to_string(), "-infinity");
+
+        assert!(Date::new(2023, 7, 4).unwrap().is_finite());
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_infinity_conversions() -> Result<(), DateTimeConversionError> {
+        let noon = Time::new(12, 0, 0.0)?;
+        assert!(Timestamp::from((Date::positive_infinity(), noon)).is_infinity());
+        assert!(Timestamp::from((Date::negative_infinity(), noon)).is_neg_infinity());
+        assert!(Timestamp::from(Date::positive_infinity()).is_infinity());
+        assert!(Date::from(Timestamp::negative_infinity()).is_neg_infinity());
+        assert!(Date::from(TimestampWithTimeZone::positive_infinity()).is_infinity());
+        assert!(TimestampWithTimeZone::try_from((
+            Timestamp::positive_infinity(),
+            "America/Denver"
+        ))?
+        .is_infinity());
+        assert!(TimestampWithTimeZone::try_from((Timestamp::negative_infinity(), "UTC"))?
+            .is_neg_infinity());
+        assert!(Timestamp::from(TimestampWithTimeZone::negative_infinity()).is_neg_infinity());
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_infinity_accessors() -> Result<(), DateTimeConversionError> {
+        assert_eq!(Date::positive_infinity().try_year(), None);
+        assert_eq!(Date::negative_infinity().try_month(), None);
+        assert_eq!(Timestamp::positive_infinity().try_day(), None);
+        assert_eq!(Timestamp::negative_infinity().try_hour(), None);
+        assert_eq!(TimestampWithTimeZone::positive_infinity().try_minute(), None);
+        assert_eq!(TimestampWithTimeZone::negative_infinity().try_second(), None);
+
+        let date = Date::new(2023, 7, 4)?;
+        assert_eq!(
+            (date.try_year(), date.try_month(), date.try_day()),
+            (Some(2023), Some(7), Some(4))
+        );
+        Ok(())
+    }
+
+    #[pg_test]
+    #[should_panic(expected = "infinite Date has no year")]
+    fn test_infinite_date_year_panics() {
+        Date::positive_infinity().year();
+    }
+
+    #[pg_test]
+    fn test_infinity_round_trips() -> Result<(), Box<dyn std::error::Error>> {
+        assert_eq!(Date::from_str("infinity")?, Date::positive_infinity());
+        assert_eq!(Timestamp::from_str("-infinity")?, Timestamp::negative_infinity());
+        assert_eq!(
+            TimestampWithTimeZone::from_str("infinity")?,
+            TimestampWithTimeZone::positive_infinity()
+        );
+
+        let json =
+            json!({ "date": Date::positive_infinity(), "ts": Timestamp::negative_infinity() });
+        assert_eq!(json!({ "date": "infinity", "ts": "-infinity" }), json);
+
+        let date: Date = from_value(json!("-infinity"))?;
+        assert!(date.is_neg_infinity());
+        let tstz: TimestampWithTimeZone = from_value(json!("infinity"))?;
+        assert!(tstz.is_infinity());
+
+        let round_trip = Spi::get_one_with_args::<Date>(
+            "SELECT accept_date_round_trip($1)",
+            vec![(PgBuiltInOids::DATEOID.oid(), Date::negative_infinity().into_datum())],
+        )?
+        .expect("datum was null");
+        assert!(round_trip.is_neg_infinity());
+        Ok(())
+    }
+
     #[rustfmt::skip]
     #[pg_test]
     fn test_from_str() -> Result<(), Box<dyn std::error::Error>> {
@@ -452,27 +543,15 @@ mod tests {
     #[pg_test]
     fn test_duration_to_interval_err() {
         use pgrx::datum::datetime_support::IntervalConversionError;
-        // normal limit of i32::MAX months
-        let duration = Duration::from_secs(
-            pg_sys::DAYS_PER_MONTH as u64 * i32::MAX as u64 * pg_sys::SECS_PER_DAY as u64,
-        );
-
+        // the most microseconds an Interval can hold
+        let duration = Duration::from_micros(i64::MAX as u64);
         let result = TryInto::<Interval>::try_into(duration);
-        match result {
-            Ok(_) => (),
-            _ => panic!("failed duration -> interval conversion"),
-        };
-
-        // one month too many, expect error
-        let duration = Duration::from_secs(
-            pg_sys::DAYS_PER_MONTH as u64 * (i32::MAX as u64 + 1u64) * pg_sys::SECS_PER_DAY as u64,
-        );
+        assert_eq!(result.map(|i| i.micros()), Ok(i64::MAX));
 
+        // one microsecond too many, expect error
+        let duration = Duration::from_micros(i64::MAX as u64 + 1);
         let result = TryInto::<Interval>::try_into(duration);
-        match result {
-            Err(IntervalConversionError::DurationMonthsOutOfBounds) => (),
-            _ => panic!("invalid duration -> interval conversion succeeded"),
-        };
+        assert_eq!(result.map(|_| ()), Err(IntervalConversionError::DurationTooLarge));
     }
 
     #[pg_test]
@@ -495,16 +574,22 @@ mod tests {
 
     #[pg_test]
     fn test_interval_to_duration_conversion() {
-        let i = Interval::new(42, 6, 3).unwrap();
-        let i_micros = i.as_micros();
+        let i = Interval::new(0, 0, 3).unwrap();
         let d: Duration = i.try_into().unwrap();
+        assert_eq!(d, Duration::from_micros(3));
 
-        assert_eq!(i_micros as u128, d.as_micros())
+        // months and days have no fixed length
+        let i = Interval::new(42, 6, 3).unwrap();
+        let d: Result<Duration, _> = i.try_into();
+        assert_eq!(d, Err(IntervalConversionError::InexactInterval));
+        let i = Interval::new(0, 1, 0).unwrap();
+        let d: Result<Duration, _> = i.try_into();
+        assert_eq!(d, Err(IntervalConversionError::InexactInterval));
     }
 
     #[pg_test]
     fn test_negative_interval_to_duration_conversion() {
-        let i = Interval::new(-42, -6, -3).unwrap();
+        let i = Interval::new(0, 0, -3).unwrap();
         let d: Result<Duration, _> = i.try_into();
 
         assert_eq!(d, Err(IntervalConversionError::NegativeInterval))
@@ -519,15 +604,16 @@ mod tests {
         assert_eq!(i.days(), 5);
         assert_eq!(i.micros(), 22_000_000); // 22 seconds
 
+        // a Duration is an exact span of time, so it's all kept in microseconds
         let d = Duration::from_secs(
-            pg_sys::DAYS_PER_MONTH as u64 * 3u64 * pg_sys::SECS_PER_DAY as u64 // 3 months
-                + 5u64 * pg_sys::SECS_PER_DAY as u64 // 5 days
+            5u64 * pg_sys::SECS_PER_DAY as u64 // 5 days
                 + 22u64, // 22 seconds more
         );
         let i: Interval = d.try_into().unwrap();
-        assert_eq!(i.months(), 3);
-        assert_eq!(i.days(), 5);
-        assert_eq!(i.micros(), 22_000_000); // 22 seconds
+        assert_eq!(i.months(), 0);
+        assert_eq!(i.days(), 0);
+        assert_eq!(i.micros(), 5 * 86_400_000_000 + 22_000_000);
+        assert_eq!(i, Interval::from_str("5 days 22 seconds").unwrap());
     }
 
     #[pg_test]
@@ -574,6 +660,28 @@ mod tests {
         Ok(())
     }
 
+    #[pg_test]
+    fn test_interval_from_micros_is_exact() {
+        let micros = i64::MAX / 3;
+        let i = Interval::from_micros(micros);
+        assert_eq!(i.micros(), micros);
+        assert_eq!((i.months(), i.days()), (0, 0));
+    }
+
+    #[pg_test]
+    fn test_timestamp_duration_arithmetic() -> Result<(), Box<dyn std::error::Error>> {
+        let ts = Timestamp::new(2023, 1, 31, 12, 0, 0.0)?;
+        let later = ts + Duration::from_secs(31 * pg_sys::SECS_PER_DAY as u64 + 1);
+        assert_eq!(later, Timestamp::new(2023, 3, 3, 12, 0, 1.0)?);
+        assert_eq!(later - Duration::from_secs(31 * pg_sys::SECS_PER_DAY as u64 + 1), ts);
+
+        let tstz = TimestampWithTimeZone::with_timezone(2023, 1, 31, 12, 0, 0.0, "UTC")?;
+        let later = tstz + Duration::from_micros(1_500_000);
+        assert_eq!(later, TimestampWithTimeZone::with_timezone(2023, 1, 31, 12, 0, 1.5, "UTC")?);
+        assert_eq!(later - Duration::from_micros(1_500_000), tstz);
+        Ok(())
+    }
+
     #[pg_test]
     fn test_old_date() -> Result<(), Box<dyn std::error::Error>> {
         let array = Spi::get_one::<Array<Date>>("SELECT ARRAY['1977-07-04']::date[]")?.unwrap();
@@ -581,4 +689,53 @@ mod tests {
         assert_eq!(first, Some(Some(Date::from_str("1977-07-04")?)));
         Ok(())
     }
+
+    #[pg_test]
+    fn test_timezone_lookup() -> Result<(), Box<dyn std::error::Error>> {
+        let new_york = TimeZone::lookup("America/New_York")?;
+        assert_eq!(new_york.name(), "America/New_York");
+        assert!(new_york.is_acceptable());
+        assert_eq!(
+            TimeZone::lookup("Not/A_Zone"),
+            Err(DateTimeConversionError::UnknownTimezone("Not/A_Zone".to_string()))
+        );
+        assert!(TimeZone::names().iter().any(|name| name == "Europe/Berlin"));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_timezone_session() -> Result<(), Box<dyn std::error::Error>> {
+        Spi::run("SET TIME ZONE 'Asia/Tokyo'")?;
+        assert_eq!(TimeZone::session().name(), "Asia/Tokyo");
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_timezone_dst() -> Result<(), Box<dyn std::error::Error>> {
+        let new_york = TimeZone::lookup("America/New_York")?;
+        let winter = TimestampWithTimeZone::with_timezone(2023, 1, 15, 12, 0, 0.0, "UTC")?;
+        let summer = TimestampWithTimeZone::with_timezone(2023, 7, 15, 12, 0, 0.0, "UTC")?;
+
+        assert_eq!(new_york.utc_offset_at(winter)?, -5 * 60 * 60);
+        assert_eq!(new_york.utc_offset_at(summer)?, -4 * 60 * 60);
+        assert!(!new_york.is_dst_at(winter)?);
+        assert!(new_york.is_dst_at(summer)?);
+        assert_eq!(new_york.abbreviation_at(winter)?, "EST");
+        assert_eq!(new_york.abbreviation_at(summer)?, "EDT");
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_timezone_local_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let new_york = TimeZone::lookup("America/New_York")?;
+        let instant = TimestampWithTimeZone::with_timezone(2023, 7, 15, 12, 0, 0.0, "UTC")?;
+
+        let local = new_york.to_local(instant)?;
+        assert_eq!(local, Timestamp::new(2023, 7, 15, 8, 0, 0.0)?);
+        assert_eq!(new_york.from_local(local)?, instant);
+
+        let fixed = TimeZone::fixed_offset(2 * 60 * 60);
+        assert_eq!(fixed.to_local(instant)?, Timestamp::new(2023, 7, 15, 14, 0, 0.0)?);
+        Ok(())
+    }
 }
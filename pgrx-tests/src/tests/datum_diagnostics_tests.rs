@@ -0,0 +1,126 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+    use std::cell::{Cell, RefCell};
+    use std::ffi::CStr;
+    use std::panic::AssertUnwindSafe;
+
+    thread_local! {
+        static PREVIOUS_HOOK: Cell<pg_sys::emit_log_hook_type> = Cell::new(None);
+        static CAPTURED: RefCell<Vec<(String, Option<String>)>> = RefCell::new(Vec::new());
+    }
+
+    #[pg_guard]
+    unsafe extern "C" fn capture_warning(edata: *mut pg_sys::ErrorData) {
+        if (*edata).elevel == pg_sys::WARNING as i32 {
+            let text = |ptr: *mut std::os::raw::c_char| {
+                (!ptr.is_null()).then(|| CStr::from_ptr(ptr).to_string_lossy().into_owned())
+            };
+            let warning = (text((*edata).message).unwrap_or_default(), text((*edata).detail));
+            CAPTURED.with(|captured| captured.borrow_mut().push(warning));
+        }
+        if let Some(previous) = PREVIOUS_HOOK.with(Cell::get) {
+            previous(edata);
+        }
+    }
+
+    /// The `(message, detail)` of each WARNING raised while running `f`
+    fn warnings<R>(f: impl FnOnce() -> R) -> (R, Vec<(String, Option<String>)>) {
+        unsafe {
+            PREVIOUS_HOOK
+                .with(|previous| previous.set(pg_sys::emit_log_hook.replace(capture_warning)));
+        }
+        let result = PgTryBuilder::new(AssertUnwindSafe(f))
+            .finally(|| unsafe { pg_sys::emit_log_hook = PREVIOUS_HOOK.with(Cell::get) })
+            .execute();
+        (result, CAPTURED.with(|captured| captured.take()))
+    }
+
+    /// Where a conversion in this file is reported to have happened
+    fn converted_here(detail: &Option<String>) -> bool {
+        detail
+            .as_deref()
+            .map_or(false, |detail| detail.starts_with(&format!("converted at {}:", file!())))
+    }
+
+    // declared as taking `bytea`, which doesn't match the Rust signature, so with the
+    // `datum-diagnostics` feature calling it raises a WARNING, but the call still works
+    #[pg_extern(sql = r#"
+        CREATE FUNCTION tests."diagnosed_len"(bytea) RETURNS int4
+        STRICT
+        LANGUAGE c /* Rust */
+        AS '@MODULE_PATHNAME@', '@FUNCTION_NAME@';
+    "#)]
+    fn diagnosed_len(s: &str) -> i32 {
+        s.len() as i32
+    }
+
+    #[pg_test]
+    fn test_mismatched_argument_still_converts() {
+        let (result, warnings) =
+            warnings(|| Spi::get_one::<i32>(r#"SELECT tests."diagnosed_len"('\x616263'::bytea)"#));
+        assert_eq!(result, Ok(Some(3)));
+
+        let [(message, detail)] = &warnings[..] else {
+            panic!("expected a single WARNING, got {warnings:?}");
+        };
+        assert_eq!(
+            message,
+            "argument 1 is of type `bytea` (oid 17, typmod -1), which can't be converted into \
+             `&str`, whose type is `text` (oid 25, typmod -1)"
+        );
+        // the argument of the `#[pg_extern]` above
+        assert!(converted_here(detail), "{detail:?}");
+    }
+
+    #[pg_test]
+    fn test_mismatched_spi_column_warns() {
+        let (result, warnings) = warnings(|| {
+            Spi::connect(|client| {
+                client.select("SELECT 'abc'::varchar(10)", None, None)?.first().get::<i32>(1)
+            })
+        });
+        assert!(matches!(result, Err(spi::Error::DatumError(_))));
+
+        let [(message, detail)] = &warnings[..] else {
+            panic!("expected a single WARNING, got {warnings:?}");
+        };
+        // the column's typmod is reported, from the tuple descriptor
+        assert_eq!(
+            message,
+            "a Datum of type `character varying(10)` (oid 1043, typmod 14) can't be converted \
+             into `i32`, whose type is `integer` (oid 23, typmod -1)"
+        );
+        // the `get()` just above
+        assert!(converted_here(detail), "{detail:?}");
+    }
+
+    #[pg_test]
+    fn test_mismatched_spi_column_still_fails() {
+        let result = Spi::get_one::<i32>("SELECT 'not a number'::text");
+        assert!(matches!(result, Err(spi::Error::DatumError(_))));
+    }
+
+    #[pg_test]
+    fn test_datum_diagnostics_are_warnings() -> Result<(), spi::Error> {
+        // the WARNING about the mismatch doesn't abort the transaction
+        let (result, warnings) = warnings(|| Spi::get_one::<bool>("SELECT 1::int8"));
+        assert!(result.is_err());
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(Spi::get_one::<i32>("SELECT 42")?, Some(42));
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+    use pgrx::varlena::{
+        datum_compression, rust_byte_slice_to_compressed_bytea, toast_raw_size, toast_size,
+        ToastCompression,
+    };
+    use pgrx::{datum_size, PgBytes};
+
+    #[pg_extern]
+    fn stored_size(b: PgBytes<'_>) -> i64 {
+        unsafe { toast_size(b.into_datum().unwrap()) as i64 }
+    }
+
+    #[pg_extern]
+    fn raw_size(b: PgBytes<'_>) -> i64 {
+        unsafe { toast_raw_size(b.into_datum().unwrap()) as i64 }
+    }
+
+    #[pg_extern]
+    fn is_compressed(b: PgBytes<'_>) -> bool {
+        unsafe { datum_compression(b.into_datum().unwrap()).is_some() }
+    }
+
+    #[pg_test]
+    fn test_datum_size() {
+        assert_eq!(datum_size(&42i64), Some(8));
+        assert_eq!(datum_size(&true), Some(1));
+        assert_eq!(datum_size(&"hello"), Some(9));
+        assert_eq!(datum_size(&Option::<i32>::None), None);
+    }
+
+    #[pg_test]
+    fn test_compressed_sizes() {
+        let bytea = rust_byte_slice_to_compressed_bytea(&[1; 100000], ToastCompression::Pglz);
+        let datum = pg_sys::Datum::from(bytea.as_ptr());
+        unsafe {
+            assert_eq!(toast_raw_size(datum), 100000 + pg_sys::VARHDRSZ);
+            assert!(toast_size(datum) < 10000);
+            assert_eq!(datum_compression(datum), Some(ToastCompression::Pglz));
+        }
+
+        let plain = pg_sys::Datum::from(pgrx::varlena::rust_byte_slice_to_bytea(b"abc").as_ptr());
+        unsafe {
+            assert_eq!(toast_size(plain), 3 + pg_sys::VARHDRSZ);
+            assert_eq!(datum_compression(plain), None);
+        }
+    }
+
+    #[pg_test]
+    fn test_toasted_sizes() -> Result<(), pgrx::spi::Error> {
+        Spi::run("CREATE TABLE datum_sizes (compressed bytea, external bytea)")?;
+        Spi::run("ALTER TABLE datum_sizes ALTER COLUMN external SET STORAGE EXTERNAL")?;
+        Spi::run(
+            "INSERT INTO datum_sizes
+             SELECT convert_to(repeat('a', 100000), 'UTF8'), convert_to(repeat('b', 100000), 'UTF8')",
+        )?;
+        let sizes = Spi::get_two::<bool, bool>(
+            "SELECT tests.stored_size(compressed) = pg_column_size(compressed)
+                    AND tests.raw_size(compressed) = 100000 + 4
+                    AND tests.is_compressed(compressed),
+                    tests.stored_size(external) = 100000 AND NOT tests.is_compressed(external)
+             FROM datum_sizes",
+        )?;
+        assert_eq!(sizes, (Some(true), Some(true)));
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+    use pgrx::prelude::*;
+    use pgrx::Detoasted;
+
+    #[pg_test]
+    fn test_detoasted_once() -> Result<(), pgrx::spi::Error> {
+        Spi::run(
+            "CREATE TABLE tests.big_text (v text);
+             ALTER TABLE tests.big_text ALTER COLUMN v SET STORAGE EXTERNAL;
+             INSERT INTO tests.big_text VALUES (repeat('abc', 100000));",
+        )?;
+        Spi::connect(|client| {
+            let datum = client
+                .select("SELECT v FROM tests.big_text", None, None)?
+                .first()
+                .get_datum_by_ordinal(1)?
+                .unwrap();
+            assert!(unsafe { pgrx::varatt_is_1b_e(datum.cast_mut_ptr::<pg_sys::varlena>()) });
+
+            let cache = Detoasted::<&str>::new();
+            assert!(cache.is_empty());
+            let first = unsafe { cache.get(datum, false) }.unwrap();
+            let second = unsafe { cache.get(datum, false) }.unwrap();
+            assert_eq!(first.len(), 300000);
+            assert_eq!(first.as_ptr(), second.as_ptr());
+            assert_eq!(cache.len(), 1);
+
+            // the same toasted value, read again, is recognized by its toast pointer
+            let again = client
+                .select("SELECT v FROM tests.big_text", None, None)?
+                .first()
+                .get_datum_by_ordinal(1)?
+                .unwrap();
+            let third = unsafe { cache.get(again, false) }.unwrap();
+            assert_eq!(first.as_ptr(), third.as_ptr());
+            assert_eq!(cache.len(), 1);
+            Ok(())
+        })
+    }
+
+    #[pg_test]
+    fn test_detoasted_plain_values() {
+        let datum = "hello".into_datum().unwrap();
+        let mut cache = Detoasted::<String>::default();
+        assert_eq!(unsafe { cache.detoast(datum) }, datum);
+        assert_eq!(unsafe { cache.get(datum, false) }.as_deref(), Some("hello"));
+        assert_eq!(unsafe { cache.get(datum, true) }, None);
+        assert_eq!(cache.len(), 1);
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,49 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::dfmgr::{ExternalLibrary, RendezvousVariable};
+    use pgrx::prelude::*;
+    use std::ptr::NonNull;
+
+    static ANSWER: i32 = 42;
+
+    #[pg_test]
+    fn test_rendezvous_variable() {
+        let first = RendezvousVariable::<i32>::find("pgrx_tests_rendezvous");
+        assert_eq!(first.get(), None);
+
+        unsafe { first.set(Some(NonNull::from(&ANSWER))) };
+        let second = RendezvousVariable::<i32>::find("pgrx_tests_rendezvous");
+        assert_eq!(second.get().map(|answer| unsafe { *answer.as_ref() }), Some(42));
+
+        unsafe { second.set(None) };
+        assert_eq!(first.get(), None);
+    }
+
+    #[pg_test]
+    fn test_external_library() {
+        let plpgsql = ExternalLibrary::load("$libdir/plpgsql").expect("plpgsql is not installed");
+        assert_eq!(plpgsql.name(), "$libdir/plpgsql");
+        assert!(plpgsql.lookup_function("plpgsql_call_handler").is_some());
+        assert!(plpgsql.lookup("Pg_magic_func").is_some());
+        assert!(plpgsql.lookup("pgrx_tests_no_such_symbol").is_none());
+    }
+
+    #[pg_test]
+    fn test_missing_external_library() {
+        assert!(ExternalLibrary::load("$libdir/pgrx_tests_no_such_library").is_none());
+    }
+}
@@ -0,0 +1,82 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+use pgrx::prelude::*;
+
+extension_sql!(
+    r#"
+CREATE DOMAIN positive_int AS integer CHECK (VALUE > 0);
+CREATE DOMAIN required_text AS text NOT NULL;
+"#,
+    name = "create_test_domains",
+);
+
+#[pg_extern(requires = ["create_test_domains"])]
+fn domain_halve(value: domain!(i32, "positive_int")) -> domain!(i32, "positive_int") {
+    value / 2
+}
+
+#[pg_extern(requires = ["create_test_domains"])]
+fn domain_maybe_upper(
+    value: Option<domain!(&str, "required_text")>,
+) -> Option<domain!(String, "required_text")> {
+    value.map(|v| v.to_uppercase())
+}
+
+#[pg_extern(requires = ["create_test_domains"])]
+fn domain_nothing() -> Option<domain!(String, "required_text")> {
+    None
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+
+    #[pg_test]
+    fn test_domain_in_function_signature() {
+        let args = Spi::get_one::<String>(
+            "SELECT pg_get_function_arguments('domain_halve'::regproc)::text",
+        );
+        assert_eq!(args, Ok(Some("value positive_int".to_string())));
+        let returns =
+            Spi::get_one::<String>("SELECT pg_get_function_result('domain_halve'::regproc)::text");
+        assert_eq!(returns, Ok(Some("positive_int".to_string())));
+    }
+
+    #[pg_test]
+    fn test_domain_roundtrip() {
+        let result = Spi::get_one::<i32>("SELECT domain_halve(10)");
+        assert_eq!(result, Ok(Some(5)));
+        let result = Spi::get_one::<String>("SELECT domain_maybe_upper('abc')::text");
+        assert_eq!(result, Ok(Some("ABC".to_string())));
+    }
+
+    #[pg_test(
+        error = "value for domain positive_int violates check constraint \"positive_int_check\""
+    )]
+    fn test_domain_argument_is_checked() {
+        Spi::run("SELECT domain_halve(-4)").unwrap();
+    }
+
+    #[pg_test(
+        error = "value for domain positive_int violates check constraint \"positive_int_check\""
+    )]
+    fn test_domain_return_is_checked() {
+        Spi::run("SELECT domain_halve(1)").unwrap();
+    }
+
+    #[pg_test(error = "domain required_text does not allow null values")]
+    fn test_domain_null_return_is_checked() {
+        Spi::run("SELECT domain_nothing()").unwrap();
+    }
+}
@@ -23,14 +23,38 @@ fn take_foo_enum(value: Foo) -> Foo {
     Foo::Three
 }
 
+extension_sql!(
+    "CREATE TYPE existing_mood AS ENUM ('sad', 'so-so', 'happy');",
+    name = "create_existing_mood"
+);
+
+#[derive(PostgresExistingEnum, PartialEq, Debug)]
+#[enum_type = "existing_mood"]
+pub enum Mood {
+    Sad,
+    #[label = "so-so"]
+    SoSo,
+    #[label = "happy"]
+    Happy,
+}
+
+#[pg_extern(requires = ["create_existing_mood"])]
+fn cheer_up(mood: Mood) -> Mood {
+    match mood {
+        Mood::Sad => Mood::SoSo,
+        _ => Mood::Happy,
+    }
+}
+
 #[cfg(any(test, feature = "pg_test"))]
 #[pgrx::pg_schema]
 mod tests {
     #[allow(unused_imports)]
     use crate as pgrx_tests;
 
-    use crate::tests::enum_type_tests::Foo;
+    use crate::tests::enum_type_tests::{Foo, Mood};
     use pgrx::prelude::*;
+    use pgrx::{PgEnumType, PgEnumTypeError};
 
     #[test]
     fn make_idea_happy() {}
@@ -40,4 +64,38 @@ mod tests {
         let result = Spi::get_one::<Foo>("SELECT take_foo_enum('One');");
         assert_eq!(Ok(Some(Foo::Three)), result);
     }
+
+    #[pg_test]
+    fn test_existing_enum() {
+        let result = Spi::get_one::<Mood>("SELECT cheer_up('so-so');");
+        assert_eq!(Ok(Some(Mood::Happy)), result);
+        let result = Spi::get_one::<String>("SELECT cheer_up('sad')::text;");
+        assert_eq!(Ok(Some("so-so".to_string())), result);
+    }
+
+    #[pg_test]
+    fn test_enum_type_lookup() {
+        let mood = PgEnumType::lookup("existing_mood").unwrap();
+        assert_eq!(mood.labels().collect::<Vec<_>>(), vec!["sad", "so-so", "happy"]);
+        let happy = mood.label_oid("happy").unwrap();
+        assert_eq!(mood.label_of(happy), Some("happy"));
+        assert_eq!(mood.label_oid("elated"), None);
+
+        assert_eq!(
+            PgEnumType::lookup("no_such_type"),
+            Err(PgEnumTypeError::NotFound("no_such_type".into()))
+        );
+        assert_eq!(PgEnumType::lookup("text"), Err(PgEnumTypeError::NotAnEnum("text".into())));
+    }
+
+    // `ALTER TYPE ... ADD VALUE` can't be run in a transaction block before Postgres 12
+    #[cfg(not(feature = "pg11"))]
+    #[pg_test]
+    fn test_enum_type_lookup_sees_new_labels() {
+        let before = PgEnumType::lookup("existing_mood").unwrap();
+        assert_eq!(before.label_oid("elated"), None);
+        Spi::run("ALTER TYPE existing_mood ADD VALUE 'elated'").unwrap();
+        let after = PgEnumType::lookup("existing_mood").unwrap();
+        assert_eq!(after.labels().last(), Some("elated"));
+    }
 }
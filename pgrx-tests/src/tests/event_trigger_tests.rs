@@ -0,0 +1,124 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::event_trigger::{DdlJournal, EventTrigger, EventTriggerEvent, PgEventTrigger};
+    use pgrx::prelude::*;
+
+    static JOURNAL: DdlJournal = DdlJournal::new("pgrx_tests", "event_trigger_journal");
+
+    #[pg_extern]
+    fn journal_ddl(fcinfo: pg_sys::FunctionCallInfo) -> EventTrigger {
+        let trigger = unsafe { PgEventTrigger::from_fcinfo(fcinfo) }.unwrap();
+        for entry in JOURNAL.record(&trigger).unwrap() {
+            assert_eq!(entry.event, trigger.event());
+        }
+        EventTrigger
+    }
+
+    #[pg_extern]
+    fn forbid_drop_table(fcinfo: pg_sys::FunctionCallInfo) -> EventTrigger {
+        let trigger = unsafe { PgEventTrigger::from_fcinfo(fcinfo) }.unwrap();
+        if trigger.event() == EventTriggerEvent::DdlCommandStart && trigger.tag() == "DROP TABLE" {
+            error!("tables are forever");
+        }
+        EventTrigger
+    }
+
+    fn setup_journal() -> Result<(), spi::Error> {
+        Spi::run(
+            "CREATE TABLE event_trigger_journal (
+                id bigserial,
+                event text NOT NULL,
+                command_tag text NOT NULL,
+                object_type text,
+                schema_name text,
+                object_identity text,
+                classid oid,
+                objid oid
+            )",
+        )?;
+        Spi::run("CREATE TABLE journaled (id int)")?;
+        Spi::run("ALTER EXTENSION pgrx_tests ADD TABLE journaled")?;
+        Spi::run("CREATE TABLE not_journaled (id int)")?;
+        Spi::run(
+            "CREATE EVENT TRIGGER journal_ddl ON ddl_command_end
+             EXECUTE FUNCTION tests.journal_ddl()",
+        )?;
+        Spi::run(
+            "CREATE EVENT TRIGGER journal_drops ON sql_drop EXECUTE FUNCTION tests.journal_ddl()",
+        )
+    }
+
+    fn journal() -> Result<Vec<String>, spi::Error> {
+        Spi::get_one::<Vec<String>>(
+            "SELECT coalesce(array_agg(event || ':' || command_tag || ':' || object_type ORDER BY id), '{}')
+               FROM event_trigger_journal",
+        )
+        .map(Option::unwrap_or_default)
+    }
+
+    #[pg_test]
+    fn test_ddl_journal() -> Result<(), spi::Error> {
+        setup_journal()?;
+        Spi::run("CREATE INDEX journaled_idx ON journaled (id)")?;
+        Spi::run("ALTER TABLE journaled ADD COLUMN name text")?;
+        Spi::run("DROP INDEX journaled_idx")?;
+        assert_eq!(
+            journal()?,
+            vec![
+                "ddl_command_end:CREATE INDEX:index",
+                "ddl_command_end:ALTER TABLE:table",
+                "sql_drop:DROP INDEX:index",
+            ]
+        );
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_ddl_journal_ignores_other_objects() -> Result<(), spi::Error> {
+        setup_journal()?;
+        Spi::run("CREATE INDEX not_journaled_idx ON not_journaled (id)")?;
+        Spi::run("ALTER TABLE not_journaled ADD COLUMN name text")?;
+        Spi::run("DROP INDEX not_journaled_idx")?;
+        Spi::run("DROP TABLE not_journaled")?;
+        assert_eq!(journal()?, Vec::<String>::new());
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_ddl_journal_ignores_reused_identities() -> Result<(), spi::Error> {
+        setup_journal()?;
+        Spi::run("CREATE INDEX reused_idx ON journaled (id)")?;
+        Spi::run("DROP INDEX reused_idx")?;
+        // an unrelated index which happens to have the same name
+        Spi::run("CREATE INDEX reused_idx ON not_journaled (id)")?;
+        Spi::run("DROP INDEX reused_idx")?;
+        assert_eq!(
+            journal()?,
+            vec!["ddl_command_end:CREATE INDEX:index", "sql_drop:DROP INDEX:index"]
+        );
+        Ok(())
+    }
+
+    #[pg_test(error = "tables are forever")]
+    fn test_event_trigger_tag() -> Result<(), spi::Error> {
+        Spi::run("CREATE TABLE forever (id int)")?;
+        Spi::run(
+            "CREATE EVENT TRIGGER forbid_drop_table ON ddl_command_start
+             EXECUTE FUNCTION tests.forbid_drop_table()",
+        )?;
+        Spi::run("DROP TABLE forever")
+    }
+}
@@ -0,0 +1,73 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::fake_clock;
+    use pgrx::prelude::*;
+    use pgrx::Interval;
+
+    const PINNED: &str = "2023-01-01 00:00:00+00";
+
+    fn pinned() -> TimestampWithTimeZone {
+        PINNED.parse().unwrap()
+    }
+
+    #[pg_test]
+    fn test_fake_clock_unpinned_by_default() {
+        assert_eq!(fake_clock::pinned(), None);
+        assert_ne!(pgrx::datum::clock_timestamp(), pinned());
+    }
+
+    #[pg_test(fake_clock = "2023-01-01 00:00:00+00")]
+    fn test_fake_clock_preset() -> Result<(), spi::Error> {
+        assert_eq!(pgrx::datum::now(), pinned());
+        assert_eq!(pgrx::datum::transaction_timestamp(), pinned());
+        assert_eq!(pgrx::datum::statement_timestamp(), pinned());
+        assert_eq!(pgrx::datum::clock_timestamp(), pinned());
+
+        let sql_now = Spi::get_one::<TimestampWithTimeZone>("SELECT now()")?;
+        assert_eq!(sql_now, Some(pinned()));
+        let sql_clock = Spi::get_one::<TimestampWithTimeZone>("SELECT clock_timestamp()")?;
+        assert_eq!(sql_clock, Some(pinned()));
+        Ok(())
+    }
+
+    #[pg_test(fake_clock = "2023-01-01 00:00:00+00")]
+    fn test_fake_clock_advance() -> Result<(), spi::Error> {
+        fake_clock::advance(Interval::from_days(1));
+        let tomorrow: TimestampWithTimeZone = "2023-01-02 00:00:00+00".parse().unwrap();
+        assert_eq!(pgrx::datum::now(), tomorrow);
+        assert_eq!(Spi::get_one::<TimestampWithTimeZone>("SELECT now()")?, Some(tomorrow));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_fake_clock_set_and_clear() {
+        fake_clock::set(pinned());
+        assert_eq!(fake_clock::pinned(), Some(pinned()));
+        assert_eq!(pgrx::datum::now(), pinned());
+
+        fake_clock::clear();
+        assert_eq!(fake_clock::pinned(), None);
+        assert_ne!(pgrx::datum::now(), pinned());
+    }
+
+    #[pg_test(
+        error = "invalid value for `pgrx.fake_clock`: THe date or time is not in the correct format"
+    )]
+    fn test_fake_clock_invalid_setting() {
+        Spi::run("SET LOCAL pgrx.fake_clock = 'not a time'").unwrap();
+        pgrx::datum::now();
+    }
+}
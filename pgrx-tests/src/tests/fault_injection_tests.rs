@@ -0,0 +1,70 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::fault_injection::{self, fault_point};
+    use pgrx::prelude::*;
+    use pgrx::PgBox;
+
+    fn faults(name: &str) -> bool {
+        PgTryBuilder::new(|| {
+            fault_point(name);
+            false
+        })
+        .catch_others(|_| true)
+        .execute()
+    }
+
+    #[pg_test(error = "injected fault at `spi`")]
+    fn test_fault_at_spi() -> Result<(), spi::Error> {
+        Spi::run("SET pgrx.inject_fault = 'spi'")?;
+        Spi::get_one::<i32>("SELECT 1")?;
+        Ok(())
+    }
+
+    #[pg_test(error = "injected fault at `datum`")]
+    fn test_fault_at_datum() -> Result<(), spi::Error> {
+        Spi::run("SET pgrx.inject_fault = 'datum'")?;
+        unsafe { i32::from_datum(pg_sys::Datum::from(42), false) };
+        Ok(())
+    }
+
+    #[pg_test(error = "injected fault at `alloc`")]
+    fn test_fault_at_alloc() -> Result<(), spi::Error> {
+        Spi::run("SET pgrx.inject_fault = 'alloc'")?;
+        unsafe { PgBox::<pg_sys::ItemPointerData>::alloc0() };
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_unarmed_fault_points() -> Result<(), spi::Error> {
+        Spi::run("SET pgrx.inject_fault = 'tests.something_else'")?;
+        assert_eq!(Spi::get_one::<i32>("SELECT 1")?, Some(1));
+        assert!(!faults("tests.point"));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_fault_on_nth_hit() -> Result<(), spi::Error> {
+        Spi::run("SET pgrx.inject_fault = 'tests.other, tests.point:2'")?;
+        assert!(!faults("tests.point"));
+        assert!(faults("tests.point"));
+        assert!(!faults("tests.point"));
+
+        fault_injection::reset();
+        assert!(!faults("tests.point"));
+        assert!(faults("tests.point"));
+        Ok(())
+    }
+}
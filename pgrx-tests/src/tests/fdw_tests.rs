@@ -0,0 +1,592 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    #[cfg(not(feature = "pg11"))]
+    use pgrx::cost::PathCost;
+    #[cfg(not(feature = "pg11"))]
+    use pgrx::fdw::{recheck_join, set_foreign_join_paths, ForeignJoin, ForeignJoinPaths};
+    use pgrx::fdw::{FdwOptions, FdwOptionsError, OptionContext, ReturnedRow};
+    use pgrx::prelude::*;
+    #[cfg(not(feature = "pg11"))]
+    use pgrx::{PgList, PgMemoryContexts, PgRelation};
+    use std::num::NonZeroUsize;
+
+    /// An FDW whose "remote server" makes up each foreign table's rows from its options, and
+    /// which pushes inner joins of two of its tables down to that server.  The server returns
+    /// every pairing of the tables' rows, and the join's clauses are checked locally.
+    #[cfg(not(feature = "pg11"))]
+    struct JoinTestFdw;
+
+    #[cfg(not(feature = "pg11"))]
+    #[derive(FdwOptions)]
+    struct JoinTestOptions {
+        rows: i32,
+        prefix: String,
+    }
+
+    /// The rows of the foreign table `relid`, as `(id, name)`
+    #[cfg(not(feature = "pg11"))]
+    fn remote_rows(relid: pg_sys::Oid) -> Vec<(i32, String)> {
+        let options = JoinTestOptions::for_table(relid).unwrap();
+        (1..=options.rows).map(|id| (id, format!("{}{id}", options.prefix))).collect()
+    }
+
+    #[cfg(not(feature = "pg11"))]
+    #[pg_extern]
+    fn join_test_fdw_handler() -> PgBox<pg_sys::FdwRoutine> {
+        unsafe {
+            let mut routine = PgBox::<pg_sys::FdwRoutine>::alloc_node(pg_sys::NodeTag_T_FdwRoutine);
+            routine.GetForeignRelSize = Some(join_test_rel_size);
+            routine.GetForeignPaths = Some(join_test_paths);
+            routine.GetForeignPlan = Some(join_test_plan);
+            routine.BeginForeignScan = Some(join_test_begin);
+            routine.IterateForeignScan = Some(join_test_iterate);
+            routine.ReScanForeignScan = Some(join_test_rescan);
+            routine.EndForeignScan = Some(join_test_end);
+            routine.RecheckForeignScan = Some(join_test_recheck);
+            set_foreign_join_paths::<JoinTestFdw>(&mut routine);
+            routine.into_pg_boxed()
+        }
+    }
+
+    #[cfg(not(feature = "pg11"))]
+    impl ForeignJoinPaths for JoinTestFdw {
+        fn get_foreign_join_paths(join: ForeignJoin<'_>) {
+            unsafe {
+                let is_base_rel = |rel: *mut pg_sys::RelOptInfo| {
+                    (*rel).reloptkind == pg_sys::RelOptKind_RELOPT_BASEREL
+                };
+                if join.already_considered()
+                    || join.jointype() != pg_sys::JoinType_JOIN_INNER
+                    || !is_base_rel(join.outerrel())
+                    || !is_base_rel(join.innerrel())
+                {
+                    return;
+                }
+                let quals = pg_sys::extract_actual_clauses(join.restrictions().as_ptr(), false);
+                join.set_fdw_private(quals);
+                // far cheaper than joining the tables locally, so the planner always pushes it down
+                let cost = PathCost { startup: 0.0, total: 0.01, rows: join.rows() };
+                join.add_path(cost, quals);
+            }
+        }
+    }
+
+    #[cfg(not(feature = "pg11"))]
+    #[pg_guard]
+    unsafe extern "C" fn join_test_rel_size(
+        _root: *mut pg_sys::PlannerInfo,
+        baserel: *mut pg_sys::RelOptInfo,
+        foreigntableid: pg_sys::Oid,
+    ) {
+        (*baserel).rows = remote_rows(foreigntableid).len() as f64;
+    }
+
+    #[cfg(not(feature = "pg11"))]
+    #[pg_guard]
+    unsafe extern "C" fn join_test_paths(
+        root: *mut pg_sys::PlannerInfo,
+        baserel: *mut pg_sys::RelOptInfo,
+        _foreigntableid: pg_sys::Oid,
+    ) {
+        let rows = (*baserel).rows;
+        let path = pg_sys::create_foreignscan_path(
+            root,
+            baserel,
+            std::ptr::null_mut(),
+            rows,
+            10.0,
+            10.0 + rows,
+            std::ptr::null_mut(),
+            // a table whose scan needs values from outside of it must say so
+            (*baserel).lateral_relids,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        );
+        pg_sys::add_path(baserel, path.cast());
+    }
+
+    #[cfg(not(feature = "pg11"))]
+    #[pg_guard]
+    unsafe extern "C" fn join_test_plan(
+        _root: *mut pg_sys::PlannerInfo,
+        foreignrel: *mut pg_sys::RelOptInfo,
+        _foreigntableid: pg_sys::Oid,
+        best_path: *mut pg_sys::ForeignPath,
+        tlist: *mut pg_sys::List,
+        scan_clauses: *mut pg_sys::List,
+        outer_plan: *mut pg_sys::Plan,
+    ) -> *mut pg_sys::ForeignScan {
+        let null = std::ptr::null_mut();
+        if (*foreignrel).reloptkind == pg_sys::RelOptKind_RELOPT_BASEREL {
+            let quals = pg_sys::extract_actual_clauses(scan_clauses, false);
+            return pg_sys::make_foreignscan(
+                tlist,
+                quals,
+                (*foreignrel).relid,
+                null,
+                null,
+                null,
+                null,
+                outer_plan,
+            );
+        }
+
+        // the join's clauses are checked locally, so the remote rows need their columns too
+        let quals = (*best_path).fdw_private;
+        let flags = pg_sys::PVC_RECURSE_PLACEHOLDERS as i32;
+        let scan_tlist = pg_sys::add_to_flat_tlist(
+            null,
+            pg_sys::pull_var_clause((*(*foreignrel).reltarget).exprs.cast(), flags),
+        );
+        let scan_tlist =
+            pg_sys::add_to_flat_tlist(scan_tlist, pg_sys::pull_var_clause(quals.cast(), flags));
+        // the local join rechecks rows for `EvalPlanQual`, which must look like the remote ones
+        if !outer_plan.is_null() {
+            (*outer_plan).targetlist = scan_tlist;
+        }
+        pg_sys::make_foreignscan(tlist, quals, 0, null, null, scan_tlist, null, outer_plan)
+    }
+
+    #[cfg(not(feature = "pg11"))]
+    struct JoinTestScan {
+        rows: Vec<Vec<Option<pg_sys::Datum>>>,
+        next: usize,
+    }
+
+    #[cfg(not(feature = "pg11"))]
+    #[pg_guard]
+    unsafe extern "C" fn join_test_begin(node: *mut pg_sys::ForeignScanState, eflags: i32) {
+        if eflags & pg_sys::EXEC_FLAG_EXPLAIN_ONLY as i32 != 0 {
+            return;
+        }
+
+        let plan = (*node).ss.ps.plan as *mut pg_sys::ForeignScan;
+        let rows: Vec<Vec<Option<pg_sys::Datum>>> = if (*plan).scan.scanrelid > 0 {
+            let relid = (*(*node).ss.ss_currentRelation).rd_id;
+            remote_rows(relid)
+                .into_iter()
+                .map(|(id, name)| vec![id.into_datum(), name.into_datum()])
+                .collect()
+        } else {
+            // the two tables of the join, as their range table indexes
+            let outer = pg_sys::bms_next_member((*plan).fs_relids, -1);
+            let inner = pg_sys::bms_next_member((*plan).fs_relids, outer);
+            let range_table =
+                PgList::<pg_sys::RangeTblEntry>::from_pg((*(*node).ss.ps.state).es_range_table);
+            let relid = |varno: i32| (*range_table.get_ptr(varno as usize - 1).unwrap()).relid;
+            let (outer_rel, inner_rel) =
+                (PgRelation::open(relid(outer)), PgRelation::open(relid(inner)));
+            let (outer_rows, inner_rows) =
+                (remote_rows(outer_rel.oid()), remote_rows(inner_rel.oid()));
+
+            let scan_tlist = PgList::<pg_sys::TargetEntry>::from_pg((*plan).fdw_scan_tlist);
+            let mut rows = Vec::new();
+            for outer_row in &outer_rows {
+                for inner_row in &inner_rows {
+                    let row = scan_tlist.iter_ptr().map(|entry| {
+                        let var = (*entry).expr as *mut pg_sys::Var;
+                        let (rel, (id, name)) = if (*var).varno as i32 == outer {
+                            (&outer_rel, outer_row)
+                        } else {
+                            (&inner_rel, inner_row)
+                        };
+                        match (*var).varattno {
+                            1 => (*id).into_datum(),
+                            2 => name.as_str().into_datum(),
+                            // the whole row, which `SELECT ... FOR UPDATE` needs for rechecks
+                            0 => {
+                                let mut values = [
+                                    (*id).into_datum().unwrap(),
+                                    name.as_str().into_datum().unwrap(),
+                                ];
+                                let mut nulls = [false, false];
+                                let tuple = pg_sys::heap_form_tuple(
+                                    rel.rd_att,
+                                    values.as_mut_ptr(),
+                                    nulls.as_mut_ptr(),
+                                );
+                                Some(pg_sys::heap_copy_tuple_as_datum(tuple, rel.rd_att))
+                            }
+                            attno => panic!("unexpected column {attno}"),
+                        }
+                    });
+                    rows.push(row.collect());
+                }
+            }
+            rows
+        };
+
+        let state = JoinTestScan { rows, next: 0 };
+        (*node).fdw_state =
+            PgMemoryContexts::CurrentMemoryContext.leak_and_drop_on_delete(state).cast();
+    }
+
+    #[cfg(not(feature = "pg11"))]
+    #[pg_guard]
+    unsafe extern "C" fn join_test_iterate(
+        node: *mut pg_sys::ForeignScanState,
+    ) -> *mut pg_sys::TupleTableSlot {
+        let slot = (*node).ss.ss_ScanTupleSlot;
+        (*(*slot).tts_ops).clear.unwrap()(slot);
+        let state = &mut *((*node).fdw_state as *mut JoinTestScan);
+        if let Some(row) = state.rows.get(state.next) {
+            state.next += 1;
+            for (i, datum) in row.iter().enumerate() {
+                *(*slot).tts_values.add(i) = datum.unwrap_or(pg_sys::Datum::from(0usize));
+                *(*slot).tts_isnull.add(i) = datum.is_none();
+            }
+            pg_sys::ExecStoreVirtualTuple(slot);
+        }
+        slot
+    }
+
+    #[cfg(not(feature = "pg11"))]
+    #[pg_guard]
+    unsafe extern "C" fn join_test_rescan(node: *mut pg_sys::ForeignScanState) {
+        (*((*node).fdw_state as *mut JoinTestScan)).next = 0;
+    }
+
+    #[cfg(not(feature = "pg11"))]
+    #[pg_guard]
+    unsafe extern "C" fn join_test_end(_node: *mut pg_sys::ForeignScanState) {}
+
+    #[cfg(not(feature = "pg11"))]
+    #[pg_guard]
+    unsafe extern "C" fn join_test_recheck(
+        node: *mut pg_sys::ForeignScanState,
+        slot: *mut pg_sys::TupleTableSlot,
+    ) -> bool {
+        recheck_join(node, slot)
+    }
+
+    #[cfg(not(feature = "pg11"))]
+    fn create_join_test_tables() -> Result<(), pgrx::spi::Error> {
+        Spi::run("CREATE FOREIGN DATA WRAPPER join_test_fdw HANDLER tests.join_test_fdw_handler")?;
+        Spi::run("CREATE SERVER join_test_server FOREIGN DATA WRAPPER join_test_fdw")?;
+        Spi::run(
+            "CREATE FOREIGN TABLE join_left (id int4, name text) SERVER join_test_server
+             OPTIONS (rows '3', prefix 'l')",
+        )?;
+        Spi::run(
+            "CREATE FOREIGN TABLE join_right (id int4, name text) SERVER join_test_server
+             OPTIONS (rows '4', prefix 'r')",
+        )
+    }
+
+    /// The plan's nodes, outermost first, without their relations
+    #[cfg(not(feature = "pg11"))]
+    fn plan_nodes(query: &str) -> Result<Vec<String>, pgrx::spi::Error> {
+        let plan = Spi::connect(|client| {
+            client
+                .select(&format!("EXPLAIN (COSTS OFF) {query}"), None, None)?
+                .map(|row| row.get::<String>(1))
+                .collect::<Result<Vec<_>, pgrx::spi::Error>>()
+        })?;
+        // details such as `Sort Key: ...` aren't nodes
+        let nodes = plan.into_iter().flatten().filter(|line| !line.contains(':')).map(|line| {
+            let node = line.trim().trim_start_matches("->").trim();
+            node.split(" on ").next().unwrap_or(node).to_string()
+        });
+        Ok(nodes.collect())
+    }
+
+    /// Rows joined on `id`, as `(left name, right name)`
+    #[cfg(not(feature = "pg11"))]
+    fn joined(query: &str) -> Result<Vec<(String, String)>, pgrx::spi::Error> {
+        Spi::connect(|client| {
+            client
+                .select(query, None, None)?
+                .map(|row| Ok((row.get::<String>(1)?.unwrap(), row.get::<String>(2)?.unwrap())))
+                .collect()
+        })
+    }
+
+    #[cfg(not(feature = "pg11"))]
+    #[pg_test]
+    fn test_foreign_join_pushdown() -> Result<(), pgrx::spi::Error> {
+        create_join_test_tables()?;
+        let query = "SELECT l.name, r.name FROM join_left l JOIN join_right r ON l.id = r.id
+                     ORDER BY l.id";
+
+        // the whole join is a single foreign scan
+        let nodes = plan_nodes(query)?;
+        assert_eq!(nodes, vec!["Sort".to_string(), "Foreign Scan".to_string()]);
+
+        let expected = vec![
+            ("l1".to_string(), "r1".to_string()),
+            ("l2".to_string(), "r2".to_string()),
+            ("l3".to_string(), "r3".to_string()),
+        ];
+        assert_eq!(joined(query)?, expected);
+        Ok(())
+    }
+
+    #[cfg(not(feature = "pg11"))]
+    #[pg_test]
+    fn test_foreign_join_pushdown_epq() -> Result<(), pgrx::spi::Error> {
+        create_join_test_tables()?;
+        let query = "SELECT l.name, r.name FROM join_left l JOIN join_right r ON l.id = r.id
+                     ORDER BY l.id FOR UPDATE";
+
+        // rows locked for update may have to be rechecked, with the local join under the scan
+        let nodes = plan_nodes(query)?;
+        let scan = nodes.iter().position(|node| node == "Foreign Scan");
+        let scan = scan.expect("the join should be pushed down");
+        assert!(
+            nodes[scan + 1..].iter().any(|node| node.ends_with("Join") || node == "Nested Loop"),
+            "the pushed-down join should keep a local join to recheck rows with: {nodes:?}"
+        );
+
+        let expected = vec![
+            ("l1".to_string(), "r1".to_string()),
+            ("l2".to_string(), "r2".to_string()),
+            ("l3".to_string(), "r3".to_string()),
+        ];
+        assert_eq!(joined(query)?, expected);
+        Ok(())
+    }
+
+    #[cfg(not(feature = "pg11"))]
+    #[pg_test]
+    fn test_foreign_join_lateral_not_pushed_down() -> Result<(), pgrx::spi::Error> {
+        create_join_test_tables()?;
+        // `outer_id` has to be computed by the join of `l` and `r`, which so needs `o`
+        let query = "SELECT o.name, x.name, x.outer_id FROM join_left o LEFT JOIN LATERAL (
+                         SELECT r.name, o.id AS outer_id
+                         FROM join_left l JOIN join_right r ON l.id = r.id
+                     ) x ON true";
+        let nodes = plan_nodes(query)?;
+        assert_eq!(
+            nodes.iter().filter(|node| *node == "Foreign Scan").count(),
+            3,
+            "a join needing values from outside of it can't be pushed down: {nodes:?}"
+        );
+        let count = Spi::get_one::<i64>(&format!("SELECT count(*) FROM ({query}) q"))?;
+        assert_eq!(count, Some(9));
+        Ok(())
+    }
+
+    #[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+    struct NeverAsync;
+
+    #[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+    impl pgrx::fdw::ForeignAsync for NeverAsync {
+        fn is_async_capable(_path: *mut pg_sys::ForeignPath) -> bool {
+            false
+        }
+        fn request(_request: pgrx::fdw::AsyncRequest<'_>) {}
+        fn configure_wait(_request: pgrx::fdw::AsyncRequest<'_>) {}
+        fn notify(_request: pgrx::fdw::AsyncRequest<'_>) {}
+    }
+
+    #[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+    #[pg_test]
+    fn test_set_foreign_async() {
+        let mut routine = pg_sys::FdwRoutine::default();
+        pgrx::fdw::set_foreign_async::<NeverAsync>(&mut routine);
+        assert!(routine.IsForeignPathAsyncCapable.is_some());
+        assert!(routine.ForeignAsyncRequest.is_some());
+        assert!(routine.ForeignAsyncConfigureWait.is_some());
+        assert!(routine.ForeignAsyncNotify.is_some());
+    }
+
+    #[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+    #[pg_test]
+    fn test_async_notifier() {
+        fn readable(socket: pg_sys::pgsocket) -> bool {
+            let mut fd = libc::pollfd { fd: socket, events: libc::POLLIN, revents: 0 };
+            unsafe { libc::poll(&mut fd, 1, 0) == 1 }
+        }
+
+        let notifier = pgrx::fdw::AsyncNotifier::new().unwrap();
+        assert!(!readable(notifier.socket()));
+
+        let waker = notifier.waker().unwrap();
+        std::thread::spawn(move || {
+            waker.wake();
+            waker.wake();
+        })
+        .join()
+        .unwrap();
+        assert!(readable(notifier.socket()));
+
+        notifier.drain();
+        assert!(!readable(notifier.socket()));
+    }
+
+    #[pg_test]
+    fn test_returned_row() -> Result<(), pgrx::TryFromDatumError> {
+        Spi::run("CREATE TYPE returned_row AS (id bigint, name text, note text)").unwrap();
+        let tupdesc = pgrx::PgTupleDesc::for_composite_type("returned_row").unwrap();
+        unsafe {
+            #[cfg(feature = "pg11")]
+            let slot = pg_sys::MakeSingleTupleTableSlot(tupdesc.as_ptr());
+            #[cfg(not(feature = "pg11"))]
+            let slot = pg_sys::MakeSingleTupleTableSlot(tupdesc.as_ptr(), &pg_sys::TTSOpsVirtual);
+
+            let stored = ReturnedRow::new(slot)
+                .set_by_index(NonZeroUsize::new(1).unwrap(), 42i64)?
+                .set_by_name("name", "Nami")?
+                .store();
+            assert_eq!(stored, slot);
+
+            let values = std::slice::from_raw_parts((*slot).tts_values, 3);
+            let isnull = std::slice::from_raw_parts((*slot).tts_isnull, 3);
+            assert_eq!(isnull, &[false, false, true]);
+            assert_eq!(i64::from_datum(values[0], false), Some(42));
+            assert_eq!(String::from_datum(values[1], false).as_deref(), Some("Nami"));
+
+            // a value of the wrong type is refused
+            assert!(ReturnedRow::new(slot).set_by_name("id", "forty-two").is_err());
+            pg_sys::ExecDropSingleTupleTableSlot(slot);
+        }
+        Ok(())
+    }
+
+    #[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+    struct Batches;
+
+    #[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+    impl pgrx::fdw::ForeignBatchInsert for Batches {
+        fn batch_size(_rinfo: *mut pg_sys::ResultRelInfo) -> usize {
+            100
+        }
+        fn insert_batch(batch: pgrx::fdw::InsertBatch<'_>) -> usize {
+            batch.len()
+        }
+    }
+
+    #[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+    #[pg_test]
+    fn test_set_foreign_batch_insert() {
+        let mut routine = pg_sys::FdwRoutine::default();
+        pgrx::fdw::set_foreign_batch_insert::<Batches>(&mut routine);
+        assert!(routine.ExecForeignBatchInsert.is_some());
+        let batch_size = routine.GetForeignModifyBatchSize.unwrap();
+
+        let mut rinfo = pg_sys::ResultRelInfo::default();
+        assert_eq!(unsafe { batch_size(&mut rinfo) }, 100);
+
+        // rows with a RETURNING clause have to be inserted one at a time
+        let mut projection = pg_sys::ProjectionInfo::default();
+        rinfo.ri_projectReturning = &mut projection;
+        assert_eq!(unsafe { batch_size(&mut rinfo) }, 1);
+    }
+
+    #[derive(Debug, PartialEq, FdwOptionValue)]
+    enum Mode {
+        ReadOnly,
+        #[fdw_option(name = "rw")]
+        ReadWrite,
+    }
+
+    #[derive(Debug, PartialEq, FdwOptions)]
+    struct ServerOptions {
+        host: String,
+        #[fdw_option(default = "5432")]
+        port: u16,
+        #[fdw_option(name = "database")]
+        dbname: Option<String>,
+        #[fdw_option(default = "off")]
+        use_ssl: bool,
+        #[fdw_option(default = "read_only")]
+        mode: Mode,
+    }
+
+    #[pg_extern]
+    fn options_test_validator(options: Vec<String>, catalog: pg_sys::Oid) {
+        pgrx::fdw::validate_options(&options, catalog, |context, options| match context {
+            OptionContext::Server => ServerOptions::validate(options),
+            _ => <()>::validate(options),
+        })
+    }
+
+    #[pg_test]
+    fn test_fdw_options_from_options() {
+        let options = ServerOptions::from_options(&[("host", "db1"), ("mode", "rw")]);
+        assert_eq!(
+            options,
+            Ok(ServerOptions {
+                host: "db1".into(),
+                port: 5432,
+                dbname: None,
+                use_ssl: false,
+                mode: Mode::ReadWrite,
+            })
+        );
+
+        let options = ServerOptions::from_options(&[
+            ("host", "db1"),
+            ("port", "6543"),
+            ("database", "things"),
+            ("use_ssl", "on"),
+        ])
+        .unwrap();
+        assert_eq!(options.port, 6543);
+        assert_eq!(options.dbname.as_deref(), Some("things"));
+        assert!(options.use_ssl);
+        assert_eq!(options.mode, Mode::ReadOnly);
+
+        assert_eq!(
+            ServerOptions::from_options(&[("port", "1")]),
+            Err(FdwOptionsError::Missing("host"))
+        );
+        assert!(matches!(
+            ServerOptions::from_options(&[("host", "db1"), ("dbname", "things")]),
+            Err(FdwOptionsError::Unknown { name, .. }) if name == "dbname"
+        ));
+        assert!(matches!(
+            ServerOptions::from_options(&[("host", "db1"), ("mode", "append")]),
+            Err(FdwOptionsError::Invalid { name: "mode", .. })
+        ));
+        assert_eq!(<()>::validate(&[]), Ok(()));
+    }
+
+    #[pg_test]
+    fn test_fdw_options_for_server() -> Result<(), pgrx::spi::Error> {
+        Spi::run(
+            "CREATE FOREIGN DATA WRAPPER options_test_fdw VALIDATOR tests.options_test_validator",
+        )?;
+        Spi::run(
+            "CREATE SERVER options_test_server FOREIGN DATA WRAPPER options_test_fdw
+             OPTIONS (host 'db1', port '6543')",
+        )?;
+        let serverid = Spi::get_one::<pg_sys::Oid>(
+            "SELECT oid FROM pg_foreign_server WHERE srvname = 'options_test_server'",
+        )?
+        .expect("the server exists");
+        let options = ServerOptions::for_server(serverid).unwrap();
+        assert_eq!(options.host, "db1");
+        assert_eq!(options.port, 6543);
+        Ok(())
+    }
+
+    #[pg_test(error = "option \"host\" is required")]
+    fn test_fdw_options_validator_missing() -> Result<(), pgrx::spi::Error> {
+        Spi::run(
+            "CREATE FOREIGN DATA WRAPPER options_test_fdw VALIDATOR tests.options_test_validator",
+        )?;
+        Spi::run("CREATE SERVER options_test_server FOREIGN DATA WRAPPER options_test_fdw")
+    }
+
+    #[pg_test(error = "invalid option \"host\"")]
+    fn test_fdw_options_validator_unknown() -> Result<(), pgrx::spi::Error> {
+        Spi::run(
+            "CREATE FOREIGN DATA WRAPPER options_test_fdw VALIDATOR tests.options_test_validator
+             OPTIONS (host 'db1')",
+        )
+    }
+}
@@ -30,6 +30,33 @@ mod tests {
         assert_eq!("Postgres type boolean oid={#16, builtin: BOOLOID} is not compatible with the Rust type alloc::string::String oid={#25, builtin: TEXTOID}", result.unwrap_err().to_string());
     }
 
+    #[pg_test]
+    fn test_try_from_datum_str() {
+        let text = "try_from_datum".into_datum().unwrap();
+        let result = unsafe { <&str as FromDatum>::try_from_datum(text, false, pg_sys::TEXTOID) };
+        assert_eq!(result, Ok(Some("try_from_datum")));
+        let result = unsafe { String::try_from_datum(text, false, pg_sys::TEXTOID) };
+        assert_eq!(result, Ok(Some(String::from("try_from_datum"))));
+    }
+
+    #[pg_test]
+    fn test_try_from_datum_invalid_utf8() {
+        use std::borrow::Cow;
+
+        // a `text` value can only be made of invalid UTF-8 by going behind Postgres' back
+        let text = pgrx::varlena::rust_byte_slice_to_bytea(b"caf\xe9");
+        let datum = pg_sys::Datum::from(text.as_ptr());
+        use pgrx::TryFromDatumError::InvalidUtf8;
+        unsafe {
+            let result = <&str as FromDatum>::try_from_datum(datum, false, pg_sys::TEXTOID);
+            assert_eq!(result, Err(InvalidUtf8));
+            let result = String::try_from_datum(datum, false, pg_sys::TEXTOID);
+            assert_eq!(result, Err(InvalidUtf8));
+            let result = Cow::<str>::try_from_datum(datum, false, pg_sys::TEXTOID);
+            assert_eq!(result, Err(InvalidUtf8));
+        }
+    }
+
     #[pg_extern]
     fn cstring_roundtrip(s: &CStr) -> &CStr {
         s
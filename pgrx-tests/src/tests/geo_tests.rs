@@ -14,6 +14,7 @@ mod tests {
     use crate as pgrx_tests;
 
     use pgrx::prelude::*;
+    use pgrx::{Path, Polygon};
 
     #[pg_test]
     fn test_point_into_datum() -> spi::Result<()> {
@@ -33,4 +34,78 @@ mod tests {
         assert_eq!(b.low.y, 2.0);
         Ok(())
     }
+
+    fn point(x: f64, y: f64) -> pg_sys::Point {
+        pg_sys::Point { x, y }
+    }
+
+    #[pg_test]
+    fn test_lseg_and_circle() -> spi::Result<()> {
+        let l = Spi::get_one::<pg_sys::LSEG>("SELECT '[(1,2),(3,4)]'::lseg")?.unwrap();
+        assert_eq!((l.p[0].x, l.p[0].y, l.p[1].x, l.p[1].y), (1.0, 2.0, 3.0, 4.0));
+
+        let c = Spi::get_one::<pg_sys::CIRCLE>("SELECT '<(1,2),3>'::circle")?.unwrap();
+        assert_eq!((c.center.x, c.center.y, c.radius), (1.0, 2.0, 3.0));
+        let same = Spi::get_one_with_args::<bool>(
+            "SELECT $1 ~= '<(1,2),3>'::circle",
+            vec![(PgBuiltInOids::CIRCLEOID.oid(), c.into_datum())],
+        )?;
+        assert_eq!(same, Some(true));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_path() -> spi::Result<()> {
+        let p = Spi::get_one::<Path>("SELECT '[(0,0),(1,1),(2,0)]'::path")?.unwrap();
+        assert!(!p.closed);
+        assert_eq!(
+            p.points.iter().map(|p| (p.x, p.y)).collect::<Vec<_>>(),
+            [(0.0, 0.0), (1.0, 1.0), (2.0, 0.0)]
+        );
+
+        let closed = Path::closed(vec![point(0.0, 0.0), point(1.0, 1.0)]);
+        let text = Spi::get_one_with_args::<String>(
+            "SELECT $1::text",
+            vec![(PgBuiltInOids::PATHOID.oid(), closed.into_datum())],
+        )?;
+        assert_eq!(text.as_deref(), Some("((0,0),(1,1))"));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_polygon() -> spi::Result<()> {
+        let polygon = Polygon::new(vec![point(0.0, 0.0), point(4.0, 0.0), point(0.0, 3.0)]);
+        let b = polygon.bounding_box();
+        assert_eq!((b.low.x, b.low.y, b.high.x, b.high.y), (0.0, 0.0, 4.0, 3.0));
+
+        let contains = Spi::get_one_with_args::<bool>(
+            "SELECT $1 @> '(1,1)'::point AND NOT $1 @> '(3,3)'::point",
+            vec![(PgBuiltInOids::POLYGONOID.oid(), polygon.into_datum())],
+        )?;
+        assert_eq!(contains, Some(true));
+
+        let p = Spi::get_one::<Polygon>("SELECT '((0,0),(1,1),(1,0))'::polygon")?.unwrap();
+        assert_eq!(p.points.len(), 3);
+        Ok(())
+    }
+
+    #[pg_extern]
+    fn geo_point_xs(points: Vec<pg_sys::Point>) -> Vec<f64> {
+        points.into_iter().map(|p| p.x).collect()
+    }
+
+    #[pg_test]
+    fn test_geo_arrays() -> spi::Result<()> {
+        let xs = Spi::get_one::<Vec<f64>>(
+            "SELECT tests.geo_point_xs(ARRAY['(1,2)'::point, '(3,4)'::point])",
+        )?;
+        assert_eq!(xs, Some(vec![1.0, 3.0]));
+
+        let polygons = Spi::get_one::<Vec<Polygon>>(
+            "SELECT ARRAY['((0,0),(1,1),(1,0))'::polygon, '((0,0),(2,2),(2,0),(0,2))'::polygon]",
+        )?
+        .unwrap();
+        assert_eq!(polygons.iter().map(|p| p.points.len()).collect::<Vec<_>>(), [3, 4]);
+        Ok(())
+    }
 }
@@ -173,4 +173,41 @@ mod tests {
         Spi::run("SET test.enum = 'three'").expect("SPI failed");
         assert_eq!(GUC.get(), TestEnum::Three);
     }
+
+    #[pg_test]
+    fn test_panic_policy_guc() {
+        GucRegistry::define_panic_policy_guc(
+            "test.panic_policy",
+            "test panic policy guc",
+            "test panic policy guc",
+            GucFlags::default(),
+        );
+        assert_eq!(pgrx::panic_policy(), pgrx::PanicPolicy::Error);
+
+        Spi::run("SET LOCAL test.panic_policy = 'fatal'").expect("SPI failed");
+        assert_eq!(pgrx::panic_policy(), pgrx::PanicPolicy::Fatal);
+
+        Spi::run("SET LOCAL test.panic_policy = 'panic'").expect("SPI failed");
+        assert_eq!(pgrx::panic_policy(), pgrx::PanicPolicy::Panic);
+
+        Spi::run("SET LOCAL test.panic_policy = DEFAULT").expect("SPI failed");
+        assert_eq!(pgrx::panic_policy(), pgrx::PanicPolicy::Error);
+    }
+
+    #[pg_test]
+    fn test_abort_on_double_panic_guc() {
+        GucRegistry::define_abort_on_double_panic_guc(
+            "test.abort_on_double_panic",
+            "test abort on double panic guc",
+            "test abort on double panic guc",
+            GucFlags::default(),
+        );
+        assert!(!pgrx::abort_on_double_panic());
+
+        Spi::run("SET LOCAL test.abort_on_double_panic = true").expect("SPI failed");
+        assert!(pgrx::abort_on_double_panic());
+
+        Spi::run("SET LOCAL test.abort_on_double_panic = DEFAULT").expect("SPI failed");
+        assert!(!pgrx::abort_on_double_panic());
+    }
 }
@@ -0,0 +1,61 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::hash::*;
+    use pgrx::prelude::*;
+
+    #[pg_test]
+    fn test_crc32c() {
+        assert_eq!(crc32c(b""), 0);
+        assert_eq!(crc32c(b"123456789"), 0xe3069283);
+        let mut crc = Crc32c::new();
+        crc.update(b"1234");
+        crc.update(b"56789");
+        assert_eq!(crc.finish(), crc32c(b"123456789"));
+    }
+
+    #[pg_test]
+    fn test_hash_bytes() -> Result<(), pgrx::spi::Error> {
+        let hashtext = Spi::get_one::<i32>("SELECT hashtext('hello world')")?.unwrap();
+        assert_eq!(hash_bytes(b"hello world"), hashtext as u32);
+
+        let extended = Spi::get_one::<i64>("SELECT hashtextextended('hello world', 42)")?.unwrap();
+        assert_eq!(hash_bytes_extended(b"hello world", 42), extended as u64);
+        // a zero seed hashes the same as `hash_any()`, in the low 32 bits
+        assert_eq!(hash_bytes_extended(b"hello world", 0) as u32, hash_bytes(b"hello world"));
+
+        let hashint4 = Spi::get_one::<i32>("SELECT hashint4(12345)")?.unwrap();
+        assert_eq!(hash_uint32(12345), hashint4 as u32);
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_page_checksum() {
+        let mut page = vec![0u8; pg_sys::BLCKSZ as usize];
+        unsafe {
+            pg_sys::PageInit(page.as_mut_ptr().cast(), pg_sys::BLCKSZ as usize, 0);
+        }
+        let checksum = page_checksum(&page, 0);
+        assert_ne!(checksum, page_checksum(&page, 1));
+
+        // the page's own checksum field isn't part of its checksum
+        let pd_checksum = std::mem::size_of::<pg_sys::PageXLogRecPtr>();
+        page[pd_checksum..pd_checksum + 2].copy_from_slice(&checksum.to_ne_bytes());
+        assert_eq!(page_checksum(&page, 0), checksum);
+
+        page[1000] = 1;
+        assert_ne!(page_checksum(&page, 0), checksum);
+    }
+}
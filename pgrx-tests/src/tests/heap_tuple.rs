@@ -35,6 +35,18 @@ CREATE TYPE AnimalFriendshipEdge AS (
     friend_1_name TEXT,
     friend_2_name TEXT
 );
+
+CREATE TYPE Kennel AS (
+    name TEXT,
+    dogs Dog[],
+    best_dog Dog
+);
+
+CREATE TYPE Collar AS (
+    tag VARCHAR(5),
+    size CHAR(3),
+    price NUMERIC(5, 2)
+);
 "#,
     name = "create_composites",
     bootstrap
@@ -294,6 +306,75 @@ mod arguments {
     }
 }
 
+// Composites within composites
+mod nested {
+    use super::*;
+
+    #[pg_extern]
+    fn kennel_scritches(kennel: pgrx::composite_type!("Kennel")) -> i32 {
+        let dogs: Vec<Option<PgHeapTuple<AllocatedByRust>>> =
+            kennel.get_by_name("dogs").unwrap().unwrap_or_default();
+        dogs.iter()
+            .flatten()
+            .map(|dog| dog.get_by_name::<i32>("scritches").unwrap().unwrap_or(0))
+            .sum()
+    }
+
+    #[pg_extern]
+    fn best_dog_name(kennel: pgrx::composite_type!("Kennel")) -> Option<String> {
+        let best_dog: PgHeapTuple<AllocatedByRust> = kennel.get_by_name("best_dog").unwrap()?;
+        best_dog.get_by_name("name").unwrap()
+    }
+
+    #[pg_extern]
+    fn make_kennel(
+        name: &str,
+        dogs: Vec<pgrx::composite_type!("Dog")>,
+    ) -> pgrx::composite_type!("Kennel") {
+        let mut kennel = PgHeapTuple::new_composite_type("Kennel").unwrap();
+        kennel.set_by_name("name", name).unwrap();
+        kennel.set_by_name("dogs", dogs).unwrap();
+        kennel
+    }
+
+    #[pg_extern]
+    fn kennel_dog_names(kennel: pgrx::composite_type!("Kennel")) -> Vec<Option<String>> {
+        let mut names = Vec::new();
+        if let Some(best_dog) = kennel.get_composite_by_name("best_dog").unwrap() {
+            names.push(best_dog.get_by_name("name").unwrap());
+        }
+        if let Some(dogs) = kennel.get_composite_array_by_name("dogs").unwrap() {
+            for dog in dogs.iter() {
+                names.push(dog.and_then(|dog| dog.get_by_name("name").unwrap()));
+            }
+        }
+        names
+    }
+
+    #[pg_extern]
+    fn puppies_of(
+        dogs: pgrx::Array<pgrx::composite_type!("Dog")>,
+    ) -> Vec<pgrx::composite_type!("Dog")> {
+        dogs.iter()
+            .flatten()
+            .map(|dog| {
+                let name: String = dog.get_by_name("name").unwrap().unwrap();
+                let mut puppy = PgHeapTuple::new_composite_type("Dog").unwrap();
+                puppy.set_by_name("name", format!("{name} Jr.")).unwrap();
+                puppy.set_by_name("scritches", 0).unwrap();
+                puppy
+            })
+            .collect()
+    }
+
+    #[pg_extern]
+    fn mixed_pets() -> Vec<pgrx::composite_type!("Dog")> {
+        let dog = PgHeapTuple::new_composite_type("Dog").unwrap();
+        let cat = PgHeapTuple::new_composite_type("Cat").unwrap();
+        vec![dog, cat]
+    }
+}
+
 // As return types
 mod returning {
     use super::*;
@@ -328,6 +409,24 @@ mod returning {
             maybe_dog
         }
 
+        /// Fills whatever columns the caller asked for with a value derived from the column's name
+        #[pg_extern]
+        fn record_from_column_list(
+            fcinfo: pg_sys::FunctionCallInfo,
+        ) -> pgrx::composite_type!("record") {
+            let mut tuple = unsafe { PgHeapTuple::new_for_call_result(fcinfo) }.unwrap();
+            let columns = unsafe { pgrx::PgTupleDesc::for_call_result(fcinfo) }.unwrap();
+            for (idx, attr) in columns.iter().enumerate() {
+                let attno = std::num::NonZeroUsize::new(idx + 1).unwrap();
+                if attr.atttypid == pg_sys::INT4OID {
+                    tuple.set_by_index(attno, attr.name().len() as i32).unwrap();
+                } else if attr.atttypid == pg_sys::TEXTOID {
+                    tuple.set_by_index(attno, attr.name().to_uppercase()).unwrap();
+                }
+            }
+            tuple
+        }
+
         #[pg_extern]
         fn scritch_strict(dog: ::pgrx::composite_type!("Dog")) -> pgrx::composite_type!("Dog") {
             // Gets resolved to:
@@ -339,6 +438,42 @@ mod returning {
         }
     }
 
+    mod setof {
+        use super::*;
+
+        /// `rows` rows of the requested columns, each value built from its row and column number
+        #[pg_extern]
+        fn records_from_column_list(
+            rows: i32,
+            fcinfo: pg_sys::FunctionCallInfo,
+        ) -> Result<SetOfIterator<'static, pgrx::composite_type!("record")>, PgHeapTupleError>
+        {
+            let mut set = unsafe { RecordSet::for_call_result(fcinfo) }?;
+            let ncolumns = set.columns().len();
+            for row in 0..rows as usize {
+                set.push_text(
+                    (0..ncolumns)
+                        .map(|column| (column != 1).then(|| (row * 10 + column).to_string())),
+                )?;
+            }
+            Ok(set.into_set_of())
+        }
+
+        /// One row, whose first column is `name` and whose others are NULL
+        #[pg_extern]
+        fn record_rows_by_builder(
+            name: &str,
+            fcinfo: pg_sys::FunctionCallInfo,
+        ) -> Result<SetOfIterator<'static, pgrx::composite_type!("record")>, PgHeapTupleError>
+        {
+            let mut set = unsafe { RecordSet::for_call_result(fcinfo) }?;
+            let row =
+                set.row().set_by_index(std::num::NonZeroUsize::new(1).unwrap(), name)?.build();
+            set.push(row)?;
+            Ok(set.into_set_of())
+        }
+    }
+
     mod vec {
         use super::*;
 
@@ -401,6 +536,52 @@ mod returning {
     // Returning VariadicArray/Array isn't supported, use a Vec.
 }
 
+// Plain structs mapped onto the composite types with `#[derive(PostgresComposite)]`
+mod derived {
+    use super::*;
+
+    #[derive(Debug, PartialEq, PostgresComposite)]
+    #[composite_type = "Dog"]
+    pub struct DerivedDog {
+        pub name: String,
+        #[column = "scritches"]
+        pub pets: Option<i32>,
+    }
+
+    #[derive(Debug, PartialEq, PostgresComposite)]
+    #[composite_type = "Kennel"]
+    pub struct DerivedKennel {
+        pub name: String,
+        pub dogs: Option<Vec<DerivedDog>>,
+        pub best_dog: Option<DerivedDog>,
+    }
+
+    #[pg_extern]
+    fn scritch_derived(mut dog: DerivedDog) -> DerivedDog {
+        dog.pets = Some(dog.pets.unwrap_or_default() + 1);
+        dog
+    }
+
+    #[pg_extern]
+    fn scritch_derived_dogs(dogs: Vec<DerivedDog>) -> Vec<DerivedDog> {
+        dogs.into_iter().map(scritch_derived).collect()
+    }
+
+    #[pg_extern]
+    fn derived_dogs_setof(dogs: Vec<DerivedDog>) -> SetOfIterator<'static, DerivedDog> {
+        SetOfIterator::new(dogs)
+    }
+
+    #[pg_extern]
+    fn derived_kennel(name: String, dogs: Vec<DerivedDog>) -> DerivedKennel {
+        let best_dog = dogs
+            .iter()
+            .max_by_key(|dog| dog.pets)
+            .map(|dog| DerivedDog { name: dog.name.clone(), pets: dog.pets });
+        DerivedKennel { name, dogs: Some(dogs), best_dog }
+    }
+}
+
 // Just a compile test...
 // We don't run these, but we ensure we can build SQL for them
 mod sql_generator_tests {
@@ -634,9 +815,9 @@ mod tests {
     #[cfg(test)]
     use crate as pgrx_tests;
     use pgrx::datum::TryFromDatumError;
-    use pgrx::heap_tuple::PgHeapTupleError;
+    use pgrx::heap_tuple::{HeapTupleBuilder, PgHeapTupleError};
     use pgrx::prelude::*;
-    use pgrx::AllocatedByRust;
+    use pgrx::{AllocatedByRust, PgTupleDesc};
     use std::num::NonZeroUsize;
 
     #[pg_test]
@@ -908,6 +1089,57 @@ mod tests {
         ));
     }
 
+    #[pg_test]
+    fn test_heap_tuple_builder() -> Result<(), TryFromDatumError> {
+        Spi::run("CREATE TYPE DogWithAge AS (name text, age int);").expect("SPI failed");
+        let tupdesc = PgTupleDesc::for_composite_type("DogWithAge").unwrap();
+
+        let dog = HeapTupleBuilder::new(tupdesc.clone()).set_by_name("name", "Brandy")?.build();
+        assert_eq!(dog.get_by_name("name")?, Some("Brandy".to_string()));
+        assert_eq!(dog.get_by_name::<i32>("age")?, None);
+
+        let dog = HeapTupleBuilder::new(tupdesc.clone())
+            .set_by_index(NonZeroUsize::new(2).unwrap(), 42)?
+            .set_by_name("name", "Nami")?
+            .build();
+        assert_eq!(dog.get_by_name("name")?, Some("Nami".to_string()));
+        assert_eq!(dog.get_by_name("age")?, Some(42i32));
+
+        assert_eq!(
+            HeapTupleBuilder::new(tupdesc.clone()).set_by_name("breed", "Corgi").err(),
+            Some(TryFromDatumError::NoSuchAttributeName("breed".into()))
+        );
+        assert!(matches!(
+            HeapTupleBuilder::new(tupdesc).set_by_name("age", "Brandy"),
+            Err(TryFromDatumError::IncompatibleTypes { .. })
+        ));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_heap_tuple_modifier() -> Result<(), Box<dyn std::error::Error>> {
+        let dog = Spi::get_one::<PgHeapTuple<'_, AllocatedByRust>>("SELECT ROW('Nami', 2)::Dog")?
+            .expect("failed to get a Dog");
+
+        let dog = dog.modify().set_by_name("scritches", 3)?.build();
+        assert_eq!(dog.get_by_name("name")?, Some("Nami".to_string()));
+        assert_eq!(dog.get_by_name("scritches")?, Some(3i32));
+
+        let dog = dog.modify().set_by_name("name", None::<String>)?.build();
+        assert_eq!(dog.get_by_name::<String>("name")?, None);
+        assert_eq!(dog.get_by_name("scritches")?, Some(3i32));
+
+        // nothing replaced is just a copy
+        let dog = dog.modify().build();
+        assert_eq!(dog.get_by_name("scritches")?, Some(3i32));
+
+        assert!(matches!(
+            dog.modify().set_by_name("scritches", "lots"),
+            Err(TryFromDatumError::IncompatibleTypes { .. })
+        ));
+        Ok(())
+    }
+
     #[pg_test]
     fn test_compatibility() {
         Spi::get_one::<PgHeapTuple<'_, AllocatedByRust>>("SELECT ROW('Nami', 2)::Dog")
@@ -926,6 +1158,53 @@ mod tests {
         }
     }
 
+    #[pg_test]
+    fn test_record_from_column_list() -> Result<(), spi::Error> {
+        let (name, len) = Spi::get_two::<String, i32>(
+            "SELECT * FROM record_from_column_list() AS t(name text, length int)",
+        )?;
+        assert_eq!(name.as_deref(), Some("NAME"));
+        assert_eq!(len, Some(6));
+        Ok(())
+    }
+
+    #[pg_test(error = "a column definition list is required for functions returning \"record\"")]
+    fn test_record_without_column_list() {
+        Spi::run("SELECT * FROM record_from_column_list()").unwrap();
+    }
+
+    #[pg_test]
+    fn test_records_from_column_list() -> Result<(), spi::Error> {
+        let sum = Spi::get_one::<i64>(
+            "SELECT sum(a + coalesce(b, 0) + c::int)
+             FROM records_from_column_list(3) AS t(a int, b bigint, c text)",
+        )?;
+        // columns 0 and 2 of rows 0, 10 and 20, while column 1 is NULL
+        assert_eq!(sum, Some(0 + 2 + 10 + 12 + 20 + 22));
+
+        let count = Spi::get_one::<i64>(
+            "SELECT count(*) FROM records_from_column_list(5) AS t(a numeric, b int)
+             WHERE b IS NULL",
+        )?;
+        assert_eq!(count, Some(5));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_record_rows_by_builder() -> Result<(), spi::Error> {
+        let (name, other) = Spi::get_two::<String, i32>(
+            "SELECT * FROM record_rows_by_builder('Nami') AS t(name text, other int)",
+        )?;
+        assert_eq!(name.as_deref(), Some("Nami"));
+        assert_eq!(other, None);
+        Ok(())
+    }
+
+    #[pg_test(error = "invalid input syntax for type date: \"2\"")]
+    fn test_records_from_column_list_invalid_text() {
+        Spi::run("SELECT * FROM records_from_column_list(1) AS t(a int, b int, c date)").unwrap();
+    }
+
     #[pg_test]
     fn test_tuple_desc_clone() -> Result<(), spi::Error> {
         let result = Spi::connect(|client| {
@@ -935,4 +1214,204 @@ mod tests {
         assert_eq!(result, 10_000);
         Ok(())
     }
+
+    #[pg_test]
+    fn test_composite_array_with_nulls() -> Result<(), spi::Error> {
+        let dogs = Spi::get_one::<Vec<Option<PgHeapTuple<'_, AllocatedByRust>>>>(
+            "SELECT ARRAY[ROW('Nami', 1)::Dog, NULL, ROW('Brandy', NULL)::Dog]",
+        )?
+        .expect("no dogs");
+        assert_eq!(dogs.len(), 3);
+        assert!(dogs[1].is_none());
+        let brandy = dogs[2].as_ref().unwrap();
+        assert_eq!(brandy.get_by_name::<&str>("name").unwrap(), Some("Brandy"));
+        assert_eq!(brandy.get_by_name::<i32>("scritches").unwrap(), None);
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_nested_composite_array() -> Result<(), spi::Error> {
+        let scritches = Spi::get_one::<i32>(
+            "SELECT kennel_scritches(ROW('Home', ARRAY[ROW('Nami', 1)::Dog, NULL, ROW('Brandy', 2)::Dog], NULL)::Kennel)",
+        )?;
+        assert_eq!(scritches, Some(3));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_nested_composite() -> Result<(), spi::Error> {
+        let name = Spi::get_one::<String>(
+            "SELECT best_dog_name(ROW('Home', NULL, ROW('Nami', 1)::Dog)::Kennel)",
+        )?;
+        assert_eq!(name.as_deref(), Some("Nami"));
+
+        let name = Spi::get_one::<String>("SELECT best_dog_name(ROW('Home', NULL, NULL)::Kennel)")?;
+        assert_eq!(name, None);
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_set_nested_composite_array() -> Result<(), spi::Error> {
+        let (name, typ) = Spi::get_two::<String, String>(
+            "SELECT k.dogs[1].name, pg_typeof(k.dogs)::text
+               FROM make_kennel('Home', ARRAY[ROW('Nami', 1)::Dog]) k",
+        )?;
+        assert_eq!(name.as_deref(), Some("Nami"));
+        assert_eq!(typ.as_deref(), Some("dog[]"));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_build_composite_array() -> Result<(), spi::Error> {
+        let (names, typ) = Spi::get_two::<Vec<String>, String>(
+            "SELECT ARRAY(SELECT p.name FROM unnest(puppies) p), pg_typeof(puppies)::text
+               FROM puppies_of(ARRAY[ROW('Nami', 1)::Dog, NULL, ROW('Brandy', 2)::Dog]) puppies",
+        )?;
+        assert_eq!(names, Some(vec!["Nami Jr.".to_string(), "Brandy Jr.".to_string()]));
+        assert_eq!(typ.as_deref(), Some("dog[]"));
+
+        let same = Spi::get_one::<bool>(
+            "SELECT puppies_of(ARRAY[ROW('Nami', 1)::Dog]) = ARRAY[ROW('Nami Jr.', 0)]::Dog[]",
+        )?;
+        assert_eq!(same, Some(true));
+        Ok(())
+    }
+
+    #[pg_test(error = "cannot put a `cat` into an array of `dog`")]
+    fn test_mixed_composite_array() {
+        Spi::run("SELECT mixed_pets()").unwrap();
+    }
+
+    #[pg_test]
+    fn test_derived_composite() -> Result<(), spi::Error> {
+        use super::derived::DerivedDog;
+
+        let dog = Spi::get_one::<DerivedDog>("SELECT scritch_derived(ROW('Nami', 1)::Dog)")?;
+        assert_eq!(dog, Some(DerivedDog { name: "Nami".into(), pets: Some(2) }));
+
+        let dog = Spi::get_one::<DerivedDog>("SELECT scritch_derived(ROW('Brandy', NULL)::Dog)")?;
+        assert_eq!(dog, Some(DerivedDog { name: "Brandy".into(), pets: Some(1) }));
+        Ok(())
+    }
+
+    #[pg_test(error = "Attribute `name` is NULL, but its Rust field is not an `Option`")]
+    fn test_derived_composite_null_attribute() {
+        Spi::run("SELECT scritch_derived(ROW(NULL, 1)::Dog)").unwrap();
+    }
+
+    #[pg_test]
+    fn test_derived_composite_array_and_setof() -> Result<(), spi::Error> {
+        let pets = Spi::get_one::<Vec<i32>>(
+            "SELECT array_agg(d.scritches) \
+               FROM unnest(scritch_derived_dogs(ARRAY[ROW('Nami', 1), ROW('Brandy', 2)]::Dog[])) d",
+        )?;
+        assert_eq!(pets, Some(vec![2, 3]));
+
+        let names = Spi::get_one::<Vec<String>>(
+            "SELECT array_agg(d.name) \
+               FROM derived_dogs_setof(ARRAY[ROW('Nami', 1), ROW('Brandy', 2)]::Dog[]) d",
+        )?;
+        assert_eq!(names, Some(vec!["Nami".to_string(), "Brandy".to_string()]));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_derived_nested_composite() -> Result<(), spi::Error> {
+        use super::derived::{DerivedDog, DerivedKennel};
+
+        let kennel = Spi::get_one::<DerivedKennel>(
+            "SELECT derived_kennel('Home', ARRAY[ROW('Nami', 1), ROW('Brandy', 2)]::Dog[])",
+        )?
+        .unwrap();
+        assert_eq!(kennel.name, "Home");
+        assert_eq!(kennel.dogs.map(|dogs| dogs.len()), Some(2));
+        assert_eq!(kennel.best_dog, Some(DerivedDog { name: "Brandy".into(), pets: Some(2) }));
+
+        let typ = Spi::get_one::<String>(
+            "SELECT pg_typeof(derived_kennel('Home', ARRAY[]::Dog[]))::text",
+        )?;
+        assert_eq!(typ.as_deref(), Some("kennel"));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_get_composite_accessors() -> Result<(), spi::Error> {
+        let names = Spi::get_one::<Vec<Option<String>>>(
+            "SELECT kennel_dog_names(ROW('Home', ARRAY[ROW('Nami', 1)::Dog, NULL], ROW('Brandy', 2)::Dog)::Kennel)",
+        )?;
+        assert_eq!(names, Some(vec![Some("Brandy".into()), Some("Nami".into()), None]));
+
+        let names = Spi::get_one::<Vec<Option<String>>>(
+            "SELECT kennel_dog_names(ROW('Empty', NULL, NULL)::Kennel)",
+        )?;
+        assert_eq!(names, Some(vec![]));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_get_composite_from_non_composite() {
+        let mut kennel = PgHeapTuple::new_composite_type("Kennel").unwrap();
+        kennel.set_by_name("name", "Home").unwrap();
+        assert!(matches!(
+            kennel.get_composite_by_name("name"),
+            Err(TryFromDatumError::IncompatibleTypes { .. })
+        ));
+        assert!(matches!(
+            kennel.get_composite_array_by_name("best_dog"),
+            Err(TryFromDatumError::IncompatibleTypes { .. })
+        ));
+        assert!(matches!(
+            kennel.get_composite_by_name("owner"),
+            Err(TryFromDatumError::NoSuchAttributeName(_))
+        ));
+    }
+
+    #[pg_test]
+    fn test_nested_composite_outlives_changes() {
+        let mut kennel = PgHeapTuple::new_composite_type("Kennel").unwrap();
+        let mut dog = PgHeapTuple::new_composite_type("Dog").unwrap();
+        dog.set_by_name("name", "Nami").unwrap();
+        kennel.set_by_name("best_dog", dog).unwrap();
+
+        let best_dog = kennel.get_composite_by_name("best_dog").unwrap().unwrap();
+        kennel.set_by_name("best_dog", Option::<PgHeapTuple<AllocatedByRust>>::None).unwrap();
+        assert_eq!(best_dog.get_by_name::<&str>("name").unwrap(), Some("Nami"));
+        assert_eq!(kennel.get_composite_by_name("best_dog").unwrap().map(|_| ()), None);
+    }
+
+    #[pg_test]
+    fn test_set_by_name_applies_typmod() {
+        let mut collar = PgHeapTuple::new_composite_type("Collar").unwrap();
+        collar.set_by_name("tag", "Nami").unwrap();
+        collar.set_by_name("size", "S").unwrap();
+        collar.set_by_name("price", AnyNumeric::try_from(9.999f64).unwrap()).unwrap();
+
+        assert_eq!(collar.get_by_name::<&str>("tag").unwrap(), Some("Nami"));
+        // char(n) is padded, and numeric(p, s) rounded, as when assigning in SQL
+        assert_eq!(collar.get_by_name::<&str>("size").unwrap(), Some("S  "));
+        assert_eq!(
+            collar.get_by_name::<AnyNumeric>("price").unwrap().map(|n| n.to_string()),
+            Some("10.00".to_string())
+        );
+    }
+
+    #[pg_test]
+    fn test_set_by_name_typmod_violations() {
+        let mut collar = PgHeapTuple::new_composite_type("Collar").unwrap();
+        assert!(matches!(
+            collar.set_by_name("tag", "Brandy"),
+            Err(TryFromDatumError::TypmodViolation { attname, .. }) if attname == "tag"
+        ));
+        assert!(matches!(
+            collar.set_by_name("price", AnyNumeric::from(1000)),
+            Err(TryFromDatumError::TypmodViolation { attname, .. }) if attname == "price"
+        ));
+        assert!(matches!(
+            HeapTupleBuilder::new(PgTupleDesc::for_composite_type("Collar").unwrap())
+                .set_by_name("size", "Large"),
+            Err(TryFromDatumError::TypmodViolation { .. })
+        ));
+        // nothing was set by the failed attempts
+        assert_eq!(collar.get_by_name::<&str>("tag").unwrap(), None);
+    }
 }
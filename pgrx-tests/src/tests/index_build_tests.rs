@@ -0,0 +1,171 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::index_build::{
+        build_scan, gin_options, gist_options, GinIndexOptions, GistBuffering, GistIndexOptions,
+        IndexBuildSorter,
+    };
+    use pgrx::itemptr::item_pointer_get_both;
+    use pgrx::prelude::*;
+    use pgrx::PgRelation;
+
+    fn setup() -> (PgRelation, PgRelation, *mut pg_sys::IndexInfo) {
+        Spi::run(
+            "CREATE TABLE index_build_test (id int, value int);
+             INSERT INTO index_build_test SELECT i, (i * 7) % 10 FROM generate_series(1, 10) i;
+             INSERT INTO index_build_test VALUES (11, NULL);
+             CREATE INDEX idx_index_build_test ON index_build_test (value) INCLUDE (id);",
+        )
+        .expect("SPI failed");
+        let heap = PgRelation::open_with_name_and_share_lock("index_build_test").unwrap();
+        let index = PgRelation::open_with_name_and_share_lock("idx_index_build_test").unwrap();
+        let index_info = unsafe { pg_sys::BuildIndexInfo(index.as_ptr()) };
+        (heap, index, index_info)
+    }
+
+    fn value(datum: pg_sys::Datum, isnull: bool) -> Option<i32> {
+        unsafe { i32::from_datum(datum, isnull) }
+    }
+
+    #[pg_test]
+    fn test_build_scan() {
+        let (heap, index, index_info) = setup();
+        let mut ids = Vec::new();
+        let ntuples = build_scan(&heap, &index, index_info, |tuple| {
+            assert_eq!(tuple.values.len(), 2);
+            assert!(tuple.alive);
+            ids.push(value(tuple.values[1], tuple.isnull[1]).unwrap());
+        });
+        ids.sort();
+        assert_eq!(ntuples, 11);
+        assert_eq!(ids, (1..=11).collect::<Vec<_>>());
+    }
+
+    #[pg_test]
+    fn test_index_build_sorter() -> Result<(), spi::Error> {
+        let (heap, index, index_info) = setup();
+        let mut sorter = IndexBuildSorter::new(&index);
+        build_scan(&heap, &index, index_info, |tuple| sorter.put(&tuple));
+
+        let mut sorted = Vec::new();
+        sorter.finish(|tuple| {
+            let (block, offset) = item_pointer_get_both(tuple.tid);
+            sorted.push((
+                value(tuple.values[0], tuple.isnull[0]),
+                value(tuple.values[1], tuple.isnull[1]),
+                format!("({},{})", block, offset),
+            ));
+        });
+
+        let values = sorted.iter().map(|(value, _, _)| *value).collect::<Vec<_>>();
+        let mut expected = (0..10).map(Some).collect::<Vec<_>>();
+        expected.push(None);
+        assert_eq!(values, expected);
+
+        for (_, id, tid) in sorted {
+            let ctid = Spi::get_one_with_args::<String>(
+                "SELECT ctid::text FROM index_build_test WHERE id = $1",
+                vec![(PgBuiltInOids::INT4OID.oid(), id.into_datum())],
+            )?;
+            assert_eq!(ctid, Some(tid));
+        }
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_gin_options() {
+        Spi::run(
+            "CREATE TABLE gin_options_test (tags int[]);
+             CREATE INDEX idx_gin_default ON gin_options_test USING gin (tags);
+             CREATE INDEX idx_gin_tuned ON gin_options_test USING gin (tags)
+                 WITH (fastupdate = off, gin_pending_list_limit = 128);
+             SET LOCAL gin_pending_list_limit = 512;",
+        )
+        .expect("SPI failed");
+        let index = PgRelation::open_with_name_and_share_lock("idx_gin_default").unwrap();
+        assert_eq!(
+            gin_options(&index),
+            Some(GinIndexOptions { fastupdate: true, pending_list_limit: 512 })
+        );
+        let index = PgRelation::open_with_name_and_share_lock("idx_gin_tuned").unwrap();
+        assert_eq!(
+            gin_options(&index),
+            Some(GinIndexOptions { fastupdate: false, pending_list_limit: 128 })
+        );
+        assert_eq!(gist_options(&index), None);
+    }
+
+    #[pg_test]
+    fn test_gist_options() {
+        Spi::run(
+            "CREATE TABLE gist_options_test (b box);
+             CREATE INDEX idx_gist_default ON gist_options_test USING gist (b);
+             CREATE INDEX idx_gist_tuned ON gist_options_test USING gist (b)
+                 WITH (fillfactor = 70, buffering = on);",
+        )
+        .expect("SPI failed");
+        let index = PgRelation::open_with_name_and_share_lock("idx_gist_default").unwrap();
+        assert_eq!(
+            gist_options(&index),
+            Some(GistIndexOptions { fillfactor: 90, buffering: GistBuffering::Auto })
+        );
+        let index = PgRelation::open_with_name_and_share_lock("idx_gist_tuned").unwrap();
+        assert_eq!(
+            gist_options(&index),
+            Some(GistIndexOptions { fillfactor: 70, buffering: GistBuffering::On })
+        );
+        assert_eq!(gin_options(&index), None);
+    }
+
+    #[cfg(not(feature = "pg11"))]
+    #[pg_test]
+    fn test_build_progress() -> Result<(), spi::Error> {
+        use pgrx::index_build::BuildProgress;
+        use pgrx::progress::{ProgressCommand, ProgressGuard};
+
+        let _guard = ProgressGuard::start(ProgressCommand::CreateIndex, pg_sys::InvalidOid);
+        let mut progress = BuildProgress::new();
+        progress.subphase(2);
+        progress.tuples_total(100);
+        progress.tuples_done(30);
+        progress.tuples_done(12);
+
+        let (subphase, total, done) = Spi::get_three::<i64, i64, i64>(
+            "SELECT param11, param12, param13
+               FROM pg_stat_get_progress_info('CREATE INDEX')
+              WHERE pid = pg_backend_pid()",
+        )?;
+        assert_eq!((subphase, total, done), (Some(2), Some(100), Some(42)));
+        Ok(())
+    }
+
+    #[cfg(not(feature = "pg11"))]
+    #[pg_test]
+    fn test_build_scan_progress() -> Result<(), spi::Error> {
+        use pgrx::progress::{ProgressCommand, ProgressGuard};
+
+        let (heap, index, index_info) = setup();
+        let _guard = ProgressGuard::start(ProgressCommand::CreateIndex, heap.oid());
+        let ntuples = build_scan(&heap, &index, index_info, |_| ());
+
+        let done = Spi::get_one::<i64>(
+            "SELECT param13
+               FROM pg_stat_get_progress_info('CREATE INDEX')
+              WHERE pid = pg_backend_pid()",
+        )?;
+        assert_eq!(done, Some(ntuples as i64));
+        Ok(())
+    }
+}
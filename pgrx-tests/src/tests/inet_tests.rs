@@ -14,7 +14,8 @@ mod tests {
     use crate as pgrx_tests;
 
     use pgrx::prelude::*;
-    use pgrx::Inet;
+    use pgrx::{Cidr, Inet, MacAddr, MacAddr8};
+    use std::net::{IpAddr, Ipv4Addr};
 
     #[pg_test]
     fn test_deserialize_inet() {
@@ -42,4 +43,56 @@ mod tests {
         );
         assert_eq!(rc, Ok(Some(true)));
     }
+
+    #[pg_test]
+    fn test_inet_addr() -> Result<(), pgrx::spi::Error> {
+        let inet = Spi::get_one::<Inet>("SELECT '10.1.2.3/16'::inet")?.unwrap();
+        assert_eq!(inet.addr(), Some(IpAddr::V4(Ipv4Addr::new(10, 1, 2, 3))));
+        assert_eq!(inet.prefix_len(), Some(16));
+
+        let inet = Inet::from("::1".parse::<IpAddr>().unwrap());
+        assert_eq!(inet.prefix_len(), Some(128));
+        let same = Spi::get_one_with_args::<bool>(
+            "SELECT $1 = '::1'::inet",
+            vec![(PgBuiltInOids::INETOID.oid(), inet.into_datum())],
+        )?;
+        assert_eq!(same, Some(true));
+        Ok(())
+    }
+
+    #[pg_extern]
+    fn take_and_return_cidr(cidr: Cidr) -> Cidr {
+        cidr
+    }
+
+    #[pg_test]
+    fn test_cidr() -> Result<(), pgrx::spi::Error> {
+        let cidr =
+            Spi::get_one::<Cidr>("SELECT tests.take_and_return_cidr('10.1'::cidr)")?.unwrap();
+        assert_eq!(&*cidr, "10.1.0.0/16");
+        assert_eq!(cidr.network(), Some(IpAddr::V4(Ipv4Addr::new(10, 1, 0, 0))));
+        assert!(cidr.contains(IpAddr::V4(Ipv4Addr::new(10, 1, 200, 3))));
+        assert!(!cidr.contains(IpAddr::V4(Ipv4Addr::new(10, 2, 0, 1))));
+        Ok(())
+    }
+
+    #[pg_extern]
+    fn take_and_return_macaddr(addr: MacAddr) -> MacAddr {
+        addr
+    }
+
+    #[pg_test]
+    fn test_macaddr() -> Result<(), pgrx::spi::Error> {
+        let addr = Spi::get_one::<MacAddr>(
+            "SELECT tests.take_and_return_macaddr('08-00-2B-01-02-03'::macaddr)",
+        )?
+        .unwrap();
+        assert_eq!(addr, MacAddr([0x08, 0x00, 0x2b, 0x01, 0x02, 0x03]));
+        assert_eq!(addr.to_string(), "08:00:2b:01:02:03");
+
+        let addr8 = Spi::get_one::<MacAddr8>("SELECT '08:00:2b:01:02:03'::macaddr8")?.unwrap();
+        assert_eq!(addr8, MacAddr8::from(addr));
+        assert!("08:00:2b".parse::<MacAddr>().is_err());
+        Ok(())
+    }
 }
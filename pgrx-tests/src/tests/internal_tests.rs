@@ -7,13 +7,37 @@
 //LICENSE All rights reserved.
 //LICENSE
 //LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+use pgrx::prelude::*;
+use pgrx::Internal;
+use std::ffi::{CStr, CString};
+
+/// Shaped like a `receive` function: reads a point sent as two network-order `int4`s
+#[pg_extern(immutable, strict)]
+fn point_recv_sum(mut buf: Internal) -> i32 {
+    let mut buf = unsafe { buf.as_receive_buffer() }.expect("buffer is null");
+    let x = buf.read_i32();
+    let y = buf.read_i32();
+    x + y
+}
+
+/// Shaped like an `input` function, taking and returning `cstring`s
+#[pg_extern(immutable, strict)]
+fn cstring_upper(input: &CStr) -> CString {
+    CString::new(input.to_bytes().to_ascii_uppercase()).unwrap()
+}
+
+#[pg_extern(immutable, strict)]
+fn cstring_owned_len(input: CString) -> i32 {
+    input.as_bytes().len() as i32
+}
+
 #[cfg(any(test, feature = "pg_test"))]
 #[pgrx::pg_schema]
 mod tests {
     #[allow(unused_imports)]
     use crate as pgrx_tests;
     use pgrx::prelude::*;
-    use pgrx::Internal;
+    use pgrx::{direct_pg_extern_function_call, Internal, StringInfo};
 
     #[pg_test]
     fn internal_insert() {
@@ -63,4 +87,36 @@ mod tests {
         assert_eq!(*inner, 5);
         assert_eq!(val.initialized(), true);
     }
+
+    #[pg_test]
+    unsafe fn internal_receive_buffer() {
+        let mut bytes = StringInfo::new();
+        bytes.push_bytes(&40i32.to_be_bytes());
+        bytes.push_bytes(&2i32.to_be_bytes());
+        let buf = pg_sys::Datum::from(bytes.into_pg());
+
+        let result =
+            direct_pg_extern_function_call::<i32>(super::point_recv_sum_wrapper, &[Some(buf)]);
+        assert_eq!(result, Some(42));
+    }
+
+    #[pg_test(error = "insufficient data left in message")]
+    unsafe fn internal_receive_buffer_too_short() {
+        let mut bytes = StringInfo::new();
+        bytes.push_bytes(&40i32.to_be_bytes());
+        let mut internal = Internal::from(Some(pg_sys::Datum::from(bytes.into_pg())));
+        let mut buf = internal.as_receive_buffer().unwrap();
+        assert_eq!(buf.read_i32(), 40);
+        assert_eq!(buf.remaining(), &[]);
+        buf.read_i32();
+    }
+
+    #[pg_test]
+    fn cstring_args_and_returns() {
+        let upper = Spi::get_one::<String>("SELECT cstring_upper('hello'::cstring)::text");
+        assert_eq!(upper, Ok(Some("HELLO".to_string())));
+
+        let len = Spi::get_one::<i32>("SELECT cstring_owned_len('hello'::cstring)");
+        assert_eq!(len, Ok(Some(5)));
+    }
 }
@@ -0,0 +1,51 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+// `pgrx_tests` calls `pg_magic_func!()` itself, rather than `pg_module_magic!()`, so this is what
+// the latter would add
+pgrx::extension_introspection!();
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+
+    #[pg_test]
+    fn test_version() {
+        let version = Spi::get_one::<&str>("SELECT public.version()");
+        assert_eq!(version, Ok(Some(env!("CARGO_PKG_VERSION"))));
+    }
+
+    #[pg_test]
+    fn test_build_info() -> Result<(), pgrx::spi::Error> {
+        let (version, pg_version) =
+            Spi::get_two::<&str, String>("SELECT version, pg_version FROM public.build_info()")?;
+        assert_eq!(version, Some(env!("CARGO_PKG_VERSION")));
+        assert!(pg_version.unwrap_or_default().starts_with("PostgreSQL "));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_functions() -> Result<(), pgrx::spi::Error> {
+        let symbol = Spi::get_one::<&str>(
+            "SELECT symbol FROM public.functions() WHERE function = 'public.version()'",
+        )?;
+        assert_eq!(symbol, Some("pgrx_extension_version_wrapper"));
+
+        // everything else in the extension is listed too, not just the introspection functions
+        let symbol = Spi::get_one::<&str>(
+            "SELECT symbol FROM public.functions() WHERE function = 'tests.test_functions()'",
+        )?;
+        assert_eq!(symbol, Some("test_functions_wrapper"));
+        Ok(())
+    }
+}
@@ -14,7 +14,12 @@ mod tests {
     use crate as pgrx_tests;
 
     use pgrx::prelude::*;
-    use pgrx::{Json, JsonB};
+    use pgrx::{Json, JsonB, JsonView};
+
+    #[pg_extern]
+    fn json_view_if_active(event: JsonView<'_>) -> Option<JsonView<'_>> {
+        event.get::<bool>("active").expect("invalid json").unwrap_or(false).then_some(event)
+    }
 
     #[pg_test]
     fn test_json() -> Result<(), pgrx::spi::Error> {
@@ -61,4 +66,145 @@ mod tests {
         assert_eq!(user.last_name, "McBlahFace");
         Ok(())
     }
+
+    #[cfg(any(
+        feature = "pg12",
+        feature = "pg13",
+        feature = "pg14",
+        feature = "pg15",
+        feature = "pg16"
+    ))]
+    #[pg_test]
+    fn test_jsonpath_round_trip() -> Result<(), pgrx::spi::Error> {
+        use pgrx::JsonPath;
+
+        let path = Spi::get_one::<JsonPath>("SELECT '$.a[*] ? (@ > 1)'::jsonpath")?.unwrap();
+        assert_eq!(path.as_str(), "$.\"a\"[*]?(@ > 1)");
+
+        let same = Spi::get_one_with_args::<JsonPath>(
+            "SELECT $1",
+            vec![(PgBuiltInOids::JSONPATHOID.oid(), path.clone().into_datum())],
+        )?;
+        assert_eq!(same, Some(path));
+        Ok(())
+    }
+
+    #[cfg(any(
+        feature = "pg12",
+        feature = "pg13",
+        feature = "pg14",
+        feature = "pg15",
+        feature = "pg16"
+    ))]
+    #[pg_test]
+    fn test_jsonpath_syntax_error() {
+        use pgrx::{JsonPath, JsonPathError};
+
+        assert!(matches!(JsonPath::new("$.a[["), Err(JsonPathError::Syntax(_))));
+    }
+
+    #[cfg(any(
+        feature = "pg12",
+        feature = "pg13",
+        feature = "pg14",
+        feature = "pg15",
+        feature = "pg16"
+    ))]
+    #[pg_test]
+    fn test_jsonpath_query() -> Result<(), pgrx::JsonPathError> {
+        use pgrx::JsonPath;
+        use serde_json::json;
+
+        let target = JsonB(json!({"items": [{"price": 5}, {"price": 15}, {"price": 25}]}));
+        let vars = JsonB(json!({"min": 10}));
+        let path = JsonPath::new("$.items[*] ? (@.price > $min).price")?;
+
+        let query = path.query(&target).vars(&vars);
+        assert_eq!(query.all()?, vec![json!(15), json!(25)]);
+        assert_eq!(query.first()?, Some(json!(15)));
+        assert_eq!(query.exists()?, Some(true));
+
+        let predicate = JsonPath::new("$.items[0].price == 5")?;
+        assert_eq!(predicate.query(&target).matches()?, Some(true));
+        Ok(())
+    }
+
+    #[cfg(any(
+        feature = "pg12",
+        feature = "pg13",
+        feature = "pg14",
+        feature = "pg15",
+        feature = "pg16"
+    ))]
+    #[pg_test]
+    fn test_jsonpath_silent() -> Result<(), pgrx::JsonPathError> {
+        use pgrx::{JsonPath, JsonPathError};
+        use serde_json::json;
+
+        let target = JsonB(json!({"a": 1}));
+        let path = JsonPath::new("strict $.b")?;
+
+        let error = path.query(&target).all().unwrap_err();
+        assert!(matches!(error, JsonPathError::Execution { .. }));
+        assert_eq!(path.query(&target).silent(true).all()?, Vec::<serde_json::Value>::new());
+        assert_eq!(path.query(&target).silent(true).first()?, None);
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_json_view_passes_through_text() -> Result<(), pgrx::spi::Error> {
+        // whitespace, key order, and duplicate keys all survive, which they wouldn't via `Json`
+        let text = r#"{"b": 1,   "active": false, "active" : true}"#;
+        let result = Spi::get_one::<String>(&format!(
+            "SELECT tests.json_view_if_active('{text}'::json)::text"
+        ))?;
+        assert_eq!(result.as_deref(), Some(text));
+
+        let result = Spi::get_one::<String>(
+            r#"SELECT tests.json_view_if_active('{"active": false}')::text"#,
+        )?;
+        assert_eq!(result, None);
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_json_view_parsing() -> Result<(), pgrx::spi::Error> {
+        use serde::Deserialize;
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Point<'a> {
+            name: &'a str,
+            x: i32,
+        }
+
+        Spi::connect(|client| {
+            let view = client
+                .select(
+                    r#"SELECT '{"name": "origin", "x": 0, "tags": [1, {"y": 2}]}'::json"#,
+                    None,
+                    None,
+                )?
+                .first()
+                .get_one::<JsonView>()?
+                .expect("datum was null");
+            assert_eq!(view.get::<&str>("name").unwrap(), Some("origin"));
+            assert_eq!(view.get::<i32>("missing").unwrap(), None);
+            assert_eq!(view.parse::<Point>().unwrap(), Point { name: "origin", x: 0 });
+            assert_eq!(view.value().unwrap()["tags"][1]["y"], 2);
+            Ok(())
+        })
+    }
+
+    #[pg_test]
+    fn test_json_view_get_from_non_object() -> Result<(), pgrx::spi::Error> {
+        Spi::connect(|client| {
+            let view = client
+                .select("SELECT '[1, 2, 3]'::json", None, None)?
+                .first()
+                .get_one::<JsonView>()?
+                .expect("datum was null");
+            assert!(view.get::<i32>("a").is_err());
+            Ok(())
+        })
+    }
 }
@@ -0,0 +1,122 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+    use pgrx::{current_context, AnyNumeric, JsonBBuilder, JsonBContainer, JsonBValue};
+    use serde_json::json;
+
+    #[pg_extern]
+    fn jsonb_set_key<'a>(
+        doc: JsonBContainer<'a>,
+        key: &str,
+        value: JsonBContainer<'a>,
+    ) -> Option<JsonBContainer<'a>> {
+        doc.set(key, value)
+    }
+
+    #[pg_test]
+    fn test_jsonb_lookup() -> Result<(), pgrx::spi::Error> {
+        let doc = Spi::get_one::<JsonBContainer>(
+            r#"SELECT '{"a": 1.5, "b": [true, null, "three"], "c": {"d": "e"}}'::jsonb"#,
+        )?
+        .expect("datum was null");
+
+        assert!(doc.is_object());
+        assert_eq!(doc.len(), 3);
+        assert_eq!(doc.keys().collect::<Vec<_>>(), vec!["a", "b", "c"]);
+        assert_eq!(doc.get("a").unwrap().as_numeric(), Some(&AnyNumeric::try_from("1.5").unwrap()));
+        assert!(doc.get("missing").is_none());
+        assert!(doc.index(0).is_none());
+
+        let b = doc.get("b").unwrap().as_container().unwrap();
+        assert!(b.is_array());
+        assert_eq!(b.index(0).unwrap().as_bool(), Some(true));
+        assert!(b.index(1).unwrap().is_null());
+        assert!(b.index(3).is_none());
+        assert_eq!(b.elements().count(), 3);
+
+        assert_eq!(doc.get_path(&["b", "2"]).unwrap().as_str(), Some("three"));
+        assert_eq!(doc.get_path(&["c", "d"]).unwrap().as_str(), Some("e"));
+        assert!(doc.get_path(&["c", "x"]).is_none());
+
+        assert_eq!(doc.to_value(), json!({"a": 1.5, "b": [true, null, "three"], "c": {"d": "e"}}));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_jsonb_scalar() -> Result<(), pgrx::spi::Error> {
+        let doc = Spi::get_one::<JsonBContainer>(r#"SELECT '"hello"'::jsonb"#)?.unwrap();
+        assert!(doc.is_scalar());
+        assert!(!doc.is_array());
+        assert_eq!(doc.scalar().unwrap().as_str(), Some("hello"));
+        assert_eq!(doc.to_value(), json!("hello"));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_jsonb_partial_update() -> Result<(), pgrx::spi::Error> {
+        let doc =
+            Spi::get_one::<JsonBContainer>(r#"SELECT '{"a": 1, "b": {"c": 2}}'::jsonb"#)?.unwrap();
+
+        let updated = doc.set("a", "one").unwrap();
+        assert_eq!(updated.to_value(), json!({"a": "one", "b": {"c": 2}}));
+        let updated = updated.set("z", doc.get("b").unwrap()).unwrap();
+        assert_eq!(updated.to_value(), json!({"a": "one", "b": {"c": 2}, "z": {"c": 2}}));
+        let updated = updated.remove("b").unwrap();
+        assert_eq!(updated.to_value(), json!({"a": "one", "z": {"c": 2}}));
+
+        let b = doc.get("b").unwrap().as_container().unwrap();
+        assert!(b.push(1).is_none());
+        assert!(b.remove("c").unwrap().is_empty());
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_jsonb_builder() -> Result<(), pgrx::spi::Error> {
+        let mut builder = JsonBBuilder::new();
+        builder
+            .begin_object()
+            .key("name")
+            .value("pgrx")
+            .key("tags")
+            .begin_array()
+            .value(1)
+            .value(JsonBValue::Null)
+            .value(false)
+            .end_array()
+            .end_object();
+        let built = current_context(|mcx| builder.build(mcx).into_datum());
+
+        let equal = Spi::get_one_with_args::<bool>(
+            r#"SELECT $1 = '{"name": "pgrx", "tags": [1, null, false]}'::jsonb"#,
+            vec![(PgBuiltInOids::JSONBOID.oid(), built)],
+        )?;
+        assert_eq!(equal, Some(true));
+
+        let mut scalar = JsonBBuilder::new();
+        scalar.value(42i64);
+        assert_eq!(current_context(|mcx| scalar.build(mcx).to_value()), json!(42));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_jsonb_set_key_from_sql() -> Result<(), pgrx::spi::Error> {
+        let result = Spi::get_one::<bool>(
+            r#"SELECT tests.jsonb_set_key('{"a": 1, "b": 2}', 'b', '[3]') = '{"a": 1, "b": [3]}'::jsonb"#,
+        )?;
+        assert_eq!(result, Some(true));
+        Ok(())
+    }
+}
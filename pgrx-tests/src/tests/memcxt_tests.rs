@@ -14,7 +14,7 @@ mod tests {
     use crate as pgrx_tests;
 
     use pgrx::prelude::*;
-    use pgrx::PgMemoryContexts;
+    use pgrx::{current_context, Array, FromDatumBorrowed, PgMemoryContexts};
     use std::sync::atomic::{AtomicBool, Ordering};
     use std::sync::Arc;
 
@@ -97,4 +97,20 @@ mod tests {
         drop(ctx);
         assert_eq!(unsafe { pg_sys::CurrentMemoryContext }, ctx_parent);
     }
+
+    #[pg_test]
+    fn test_from_datum_borrowed() {
+        let text = "borrowed".into_datum().unwrap();
+        let len = current_context(|mcx| unsafe {
+            <&str>::from_datum_borrowed(text, false, pg_sys::TEXTOID, mcx).map(str::len)
+        });
+        assert_eq!(len, Some(8));
+
+        let array = vec![1, 2, 3].into_datum().unwrap();
+        let sum = current_context(|mcx| unsafe {
+            Array::<i32>::from_datum_borrowed(array, false, pg_sys::INT4ARRAYOID, mcx)
+                .map(|array| array.iter_deny_null().sum::<i32>())
+        });
+        assert_eq!(sum, Some(6));
+    }
 }
@@ -0,0 +1,62 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static QUERY_CALLS: AtomicUsize = AtomicUsize::new(0);
+    static SESSION_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    #[pg_extern(immutable, memoize)]
+    fn memoized_square(value: i64) -> i64 {
+        QUERY_CALLS.fetch_add(1, Ordering::SeqCst);
+        value * value
+    }
+
+    #[pg_extern(immutable, memoize = "session")]
+    fn memoized_label(value: Option<&str>) -> Option<String> {
+        SESSION_CALLS.fetch_add(1, Ordering::SeqCst);
+        value.map(|value| format!("<{value}>"))
+    }
+
+    #[pg_test]
+    fn test_memoize_query() -> Result<(), pgrx::spi::Error> {
+        QUERY_CALLS.store(0, Ordering::SeqCst);
+        let sum = Spi::get_one::<i64>(
+            "SELECT sum(tests.memoized_square(x % 3))::bigint FROM generate_series(1, 30) x",
+        )?;
+        assert_eq!(sum, Some(50));
+        assert_eq!(QUERY_CALLS.load(Ordering::SeqCst), 3);
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_memoize_session() -> Result<(), pgrx::spi::Error> {
+        let count_labels = || {
+            Spi::get_one::<i64>(
+                "SELECT count(tests.memoized_label(CASE WHEN x % 2 = 0 THEN 'even' END))
+                   FROM generate_series(1, 10) x",
+            )
+        };
+        assert_eq!(count_labels()?, Some(5));
+        // at most one call each for 'even' and NULL, and none once they're cached
+        let calls = SESSION_CALLS.load(Ordering::SeqCst);
+        assert!(calls <= 2);
+        assert_eq!(count_labels()?, Some(5));
+        assert_eq!(count_labels()?, Some(5));
+        assert_eq!(SESSION_CALLS.load(Ordering::SeqCst), calls);
+        Ok(())
+    }
+}
@@ -9,47 +9,94 @@
 //LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
 mod aggregate_tests;
 mod anyarray_tests;
+mod anycompatible_tests;
 mod array_tests;
 mod attributes_tests;
 mod bgworker_tests;
 mod bytea_tests;
+mod catalog_tests;
+#[cfg(feature = "chrono")]
+mod chrono_tests;
 mod cfg_tests;
+mod config_file_tests;
+mod cost_tests;
+mod cow_tests;
 mod datetime_tests;
+#[cfg(feature = "datum-diagnostics")]
+mod datum_diagnostics_tests;
+mod datum_size_tests;
 mod default_arg_value_tests;
 mod derive_pgtype_lifetimes;
+mod detoasted_tests;
+mod dfmgr_tests;
+mod domain_tests;
 mod enum_type_tests;
+mod event_trigger_tests;
+mod fake_clock_tests;
+mod fault_injection_tests;
 mod fcinfo_tests;
+mod fdw_tests;
 mod from_into_datum_tests;
 mod geo_tests;
 mod guc_tests;
+mod hash_tests;
 mod heap_tuple;
 #[cfg(feature = "cshim")]
 mod hooks_tests;
+mod index_build_tests;
 mod inet_tests;
 mod internal_tests;
+mod introspection_tests;
 mod issue1134;
 mod json_tests;
+mod jsonb_tests;
 mod lifetime_tests;
 mod log_tests;
 mod memcxt_tests;
+mod memoize_tests;
 mod name_tests;
+mod namespace_tests;
+mod node_tests;
+mod notify_tests;
+mod nullable_tests;
 mod numeric_tests;
+mod panic_free_tests;
+mod pg_char_tests;
 mod pg_extern_tests;
 mod pg_guard_tests;
+mod pg_lsn_tests;
+mod pg_str_tests;
+mod pg_test_presets_tests;
 mod pg_try_tests;
+mod pg_version_tests;
 mod pgbox_tests;
+mod pgstat_tests;
 mod pgrx_module_qualification;
 mod postgres_type_tests;
+mod privilege_tests;
+mod progress_tests;
 mod range_tests;
 mod result_tests;
 mod roundtrip_tests;
+mod schema_snapshot_tests;
 mod schema_tests;
+mod session_tests;
 mod shmem_tests;
+mod signal_log_tests;
 mod spi_tests;
 mod srf_tests;
+mod stringinfo_tests;
 mod struct_type_tests;
+mod systable_tests;
+mod table_builder_tests;
+mod text_search_tests;
+mod thread_tests;
+mod tid_tests;
 mod trigger_tests;
+mod typed_oid_tests;
+#[cfg(feature = "uuid-crate")]
 mod uuid_tests;
+mod varbit_tests;
 mod variadic_tests;
 mod xact_callback_tests;
 mod xid64_tests;
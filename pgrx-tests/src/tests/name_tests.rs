@@ -24,4 +24,37 @@ mod tests {
     fn renamed_func() {
         Spi::run("SELECT renamed_func();").expect("SPI failed");
     }
+
+    #[pg_test]
+    fn name_data_roundtrip() {
+        let name: pg_sys::NameData = "pgrx".parse().unwrap();
+        assert_eq!(name.to_str(), Ok("pgrx"));
+        assert_eq!(name, "pgrx");
+        assert_eq!(name_data_to_str(&name), "pgrx");
+        assert!(name < "pgrz".parse().unwrap());
+        assert!("a\0b".parse::<pg_sys::NameData>().is_err());
+    }
+
+    #[pg_test]
+    fn name_data_truncates_like_postgres() {
+        let long = "é".repeat(64);
+        let name = pg_sys::NameData::try_from_str(&long).unwrap();
+        let truncated = Spi::get_one_with_args::<String>(
+            "SELECT $1::name::text",
+            vec![(PgBuiltInOids::TEXTOID.oid(), long.into_datum())],
+        );
+        assert_eq!(Ok(Some(pgrx::name_to_string(&name))), truncated);
+        assert_eq!(name.as_bytes().len(), pg_sys::NameData::MAX_LEN - 1);
+    }
+
+    #[pg_test]
+    fn name_to_str_borrows_utf8() {
+        let name: pg_sys::NameData = "café".parse().unwrap();
+        pgrx::current_context(|memcx| {
+            // the test database is UTF-8, so there's nothing to transcode
+            let s = pgrx::name_to_str(&name, memcx).unwrap();
+            assert_eq!(s, "café");
+            assert_eq!(s.as_ptr(), name.as_bytes().as_ptr());
+        });
+    }
 }
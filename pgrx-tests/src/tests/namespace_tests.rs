@@ -0,0 +1,84 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+    use pgrx::{
+        lookup_qualified_relid, lookup_relid, schema_name, schema_oid, search_path,
+        OverrideSearchPath,
+    };
+
+    const NO_LOCK: pg_sys::LOCKMODE = pg_sys::NoLock as _;
+
+    fn regclass(name: &str) -> Result<Option<pg_sys::Oid>, pgrx::spi::Error> {
+        Spi::get_one(&format!("SELECT '{name}'::regclass::oid"))
+    }
+
+    #[pg_test]
+    fn test_lookup_relid() -> Result<(), pgrx::spi::Error> {
+        Spi::run("CREATE SCHEMA \"Odd Schema\"")?;
+        Spi::run("CREATE TABLE \"Odd Schema\".\"Mixed Case\" (id int)")?;
+
+        let oid = lookup_relid("\"Odd Schema\".\"Mixed Case\"", NO_LOCK, false);
+        assert_eq!(oid, regclass("\"Odd Schema\".\"Mixed Case\"")?);
+        assert_eq!(lookup_qualified_relid(Some("Odd Schema"), "Mixed Case", NO_LOCK, false), oid);
+        assert_eq!(lookup_relid("\"Odd Schema\".mixed_case", NO_LOCK, true), None);
+        assert_eq!(lookup_relid("no_such_schema.pg_class", NO_LOCK, true), None);
+        Ok(())
+    }
+
+    #[pg_test(error = "relation \"no_such_table\" does not exist")]
+    fn test_lookup_relid_missing() {
+        lookup_relid("no_such_table", NO_LOCK, false);
+    }
+
+    #[pg_test]
+    fn test_lookup_relid_temp() -> Result<(), pgrx::spi::Error> {
+        Spi::run("CREATE TABLE public.shadowed (id int)")?;
+        Spi::run("CREATE TEMP TABLE shadowed (id int)")?;
+        let temp = lookup_relid("pg_temp.shadowed", NO_LOCK, false);
+        assert_ne!(temp, lookup_relid("public.shadowed", NO_LOCK, false));
+        // temporary tables are searched first
+        assert_eq!(lookup_relid("shadowed", NO_LOCK, false), temp);
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_override_search_path() -> Result<(), pgrx::spi::Error> {
+        Spi::run("CREATE SCHEMA sp_a")?;
+        Spi::run("CREATE TABLE sp_a.sp_table (id int)")?;
+        let sp_a = schema_oid("sp_a", false).unwrap();
+        assert_eq!(schema_oid("sp_missing", true), None);
+        assert_eq!(schema_name(sp_a).as_deref(), Some("sp_a"));
+        assert_eq!(lookup_relid("sp_table", NO_LOCK, true), None);
+
+        {
+            let _path = OverrideSearchPath::new([sp_a]);
+            assert!(lookup_relid("sp_table", NO_LOCK, true).is_some());
+            assert!(search_path(false).contains(&sp_a));
+
+            {
+                let _inner = OverrideSearchPath::catalog_only();
+                assert_eq!(lookup_relid("sp_table", NO_LOCK, true), None);
+                assert!(lookup_relid("pg_class", NO_LOCK, true).is_some());
+            }
+
+            assert!(lookup_relid("sp_table", NO_LOCK, true).is_some());
+        }
+
+        assert_eq!(lookup_relid("sp_table", NO_LOCK, true), None);
+        assert!(!search_path(true).contains(&sp_a));
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::pg_sys::PgNode;
+    use pgrx::prelude::*;
+
+    fn int4_const(value: i32) -> *mut pg_sys::Const {
+        unsafe {
+            pg_sys::makeConst(
+                pg_sys::INT4OID,
+                -1,
+                pg_sys::InvalidOid,
+                4,
+                value.into_datum().unwrap(),
+                false,
+                true,
+            )
+        }
+    }
+
+    #[pg_test]
+    fn test_pretty_const() {
+        let node = unsafe { &*int4_const(42) };
+        let pretty = node.pretty().to_string();
+        assert!(pretty.starts_with("{CONST\n   :consttype 23 /* integer */\n"), "{pretty}");
+        assert!(pretty.ends_with("\n}"), "{pretty}");
+        assert_eq!(pretty.lines().count(), node.to_string().matches(" :").count() + 2);
+    }
+
+    #[pg_test]
+    fn test_pretty_without_oids() {
+        let node = unsafe { &*int4_const(42) };
+        let pretty = node.pretty().resolve_oids(false).to_string();
+        assert!(pretty.starts_with("{CONST\n   :consttype 23\n"), "{pretty}");
+    }
+
+    #[pg_test]
+    fn test_pretty_nested() {
+        let node = unsafe {
+            let args = pg_sys::lappend(std::ptr::null_mut(), int4_const(1).cast());
+            let args = pg_sys::lappend(args, int4_const(2).cast());
+            &*pg_sys::makeBoolExpr(pg_sys::BoolExprType_AND_EXPR, args, -1)
+        };
+        let pretty = node.pretty().to_string();
+        assert!(pretty.starts_with("{BOOLEXPR\n"), "{pretty}");
+        assert!(
+            pretty.contains("\n   :args (\n      {CONST\n         :consttype 23 /* integer */\n")
+        );
+        assert!(pretty.contains("\n      }\n   )\n"), "{pretty}");
+    }
+}
@@ -0,0 +1,77 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::notify::NotifyBatcher;
+    use pgrx::prelude::*;
+
+    static SUBXACT: NotifyBatcher = NotifyBatcher::new("pgrx_notify_subxact");
+
+    #[pg_extern]
+    fn notify_queue_subxact(payload: &str, fail: bool) {
+        SUBXACT.queue(payload);
+        if fail {
+            error!("rolling back {payload}");
+        }
+    }
+
+    #[pg_test]
+    fn test_notify_coalesces_payloads() {
+        let batcher = NotifyBatcher::new("pgrx_notify_coalesce").separator(",").max_payload_len(5);
+        for payload in ["a", "b", "a", "ccc", "dd", "b"] {
+            batcher.queue(payload);
+        }
+        assert_eq!(batcher.pending_notifications(), vec!["a,b", "ccc", "dd"]);
+    }
+
+    #[pg_test]
+    fn test_notify_overflow() {
+        let batcher = NotifyBatcher::new("pgrx_notify_overflow")
+            .separator(",")
+            .max_payload_len(5)
+            .max_notifications(2)
+            .overflow_payload("resync");
+        for payload in ["a", "b", "ccc", "dd"] {
+            batcher.queue(payload);
+        }
+        assert_eq!(batcher.pending_notifications(), vec!["a,b", "resync"]);
+    }
+
+    #[pg_test]
+    #[should_panic(expected = "may be at most 3")]
+    fn test_notify_payload_too_long() {
+        NotifyBatcher::new("pgrx_notify_too_long").max_payload_len(3).queue("abcd");
+    }
+
+    #[pg_test]
+    fn test_notify_subtransactions() -> Result<(), pgrx::spi::Error> {
+        SUBXACT.queue("kept");
+        Spi::run(
+            "DO $$
+            BEGIN
+                BEGIN
+                    PERFORM tests.notify_queue_subxact('committed', false);
+                EXCEPTION WHEN others THEN NULL;
+                END;
+                BEGIN
+                    PERFORM tests.notify_queue_subxact('aborted', true);
+                EXCEPTION WHEN others THEN NULL;
+                END;
+            END $$",
+        )?;
+        assert_eq!(SUBXACT.pending_notifications(), vec!["kept\ncommitted"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+    use pgrx::Nullable;
+
+    #[pg_extern]
+    fn nullable_describe(value: Nullable<i32>) -> String {
+        match value {
+            Nullable::Absent => "absent".into(),
+            Nullable::Null => "null".into(),
+            Nullable::Valid(value) => value.to_string(),
+        }
+    }
+
+    #[pg_extern]
+    fn nullable_default(value: default!(Nullable<&str>, "NULL")) -> bool {
+        value.is_null()
+    }
+
+    // declared both with and without its argument, as Postgres only calls a function with fewer
+    // arguments than its symbol takes through such a declaration
+    #[pg_extern(sql = r#"
+        CREATE FUNCTION tests."nullable_presence"() RETURNS text
+        LANGUAGE c /* Rust */
+        AS '@MODULE_PATHNAME@', '@FUNCTION_NAME@';
+        CREATE FUNCTION tests."nullable_presence"(int4) RETURNS text
+        LANGUAGE c /* Rust */
+        AS '@MODULE_PATHNAME@', '@FUNCTION_NAME@';
+    "#)]
+    fn nullable_presence(value: Nullable<i32>) -> String {
+        nullable_describe(value)
+    }
+
+    #[pg_test]
+    fn test_nullable_argument() {
+        let described = Spi::get_one::<&str>("SELECT tests.nullable_describe(NULL)");
+        assert_eq!(described, Ok(Some("null")));
+        let described = Spi::get_one::<&str>("SELECT tests.nullable_describe(42)");
+        assert_eq!(described, Ok(Some("42")));
+    }
+
+    #[pg_test]
+    fn test_nullable_default() {
+        // Postgres fills in the default before calling, so it's `Null` rather than `Absent`
+        let is_null = Spi::get_one::<bool>("SELECT tests.nullable_default()");
+        assert_eq!(is_null, Ok(Some(true)));
+        let is_null = Spi::get_one::<bool>("SELECT tests.nullable_default('value')");
+        assert_eq!(is_null, Ok(Some(false)));
+    }
+
+    #[pg_test]
+    fn test_nullable_absent() {
+        let described = Spi::get_one::<&str>(r#"SELECT tests."nullable_presence"()"#);
+        assert_eq!(described, Ok(Some("absent")));
+        let described = Spi::get_one::<&str>(r#"SELECT tests."nullable_presence"(NULL)"#);
+        assert_eq!(described, Ok(Some("null")));
+        let described = Spi::get_one::<&str>(r#"SELECT tests."nullable_presence"(7)"#);
+        assert_eq!(described, Ok(Some("7")));
+    }
+
+    #[pg_test]
+    fn test_nullable_is_not_strict() {
+        let strict = Spi::get_one::<bool>(
+            "SELECT proisstrict FROM pg_proc WHERE oid = 'tests.nullable_describe'::regproc",
+        );
+        assert_eq!(strict, Ok(Some(false)));
+    }
+}
@@ -16,6 +16,7 @@ mod tests {
     use pgrx::prelude::*;
 
     use pgrx::numeric::Error;
+    use pgrx::PgNumeric;
     use serde::Deserialize;
 
     #[pg_extern]
@@ -33,6 +34,21 @@ mod tests {
         AnyNumeric::try_from(std::u64::MAX).unwrap()
     }
 
+    #[pg_extern]
+    fn pg_numeric_mean(a: PgNumeric, b: PgNumeric) -> PgNumeric {
+        ((a + b) / PgNumeric::from(2)).round(2)
+    }
+
+    #[pg_extern]
+    fn double_an_i128(value: i128) -> i128 {
+        value * 2
+    }
+
+    #[pg_extern]
+    fn u128_identity(value: u128) -> u128 {
+        value
+    }
+
     #[pg_test]
     fn select_a_numeric() -> Result<(), Box<dyn std::error::Error>> {
         let result = Spi::get_one::<AnyNumeric>("SELECT 42::numeric")?.expect("SPI returned null");
@@ -175,6 +191,35 @@ mod tests {
         }
     }
 
+    #[pg_test]
+    fn test_int128_conversions() -> Result<(), pgrx::spi::Error> {
+        let result = Spi::get_one::<bool>(
+            "SELECT tests.double_an_i128(-85070591730234615865843651857942052864) = -170141183460469231731687303715884105728",
+        )?;
+        assert_eq!(result, Some(true));
+
+        let result = Spi::get_one::<u128>(
+            "SELECT tests.u128_identity(340282366920938463463374607431768211455)",
+        )?;
+        assert_eq!(result, Some(u128::MAX));
+
+        let result = Spi::get_one::<i128>("SELECT 100000000000000000000.5::numeric")?;
+        assert_eq!(result, Some(100000000000000000001));
+
+        let result = Spi::get_one::<i128>("SELECT NULL::numeric")?;
+        assert_eq!(result, None);
+
+        assert!(u128::try_from(AnyNumeric::from(-1)).is_err());
+        Ok(())
+    }
+
+    #[pg_test(
+        error = "numeric is out of range for type u128: number too large to fit in target type"
+    )]
+    fn test_u128_overflow() -> Result<Option<u128>, pgrx::spi::Error> {
+        Spi::get_one::<u128>("SELECT 340282366920938463463374607431768211456::numeric")
+    }
+
     #[pg_test]
     fn test_nan_ordering() {
         let mut v = vec![
@@ -224,4 +269,36 @@ mod tests {
         assert_eq!(expected, numbers.into_iter().map(|n| n.unwrap_or_default()).sum());
         Ok(())
     }
+
+    #[pg_test]
+    fn test_round_and_trunc() -> Result<(), Box<dyn std::error::Error>> {
+        let n = AnyNumeric::try_from("1234.5678")?;
+        assert_eq!(n.round(2).to_string(), "1234.57");
+        assert_eq!(n.round(0).to_string(), "1235");
+        assert_eq!(n.round(-2).to_string(), "1200");
+        assert_eq!(n.trunc(2).to_string(), "1234.56");
+        assert_eq!(n.trunc(-2).to_string(), "1200");
+        assert_eq!(AnyNumeric::try_from("-2.5")?.round(0).to_string(), "-3");
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_scale() -> Result<(), Box<dyn std::error::Error>> {
+        assert_eq!(AnyNumeric::try_from("1234.5678")?.scale(), Some(4));
+        assert_eq!(AnyNumeric::from(42).scale(), Some(0));
+        assert_eq!(AnyNumeric::try_from("NaN")?.scale(), None);
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_pg_numeric() -> Result<(), Box<dyn std::error::Error>> {
+        let mean = Spi::get_one::<PgNumeric>("SELECT tests.pg_numeric_mean(1.005, 2.0)")?;
+        assert_eq!(mean.map(|n| n.to_string()).as_deref(), Some("1.50"));
+
+        let n = PgNumeric::try_from("12345678901234567890.125")?;
+        assert!(n > PgNumeric::from(i64::MAX));
+        assert_eq!(i64::try_from(n.trunc(0) - PgNumeric::from(i64::MAX))?, 3122306864379792083);
+        assert_eq!(f64::try_from(PgNumeric::try_from(0.5f64)?)?, 0.5);
+        Ok(())
+    }
 }
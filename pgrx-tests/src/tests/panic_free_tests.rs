@@ -0,0 +1,82 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::panic_free::{self, Error};
+    use pgrx::prelude::*;
+    use pgrx::varlena::{rust_byte_slice_to_bytea, rust_byte_slice_to_compressed_bytea};
+    use pgrx::{current_context, ToastCompression};
+
+    #[pg_test]
+    fn test_by_value() {
+        let datum = 42i32.into_datum().unwrap();
+        assert_eq!(panic_free::by_value::<i32>(datum, false, pg_sys::INT4OID), Ok(Some(42)));
+        assert_eq!(panic_free::by_value::<i32>(datum, true, pg_sys::INT4OID), Ok(None));
+        assert_eq!(
+            panic_free::by_value::<i64>(datum, false, pg_sys::INT4OID),
+            Err(Error::IncompatibleTypes { expected: pg_sys::INT8OID, actual: pg_sys::INT4OID })
+        );
+
+        let datum = (-1.5f64).into_datum().unwrap();
+        assert_eq!(panic_free::by_value::<f64>(datum, false, pg_sys::FLOAT8OID), Ok(Some(-1.5)));
+        let datum = (-7i16).into_datum().unwrap();
+        assert_eq!(panic_free::by_value::<i16>(datum, false, pg_sys::INT2OID), Ok(Some(-7)));
+    }
+
+    #[pg_test]
+    fn test_text() {
+        let datum = "panic free".into_datum().unwrap();
+        let text = unsafe { panic_free::text(datum, false, pg_sys::TEXTOID) };
+        assert_eq!(text, Ok(Some("panic free")));
+        let text = unsafe { panic_free::text(datum, false, pg_sys::INT4OID) };
+        assert_eq!(
+            text,
+            Err(Error::IncompatibleTypes { expected: pg_sys::TEXTOID, actual: pg_sys::INT4OID })
+        );
+
+        // a `text` value can only be made of invalid UTF-8 by going behind Postgres' back
+        let invalid = rust_byte_slice_to_bytea(b"caf\xe9");
+        let text = unsafe { panic_free::text_to_str(invalid.as_ptr()) };
+        assert!(matches!(text, Err(Error::InvalidUtf8(_))));
+    }
+
+    #[pg_test]
+    fn test_varlena() {
+        let bytea = rust_byte_slice_to_bytea(&[1, 2, 3]);
+        unsafe {
+            assert_eq!(panic_free::varsize_any(bytea.as_ptr()), Ok(pg_sys::VARHDRSZ + 3));
+            assert_eq!(panic_free::varlena_data(bytea.as_ptr()), Ok(&[1u8, 2, 3][..]));
+        }
+
+        let compressed = rust_byte_slice_to_compressed_bytea(&[7; 100000], ToastCompression::Pglz);
+        let data = unsafe { panic_free::varlena_data(compressed.as_ptr()) };
+        assert_eq!(data, Err(Error::Toasted));
+
+        // an external varlena's header, with a tag Postgres doesn't know
+        let bogus = [0x01u8, 0xff, 0, 0, 0, 0, 0, 0];
+        let size = unsafe { panic_free::varsize_any(bogus.as_ptr().cast()) };
+        assert_eq!(size, Err(Error::UnknownVartag(0xff)));
+    }
+
+    #[pg_test]
+    fn test_try_palloc_in() {
+        current_context(|mcx| {
+            let bytes = panic_free::try_palloc_in(mcx, 64).unwrap();
+            assert_eq!(bytes, &[0u8; 64][..]);
+
+            let huge = panic_free::try_palloc_in(mcx, usize::MAX);
+            assert_eq!(huge, Err(Error::InvalidAllocSize(usize::MAX)));
+        });
+    }
+}
@@ -0,0 +1,72 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+    use pgrx::PgChar;
+
+    #[pg_extern]
+    fn char_upper(c: PgChar) -> PgChar {
+        PgChar(c.0.to_ascii_uppercase())
+    }
+
+    #[pg_test]
+    fn test_pg_char_datum() -> Result<(), pgrx::spi::Error> {
+        let relkind =
+            Spi::get_one::<PgChar>("SELECT relkind FROM pg_class WHERE relname = 'pg_class'")?;
+        assert_eq!(relkind, Some(PgChar(b'r')));
+
+        let upper = Spi::get_one::<PgChar>("SELECT tests.char_upper('x'::\"char\")")?;
+        assert_eq!(upper, Some(PgChar(b'X')));
+
+        // older versions read and write non-ASCII bytes as they are
+        #[cfg(any(feature = "pg15", feature = "pg16"))]
+        {
+            let same = Spi::get_one_with_args::<bool>(
+                "SELECT $1 = '\\201'::\"char\"",
+                vec![(PgBuiltInOids::CHAROID.oid(), PgChar(0o201).into_datum())],
+            )?;
+            assert_eq!(same, Some(true));
+        }
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_pg_char_text() -> Result<(), pgrx::spi::Error> {
+        #[cfg(any(feature = "pg15", feature = "pg16"))]
+        let chars = [PgChar(b'a'), PgChar(0), PgChar(0o201), PgChar(b'\\')];
+        #[cfg(not(any(feature = "pg15", feature = "pg16")))]
+        let chars = [PgChar(b'a'), PgChar(0), PgChar(b'\\')];
+        for c in chars {
+            let text = Spi::get_one_with_args::<String>(
+                "SELECT $1::text",
+                vec![(PgBuiltInOids::CHAROID.oid(), c.into_datum())],
+            )?;
+            assert_eq!(text, Some(c.to_string()));
+            assert_eq!(c.to_string().parse::<PgChar>(), Ok(c));
+        }
+        assert_eq!(PgChar(0o201).as_char(), None);
+        assert_eq!(PgChar::try_from('é').ok(), None);
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_pg_char_array() -> Result<(), pgrx::spi::Error> {
+        let kinds = Spi::get_one::<Vec<PgChar>>(
+            "SELECT array_agg(DISTINCT relkind ORDER BY relkind) FROM pg_class WHERE relkind IN ('r', 'i')",
+        )?;
+        assert_eq!(kinds, Some(vec![PgChar(b'i'), PgChar(b'r')]));
+        Ok(())
+    }
+}
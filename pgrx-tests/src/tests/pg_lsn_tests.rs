@@ -0,0 +1,66 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+    use pgrx::PgLsn;
+
+    #[pg_extern]
+    fn lsn_advance(lsn: PgLsn, bytes: i64) -> PgLsn {
+        lsn + bytes as u64
+    }
+
+    #[pg_test]
+    fn test_pg_lsn_datum() -> Result<(), pgrx::spi::Error> {
+        let lsn = Spi::get_one::<PgLsn>("SELECT '16/B374D848'::pg_lsn")?.unwrap();
+        assert_eq!(lsn, PgLsn(0x16_B374D848));
+        assert_eq!(lsn.to_string(), "16/B374D848");
+
+        let advanced =
+            Spi::get_one::<bool>("SELECT tests.lsn_advance('0/FFFFFFFF', 1) = '1/0'::pg_lsn")?;
+        assert_eq!(advanced, Some(true));
+
+        let diff = Spi::get_one_with_args::<i64>(
+            "SELECT pg_wal_lsn_diff($1, '16/B3740000')::bigint",
+            vec![(PgBuiltInOids::LSNOID.oid(), lsn.into_datum())],
+        )?;
+        assert_eq!(diff.map(i128::from), Some(lsn - "16/B3740000".parse::<PgLsn>().unwrap()));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_pg_lsn_ordering() -> Result<(), pgrx::spi::Error> {
+        let lsns = Spi::get_one::<Vec<PgLsn>>(
+            "SELECT array_agg(l ORDER BY l) FROM unnest('{1/0, 0/FFFFFFFF, 0/0}'::pg_lsn[]) l",
+        )?
+        .unwrap();
+        assert_eq!(lsns, ["0/0", "0/FFFFFFFF", "1/0"].map(|s| s.parse::<PgLsn>().unwrap()));
+        assert!(!lsns[0].is_valid());
+        let mut sorted = lsns.clone();
+        sorted.sort();
+        assert_eq!(sorted, lsns);
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_pg_lsn_arithmetic() {
+        let lsn: PgLsn = "1/10".parse().unwrap();
+        assert_eq!(lsn - 0x20, "0/FFFFFFF0".parse().unwrap());
+        assert_eq!(PgLsn::INVALID.checked_sub(1), None);
+        assert_eq!(PgLsn(u64::MAX).checked_add(1), None);
+        assert_eq!(PgLsn::INVALID - lsn, -0x1_00000010);
+        assert!("1/".parse::<PgLsn>().is_err());
+        assert!("123456789/0".parse::<PgLsn>().is_err());
+    }
+}
@@ -0,0 +1,315 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+    use pgrx::varlena::{
+        detoast_prefix, detoast_slice, detoast_text_prefix, rust_byte_slice_to_bytea,
+        rust_byte_slice_to_compressed_bytea, rust_str_to_text_p, ToastCompression,
+    };
+    use pgrx::{FromDatumBorrowed, PgBytes, PgMemoryContexts, PgStr, Toasted};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PostgresType, Serialize, Deserialize)]
+    pub struct Manuscript {
+        title: String,
+        body: String,
+    }
+
+    #[pg_extern]
+    fn manuscript(title: &str, body: &str) -> Manuscript {
+        Manuscript { title: title.to_string(), body: body.to_string() }
+    }
+
+    #[pg_extern]
+    fn pg_str_len(s: PgStr<'_>) -> i64 {
+        s.len() as i64
+    }
+
+    #[pg_extern]
+    fn pg_str_upper(s: PgStr<'_>) -> String {
+        s.to_uppercase()
+    }
+
+    #[pg_extern]
+    fn pg_str_passthrough(s: PgStr<'_>) -> PgStr<'_> {
+        s
+    }
+
+    #[pg_extern]
+    fn pg_str_prefix(s: PgStr<'_>, len: i32) -> &str {
+        s.prefix(len as usize)
+    }
+
+    #[pg_extern]
+    fn pg_str_slice(s: PgStr<'_>, offset: i32, len: i32) -> Vec<u8> {
+        s.slice(offset as usize, len as usize).to_vec()
+    }
+
+    #[pg_extern]
+    fn toasted_len(m: Toasted<'_, Manuscript>) -> i64 {
+        m.len() as i64
+    }
+
+    #[pg_extern]
+    fn toasted_title(m: Toasted<'_, Manuscript>) -> String {
+        m.get().title
+    }
+
+    #[pg_extern]
+    fn toasted_passthrough(m: Toasted<'_, Manuscript>) -> Toasted<'_, Manuscript> {
+        m
+    }
+
+    #[pg_extern]
+    fn toasted_text_prefix(t: Toasted<'_, String>) -> String {
+        if t.len() > 8 {
+            String::from_utf8_lossy(t.slice(0, 8)).into_owned()
+        } else {
+            t.get()
+        }
+    }
+
+    #[pg_extern]
+    fn pg_bytes_len(b: PgBytes<'_>) -> i64 {
+        b.len() as i64
+    }
+
+    #[pg_extern]
+    fn pg_bytes_slice(b: PgBytes<'_>, offset: i32, len: i32) -> Vec<u8> {
+        b.slice(offset as usize, len as usize).to_vec()
+    }
+
+    #[pg_extern]
+    fn pg_bytes_passthrough(b: PgBytes<'_>) -> PgBytes<'_> {
+        b
+    }
+
+    #[pg_test]
+    fn test_pg_str_inline() -> Result<(), pgrx::spi::Error> {
+        assert_eq!(Spi::get_one::<i64>("SELECT tests.pg_str_len('héllo')")?, Some(6));
+        assert_eq!(
+            Spi::get_one::<String>("SELECT tests.pg_str_upper('héllo')")?,
+            Some("HÉLLO".to_string())
+        );
+        assert_eq!(Spi::get_one::<i64>("SELECT tests.pg_str_len('')")?, Some(0));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_pg_str_toasted() -> Result<(), pgrx::spi::Error> {
+        Spi::run("CREATE TABLE pg_str_toasted (t text, b bytea)")?;
+        Spi::run(
+            "INSERT INTO pg_str_toasted
+             SELECT repeat('abc', 100000), convert_to(repeat('xyz', 100000), 'UTF8')",
+        )?;
+        assert_eq!(
+            Spi::get_one::<i64>("SELECT tests.pg_str_len(t) FROM pg_str_toasted")?,
+            Some(300_000)
+        );
+        assert_eq!(
+            Spi::get_one::<bool>(
+                "SELECT tests.pg_str_passthrough(t) = t AND tests.pg_str_upper(t) = upper(t)
+                 FROM pg_str_toasted"
+            )?,
+            Some(true)
+        );
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_pg_bytes_inline() -> Result<(), pgrx::spi::Error> {
+        assert_eq!(Spi::get_one::<i64>("SELECT tests.pg_bytes_len('\\x0102'::bytea)")?, Some(2));
+        assert_eq!(
+            Spi::get_one::<Vec<u8>>("SELECT tests.pg_bytes_slice('abcdefg'::bytea, 1, 3)")?,
+            Some(b"bcd".to_vec())
+        );
+        assert_eq!(
+            Spi::get_one::<Vec<u8>>("SELECT tests.pg_bytes_slice('abc'::bytea, 2, 10)")?,
+            Some(b"c".to_vec())
+        );
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_pg_bytes_toasted() -> Result<(), pgrx::spi::Error> {
+        Spi::run("CREATE TABLE pg_bytes_toasted (b bytea)")?;
+        // store the value out of line without compression, so slicing only fetches some chunks
+        Spi::run("ALTER TABLE pg_bytes_toasted ALTER COLUMN b SET STORAGE EXTERNAL")?;
+        Spi::run(
+            "INSERT INTO pg_bytes_toasted SELECT convert_to(repeat('0123456789', 50000), 'UTF8')",
+        )?;
+        assert_eq!(
+            Spi::get_one::<i64>("SELECT tests.pg_bytes_len(b) FROM pg_bytes_toasted")?,
+            Some(500_000)
+        );
+        assert_eq!(
+            Spi::get_one::<Vec<u8>>(
+                "SELECT tests.pg_bytes_slice(b, 250003, 4) FROM pg_bytes_toasted"
+            )?,
+            Some(b"3456".to_vec())
+        );
+        assert_eq!(
+            Spi::get_one::<bool>("SELECT tests.pg_bytes_passthrough(b) = b FROM pg_bytes_toasted")?,
+            Some(true)
+        );
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_pg_str_prefix() -> Result<(), pgrx::spi::Error> {
+        assert_eq!(Spi::get_one::<&str>("SELECT tests.pg_str_prefix('héllo', 3)")?, Some("hé"));
+        // the prefix ends partway through 'é'
+        assert_eq!(Spi::get_one::<&str>("SELECT tests.pg_str_prefix('héllo', 2)")?, Some("h"));
+        assert_eq!(
+            Spi::get_one::<&str>("SELECT tests.pg_str_prefix(repeat('abc', 100000), 4)")?,
+            Some("abca")
+        );
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_pg_str_slice() -> Result<(), pgrx::spi::Error> {
+        assert_eq!(
+            Spi::get_one::<Vec<u8>>("SELECT tests.pg_str_slice('héllo', 1, 2)")?,
+            Some("é".as_bytes().to_vec())
+        );
+        assert_eq!(
+            Spi::get_one::<Vec<u8>>(
+                "SELECT tests.pg_str_slice(repeat('abc', 100000), 299998, 10)"
+            )?,
+            Some(b"bc".to_vec())
+        );
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_toasted_custom_type() -> Result<(), pgrx::spi::Error> {
+        Spi::run("CREATE TABLE toasted_manuscripts (m tests.Manuscript)")?;
+        Spi::run(
+            "INSERT INTO toasted_manuscripts
+             SELECT tests.manuscript('short', 'tiny')
+             UNION ALL SELECT tests.manuscript('long', repeat('words ', 100000))",
+        )?;
+        assert_eq!(
+            Spi::get_one::<bool>(
+                "SELECT tests.toasted_len(m) > 600000 FROM toasted_manuscripts
+                 WHERE tests.toasted_title(m) = 'long'"
+            )?,
+            Some(true)
+        );
+        assert_eq!(
+            Spi::get_one::<String>(
+                "SELECT tests.toasted_title(tests.toasted_passthrough(m)) FROM toasted_manuscripts
+                 ORDER BY tests.toasted_len(m) LIMIT 1"
+            )?,
+            Some("short".to_string())
+        );
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_toasted_builtin_type() -> Result<(), pgrx::spi::Error> {
+        assert_eq!(
+            Spi::get_one::<String>("SELECT tests.toasted_text_prefix(repeat('abcd', 100000))")?,
+            Some("abcdabcd".to_string())
+        );
+        assert_eq!(
+            Spi::get_one::<String>("SELECT tests.toasted_text_prefix('short')")?,
+            Some("short".to_string())
+        );
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_borrowed_detoasts_into_its_context() {
+        let bytes = b"0123456789".repeat(10000);
+        let compressed = rust_byte_slice_to_compressed_bytea(&bytes, ToastCompression::Pglz);
+        let datum = pg_sys::Datum::from(compressed.as_ptr());
+        pgrx::current_context(|memcx| unsafe {
+            let borrowed = PgBytes::from_datum_borrowed(datum, false, pg_sys::BYTEAOID, memcx)
+                .expect("bytea should not be NULL");
+            // detoasted while another context is current, which is then deleted
+            let mut other = PgMemoryContexts::new("pg_bytes_other");
+            let detoasted = other.switch_to(|_| borrowed.as_bytes());
+            drop(other);
+            assert_eq!(detoasted, &bytes[..]);
+        });
+    }
+
+    #[pg_test]
+    fn test_detoast_slice() {
+        let bytes = b"0123456789".repeat(10000);
+        let compressed = rust_byte_slice_to_compressed_bytea(&bytes, ToastCompression::Pglz);
+        let datum = pg_sys::Datum::from(compressed.as_ptr());
+        unsafe {
+            assert_eq!(detoast_prefix(datum, 4), b"0123");
+            assert_eq!(detoast_slice(datum, 50005, 3), b"567");
+            assert_eq!(detoast_slice(datum, 99998, 10), b"89");
+            assert_eq!(detoast_slice(datum, 200000, 10), b"");
+        }
+
+        let text = rust_str_to_text_p("héllo");
+        let datum = pg_sys::Datum::from(text.as_ptr());
+        unsafe {
+            assert_eq!(detoast_text_prefix(datum, 2), "h");
+            assert_eq!(detoast_text_prefix(datum, 100), "héllo");
+        }
+    }
+
+    #[pg_test(error = "text is not valid UTF-8: invalid utf-8 sequence of 1 bytes from index 3")]
+    fn test_detoast_text_prefix_invalid() {
+        // a `text` value can only be made of invalid UTF-8 by going behind Postgres' back
+        let invalid = rust_byte_slice_to_bytea(b"caf\xe9!");
+        let datum = pg_sys::Datum::from(invalid.as_ptr());
+        unsafe {
+            detoast_text_prefix(datum, 100);
+        }
+    }
+
+    #[pg_test]
+    fn test_transcode() {
+        use pgrx::varlena::{server_to_utf8, transcode};
+
+        pgrx::current_context(|memcx| {
+            let latin1 = b"na\xefve caf\xe9";
+            let utf8 = transcode(latin1, pg_sys::pg_enc_PG_LATIN1, pg_sys::pg_enc_PG_UTF8, memcx);
+            assert_eq!(utf8, "naïve café".as_bytes());
+            assert_eq!(
+                transcode(utf8, pg_sys::pg_enc_PG_UTF8, pg_sys::pg_enc_PG_LATIN1, memcx),
+                latin1
+            );
+
+            // nothing to convert, so these are borrowed as they are
+            let ascii = b"plain";
+            let same = transcode(ascii, pg_sys::pg_enc_PG_LATIN1, pg_sys::pg_enc_PG_UTF8, memcx);
+            assert_eq!(same.as_ptr(), ascii.as_ptr());
+            assert_eq!(server_to_utf8(utf8, memcx).as_ptr(), utf8.as_ptr());
+        });
+    }
+
+    #[pg_test(
+        error = "character with byte sequence 0xe2 0x9c 0x93 in encoding \"UTF8\" has no equivalent in encoding \"LATIN1\""
+    )]
+    fn test_transcode_unrepresentable() {
+        pgrx::current_context(|memcx| {
+            pgrx::varlena::transcode(
+                "✓".as_bytes(),
+                pg_sys::pg_enc_PG_UTF8,
+                pg_sys::pg_enc_PG_LATIN1,
+                memcx,
+            );
+        });
+    }
+}
@@ -0,0 +1,85 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+use pgrx::prelude::*;
+
+// roles belong to the cluster rather than the database, so this one may be left over from an
+// earlier run
+extension_sql!(
+    r#"
+DO $$
+BEGIN
+    CREATE ROLE pgrx_test_preset_role NOLOGIN;
+EXCEPTION WHEN duplicate_object THEN NULL;
+END
+$$;
+GRANT USAGE ON SCHEMA tests TO pgrx_test_preset_role;
+"#,
+    name = "create_test_preset_role",
+    finalize,
+);
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+
+    #[pg_test(isolation = "serializable")]
+    fn test_preset_isolation() -> Result<(), spi::Error> {
+        let isolation = Spi::get_one::<String>("SHOW transaction_isolation")?;
+        assert_eq!(isolation.as_deref(), Some("serializable"));
+        Ok(())
+    }
+
+    #[pg_test(search_path = "tests, public")]
+    fn test_preset_search_path() -> Result<(), spi::Error> {
+        let search_path = Spi::get_one::<String>("SHOW search_path")?;
+        assert_eq!(search_path.as_deref(), Some("tests, public"));
+        Ok(())
+    }
+
+    #[pg_test(set(work_mem = "1234kB", "pgrx.test_preset" = "hello"))]
+    fn test_preset_gucs() -> Result<(), spi::Error> {
+        assert_eq!(Spi::get_one::<String>("SHOW work_mem")?.as_deref(), Some("1234kB"));
+        let custom = Spi::get_one::<String>("SELECT current_setting('pgrx.test_preset')")?;
+        assert_eq!(custom.as_deref(), Some("hello"));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_presets_are_transaction_local() -> Result<(), spi::Error> {
+        let isolation = Spi::get_one::<String>("SHOW transaction_isolation")?;
+        assert_eq!(isolation.as_deref(), Some("read committed"));
+        let custom = Spi::get_one::<String>("SELECT current_setting('pgrx.test_preset', true)")?;
+        assert_ne!(custom.as_deref(), Some("hello"));
+        Ok(())
+    }
+
+    #[pg_test(role = "pgrx_test_preset_role")]
+    fn test_preset_role() -> Result<(), spi::Error> {
+        let current_user = Spi::get_one::<String>("SELECT current_user::text")?;
+        assert_eq!(current_user.as_deref(), Some("pgrx_test_preset_role"));
+        // everyone may create temporary tables
+        Spi::run("CREATE TEMPORARY TABLE preset_role_scratch (id int)")?;
+        Spi::run("INSERT INTO preset_role_scratch VALUES (1)")?;
+        let count = Spi::get_one::<i64>("SELECT count(*) FROM preset_role_scratch")?;
+        assert_eq!(count, Some(1));
+        Ok(())
+    }
+
+    // `pg_monitor` isn't allowed to use the `tests` schema, so the test function can't even be
+    // called once the role is switched to
+    #[pg_test(role = "pg_monitor", error = "permission denied for schema tests")]
+    fn test_preset_role_without_privileges() {
+        unreachable!("the test function should not be callable as pg_monitor")
+    }
+}
@@ -0,0 +1,66 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+
+    pg_version_at_least!(14, {
+        #[pg_extern]
+        fn pg_version_multirange_only() -> bool {
+            true
+        }
+    } else {
+        #[pg_extern]
+        fn pg_version_no_multiranges() -> bool {
+            false
+        }
+    });
+
+    #[pg_test]
+    fn test_pg_version_major() {
+        let server =
+            Spi::get_one::<i32>("SELECT current_setting('server_version_num')::int / 10000");
+        assert_eq!(server, Ok(Some(pgrx::pg_version::MAJOR as i32)));
+        assert_eq!(pgrx::pg_version::MAJOR as u32, pgrx::pg_version::VERSION_NUM / 10000);
+    }
+
+    #[pg_test]
+    fn test_pg_version_expressions() {
+        let at_least = pg_version_at_least!(14, { 14 } else { 0 });
+        assert_eq!(at_least == 14, pgrx::pg_version::at_least(14));
+
+        let below = pg_version_below!(14, { true } else { false });
+        assert_eq!(below, !pgrx::pg_version::at_least(14));
+
+        let mut ran = false;
+        pg_version_at_least!(11, {
+            ran = true;
+        });
+        assert!(ran);
+    }
+
+    #[pg_test]
+    fn test_pg_version_items_follow_version() -> Result<(), spi::Error> {
+        let defined = |name: &str| {
+            Spi::get_one_with_args::<bool>(
+                "SELECT EXISTS(SELECT 1 FROM pg_proc WHERE proname = $1)",
+                vec![(PgBuiltInOids::TEXTOID.oid(), name.into_datum())],
+            )
+        };
+        let newer = pgrx::pg_version::at_least(14);
+        assert_eq!(defined("pg_version_multirange_only")?, Some(newer));
+        assert_eq!(defined("pg_version_no_multiranges")?, Some(!newer));
+        Ok(())
+    }
+}
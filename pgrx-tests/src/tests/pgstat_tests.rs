@@ -0,0 +1,55 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+    use pgrx::{pgstat, PgRelation};
+
+    #[pg_test]
+    fn test_count_heap_insert() -> Result<(), spi::Error> {
+        Spi::run("CREATE TABLE tests.pgstat_insert (id int)")?;
+        let rel = PgRelation::open_with_name_and_share_lock("tests.pgstat_insert").unwrap();
+        pgstat::count_heap_insert(&rel, 42);
+        pgstat::count_heap_delete(&rel);
+
+        let inserted = Spi::get_one::<i64>(
+            "SELECT pg_stat_get_xact_tuples_inserted('tests.pgstat_insert'::regclass)",
+        )?;
+        let deleted = Spi::get_one::<i64>(
+            "SELECT pg_stat_get_xact_tuples_deleted('tests.pgstat_insert'::regclass)",
+        )?;
+        assert_eq!(inserted, Some(42));
+        assert_eq!(deleted, Some(1));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_count_heap_scan() -> Result<(), spi::Error> {
+        Spi::run("CREATE TABLE tests.pgstat_scan (id int)")?;
+        let rel = PgRelation::open_with_name_and_share_lock("tests.pgstat_scan").unwrap();
+        pgstat::count_heap_scan(&rel);
+        pgstat::count_heap_getnext(&rel);
+        pgstat::count_heap_getnext(&rel);
+
+        let scans =
+            Spi::get_one::<i64>("SELECT pg_stat_get_xact_numscans('tests.pgstat_scan'::regclass)")?;
+        let returned = Spi::get_one::<i64>(
+            "SELECT pg_stat_get_xact_tuples_returned('tests.pgstat_scan'::regclass)",
+        )?;
+        assert_eq!(scans, Some(1));
+        assert_eq!(returned, Some(2));
+        Ok(())
+    }
+}
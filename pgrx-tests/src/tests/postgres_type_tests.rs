@@ -9,7 +9,7 @@
 //LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
 use core::ffi::CStr;
 use pgrx::prelude::*;
-use pgrx::{InOutFuncs, PgVarlena, PgVarlenaInOutFuncs, StringInfo};
+use pgrx::{InOutFuncs, PgVarlena, PgVarlenaInOutFuncs, ReceiveBuffer, SendRecvFuncs, StringInfo};
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
@@ -153,6 +153,71 @@ pub enum JsonEnumType {
     E2 { b: f32 },
 }
 
+#[derive(Serialize, Deserialize, PostgresType)]
+#[storage = "main"]
+#[compression = "pglz"]
+pub struct CompressedType {
+    text: String,
+}
+
+#[pg_extern(immutable)]
+fn make_compressed_type(len: i32) -> CompressedType {
+    CompressedType { text: "a".repeat(len as usize) }
+}
+
+#[pg_extern(immutable)]
+fn compressed_type_len(input: CompressedType) -> i32 {
+    input.text.len() as i32
+}
+
+#[derive(Serialize, Deserialize, PostgresType)]
+#[storage = "external"]
+#[alignment = "double"]
+pub struct StreamedType {
+    text: String,
+}
+
+#[pg_extern(immutable)]
+fn make_streamed_type(len: i32) -> StreamedType {
+    StreamedType { text: "a".repeat(len as usize) }
+}
+
+#[derive(Serialize, Deserialize, PostgresType)]
+#[sendrecvfuncs]
+pub struct BinaryPoint {
+    x: i32,
+    y: i32,
+}
+
+impl SendRecvFuncs for BinaryPoint {
+    fn send(&self) -> Vec<u8> {
+        [self.x.to_be_bytes(), self.y.to_be_bytes()].concat()
+    }
+
+    fn recv(buffer: &mut ReceiveBuffer<'_>) -> Self {
+        BinaryPoint { x: buffer.read_i32(), y: buffer.read_i32() }
+    }
+}
+
+// `Parcel` is declared before the types it's made of, whose SQL must still come first
+#[derive(Serialize, Deserialize, PostgresType)]
+pub struct Parcel {
+    label: ParcelLabel,
+    contents: Vec<JsonType>,
+    kind: Option<JsonEnumType>,
+    shipped: Date,
+}
+
+#[derive(Serialize, Deserialize, PostgresType)]
+pub struct ParcelLabel {
+    to: String,
+}
+
+#[pg_extern(immutable)]
+fn parcel_label(parcel: Parcel) -> ParcelLabel {
+    parcel.label
+}
+
 #[cfg(any(test, feature = "pg_test"))]
 #[pgrx::pg_schema]
 mod tests {
@@ -160,11 +225,16 @@ mod tests {
     use crate as pgrx_tests;
 
     use crate::tests::postgres_type_tests::{
-        CustomTextFormatSerializedEnumType, CustomTextFormatSerializedType, JsonEnumType, JsonType,
-        VarlenaEnumType, VarlenaType,
+        BinaryPoint, CustomTextFormatSerializedEnumType, CustomTextFormatSerializedType,
+        JsonEnumType, JsonType, Parcel, ParcelLabel, StreamedType, VarlenaEnumType, VarlenaType,
     };
     use pgrx::prelude::*;
-    use pgrx::PgVarlena;
+    use pgrx::varlena::{
+        datum_compression, rust_byte_slice_to_compressed_bytea, varsize_any, DetoastReader,
+        ToastCompression,
+    };
+    use pgrx::{cbor_decode_streaming, PgVarlena, StringInfo};
+    use std::io::Read;
 
     #[pg_test]
     fn test_mytype() -> Result<(), pgrx::spi::Error> {
@@ -254,4 +324,159 @@ mod tests {
         assert!(matches!(result, JsonEnumType::E1 { a } if a == 1.0));
         Ok(())
     }
+
+    #[pg_test]
+    fn test_nested_types() -> Result<(), pgrx::spi::Error> {
+        let parcel = Spi::get_one::<Parcel>(
+            r#"SELECT '{"label": {"to": "Nami"},
+                        "contents": [{"a": 1.0, "b": 2.0, "c": 3}],
+                        "kind": {"type": "E2", "b": 4.0},
+                        "shipped": "2023-05-01"}'::Parcel"#,
+        )?
+        .unwrap();
+        assert_eq!(parcel.label.to, "Nami");
+        assert_eq!(parcel.contents.len(), 1);
+        assert_eq!(parcel.contents[0].c, 3);
+        assert!(matches!(parcel.kind, Some(JsonEnumType::E2 { b }) if b == 4.0));
+        assert_eq!(parcel.shipped.to_iso_string(), "2023-05-01");
+
+        let label = Spi::get_one::<ParcelLabel>(
+            r#"SELECT tests.parcel_label('{"label": {"to": "Brandy"}, "contents": [], "kind": null,
+                                           "shipped": "2023-05-01"}')"#,
+        )?
+        .unwrap();
+        assert_eq!(label.to, "Brandy");
+
+        // the types a type is made of are created first
+        let ordered = Spi::get_one::<bool>(
+            "SELECT 'parcellabel'::regtype::oid < 'parcel'::regtype::oid
+                AND 'jsontype'::regtype::oid < 'parcel'::regtype::oid",
+        )?;
+        assert_eq!(ordered, Some(true));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_type_storage() -> Result<(), pgrx::spi::Error> {
+        let storage = Spi::get_one::<i8>(
+            "SELECT typstorage::\"char\" FROM pg_type WHERE typname = 'compressedtype'",
+        )?;
+        assert_eq!(storage, Some(b'm' as i8));
+        let storage = Spi::get_one::<i8>(
+            "SELECT typstorage::\"char\" FROM pg_type WHERE typname = 'jsontype'",
+        )?;
+        assert_eq!(storage, Some(b'x' as i8));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_type_alignment() -> Result<(), pgrx::spi::Error> {
+        let alignment = Spi::get_one::<i8>(
+            "SELECT typalign::\"char\" FROM pg_type WHERE typname = 'streamedtype'",
+        )?;
+        assert_eq!(alignment, Some(b'd' as i8));
+        let alignment = Spi::get_one::<i8>(
+            "SELECT typalign::\"char\" FROM pg_type WHERE typname = 'jsontype'",
+        )?;
+        assert_eq!(alignment, Some(b'i' as i8));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_streamed_type() -> Result<(), pgrx::spi::Error> {
+        Spi::run(
+            "CREATE TABLE tests.streamed (v StreamedType);
+             INSERT INTO tests.streamed VALUES (tests.make_streamed_type(100000));",
+        )?;
+        Spi::connect(|client| {
+            let datum = client
+                .select("SELECT v FROM tests.streamed", None, None)?
+                .first()
+                .get_datum_by_ordinal(1)?
+                .unwrap();
+            assert_eq!(unsafe { datum_compression(datum) }, None);
+
+            let streamed = unsafe { cbor_decode_streaming::<StreamedType>(datum, 1000) };
+            assert_eq!(streamed.text.len(), 100000);
+
+            let mut reader = unsafe { DetoastReader::new(datum, 1000) };
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).unwrap();
+            assert_eq!(bytes.len(), reader.len());
+            assert!(bytes.len() > 100000);
+            Ok(())
+        })
+    }
+
+    #[pg_test]
+    fn test_compressed_type() -> Result<(), pgrx::spi::Error> {
+        let small = Spi::get_one::<bool>(
+            "SELECT pg_column_size(tests.make_compressed_type(100000)) < 10000",
+        )?;
+        assert_eq!(small, Some(true));
+        let len = Spi::get_one::<i32>(
+            "SELECT tests.compressed_type_len(tests.make_compressed_type(100000))",
+        )?;
+        assert_eq!(len, Some(100000));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_compressed_bytea() -> Result<(), pgrx::spi::Error> {
+        let bytes = vec![7u8; 100000];
+        let bytea = rust_byte_slice_to_compressed_bytea(&bytes, ToastCompression::Pglz);
+        assert!(unsafe { varsize_any(bytea.as_ptr()) } < 10000);
+
+        let same = Spi::get_one_with_args::<Vec<u8>>(
+            "SELECT $1",
+            vec![(PgBuiltInOids::BYTEAOID.oid(), Some(bytea.into_pg().into()))],
+        )?;
+        assert_eq!(same, Some(bytes));
+
+        let incompressible = (0..=255u8).collect::<Vec<_>>();
+        let bytea = rust_byte_slice_to_compressed_bytea(&incompressible, ToastCompression::Pglz);
+        assert_eq!(unsafe { varsize_any(bytea.as_ptr()) }, pg_sys::VARHDRSZ + 256);
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_sendrecvfuncs_registered() -> Result<(), pgrx::spi::Error> {
+        let (send, recv) = Spi::get_two::<String, String>(
+            "SELECT typsend::text, typreceive::text FROM pg_type WHERE typname = 'binarypoint'",
+        )?;
+        assert!(send.unwrap().ends_with("binarypoint_send"));
+        assert!(recv.unwrap().ends_with("binarypoint_recv"));
+
+        let (send, recv) = Spi::get_two::<bool, bool>(
+            "SELECT typsend::oid = 0, typreceive::oid = 0 FROM pg_type WHERE typname = 'jsontype'",
+        )?;
+        assert_eq!((send, recv), (Some(true), Some(true)));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_binary_send() -> Result<(), pgrx::spi::Error> {
+        let bytes = Spi::get_one::<Vec<u8>>(
+            r#"SELECT binarypoint_send('{"x": 1, "y": -2}'::BinaryPoint)"#,
+        )?;
+        assert_eq!(bytes, Some(vec![0, 0, 0, 1, 0xff, 0xff, 0xff, 0xfe]));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_binary_recv() -> Result<(), pgrx::spi::Error> {
+        let typoid = Spi::get_one::<pg_sys::Oid>("SELECT 'BinaryPoint'::regtype::oid")?.unwrap();
+        let mut buffer = StringInfo::new();
+        buffer.push_bytes(&[0, 0, 0, 3, 0, 0, 0, 4]);
+
+        let point = unsafe {
+            let mut recv = pg_sys::InvalidOid;
+            let mut ioparam = pg_sys::InvalidOid;
+            pg_sys::getTypeBinaryInputInfo(typoid, &mut recv, &mut ioparam);
+            let datum = pg_sys::OidReceiveFunctionCall(recv, buffer.into_pg(), ioparam, -1);
+            BinaryPoint::from_datum(datum, false).unwrap()
+        };
+        assert_eq!((point.x, point.y), (3, 4));
+        Ok(())
+    }
 }
@@ -0,0 +1,62 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+
+    #[pg_extern(revoke_from_public, grant = "pg_monitor", grant = "pg_read_all_stats")]
+    fn privileged_answer(x: i32) -> i32 {
+        x + 42
+    }
+
+    #[pg_extern(revoke_from_public)]
+    fn owner_only_answer() -> i32 {
+        42
+    }
+
+    fn can_execute(role: &str, function: &str) -> Result<Option<bool>, spi::Error> {
+        Spi::get_one_with_args::<bool>(
+            "SELECT has_function_privilege($1, $2, 'EXECUTE')",
+            vec![
+                (PgBuiltInOids::TEXTOID.oid(), role.into_datum()),
+                (PgBuiltInOids::TEXTOID.oid(), function.into_datum()),
+            ],
+        )
+    }
+
+    #[pg_test]
+    fn test_grant_to_roles() -> Result<(), spi::Error> {
+        assert_eq!(can_execute("pg_monitor", "tests.privileged_answer(integer)")?, Some(true));
+        assert_eq!(
+            can_execute("pg_read_all_stats", "tests.privileged_answer(integer)")?,
+            Some(true)
+        );
+        assert_eq!(
+            can_execute("pg_signal_backend", "tests.privileged_answer(integer)")?,
+            Some(false)
+        );
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_revoke_from_public() -> Result<(), spi::Error> {
+        let public_grants = Spi::get_one::<i64>(
+            "SELECT count(*) FROM pg_proc, aclexplode(proacl) acl
+              WHERE pg_proc.oid = 'tests.owner_only_answer()'::regprocedure AND acl.grantee = 0",
+        )?;
+        assert_eq!(public_grants, Some(0));
+        assert_eq!(can_execute("pg_monitor", "tests.owner_only_answer()")?, Some(false));
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+    use pgrx::progress::{ProgressCommand, ProgressGuard, NUM_PROGRESS_PARAMS};
+    use std::time::Duration;
+
+    #[pg_test]
+    fn test_progress_reported() -> Result<(), spi::Error> {
+        let relid = Spi::get_one::<pg_sys::Oid>("SELECT 'pg_class'::regclass::oid")?.unwrap();
+        let mut progress = ProgressGuard::start(ProgressCommand::Vacuum, relid).elapsed_param(0);
+        std::thread::sleep(Duration::from_millis(5));
+        progress.update(1, 42);
+        progress.update_many(&[(2, 7), (3, -1)]);
+        assert!(progress.elapsed() >= Duration::from_millis(5));
+
+        // this backend's status is read once per transaction, so it's all checked at once
+        let (reported_relid, elapsed_ms, param2, param3, param4) =
+            Spi::get_row::<(pg_sys::Oid, i64, i64, i64, i64)>(
+                "SELECT relid, param1, param2, param3, param4
+                   FROM pg_stat_get_progress_info('VACUUM')
+                  WHERE pid = pg_backend_pid()",
+            )?;
+        assert_eq!(reported_relid, Some(relid));
+        assert!(elapsed_ms.unwrap() >= 5);
+        assert_eq!((param2, param3, param4), (Some(42), Some(7), Some(-1)));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_progress_ended_on_drop() -> Result<(), spi::Error> {
+        drop(ProgressGuard::start(ProgressCommand::Vacuum, pg_sys::InvalidOid));
+        let reporting = Spi::get_one::<bool>(
+            "SELECT EXISTS (SELECT 1 FROM pg_stat_get_progress_info('VACUUM') WHERE pid = pg_backend_pid())",
+        )?;
+        assert_eq!(reporting, Some(false));
+        Ok(())
+    }
+
+    #[pg_test(error = "progress parameter 20 is out of range, there are only 20")]
+    #[cfg(not(feature = "pg11"))]
+    fn test_progress_param_out_of_range() {
+        let mut progress = ProgressGuard::start(ProgressCommand::Vacuum, pg_sys::InvalidOid);
+        progress.update(NUM_PROGRESS_PARAMS, 1);
+    }
+
+    #[pg_test(error = "canceling statement due to user request")]
+    fn test_progress_tick_honors_cancel() {
+        let mut progress = ProgressGuard::start(ProgressCommand::Vacuum, pg_sys::InvalidOid);
+        unsafe {
+            // what Postgres does when it receives a SIGINT
+            pg_sys::StatementCancelHandler(libc::SIGINT);
+        }
+        progress.tick();
+        unreachable!("tick() didn't notice the cancel");
+    }
+}
@@ -0,0 +1,36 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+
+    #[pg_extern]
+    fn snapshot_add_one(value: i32) -> i32 {
+        value + 1
+    }
+
+    #[test]
+    fn sql_schema_matches_snapshot() {
+        pgrx_tests::schema_snapshot!(
+            "snapshots/schema_snapshot_tests.sql",
+            module = "pgrx_tests::tests::schema_snapshot_tests"
+        );
+    }
+
+    #[pg_test]
+    fn test_snapshot_function() -> Result<(), spi::Error> {
+        assert_eq!(Spi::get_one::<i32>("SELECT tests.snapshot_add_one(41)")?, Some(42));
+        Ok(())
+    }
+}
@@ -39,6 +39,30 @@ mod test_schema {
     #[pgrx(sql = "CREATE TYPE test_schema.ManuallyRenderedType;")]
     pub struct OverriddenType(pub u64);
 
+    #[pgrx::pg_schema(nested)]
+    pub mod nested {
+        use pgrx::prelude::*;
+        use serde::{Deserialize, Serialize};
+
+        #[pg_extern]
+        fn func_in_nested_schema() -> i32 {
+            42
+        }
+
+        #[derive(Debug, PostgresType, Serialize, Deserialize)]
+        pub struct NestedType(pub u64);
+    }
+
+    #[pgrx::pg_schema]
+    pub mod flat {
+        use pgrx::prelude::*;
+
+        #[pg_extern]
+        fn func_in_flat_schema() -> i32 {
+            44
+        }
+    }
+
     fn generate_function(
         entity: &SqlGraphEntity,
         _context: &PgrxSql,
@@ -82,6 +106,16 @@ fn type_in_diff_schema() -> test_schema::TestType {
     test_schema::TestType(1)
 }
 
+#[pg_extern(schema = "test_schema.nested")]
+fn func_in_nested_schema2() -> i32 {
+    43
+}
+
+#[pg_extern]
+fn type_in_nested_schema() -> test_schema::nested::NestedType {
+    test_schema::nested::NestedType(2)
+}
+
 #[cfg(any(test, feature = "pg_test"))]
 #[pgrx::pg_schema]
 mod tests {
@@ -105,6 +139,28 @@ mod tests {
         Spi::run("SELECT type_in_diff_schema();").expect("SPI failed");
     }
 
+    #[pg_test]
+    fn test_in_nested_schema() {
+        let result = Spi::get_one::<i32>(r#"SELECT "test_schema.nested".func_in_nested_schema();"#);
+        assert_eq!(result, Ok(Some(42)));
+        let result =
+            Spi::get_one::<i32>(r#"SELECT "test_schema.nested".func_in_nested_schema2();"#);
+        assert_eq!(result, Ok(Some(43)));
+    }
+
+    #[pg_test]
+    fn test_in_flat_schema() {
+        let result = Spi::get_one::<i32>("SELECT flat.func_in_flat_schema();");
+        assert_eq!(result, Ok(Some(44)));
+    }
+
+    #[pg_test]
+    fn test_type_in_nested_schema() {
+        let result =
+            Spi::get_one::<String>("SELECT pg_typeof(type_in_nested_schema())::regtype::text;");
+        assert_eq!(result, Ok(Some(r#""test_schema.nested".nestedtype"#.to_string())));
+    }
+
     #[pg_test]
     fn elided_extern_is_elided() {
         // Validate that a function we know exists, exists
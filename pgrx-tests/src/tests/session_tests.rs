@@ -0,0 +1,64 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+    use pgrx::session;
+
+    #[pg_test]
+    fn test_backend_pid() -> Result<(), spi::Error> {
+        assert_eq!(Some(session::backend_pid()), Spi::get_one::<i32>("SELECT pg_backend_pid()")?);
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_database_name() -> Result<(), spi::Error> {
+        assert_eq!(
+            session::database_name(),
+            Spi::get_one::<String>("SELECT current_database()::text")?
+        );
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_user_name() -> Result<(), spi::Error> {
+        assert_eq!(
+            Some(session::user_name()),
+            Spi::get_one::<String>("SELECT current_user::text")?
+        );
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_application_name() -> Result<(), spi::Error> {
+        Spi::run("SET LOCAL application_name TO 'pgrx_session_test'")?;
+        assert_eq!(session::application_name().as_deref(), Some("pgrx_session_test"));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_client_addr() -> Result<(), spi::Error> {
+        let expected = Spi::get_one::<pgrx::Inet>("SELECT inet_client_addr()")?;
+        assert_eq!(session::client_addr(), expected);
+        assert_eq!(session::client_port(), Spi::get_one::<i32>("SELECT inet_client_port()")?);
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_current_query() -> Result<(), spi::Error> {
+        assert_eq!(session::current_query(), Spi::get_one::<String>("SELECT current_query()")?);
+        Ok(())
+    }
+}
@@ -7,18 +7,23 @@
 //LICENSE All rights reserved.
 //LICENSE
 //LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+use pgrx::bgworkers::BackgroundWorkerLeader;
 use pgrx::prelude::*;
 use pgrx::{pg_shmem_init, PgAtomic, PgLwLock, PgSharedMemoryInitialization};
 use std::sync::atomic::AtomicBool;
 
 static ATOMIC: PgAtomic<AtomicBool> = PgAtomic::new();
 static LWLOCK: PgLwLock<bool> = PgLwLock::new();
+pub(crate) static LEADER: PgLwLock<BackgroundWorkerLeader> = PgLwLock::new();
+pub(crate) static WORKER_LEADER: PgLwLock<BackgroundWorkerLeader> = PgLwLock::new();
 
 #[pg_guard]
 pub extern "C" fn _PG_init() {
     // This ensures that this functionality works across PostgreSQL versions
     pg_shmem_init!(ATOMIC);
     pg_shmem_init!(LWLOCK);
+    pg_shmem_init!(LEADER);
+    pg_shmem_init!(WORKER_LEADER);
 }
 #[cfg(any(test, feature = "pg_test"))]
 #[pgrx::pg_schema]
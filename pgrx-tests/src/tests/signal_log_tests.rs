@@ -0,0 +1,92 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+    use pgrx::signal_log::SignalSafeLog;
+
+    fn drain_all<const N: usize, const LEN: usize>(
+        log: &SignalSafeLog<N, LEN>,
+    ) -> (Vec<(PgLogLevel, String)>, usize) {
+        let mut messages = Vec::new();
+        let (drained, lost) =
+            log.drain(|level, message| messages.push((level, message.to_string())));
+        assert_eq!(drained, messages.len());
+        (messages, lost)
+    }
+
+    #[pg_test]
+    fn test_signal_log_flush() {
+        let log = SignalSafeLog::<4>::new();
+        log.log(PgLogLevel::LOG, "first");
+        log.log_fmt(PgLogLevel::NOTICE, format_args!("second: {}", 2));
+        assert_eq!(log.flush(), 2);
+        assert_eq!(log.flush(), 0);
+    }
+
+    #[pg_test]
+    fn test_signal_log_error_level_does_not_raise() {
+        let log = SignalSafeLog::<4>::new();
+        log.log(PgLogLevel::ERROR, "logged as a warning");
+        assert_eq!(log.flush(), 1);
+    }
+
+    #[pg_test]
+    fn test_signal_log_overflow() {
+        let log = SignalSafeLog::<4>::new();
+        for i in 0..10 {
+            log.log_fmt(PgLogLevel::LOG, format_args!("message {i}"));
+        }
+        let (messages, lost) = drain_all(&log);
+        assert_eq!(lost, 6);
+        let expected = (6..10).map(|i| format!("message {i}")).collect::<Vec<_>>();
+        assert_eq!(messages.into_iter().map(|(_, m)| m).collect::<Vec<_>>(), expected);
+    }
+
+    #[pg_test]
+    fn test_signal_log_truncation() {
+        let log = SignalSafeLog::<2, 8>::new();
+        log.log(PgLogLevel::LOG, "0123456789");
+        // 'é' is two bytes, and mustn't be split
+        log.log_fmt(PgLogLevel::LOG, format_args!("{}{}", "abcdefg", "é"));
+        let (messages, lost) = drain_all(&log);
+        assert_eq!(lost, 0);
+        assert_eq!(
+            messages,
+            vec![
+                (PgLogLevel::LOG, "01234567".to_string()),
+                (PgLogLevel::LOG, "abcdefg".to_string())
+            ]
+        );
+    }
+
+    static FROM_HANDLER: SignalSafeLog<8> = SignalSafeLog::new();
+
+    extern "C" fn log_signal(signal: libc::c_int) {
+        FROM_HANDLER.log_fmt(PgLogLevel::LOG, format_args!("caught signal {signal}"));
+    }
+
+    #[pg_test]
+    fn test_signal_log_from_signal_handler() {
+        let (messages, lost) = unsafe {
+            let previous = libc::signal(libc::SIGUSR2, log_signal as libc::sighandler_t);
+            libc::raise(libc::SIGUSR2);
+            libc::signal(libc::SIGUSR2, previous);
+            drain_all(&FROM_HANDLER)
+        };
+        assert_eq!(lost, 0);
+        assert_eq!(messages, vec![(PgLogLevel::LOG, format!("caught signal {}", libc::SIGUSR2))]);
+    }
+}
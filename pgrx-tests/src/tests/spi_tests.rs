@@ -119,6 +119,60 @@ mod tests {
         })
     }
 
+    #[pg_test]
+    fn test_spi_get_row() -> Result<(), spi::Error> {
+        let (i, s, b, n) =
+            Spi::get_row::<(i64, String, bool, i32)>("SELECT 42::bigint, 'test', true, NULL::int")?;
+        assert_eq!(Some(42), i);
+        assert_eq!(Some("test".to_string()), s);
+        assert_eq!(Some(true), b);
+        assert_eq!(None, n);
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_spi_get_row_twelve_columns() -> Result<(), spi::Error> {
+        let row = Spi::get_row::<(i32, i32, i32, i32, i32, i32, i32, i32, i32, i32, i32, i32)>(
+            "SELECT 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12",
+        )?;
+        assert_eq!(row.0, Some(1));
+        assert_eq!(row.11, Some(12));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_spi_get_row_with_args() -> Result<(), spi::Error> {
+        let (a, b) = Spi::get_row_with_args::<(i32, String)>(
+            "SELECT $1 + 1, $2 || '!'",
+            vec![
+                (PgBuiltInOids::INT4OID.oid(), 41.into_datum()),
+                (PgBuiltInOids::TEXTOID.oid(), "hi".into_datum()),
+            ],
+        )?;
+        assert_eq!(Some(42), a);
+        assert_eq!(Some("hi!".to_string()), b);
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_spi_get_row_column_count_mismatch() {
+        assert_eq!(
+            Spi::get_row::<(i32, i32)>("SELECT 1, 2, 3"),
+            Err(spi::Error::ColumnCountMismatch { expected: 2, got: 3 })
+        );
+        assert_eq!(
+            Spi::get_row::<(i32, i32)>("SELECT 1"),
+            Err(spi::Error::ColumnCountMismatch { expected: 2, got: 1 })
+        );
+    }
+
+    #[pg_test]
+    fn test_spi_get_row_type_mismatch() {
+        // the type of a NULL column is still checked
+        let result = Spi::get_row::<(i32, bool)>("SELECT 1, NULL::text");
+        assert!(matches!(result, Err(spi::Error::DatumError(_))));
+    }
+
     #[pg_test]
     fn test_spi_select_zero_rows() {
         assert!(Spi::get_one::<i32>("SELECT 1 LIMIT 0").is_err());
@@ -397,6 +451,77 @@ mod tests {
         Ok(())
     }
 
+    #[pg_test]
+    fn test_spi_prepare() -> Result<(), spi::Error> {
+        Spi::run("CREATE TABLE tests.prepared_table (id int, name text)")?;
+        let insert = Spi::prepare(
+            "INSERT INTO tests.prepared_table VALUES ($1, $2)",
+            Some(vec![
+                PgOid::BuiltIn(PgBuiltInOids::INT4OID),
+                PgOid::BuiltIn(PgBuiltInOids::TEXTOID),
+            ]),
+        )?;
+        insert.run((1, "one"))?;
+        insert.run((2, None::<&str>))?;
+
+        let lookup = Spi::prepare(
+            "SELECT id, name FROM tests.prepared_table WHERE id = $1",
+            Some(vec![PgOid::BuiltIn(PgBuiltInOids::INT4OID)]),
+        )?;
+        assert_eq!(lookup.get_one::<i32>((1,))?, Some(1));
+        assert_eq!(lookup.get_row::<(i32, String)>((1,))?, (Some(1), Some("one".into())));
+        assert_eq!(lookup.get_row::<(i32, String)>((2,))?, (Some(2), None));
+
+        let count = Spi::prepare("SELECT count(*) FROM tests.prepared_table", None)?;
+        assert_eq!(count.get_one::<i64>(())?, Some(2));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_spi_prepare_typed_argument_mismatch() -> Result<(), spi::Error> {
+        let stmt = Spi::prepare("SELECT $1", Some(vec![PgOid::BuiltIn(PgBuiltInOids::INT4OID)]))?;
+        assert!(matches!(
+            stmt.get_one::<i32>(()),
+            Err(spi::Error::PreparedStatementArgumentMismatch { expected: 1, got: 0 })
+        ));
+        assert!(matches!(
+            stmt.get_one::<i32>(("42",)),
+            Err(spi::Error::DatumError(pgrx::TryFromDatumError::IncompatibleTypes { .. }))
+        ));
+        Ok(())
+    }
+
+    thread_local! {
+        static CACHED_STATEMENT: once_cell::unsync::OnceCell<spi::OwnedPreparedStatement> =
+            once_cell::unsync::OnceCell::new();
+    }
+
+    #[pg_extern]
+    fn spi_cached_statement(value: i64) -> Result<Option<i64>, spi::Error> {
+        CACHED_STATEMENT.with(|cached| {
+            cached
+                .get_or_try_init(|| {
+                    Spi::prepare(
+                        "SELECT $1 * 2",
+                        Some(vec![PgOid::BuiltIn(PgBuiltInOids::INT8OID)]),
+                    )
+                })?
+                .get_one((value,))
+        })
+    }
+
+    #[pg_test]
+    fn test_spi_cached_statement() -> Result<(), spi::Error> {
+        assert_eq!(
+            Spi::get_one::<i64>(
+                "SELECT sum(tests.spi_cached_statement(x)) FROM generate_series(1, 10) x"
+            )?,
+            Some(110)
+        );
+        assert_eq!(Spi::get_one::<i64>("SELECT tests.spi_cached_statement(21)")?, Some(42));
+        Ok(())
+    }
+
     #[pg_test]
     fn test_option() {
         assert!(Spi::get_one::<i32>("SELECT NULL::integer").unwrap().is_none());
@@ -154,6 +154,27 @@ fn one_col_result_option<'a>(
     Ok(Some(TableIterator::new(std::iter::once((42,)))))
 }
 
+#[pg_extern]
+fn cursor_table(
+    batch_size: i64,
+) -> TableIterator<'static, (name!(n, Option<i32>), name!(word, Option<String>))> {
+    Spi::connect(|client| {
+        let cursor = client.open_cursor(
+            "SELECT n, 'word ' || n FROM generate_series(1, 10) AS n ORDER BY n",
+            None,
+        );
+        TableIterator::from_cursor(cursor, batch_size, |row| Ok((row.get(1)?, row.get(2)?)))
+    })
+}
+
+#[pg_extern]
+fn cursor_setof(batch_size: i64) -> SetOfIterator<'static, i32> {
+    Spi::connect(|client| {
+        let cursor = client.open_cursor("SELECT * FROM generate_series(1, 10)", None);
+        SetOfIterator::from_cursor(cursor, batch_size, |row| Ok(row.get(1)?.unwrap_or_default()))
+    })
+}
+
 #[cfg(any(test, feature = "pg_test"))]
 #[pgrx::pg_schema]
 mod tests {
@@ -345,4 +366,35 @@ mod tests {
         assert_eq!(Spi::get_one::<i32>("SELECT * from one_col_result()"), Ok(Some(42)));
         assert_eq!(Spi::get_one::<i32>("SELECT * from one_col_result_option()"), Ok(Some(42)));
     }
+
+    #[pg_test]
+    fn test_cursor_table() -> Result<(), spi::Error> {
+        // batch sizes that don't divide, exactly divide, and exceed the number of rows
+        for batch_size in [3, 5, 100] {
+            let rows = Spi::connect(|client| {
+                client
+                    .select(&format!("SELECT * FROM cursor_table({batch_size})"), None, None)?
+                    .map(|row| Ok((row.get::<i32>(1)?, row.get::<String>(2)?)))
+                    .collect::<Result<Vec<_>, spi::Error>>()
+            })?;
+            let expected =
+                (1..=10).map(|n| (Some(n), Some(format!("word {n}")))).collect::<Vec<_>>();
+            assert_eq!(rows, expected);
+        }
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_cursor_setof() {
+        assert_eq!(Spi::get_one::<i64>("SELECT sum(x) FROM cursor_setof(4) x"), Ok(Some(55)));
+        assert_eq!(
+            Spi::get_one::<i64>("SELECT count(*) FROM (SELECT cursor_setof(2) LIMIT 3) x"),
+            Ok(Some(3))
+        );
+    }
+
+    #[pg_test(error = "batch_size must be positive")]
+    fn test_cursor_zero_batch_size() {
+        Spi::run("SELECT * FROM cursor_setof(0)").unwrap();
+    }
 }
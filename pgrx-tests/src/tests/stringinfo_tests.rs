@@ -0,0 +1,66 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+    use pgrx::TextBuilder;
+    use std::fmt::Write;
+
+    #[pg_extern]
+    fn text_builder_squares(n: i32) -> TextBuilder {
+        let mut text = TextBuilder::new();
+        for i in 1..=n {
+            writeln!(text, "{i}: {}", i * i).unwrap();
+        }
+        text
+    }
+
+    #[pg_test]
+    fn test_text_builder_returned() -> Result<(), spi::Error> {
+        let text = Spi::get_one::<String>("SELECT tests.text_builder_squares(3)")?;
+        assert_eq!(text.as_deref(), Some("1: 1\n2: 4\n3: 9\n"));
+
+        let text = Spi::get_one::<String>("SELECT tests.text_builder_squares(0)")?;
+        assert_eq!(text.as_deref(), Some(""));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_text_builder_large() -> Result<(), spi::Error> {
+        let (lines, len) = Spi::get_two::<i64, i32>(
+            "SELECT count(*), length(t) FROM tests.text_builder_squares(100000) t, \
+                    regexp_split_to_table(rtrim(t, E'\\n'), E'\\n') GROUP BY t",
+        )?;
+        assert_eq!(lines, Some(100000));
+        let expected = (1..=100000i64).map(|i| format!("{i}: {}\n", i * i).len()).sum::<usize>();
+        assert_eq!(len, Some(expected as i32));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_text_builder_writes() {
+        let mut text = TextBuilder::with_capacity(64);
+        assert!(text.is_empty());
+        text.push_str("hello");
+        write!(text, ", {}", "world").unwrap();
+        std::io::Write::write_all(&mut text, b"!").unwrap();
+        assert_eq!(text.len(), 13);
+        assert_eq!(text.as_str(), Ok("hello, world!"));
+
+        let datum = text.into_text_datum();
+        let round_trip = unsafe { String::from_datum(datum, false) };
+        assert_eq!(round_trip.as_deref(), Some("hello, world!"));
+    }
+}
@@ -0,0 +1,87 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+    use pgrx::systable::{ScanKeys, ScanStrategy, SysTableScan};
+    use pgrx::PgRelation;
+
+    fn pg_class() -> PgRelation {
+        unsafe { PgRelation::with_lock(pg_sys::RelationRelationId, pg_sys::AccessShareLock as _) }
+    }
+
+    fn relname(tuple: PgHeapTuple<'_, AllocatedByRust>) -> String {
+        unsafe {
+            let form = &*(pg_sys::GETSTRUCT(tuple.into_pg()) as pg_sys::Form_pg_class);
+            pgrx::name_to_string(&form.relname)
+        }
+    }
+
+    fn class_name_index() -> Option<pg_sys::Oid> {
+        Some(unsafe { pg_sys::Oid::from_u32_unchecked(pg_sys::ClassNameNspIndexId) })
+    }
+
+    #[pg_test]
+    fn test_systable_scan_by_name() {
+        let pg_class = pg_class();
+        let catalog = unsafe { pg_sys::Oid::from_u32_unchecked(pg_sys::PG_CATALOG_NAMESPACE) };
+        let keys = ScanKeys::new()
+            .name(pg_sys::Anum_pg_class_relname, ScanStrategy::Equal, "pg_class")
+            .eq(pg_sys::Anum_pg_class_relnamespace, catalog);
+
+        let names =
+            SysTableScan::new(&pg_class, class_name_index(), keys).map(relname).collect::<Vec<_>>();
+        assert_eq!(names, vec!["pg_class".to_string()]);
+    }
+
+    #[pg_test]
+    fn test_systable_scan_is_ordered() -> Result<(), spi::Error> {
+        let pg_class = pg_class();
+        let keys = ScanKeys::new()
+            .name(pg_sys::Anum_pg_class_relname, ScanStrategy::GreaterEqual, "pg_stat")
+            .name(pg_sys::Anum_pg_class_relname, ScanStrategy::Less, "pg_stau");
+
+        let names =
+            SysTableScan::new(&pg_class, class_name_index(), keys).map(relname).collect::<Vec<_>>();
+        let expected = Spi::get_one::<Vec<String>>(
+            "SELECT array_agg(relname::text ORDER BY relname COLLATE \"C\", relnamespace) \
+               FROM pg_class WHERE relname >= 'pg_stat' AND relname < 'pg_stau'",
+        )?;
+        assert!(names.len() > 1);
+        assert_eq!(Some(names), expected);
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_systable_heap_scan() -> Result<(), spi::Error> {
+        Spi::run(
+            "CREATE TABLE systable_scan_test AS SELECT g AS id FROM generate_series(1, 10) g",
+        )?;
+        let table = PgRelation::open_with_name_and_share_lock("systable_scan_test").unwrap();
+
+        let keys =
+            ScanKeys::new().key(1, ScanStrategy::Greater, 3).key(1, ScanStrategy::LessEqual, 7);
+        let mut ids = SysTableScan::new(&table, None, keys)
+            .map(|tuple| tuple.get_by_index::<i32>(1.try_into().unwrap()).unwrap().unwrap())
+            .collect::<Vec<_>>();
+        ids.sort();
+        assert_eq!(ids, vec![4, 5, 6, 7]);
+        Ok(())
+    }
+
+    #[pg_test(error = "type `point` has no btree Equal operator")]
+    fn test_systable_key_without_btree_opclass() {
+        ScanKeys::new().eq(1, pgrx::pg_sys::Point { x: 1.0, y: 2.0 });
+    }
+}
@@ -0,0 +1,73 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+    use pgrx::table_builder::*;
+
+    #[pg_test]
+    fn test_create_temp_table() -> Result<(), spi::Error> {
+        let oid = TableBuilder::temporary("tb_temp")
+            .column("id", "int")
+            .column("Mixed Case", "text")
+            .on_commit(OnCommit::Drop)
+            .create()?;
+
+        let (persistence, namespace) = Spi::get_two_with_args::<String, pg_sys::Oid>(
+            "SELECT relpersistence::text, relnamespace FROM pg_class WHERE oid = $1",
+            vec![(PgBuiltInOids::OIDOID.oid(), oid.into_datum())],
+        )?;
+        assert_eq!(persistence, Some(RelPersistence::Temporary.as_char().to_string()));
+        assert_eq!(namespace, my_temp_namespace());
+        assert!(is_temp_namespace(namespace.unwrap()));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_create_unlogged_table() -> Result<(), spi::Error> {
+        let oid =
+            TableBuilder::unlogged("tb_unlogged").schema("tests").column("id", "int").create()?;
+
+        let persistence = Spi::get_one_with_args::<String>(
+            "SELECT relpersistence::text FROM pg_class WHERE oid = $1",
+            vec![(PgBuiltInOids::OIDOID.oid(), oid.into_datum())],
+        )?;
+        assert_eq!(persistence, Some(RelPersistence::Unlogged.as_char().to_string()));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_to_sql() {
+        let sql = TableBuilder::temporary("staging")
+            .if_not_exists()
+            .column("id", "bigint")
+            .on_commit(OnCommit::DeleteRows)
+            .to_sql();
+        assert_eq!(
+            sql,
+            "CREATE TEMPORARY TABLE IF NOT EXISTS pg_temp.staging (id bigint) ON COMMIT DELETE ROWS"
+        );
+    }
+
+    #[cfg(feature = "cshim")]
+    #[pg_test]
+    fn test_creation_namespace_is_temp() -> Result<(), spi::Error> {
+        TableBuilder::temporary("tb_ensure_temp_schema").column("id", "int").create()?;
+        assert!(!creation_namespace_is_temp());
+        Spi::run("SET LOCAL search_path TO pg_temp, public")?;
+        assert!(creation_namespace_is_temp());
+        Ok(())
+    }
+}
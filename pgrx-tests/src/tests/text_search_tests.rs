@@ -0,0 +1,98 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+    use pgrx::{TextSearchError, TsPosition, TsQuery, TsQueryItem, TsVector, TsWeight};
+
+    #[pg_extern]
+    fn take_and_return_tsvector(vector: TsVector) -> TsVector {
+        vector
+    }
+
+    #[pg_extern]
+    fn tsvector_matches(vector: TsVector, query: TsQuery) -> bool {
+        vector.matches(&query)
+    }
+
+    #[pg_test]
+    fn test_tsvector_lexemes() -> Result<(), pgrx::spi::Error> {
+        let vector =
+            Spi::get_one::<TsVector>("SELECT 'a fat cat sat on a mat'::tsvector")?.unwrap();
+        let lexemes = vector.iter().map(|entry| entry.lexeme.as_str()).collect::<Vec<_>>();
+        assert_eq!(lexemes, ["a", "cat", "fat", "mat", "on", "sat"]);
+        assert!(vector.iter().all(|entry| entry.positions.is_empty()));
+
+        let vector = Spi::get_one::<TsVector>("SELECT 'fat:2,4B cat:3A'::tsvector")?.unwrap();
+        assert_eq!(
+            vector.get("fat").unwrap().positions,
+            [TsPosition::new(2, TsWeight::D), TsPosition::new(4, TsWeight::B)]
+        );
+        assert_eq!(vector.get("cat").unwrap().positions, [TsPosition::new(3, TsWeight::A)]);
+        assert_eq!(vector.to_string(), "'cat':3A 'fat':2,4B");
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_tsvector_from_lexemes() -> Result<(), Box<dyn std::error::Error>> {
+        let vector = TsVector::from_lexemes([
+            ("rat", vec![TsPosition::from(5)]),
+            ("it's", vec![TsPosition::new(1, TsWeight::A)]),
+            ("rat", vec![TsPosition::from(2)]),
+        ])?;
+        assert_eq!(vector.len(), 2);
+        assert_eq!(vector.get("rat").unwrap().positions, [2.into(), 5.into()]);
+
+        let same = Spi::get_one_with_args::<bool>(
+            "SELECT tests.take_and_return_tsvector($1) = $$'it''s':1A 'rat':2,5$$::tsvector",
+            vec![(PgBuiltInOids::TSVECTOROID.oid(), vector.into_datum())],
+        )?;
+        assert_eq!(same, Some(true));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_tsquery_items() -> Result<(), pgrx::spi::Error> {
+        let query = Spi::get_one::<TsQuery>("SELECT '!fat & (rat:*AB <2> cat)'::tsquery")?.unwrap();
+        assert_eq!(query.as_str(), "!'fat' & 'rat':*AB <2> 'cat'");
+        assert_eq!(query.lexemes().collect::<Vec<_>>(), ["cat", "rat", "fat"]);
+        assert!(query.items().contains(&TsQueryItem::Phrase { distance: 2 }));
+        assert!(query.items().contains(&TsQueryItem::Lexeme {
+            lexeme: "rat".into(),
+            weights: vec![TsWeight::A, TsWeight::B],
+            prefix: true,
+        }));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_tsquery_matches() -> Result<(), Box<dyn std::error::Error>> {
+        let vector = TsVector::new("a fat cat sat on a mat")?;
+        assert!(vector.matches(&TsQuery::new("cat & mat")?));
+        assert!(!vector.matches(&TsQuery::new("cat & !mat")?));
+        assert!(TsQuery::new("ca:*")?.matches(&vector));
+
+        let matches = Spi::get_one::<bool>(
+            "SELECT tests.tsvector_matches('fat:2 rat:3'::tsvector, 'fat <-> rat'::tsquery)",
+        )?;
+        assert_eq!(matches, Some(true));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_invalid_text_search() {
+        assert!(matches!(TsQuery::new("fat & & rat"), Err(TextSearchError::TsQuery(_))));
+        assert!(matches!(TsVector::new("'unterminated"), Err(TextSearchError::TsVector(_))));
+    }
+}
@@ -0,0 +1,108 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    #[cfg(feature = "rayon")]
+    use once_cell::sync::Lazy;
+    use pgrx::prelude::*;
+    #[cfg(feature = "rayon")]
+    use pgrx::thread::ComputePool;
+    use pgrx::thread::{scoped_worker, Cancelled};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[cfg(feature = "rayon")]
+    static POOL: Lazy<ComputePool> = Lazy::new(|| ComputePool::new(4).expect("no threads"));
+
+    #[pg_test]
+    fn test_scoped_worker_joins() {
+        let data = (1..=1000u64).collect::<Vec<_>>();
+        let sum: u64 = scoped_worker(|scope| {
+            let handles = data
+                .chunks(100)
+                .map(|chunk| scope.spawn(move |_| chunk.iter().sum::<u64>()))
+                .collect::<Vec<_>>();
+            handles.into_iter().map(|handle| handle.join()).sum()
+        });
+        assert_eq!(sum, 500500);
+    }
+
+    #[pg_test]
+    fn test_scoped_worker_waits_for_unjoined() {
+        let finished = AtomicUsize::new(0);
+        scoped_worker(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|_| {
+                    std::thread::sleep(std::time::Duration::from_millis(50));
+                    finished.fetch_add(1, Ordering::Relaxed);
+                });
+            }
+        });
+        assert_eq!(finished.load(Ordering::Relaxed), 4);
+    }
+
+    #[pg_test(error = "canceling statement due to user request")]
+    fn test_scoped_worker_cancelled() {
+        scoped_worker(|scope| {
+            let handle = scope.spawn(|token| -> Result<(), Cancelled> {
+                loop {
+                    token.check()?;
+                    std::thread::sleep(std::time::Duration::from_millis(1));
+                }
+            });
+            unsafe { libc::raise(libc::SIGINT) };
+            assert_eq!(handle.join(), Err(Cancelled));
+        });
+        unreachable!("the cancel should have been raised")
+    }
+
+    #[pg_test(error = "worker panicked")]
+    fn test_scoped_worker_panic() {
+        scoped_worker(|scope| {
+            scope.spawn(|_| panic!("worker panicked")).join();
+        });
+    }
+
+    #[cfg(feature = "rayon")]
+    #[pg_test]
+    fn test_compute_pool_map() -> Result<(), Cancelled> {
+        let doubled = POOL.run(|ctx| ctx.map(0..1000u64, |n| n * 2))?;
+        assert_eq!(doubled.len(), 1000);
+        assert_eq!(doubled.iter().sum::<u64>(), 999000);
+
+        let (a, b) = POOL.run(|ctx| ctx.join(|| 1, || 2))?;
+        assert_eq!((a, b), (1, 2));
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon")]
+    #[pg_test]
+    fn test_compute_pool_is_bounded() {
+        assert_eq!(POOL.num_threads(), 4);
+        let threads = POOL.run(|ctx| {
+            ctx.map(0..100, |_| std::thread::current().name().map(str::to_string)).unwrap()
+        });
+        assert!(threads.iter().all(|name| name.as_deref().unwrap().starts_with("pgrx-compute-")));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[pg_test(error = "canceling statement due to user request")]
+    fn test_compute_pool_cancelled() {
+        unsafe { libc::raise(libc::SIGINT) };
+        let result = POOL.run(|ctx| {
+            ctx.for_each(0..u64::MAX, |_| std::thread::sleep(std::time::Duration::from_millis(1)))
+        });
+        assert_eq!(result, Err(Cancelled));
+    }
+}
@@ -0,0 +1,73 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+    use pgrx::Tid;
+
+    #[pg_extern]
+    fn tid_parts(tid: Tid) -> TableIterator<'static, (name!(block, i64), name!(offset, i32))> {
+        let (block, offset) = tid.parts();
+        TableIterator::once((block as i64, offset as i32))
+    }
+
+    #[pg_extern]
+    fn tid_next(tid: Tid) -> Tid {
+        Tid::new(tid.block(), tid.offset() + 1)
+    }
+
+    #[pg_test]
+    fn test_tid_argument() -> Result<(), spi::Error> {
+        let parts = Spi::get_two::<i64, i32>("SELECT * FROM tests.tid_parts('(4294967295,7)')")?;
+        assert_eq!(parts, (Some(u32::MAX as i64), Some(7)));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_tid_return() -> Result<(), spi::Error> {
+        let tid = Spi::get_one::<String>("SELECT tests.tid_next('(12,3)')::text")?;
+        assert_eq!(tid.as_deref(), Some("(12,4)"));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_ctid_roundtrip() -> Result<(), spi::Error> {
+        Spi::run("CREATE TABLE tid_test AS SELECT generate_series(1, 3) AS id")?;
+        let tid = Spi::get_one::<Tid>("SELECT ctid FROM tid_test WHERE id = 2")?.unwrap();
+        assert_eq!(tid.parts(), (0, 2));
+        assert!(tid.is_valid());
+
+        let id = Spi::get_one_with_args::<i32>(
+            "SELECT id FROM tid_test WHERE ctid = $1",
+            vec![(PgBuiltInOids::TIDOID.oid(), tid.into_datum())],
+        )?;
+        assert_eq!(id, Some(2));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_tid_text_and_order() {
+        let tid = Tid::new(3, 7);
+        assert_eq!(tid.to_string(), "(3,7)");
+        assert_eq!(" ( 3 , 7 ) ".parse::<Tid>(), Ok(tid));
+        assert!("(3,)".parse::<Tid>().is_err());
+        assert!("3,7".parse::<Tid>().is_err());
+        assert!(Tid::new(0, 9) < Tid::new(1, 1));
+        assert!(!Tid::new(1, 0).is_valid());
+
+        let item_pointer = pg_sys::ItemPointerData::from(tid);
+        assert_eq!(Tid::from(item_pointer), tid);
+    }
+}
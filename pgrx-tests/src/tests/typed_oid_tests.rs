@@ -0,0 +1,86 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+    use pgrx::{NamespaceOid, ProcOid, RelationOid, TypeOid};
+
+    #[pg_extern]
+    fn relation_name(rel: RelationOid) -> String {
+        unsafe { PgRelation::with_lock(rel, pg_sys::AccessShareLock as _) }.name().to_string()
+    }
+
+    #[pg_extern]
+    fn typed_oid_roundtrip_type(typ: TypeOid) -> TypeOid {
+        typ
+    }
+
+    #[pg_extern]
+    fn typed_oid_roundtrip_proc(proc: ProcOid) -> ProcOid {
+        proc
+    }
+
+    #[pg_extern]
+    fn typed_oid_roundtrip_namespace(nsp: NamespaceOid) -> NamespaceOid {
+        nsp
+    }
+
+    #[pg_test]
+    fn test_relation_oid_argument() -> Result<(), spi::Error> {
+        Spi::run("CREATE TABLE typed_oid_table (id int)")?;
+        let name = Spi::get_one::<String>("SELECT tests.relation_name('typed_oid_table')")?;
+        assert_eq!(name.as_deref(), Some("typed_oid_table"));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_typed_oid_sql_types() -> Result<(), spi::Error> {
+        let typ = Spi::get_one::<String>("SELECT tests.typed_oid_roundtrip_type('int4')::text")?;
+        assert_eq!(typ.as_deref(), Some("integer"));
+        let proc = Spi::get_one::<String>("SELECT tests.typed_oid_roundtrip_proc('now()')::text")?;
+        assert_eq!(proc.as_deref(), Some("now()"));
+        let nsp = Spi::get_one::<String>(
+            "SELECT tests.typed_oid_roundtrip_namespace('pg_catalog')::text",
+        )?;
+        assert_eq!(nsp.as_deref(), Some("pg_catalog"));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_typed_oid_from_datum() -> Result<(), spi::Error> {
+        let rel = Spi::get_one::<RelationOid>("SELECT 'pg_class'::regclass")?;
+        assert_eq!(rel, Some(RelationOid::from_raw_unchecked(pg_sys::RelationRelationId)));
+        // a plain oid can be read as a typed one too
+        let typ = Spi::get_one::<TypeOid>("SELECT 'int4'::regtype::oid")?;
+        assert_eq!(typ, Some(TypeOid::from_raw_unchecked(pg_sys::INT4OID)));
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_relation_typed_oids() {
+        let rel = unsafe {
+            PgRelation::with_lock(
+                RelationOid::from_raw_unchecked(pg_sys::RelationRelationId),
+                pg_sys::AccessShareLock as _,
+            )
+        };
+        assert_eq!(rel.relation_oid(), pg_sys::RelationRelationId);
+        let nsp = NamespaceOid::from_raw_unchecked(rel.namespace_oid());
+        assert_eq!(nsp.as_oid().as_u32(), pg_sys::PG_CATALOG_NAMESPACE);
+        assert!(nsp.is_valid());
+        assert!(!NamespaceOid::INVALID.is_valid());
+        assert_eq!(pg_sys::Oid::from(rel.relation_oid()), rel.oid());
+    }
+}
@@ -29,6 +29,16 @@ fn display_uuid(uuid: Uuid) -> String {
     format!("{}", uuid)
 }
 
+#[pg_extern]
+fn accept_uuid_crate(uuid: uuid::Uuid) -> uuid::Uuid {
+    uuid
+}
+
+#[pg_extern]
+fn return_uuid_crate() -> uuid::Uuid {
+    uuid::Uuid::from_bytes(TEST_UUID_V4)
+}
+
 #[cfg(any(test, feature = "pg_test"))]
 #[pg_schema]
 mod tests {
@@ -73,4 +83,31 @@ mod tests {
         let uuid = Spi::get_one::<Uuid>("SELECT '123e4567-e89b-12d3-a456-426614174000'::uuid;");
         assert_eq!(uuid, Ok(Some(Uuid::from_bytes(super::TEST_UUID_V4))));
     }
+
+    #[pg_test]
+    fn test_accept_uuid_crate() {
+        let result = Spi::get_one::<bool>("SELECT accept_uuid_crate('123e4567-e89b-12d3-a456-426614174000'::uuid) = '123e4567-e89b-12d3-a456-426614174000'::uuid;");
+        assert_eq!(result, Ok(Some(true)));
+    }
+
+    #[pg_test]
+    fn test_return_uuid_crate() {
+        let result = Spi::get_one::<String>("SELECT return_uuid_crate()::text;");
+        assert_eq!(result, Ok(Some("123e4567-e89b-12d3-a456-426614174000".to_string())));
+    }
+
+    #[pg_test]
+    fn test_parse_uuid_crate() {
+        let uuid =
+            Spi::get_one::<uuid::Uuid>("SELECT '123e4567-e89b-12d3-a456-426614174000'::uuid;");
+        assert_eq!(uuid, Ok(Some(uuid::Uuid::from_bytes(super::TEST_UUID_V4))));
+    }
+
+    #[pg_test]
+    fn test_uuid_crate_conversions() {
+        let ours = Uuid::from_bytes(super::TEST_UUID_V4);
+        let theirs = uuid::Uuid::from(ours);
+        assert_eq!(theirs.to_string(), ours.to_string());
+        assert_eq!(Uuid::from(theirs), ours);
+    }
 }
@@ -0,0 +1,91 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+#[cfg(any(test, feature = "pg_test"))]
+#[pgrx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgrx_tests;
+
+    use pgrx::prelude::*;
+    use pgrx::{spi, VarBit};
+
+    #[pg_extern]
+    fn varbit_flip(mut bits: VarBit) -> VarBit {
+        for i in 0..bits.len() {
+            bits.set(i, !bits.get(i).unwrap());
+        }
+        bits
+    }
+
+    #[pg_test]
+    fn test_varbit_from_datum() -> Result<(), pgrx::spi::Error> {
+        let bits = Spi::get_one::<VarBit>("SELECT B'1011000001'::varbit")?.unwrap();
+        assert_eq!(bits.len(), 10);
+        assert_eq!(bits.as_bytes(), [0b1011_0000, 0b0100_0000]);
+        assert_eq!(bits.get(0), Some(true));
+        assert_eq!(bits.get(1), Some(false));
+        assert_eq!(bits.get(10), None);
+        assert_eq!(bits.count_ones(), 4);
+        assert_eq!(bits.to_string(), "1011000001");
+
+        let fixed = Spi::get_one::<VarBit>("SELECT B'101'::bit(5)::varbit")?.unwrap();
+        assert_eq!(fixed.to_string(), "10100");
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_varbit_from_bit_datum() {
+        // `bit(n)` has a fixed length, so it must be cast to `varbit` explicitly
+        let result = Spi::get_one::<VarBit>("SELECT B'101'::bit(3)");
+        assert!(matches!(
+            result,
+            Err(spi::Error::DatumError(pgrx::TryFromDatumError::IncompatibleTypes { .. }))
+        ));
+    }
+
+    #[pg_test]
+    fn test_varbit_into_datum() -> Result<(), pgrx::spi::Error> {
+        let flipped = Spi::get_one::<String>("SELECT tests.varbit_flip(B'1100101')::text")?;
+        assert_eq!(flipped.as_deref(), Some("0011010"));
+
+        let bits = [true, false, true].into_iter().collect::<VarBit>();
+        let same = Spi::get_one_with_args::<bool>(
+            "SELECT $1 = B'101' AND length($1) = 3",
+            vec![(PgBuiltInOids::VARBITOID.oid(), bits.into_datum())],
+        )?;
+        assert_eq!(same, Some(true));
+
+        Spi::run("CREATE TABLE varbit_column (b bit(4))")?;
+        Spi::run_with_args(
+            "INSERT INTO varbit_column VALUES ($1::bit(4))",
+            Some(vec![(
+                PgBuiltInOids::VARBITOID.oid(),
+                "11".parse::<VarBit>().unwrap().into_datum(),
+            )]),
+        )?;
+        let stored = Spi::get_one::<VarBit>("SELECT b::varbit FROM varbit_column")?.unwrap();
+        assert_eq!(stored.to_string(), "1100");
+        Ok(())
+    }
+
+    #[pg_test]
+    fn test_varbit_resize() {
+        let mut bits = "10111".parse::<VarBit>().unwrap();
+        bits.resize(3);
+        assert_eq!(bits.to_string(), "101");
+        assert_eq!(bits.as_bytes(), [0b1010_0000]);
+        bits.resize(10);
+        assert_eq!(bits.to_string(), "1010000000");
+        bits.push(true);
+        assert_eq!(bits.len(), 11);
+        assert_eq!(VarBit::from_bytes(&[0xff, 0xff], 9).as_bytes(), [0xff, 0x80]);
+        assert!("012".parse::<VarBit>().is_err());
+    }
+}
@@ -14,7 +14,10 @@ mod tests {
     use crate as pgrx_tests;
 
     use pgrx::prelude::*;
-    use pgrx::{info, register_xact_callback, PgXactCallbackEvent};
+    use pgrx::{
+        info, register_exit_callback, register_xact_callback, PgExitCallbackEvent,
+        PgXactCallbackEvent,
+    };
 
     #[test]
     fn make_idea_happy() {}
@@ -23,4 +26,48 @@ mod tests {
     fn test_xact_callback() {
         register_xact_callback(PgXactCallbackEvent::Abort, || info!("TESTMSG: Called on abort"));
     }
+
+    /// Where `register_exit_markers()` records each exit callback as it runs
+    fn exit_marker_path() -> std::path::PathBuf {
+        std::env::temp_dir().join("pgrx-tests-exit-callbacks")
+    }
+
+    #[pg_extern]
+    fn register_exit_markers() {
+        fn mark(event: &str, code: i32) {
+            use std::io::Write;
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(exit_marker_path())
+                .expect("couldn't open the exit marker file");
+            writeln!(file, "{event} {code}").expect("couldn't write to the exit marker file");
+        }
+
+        register_exit_callback(PgExitCallbackEvent::OnProcExit, |code| mark("on_proc_exit", code));
+        register_exit_callback(PgExitCallbackEvent::BeforeShmemExit, |code| {
+            mark("before_shmem_exit", code)
+        });
+    }
+
+    #[test]
+    fn test_exit_callback() -> eyre::Result<()> {
+        let _ = std::fs::remove_file(exit_marker_path());
+        // the backend exits, and so runs the callbacks, once the test's connection is closed
+        pgrx_tests::run_test("register_exit_markers", None, vec![])?;
+
+        let start = std::time::Instant::now();
+        let markers = loop {
+            match std::fs::read_to_string(exit_marker_path()) {
+                Ok(markers) if markers.lines().count() == 2 => break markers,
+                _ if start.elapsed() > std::time::Duration::from_secs(10) => {
+                    panic!("the exit callbacks didn't run")
+                }
+                _ => std::thread::sleep(std::time::Duration::from_millis(100)),
+            }
+        };
+        assert_eq!(markers, "before_shmem_exit 0\non_proc_exit 0\n");
+        std::fs::remove_file(exit_marker_path())?;
+        Ok(())
+    }
 }
@@ -267,6 +267,53 @@ CREATE AGGREGATE DemoSum (
 );
 ```
 
+## Parallel Aggregation with `Internal` State
+
+Postgres can only run an aggregate whose state is [`Internal`](crate::datum::Internal) in parallel
+if it can serialize the state to send it between workers.  Derive [`AggregateSerial`] for the
+Rust type kept in the `Internal`, and name it in `#[pg_aggregate(serial = ...)]`: the `SERIALFUNC`
+and `DESERIALFUNC` are then generated, encoding the state as CBOR in a `bytea`.
+
+```rust
+# use pgrx::prelude::*;
+# use pgrx::{AggregateSerial, Internal, ParallelOption};
+# use serde::{Serialize, Deserialize};
+#
+#[derive(Default, Serialize, Deserialize, AggregateSerial)]
+pub struct SumState {
+    sum: i64,
+}
+
+pub struct ParallelSum;
+
+#[pg_aggregate(serial = SumState)]
+impl Aggregate for ParallelSum {
+    const PARALLEL: Option<ParallelOption> = Some(ParallelOption::Safe);
+    type Args = i32;
+    type State = Internal;
+    type Finalize = i64;
+
+    fn state(mut current: Self::State, arg: Self::Args, _: pg_sys::FunctionCallInfo) -> Self::State {
+        unsafe { current.get_or_insert_default::<SumState>() }.sum += arg as i64;
+        current
+    }
+
+    fn combine(mut a: Self::State, mut b: Self::State, _: pg_sys::FunctionCallInfo) -> Self::State {
+        let b = unsafe { b.get_or_insert_default::<SumState>() }.sum;
+        unsafe { a.get_or_insert_default::<SumState>() }.sum += b;
+        a
+    }
+
+    fn finalize(
+        mut current: Self::State,
+        _: Self::OrderedSetArgs,
+        _: pg_sys::FunctionCallInfo,
+    ) -> Self::Finalize {
+        unsafe { current.get_or_insert_default::<SumState>() }.sum
+    }
+}
+```
+
 */
 
 use crate::error;
@@ -451,3 +498,27 @@ where
         }
     }
 }
+
+/// A type which can be kept in an aggregate's [`Internal`](crate::datum::Internal) state and
+/// serialized to pass it between parallel workers.
+///
+/// Usually derived with [`#[derive(AggregateSerial)]`](pgrx_macros::AggregateSerial), which
+/// encodes the value as CBOR with `serde`, and used by naming the type in
+/// `#[pg_aggregate(serial = ...)]`.  See the [module documentation](self) for an example.
+pub trait AggregateSerial: Sized {
+    /// Encode the state as the bytes of the `bytea` returned by the aggregate's `SERIALFUNC`
+    fn serialize_state(&self) -> Vec<u8>;
+
+    /// Decode a state from the bytes given to the aggregate's `DESERIALFUNC`
+    fn deserialize_state(bytes: &[u8]) -> Self;
+}
+
+#[doc(hidden)]
+pub fn serialize_state_cbor<T: serde::Serialize>(state: &T) -> Vec<u8> {
+    serde_cbor::to_vec(state).expect("failed to serialize aggregate state as CBOR")
+}
+
+#[doc(hidden)]
+pub fn deserialize_state_cbor<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> T {
+    serde_cbor::from_slice(bytes).expect("failed to deserialize aggregate state from CBOR")
+}
@@ -11,6 +11,7 @@
 //!
 //! See: [https://www.postgresql.org/docs/current/bgworker.html](https://www.postgresql.org/docs/current/bgworker.html)
 use crate::pg_sys;
+use crate::{register_exit_callback, PGRXSharedMemory, PgExitCallbackEvent, PgLwLock};
 use pgrx_pg_sys::PgTryBuilder;
 use std::convert::TryInto;
 use std::ffi::{CStr, CString};
@@ -211,6 +212,174 @@ impl BackgroundWorker {
             result
         }
     }
+
+    /// Try to become the leader of the singleton role coordinated through `slot`, without waiting.
+    ///
+    /// Returns `None` if another live process currently holds the role.  If the recorded leader's
+    /// process no longer exists it is assumed to have crashed, and its role is taken over.
+    ///
+    /// Leadership is given up when the returned [`Leadership`] is dropped, or when this process
+    /// exits.  This may be called from any process attached to shared memory, not only background
+    /// workers.
+    pub fn try_become_leader(
+        slot: &'static PgLwLock<BackgroundWorkerLeader>,
+    ) -> Option<Leadership> {
+        let term = {
+            let mut leader = slot.exclusive();
+            if leader.pid != 0 && process_is_alive(leader.pid) {
+                return None;
+            }
+            leader.pid = unsafe { pg_sys::MyProcPid };
+            leader.term += 1;
+            leader.term
+        };
+        release_leadership_at_exit(slot);
+        Some(Leadership { slot, term })
+    }
+
+    /// Wait on the background worker's latch, retrying every `poll` interval, until this worker
+    /// becomes the leader of `slot`.  This is how a standby worker takes over when the current
+    /// leader stops or crashes.
+    ///
+    /// Returns `None` if a SIGTERM is received before leadership was acquired.
+    pub fn wait_for_leadership(
+        slot: &'static PgLwLock<BackgroundWorkerLeader>,
+        poll: Duration,
+    ) -> Option<Leadership> {
+        loop {
+            if let Some(leadership) = BackgroundWorker::try_become_leader(slot) {
+                return Some(leadership);
+            }
+            if !BackgroundWorker::wait_latch(Some(poll)) {
+                return None;
+            }
+        }
+    }
+}
+
+/// Shared memory state used to elect a single leader among the processes that want to perform
+/// some singleton role, such as several copies of the same background worker across which only
+/// one should do the actual work.
+///
+/// It must be stored in a `static` [`PgLwLock`] which is initialized with `pg_shmem_init!()`
+/// during `_PG_init()`, then passed to [`BackgroundWorker::try_become_leader`] or
+/// [`BackgroundWorker::wait_for_leadership`].
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use pgrx::prelude::*;
+/// use pgrx::bgworkers::{BackgroundWorker, BackgroundWorkerLeader, SignalWakeFlags};
+/// use pgrx::{pg_shmem_init, PgLwLock, PgSharedMemoryInitialization};
+/// use std::time::Duration;
+///
+/// static ARCHIVER: PgLwLock<BackgroundWorkerLeader> = PgLwLock::new();
+///
+/// #[pg_guard]
+/// pub extern "C" fn _PG_init() {
+///     pg_shmem_init!(ARCHIVER);
+/// }
+///
+/// #[pg_guard]
+/// #[no_mangle]
+/// pub extern "C" fn archiver_main(_arg: pg_sys::Datum) {
+///     BackgroundWorker::attach_signal_handlers(SignalWakeFlags::SIGHUP | SignalWakeFlags::SIGTERM);
+///
+///     // standbys park here until the current leader goes away
+///     let poll = Duration::from_secs(1);
+///     let Some(_leadership) = BackgroundWorker::wait_for_leadership(&ARCHIVER, poll) else {
+///         return;
+///     };
+///     while BackgroundWorker::wait_latch(Some(Duration::from_secs(10))) {
+///         // do the archiving
+///     }
+/// }
+/// ```
+///
+/// Leadership does not survive a server crash: when any backend crashes the postmaster
+/// reinitializes shared memory, and the role starts out vacant again.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct BackgroundWorkerLeader {
+    pid: i32,
+    term: u64,
+}
+
+unsafe impl PGRXSharedMemory for BackgroundWorkerLeader {}
+
+impl BackgroundWorkerLeader {
+    /// The process id of the current leader, if there is one
+    pub fn leader_pid(&self) -> Option<i32> {
+        (self.pid != 0).then_some(self.pid)
+    }
+
+    /// How many times leadership has been acquired since shared memory was initialized
+    pub fn term(&self) -> u64 {
+        self.term
+    }
+}
+
+/// Proof that this process is the leader of a [`BackgroundWorkerLeader`] slot.  Leadership is
+/// given up when this is dropped.
+pub struct Leadership {
+    slot: &'static PgLwLock<BackgroundWorkerLeader>,
+    term: u64,
+}
+
+impl Leadership {
+    /// The term under which this process became the leader
+    pub fn term(&self) -> u64 {
+        self.term
+    }
+
+    /// Is this process still the leader?
+    ///
+    /// This is only false if another process decided we had crashed and took over, which can
+    /// happen when our process id is no longer visible to it.
+    pub fn is_current(&self) -> bool {
+        let leader = self.slot.share();
+        leader.pid == unsafe { pg_sys::MyProcPid } && leader.term == self.term
+    }
+}
+
+impl Drop for Leadership {
+    fn drop(&mut self) {
+        release_leadership(self.slot);
+    }
+}
+
+fn process_is_alive(pid: i32) -> bool {
+    // signal 0 performs the existence and permission checks without sending anything.  EPERM
+    // means the process exists but belongs to someone else, which still counts as alive
+    unsafe { libc::kill(pid, 0) == 0 }
+    || std::io::Error::last_os_error().raw_os_error() != Some(libc::ESRCH)
+}
+
+fn release_leadership(slot: &PgLwLock<BackgroundWorkerLeader>) {
+    let mut leader = slot.exclusive();
+    if leader.pid == unsafe { pg_sys::MyProcPid } {
+        leader.pid = 0;
+    }
+}
+
+/// Make sure `slot` is released if this process exits while holding it.  The exit callback is
+/// only registered the first time a process becomes leader of a given slot
+fn release_leadership_at_exit(slot: &'static PgLwLock<BackgroundWorkerLeader>) {
+    static mut REGISTERED: Vec<usize> = Vec::new();
+
+    let addr = slot as *const _ as usize;
+    unsafe {
+        // SAFETY:  Postgres backends are single-threaded
+        if REGISTERED.contains(&addr) {
+            return;
+        }
+        REGISTERED.push(addr);
+    }
+
+    // `PgLwLock` holds raw pointers into shared memory, which are safe to use during exit
+    let slot = std::panic::AssertUnwindSafe(slot);
+    register_exit_callback(PgExitCallbackEvent::BeforeShmemExit, move |_code| {
+        release_leadership(*slot);
+    });
 }
 
 unsafe extern "C" fn worker_spi_sighup(_signal_args: i32) {
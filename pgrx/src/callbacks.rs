@@ -7,7 +7,8 @@
 //LICENSE All rights reserved.
 //LICENSE
 //LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
-//! Provides safe wrappers around Postgres' "Transaction" and "Sub Transaction" hook system
+//! Provides safe wrappers around Postgres' "Transaction" and "Sub Transaction" hook system, along
+//! with its backend exit callbacks
 
 use crate as pgrx; // for #[pg_guard] support from within ourself
 use crate::pg_sys;
@@ -377,3 +378,98 @@ where
 
     SubXactCallbackReceipt(wrapped_func)
 }
+
+/// Postgres backend exit events for which closures can be registered
+#[derive(Hash, Eq, PartialEq, Clone, Copy, Debug, Enum)]
+pub enum PgExitCallbackEvent {
+    /// Fired early during backend exit, while the backend is still attached to shared memory and
+    /// can still access the catalogs.  This is the place to flush buffered data to shared memory.
+    ///
+    /// Runs after Postgres' own `before_shmem_exit` callbacks registered later than the first
+    /// pgrx `BeforeShmemExit` closure, and before those registered earlier.
+    BeforeShmemExit,
+
+    /// Fired at the very end of backend exit, after it has detached from shared memory.  Only
+    /// process-local resources, such as connections to external services or open files, should be
+    /// touched here.
+    OnProcExit,
+}
+
+/// An internal wrapper for an exit callback closure
+struct ExitCallbackWrapper(
+    Box<dyn FnOnce(i32) + std::panic::UnwindSafe + std::panic::RefUnwindSafe + 'static>,
+);
+
+/// Register a closure to be called when the backend exits.  The closure receives the process exit
+/// code.
+///
+/// Like Postgres' own exit callbacks, closures registered for the same event are called in the
+/// reverse order of their registration, so that something registered later, and which might depend
+/// on something registered earlier, is torn down first.
+///
+/// Exit callbacks remain registered for the life of the backend and cannot be unregistered.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgrx::*;
+///
+/// register_exit_callback(PgExitCallbackEvent::BeforeShmemExit, |_code| {
+///     // flush any buffered statistics into shared memory
+/// });
+/// register_exit_callback(PgExitCallbackEvent::OnProcExit, |code| {
+///     // close the connection to some external service
+///     let _ = code;
+/// });
+/// ```
+///
+/// ## Safety
+///
+/// A Rust `panic!()` or Postgres `ereport(ERROR)` while an exit callback is executing is promoted
+/// to `FATAL` by Postgres, and any remaining exit callbacks, both those registered through pgrx and
+/// Postgres' own, are skipped.
+pub fn register_exit_callback<F>(which_event: PgExitCallbackEvent, f: F)
+where
+    F: FnOnce(i32) + std::panic::UnwindSafe + std::panic::RefUnwindSafe + 'static,
+{
+    // our closures for each exit event.  An event's Vec is None until we've registered our single
+    // Postgres callback for it
+    static mut EXIT_HOOKS: Option<EnumMap<PgExitCallbackEvent, Option<Vec<ExitCallbackWrapper>>>> =
+        None;
+
+    // internal function that we register with Postgres, once per event.  The event is
+    // passed through the callback's Datum argument
+    #[pg_guard]
+    unsafe extern "C" fn callback(code: ::std::os::raw::c_int, arg: pg_sys::Datum) {
+        let which_event = PgExitCallbackEvent::from_usize(arg.value());
+
+        let hooks = EXIT_HOOKS.as_mut().and_then(|hooks| hooks[which_event].take());
+
+        // run them last-in, first-out, just like Postgres does
+        if let Some(hooks) = hooks {
+            for hook in hooks.into_iter().rev() {
+                hook.0(code);
+            }
+        }
+    }
+
+    let hooks = unsafe {
+        // SAFETY:  Postgres backends are single-threaded
+        EXIT_HOOKS.get_or_insert_with(Default::default)
+    };
+
+    let entry = hooks[which_event].get_or_insert_with(|| {
+        let arg = pg_sys::Datum::from(which_event.into_usize());
+        unsafe {
+            // SAFETY:  `callback` is a valid pg_on_exit_callback and we only register it once per event
+            match which_event {
+                PgExitCallbackEvent::BeforeShmemExit => {
+                    pg_sys::before_shmem_exit(Some(callback), arg)
+                }
+                PgExitCallbackEvent::OnProcExit => pg_sys::on_proc_exit(Some(callback), arg),
+            }
+        }
+        Vec::new()
+    });
+    entry.push(ExitCallbackWrapper(Box::new(f)));
+}
@@ -0,0 +1,206 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Typed reads of role (`pg_authid`) and database (`pg_database`) metadata through the syscache
+//!
+//! These avoid an SPI query against the catalogs, which matters on hot paths such as per-query
+//! authorization checks.  They read the same cached tuples Postgres itself uses, so they see
+//! catalog changes made by the current transaction and by other committed transactions once
+//! their invalidations have been processed.
+use crate::pg_sys;
+use pgrx_pg_sys::AsPgCStr;
+use std::ffi::CStr;
+
+/// A role, as stored in `pg_authid`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoleInfo {
+    pub oid: pg_sys::Oid,
+    pub name: String,
+    pub superuser: bool,
+    pub inherit: bool,
+    pub create_role: bool,
+    pub create_db: bool,
+    pub can_login: bool,
+    pub replication: bool,
+    pub bypass_rls: bool,
+    /// The maximum number of concurrent connections for the role, or `None` if unlimited
+    pub connection_limit: Option<i32>,
+}
+
+impl RoleInfo {
+    /// Look up the role with the given oid
+    pub fn lookup(oid: pg_sys::Oid) -> Option<RoleInfo> {
+        unsafe {
+            // SAFETY:  AUTHOID is keyed by a single oid
+            let tup = pg_sys::SearchSysCache1(
+                pg_sys::SysCacheIdentifier_AUTHOID as i32,
+                pg_sys::Datum::from(oid),
+            );
+            if tup.is_null() {
+                return None;
+            }
+
+            // SAFETY:  tup is a valid pg_authid tuple, which we release once we've copied from it
+            let form = &*(pg_sys::GETSTRUCT(tup) as pg_sys::Form_pg_authid);
+            let role = RoleInfo {
+                oid,
+                name: crate::name_to_string(&form.rolname),
+                superuser: form.rolsuper,
+                inherit: form.rolinherit,
+                create_role: form.rolcreaterole,
+                create_db: form.rolcreatedb,
+                can_login: form.rolcanlogin,
+                replication: form.rolreplication,
+                bypass_rls: form.rolbypassrls,
+                connection_limit: (form.rolconnlimit >= 0).then_some(form.rolconnlimit),
+            };
+            pg_sys::ReleaseSysCache(tup);
+            Some(role)
+        }
+    }
+
+    /// Look up the role named `name`
+    pub fn lookup_by_name(name: &str) -> Option<RoleInfo> {
+        let oid = unsafe { pg_sys::get_role_oid(name.as_pg_cstr(), true) };
+        Self::lookup(oid)
+    }
+
+    /// The role whose privileges the current session is using, which is the role set by
+    /// `SET ROLE` or, inside a `SECURITY DEFINER` function, the function's owner
+    pub fn current() -> RoleInfo {
+        let oid = unsafe { pg_sys::GetUserId() };
+        Self::lookup(oid).expect("the current user does not exist")
+    }
+
+    /// Is this role a member of `role`, directly or indirectly, whether or not it inherits its
+    /// privileges?  Unlike [`RoleInfo::has_privs_of()`], superusers are not treated specially.
+    pub fn is_member_of(&self, role: pg_sys::Oid) -> bool {
+        unsafe { pg_sys::is_member_of_role_nosuper(self.oid, role) }
+    }
+
+    /// Does this role have the privileges of `role`, because it is `role`, inherits from it, or
+    /// is a superuser?
+    pub fn has_privs_of(&self, role: pg_sys::Oid) -> bool {
+        unsafe { pg_sys::has_privs_of_role(self.oid, role) }
+    }
+}
+
+/// A database, as stored in `pg_database`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatabaseInfo {
+    pub oid: pg_sys::Oid,
+    pub name: String,
+    pub owner: pg_sys::Oid,
+    /// The numeric identifier of the database's character encoding
+    pub encoding: i32,
+    /// `LC_COLLATE` for the database
+    pub collate: String,
+    /// `LC_CTYPE` for the database
+    pub ctype: String,
+    pub is_template: bool,
+    pub allow_connections: bool,
+    /// The maximum number of concurrent connections to the database, or `None` if unlimited
+    pub connection_limit: Option<i32>,
+    /// The oid of the database's default tablespace
+    pub tablespace: pg_sys::Oid,
+}
+
+impl DatabaseInfo {
+    /// Look up the database with the given oid
+    pub fn lookup(oid: pg_sys::Oid) -> Option<DatabaseInfo> {
+        unsafe {
+            // SAFETY:  DATABASEOID is keyed by a single oid
+            let tup = pg_sys::SearchSysCache1(
+                pg_sys::SysCacheIdentifier_DATABASEOID as i32,
+                pg_sys::Datum::from(oid),
+            );
+            if tup.is_null() {
+                return None;
+            }
+
+            // SAFETY:  tup is a valid pg_database tuple, which we release once we've copied
+            // from it
+            let form = &*(pg_sys::GETSTRUCT(tup) as pg_sys::Form_pg_database);
+            let database = DatabaseInfo {
+                oid,
+                name: crate::name_to_string(&form.datname),
+                owner: form.datdba,
+                encoding: form.encoding,
+                collate: database_locale(tup, pg_sys::Anum_pg_database_datcollate),
+                ctype: database_locale(tup, pg_sys::Anum_pg_database_datctype),
+                is_template: form.datistemplate,
+                allow_connections: form.datallowconn,
+                connection_limit: (form.datconnlimit >= 0).then_some(form.datconnlimit),
+                tablespace: form.dattablespace,
+            };
+            pg_sys::ReleaseSysCache(tup);
+            Some(database)
+        }
+    }
+
+    /// Look up the database named `name`
+    pub fn lookup_by_name(name: &str) -> Option<DatabaseInfo> {
+        let oid = unsafe { pg_sys::get_database_oid(name.as_pg_cstr(), true) };
+        Self::lookup(oid)
+    }
+
+    /// The database the current backend is connected to
+    pub fn current() -> DatabaseInfo {
+        let oid = unsafe { pg_sys::MyDatabaseId };
+        Self::lookup(oid).expect("the current database does not exist")
+    }
+
+    /// The name of the database's character encoding, such as `UTF8`
+    pub fn encoding_name(&self) -> &'static str {
+        unsafe {
+            // SAFETY:  pg_encoding_to_char() returns a pointer to a static string, or "" for an
+            // unknown encoding
+            CStr::from_ptr(pg_sys::pg_encoding_to_char(self.encoding))
+        }
+        .to_str()
+        .expect("encoding name is not valid UTF8")
+    }
+
+    /// The name of the database's default tablespace, if it still exists
+    pub fn tablespace_name(&self) -> Option<String> {
+        unsafe {
+            // SAFETY:  get_tablespace_name() returns NULL or a palloc'd copy of the name
+            let name = pg_sys::get_tablespace_name(self.tablespace);
+            (!name.is_null()).then(|| CStr::from_ptr(name).to_string_lossy().into_owned())
+        }
+    }
+}
+
+/// Read the `datcollate` or `datctype` column of a `pg_database` tuple
+///
+/// # Safety
+///
+/// `tup` must be a valid tuple from the `DATABASEOID` syscache
+unsafe fn database_locale(tup: pg_sys::HeapTuple, attno: u32) -> String {
+    let mut is_null = false;
+    let datum = pg_sys::SysCacheGetAttr(
+        pg_sys::SysCacheIdentifier_DATABASEOID as i32,
+        tup,
+        attno as pg_sys::AttrNumber,
+        &mut is_null,
+    );
+    if is_null {
+        return String::new();
+    }
+
+    // Postgres 15 moved these columns from `name` to `text`, to make room for ICU locales
+    #[cfg(any(feature = "pg15", feature = "pg16"))]
+    {
+        <String as crate::FromDatum>::from_datum(datum, false).unwrap_or_default()
+    }
+    #[cfg(not(any(feature = "pg15", feature = "pg16")))]
+    {
+        crate::name_to_string(&*datum.cast_mut_ptr::<pg_sys::NameData>())
+    }
+}
@@ -0,0 +1,204 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Typed readers for extension-specific configuration files
+//!
+//! Extensions whose configuration doesn't fit into GUCs often keep a file alongside the cluster's
+//! data directory or in `$SHAREDIR/extension`.  [`ExtensionConfigFile`] locates that file, parses
+//! it with `serde`, caches the result, and re-reads it whenever its location or contents change,
+//! such as after a `SIGHUP` changes the override path GUC.
+use crate::{pg_sys, GucSetting};
+use core::ffi::CStr;
+use serde::de::DeserializeOwned;
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::SystemTime;
+
+/// Where an [`ExtensionConfigFile`] looks for its file when no override path is set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFileLocation {
+    /// The cluster's data directory, `$PGDATA`
+    DataDir,
+    /// The `extension/` directory under the installation's `SHAREDIR`, next to the extension's
+    /// control file
+    ShareExtension,
+}
+
+/// Errors that can occur while loading an [`ExtensionConfigFile`]
+#[derive(thiserror::Error, Debug)]
+pub enum ConfigFileError {
+    #[error("could not read configuration file `{0}`: {1}")]
+    Io(PathBuf, #[source] std::io::Error),
+
+    #[error("could not parse configuration file `{0}`: {1}")]
+    Parse(PathBuf, String),
+}
+
+struct Loaded<T> {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    value: Rc<T>,
+}
+
+/// A configuration file, parsed into `T`, that is read lazily and re-read when it changes.
+///
+/// The file is parsed as JSON unless a different parser is provided with
+/// [`ExtensionConfigFile::with_parser()`].
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgrx::prelude::*;
+/// use pgrx::config_file::{ConfigFileLocation, ExtensionConfigFile};
+/// use pgrx::{GucContext, GucFlags, GucRegistry, GucSetting};
+/// use std::ffi::CStr;
+///
+/// #[derive(serde::Deserialize)]
+/// struct MyConfig {
+///     endpoints: Vec<String>,
+/// }
+///
+/// static CONFIG_PATH: GucSetting<Option<&'static CStr>> = GucSetting::<Option<&'static CStr>>::new(None);
+/// static CONFIG: ExtensionConfigFile<MyConfig> =
+///     ExtensionConfigFile::new("my_extension.json", ConfigFileLocation::ShareExtension)
+///         .with_override_guc(&CONFIG_PATH);
+///
+/// #[pg_guard]
+/// pub extern "C" fn _PG_init() {
+///     GucRegistry::define_string_guc(
+///         "my_extension.config_file",
+///         "Path to the my_extension configuration file",
+///         "Overrides the default location of $SHAREDIR/extension/my_extension.json",
+///         &CONFIG_PATH,
+///         GucContext::Sighup,
+///         GucFlags::default(),
+///     );
+/// }
+///
+/// fn endpoints() -> Vec<String> {
+///     CONFIG.get().expect("invalid configuration").endpoints.clone()
+/// }
+/// ```
+pub struct ExtensionConfigFile<T> {
+    file_name: &'static str,
+    location: ConfigFileLocation,
+    override_guc: Option<&'static GucSetting<Option<&'static CStr>>>,
+    parser: fn(&str) -> Result<T, String>,
+    loaded: RefCell<Option<Loaded<T>>>,
+}
+
+// SAFETY:  Postgres backends are single-threaded, just like with `GucSetting`
+unsafe impl<T> Sync for ExtensionConfigFile<T> {}
+
+impl<T: DeserializeOwned> ExtensionConfigFile<T> {
+    /// Describe the configuration file named `file_name` that lives in `location`
+    pub const fn new(file_name: &'static str, location: ConfigFileLocation) -> Self {
+        ExtensionConfigFile {
+            file_name,
+            location,
+            override_guc: None,
+            parser: parse_json::<T>,
+            loaded: RefCell::new(None),
+        }
+    }
+}
+
+impl<T> ExtensionConfigFile<T> {
+    /// Use the value of the string GUC `guc`, when it's set, as the path to the configuration
+    /// file.  Relative paths are relative to the data directory.
+    pub const fn with_override_guc(
+        mut self,
+        guc: &'static GucSetting<Option<&'static CStr>>,
+    ) -> Self {
+        self.override_guc = Some(guc);
+        self
+    }
+
+    /// Parse the file contents with `parser` rather than as JSON
+    pub const fn with_parser(mut self, parser: fn(&str) -> Result<T, String>) -> Self {
+        self.parser = parser;
+        self
+    }
+
+    /// The path the configuration file is currently read from
+    pub fn path(&self) -> PathBuf {
+        if let Some(path) = self.override_guc.and_then(|guc| guc.get()) {
+            let path = Path::new(path.to_str().expect("configuration file path is not UTF8"));
+            if !path.as_os_str().is_empty() {
+                return data_dir().join(path);
+            }
+        }
+
+        match self.location {
+            ConfigFileLocation::DataDir => data_dir().join(self.file_name),
+            ConfigFileLocation::ShareExtension => {
+                share_dir().join("extension").join(self.file_name)
+            }
+        }
+    }
+
+    /// Returns the parsed configuration, reading the file if it hasn't been read yet, or if its
+    /// path or modification time has changed since it was last read
+    pub fn get(&self) -> Result<Rc<T>, ConfigFileError> {
+        let path = self.path();
+        let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+
+        if let Some(loaded) = self.loaded.borrow().as_ref() {
+            if loaded.path == path && loaded.modified.is_some() && loaded.modified == modified {
+                return Ok(loaded.value.clone());
+            }
+        }
+
+        self.load(path, modified)
+    }
+
+    /// Unconditionally re-read the configuration file, such as after a background worker
+    /// receives `SIGHUP`
+    pub fn reload(&self) -> Result<Rc<T>, ConfigFileError> {
+        let path = self.path();
+        let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+        self.load(path, modified)
+    }
+
+    fn load(&self, path: PathBuf, modified: Option<SystemTime>) -> Result<Rc<T>, ConfigFileError> {
+        let contents =
+            std::fs::read_to_string(&path).map_err(|e| ConfigFileError::Io(path.clone(), e))?;
+        let value =
+            Rc::new((self.parser)(&contents).map_err(|e| ConfigFileError::Parse(path.clone(), e))?);
+
+        self.loaded.replace(Some(Loaded { path, modified, value: value.clone() }));
+        Ok(value)
+    }
+}
+
+fn parse_json<T: DeserializeOwned>(contents: &str) -> Result<T, String> {
+    serde_json::from_str(contents).map_err(|e| e.to_string())
+}
+
+/// The cluster's data directory
+pub fn data_dir() -> PathBuf {
+    unsafe {
+        // SAFETY:  DataDir is set during backend startup, before extension libraries are loaded
+        let dir = pg_sys::DataDir;
+        assert!(!dir.is_null(), "DataDir is not set");
+        PathBuf::from(CStr::from_ptr(dir).to_str().expect("DataDir is not UTF8"))
+    }
+}
+
+/// The installation's `SHAREDIR`, as reported by `pg_config --sharedir`
+pub fn share_dir() -> PathBuf {
+    let mut buf = [0 as core::ffi::c_char; pg_sys::MAXPGPATH as usize];
+    unsafe {
+        // SAFETY:  my_exec_path is set during process startup and `buf` is MAXPGPATH bytes long,
+        // which is what get_share_path() expects
+        pg_sys::get_share_path(pg_sys::my_exec_path.as_ptr(), buf.as_mut_ptr());
+        PathBuf::from(CStr::from_ptr(buf.as_ptr()).to_str().expect("SHAREDIR is not UTF8"))
+    }
+}
@@ -0,0 +1,306 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Cost estimation for paths created by foreign data wrappers and custom scan providers
+//!
+//! The planner compares paths by their costs, so paths estimated with constants pulled out of
+//! thin air tend to be chosen, or ignored, for the wrong reasons.  These helpers estimate costs
+//! from the same `*_cost` settings, and the same per-relation restriction and target costs, that
+//! the planner uses for its own paths.
+//!
+//! ## Examples
+//!
+//! ```rust,no_run
+//! use pgrx::cost::{baserel_param_info, ScanCost};
+//! use pgrx::prelude::*;
+//!
+//! unsafe fn estimate(
+//!     root: *mut pg_sys::PlannerInfo,
+//!     baserel: *mut pg_sys::RelOptInfo,
+//!     pages: f64,
+//!     tuples: f64,
+//! ) -> pgrx::cost::PathCost {
+//!     // an unparameterized path which reads every page and tuple of the remote table once
+//!     let param_info = baserel_param_info(root, baserel, std::ptr::null_mut());
+//!     ScanCost::for_rel(root, baserel, param_info)
+//!         .startup(100.0)
+//!         .seq_pages(pages)
+//!         .tuples(tuples)
+//!         .finish()
+//! }
+//! ```
+use crate::{pg_sys, PgList};
+
+/// The planner's cost settings, as of when [`PlannerCosts::current()`] was called
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PlannerCosts {
+    /// `seq_page_cost`
+    pub seq_page_cost: f64,
+    /// `random_page_cost`
+    pub random_page_cost: f64,
+    /// `cpu_tuple_cost`
+    pub cpu_tuple_cost: f64,
+    /// `cpu_index_tuple_cost`
+    pub cpu_index_tuple_cost: f64,
+    /// `cpu_operator_cost`
+    pub cpu_operator_cost: f64,
+    /// `parallel_tuple_cost`
+    pub parallel_tuple_cost: f64,
+    /// `parallel_setup_cost`
+    pub parallel_setup_cost: f64,
+    /// `effective_cache_size`, in pages
+    pub effective_cache_size: i32,
+}
+
+impl PlannerCosts {
+    pub fn current() -> Self {
+        unsafe {
+            // SAFETY:  these are only written by the GUC machinery, on this thread
+            Self {
+                seq_page_cost: pg_sys::seq_page_cost,
+                random_page_cost: pg_sys::random_page_cost,
+                cpu_tuple_cost: pg_sys::cpu_tuple_cost,
+                cpu_index_tuple_cost: pg_sys::cpu_index_tuple_cost,
+                cpu_operator_cost: pg_sys::cpu_operator_cost,
+                parallel_tuple_cost: pg_sys::parallel_tuple_cost,
+                parallel_setup_cost: pg_sys::parallel_setup_cost,
+                effective_cache_size: pg_sys::effective_cache_size,
+            }
+        }
+    }
+}
+
+/// The estimated costs and row count of a path
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct PathCost {
+    /// The cost of returning the first row
+    pub startup: f64,
+    /// The cost of returning every row
+    pub total: f64,
+    /// The number of rows returned
+    pub rows: f64,
+}
+
+impl PathCost {
+    /// The cost of returning every row after the first
+    pub fn run(&self) -> f64 {
+        self.total - self.startup
+    }
+
+    /// Set `path`'s costs and row count to these.
+    pub fn apply_to(&self, path: &mut pg_sys::Path) {
+        path.startup_cost = self.startup;
+        path.total_cost = self.total;
+        path.rows = self.rows;
+    }
+}
+
+/// Round a row count estimate to an integer of at least one, as the planner does.
+pub fn clamp_row_est(rows: f64) -> f64 {
+    unsafe {
+        // SAFETY:  this is just arithmetic
+        pg_sys::clamp_row_est(rows)
+    }
+}
+
+/// The cost of evaluating `quals`, which may be a list of `RestrictInfo`s or of bare expressions.
+///
+/// # Safety
+///
+/// `root` must be the planner's current `PlannerInfo`, and `quals` a valid `List`, or null.
+pub unsafe fn qual_cost(
+    root: *mut pg_sys::PlannerInfo,
+    quals: *mut pg_sys::List,
+) -> pg_sys::QualCost {
+    let mut cost = pg_sys::QualCost::default();
+    pg_sys::cost_qual_eval(&mut cost, quals, root);
+    cost
+}
+
+/// Find or create the `ParamPathInfo` for a path scanning `baserel` which requires the outer
+/// relations `required_outer`, or null if it's unparameterized.
+///
+/// Paths with the same `required_outer` share a `ParamPathInfo`, which holds the join clauses
+/// the path can enforce and its row estimate once they have been.
+///
+/// # Safety
+///
+/// `root` must be the planner's current `PlannerInfo`, `baserel` one of its base relations, and
+/// `required_outer` a valid `Relids`, or null.
+pub unsafe fn baserel_param_info(
+    root: *mut pg_sys::PlannerInfo,
+    baserel: *mut pg_sys::RelOptInfo,
+    required_outer: pg_sys::Relids,
+) -> *mut pg_sys::ParamPathInfo {
+    pg_sys::get_baserel_parampathinfo(root, baserel, required_outer)
+}
+
+/// The number of rows a scan of `baserel` returns, given its `param_info`.
+///
+/// # Safety
+///
+/// `baserel` must be a valid `RelOptInfo` and `param_info` a valid `ParamPathInfo`, or null.
+pub unsafe fn param_rows(
+    baserel: *mut pg_sys::RelOptInfo,
+    param_info: *mut pg_sys::ParamPathInfo,
+) -> f64 {
+    if param_info.is_null() {
+        (*baserel).rows
+    } else {
+        (*param_info).ppi_rows
+    }
+}
+
+/// The join clauses, as `RestrictInfo`s, which a path with `param_info` must enforce.
+///
+/// # Safety
+///
+/// `param_info` must be a valid `ParamPathInfo`, or null.
+pub unsafe fn param_clauses(
+    param_info: *mut pg_sys::ParamPathInfo,
+) -> PgList<pg_sys::RestrictInfo> {
+    if param_info.is_null() {
+        PgList::new()
+    } else {
+        PgList::from_pg((*param_info).ppi_clauses)
+    }
+}
+
+/// Estimates the cost of scanning a relation the way `cost_seqscan()` and its siblings do.
+///
+/// Page and tuple costs are accumulated with [`seq_pages()`](ScanCost::seq_pages),
+/// [`random_pages()`](ScanCost::random_pages), [`tuples()`](ScanCost::tuples) and friends, then
+/// [`finish()`](ScanCost::finish) adds the cost of computing the relation's target list for each
+/// returned row.  Pages are charged at the global page costs, ignoring any tablespace overrides.
+#[derive(Debug, Copy, Clone)]
+pub struct ScanCost {
+    costs: PlannerCosts,
+    rows: f64,
+    qual: pg_sys::QualCost,
+    target: pg_sys::QualCost,
+    startup: f64,
+    disk: f64,
+    cpu: f64,
+}
+
+impl ScanCost {
+    /// Start estimating a scan returning `rows` rows, with no quals or target list to evaluate.
+    pub fn new(rows: f64) -> Self {
+        Self {
+            costs: PlannerCosts::current(),
+            rows,
+            qual: Default::default(),
+            target: Default::default(),
+            startup: 0.0,
+            disk: 0.0,
+            cpu: 0.0,
+        }
+    }
+
+    /// Start estimating a scan of `baserel` which evaluates its restrictions, and those of
+    /// `param_info`, locally.
+    ///
+    /// # Safety
+    ///
+    /// `root` must be the planner's current `PlannerInfo`, `baserel` one of its base relations,
+    /// with its size estimates already set, and `param_info` a `ParamPathInfo` for `baserel`, or
+    /// null.
+    pub unsafe fn for_rel(
+        root: *mut pg_sys::PlannerInfo,
+        baserel: *mut pg_sys::RelOptInfo,
+        param_info: *mut pg_sys::ParamPathInfo,
+    ) -> Self {
+        let mut qual = (*baserel).baserestrictcost;
+        if !param_info.is_null() {
+            let join_cost = qual_cost(root, (*param_info).ppi_clauses);
+            qual.startup += join_cost.startup;
+            qual.per_tuple += join_cost.per_tuple;
+        }
+        let target = if (*baserel).reltarget.is_null() {
+            Default::default()
+        } else {
+            (*(*baserel).reltarget).cost
+        };
+        Self { qual, target, ..Self::new(param_rows(baserel, param_info)) }
+    }
+
+    /// Evaluate `qual` for every scanned tuple, in addition to the relation's restrictions.
+    pub fn qual(mut self, qual: pg_sys::QualCost) -> Self {
+        self.qual.startup += qual.startup;
+        self.qual.per_tuple += qual.per_tuple;
+        self
+    }
+
+    /// Add a fixed `cost` incurred before the first row is returned, such as connecting to a
+    /// remote server.
+    pub fn startup(mut self, cost: f64) -> Self {
+        self.startup += cost;
+        self
+    }
+
+    /// Read `pages` pages sequentially.
+    pub fn seq_pages(mut self, pages: f64) -> Self {
+        self.disk += self.costs.seq_page_cost * pages;
+        self
+    }
+
+    /// Read `pages` pages in random order.
+    pub fn random_pages(mut self, pages: f64) -> Self {
+        self.disk += self.costs.random_page_cost * pages;
+        self
+    }
+
+    /// Process `tuples` tuples, evaluating the quals against each.
+    pub fn tuples(mut self, tuples: f64) -> Self {
+        self.cpu += (self.costs.cpu_tuple_cost + self.qual.per_tuple) * tuples;
+        self
+    }
+
+    /// Process `tuples` index entries.
+    pub fn index_tuples(mut self, tuples: f64) -> Self {
+        self.cpu += self.costs.cpu_index_tuple_cost * tuples;
+        self
+    }
+
+    /// Evaluate `operators` operators or functions.
+    pub fn operators(mut self, operators: f64) -> Self {
+        self.cpu += self.costs.cpu_operator_cost * operators;
+        self
+    }
+
+    /// The costs accumulated so far, plus those of evaluating the quals once and the target list
+    /// for each returned row.
+    pub fn finish(&self) -> PathCost {
+        let startup = self.startup + self.qual.startup + self.target.startup;
+        let run = self.disk + self.cpu + self.target.per_tuple * self.rows;
+        PathCost { startup, total: startup + run, rows: self.rows }
+    }
+}
+
+/// The costs of a sequential scan of `baserel`, exactly as the planner computes them.
+///
+/// # Safety
+///
+/// `root` must be the planner's current `PlannerInfo`, `baserel` one of its plain-table base
+/// relations, with its size estimates already set, and `param_info` a `ParamPathInfo` for
+/// `baserel`, or null.
+pub unsafe fn seqscan_cost(
+    root: *mut pg_sys::PlannerInfo,
+    baserel: *mut pg_sys::RelOptInfo,
+    param_info: *mut pg_sys::ParamPathInfo,
+) -> PathCost {
+    let mut path = pg_sys::Path::default();
+    path.type_ = pg_sys::NodeTag_T_Path;
+    path.pathtype = pg_sys::NodeTag_T_SeqScan;
+    path.parent = baserel;
+    path.pathtarget = (*baserel).reltarget;
+    path.param_info = param_info;
+    pg_sys::cost_seqscan(&mut path, root, baserel, param_info);
+    PathCost { startup: path.startup_cost, total: path.total_cost, rows: path.rows }
+}
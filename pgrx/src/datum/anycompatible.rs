@@ -0,0 +1,278 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! The `anycompatible` family of polymorphic pseudo-types, added in Postgres 13.
+//!
+//! Unlike `anyelement` and `anyarray`, whose arguments must all be exactly the same type, Postgres
+//! coerces every `anycompatible` argument to a common type before calling the function.  The type
+//! ID each value carries is that resolved common type.
+//!
+//! [`pg_getarg_element_type`](crate::fcinfo::pg_getarg_element_type) resolves the element type of
+//! any of these arguments, whether or not it's an array.
+use crate::{pg_sys, FromDatum, IntoDatum};
+use pgrx_sql_entity_graph::metadata::{
+    ArgumentError, Returns, ReturnsError, SqlMapping, SqlTranslatable,
+};
+
+/// The [`anycompatible` polymorphic pseudo-type][anycompatible].
+///
+// rustdoc doesn't directly support a warning block: https://github.com/rust-lang/rust/issues/73935
+/// **Warning**: Calling [`FromDatum::from_datum`] with this type will unconditonally panic. Call
+/// [`FromDatum::from_polymorphic_datum`] with a type ID instead.
+///
+/// [anycompatible]: https://www.postgresql.org/docs/current/extend-type-system.html#EXTEND-TYPES-POLYMORPHIC
+#[derive(Debug, Clone, Copy)]
+pub struct AnyCompatible {
+    datum: pg_sys::Datum,
+    typoid: pg_sys::Oid,
+}
+
+impl AnyCompatible {
+    pub fn datum(&self) -> pg_sys::Datum {
+        self.datum
+    }
+
+    /// The common type Postgres resolved for all of the function's `anycompatible` arguments
+    pub fn oid(&self) -> pg_sys::Oid {
+        self.typoid
+    }
+
+    /// The type of an array of this element's type, which is what an `anycompatiblearray`
+    /// argument or return value of the same function resolves to
+    pub fn array_oid(&self) -> Option<pg_sys::Oid> {
+        let array_oid = unsafe { pg_sys::get_array_type(self.typoid) };
+        (array_oid != pg_sys::InvalidOid).then_some(array_oid)
+    }
+
+    /// Convert this element into a specific type.
+    ///
+    /// # Safety
+    ///
+    /// This function is unsafe as it cannot guarantee that the underlying datum can be represented
+    /// as `T`.  This is your responsibility
+    #[inline]
+    pub unsafe fn into<T: FromDatum>(&self) -> Option<T> {
+        T::from_polymorphic_datum(self.datum(), false, self.oid())
+    }
+}
+
+impl FromDatum for AnyCompatible {
+    const GET_TYPOID: bool = true;
+
+    /// You should **never** call this function to make this type; it will unconditionally panic.
+    /// For polymorphic types such as this one, you must use [`FromDatum::from_polymorphic_datum`]
+    /// and pass a type ID.
+    #[inline]
+    unsafe fn from_datum(_datum: pg_sys::Datum, _is_null: bool) -> Option<AnyCompatible> {
+        panic!("Can't create a polymorphic type using from_datum, call FromDatum::from_polymorphic_datum instead")
+    }
+
+    #[inline]
+    unsafe fn from_polymorphic_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        typoid: pg_sys::Oid,
+    ) -> Option<AnyCompatible> {
+        if is_null {
+            None
+        } else {
+            Some(AnyCompatible { datum, typoid })
+        }
+    }
+}
+
+impl IntoDatum for AnyCompatible {
+    #[inline]
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(self.datum)
+    }
+
+    fn type_oid() -> pg_sys::Oid {
+        pg_sys::ANYCOMPATIBLEOID
+    }
+}
+
+unsafe impl SqlTranslatable for AnyCompatible {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        Ok(SqlMapping::literal("anycompatible"))
+    }
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        Ok(Returns::One(SqlMapping::literal("anycompatible")))
+    }
+}
+
+/// The [`anycompatiblearray` polymorphic pseudo-type][anycompatible].
+///
+// rustdoc doesn't directly support a warning block: https://github.com/rust-lang/rust/issues/73935
+/// **Warning**: Calling [`FromDatum::from_datum`] with this type will unconditonally panic. Call
+/// [`FromDatum::from_polymorphic_datum`] with a type ID instead.
+///
+/// [anycompatible]: https://www.postgresql.org/docs/current/extend-type-system.html#EXTEND-TYPES-POLYMORPHIC
+#[derive(Debug, Clone, Copy)]
+pub struct AnyCompatibleArray {
+    datum: pg_sys::Datum,
+    typoid: pg_sys::Oid,
+}
+
+impl AnyCompatibleArray {
+    pub fn datum(&self) -> pg_sys::Datum {
+        self.datum
+    }
+
+    /// The array type Postgres resolved for the function's `anycompatiblearray` arguments
+    pub fn oid(&self) -> pg_sys::Oid {
+        self.typoid
+    }
+
+    /// The element type of this array, which is what an `anycompatible` argument or return value
+    /// of the same function resolves to
+    pub fn element_oid(&self) -> pg_sys::Oid {
+        unsafe { pg_sys::get_element_type(self.typoid) }
+    }
+
+    #[inline]
+    pub fn into<T: FromDatum>(&self) -> Option<T> {
+        unsafe { T::from_polymorphic_datum(self.datum(), false, self.oid()) }
+    }
+}
+
+impl FromDatum for AnyCompatibleArray {
+    const GET_TYPOID: bool = true;
+
+    /// You should **never** call this function to make this type; it will unconditionally panic.
+    /// For polymorphic types such as this one, you must use [`FromDatum::from_polymorphic_datum`]
+    /// and pass a type ID.
+    #[inline]
+    unsafe fn from_datum(_datum: pg_sys::Datum, _is_null: bool) -> Option<AnyCompatibleArray> {
+        panic!("Can't create a polymorphic type using from_datum, call FromDatum::from_polymorphic_datum instead")
+    }
+
+    #[inline]
+    unsafe fn from_polymorphic_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        typoid: pg_sys::Oid,
+    ) -> Option<AnyCompatibleArray> {
+        if is_null {
+            None
+        } else {
+            Some(AnyCompatibleArray { datum, typoid })
+        }
+    }
+}
+
+impl IntoDatum for AnyCompatibleArray {
+    #[inline]
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(self.datum)
+    }
+
+    fn type_oid() -> pg_sys::Oid {
+        pg_sys::ANYCOMPATIBLEARRAYOID
+    }
+}
+
+unsafe impl SqlTranslatable for AnyCompatibleArray {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        Ok(SqlMapping::literal("anycompatiblearray"))
+    }
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        Ok(Returns::One(SqlMapping::literal("anycompatiblearray")))
+    }
+}
+
+/// The [`anycompatiblenonarray` polymorphic pseudo-type][anycompatible].
+///
+/// This is the same as [`AnyCompatible`] except Postgres will refuse to resolve it to an array
+/// type.
+///
+// rustdoc doesn't directly support a warning block: https://github.com/rust-lang/rust/issues/73935
+/// **Warning**: Calling [`FromDatum::from_datum`] with this type will unconditonally panic. Call
+/// [`FromDatum::from_polymorphic_datum`] with a type ID instead.
+///
+/// [anycompatible]: https://www.postgresql.org/docs/current/extend-type-system.html#EXTEND-TYPES-POLYMORPHIC
+#[derive(Debug, Clone, Copy)]
+pub struct AnyCompatibleNonArray {
+    datum: pg_sys::Datum,
+    typoid: pg_sys::Oid,
+}
+
+impl AnyCompatibleNonArray {
+    pub fn datum(&self) -> pg_sys::Datum {
+        self.datum
+    }
+
+    /// The common type Postgres resolved for all of the function's `anycompatible` family
+    /// arguments
+    pub fn oid(&self) -> pg_sys::Oid {
+        self.typoid
+    }
+
+    /// The type of an array of this element's type, if one exists
+    pub fn array_oid(&self) -> Option<pg_sys::Oid> {
+        let array_oid = unsafe { pg_sys::get_array_type(self.typoid) };
+        (array_oid != pg_sys::InvalidOid).then_some(array_oid)
+    }
+
+    /// Convert this element into a specific type.
+    ///
+    /// # Safety
+    ///
+    /// This function is unsafe as it cannot guarantee that the underlying datum can be represented
+    /// as `T`.  This is your responsibility
+    #[inline]
+    pub unsafe fn into<T: FromDatum>(&self) -> Option<T> {
+        T::from_polymorphic_datum(self.datum(), false, self.oid())
+    }
+}
+
+impl FromDatum for AnyCompatibleNonArray {
+    const GET_TYPOID: bool = true;
+
+    /// You should **never** call this function to make this type; it will unconditionally panic.
+    /// For polymorphic types such as this one, you must use [`FromDatum::from_polymorphic_datum`]
+    /// and pass a type ID.
+    #[inline]
+    unsafe fn from_datum(_datum: pg_sys::Datum, _is_null: bool) -> Option<AnyCompatibleNonArray> {
+        panic!("Can't create a polymorphic type using from_datum, call FromDatum::from_polymorphic_datum instead")
+    }
+
+    #[inline]
+    unsafe fn from_polymorphic_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        typoid: pg_sys::Oid,
+    ) -> Option<AnyCompatibleNonArray> {
+        if is_null {
+            None
+        } else {
+            Some(AnyCompatibleNonArray { datum, typoid })
+        }
+    }
+}
+
+impl IntoDatum for AnyCompatibleNonArray {
+    #[inline]
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(self.datum)
+    }
+
+    fn type_oid() -> pg_sys::Oid {
+        pg_sys::ANYCOMPATIBLENONARRAYOID
+    }
+}
+
+unsafe impl SqlTranslatable for AnyCompatibleNonArray {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        Ok(SqlMapping::literal("anycompatiblenonarray"))
+    }
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        Ok(Returns::One(SqlMapping::literal("anycompatiblenonarray")))
+    }
+}
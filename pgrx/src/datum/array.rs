@@ -741,6 +741,20 @@ impl<T: IntoDatum + FromDatum> IntoDatum for Array<'_, T> {
     fn type_oid() -> Oid {
         T::array_type_oid()
     }
+
+    #[inline]
+    fn is_compatible_with(other: Oid) -> bool {
+        Self::type_oid() == other || is_array_of::<T>(other)
+    }
+}
+
+/// Is `array_oid` an array type whose elements are compatible with `T`?
+///
+/// This is what lets an array of a specific composite type, such as `dog[]`, be read as a
+/// `Vec<PgHeapTuple>` or `Array<PgHeapTuple>`, whose `type_oid()` can only say `record[]`.
+fn is_array_of<T: IntoDatum>(array_oid: Oid) -> bool {
+    let element_oid = unsafe { pg_sys::get_element_type(array_oid) };
+    element_oid != pg_sys::InvalidOid && T::is_compatible_with(element_oid)
 }
 
 impl<T: FromDatum> FromDatum for Vec<T> {
@@ -802,14 +816,30 @@ where
     T: IntoDatum,
 {
     fn into_datum(self) -> Option<pg_sys::Datum> {
+        // an array of composites needs to be an array of their specific type, not of `record`, so
+        // it can be stored in or compared against a column of that array type.  An empty `Vec` of
+        // anonymous `PgHeapTuple`s has no element to ask, so it can only become `record[]`
+        let element_oid =
+            self.iter().find_map(|element| element.composite_type_oid()).unwrap_or(T::type_oid());
         let mut state = unsafe {
             pg_sys::initArrayResult(
-                T::type_oid(),
+                element_oid,
                 PgMemoryContexts::CurrentMemoryContext.value(),
                 false,
             )
         };
         for s in self {
+            if let Some(oid) = s.composite_type_oid() {
+                // every element shares the array's single element type, so mixing composites would
+                // hand Postgres tuples it can't decode
+                if oid != element_oid {
+                    panic!(
+                        "cannot put a `{}` into an array of `{}`",
+                        format_type(oid),
+                        format_type(element_oid)
+                    );
+                }
+            }
             let datum = s.into_datum();
             let isnull = datum.is_none();
 
@@ -818,7 +848,7 @@ where
                     state,
                     datum.unwrap_or(0.into()),
                     isnull,
-                    T::type_oid(),
+                    element_oid,
                     PgMemoryContexts::CurrentMemoryContext.value(),
                 );
             }
@@ -840,10 +870,16 @@ where
 
     #[inline]
     fn is_compatible_with(other: pg_sys::Oid) -> bool {
-        Self::type_oid() == other || other == unsafe { pg_sys::get_array_type(T::type_oid()) }
+        Self::type_oid() == other
+            || other == unsafe { pg_sys::get_array_type(T::type_oid()) }
+            || is_array_of::<T>(other)
     }
 }
 
+fn format_type(oid: pg_sys::Oid) -> String {
+    unsafe { core::ffi::CStr::from_ptr(pg_sys::format_type_be(oid)) }.to_string_lossy().into_owned()
+}
+
 impl<'a, T> IntoDatum for &'a [T]
 where
     T: IntoDatum + Copy + 'a,
@@ -887,7 +923,9 @@ where
 
     #[inline]
     fn is_compatible_with(other: pg_sys::Oid) -> bool {
-        Self::type_oid() == other || other == unsafe { pg_sys::get_array_type(T::type_oid()) }
+        Self::type_oid() == other
+            || other == unsafe { pg_sys::get_array_type(T::type_oid()) }
+            || is_array_of::<T>(other)
     }
 }
 
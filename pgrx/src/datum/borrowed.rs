@@ -0,0 +1,160 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Converting Datums into Rust types which borrow from them, tied to a memory context
+//!
+//! [`FromDatum`] lets borrowed views such as `&'a str` pick any lifetime `'a`, so nothing stops
+//! them from outliving the memory they point into.  [`FromDatumBorrowed`] ties them to the
+//! lifetime of a [`MemCx`] instead.
+//!
+//! `#[pg_extern]` functions receive their arguments this way, with the lifetime of a [`MemCx`]
+//! for the call's memory context, so a borrowed argument can be returned but not kept:
+//!
+//! ```rust,no_run
+//! use pgrx::prelude::*;
+//!
+//! #[pg_extern]
+//! fn first_word<'a>(text: &'a str) -> &'a str {
+//!     text.split_whitespace().next().unwrap_or("")
+//! }
+//! ```
+//!
+//! ```rust,compile_fail
+//! use pgrx::prelude::*;
+//! use std::sync::Mutex;
+//!
+//! static LAST: Mutex<Option<&'static str>> = Mutex::new(None);
+//!
+//! // the argument only lives as long as the call's memory context
+//! #[pg_extern]
+//! fn remember(text: &'static str) {
+//!     *LAST.lock().unwrap() = Some(text);
+//! }
+//! ```
+//!
+//! Types which don't implement [`FromDatumBorrowed`] are still converted with [`FromDatum`], as are
+//! the arguments of set-returning functions, whose iterators outlive the call which creates them.
+//! Code which converts Datums itself can do the same with
+//! [`pg_getarg_borrowed`](crate::fcinfo::pg_getarg_borrowed), or with
+//! [`current_context`](crate::memcxt::current_context) for Datums obtained from elsewhere.
+use crate::memcxt::MemCx;
+use crate::{pg_sys, Array, FromDatum, VariadicArray};
+use core::ffi::CStr;
+use core::marker::PhantomData;
+
+/// Convert a `(pg_sys::Datum, is_null:bool, typoid)` triple into a Rust type which may borrow from
+/// memory in the given memory context, and so can't outlive it.
+pub trait FromDatumBorrowed<'mcx>: Sized {
+    /// Like [`FromDatum::from_polymorphic_datum`], but anything Postgres allocates for the result,
+    /// such as a detoasted copy of the Datum, is allocated in `memcx`.
+    ///
+    /// # Safety
+    ///
+    /// The same as for [`FromDatum::from_polymorphic_datum`]: the Datum must be of a type
+    /// compatible with `Self`, and must point into memory which lives at least as long as `memcx`.
+    unsafe fn from_datum_borrowed(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        typoid: pg_sys::Oid,
+        memcx: &MemCx<'mcx>,
+    ) -> Option<Self>;
+}
+
+/// Types whose [`FromDatum`] impl is valid for any lifetime only borrow from the Datum or from
+/// what's allocated while converting it
+macro_rules! from_datum_borrowed {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl<'mcx> FromDatumBorrowed<'mcx> for $ty {
+                #[inline]
+                unsafe fn from_datum_borrowed(
+                    datum: pg_sys::Datum,
+                    is_null: bool,
+                    typoid: pg_sys::Oid,
+                    memcx: &MemCx<'mcx>,
+                ) -> Option<Self> {
+                    memcx.exec_in(|| FromDatum::from_polymorphic_datum(datum, is_null, typoid))
+                }
+            }
+        )*
+    };
+}
+
+from_datum_borrowed!(&'mcx str, &'mcx [u8], &'mcx CStr);
+
+impl<'mcx, T: FromDatum> FromDatumBorrowed<'mcx> for Array<'mcx, T> {
+    #[inline]
+    unsafe fn from_datum_borrowed(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        typoid: pg_sys::Oid,
+        memcx: &MemCx<'mcx>,
+    ) -> Option<Self> {
+        memcx.exec_in(|| FromDatum::from_polymorphic_datum(datum, is_null, typoid))
+    }
+}
+
+impl<'mcx, T: FromDatum> FromDatumBorrowed<'mcx> for VariadicArray<'mcx, T> {
+    #[inline]
+    unsafe fn from_datum_borrowed(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        typoid: pg_sys::Oid,
+        memcx: &MemCx<'mcx>,
+    ) -> Option<Self> {
+        memcx.exec_in(|| FromDatum::from_polymorphic_datum(datum, is_null, typoid))
+    }
+}
+
+/// Gets the argument of a `#[pg_extern]` function as `T`, for the code `#[pg_extern]` generates.
+///
+/// Types which implement [`FromDatumBorrowed`] are tied to the call's [`MemCx`], and
+/// [`ArgumentFallback`] converts every other type with [`FromDatum`].
+///
+/// This uses the fact that inherent implementations are a higher priority than trait
+/// implementations.
+#[doc(hidden)]
+pub struct Argument<T>(PhantomData<T>);
+
+impl<'mcx, T: FromDatumBorrowed<'mcx>> Argument<T> {
+    /// # Safety
+    ///
+    /// The same as for [`pg_getarg_borrowed`](crate::fcinfo::pg_getarg_borrowed)
+    #[inline]
+    pub unsafe fn get(
+        fcinfo: pg_sys::FunctionCallInfo,
+        num: usize,
+        memcx: &MemCx<'mcx>,
+    ) -> Option<T> {
+        crate::fcinfo::pg_getarg_borrowed(fcinfo, num, memcx)
+    }
+}
+
+#[doc(hidden)]
+pub trait ArgumentFallback {
+    type Value;
+
+    /// # Safety
+    ///
+    /// The same as for [`pg_getarg`](crate::fcinfo::pg_getarg)
+    unsafe fn get(
+        fcinfo: pg_sys::FunctionCallInfo,
+        num: usize,
+        memcx: &MemCx<'_>,
+    ) -> Option<Self::Value>;
+}
+
+impl<T: FromDatum> ArgumentFallback for Argument<T> {
+    type Value = T;
+
+    #[inline]
+    unsafe fn get(fcinfo: pg_sys::FunctionCallInfo, num: usize, _memcx: &MemCx<'_>) -> Option<T> {
+        crate::fcinfo::pg_getarg(fcinfo, num)
+    }
+}
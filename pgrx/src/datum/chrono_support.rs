@@ -0,0 +1,191 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Conversions between Postgres' date and time types and those of the [`chrono`] crate, enabled
+//! with pgrx's `chrono` feature.
+//!
+//! | Postgres                   | pgrx                      | chrono               |
+//! |----------------------------|---------------------------|----------------------|
+//! | `date`                     | [`Date`]                  | [`NaiveDate`]        |
+//! | `timestamp`                | [`Timestamp`]             | [`NaiveDateTime`]    |
+//! | `timestamp with time zone` | [`TimestampWithTimeZone`] | [`DateTime<Utc>`]    |
+//! | `interval`                 | [`Interval`]              | [`Duration`]         |
+//!
+//! The chrono types can be used directly as `#[pg_extern]` arguments and return types.  Postgres'
+//! `infinity` and `-infinity` have no chrono equivalent, and neither do chrono values outside of
+//! the range Postgres supports, so converting those raises an error.  Nor does an `interval` with
+//! months or days, whose lengths vary, so only one of exactly so many microseconds converts to a
+//! [`Duration`].  Use the `TryFrom` conversions with the pgrx types to handle them yourself.
+use super::time_stamp_with_timezone::MIN_TIMESTAMP_USEC;
+use crate::datum::datetime_support::IntervalConversionError;
+use crate::{
+    pg_sys, Date, DateTimeConversionError, FromDatum, FromTimeError, Interval, IntoDatum,
+    Timestamp, TimestampWithTimeZone,
+};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use pgrx_sql_entity_graph::metadata::{
+    ArgumentError, Returns, ReturnsError, SqlMapping, SqlTranslatable,
+};
+
+/// The earliest date Postgres supports, 4714-11-24 BC, in days since the Postgres epoch
+const MIN_DATE_PG_EPOCH_DAYS: i64 = -(pg_sys::POSTGRES_EPOCH_JDATE as i64);
+
+/// Midnight, 2000-01-01, which Postgres counts its dates and timestamps from
+fn pg_epoch() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2000, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap()
+}
+
+impl TryFrom<Date> for NaiveDate {
+    type Error = FromTimeError;
+
+    fn try_from(date: Date) -> Result<Self, Self::Error> {
+        if date.is_neg_infinity() {
+            return Err(FromTimeError::NegInfinity);
+        } else if date.is_infinity() {
+            return Err(FromTimeError::Infinity);
+        }
+        pg_epoch()
+            .date()
+            .checked_add_signed(Duration::days(date.to_pg_epoch_days() as i64))
+            .ok_or(FromTimeError::ChronoCrate)
+    }
+}
+
+impl TryFrom<NaiveDate> for Date {
+    type Error = DateTimeConversionError;
+
+    fn try_from(date: NaiveDate) -> Result<Self, Self::Error> {
+        let days = date.signed_duration_since(pg_epoch().date()).num_days();
+        if days < MIN_DATE_PG_EPOCH_DAYS {
+            return Err(DateTimeConversionError::FieldOverflow);
+        }
+        // chrono's latest date is well before Postgres'
+        Ok(Date::from(days as pg_sys::DateADT))
+    }
+}
+
+impl TryFrom<Timestamp> for NaiveDateTime {
+    type Error = FromTimeError;
+
+    fn try_from(ts: Timestamp) -> Result<Self, Self::Error> {
+        if ts.is_neg_infinity() {
+            return Err(FromTimeError::NegInfinity);
+        } else if ts.is_infinity() {
+            return Err(FromTimeError::Infinity);
+        }
+        pg_epoch()
+            .checked_add_signed(Duration::microseconds(ts.into_inner()))
+            .ok_or(FromTimeError::ChronoCrate)
+    }
+}
+
+impl TryFrom<NaiveDateTime> for Timestamp {
+    type Error = DateTimeConversionError;
+
+    fn try_from(ts: NaiveDateTime) -> Result<Self, Self::Error> {
+        let micros = ts
+            .signed_duration_since(pg_epoch())
+            .num_microseconds()
+            .ok_or(DateTimeConversionError::FieldOverflow)?;
+        if micros < MIN_TIMESTAMP_USEC {
+            return Err(DateTimeConversionError::FieldOverflow);
+        }
+        // chrono's latest timestamp is well before Postgres'
+        Ok(Timestamp::from(micros))
+    }
+}
+
+impl TryFrom<TimestampWithTimeZone> for DateTime<Utc> {
+    type Error = FromTimeError;
+
+    fn try_from(ts: TimestampWithTimeZone) -> Result<Self, Self::Error> {
+        // a `timestamp with time zone` is stored as UTC
+        let utc = NaiveDateTime::try_from(Timestamp::from(ts.into_inner()))?;
+        Ok(Utc.from_utc_datetime(&utc))
+    }
+}
+
+impl TryFrom<DateTime<Utc>> for TimestampWithTimeZone {
+    type Error = DateTimeConversionError;
+
+    fn try_from(ts: DateTime<Utc>) -> Result<Self, Self::Error> {
+        let utc = Timestamp::try_from(ts.naive_utc())?;
+        TimestampWithTimeZone::try_from(utc.into_inner())
+            .map_err(|_| DateTimeConversionError::FieldOverflow)
+    }
+}
+
+impl TryFrom<Interval> for Duration {
+    type Error = IntervalConversionError;
+
+    fn try_from(interval: Interval) -> Result<Self, Self::Error> {
+        if interval.months() != 0 || interval.days() != 0 {
+            return Err(IntervalConversionError::InexactInterval);
+        }
+        Ok(Duration::microseconds(interval.micros()))
+    }
+}
+
+impl TryFrom<Duration> for Interval {
+    type Error = IntervalConversionError;
+
+    fn try_from(duration: Duration) -> Result<Self, Self::Error> {
+        let micros =
+            duration.num_microseconds().ok_or(IntervalConversionError::DurationTooLarge)?;
+        Ok(Interval::from_micros(micros))
+    }
+}
+
+/// Implement `FromDatum`, `IntoDatum` and `SqlTranslatable` for a chrono type by way of the pgrx
+/// type for the same Postgres type
+macro_rules! via_pgrx_type {
+    ($chrono:ty, $pgrx:ty, $sql:literal) => {
+        impl FromDatum for $chrono {
+            #[inline]
+            unsafe fn from_polymorphic_datum(
+                datum: pg_sys::Datum,
+                is_null: bool,
+                typoid: pg_sys::Oid,
+            ) -> Option<Self> {
+                <$pgrx>::from_polymorphic_datum(datum, is_null, typoid).map(|value| {
+                    value.try_into().unwrap_or_else(|e| {
+                        panic!("cannot convert `{}` to `{}`: {e}", $sql, stringify!($chrono))
+                    })
+                })
+            }
+        }
+
+        impl IntoDatum for $chrono {
+            #[inline]
+            fn into_datum(self) -> Option<pg_sys::Datum> {
+                <$pgrx>::try_from(self)
+                    .unwrap_or_else(|e| panic!("cannot convert `{self}` to `{}`: {e}", $sql))
+                    .into_datum()
+            }
+
+            fn type_oid() -> pg_sys::Oid {
+                <$pgrx>::type_oid()
+            }
+        }
+
+        unsafe impl SqlTranslatable for $chrono {
+            fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+                Ok(SqlMapping::literal($sql))
+            }
+            fn return_sql() -> Result<Returns, ReturnsError> {
+                Ok(Returns::One(SqlMapping::literal($sql)))
+            }
+        }
+    };
+}
+
+via_pgrx_type!(NaiveDate, Date, "date");
+via_pgrx_type!(NaiveDateTime, Timestamp, "timestamp");
+via_pgrx_type!(DateTime<Utc>, TimestampWithTimeZone, "timestamp with time zone");
+via_pgrx_type!(Duration, Interval, "interval");
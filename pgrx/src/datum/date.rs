@@ -168,18 +168,54 @@ impl Date {
     }
 
     /// Extract the `month`
+    ///
+    /// # Panics
+    ///
+    /// Panics if this [`Date`] is infinite.  Use [`Date::try_month`] to handle that case.
     pub fn month(&self) -> u8 {
-        self.extract_part(DateTimeParts::Month).unwrap().try_into().unwrap()
+        self.try_month().expect("infinite Date has no month")
+    }
+
+    /// Like [`Date::month`], but returns `None` if this [`Date`] is infinite
+    pub fn try_month(&self) -> Option<u8> {
+        if self.is_infinite() {
+            return None;
+        }
+        self.extract_part(DateTimeParts::Month).map(|part| part.try_into().unwrap())
     }
 
     /// Extract the `day`
+    ///
+    /// # Panics
+    ///
+    /// Panics if this [`Date`] is infinite.  Use [`Date::try_day`] to handle that case.
     pub fn day(&self) -> u8 {
-        self.extract_part(DateTimeParts::Day).unwrap().try_into().unwrap()
+        self.try_day().expect("infinite Date has no day")
+    }
+
+    /// Like [`Date::day`], but returns `None` if this [`Date`] is infinite
+    pub fn try_day(&self) -> Option<u8> {
+        if self.is_infinite() {
+            return None;
+        }
+        self.extract_part(DateTimeParts::Day).map(|part| part.try_into().unwrap())
     }
 
     /// Extract the `year`
+    ///
+    /// # Panics
+    ///
+    /// Panics if this [`Date`] is infinite.  Use [`Date::try_year`] to handle that case.
     pub fn year(&self) -> i32 {
-        self.extract_part(DateTimeParts::Year).unwrap().try_into().unwrap()
+        self.try_year().expect("infinite Date has no year")
+    }
+
+    /// Like [`Date::year`], but returns `None` if this [`Date`] is infinite
+    pub fn try_year(&self) -> Option<i32> {
+        if self.is_infinite() {
+            return None;
+        }
+        self.extract_part(DateTimeParts::Year).map(|part| part.try_into().unwrap())
     }
 
     /// Does this [`Date`] represent positive infinity?
@@ -194,10 +230,22 @@ impl Date {
         self.0 == Self::NEG_INFINITY
     }
 
+    /// Does this [`Date`] represent either positive or negative infinity?
+    #[inline]
+    pub fn is_infinite(&self) -> bool {
+        self.is_infinity() || self.is_neg_infinity()
+    }
+
     /// Return the Julian days value of this [`Date`]
+    ///
+    /// Positive and negative infinity are returned as [`i32::MAX`] and [`i32::MIN`].
     #[inline]
     pub fn to_julian_days(&self) -> i32 {
-        self.0 + POSTGRES_EPOCH_JDATE
+        if self.is_infinite() {
+            self.0
+        } else {
+            self.0 + POSTGRES_EPOCH_JDATE
+        }
     }
 
     /// Return the Postgres epoch days value of this [`Date`]
@@ -207,21 +255,37 @@ impl Date {
     }
 
     /// Returns the date as an i32 representing the elapsed time since UNIX epoch in days
+    ///
+    /// Positive and negative infinity are returned as [`i32::MAX`] and [`i32::MIN`].
     #[inline]
     pub fn to_unix_epoch_days(&self) -> i32 {
-        self.0 + POSTGRES_EPOCH_JDATE - UNIX_EPOCH_JDATE
+        if self.is_infinite() {
+            self.0
+        } else {
+            self.0 + POSTGRES_EPOCH_JDATE - UNIX_EPOCH_JDATE
+        }
     }
 
     /// Return the date as a stack-allocated [`libc::time_t`] instance
+    ///
+    /// Positive and negative infinity are returned as [`libc::time_t::MAX`] and
+    /// [`libc::time_t::MIN`].
     #[inline]
     pub fn to_posix_time(&self) -> libc::time_t {
+        if self.is_infinity() {
+            return libc::time_t::MAX;
+        } else if self.is_neg_infinity() {
+            return libc::time_t::MIN;
+        }
         let secs_per_day: libc::time_t =
             pg_sys::SECS_PER_DAY.try_into().expect("couldn't fit time into time_t");
         libc::time_t::from(self.to_unix_epoch_days()) * secs_per_day
     }
 
+    /// Is this [`Date`] neither positive nor negative infinity?
+    #[inline]
     pub fn is_finite(&self) -> bool {
-        unsafe { direct_function_call(pg_sys::date_finite, &[self.into_datum()]).unwrap() }
+        !self.is_infinite()
     }
 
     /// Return the backing [`pg_sy::DateADT`] value.
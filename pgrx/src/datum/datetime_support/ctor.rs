@@ -9,10 +9,16 @@
 //LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
 //! Exposes constructor methods for creating [`TimestampWithTimeZone`]s based on the various
 //! ways Postgres likes to interpret the "current time".
+//!
+//! Those which report the current transaction, statement or wall-clock time follow the
+//! [fake clock][crate::fake_clock] instead, when it's pinned.
 use crate::{direct_function_call, pg_sys, Date, IntoDatum, Timestamp, TimestampWithTimeZone};
 
 /// Current date and time (start of current transaction)
 pub fn now() -> TimestampWithTimeZone {
+    if let Some(pinned) = crate::fake_clock::pinned() {
+        return pinned;
+    }
     unsafe { pg_sys::GetCurrentTransactionStartTimestamp().try_into().unwrap() }
 }
 
@@ -25,6 +31,9 @@ pub fn transaction_timestamp() -> TimestampWithTimeZone {
 
 /// Current date and time (start of current statement)
 pub fn statement_timestamp() -> TimestampWithTimeZone {
+    if let Some(pinned) = crate::fake_clock::pinned() {
+        return pinned;
+    }
     unsafe { pg_sys::GetCurrentStatementStartTimestamp().try_into().unwrap() }
 }
 
@@ -33,6 +42,9 @@ pub fn statement_timestamp() -> TimestampWithTimeZone {
 /// Result is in the form of a [`TimestampWithTimeZone`] value, and is expressed to the
 /// full precision of the `gettimeofday()` syscall
 pub fn clock_timestamp() -> TimestampWithTimeZone {
+    if let Some(pinned) = crate::fake_clock::pinned() {
+        return pinned;
+    }
     unsafe { pg_sys::GetCurrentTimestamp().try_into().unwrap() }
 }
 
@@ -21,9 +21,11 @@ use std::marker::PhantomData;
 
 mod ctor;
 mod ops;
+mod timezone;
 
 pub use ctor::*;
 pub use ops::*;
+pub use timezone::*;
 
 /// Tags to identify which "part" of a date or time-type value to extract or truncate to
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -695,6 +697,10 @@ pub enum IntervalConversionError {
     NegativeInterval,
     #[error("Interval overflows Duration's u64 micros constructor")]
     IntervalTooLarge,
+    #[error("Intervals with months or days have no fixed length, and cannot be converted into Durations")]
+    InexactInterval,
+    #[error("Duration overflows an Interval's i64 microseconds")]
+    DurationTooLarge,
 }
 
 #[derive(thiserror::Error, Debug, PartialEq, Eq)]
@@ -366,3 +366,39 @@ impl Sub<Interval> for TimeWithTimeZone {
         }
     }
 }
+
+impl Add<std::time::Duration> for Timestamp {
+    type Output = Timestamp;
+
+    fn add(self, rhs: std::time::Duration) -> Self::Output {
+        self + duration_to_interval(rhs)
+    }
+}
+
+impl Sub<std::time::Duration> for Timestamp {
+    type Output = Timestamp;
+
+    fn sub(self, rhs: std::time::Duration) -> Self::Output {
+        self - duration_to_interval(rhs)
+    }
+}
+
+impl Add<std::time::Duration> for TimestampWithTimeZone {
+    type Output = TimestampWithTimeZone;
+
+    fn add(self, rhs: std::time::Duration) -> Self::Output {
+        self + duration_to_interval(rhs)
+    }
+}
+
+impl Sub<std::time::Duration> for TimestampWithTimeZone {
+    type Output = TimestampWithTimeZone;
+
+    fn sub(self, rhs: std::time::Duration) -> Self::Output {
+        self - duration_to_interval(rhs)
+    }
+}
+
+fn duration_to_interval(duration: std::time::Duration) -> Interval {
+    Interval::try_from(duration).expect("Duration overflows an Interval's i64 microseconds")
+}
@@ -0,0 +1,216 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Access to the server's timezone database
+use crate::{pg_sys, DateTimeConversionError, Timestamp, TimestampWithTimeZone};
+use core::ffi::CStr;
+use core::fmt::{Debug, Formatter};
+use core::ptr::NonNull;
+
+/// A timezone from the server's timezone database, the same one Postgres uses for `timestamptz`
+///
+/// Doing timezone conversions through Postgres keeps them consistent with SQL, including across
+/// changes to daylight saving rules, rather than depending on whichever tzdata a crate like
+/// `chrono-tz` bundled.
+///
+/// Postgres caches loaded timezones for the life of the backend, so a `TimeZone` can be kept
+/// around freely.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct TimeZone(NonNull<pg_sys::pg_tz>);
+
+impl TimeZone {
+    /// The session's `TimeZone` setting, which `timestamptz` values are displayed in
+    pub fn session() -> Self {
+        unsafe { TimeZone(NonNull::new(pg_sys::session_timezone).expect("no session timezone")) }
+    }
+
+    /// The server's `log_timezone` setting
+    pub fn log() -> Self {
+        unsafe { TimeZone(NonNull::new(pg_sys::log_timezone).expect("no log timezone")) }
+    }
+
+    /// Look up a timezone by name, such as `America/New_York` or `UTC`
+    ///
+    /// Abbreviations such as `EST` are only found if the database has a zone of that name.  Use
+    /// [`get_timezone_offset`](crate::get_timezone_offset) for those.
+    pub fn lookup<Tz: AsRef<str>>(name: Tz) -> Result<Self, DateTimeConversionError> {
+        let name = name.as_ref();
+        let unknown = || DateTimeConversionError::UnknownTimezone(name.to_string());
+        let c_name = alloc::ffi::CString::new(name).map_err(|_| unknown())?;
+        unsafe { NonNull::new(pg_sys::pg_tzset(c_name.as_ptr())).map(TimeZone).ok_or_else(unknown) }
+    }
+
+    /// A timezone at a fixed offset from UTC, in seconds east of Greenwich
+    pub fn fixed_offset(utc_offset: i32) -> Self {
+        // Postgres takes the offset in the POSIX sense, seconds west of Greenwich
+        unsafe {
+            TimeZone(
+                NonNull::new(pg_sys::pg_tzset_offset(-utc_offset as _))
+                    .expect("invalid timezone offset"),
+            )
+        }
+    }
+
+    /// The names of all the timezones in the database that can be used as the `TimeZone` setting,
+    /// as the `pg_timezone_names` view lists them
+    pub fn names() -> Vec<String> {
+        let mut names = Vec::new();
+        unsafe {
+            let tzenum = pg_sys::pg_tzenumerate_start();
+            loop {
+                // the enumerator reuses its `pg_tz`, so only the name is kept
+                let tz = pg_sys::pg_tzenumerate_next(tzenum);
+                if tz.is_null() {
+                    break;
+                }
+                if pg_sys::pg_tz_acceptable(tz) {
+                    names.push(
+                        CStr::from_ptr(pg_sys::pg_get_timezone_name(tz))
+                            .to_string_lossy()
+                            .into_owned(),
+                    );
+                }
+            }
+            pg_sys::pg_tzenumerate_end(tzenum);
+        }
+        names
+    }
+
+    /// The timezone's name, as it was looked up
+    pub fn name(&self) -> &str {
+        unsafe {
+            CStr::from_ptr(pg_sys::pg_get_timezone_name(self.0.as_ptr()))
+                .to_str()
+                .expect("timezone name is not UTF8")
+        }
+    }
+
+    /// Can this timezone be used as the `TimeZone` setting?  Postgres rejects zones using leap
+    /// seconds
+    pub fn is_acceptable(&self) -> bool {
+        unsafe { pg_sys::pg_tz_acceptable(self.0.as_ptr()) }
+    }
+
+    /// The timezone's offset from UTC at the instant `ts`, in seconds east of Greenwich
+    pub fn utc_offset_at(&self, ts: TimestampWithTimeZone) -> Result<i32, DateTimeConversionError> {
+        Ok(self.decompose(ts)?.utc_offset)
+    }
+
+    /// Is daylight saving time in effect in this timezone at the instant `ts`?
+    pub fn is_dst_at(&self, ts: TimestampWithTimeZone) -> Result<bool, DateTimeConversionError> {
+        Ok(self.decompose(ts)?.tm.tm_isdst > 0)
+    }
+
+    /// The timezone's abbreviation at the instant `ts`, such as `EST` or `EDT`
+    pub fn abbreviation_at(
+        &self,
+        ts: TimestampWithTimeZone,
+    ) -> Result<String, DateTimeConversionError> {
+        Ok(self.decompose(ts)?.abbreviation)
+    }
+
+    /// The local time in this timezone at the instant `ts`
+    pub fn to_local(
+        &self,
+        ts: TimestampWithTimeZone,
+    ) -> Result<Timestamp, DateTimeConversionError> {
+        if !ts.is_finite() {
+            return Ok(Timestamp::from(ts.into_inner()));
+        }
+        let mut parts = self.decompose(ts)?;
+        let mut local = 0;
+        unsafe {
+            if pg_sys::tm2timestamp(&mut parts.tm, parts.fsec, std::ptr::null_mut(), &mut local)
+                != 0
+            {
+                return Err(DateTimeConversionError::FieldOverflow);
+            }
+        }
+        Ok(Timestamp::from(local))
+    }
+
+    /// The instant at which the local time in this timezone is `ts`
+    ///
+    /// Local times which are skipped or repeated by a daylight saving transition are resolved the
+    /// same way Postgres resolves them for `timestamp AT TIME ZONE`.
+    pub fn from_local(
+        &self,
+        ts: Timestamp,
+    ) -> Result<TimestampWithTimeZone, DateTimeConversionError> {
+        let overflow = |_| DateTimeConversionError::FieldOverflow;
+        if !ts.is_finite() {
+            return TimestampWithTimeZone::try_from(ts.into_inner()).map_err(overflow);
+        }
+        let mut tm = pg_sys::pg_tm::default();
+        let mut fsec = 0;
+        let mut instant = 0;
+        unsafe {
+            if pg_sys::timestamp2tm(
+                ts.into_inner(),
+                std::ptr::null_mut(),
+                &mut tm,
+                &mut fsec,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            ) != 0
+            {
+                return Err(DateTimeConversionError::FieldOverflow);
+            }
+            let mut tz = pg_sys::DetermineTimeZoneOffset(&mut tm, self.0.as_ptr());
+            if pg_sys::tm2timestamp(&mut tm, fsec, &mut tz, &mut instant) != 0 {
+                return Err(DateTimeConversionError::FieldOverflow);
+            }
+        }
+        TimestampWithTimeZone::try_from(instant).map_err(overflow)
+    }
+
+    fn decompose(&self, ts: TimestampWithTimeZone) -> Result<Parts, DateTimeConversionError> {
+        if !ts.is_finite() {
+            return Err(DateTimeConversionError::FieldOverflow);
+        }
+        let mut tz = 0;
+        let mut tm = pg_sys::pg_tm::default();
+        let mut fsec = 0;
+        let mut tzn = std::ptr::null();
+        unsafe {
+            if pg_sys::timestamp2tm(
+                ts.into_inner(),
+                &mut tz,
+                &mut tm,
+                &mut fsec,
+                &mut tzn,
+                self.0.as_ptr(),
+            ) != 0
+            {
+                return Err(DateTimeConversionError::FieldOverflow);
+            }
+            let abbreviation = if tzn.is_null() {
+                String::new()
+            } else {
+                CStr::from_ptr(tzn).to_string_lossy().into_owned()
+            };
+            Ok(Parts { tm, fsec, utc_offset: -tz, abbreviation })
+        }
+    }
+}
+
+impl Debug for TimeZone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("TimeZone").field(&self.name()).finish()
+    }
+}
+
+/// A `timestamptz` broken down in some timezone
+struct Parts {
+    tm: pg_sys::pg_tm,
+    fsec: pg_sys::fsec_t,
+    /// seconds east of Greenwich
+    utc_offset: i32,
+    abbreviation: String,
+}
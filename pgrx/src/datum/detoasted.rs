@@ -0,0 +1,136 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! A cache of detoasted values, for functions which look at the same toasted value many times
+use crate::{pg_sys, varlena, FromDatum, PgMemoryContexts};
+use core::cell::RefCell;
+use core::marker::PhantomData;
+use std::collections::HashMap;
+
+/// Detoasts each distinct varlena [`pg_sys::Datum`] it's given at most once, and hands out `T`s
+/// built from the detoasted copy on every later request for the same Datum.
+///
+/// Converting a toasted Datum with [`FromDatum`] fetches it from its toast table and decompresses
+/// it every time, so a function which walks over the pieces of one large value, or reads the same
+/// attribute out of a tuple in a loop, pays that cost over and over.  A `Detoasted` remembers the
+/// detoasted copy of each Datum and converts from that instead.  Values still in their toast table
+/// are recognized by their toast pointer, so the same value read out of two different tuples is
+/// only fetched once.  Any other Datum is recognized by its pointer and size, which is as much as
+/// can be known about it without reading all of it.
+///
+/// The copies are allocated in the memory context which was current when the `Detoasted` was made,
+/// which for a `#[pg_extern]` function is the one Postgres frees once the call returns.  A
+/// `Detoasted` should not outlive that call.
+///
+/// ```rust,no_run
+/// use pgrx::prelude::*;
+/// use pgrx::Detoasted;
+///
+/// # fn doc(datum: pg_sys::Datum) {
+/// let cache = Detoasted::<&str>::new();
+/// for _ in 0..10 {
+///     // only the first call reads the value out of its toast table
+///     let text = unsafe { cache.get(datum, false) };
+/// }
+/// # }
+/// ```
+pub struct Detoasted<T> {
+    memcxt: pg_sys::MemoryContext,
+    copies: RefCell<HashMap<Key, pg_sys::Datum>>,
+    __marker: PhantomData<T>,
+}
+
+impl<T: FromDatum> Detoasted<T> {
+    /// Make an empty cache whose detoasted copies will live in the `CurrentMemoryContext`
+    pub fn new() -> Self {
+        Detoasted {
+            memcxt: PgMemoryContexts::CurrentMemoryContext.value(),
+            copies: Default::default(),
+            __marker: PhantomData,
+        }
+    }
+
+    /// Convert `datum` to a `T`, detoasting it first unless it was already detoasted by this cache.
+    ///
+    /// # Safety
+    ///
+    /// `datum` must be a valid varlena Datum of a type `T` can be made from, and the result must not
+    /// be used after the memory context this `Detoasted` was made in has been reset or deleted.
+    pub unsafe fn get(&self, datum: pg_sys::Datum, is_null: bool) -> Option<T> {
+        if is_null || datum.is_null() {
+            return None;
+        }
+        let detoasted = self.detoast(datum);
+        T::from_datum(detoasted, false)
+    }
+
+    /// The detoasted copy of `datum`, which is `datum` itself when it was never toasted.
+    ///
+    /// # Safety
+    ///
+    /// `datum` must be a valid, non-null varlena Datum.
+    pub unsafe fn detoast(&self, datum: pg_sys::Datum) -> pg_sys::Datum {
+        *self.copies.borrow_mut().entry(Key::of(datum)).or_insert_with(|| {
+            PgMemoryContexts::For(self.memcxt).switch_to(|_| {
+                pg_sys::Datum::from(pg_sys::pg_detoast_datum_packed(datum.cast_mut_ptr()))
+            })
+        })
+    }
+
+    /// How many distinct Datums this cache has detoasted
+    pub fn len(&self) -> usize {
+        self.copies.borrow().len()
+    }
+
+    /// Has this cache not yet been asked for anything?
+    pub fn is_empty(&self) -> bool {
+        self.copies.borrow().is_empty()
+    }
+
+    /// Forget every detoasted copy.  The copies themselves stay allocated until their memory context
+    /// goes away, as `T`s handed out earlier may still point into them.
+    pub fn clear(&mut self) {
+        self.copies.get_mut().clear();
+    }
+}
+
+/// What a [`Detoasted`] remembers a Datum by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Key {
+    /// A value stored in a toast table, which the toast pointer identifies wherever it's read from
+    OnDisk { toastrelid: pg_sys::Oid, valueid: pg_sys::Oid },
+    /// Anything else.  A pointer alone could be reused for a different value once the memory it
+    /// pointed to is freed, so the value's size goes into the key too
+    InMemory { ptr: usize, size: usize },
+}
+
+impl Key {
+    /// # Safety
+    ///
+    /// `datum` must be a valid, non-null varlena Datum.
+    unsafe fn of(datum: pg_sys::Datum) -> Key {
+        let ptr = datum.cast_mut_ptr::<pg_sys::varlena>();
+        if varlena::varatt_is_1b_e(ptr)
+            && varlena::vartag_external(ptr) as pg_sys::vartag_external
+                == pg_sys::vartag_external_VARTAG_ONDISK
+        {
+            let external =
+                varlena::vardata_1b_e(ptr).cast::<pg_sys::varatt_external>().read_unaligned();
+            Key::OnDisk { toastrelid: external.va_toastrelid, valueid: external.va_valueid }
+        } else {
+            Key::InMemory { ptr: datum.value(), size: varlena::varsize_any(ptr) }
+        }
+    }
+}
+
+impl<T: FromDatum> Default for Detoasted<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
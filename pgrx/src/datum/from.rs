@@ -14,6 +14,7 @@ use crate::{
 };
 use core::ffi::CStr;
 use pgrx_pg_sys::{Datum, Oid};
+use std::borrow::Cow;
 use std::num::NonZeroUsize;
 
 /// If converting a Datum to a Rust type fails, this is the set of possible reasons why.
@@ -32,6 +33,12 @@ pub enum TryFromDatumError {
 
     #[error("The specified attribute name `{0}` is not present")]
     NoSuchAttributeName(String),
+
+    #[error("The datum is not valid UTF-8")]
+    InvalidUtf8,
+
+    #[error("The value for attribute `{attname}` does not fit its type: {message}")]
+    TypmodViolation { attname: String, message: String },
 }
 
 /// Convert a `(pg_sys::Datum, is_null:bool)` pair into a Rust type
@@ -59,6 +66,7 @@ pub trait FromDatum {
     where
         Self: Sized,
     {
+        crate::fault_injection::fault_point(crate::fault_injection::DATUM);
         FromDatum::from_polymorphic_datum(datum, is_null, pg_sys::InvalidOid)
     }
 
@@ -105,10 +113,15 @@ pub trait FromDatum {
     /// a `Result` around an `Option`, as a Datum can be null.  It's intended to be used in
     /// situations where the caller needs to know whether the type conversion succeeded or failed.
     ///
+    /// The text types (`&str`, `String`, and `Cow<'_, str>`) override this so it never panics, and
+    /// returns [`TryFromDatumError::InvalidUtf8`] for text which isn't valid UTF-8.  Other types
+    /// may still panic on a malformed Datum, as their `from_polymorphic_datum()` does.
+    ///
     /// ## Safety
     ///
     /// Same caveats as `FromDatum::from_datum(...)`
     #[inline]
+    #[track_caller]
     unsafe fn try_from_datum(
         datum: pg_sys::Datum,
         is_null: bool,
@@ -117,7 +130,9 @@ pub trait FromDatum {
     where
         Self: Sized + IntoDatum,
     {
+        crate::fault_injection::fault_point(crate::fault_injection::DATUM);
         if !is_binary_coercible::<Self>(type_oid) {
+            crate::datum_diagnostics::incompatible_types::<Self>(Self::type_oid(), type_oid, -1);
             Err(TryFromDatumError::IncompatibleTypes {
                 rust_type: std::any::type_name::<Self>(),
                 rust_oid: Self::type_oid(),
@@ -125,12 +140,17 @@ pub trait FromDatum {
                 datum_oid: type_oid,
             })
         } else {
-            Ok(FromDatum::from_polymorphic_datum(datum, is_null, type_oid))
+            let value = FromDatum::from_polymorphic_datum(datum, is_null, type_oid);
+            if value.is_none() && !is_null {
+                crate::datum_diagnostics::no_value::<Self>(type_oid, -1);
+            }
+            Ok(value)
         }
     }
 
     /// A version of `try_from_datum` that switches to the given context to convert from Datum
     #[inline]
+    #[track_caller]
     unsafe fn try_from_datum_in_memory_context(
         memory_context: PgMemoryContexts,
         datum: pg_sys::Datum,
@@ -141,6 +161,7 @@ pub trait FromDatum {
         Self: Sized + IntoDatum,
     {
         if !is_binary_coercible::<Self>(type_oid) {
+            crate::datum_diagnostics::incompatible_types::<Self>(Self::type_oid(), type_oid, -1);
             Err(TryFromDatumError::IncompatibleTypes {
                 rust_type: std::any::type_name::<Self>(),
                 rust_oid: Self::type_oid(),
@@ -148,12 +169,17 @@ pub trait FromDatum {
                 datum_oid: type_oid,
             })
         } else {
-            Ok(FromDatum::from_datum_in_memory_context(memory_context, datum, is_null, type_oid))
+            let value =
+                FromDatum::from_datum_in_memory_context(memory_context, datum, is_null, type_oid);
+            if value.is_none() && !is_null {
+                crate::datum_diagnostics::no_value::<Self>(type_oid, -1);
+            }
+            Ok(value)
         }
     }
 }
 
-fn is_binary_coercible<T: IntoDatum>(type_oid: pg_sys::Oid) -> bool {
+pub(crate) fn is_binary_coercible<T: IntoDatum>(type_oid: pg_sys::Oid) -> bool {
     T::is_compatible_with(type_oid) || unsafe { pg_sys::IsBinaryCoercible(type_oid, T::type_oid()) }
 }
 
@@ -335,9 +361,13 @@ impl FromDatum for f64 {
 
 /// For Postgres text and varchar
 ///
-/// Note that while these conversions are inherently unsafe, they still enforce
-/// UTF-8 correctness, so they may panic if you use PGX with a database
+/// In a database whose encoding isn't UTF-8, text with any non-ASCII characters is transcoded
+/// into UTF-8 by Postgres.  Note that while these conversions are inherently unsafe, they still
+/// enforce UTF-8 correctness, so they may panic if you use PGX with a `SQL_ASCII` database
 /// that has non-UTF-8 data. The details of this are subject to change.
+///
+/// [`FromDatum::try_from_datum`] never panics, and checks that the text is valid UTF-8 whatever
+/// the database's encoding, returning [`TryFromDatumError::InvalidUtf8`] if it isn't.
 impl<'a> FromDatum for &'a str {
     #[inline]
     unsafe fn from_polymorphic_datum(
@@ -359,6 +389,15 @@ impl<'a> FromDatum for &'a str {
         }
     }
 
+    #[track_caller]
+    unsafe fn try_from_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        type_oid: pg_sys::Oid,
+    ) -> Result<Option<Self>, TryFromDatumError> {
+        try_text_from_datum::<Self>(datum, is_null, type_oid)
+    }
+
     unsafe fn from_datum_in_memory_context(
         mut memory_context: PgMemoryContexts,
         datum: pg_sys::Datum,
@@ -387,28 +426,68 @@ impl<'a> FromDatum for &'a str {
 
 // This is not marked inline on purpose, to allow it to be in a single code section
 // which is then branch-predicted on every time by the CPU.
-unsafe fn convert_varlena_to_str_memoized<'a>(varlena: *const pg_sys::varlena) -> &'a str {
+pub(crate) unsafe fn convert_varlena_to_str_memoized<'a>(
+    varlena: *const pg_sys::varlena,
+) -> &'a str {
     match *crate::UTF8DATABASE {
         crate::Utf8Compat::Yes => varlena::text_to_rust_str_unchecked(varlena),
-        crate::Utf8Compat::Maybe => varlena::text_to_rust_str(varlena)
+        // anything else is transcoded, or needs checking
+        crate::Utf8Compat::Maybe | crate::Utf8Compat::Ascii => varlena::text_to_rust_str(varlena)
             .expect("datums converted to &str should be valid UTF-8"),
-        crate::Utf8Compat::Ascii => {
-            let bytes = varlena_to_byte_slice(varlena);
-            if bytes.is_ascii() {
-                core::str::from_utf8_unchecked(bytes)
-            } else {
-                panic!("datums converted to &str should be valid UTF-8, database encoding is only UTF-8 compatible for ASCII")
-            }
-        }
     }
 }
 
+/// The fallible core of [`FromDatum::try_from_datum`] for `&str` and `String`
+///
+/// Unlike [`FromDatum::from_datum`], this checks that the text is valid UTF-8 whatever the
+/// database's encoding, so text made from arbitrary bytes is an error rather than undefined
+/// behavior.
+///
+/// # Safety
+///
+/// The same as for [`FromDatum::try_from_datum`].  The result borrows from the detoasted Datum,
+/// or from a transcoded copy of it, both in `CurrentMemoryContext`, so `'a` must not outlive it.
+#[track_caller]
+unsafe fn try_text_from_datum<'a, T: IntoDatum>(
+    datum: pg_sys::Datum,
+    is_null: bool,
+    type_oid: pg_sys::Oid,
+) -> Result<Option<&'a str>, TryFromDatumError> {
+    if !is_binary_coercible::<T>(type_oid) {
+        crate::datum_diagnostics::incompatible_types::<T>(T::type_oid(), type_oid, -1);
+        Err(TryFromDatumError::IncompatibleTypes {
+            rust_type: std::any::type_name::<T>(),
+            rust_oid: T::type_oid(),
+            datum_type: lookup_type_name(type_oid),
+            datum_oid: type_oid,
+        })
+    } else if is_null || datum.is_null() {
+        Ok(None)
+    } else {
+        let varlena = pg_sys::pg_detoast_datum_packed(datum.cast_mut_ptr());
+        try_convert_varlena_to_str(varlena).map(Some)
+    }
+}
+
+/// Convert a text varlena to a `&str`, checking that it's valid UTF-8 whatever the database's
+/// encoding
+///
+/// # Safety
+///
+/// The same as for [`varlena::text_to_rust_str`]
+unsafe fn try_convert_varlena_to_str<'a>(
+    varlena: *const pg_sys::varlena,
+) -> Result<&'a str, TryFromDatumError> {
+    varlena::text_to_rust_str(varlena).map_err(|_| TryFromDatumError::InvalidUtf8)
+}
+
 /// For Postgres text, varchar, or any `pg_sys::varlena`-based type
 ///
 /// This returns a **copy**, allocated and managed by Rust, of the underlying `varlena` Datum
 ///
-/// Note that while these conversions are inherently unsafe, they still enforce
-/// UTF-8 correctness, so they may panic if you use PGX with a database
+/// In a database whose encoding isn't UTF-8, text with any non-ASCII characters is transcoded
+/// into UTF-8 by Postgres.  Note that while these conversions are inherently unsafe, they still
+/// enforce UTF-8 correctness, so they may panic if you use PGX with a `SQL_ASCII` database
 /// that has non-UTF-8 data. The details of this are subject to change.
 impl FromDatum for String {
     #[inline]
@@ -419,6 +498,15 @@ impl FromDatum for String {
     ) -> Option<String> {
         FromDatum::from_polymorphic_datum(datum, is_null, typoid).map(|s: &str| s.to_owned())
     }
+
+    #[track_caller]
+    unsafe fn try_from_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        type_oid: pg_sys::Oid,
+    ) -> Result<Option<Self>, TryFromDatumError> {
+        try_text_from_datum::<Self>(datum, is_null, type_oid).map(|s| s.map(str::to_owned))
+    }
 }
 
 impl FromDatum for char {
@@ -468,6 +556,22 @@ impl<'a> FromDatum for &'a core::ffi::CStr {
     }
 }
 
+/// for cstring, copied into Rust-owned memory
+impl FromDatum for alloc::ffi::CString {
+    #[inline]
+    unsafe fn from_polymorphic_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        _: pg_sys::Oid,
+    ) -> Option<alloc::ffi::CString> {
+        if is_null || datum.is_null() {
+            None
+        } else {
+            Some(core::ffi::CStr::from_ptr(datum.cast_mut_ptr()).to_owned())
+        }
+    }
+}
+
 /// for bytea
 impl<'a> FromDatum for &'a [u8] {
     #[inline]
@@ -533,6 +637,105 @@ impl FromDatum for Vec<u8> {
     }
 }
 
+/// Is the varlena at `ptr` compressed or stored out-of-line, so its bytes can't be read in place?
+///
+/// Short, inline values aren't, as `pg_detoast_datum_packed()` would leave them where they are.
+unsafe fn is_toasted(ptr: *const pg_sys::varlena) -> bool {
+    varlena::varatt_is_1b_e(ptr) || varlena::varatt_is_b8_c(ptr)
+}
+
+/// Pass a detoasted copy of the varlena at `ptr` to `f`, and free the copy afterwards
+unsafe fn with_detoasted<R>(
+    ptr: *mut pg_sys::varlena,
+    f: impl FnOnce(*const pg_sys::varlena) -> R,
+) -> R {
+    let detoasted = pg_sys::pg_detoast_datum_packed(ptr);
+    let result = f(detoasted);
+    pg_sys::pfree(detoasted.cast());
+    result
+}
+
+/// for text, varchar
+///
+/// This borrows the value when it's stored inline and uncompressed, and otherwise detoasts it into
+/// an owned `String`, so the only allocations are for values which had to be detoasted anyway.
+impl<'a> FromDatum for Cow<'a, str> {
+    #[inline]
+    unsafe fn from_polymorphic_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        _: pg_sys::Oid,
+    ) -> Option<Cow<'a, str>> {
+        if is_null || datum.is_null() {
+            None
+        } else {
+            let ptr = datum.cast_mut_ptr();
+            if is_toasted(ptr) {
+                let s = with_detoasted(ptr, |varlena| {
+                    convert_varlena_to_str_memoized(varlena).to_owned()
+                });
+                Some(Cow::Owned(s))
+            } else {
+                Some(Cow::Borrowed(convert_varlena_to_str_memoized(ptr)))
+            }
+        }
+    }
+
+    #[track_caller]
+    unsafe fn try_from_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        type_oid: pg_sys::Oid,
+    ) -> Result<Option<Self>, TryFromDatumError> {
+        if !is_binary_coercible::<Self>(type_oid) {
+            crate::datum_diagnostics::incompatible_types::<Self>(Self::type_oid(), type_oid, -1);
+            Err(TryFromDatumError::IncompatibleTypes {
+                rust_type: std::any::type_name::<Self>(),
+                rust_oid: Self::type_oid(),
+                datum_type: lookup_type_name(type_oid),
+                datum_oid: type_oid,
+            })
+        } else if is_null || datum.is_null() {
+            Ok(None)
+        } else {
+            let ptr = datum.cast_mut_ptr();
+            if is_toasted(ptr) {
+                with_detoasted(ptr, |varlena| {
+                    try_convert_varlena_to_str(varlena).map(|s| Some(Cow::Owned(s.to_owned())))
+                })
+            } else {
+                try_convert_varlena_to_str(ptr).map(|s| Some(Cow::Borrowed(s)))
+            }
+        }
+    }
+}
+
+/// for bytea
+///
+/// This borrows the value when it's stored inline and uncompressed, and otherwise detoasts it into
+/// an owned `Vec<u8>`, so the only allocations are for values which had to be detoasted anyway.
+impl<'a> FromDatum for Cow<'a, [u8]> {
+    #[inline]
+    unsafe fn from_polymorphic_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        _typoid: pg_sys::Oid,
+    ) -> Option<Cow<'a, [u8]>> {
+        if is_null || datum.is_null() {
+            None
+        } else {
+            let ptr = datum.cast_mut_ptr();
+            if is_toasted(ptr) {
+                Some(Cow::Owned(with_detoasted(ptr, |varlena| {
+                    varlena_to_byte_slice(varlena).to_vec()
+                })))
+            } else {
+                Some(Cow::Borrowed(varlena_to_byte_slice(ptr)))
+            }
+        }
+    }
+}
+
 /// for VOID -- always converts to `Some(())`, even if the "is_null" argument is true
 impl FromDatum for () {
     #[inline]
@@ -7,68 +7,196 @@
 //LICENSE All rights reserved.
 //LICENSE
 //LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
-use crate::{pg_sys, FromDatum, IntoDatum, PgMemoryContexts};
+//! Postgres' geometric types
+//!
+//! The fixed-size types, `point`, `box`, `lseg`, and `circle`, convert to and from their
+//! `pg_sys` structs.  The variable-length `path` and `polygon` convert to and from [`Path`] and
+//! [`Polygon`].
+use crate::{pg_sys, varlena, FromDatum, IntoDatum, PgMemoryContexts};
+use pgrx_sql_entity_graph::metadata::{
+    ArgumentError, Returns, ReturnsError, SqlMapping, SqlTranslatable,
+};
 
-impl FromDatum for pg_sys::BOX {
+macro_rules! fixed_size_geo {
+    ($ty:ty, $oid:ident) => {
+        impl FromDatum for $ty {
+            unsafe fn from_polymorphic_datum(
+                datum: pg_sys::Datum,
+                is_null: bool,
+                _: pg_sys::Oid,
+            ) -> Option<Self>
+            where
+                Self: Sized,
+            {
+                if is_null {
+                    None
+                } else {
+                    let ptr: *mut Self = datum.cast_mut_ptr();
+                    Some(ptr.read())
+                }
+            }
+        }
+
+        impl IntoDatum for $ty {
+            fn into_datum(mut self) -> Option<pg_sys::Datum> {
+                unsafe {
+                    let copy = PgMemoryContexts::CurrentMemoryContext
+                        .copy_ptr_into(&mut self, std::mem::size_of::<Self>());
+                    Some(copy.into())
+                }
+            }
+
+            fn type_oid() -> pg_sys::Oid {
+                pg_sys::$oid
+            }
+        }
+    };
+}
+
+fixed_size_geo!(pg_sys::BOX, BOXOID);
+fixed_size_geo!(pg_sys::Point, POINTOID);
+fixed_size_geo!(pg_sys::LSEG, LSEGOID);
+fixed_size_geo!(pg_sys::CIRCLE, CIRCLEOID);
+
+/// A `path` type from PostgreSQL: a series of points, which is open, or closed like a polygon
+#[derive(Debug, Clone, Default)]
+pub struct Path {
+    pub points: Vec<pg_sys::Point>,
+    pub closed: bool,
+}
+
+impl Path {
+    pub fn open(points: Vec<pg_sys::Point>) -> Self {
+        Path { points, closed: false }
+    }
+
+    pub fn closed(points: Vec<pg_sys::Point>) -> Self {
+        Path { points, closed: true }
+    }
+}
+
+/// A `polygon` type from PostgreSQL
+#[derive(Debug, Clone, Default)]
+pub struct Polygon {
+    pub points: Vec<pg_sys::Point>,
+}
+
+impl Polygon {
+    pub fn new(points: Vec<pg_sys::Point>) -> Self {
+        Polygon { points }
+    }
+
+    /// The smallest box containing every point, which Postgres stores alongside them
+    pub fn bounding_box(&self) -> pg_sys::BOX {
+        let mut points = self.points.iter();
+        let Some(first) = points.next() else { return pg_sys::BOX::default() };
+        let mut bounds = pg_sys::BOX { high: *first, low: *first };
+        for point in points {
+            bounds.high.x = bounds.high.x.max(point.x);
+            bounds.high.y = bounds.high.y.max(point.y);
+            bounds.low.x = bounds.low.x.min(point.x);
+            bounds.low.y = bounds.low.y.min(point.y);
+        }
+        bounds
+    }
+}
+
+/// Allocate a `path` or `polygon` of `npts` points, with its header set, returning it and a
+/// pointer to where its points go
+unsafe fn alloc_points<T>(offset_of_points: usize, npts: usize) -> (*mut T, *mut pg_sys::Point) {
+    let size = offset_of_points + npts * std::mem::size_of::<pg_sys::Point>();
+    let ptr = pg_sys::palloc0(size) as *mut u8;
+    varlena::set_varsize(ptr.cast(), size as i32);
+    (ptr.cast(), ptr.add(offset_of_points).cast())
+}
+
+impl FromDatum for Path {
     unsafe fn from_polymorphic_datum(
         datum: pg_sys::Datum,
         is_null: bool,
         _: pg_sys::Oid,
-    ) -> Option<Self>
-    where
-        Self: Sized,
-    {
+    ) -> Option<Self> {
         if is_null {
-            None
-        } else {
-            let the_box = datum.cast_mut_ptr::<pg_sys::BOX>();
-            Some(the_box.read())
+            return None;
+        }
+
+        let path = pg_sys::pg_detoast_datum(datum.cast_mut_ptr()) as *mut pg_sys::PATH;
+        let points = (*path).p.as_slice((*path).npts as usize).to_vec();
+        let closed = (*path).closed != 0;
+        if path as *mut pg_sys::varlena != datum.cast_mut_ptr() {
+            pg_sys::pfree(path.cast());
         }
+        Some(Path { points, closed })
     }
 }
 
-impl IntoDatum for pg_sys::BOX {
-    fn into_datum(mut self) -> Option<pg_sys::Datum> {
+impl IntoDatum for Path {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
         unsafe {
-            let ptr = PgMemoryContexts::CurrentMemoryContext
-                .copy_ptr_into(&mut self, std::mem::size_of::<pg_sys::BOX>());
-            Some(ptr.into())
+            let offset = std::mem::size_of::<pg_sys::PATH>();
+            let (path, points) = alloc_points::<pg_sys::PATH>(offset, self.points.len());
+            (*path).npts = self.points.len() as i32;
+            (*path).closed = self.closed as i32;
+            points.copy_from_nonoverlapping(self.points.as_ptr(), self.points.len());
+            Some(path.into())
         }
     }
 
     fn type_oid() -> pg_sys::Oid {
-        pg_sys::BOXOID
+        pg_sys::PATHOID
+    }
+}
+
+unsafe impl SqlTranslatable for Path {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        Ok(SqlMapping::literal("path"))
+    }
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        Ok(Returns::One(SqlMapping::literal("path")))
     }
 }
 
-impl FromDatum for pg_sys::Point {
+impl FromDatum for Polygon {
     unsafe fn from_polymorphic_datum(
         datum: pg_sys::Datum,
         is_null: bool,
         _: pg_sys::Oid,
-    ) -> Option<Self>
-    where
-        Self: Sized,
-    {
+    ) -> Option<Self> {
         if is_null {
-            None
-        } else {
-            let point: *mut Self = datum.cast_mut_ptr();
-            Some(point.read())
+            return None;
+        }
+
+        let polygon = pg_sys::pg_detoast_datum(datum.cast_mut_ptr()) as *mut pg_sys::POLYGON;
+        let points = (*polygon).p.as_slice((*polygon).npts as usize).to_vec();
+        if polygon as *mut pg_sys::varlena != datum.cast_mut_ptr() {
+            pg_sys::pfree(polygon.cast());
         }
+        Some(Polygon { points })
     }
 }
 
-impl IntoDatum for pg_sys::Point {
-    fn into_datum(mut self) -> Option<pg_sys::Datum> {
+impl IntoDatum for Polygon {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
         unsafe {
-            let copy = PgMemoryContexts::CurrentMemoryContext
-                .copy_ptr_into(&mut self, std::mem::size_of::<pg_sys::Point>());
-            Some(copy.into())
+            let offset = std::mem::size_of::<pg_sys::POLYGON>();
+            let (polygon, points) = alloc_points::<pg_sys::POLYGON>(offset, self.points.len());
+            (*polygon).npts = self.points.len() as i32;
+            (*polygon).boundbox = self.bounding_box();
+            points.copy_from_nonoverlapping(self.points.as_ptr(), self.points.len());
+            Some(polygon.into())
         }
     }
 
     fn type_oid() -> pg_sys::Oid {
-        pg_sys::POINTOID
+        pg_sys::POLYGONOID
+    }
+}
+
+unsafe impl SqlTranslatable for Polygon {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        Ok(SqlMapping::literal("polygon"))
+    }
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        Ok(Returns::One(SqlMapping::literal("polygon")))
     }
 }
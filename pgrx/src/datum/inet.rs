@@ -17,12 +17,111 @@ use pgrx_sql_entity_graph::metadata::{
 use serde::de::{Error, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
+use std::net::IpAddr;
 use std::ops::Deref;
+use std::str::FromStr;
 
 /// An `inet` type from PostgreSQL
-#[derive(Debug, Ord, PartialOrd, Eq, PartialEq)]
+///
+/// This holds the address as Postgres prints it, such as `192.168.0.1` or `10.1.0.0/16`.  Use
+/// [`Inet::addr`] and [`Inet::prefix_len`] to work with it as a [`std::net::IpAddr`].
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub struct Inet(pub String);
 
+impl Inet {
+    /// The address, without its netmask
+    ///
+    /// Returns `None` if this `Inet` was built from a string Postgres wouldn't print.
+    pub fn addr(&self) -> Option<IpAddr> {
+        split_prefix(&self.0).0.parse().ok()
+    }
+
+    /// The length of the netmask, which is the address' width when it isn't written
+    pub fn prefix_len(&self) -> Option<u8> {
+        prefix_len(&self.0, self.addr()?)
+    }
+}
+
+impl From<IpAddr> for Inet {
+    fn from(addr: IpAddr) -> Self {
+        Inet(addr.to_string())
+    }
+}
+
+/// A `cidr` type from PostgreSQL
+///
+/// Unlike an [`Inet`], a `cidr` is a network, so it has no bits set to the right of its netmask,
+/// and Postgres always prints the netmask, such as `10.1.0.0/16`.
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct Cidr(pub String);
+
+impl Cidr {
+    /// The network's address
+    pub fn network(&self) -> Option<IpAddr> {
+        split_prefix(&self.0).0.parse().ok()
+    }
+
+    /// The length of the network's netmask
+    pub fn prefix_len(&self) -> Option<u8> {
+        prefix_len(&self.0, self.network()?)
+    }
+
+    /// Does the network contain `addr`?
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        let (Some(network), Some(prefix_len)) = (self.network(), self.prefix_len()) else {
+            return false;
+        };
+        match (network, addr) {
+            (IpAddr::V4(network), IpAddr::V4(addr)) => {
+                let mask = u32::MAX.checked_shl(32 - prefix_len as u32).unwrap_or(0);
+                u32::from(network) & mask == u32::from(addr) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(addr)) => {
+                let mask = u128::MAX.checked_shl(128 - prefix_len as u32).unwrap_or(0);
+                u128::from(network) & mask == u128::from(addr) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Deref for Cidr {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<String> for Cidr {
+    fn from(val: String) -> Self {
+        Cidr(val)
+    }
+}
+
+impl From<IpAddr> for Cidr {
+    /// The network holding only `addr`
+    fn from(addr: IpAddr) -> Self {
+        let width = if addr.is_ipv4() { 32 } else { 128 };
+        Cidr(format!("{addr}/{width}"))
+    }
+}
+
+fn split_prefix(s: &str) -> (&str, Option<&str>) {
+    match s.split_once('/') {
+        Some((addr, prefix_len)) => (addr, Some(prefix_len)),
+        None => (s, None),
+    }
+}
+
+fn prefix_len(s: &str, addr: IpAddr) -> Option<u8> {
+    match split_prefix(s).1 {
+        Some(prefix_len) => prefix_len.parse().ok(),
+        None if addr.is_ipv4() => Some(32),
+        None => Some(128),
+    }
+}
+
 impl Deref for Inet {
     type Target = str;
 
@@ -131,3 +230,153 @@ unsafe impl SqlTranslatable for Inet {
         Ok(Returns::One(SqlMapping::literal("inet")))
     }
 }
+
+impl FromDatum for Cidr {
+    unsafe fn from_polymorphic_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        _typoid: pg_sys::Oid,
+    ) -> Option<Cidr> {
+        if is_null {
+            None
+        } else {
+            let cstr = direct_function_call::<&CStr>(pg_sys::cidr_out, &[Some(datum)]);
+            Some(Cidr(
+                cstr.unwrap().to_str().expect("unable to convert &cstr cidr into &str").to_owned(),
+            ))
+        }
+    }
+}
+
+impl IntoDatum for Cidr {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        let cstr = alloc::ffi::CString::new(self.0).expect("failed to convert cidr into CString");
+        unsafe { direct_function_call_as_datum(pg_sys::cidr_in, &[cstr.as_c_str().into_datum()]) }
+    }
+
+    fn type_oid() -> pg_sys::Oid {
+        pg_sys::CIDROID
+    }
+}
+
+unsafe impl SqlTranslatable for Cidr {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        Ok(SqlMapping::literal("cidr"))
+    }
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        Ok(Returns::One(SqlMapping::literal("cidr")))
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("invalid MAC address: {0}")]
+pub struct MacAddrParseError(String);
+
+/// Parse the `:`-separated hex bytes Postgres prints for a `macaddr` or `macaddr8`
+fn parse_mac_addr<const N: usize>(s: &str) -> Result<[u8; N], MacAddrParseError> {
+    let mut bytes = [0; N];
+    let mut parts = s.split(':');
+    for byte in bytes.iter_mut() {
+        *byte = parts
+            .next()
+            .filter(|part| part.len() == 2)
+            .and_then(|part| u8::from_str_radix(part, 16).ok())
+            .ok_or_else(|| MacAddrParseError(s.to_string()))?;
+    }
+    match parts.next() {
+        None => Ok(bytes),
+        Some(_) => Err(MacAddrParseError(s.to_string())),
+    }
+}
+
+fn fmt_mac_addr(bytes: &[u8], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for (i, byte) in bytes.iter().enumerate() {
+        if i > 0 {
+            f.write_str(":")?;
+        }
+        write!(f, "{byte:02x}")?;
+    }
+    Ok(())
+}
+
+/// A `macaddr` type from PostgreSQL
+///
+/// Its text form, both from [`Display`](fmt::Display) and [`FromStr`], is the one Postgres
+/// prints: `08:00:2b:01:02:03`.
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash, Default)]
+pub struct MacAddr(pub [u8; 6]);
+
+/// A `macaddr8` type from PostgreSQL, a MAC address in EUI-64 format
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash, Default)]
+pub struct MacAddr8(pub [u8; 8]);
+
+impl From<MacAddr> for MacAddr8 {
+    /// Convert to EUI-64 as Postgres' `macaddr8(macaddr)` does, with `ff:fe` in the middle
+    fn from(addr: MacAddr) -> Self {
+        let [a, b, c, d, e, f] = addr.0;
+        MacAddr8([a, b, c, 0xff, 0xfe, d, e, f])
+    }
+}
+
+macro_rules! mac_addr_datum {
+    ($rust:ident, $sql:literal, $oid:ident, $in:ident, $out:ident) => {
+        impl fmt::Display for $rust {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt_mac_addr(&self.0, f)
+            }
+        }
+
+        impl FromStr for $rust {
+            type Err = MacAddrParseError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                parse_mac_addr(s).map($rust)
+            }
+        }
+
+        impl FromDatum for $rust {
+            unsafe fn from_polymorphic_datum(
+                datum: pg_sys::Datum,
+                is_null: bool,
+                _typoid: pg_sys::Oid,
+            ) -> Option<$rust> {
+                if is_null {
+                    None
+                } else {
+                    let cstr = direct_function_call::<&CStr>(pg_sys::$out, &[Some(datum)]);
+                    let s = cstr.unwrap().to_str().expect(concat!(
+                        "unable to convert &cstr ",
+                        $sql,
+                        " into &str"
+                    ));
+                    Some(s.parse().expect(concat!("Postgres printed an invalid ", $sql)))
+                }
+            }
+        }
+
+        impl IntoDatum for $rust {
+            fn into_datum(self) -> Option<pg_sys::Datum> {
+                let cstr = alloc::ffi::CString::new(self.to_string()).unwrap();
+                unsafe {
+                    direct_function_call_as_datum(pg_sys::$in, &[cstr.as_c_str().into_datum()])
+                }
+            }
+
+            fn type_oid() -> pg_sys::Oid {
+                pg_sys::$oid
+            }
+        }
+
+        unsafe impl SqlTranslatable for $rust {
+            fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+                Ok(SqlMapping::literal($sql))
+            }
+            fn return_sql() -> Result<Returns, ReturnsError> {
+                Ok(Returns::One(SqlMapping::literal($sql)))
+            }
+        }
+    };
+}
+
+mac_addr_datum!(MacAddr, "macaddr", MACADDROID, macaddr_in, macaddr_out);
+mac_addr_datum!(MacAddr8, "macaddr8", MACADDR8OID, macaddr8_in, macaddr8_out);
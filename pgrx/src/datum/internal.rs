@@ -7,7 +7,7 @@
 //LICENSE All rights reserved.
 //LICENSE
 //LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
-use crate::{pg_sys, FromDatum, IntoDatum, PgMemoryContexts};
+use crate::{ereport, pg_sys, FromDatum, IntoDatum, PgLogLevel, PgMemoryContexts, PgSqlErrorCode};
 use pgrx_sql_entity_graph::metadata::{
     ArgumentError, Returns, ReturnsError, SqlMapping, SqlTranslatable,
 };
@@ -150,6 +150,89 @@ impl Internal {
     pub fn unwrap(self) -> Option<pg_sys::Datum> {
         self.0
     }
+
+    /// Treat this [`Internal`] as the message buffer Postgres passes as the first argument of a
+    /// type's `receive` function, returning `None` if the datum is null.
+    ///
+    /// ## Safety
+    ///
+    /// We cannot guarantee that the contained datum points to a [`pg_sys::StringInfoData`].  This is
+    /// your responsibility.
+    #[inline]
+    pub unsafe fn as_receive_buffer(&mut self) -> Option<ReceiveBuffer<'_>> {
+        self.get_mut::<pg_sys::StringInfoData>().map(|buf| ReceiveBuffer { buf })
+    }
+}
+
+/// The binary representation of a value, as passed to a type's `receive` function as an `internal`
+/// argument.
+///
+/// Reads start at, and advance, the buffer's cursor.  Numbers are read in network byte order, as
+/// Postgres' `pq_getmsgint()` and friends do, and reading past the end of the buffer raises the
+/// same "insufficient data left in message" error.
+pub struct ReceiveBuffer<'a> {
+    buf: &'a mut pg_sys::StringInfoData,
+}
+
+impl<'a> ReceiveBuffer<'a> {
+    /// The bytes which have not yet been read
+    pub fn remaining(&self) -> &[u8] {
+        let len = (self.buf.len - self.buf.cursor) as usize;
+        if len == 0 {
+            return &[];
+        }
+        unsafe {
+            // SAFETY:  Postgres keeps `cursor <= len` and `data` valid for `len` bytes
+            std::slice::from_raw_parts(self.buf.data.add(self.buf.cursor as usize).cast(), len)
+        }
+    }
+
+    /// Read the next `len` bytes
+    pub fn read_bytes(&mut self, len: usize) -> &[u8] {
+        if len > self.remaining().len() {
+            ereport!(
+                PgLogLevel::ERROR,
+                PgSqlErrorCode::ERRCODE_PROTOCOL_VIOLATION,
+                "insufficient data left in message"
+            );
+        }
+        let start = self.buf.cursor as usize;
+        self.buf.cursor += len as i32;
+        unsafe {
+            // SAFETY:  we just checked that `len` bytes remain after `start`
+            std::slice::from_raw_parts(self.buf.data.add(start).cast(), len)
+        }
+    }
+
+    /// Read the remaining bytes
+    pub fn read_remaining(&mut self) -> &[u8] {
+        let len = self.remaining().len();
+        self.read_bytes(len)
+    }
+
+    pub fn read_u8(&mut self) -> u8 {
+        self.read_bytes(1)[0]
+    }
+
+    pub fn read_i16(&mut self) -> i16 {
+        i16::from_be_bytes(self.read_bytes(2).try_into().unwrap())
+    }
+
+    pub fn read_i32(&mut self) -> i32 {
+        i32::from_be_bytes(self.read_bytes(4).try_into().unwrap())
+    }
+
+    pub fn read_i64(&mut self) -> i64 {
+        i64::from_be_bytes(self.read_bytes(8).try_into().unwrap())
+    }
+
+    pub fn read_f32(&mut self) -> f32 {
+        f32::from_be_bytes(self.read_bytes(4).try_into().unwrap())
+    }
+
+    pub fn read_f64(&mut self) -> f64 {
+        f64::from_be_bytes(self.read_bytes(8).try_into().unwrap())
+    }
 }
 
 impl From<Option<pg_sys::Datum>> for Internal {
@@ -70,8 +70,10 @@ impl Interval {
         Self::from(None, None, None, None, None, None, Some(seconds)).unwrap()
     }
 
+    /// Unlike [`Interval::from_seconds`], this doesn't round-trip through an `f64`, so every
+    /// microsecond is preserved
     pub fn from_micros(microseconds: i64) -> Self {
-        Self::from_seconds(microseconds as f64 / 1_000_000.0)
+        Interval(pg_sys::Interval { time: microseconds, day: 0, month: 0 })
     }
 
     pub fn from(
@@ -250,23 +252,15 @@ impl IntoDatum for Interval {
     }
 }
 
+/// A [`std::time::Duration`] is an exact span of time, so it's kept entirely in the microseconds
+/// field rather than being spread across months and days, whose lengths vary.  Any nanoseconds
+/// smaller than a microsecond are truncated, as that's all the precision an `Interval` has.
 impl TryFrom<std::time::Duration> for Interval {
     type Error = IntervalConversionError;
     fn try_from(duration: std::time::Duration) -> Result<Interval, Self::Error> {
-        let microseconds = duration.as_micros();
-        let seconds = microseconds / USECS_PER_SEC as u128;
-        let days = seconds / pg_sys::SECS_PER_DAY as u128;
-        let months = days / pg_sys::DAYS_PER_MONTH as u128;
-        let leftover_days = days - months * pg_sys::DAYS_PER_MONTH as u128;
-        let leftover_microseconds = microseconds
-            - (leftover_days * USECS_PER_DAY as u128
-                + (months * pg_sys::DAYS_PER_MONTH as u128 * USECS_PER_DAY as u128));
-
-        Interval::new(
-            months.try_into().map_err(|_| IntervalConversionError::DurationMonthsOutOfBounds)?,
-            leftover_days.try_into().expect("bad math during Duration to Interval days"),
-            leftover_microseconds.try_into().expect("bad math during Duration to Interval micros"),
-        )
+        let micros = i64::try_from(duration.as_micros())
+            .map_err(|_| IntervalConversionError::DurationTooLarge)?;
+        Ok(Interval::from_micros(micros))
     }
 }
 
@@ -276,21 +270,21 @@ impl From<Time> for Interval {
     }
 }
 
+/// Only an `Interval` of exactly so many microseconds can be converted, as its months and days
+/// have no fixed length: a month may have 28 to 31 days, and a day 23 to 25 hours across a
+/// daylight saving time change.  Use [`Interval::as_micros()`] to count them as Postgres does when
+/// comparing intervals, with 30 day months and 24 hour days.
 impl TryFrom<Interval> for std::time::Duration {
     type Error = IntervalConversionError;
 
     fn try_from(interval: Interval) -> Result<Self, Self::Error> {
-        if interval.0.time < 0 || interval.0.month < 0 || interval.0.day < 0 {
+        if interval.0.month != 0 || interval.0.day != 0 {
+            return Err(IntervalConversionError::InexactInterval);
+        } else if interval.0.time < 0 {
             return Err(IntervalConversionError::NegativeInterval);
         }
 
-        let micros = interval.0.time as u128
-            + interval.0.day as u128 * pg_sys::SECS_PER_DAY as u128 * USECS_PER_SEC as u128
-            + interval.0.month as u128 * pg_sys::DAYS_PER_MONTH as u128 * USECS_PER_DAY as u128;
-
-        Ok(std::time::Duration::from_micros(
-            micros.try_into().map_err(|_| IntervalConversionError::IntervalTooLarge)?,
-        ))
+        Ok(std::time::Duration::from_micros(interval.0.time as u64))
     }
 }
 
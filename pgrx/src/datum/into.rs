@@ -17,6 +17,7 @@ use core::fmt::Display;
 use pgrx_pg_sys::panic::ErrorReportable;
 use pgrx_pg_sys::{Datum, Oid};
 use std::any::Any;
+use std::borrow::Cow;
 
 /// Convert a Rust type into a `pg_sys::Datum`.
 ///
@@ -89,6 +90,14 @@ where
     fn type_oid() -> pg_sys::Oid {
         T::type_oid()
     }
+
+    fn composite_type_oid(&self) -> Option<Oid> {
+        self.as_ref().and_then(|t| t.composite_type_oid())
+    }
+
+    fn is_compatible_with(other: pg_sys::Oid) -> bool {
+        T::is_compatible_with(other)
+    }
 }
 
 impl<T, E> IntoDatum for Result<T, E>
@@ -266,10 +275,11 @@ impl IntoDatum for PgOid {
 }
 
 /// for text, varchar
+/// Transcoded into the database's encoding, if that isn't UTF-8
 impl<'a> IntoDatum for &'a str {
     #[inline]
     fn into_datum(self) -> Option<pg_sys::Datum> {
-        self.as_bytes().into_datum()
+        crate::varlena::utf8_to_server(self, |bytes| bytes.into_datum())
     }
 
     fn type_oid() -> pg_sys::Oid {
@@ -314,6 +324,22 @@ impl IntoDatum for &String {
     }
 }
 
+impl IntoDatum for Cow<'_, str> {
+    #[inline]
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        self.as_ref().into_datum()
+    }
+
+    fn type_oid() -> pg_sys::Oid {
+        pg_sys::TEXTOID
+    }
+
+    #[inline]
+    fn is_compatible_with(other: Oid) -> bool {
+        Self::type_oid() == other || other == pg_sys::VARCHAROID
+    }
+}
+
 impl IntoDatum for char {
     #[inline]
     fn into_datum(self) -> Option<pg_sys::Datum> {
@@ -431,6 +457,18 @@ impl IntoDatum for Vec<u8> {
     }
 }
 
+impl IntoDatum for Cow<'_, [u8]> {
+    #[inline]
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        self.as_ref().into_datum()
+    }
+
+    #[inline]
+    fn type_oid() -> pg_sys::Oid {
+        pg_sys::BYTEAOID
+    }
+}
+
 /// for VOID
 impl IntoDatum for () {
     #[inline]
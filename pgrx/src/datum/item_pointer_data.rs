@@ -10,6 +10,106 @@
 use crate::{
     item_pointer_get_both, item_pointer_set_all, pg_sys, FromDatum, IntoDatum, PgMemoryContexts,
 };
+use pgrx_sql_entity_graph::metadata::{
+    ArgumentError, Returns, ReturnsError, SqlMapping, SqlTranslatable,
+};
+use std::fmt;
+use std::str::FromStr;
+
+/// A `tid` type from PostgreSQL: the physical location of a row version, as the block it's in and
+/// its offset within that block
+///
+/// Tids order by block, then offset, as the rows are laid out in the table.  They print and parse
+/// as Postgres does, such as `(0,1)`.
+///
+/// ```rust,no_run
+/// use pgrx::Tid;
+///
+/// let tid = Tid::new(3, 7);
+/// assert_eq!(tid.parts(), (3, 7));
+/// assert_eq!(tid.to_string(), "(3,7)");
+/// assert_eq!("(3,7)".parse::<Tid>().unwrap(), tid);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Tid {
+    block: pg_sys::BlockNumber,
+    offset: pg_sys::OffsetNumber,
+}
+
+impl Tid {
+    pub const fn new(block: pg_sys::BlockNumber, offset: pg_sys::OffsetNumber) -> Self {
+        Tid { block, offset }
+    }
+
+    /// The number of the block holding the row
+    #[inline]
+    pub const fn block(&self) -> pg_sys::BlockNumber {
+        self.block
+    }
+
+    /// The row's line pointer within its block, starting at 1
+    #[inline]
+    pub const fn offset(&self) -> pg_sys::OffsetNumber {
+        self.offset
+    }
+
+    /// The `(block, offset)` pair
+    #[inline]
+    pub const fn parts(&self) -> (pg_sys::BlockNumber, pg_sys::OffsetNumber) {
+        (self.block, self.offset)
+    }
+
+    /// Does this point at a row?  A zero offset never does.
+    #[inline]
+    pub const fn is_valid(&self) -> bool {
+        self.offset != pg_sys::InvalidOffsetNumber
+    }
+}
+
+impl From<pg_sys::ItemPointerData> for Tid {
+    fn from(tid: pg_sys::ItemPointerData) -> Self {
+        let (block, offset) = item_pointer_get_both(tid);
+        Tid { block, offset }
+    }
+}
+
+impl From<Tid> for pg_sys::ItemPointerData {
+    fn from(tid: Tid) -> Self {
+        let mut item_pointer = pg_sys::ItemPointerData::default();
+        item_pointer_set_all(&mut item_pointer, tid.block, tid.offset);
+        item_pointer
+    }
+}
+
+impl fmt::Display for Tid {
+    /// As Postgres' `tidout()`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({},{})", self.block, self.offset)
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("invalid input syntax for type tid: \"{0}\"")]
+pub struct ParseTidError(String);
+
+impl FromStr for Tid {
+    type Err = ParseTidError;
+
+    /// As Postgres' `tidin()`, which allows whitespace around the numbers
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseTidError(s.to_string());
+        let (block, offset) = s
+            .trim()
+            .strip_prefix('(')
+            .and_then(|s| s.strip_suffix(')'))
+            .and_then(|s| s.split_once(','))
+            .ok_or_else(error)?;
+        Ok(Tid {
+            block: block.trim().parse().map_err(|_| error())?,
+            offset: offset.trim().parse().map_err(|_| error())?,
+        })
+    }
+}
 
 impl FromDatum for pg_sys::ItemPointerData {
     #[inline]
@@ -50,3 +150,34 @@ impl IntoDatum for pg_sys::ItemPointerData {
         pg_sys::TIDOID
     }
 }
+
+impl FromDatum for Tid {
+    #[inline]
+    unsafe fn from_polymorphic_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        typoid: pg_sys::Oid,
+    ) -> Option<Tid> {
+        pg_sys::ItemPointerData::from_polymorphic_datum(datum, is_null, typoid).map(Tid::from)
+    }
+}
+
+impl IntoDatum for Tid {
+    #[inline]
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        pg_sys::ItemPointerData::from(self).into_datum()
+    }
+
+    fn type_oid() -> pg_sys::Oid {
+        pg_sys::TIDOID
+    }
+}
+
+unsafe impl SqlTranslatable for Tid {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        Ok(SqlMapping::literal("tid"))
+    }
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        Ok(Returns::One(SqlMapping::literal("tid")))
+    }
+}
@@ -14,8 +14,12 @@ use crate::{
 use pgrx_sql_entity_graph::metadata::{
     ArgumentError, Returns, ReturnsError, SqlMapping, SqlTranslatable,
 };
-use serde::{Serialize, Serializer};
+use serde::de::{IgnoredAny, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
+use std::borrow::Cow;
+use std::fmt;
+use std::marker::PhantomData;
 
 /// A `json` type from PostgreSQL
 #[derive(Debug)]
@@ -25,6 +29,29 @@ pub struct Json(pub Value);
 #[derive(Debug)]
 pub struct JsonB(pub Value);
 
+/// A `json` value from PostgreSQL, borrowed as its original text and only parsed on demand
+///
+/// Unlike [`Json`], reading a `JsonView` argument doesn't parse it, and returning one hands
+/// Postgres back the very datum it came from, so a function which forwards `json` values, perhaps
+/// after inspecting a field or two, skips the parse-and-reserialize round trip.  It also
+/// preserves the text exactly, including whitespace, key order, and duplicate keys.
+///
+/// ```rust,no_run
+/// use pgrx::prelude::*;
+/// use pgrx::JsonView;
+///
+/// #[pg_extern]
+/// fn if_active(event: JsonView<'_>) -> Option<JsonView<'_>> {
+///     let active = event.get::<bool>("active").expect("event is not valid json");
+///     active.unwrap_or(false).then_some(event)
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct JsonView<'a> {
+    varlena: *mut pg_sys::varlena,
+    text: &'a str,
+}
+
 /// A wholly Rust-[`String`][std::string::String] owned copy of a `json` type from PostgreSQL
 #[derive(Debug)]
 pub struct JsonString(pub String);
@@ -119,6 +146,83 @@ impl FromDatum for JsonString {
     }
 }
 
+/// for json, without parsing it
+impl<'a> FromDatum for JsonView<'a> {
+    #[inline]
+    unsafe fn from_polymorphic_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        _: pg_sys::Oid,
+    ) -> Option<JsonView<'a>> {
+        if is_null || datum.is_null() {
+            None
+        } else {
+            let varlena = pg_sys::pg_detoast_datum_packed(datum.cast_mut_ptr());
+            let text = super::from::convert_varlena_to_str_memoized(varlena);
+            Some(JsonView { varlena, text })
+        }
+    }
+}
+
+impl<'a> JsonView<'a> {
+    /// The original text of the value
+    #[inline]
+    pub fn as_str(&self) -> &'a str {
+        self.text
+    }
+
+    /// Deserialize the whole value.  Strings in `T` may borrow from the original text.
+    pub fn parse<T: Deserialize<'a>>(&self) -> serde_json::Result<T> {
+        serde_json::from_str(self.text)
+    }
+
+    /// Parse the whole value into a [`serde_json::Value`]
+    pub fn value(&self) -> serde_json::Result<Value> {
+        self.parse()
+    }
+
+    /// Deserialize only the top-level field `key` of a json object, skipping over the others
+    /// without building them.  Returns `Ok(None)` if there's no such field, and an error if the
+    /// value isn't an object.  If `key` appears more than once, the last one wins, as in `jsonb`.
+    pub fn get<T: Deserialize<'a>>(&self, key: &str) -> serde_json::Result<Option<T>> {
+        let mut deserializer = serde_json::Deserializer::from_str(self.text);
+        let field = deserializer.deserialize_map(FieldVisitor { key, marker: PhantomData::<T> })?;
+        deserializer.end()?;
+        Ok(field)
+    }
+}
+
+struct FieldVisitor<'k, T> {
+    key: &'k str,
+    marker: PhantomData<T>,
+}
+
+impl<'de, 'k, T: Deserialize<'de>> Visitor<'de> for FieldVisitor<'k, T> {
+    type Value = Option<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a json object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut field = None;
+        while let Some(key) = map.next_key::<Cow<'de, str>>()? {
+            if key == self.key {
+                field = Some(map.next_value()?);
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(field)
+    }
+}
+
+impl fmt::Display for JsonView<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.text)
+    }
+}
+
 /// for json
 impl IntoDatum for Json {
     fn into_datum(self) -> Option<pg_sys::Datum> {
@@ -146,6 +250,18 @@ impl IntoDatum for JsonB {
     }
 }
 
+/// for json, returning the datum the view was read from
+impl IntoDatum for JsonView<'_> {
+    #[inline]
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(pg_sys::Datum::from(self.varlena))
+    }
+
+    fn type_oid() -> pg_sys::Oid {
+        pg_sys::JSONOID
+    }
+}
+
 /// for jsonstring
 impl IntoDatum for JsonString {
     fn into_datum(self) -> Option<pg_sys::Datum> {
@@ -195,6 +311,15 @@ unsafe impl SqlTranslatable for Json {
     }
 }
 
+unsafe impl SqlTranslatable for JsonView<'_> {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        Ok(SqlMapping::literal("json"))
+    }
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        Ok(Returns::One(SqlMapping::literal("json")))
+    }
+}
+
 unsafe impl SqlTranslatable for crate::datum::JsonB {
     fn argument_sql() -> Result<SqlMapping, ArgumentError> {
         Ok(SqlMapping::literal("jsonb"))
@@ -0,0 +1,647 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Direct access to Postgres' binary `jsonb` representation.
+//!
+//! [`JsonB`] converts the whole document to and from a [`serde_json::Value`] by way of its text
+//! form, which is wasteful when a function only needs a few fields out of a large document.
+//! [`JsonBContainer`] instead reads the binary container in place, and [`JsonBBuilder`] assembles
+//! a new one with `pushJsonbValue()`, so lookups and partial updates never leave Postgres' format.
+use crate::{
+    pg_sys, varsize_any_exhdr, AnyNumeric, FromDatum, IntoDatum, JsonB, MemCx, PgMemoryContexts,
+};
+use core::fmt::{Debug, Formatter};
+use core::marker::PhantomData;
+use core::ptr::NonNull;
+use pgrx_sql_entity_graph::metadata::{
+    ArgumentError, Returns, ReturnsError, SqlMapping, SqlTranslatable,
+};
+use serde_json::{Map, Value};
+
+/// A borrowed view of a binary `jsonb` object, array, or scalar.
+///
+/// This is either a whole `jsonb` datum or a container nested within one.  Nothing is copied or
+/// parsed until it is asked for, and the values it returns point back into the same memory.
+#[derive(Clone, Copy)]
+pub struct JsonBContainer<'a> {
+    container: NonNull<pg_sys::JsonbContainer>,
+    /// the container's size in bytes
+    size: usize,
+    _marker: PhantomData<&'a pg_sys::JsonbContainer>,
+}
+
+/// A single value found in a [`JsonBContainer`]
+#[derive(Debug, Clone)]
+pub enum JsonBValue<'a> {
+    Null,
+    Bool(bool),
+    Numeric(AnyNumeric),
+    String(&'a str),
+    /// A nested object or array
+    Container(JsonBContainer<'a>),
+}
+
+impl<'a> JsonBContainer<'a> {
+    #[inline]
+    fn header(&self) -> u32 {
+        unsafe { self.container.as_ref().header }
+    }
+
+    #[inline]
+    pub fn is_object(&self) -> bool {
+        self.header() & pg_sys::JB_FOBJECT != 0
+    }
+
+    #[inline]
+    pub fn is_array(&self) -> bool {
+        self.header() & pg_sys::JB_FARRAY != 0 && !self.is_scalar()
+    }
+
+    /// Is this a top-level scalar, such as `'42'::jsonb`?
+    #[inline]
+    pub fn is_scalar(&self) -> bool {
+        self.header() & pg_sys::JB_FSCALAR != 0
+    }
+
+    /// The number of key/value pairs in an object, or elements in an array.  A scalar has one.
+    #[inline]
+    pub fn len(&self) -> usize {
+        (self.header() & pg_sys::JB_CMASK) as usize
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The value of a top-level scalar
+    pub fn scalar(&self) -> Option<JsonBValue<'a>> {
+        if !self.is_scalar() {
+            return None;
+        }
+        unsafe {
+            self.take_value(pg_sys::getIthJsonbValueFromContainer(self.container.as_ptr(), 0))
+        }
+    }
+
+    /// Look up `key` in an object, like the `->` operator.  Returns `None` if this isn't an object
+    /// or doesn't have the key.
+    pub fn get(&self, key: &str) -> Option<JsonBValue<'a>> {
+        if !self.is_object() {
+            return None;
+        }
+        unsafe {
+            let mut key_value = pg_sys::JsonbValue::default();
+            key_value.type_ = pg_sys::jbvType_jbvString;
+            key_value.val.string.len = key.len().try_into().expect("jsonb key is too long");
+            key_value.val.string.val = key.as_ptr() as *mut std::os::raw::c_char;
+
+            self.take_value(pg_sys::findJsonbValueFromContainer(
+                self.container.as_ptr(),
+                pg_sys::JB_FOBJECT,
+                &mut key_value,
+            ))
+        }
+    }
+
+    /// The element at `index` in an array.  Returns `None` if this isn't an array or `index` is
+    /// out of bounds.
+    pub fn index(&self, index: usize) -> Option<JsonBValue<'a>> {
+        if !self.is_array() || index >= self.len() {
+            return None;
+        }
+        unsafe {
+            self.take_value(pg_sys::getIthJsonbValueFromContainer(
+                self.container.as_ptr(),
+                index as u32,
+            ))
+        }
+    }
+
+    /// Follow a path of object keys and array indexes, like the `#>` operator
+    pub fn get_path(&self, path: &[&str]) -> Option<JsonBValue<'a>> {
+        let mut current = JsonBValue::Container(*self);
+        for step in path {
+            let JsonBValue::Container(container) = current else { return None };
+            current = if container.is_object() {
+                container.get(step)?
+            } else {
+                container.index(step.parse().ok()?)?
+            };
+        }
+        Some(current)
+    }
+
+    /// The key/value pairs of an object, in the order Postgres stores them
+    pub fn entries(&self) -> impl Iterator<Item = (&'a str, JsonBValue<'a>)> {
+        let mut key = None;
+        self.tokens(true).filter_map(move |(token, value)| match token {
+            pg_sys::JsonbIteratorToken_WJB_KEY => {
+                key = Some(unsafe { jsonb_str(&value) });
+                None
+            }
+            pg_sys::JsonbIteratorToken_WJB_VALUE => {
+                Some((key.take()?, unsafe { JsonBValue::from_jsonb_value(&value) }))
+            }
+            _ => None,
+        })
+    }
+
+    /// The keys of an object
+    pub fn keys(&self) -> impl Iterator<Item = &'a str> {
+        self.entries().map(|(key, _)| key)
+    }
+
+    /// The elements of an array
+    pub fn elements(&self) -> impl Iterator<Item = JsonBValue<'a>> {
+        let is_array = self.is_array();
+        self.tokens(true).filter_map(move |(token, value)| match token {
+            pg_sys::JsonbIteratorToken_WJB_ELEM if is_array => {
+                Some(unsafe { JsonBValue::from_jsonb_value(&value) })
+            }
+            _ => None,
+        })
+    }
+
+    /// A copy of this object with `key` set to `value`, leaving every other pair as it was.
+    /// Returns `None` if this isn't an object.
+    pub fn set(&self, key: &str, value: impl Into<JsonBValue<'a>>) -> Option<JsonBContainer<'a>> {
+        if !self.is_object() {
+            return None;
+        }
+
+        let mut value = Some(value.into());
+        let mut builder = JsonBBuilder::new();
+        builder.begin_object();
+        for (existing_key, existing_value) in self.entries() {
+            builder.key(existing_key);
+            match value.take() {
+                Some(value) if existing_key == key => builder.value(value),
+                other => {
+                    value = other;
+                    builder.value(existing_value)
+                }
+            };
+        }
+        if let Some(value) = value {
+            builder.key(key).value(value);
+        }
+        builder.end_object();
+        // SAFETY: the copy is made in the `CurrentMemoryContext`, where `self`'s datum lives for
+        // the duration of a function call
+        Some(unsafe { builder.finish() })
+    }
+
+    /// A copy of this object without `key`.  Returns `None` if this isn't an object.
+    pub fn remove(&self, key: &str) -> Option<JsonBContainer<'a>> {
+        if !self.is_object() {
+            return None;
+        }
+
+        let mut builder = JsonBBuilder::new();
+        builder.begin_object();
+        for (existing_key, existing_value) in self.entries().filter(|(k, _)| *k != key) {
+            builder.key(existing_key).value(existing_value);
+        }
+        builder.end_object();
+        // SAFETY: the copy is made in the `CurrentMemoryContext`, where `self`'s datum lives for
+        // the duration of a function call
+        Some(unsafe { builder.finish() })
+    }
+
+    /// A copy of this array with `value` appended.  Returns `None` if this isn't an array.
+    pub fn push(&self, value: impl Into<JsonBValue<'a>>) -> Option<JsonBContainer<'a>> {
+        if !self.is_array() {
+            return None;
+        }
+
+        let mut builder = JsonBBuilder::new();
+        builder.begin_array();
+        for element in self.elements() {
+            builder.value(element);
+        }
+        builder.value(value).end_array();
+        // SAFETY: the copy is made in the `CurrentMemoryContext`, where `self`'s datum lives for
+        // the duration of a function call
+        Some(unsafe { builder.finish() })
+    }
+
+    /// Convert the whole container into a [`serde_json::Value`]
+    pub fn to_value(&self) -> Value {
+        if let Some(scalar) = self.scalar() {
+            scalar.to_value()
+        } else if self.is_object() {
+            Value::Object(
+                self.entries().map(|(k, v)| (k.to_string(), v.to_value())).collect::<Map<_, _>>(),
+            )
+        } else {
+            Value::Array(self.elements().map(|v| v.to_value()).collect())
+        }
+    }
+
+    fn tokens(
+        &self,
+        skip_nested: bool,
+    ) -> impl Iterator<Item = (pg_sys::JsonbIteratorToken, pg_sys::JsonbValue)> + 'a {
+        let mut it = unsafe { pg_sys::JsonbIteratorInit(self.container.as_ptr()) };
+        std::iter::from_fn(move || {
+            let mut value = pg_sys::JsonbValue::default();
+            // SAFETY:  once the iterator is exhausted, Postgres sets `it` to NULL and keeps
+            // returning WJB_DONE
+            let token = unsafe { pg_sys::JsonbIteratorNext(&mut it, &mut value, skip_nested) };
+            (token != pg_sys::JsonbIteratorToken_WJB_DONE).then_some((token, value))
+        })
+    }
+
+    /// Convert a `JsonbValue` palloc'd by a lookup function, then free it
+    unsafe fn take_value(&self, value: *mut pg_sys::JsonbValue) -> Option<JsonBValue<'a>> {
+        if value.is_null() {
+            return None;
+        }
+        let converted = JsonBValue::from_jsonb_value(&*value);
+        pg_sys::pfree(value.cast());
+        Some(converted)
+    }
+
+    /// Describe this container as a `jbvBinary` `JsonbValue`
+    unsafe fn as_jsonb_value(&self) -> pg_sys::JsonbValue {
+        let mut value = pg_sys::JsonbValue::default();
+        value.type_ = pg_sys::jbvType_jbvBinary;
+        value.val.binary.len = self.size.try_into().expect("jsonb container is too large");
+        value.val.binary.data = self.container.as_ptr();
+        value
+    }
+}
+
+/// The string behind a `jbvString` `JsonbValue`.  Postgres has already validated it against the
+/// database encoding.
+unsafe fn jsonb_str<'a>(value: &pg_sys::JsonbValue) -> &'a str {
+    let string = value.val.string;
+    std::str::from_utf8_unchecked(std::slice::from_raw_parts(
+        string.val as *const u8,
+        string.len as usize,
+    ))
+}
+
+impl<'a> JsonBValue<'a> {
+    unsafe fn from_jsonb_value(value: &pg_sys::JsonbValue) -> Self {
+        match value.type_ {
+            pg_sys::jbvType_jbvNull => JsonBValue::Null,
+            pg_sys::jbvType_jbvBool => JsonBValue::Bool(value.val.boolean),
+            pg_sys::jbvType_jbvNumeric => {
+                // the numeric points into the container, so take our own copy
+                let numeric =
+                    AnyNumeric::from_datum(pg_sys::Datum::from(value.val.numeric), false).unwrap();
+                JsonBValue::Numeric(numeric.clone())
+            }
+            pg_sys::jbvType_jbvString => JsonBValue::String(jsonb_str(value)),
+            pg_sys::jbvType_jbvBinary => JsonBValue::Container(JsonBContainer {
+                container: NonNull::new(value.val.binary.data)
+                    .expect("jsonb container pointer is NULL"),
+                size: value.val.binary.len as usize,
+                _marker: PhantomData,
+            }),
+            other => panic!("unexpected jsonb value type: {other}"),
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, JsonBValue::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonBValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_numeric(&self) -> Option<&AnyNumeric> {
+        match self {
+            JsonBValue::Numeric(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&'a str> {
+        match self {
+            JsonBValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_container(&self) -> Option<JsonBContainer<'a>> {
+        match self {
+            JsonBValue::Container(c) => Some(*c),
+            _ => None,
+        }
+    }
+
+    /// Convert this value into a [`serde_json::Value`]
+    pub fn to_value(&self) -> Value {
+        match self {
+            JsonBValue::Null => Value::Null,
+            JsonBValue::Bool(b) => Value::Bool(*b),
+            JsonBValue::Numeric(n) => {
+                serde_json::from_str(&n.to_string()).expect("jsonb numeric is not a JSON number")
+            }
+            JsonBValue::String(s) => Value::String(s.to_string()),
+            JsonBValue::Container(c) => c.to_value(),
+        }
+    }
+
+    /// Build a `JsonbValue` for [`pushJsonbValue`][pg_sys::pushJsonbValue].  Strings and numerics
+    /// are copied into the `CurrentMemoryContext` so they outlive the Rust values they came from.
+    unsafe fn to_jsonb_value(&self) -> pg_sys::JsonbValue {
+        let mut value = pg_sys::JsonbValue::default();
+        match self {
+            JsonBValue::Null => value.type_ = pg_sys::jbvType_jbvNull,
+            JsonBValue::Bool(b) => {
+                value.type_ = pg_sys::jbvType_jbvBool;
+                value.val.boolean = *b;
+            }
+            JsonBValue::Numeric(n) => {
+                value.type_ = pg_sys::jbvType_jbvNumeric;
+                value.val.numeric = n.clone().into_datum().unwrap().cast_mut_ptr();
+            }
+            JsonBValue::String(s) => {
+                value.type_ = pg_sys::jbvType_jbvString;
+                value.val.string.len = s.len().try_into().expect("jsonb string is too long");
+                value.val.string.val = palloc_str(s);
+            }
+            JsonBValue::Container(c) => value = c.as_jsonb_value(),
+        }
+        value
+    }
+}
+
+unsafe fn palloc_str(s: &str) -> *mut std::os::raw::c_char {
+    let ptr = pg_sys::palloc(s.len().max(1)).cast::<u8>();
+    ptr.copy_from_nonoverlapping(s.as_ptr(), s.len());
+    ptr.cast()
+}
+
+/// Assembles a new binary `jsonb` value with Postgres' `pushJsonbValue()`.
+///
+/// Containers borrowed from another `jsonb` are copied in without being converted to text.
+///
+/// ```rust,no_run
+/// use pgrx::{current_context, JsonB, JsonBBuilder, JsonBContainer};
+///
+/// fn tag(doc: JsonBContainer) -> JsonB {
+///     current_context(|mcx| {
+///         let mut builder = JsonBBuilder::new();
+///         builder.begin_object().key("tag").value("v1").key("doc").value(doc).end_object();
+///         builder.build(mcx).into()
+///     })
+/// }
+/// ```
+pub struct JsonBBuilder<'a> {
+    state: *mut pg_sys::JsonbParseState,
+    result: *mut pg_sys::JsonbValue,
+    _marker: PhantomData<&'a pg_sys::JsonbContainer>,
+}
+
+impl<'a> Default for JsonBBuilder<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> JsonBBuilder<'a> {
+    pub fn new() -> Self {
+        JsonBBuilder {
+            state: std::ptr::null_mut(),
+            result: std::ptr::null_mut(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn begin_object(&mut self) -> &mut Self {
+        unsafe { self.push(pg_sys::JsonbIteratorToken_WJB_BEGIN_OBJECT, std::ptr::null_mut()) }
+    }
+
+    pub fn end_object(&mut self) -> &mut Self {
+        unsafe { self.push(pg_sys::JsonbIteratorToken_WJB_END_OBJECT, std::ptr::null_mut()) }
+    }
+
+    pub fn begin_array(&mut self) -> &mut Self {
+        unsafe { self.push(pg_sys::JsonbIteratorToken_WJB_BEGIN_ARRAY, std::ptr::null_mut()) }
+    }
+
+    pub fn end_array(&mut self) -> &mut Self {
+        unsafe { self.push(pg_sys::JsonbIteratorToken_WJB_END_ARRAY, std::ptr::null_mut()) }
+    }
+
+    /// The key for the next [`value`][JsonBBuilder::value] in the current object
+    pub fn key(&mut self, key: &str) -> &mut Self {
+        unsafe {
+            let mut value = JsonBValue::String(key).to_jsonb_value();
+            self.push(pg_sys::JsonbIteratorToken_WJB_KEY, &mut value)
+        }
+    }
+
+    /// Add a value to the current object or array.  Outside of any object or array, `value`
+    /// becomes the whole document.
+    pub fn value(&mut self, value: impl Into<JsonBValue<'a>>) -> &mut Self {
+        let value = match value.into() {
+            // pushJsonbValue() would unpack a top-level scalar into a one-element array
+            JsonBValue::Container(c) if c.is_scalar() => c.scalar().unwrap(),
+            value => value,
+        };
+
+        unsafe {
+            if self.state.is_null() {
+                match value {
+                    JsonBValue::Container(c) => {
+                        for (token, mut value) in c.tokens(false) {
+                            let value = if token < pg_sys::JsonbIteratorToken_WJB_BEGIN_ARRAY {
+                                &mut value as *mut _
+                            } else {
+                                std::ptr::null_mut()
+                            };
+                            self.push(token, value);
+                        }
+                    }
+                    scalar => {
+                        // a top-level scalar is stored as a one-element "raw scalar" array
+                        let mut array = pg_sys::JsonbValue::default();
+                        array.type_ = pg_sys::jbvType_jbvArray;
+                        array.val.array.rawScalar = true;
+                        self.push(pg_sys::JsonbIteratorToken_WJB_BEGIN_ARRAY, &mut array);
+                        let mut scalar = scalar.to_jsonb_value();
+                        self.push(pg_sys::JsonbIteratorToken_WJB_ELEM, &mut scalar);
+                        self.push(pg_sys::JsonbIteratorToken_WJB_END_ARRAY, std::ptr::null_mut());
+                    }
+                }
+                return self;
+            }
+
+            let token = if (*self.state).contVal.type_ == pg_sys::jbvType_jbvObject {
+                pg_sys::JsonbIteratorToken_WJB_VALUE
+            } else {
+                pg_sys::JsonbIteratorToken_WJB_ELEM
+            };
+            let mut value = value.to_jsonb_value();
+            self.push(token, &mut value)
+        }
+    }
+
+    /// Finish building, returning the new `jsonb` allocated in `mcx`, which it can't outlive.
+    ///
+    /// # Panics
+    ///
+    /// If nothing was added, or an object or array is still open
+    pub fn build<'mcx>(self, mcx: &MemCx<'mcx>) -> JsonBContainer<'mcx> {
+        // SAFETY: `mcx` is the `CurrentMemoryContext` while the copy is made, and the result
+        // borrows from it
+        mcx.exec_in(|| unsafe { self.finish() })
+    }
+
+    /// Finish building in the `CurrentMemoryContext`.
+    ///
+    /// # Safety
+    ///
+    /// The `CurrentMemoryContext` must not be reset or deleted for as long as `'mcx`
+    unsafe fn finish<'mcx>(self) -> JsonBContainer<'mcx> {
+        assert!(
+            self.state.is_null() && !self.result.is_null(),
+            "JsonBBuilder has an unterminated object or array"
+        );
+        let jsonb = pg_sys::JsonbValueToJsonb(self.result);
+        JsonBContainer {
+            container: NonNull::new_unchecked(std::ptr::addr_of_mut!((*jsonb).root)),
+            size: varsize_any_exhdr(jsonb.cast()),
+            _marker: PhantomData,
+        }
+    }
+
+    unsafe fn push(
+        &mut self,
+        token: pg_sys::JsonbIteratorToken,
+        value: *mut pg_sys::JsonbValue,
+    ) -> &mut Self {
+        self.result = pg_sys::pushJsonbValue(&mut self.state, token, value);
+        self
+    }
+}
+
+impl<'a> From<bool> for JsonBValue<'a> {
+    fn from(value: bool) -> Self {
+        JsonBValue::Bool(value)
+    }
+}
+
+impl<'a> From<&'a str> for JsonBValue<'a> {
+    fn from(value: &'a str) -> Self {
+        JsonBValue::String(value)
+    }
+}
+
+impl<'a> From<AnyNumeric> for JsonBValue<'a> {
+    fn from(value: AnyNumeric) -> Self {
+        JsonBValue::Numeric(value)
+    }
+}
+
+impl<'a> From<i32> for JsonBValue<'a> {
+    fn from(value: i32) -> Self {
+        JsonBValue::Numeric(value.into())
+    }
+}
+
+impl<'a> From<i64> for JsonBValue<'a> {
+    fn from(value: i64) -> Self {
+        JsonBValue::Numeric(value.into())
+    }
+}
+
+impl<'a> From<JsonBContainer<'a>> for JsonBValue<'a> {
+    fn from(value: JsonBContainer<'a>) -> Self {
+        JsonBValue::Container(value)
+    }
+}
+
+impl<'a, T: Into<JsonBValue<'a>>> From<Option<T>> for JsonBValue<'a> {
+    fn from(value: Option<T>) -> Self {
+        value.map(Into::into).unwrap_or(JsonBValue::Null)
+    }
+}
+
+impl From<JsonBContainer<'_>> for JsonB {
+    fn from(value: JsonBContainer<'_>) -> Self {
+        JsonB(value.to_value())
+    }
+}
+
+impl Debug for JsonBContainer<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("JsonBContainer").field(&self.to_value()).finish()
+    }
+}
+
+impl<'a> FromDatum for JsonBContainer<'a> {
+    unsafe fn from_polymorphic_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        _typoid: pg_sys::Oid,
+    ) -> Option<Self> {
+        if is_null {
+            None
+        } else {
+            // the container has to be aligned, so this can't use pg_detoast_datum_packed()
+            let jsonb = pg_sys::pg_detoast_datum(datum.cast_mut_ptr()).cast::<pg_sys::Jsonb>();
+            Some(JsonBContainer {
+                container: NonNull::new_unchecked(std::ptr::addr_of_mut!((*jsonb).root)),
+                size: varsize_any_exhdr(jsonb.cast()),
+                _marker: PhantomData,
+            })
+        }
+    }
+
+    unsafe fn from_datum_in_memory_context(
+        mut memory_context: PgMemoryContexts,
+        datum: pg_sys::Datum,
+        is_null: bool,
+        typoid: pg_sys::Oid,
+    ) -> Option<Self> {
+        if is_null {
+            None
+        } else {
+            memory_context.switch_to(|_| {
+                // the container borrows from the datum, so it needs its own copy in this context
+                let copy = pg_sys::pg_detoast_datum_copy(datum.cast_mut_ptr());
+                Self::from_polymorphic_datum(pg_sys::Datum::from(copy), false, typoid)
+            })
+        }
+    }
+}
+
+impl IntoDatum for JsonBContainer<'_> {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        // a nested container has no varlena header of its own, so this always makes a copy
+        unsafe {
+            let mut value = self.as_jsonb_value();
+            Some(pg_sys::Datum::from(pg_sys::JsonbValueToJsonb(&mut value)))
+        }
+    }
+
+    fn type_oid() -> pg_sys::Oid {
+        pg_sys::JSONBOID
+    }
+}
+
+unsafe impl SqlTranslatable for JsonBContainer<'_> {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        Ok(SqlMapping::literal("jsonb"))
+    }
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        Ok(Returns::One(SqlMapping::literal("jsonb")))
+    }
+}
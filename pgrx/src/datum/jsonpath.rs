@@ -0,0 +1,224 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+use crate::pg_sys::panic::CaughtError;
+use crate::{
+    direct_function_call, direct_function_call_as_datum, pg_sys, void_mut_ptr, FromDatum,
+    IntoDatum, JsonB,
+};
+use core::ffi::CStr;
+use pgrx_pg_sys::errcodes::PgSqlErrorCode;
+use pgrx_pg_sys::PgTryBuilder;
+use pgrx_sql_entity_graph::metadata::{
+    ArgumentError, Returns, ReturnsError, SqlMapping, SqlTranslatable,
+};
+use serde_json::Value;
+
+/// A `jsonpath` type from PostgreSQL
+///
+/// This holds the path's canonical text, as Postgres prints it.  Use [`JsonPath::query`] to apply
+/// it to a [`JsonB`] the way the `jsonb_path_*` SQL functions do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonPath(String);
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum JsonPathError {
+    #[error("invalid jsonpath: {0}")]
+    Syntax(String),
+    #[error("{message}")]
+    Execution { code: PgSqlErrorCode, message: String },
+}
+
+impl JsonPath {
+    /// Parse a `jsonpath`, such as `$.items[*] ? (@.price > $min)`
+    pub fn new<P: AsRef<str>>(path: P) -> Result<Self, JsonPathError> {
+        let path = alloc::ffi::CString::new(path.as_ref())
+            .map_err(|_| JsonPathError::Syntax("jsonpath contains a NUL byte".to_string()))?;
+        PgTryBuilder::new(|| unsafe {
+            let datum =
+                direct_function_call_as_datum(pg_sys::jsonpath_in, &[Some(path.as_ptr().into())])
+                    .expect("jsonpath_in returned NULL");
+            Ok(JsonPath::from_datum(datum, false).expect("jsonpath_out returned NULL"))
+        })
+        .catch_others(|e| match &e {
+            CaughtError::PostgresError(report)
+                if report.sql_error_code() == PgSqlErrorCode::ERRCODE_SYNTAX_ERROR
+                    || is_data_exception(report.sql_error_code()) =>
+            {
+                Err(JsonPathError::Syntax(report.message().to_string()))
+            }
+            _ => e.rethrow(),
+        })
+        .execute()
+    }
+
+    /// The path's canonical text
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Prepare to apply this path to `target`
+    pub fn query<'a>(&'a self, target: &'a JsonB) -> JsonPathQuery<'a> {
+        JsonPathQuery { path: self, target, vars: None, silent: false }
+    }
+}
+
+impl core::fmt::Display for JsonPath {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl core::str::FromStr for JsonPath {
+    type Err = JsonPathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        JsonPath::new(s)
+    }
+}
+
+/// Applies a [`JsonPath`] to a [`JsonB`], as the `jsonb_path_*` SQL functions do
+///
+/// Errors evaluating the path, such as a missing key in `strict` mode or a variable that wasn't
+/// given, are returned as [`JsonPathError::Execution`] unless the query is [`silent`], in which
+/// case they're treated as producing nothing, just as the SQL functions' `silent` argument does.
+///
+/// [`silent`]: JsonPathQuery::silent
+pub struct JsonPathQuery<'a> {
+    path: &'a JsonPath,
+    target: &'a JsonB,
+    vars: Option<&'a JsonB>,
+    silent: bool,
+}
+
+impl<'a> JsonPathQuery<'a> {
+    /// Values for the path's `$name` variables, as a JSON object
+    pub fn vars(mut self, vars: &'a JsonB) -> Self {
+        self.vars = Some(vars);
+        self
+    }
+
+    /// Suppress errors evaluating the path
+    pub fn silent(mut self, silent: bool) -> Self {
+        self.silent = silent;
+        self
+    }
+
+    /// Does the path return any item?  Like `jsonb_path_exists()`
+    ///
+    /// `None` when the path failed to evaluate in silent mode.
+    pub fn exists(&self) -> Result<Option<bool>, JsonPathError> {
+        self.execute(pg_sys::jsonb_path_exists)
+    }
+
+    /// The result of a predicate path, such as `$.price > 10`.  Like `jsonb_path_match()`
+    ///
+    /// `None` when the predicate's result is unknown.
+    pub fn matches(&self) -> Result<Option<bool>, JsonPathError> {
+        self.execute(pg_sys::jsonb_path_match)
+    }
+
+    /// All the items the path returns.  Like `jsonb_path_query_array()`
+    pub fn all(&self) -> Result<Vec<Value>, JsonPathError> {
+        match self.execute::<JsonB>(pg_sys::jsonb_path_query_array)? {
+            Some(JsonB(Value::Array(items))) => Ok(items),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// The first item the path returns.  Like `jsonb_path_query_first()`
+    pub fn first(&self) -> Result<Option<Value>, JsonPathError> {
+        Ok(self.execute::<JsonB>(pg_sys::jsonb_path_query_first)?.map(|JsonB(value)| value))
+    }
+
+    fn execute<R: FromDatum>(
+        &self,
+        func: unsafe fn(pg_sys::FunctionCallInfo) -> pg_sys::Datum,
+    ) -> Result<Option<R>, JsonPathError> {
+        let target = jsonb_datum(&self.target.0);
+        let vars = jsonb_datum(self.vars.map_or(&Value::Object(Default::default()), |v| &v.0));
+        let path = self.path.clone().into_datum();
+        let silent = self.silent.into_datum();
+        PgTryBuilder::new(|| unsafe {
+            Ok(direct_function_call::<R>(func, &[target, path, vars, silent]))
+        })
+        .catch_others(|e| match &e {
+            CaughtError::PostgresError(report) if is_data_exception(report.sql_error_code()) => {
+                Err(JsonPathError::Execution {
+                    code: report.sql_error_code(),
+                    message: report.message().to_string(),
+                })
+            }
+            _ => e.rethrow(),
+        })
+        .execute()
+    }
+}
+
+/// Errors evaluating jsonpaths are all in the "data exception" class, `22xxx`
+fn is_data_exception(code: PgSqlErrorCode) -> bool {
+    // the SQLSTATE's class is its first two characters, in the low 12 bits
+    const CLASS_MASK: isize = 0xFFF;
+    code as isize & CLASS_MASK == PgSqlErrorCode::ERRCODE_DATA_EXCEPTION as isize & CLASS_MASK
+}
+
+fn jsonb_datum(value: &Value) -> Option<pg_sys::Datum> {
+    let string = serde_json::to_string(value).expect("failed to serialize JsonB value");
+    let cstring =
+        alloc::ffi::CString::new(string).expect("string version of jsonb is not valid UTF8");
+    unsafe { direct_function_call_as_datum(pg_sys::jsonb_in, &[Some(cstring.as_ptr().into())]) }
+}
+
+/// for jsonpath
+impl FromDatum for JsonPath {
+    unsafe fn from_polymorphic_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        _: pg_sys::Oid,
+    ) -> Option<JsonPath> {
+        if is_null {
+            None
+        } else {
+            let cstr = direct_function_call::<&CStr>(pg_sys::jsonpath_out, &[Some(datum)])
+                .expect("failed to convert jsonpath to a cstring");
+            let path =
+                cstr.to_str().expect("text version of jsonpath is not valid UTF8").to_string();
+
+            // free the cstring returned from direct_function_call -- we don't need it anymore
+            pg_sys::pfree(cstr.as_ptr() as void_mut_ptr);
+
+            Some(JsonPath(path))
+        }
+    }
+}
+
+/// for jsonpath
+impl IntoDatum for JsonPath {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        let cstring =
+            alloc::ffi::CString::new(self.0).expect("string version of jsonpath is not valid UTF8");
+
+        unsafe {
+            direct_function_call_as_datum(pg_sys::jsonpath_in, &[Some(cstring.as_ptr().into())])
+        }
+    }
+
+    fn type_oid() -> pg_sys::Oid {
+        pg_sys::JSONPATHOID
+    }
+}
+
+unsafe impl SqlTranslatable for JsonPath {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        Ok(SqlMapping::literal("jsonpath"))
+    }
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        Ok(Returns::One(SqlMapping::literal("jsonpath")))
+    }
+}
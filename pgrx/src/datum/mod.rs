@@ -10,10 +10,17 @@
 //! Handing for easily converting Postgres Datum types into their corresponding Rust types
 //! and converting Rust types into their corresponding Postgres types
 mod anyarray;
+#[cfg(any(feature = "pg13", feature = "pg14", feature = "pg15", feature = "pg16"))]
+mod anycompatible;
 mod anyelement;
+mod anynonarray;
 mod array;
+mod borrowed;
+#[cfg(feature = "chrono")]
+mod chrono_support;
 mod date;
 pub mod datetime_support;
+mod detoasted;
 mod from;
 mod geo;
 mod inet;
@@ -22,25 +29,49 @@ mod interval;
 mod into;
 mod item_pointer_data;
 mod json;
+mod jsonb;
+#[cfg(any(
+    feature = "pg12",
+    feature = "pg13",
+    feature = "pg14",
+    feature = "pg15",
+    feature = "pg16"
+))]
+mod jsonpath;
+mod nullable;
 pub mod numeric;
 pub mod numeric_support;
+mod pg_char;
+mod pg_lsn;
+mod pg_str;
 #[deny(unsafe_op_in_unsafe_fn)]
 mod range;
+mod size;
+mod text_search;
 mod time;
 mod time_stamp;
 mod time_stamp_with_timezone;
 mod time_with_timezone;
 mod tuples;
+mod typed_oid;
 mod uuid;
+#[cfg(feature = "uuid-crate")]
+mod uuid_support;
+mod varbit;
 mod varlena;
 
 pub use self::time::*;
 pub use self::uuid::*;
 pub use anyarray::*;
+#[cfg(any(feature = "pg13", feature = "pg14", feature = "pg15", feature = "pg16"))]
+pub use anycompatible::*;
 pub use anyelement::*;
+pub use anynonarray::*;
 pub use array::*;
+pub use borrowed::*;
 pub use date::*;
 pub use datetime_support::*;
+pub use detoasted::*;
 pub use from::*;
 pub use geo::*;
 pub use inet::*;
@@ -49,14 +80,31 @@ pub use interval::*;
 pub use into::*;
 pub use item_pointer_data::*;
 pub use json::*;
-pub use numeric::{AnyNumeric, Numeric};
+pub use jsonb::*;
+#[cfg(any(
+    feature = "pg12",
+    feature = "pg13",
+    feature = "pg14",
+    feature = "pg15",
+    feature = "pg16"
+))]
+pub use jsonpath::*;
+pub use nullable::*;
+pub use numeric::{AnyNumeric, Numeric, PgNumeric};
 use once_cell::sync::Lazy;
+pub use pg_char::*;
+pub use pg_lsn::*;
+pub use pg_str::{PgBytes, PgStr, Toasted, VarlenaType};
 pub use range::*;
+pub use size::*;
 use std::any::TypeId;
+pub use text_search::*;
 pub use time_stamp::*;
 pub use time_stamp_with_timezone::*;
 pub use time_with_timezone::*;
 pub use tuples::*;
+pub use typed_oid::*;
+pub use varbit::*;
 pub use varlena::*;
 
 use crate::PgBox;
@@ -64,7 +112,12 @@ use pgrx_sql_entity_graph::RustSqlMapping;
 
 /// A tagging trait to indicate a user type is also meant to be used by Postgres
 /// Implemented automatically by `#[derive(PostgresType)]`
-pub trait PostgresType {}
+pub trait PostgresType {
+    /// How to compress the type's values as they're converted into Datums, set with
+    /// `#[compression = "pglz"]` or `#[compression = "lz4"]`.  See
+    /// [`compress_varlena`](crate::varlena::compress_varlena).
+    const COMPRESSION: Option<crate::varlena::ToastCompression> = None;
+}
 
 /// A type which can have it's [`core::any::TypeId`]s registered for Rust to SQL mapping.
 ///
@@ -516,3 +569,43 @@ impl<T: Copy + 'static> WithVarlenaTypeIds<T> {
         }
     }
 }
+
+impl<T: VarlenaType + 'static> WithVarlenaTypeIds<T> {
+    pub const TOASTED_ID: Lazy<Option<TypeId>> =
+        Lazy::new(|| Some(TypeId::of::<Toasted<'static, T>>()));
+    pub const OPTION_TOASTED_ID: Lazy<Option<TypeId>> =
+        Lazy::new(|| Some(TypeId::of::<Option<Toasted<'static, T>>>()));
+
+    pub fn register_toasted(
+        map: &mut std::collections::HashSet<RustSqlMapping>,
+        single_sql: String,
+    ) {
+        if let Some(id) = *WithVarlenaTypeIds::<T>::TOASTED_ID {
+            let rust = core::any::type_name::<Toasted<'static, T>>().to_string();
+            assert_eq!(
+                map.insert(RustSqlMapping {
+                    sql: single_sql.clone(),
+                    rust: rust.to_string(),
+                    id: id,
+                }),
+                true,
+                "Cannot map `{}` twice.",
+                rust,
+            );
+        }
+
+        if let Some(id) = *WithVarlenaTypeIds::<T>::OPTION_TOASTED_ID {
+            let rust = core::any::type_name::<Option<Toasted<'static, T>>>().to_string();
+            assert_eq!(
+                map.insert(RustSqlMapping {
+                    sql: single_sql.clone(),
+                    rust: rust.to_string(),
+                    id: id,
+                }),
+                true,
+                "Cannot map `{}` twice.",
+                rust,
+            );
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+use crate::{pg_sys, FromDatum, IntoDatum};
+use pgrx_sql_entity_graph::metadata::{
+    ArgumentError, Returns, ReturnsError, SqlMapping, SqlTranslatable,
+};
+
+/// A `#[pg_extern]` argument which may be SQL `NULL`.
+///
+/// Like `Option<T>`, an argument of this type stops the function from being inferred as `STRICT`,
+/// so Postgres calls it with `NULL`s rather than returning `NULL` on its behalf.  Unlike
+/// `Option<T>`, it spells out in the signature that `NULL` is an expected input to be handled,
+/// rather than an absent value.
+///
+/// An argument the function was called without is [`Nullable::Absent`], as `#[pg_extern]` checks
+/// the call's `fcinfo->nargs`.  That only happens when the function is called through a shorter
+/// SQL declaration of the same symbol, or directly from C:  Postgres substitutes argument defaults
+/// before calling the function, so an argument declared with `default!(Nullable<T>, NULL)` and
+/// omitted by the caller arrives as [`Nullable::Null`], just as if `NULL` were passed.
+///
+/// ```rust,no_run
+/// use pgrx::prelude::*;
+/// use pgrx::Nullable;
+///
+/// #[pg_extern]
+/// fn describe(value: Nullable<i32>) -> String {
+///     match value {
+///         Nullable::Absent => "absent".into(),
+///         Nullable::Null => "null".into(),
+///         Nullable::Valid(value) => value.to_string(),
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Nullable<T> {
+    /// The argument wasn't passed at all
+    Absent,
+    /// The argument was SQL `NULL`
+    Null,
+    Valid(T),
+}
+
+impl<T> Nullable<T> {
+    pub fn is_absent(&self) -> bool {
+        matches!(self, Nullable::Absent)
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Nullable::Null)
+    }
+
+    pub fn is_valid(&self) -> bool {
+        matches!(self, Nullable::Valid(_))
+    }
+
+    pub fn as_ref(&self) -> Nullable<&T> {
+        match self {
+            Nullable::Absent => Nullable::Absent,
+            Nullable::Null => Nullable::Null,
+            Nullable::Valid(value) => Nullable::Valid(value),
+        }
+    }
+
+    /// Both an absent argument and SQL `NULL` are `None`
+    pub fn into_option(self) -> Option<T> {
+        match self {
+            Nullable::Absent | Nullable::Null => None,
+            Nullable::Valid(value) => Some(value),
+        }
+    }
+}
+
+impl<T> From<Option<T>> for Nullable<T> {
+    fn from(value: Option<T>) -> Self {
+        match value {
+            None => Nullable::Null,
+            Some(value) => Nullable::Valid(value),
+        }
+    }
+}
+
+impl<T> From<Nullable<T>> for Option<T> {
+    fn from(value: Nullable<T>) -> Self {
+        value.into_option()
+    }
+}
+
+/// Converting a Datum never produces `None`, as SQL `NULL` becomes [`Nullable::Null`].  It never
+/// produces [`Nullable::Absent`] either, which only `#[pg_extern]`'s argument handling can tell.
+impl<T: FromDatum> FromDatum for Nullable<T> {
+    const GET_TYPOID: bool = T::GET_TYPOID;
+
+    #[inline]
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool) -> Option<Self> {
+        Some(T::from_datum(datum, is_null).into())
+    }
+
+    #[inline]
+    unsafe fn from_polymorphic_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        typoid: pg_sys::Oid,
+    ) -> Option<Self> {
+        Some(T::from_polymorphic_datum(datum, is_null, typoid).into())
+    }
+}
+
+impl<T: IntoDatum> IntoDatum for Nullable<T> {
+    #[inline]
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        self.into_option().and_then(IntoDatum::into_datum)
+    }
+
+    fn type_oid() -> pg_sys::Oid {
+        T::type_oid()
+    }
+
+    fn is_compatible_with(other: pg_sys::Oid) -> bool {
+        T::is_compatible_with(other)
+    }
+}
+
+unsafe impl<T: SqlTranslatable> SqlTranslatable for Nullable<T> {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        T::argument_sql()
+    }
+
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        T::return_sql()
+    }
+
+    fn optional() -> bool {
+        true
+    }
+}
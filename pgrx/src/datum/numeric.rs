@@ -14,7 +14,7 @@ use std::iter::Sum;
 
 use crate::numeric_support::convert::{from_primitive_helper, FromPrimitiveFunc};
 pub use crate::numeric_support::error::Error;
-use crate::{direct_function_call, pg_sys, varsize, PgMemoryContexts};
+use crate::{direct_function_call, pg_sys, varsize, IntoDatum, PgMemoryContexts};
 
 /// A wrapper around the Postgres SQL `NUMERIC(P, S)` type.  Its `Precision` and `Scale` values
 /// are known at compile-time to assist with scale conversions and general type safety.
@@ -45,6 +45,15 @@ pub struct AnyNumeric {
     pub(crate) need_pfree: bool,
 }
 
+/// Another name for [`AnyNumeric`], for those looking for pgrx's `numeric` type by the `Pg*` name
+/// most of its other wrappers use.
+///
+/// [`AnyNumeric`] already is the first-class `numeric`:  it does arithmetic with the `std::ops`
+/// traits, compares with `PartialOrd`, rounds with [`AnyNumeric::round`] and
+/// [`AnyNumeric::trunc`], and converts to and from Rust integers, floats and strings with
+/// `TryFrom`, all without going through text or losing precision.
+pub type PgNumeric = AnyNumeric;
+
 impl Clone for AnyNumeric {
     /// Performs a deep clone of this [`AnyNumeric`] into the [`pg_sys::CurrentMemoryContext`].
     fn clone(&self) -> Self {
@@ -177,6 +186,32 @@ impl AnyNumeric {
         unsafe { direct_function_call(pg_sys::numeric_floor, &[self.as_datum()]).unwrap() }
     }
 
+    /// Round this [`AnyNumeric`] to `scale` decimal places.  A negative `scale` rounds to the left
+    /// of the decimal point.
+    ///
+    /// Ties are rounded away from zero, as with SQL's `round(numeric, int)`.
+    pub fn round(&self, scale: i32) -> Self {
+        unsafe {
+            direct_function_call(pg_sys::numeric_round, &[self.as_datum(), scale.into_datum()])
+                .unwrap()
+        }
+    }
+
+    /// Truncate this [`AnyNumeric`] to `scale` decimal places.  A negative `scale` truncates to the
+    /// left of the decimal point.
+    pub fn trunc(&self, scale: i32) -> Self {
+        unsafe {
+            direct_function_call(pg_sys::numeric_trunc, &[self.as_datum(), scale.into_datum()])
+                .unwrap()
+        }
+    }
+
+    /// The number of decimal digits in the fractional part of this [`AnyNumeric`], or `None` if it
+    /// is not-a-number
+    pub fn scale(&self) -> Option<i32> {
+        unsafe { direct_function_call(pg_sys::numeric_scale, &[self.as_datum()]) }
+    }
+
     /// Calculate the greatest common divisor of this an another [`AnyNumeric`]
     #[cfg(not(any(feature = "pg11", feature = "pg12")))]
     pub fn gcd(&self, n: &AnyNumeric) -> AnyNumeric {
@@ -44,11 +44,18 @@ macro_rules! anynumeric_to_oversized_primitive {
         impl TryFrom<AnyNumeric> for $ty {
             type Error = Error;
 
+            #[allow(unknown_lints, clippy::unnecessary_fallible_conversions)]
             fn try_from(value: AnyNumeric) -> Result<Self, Self::Error> {
                 match to_primitive_helper::<$as_>(&value, pg_sys::$pg_func) {
-                    Ok(value) => Ok(value as $ty),
-                    Err(Error::OutOfRange(_)) => <$ty>::from_str(value.to_string().as_str())
-                        .map_err(|e| Error::OutOfRange(format!("{e}"))),
+                    Ok(value) => {
+                        <$ty>::try_from(value).map_err(|e| Error::OutOfRange(format!("{e}")))
+                    }
+                    // round first, like the smaller conversion did, so that a fractional value
+                    // isn't mistaken for an unparsable one
+                    Err(Error::OutOfRange(_)) => {
+                        <$ty>::from_str(value.round(0).to_string().as_str())
+                            .map_err(|e| Error::OutOfRange(format!("{e}")))
+                    }
                     Err(e) => Err(e),
                 }
             }
@@ -91,11 +98,18 @@ macro_rules! numeric_to_oversized_primitive {
         impl<const P: u32, const S: u32> TryFrom<Numeric<P, S>> for $ty {
             type Error = Error;
 
+            #[allow(unknown_lints, clippy::unnecessary_fallible_conversions)]
             fn try_from(value: Numeric<P, S>) -> Result<Self, Self::Error> {
                 match to_primitive_helper::<$as_>(&value, pg_sys::$pg_func) {
-                    Ok(value) => Ok(value as $ty),
-                    Err(Error::OutOfRange(_)) => <$ty>::from_str(value.to_string().as_str())
-                        .map_err(|e| Error::OutOfRange(format!("{e}"))),
+                    Ok(value) => {
+                        <$ty>::try_from(value).map_err(|e| Error::OutOfRange(format!("{e}")))
+                    }
+                    // round first, like the smaller conversion did, so that a fractional value
+                    // isn't mistaken for an unparsable one
+                    Err(Error::OutOfRange(_)) => {
+                        <$ty>::from_str(value.round(0).to_string().as_str())
+                            .map_err(|e| Error::OutOfRange(format!("{e}")))
+                    }
                     Err(e) => Err(e),
                 }
             }
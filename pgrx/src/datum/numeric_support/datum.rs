@@ -7,7 +7,10 @@
 //LICENSE All rights reserved.
 //LICENSE
 //LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
-use crate::{pg_sys, AnyNumeric, FromDatum, IntoDatum, Numeric, PgMemoryContexts};
+use crate::{
+    ereport, pg_sys, AnyNumeric, FromDatum, IntoDatum, Numeric, PgLogLevel, PgMemoryContexts,
+    PgSqlErrorCode,
+};
 
 impl FromDatum for AnyNumeric {
     #[inline]
@@ -95,3 +98,47 @@ impl<const P: u32, const S: u32> IntoDatum for Numeric<P, S> {
         pg_sys::NUMERICOID
     }
 }
+
+macro_rules! numeric_datum_for_int128 {
+    ($ty:ty) => {
+        /// for numeric, raising an ERROR if the value isn't a whole number in range.  Fractional
+        /// values are rounded, like Postgres' own numeric to integer casts
+        impl FromDatum for $ty {
+            #[inline]
+            unsafe fn from_polymorphic_datum(
+                datum: pg_sys::Datum,
+                is_null: bool,
+                typoid: pg_sys::Oid,
+            ) -> Option<Self> {
+                let numeric = AnyNumeric::from_polymorphic_datum(datum, is_null, typoid)?;
+                match <$ty>::try_from(numeric) {
+                    Ok(value) => Some(value),
+                    Err(e) => {
+                        ereport!(
+                            PgLogLevel::ERROR,
+                            PgSqlErrorCode::ERRCODE_NUMERIC_VALUE_OUT_OF_RANGE,
+                            format!("numeric is out of range for type {}: {e}", stringify!($ty))
+                        );
+                        unreachable!()
+                    }
+                }
+            }
+        }
+
+        /// for numeric
+        impl IntoDatum for $ty {
+            #[inline]
+            fn into_datum(self) -> Option<pg_sys::Datum> {
+                AnyNumeric::from(self).into_datum()
+            }
+
+            #[inline]
+            fn type_oid() -> pg_sys::Oid {
+                pg_sys::NUMERICOID
+            }
+        }
+    };
+}
+
+numeric_datum_for_int128!(i128);
+numeric_datum_for_int128!(u128);
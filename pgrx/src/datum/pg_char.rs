@@ -0,0 +1,141 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+use crate::{pg_sys, FromDatum, IntoDatum};
+use pgrx_sql_entity_graph::metadata::{
+    ArgumentError, Returns, ReturnsError, SqlMapping, SqlTranslatable,
+};
+use std::fmt;
+use std::str::FromStr;
+
+/// A `"char"` type from PostgreSQL: a single byte, which the system catalogs use for flags and
+/// kinds, such as `pg_class.relkind`
+///
+/// Unlike `i8`, which also maps to `"char"`, it prints and parses as Postgres 15 and later do: as
+/// the byte itself if it's ASCII, or as a backslash and three octal digits otherwise.  The zero
+/// byte prints as an empty string.
+///
+/// ```rust,no_run
+/// use pgrx::PgChar;
+///
+/// let relkind = PgChar::from(b'r');
+/// assert_eq!(relkind, 'r');
+/// assert_eq!(relkind.to_string(), "r");
+/// assert_eq!(PgChar(0o201).to_string(), "\\201");
+/// assert_eq!("\\201".parse::<PgChar>().unwrap(), PgChar(0o201));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[repr(transparent)]
+pub struct PgChar(pub u8);
+
+impl PgChar {
+    /// The byte as a Rust `char`, if it's ASCII
+    pub fn as_char(&self) -> Option<char> {
+        self.0.is_ascii().then_some(self.0 as char)
+    }
+}
+
+impl From<u8> for PgChar {
+    fn from(value: u8) -> Self {
+        PgChar(value)
+    }
+}
+
+impl From<PgChar> for u8 {
+    fn from(value: PgChar) -> Self {
+        value.0
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("'{0}' is not an ASCII character")]
+pub struct PgCharFromCharError(char);
+
+impl TryFrom<char> for PgChar {
+    type Error = PgCharFromCharError;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        if value.is_ascii() {
+            Ok(PgChar(value as u8))
+        } else {
+            Err(PgCharFromCharError(value))
+        }
+    }
+}
+
+impl PartialEq<char> for PgChar {
+    fn eq(&self, other: &char) -> bool {
+        self.as_char() == Some(*other)
+    }
+}
+
+impl fmt::Display for PgChar {
+    /// As Postgres' `charout()`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            0 => Ok(()),
+            byte if byte.is_ascii() => write!(f, "{}", byte as char),
+            byte => write!(f, "\\{byte:03o}"),
+        }
+    }
+}
+
+impl FromStr for PgChar {
+    type Err = std::convert::Infallible;
+
+    /// As Postgres' `charin()`: a backslash and three octal digits is that byte, otherwise it's
+    /// the first byte of the string, or zero if the string is empty
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = s.as_bytes();
+        if let [b'\\', digits @ ..] = bytes {
+            if digits.len() == 3 && digits.iter().all(|d| (b'0'..=b'7').contains(d)) {
+                if let Ok(byte) = u8::from_str_radix(&s[1..], 8) {
+                    return Ok(PgChar(byte));
+                }
+            }
+        }
+        Ok(PgChar(bytes.first().copied().unwrap_or(0)))
+    }
+}
+
+impl FromDatum for PgChar {
+    #[inline]
+    unsafe fn from_polymorphic_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        _typoid: pg_sys::Oid,
+    ) -> Option<PgChar> {
+        if is_null {
+            None
+        } else {
+            Some(PgChar(datum.value() as u8))
+        }
+    }
+}
+
+impl IntoDatum for PgChar {
+    #[inline]
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(pg_sys::Datum::from(self.0))
+    }
+
+    #[inline]
+    fn type_oid() -> pg_sys::Oid {
+        pg_sys::CHAROID
+    }
+}
+
+unsafe impl SqlTranslatable for PgChar {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        Ok(SqlMapping::As(String::from("\"char\"")))
+    }
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        Ok(Returns::One(SqlMapping::As(String::from("\"char\""))))
+    }
+}
@@ -0,0 +1,171 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+use crate::{pg_sys, FromDatum, IntoDatum};
+use pgrx_sql_entity_graph::metadata::{
+    ArgumentError, Returns, ReturnsError, SqlMapping, SqlTranslatable,
+};
+use std::fmt;
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+use std::str::FromStr;
+
+/// A `pg_lsn` type from PostgreSQL: a byte position in the write-ahead log
+///
+/// It prints as Postgres does, as the high and low 32 bits in hex, such as `16/B374D848`.
+/// Adding or subtracting a `u64` moves it by that many bytes, and subtracting another `PgLsn`
+/// gives the number of bytes between them, as `pg_wal_lsn_diff()` does.
+///
+/// ```rust,no_run
+/// use pgrx::PgLsn;
+///
+/// let flushed: PgLsn = "16/B374D848".parse().unwrap();
+/// let replayed: PgLsn = "16/B3740000".parse().unwrap();
+/// assert_eq!(flushed - replayed, 0xD848);
+/// assert_eq!((replayed + 0xD848).to_string(), "16/B374D848");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[repr(transparent)]
+pub struct PgLsn(pub u64);
+
+impl PgLsn {
+    /// `0/0`, which Postgres uses to mean "no position"
+    pub const INVALID: PgLsn = PgLsn(0);
+
+    pub fn is_valid(&self) -> bool {
+        *self != Self::INVALID
+    }
+
+    /// Move forward `bytes`, or return `None` if that overflows
+    pub fn checked_add(self, bytes: u64) -> Option<PgLsn> {
+        self.0.checked_add(bytes).map(PgLsn)
+    }
+
+    /// Move back `bytes`, or return `None` if that is before `0/0`
+    pub fn checked_sub(self, bytes: u64) -> Option<PgLsn> {
+        self.0.checked_sub(bytes).map(PgLsn)
+    }
+}
+
+impl From<u64> for PgLsn {
+    fn from(value: u64) -> Self {
+        PgLsn(value)
+    }
+}
+
+impl From<PgLsn> for u64 {
+    fn from(lsn: PgLsn) -> Self {
+        lsn.0
+    }
+}
+
+impl fmt::Display for PgLsn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:X}/{:X}", self.0 >> 32, self.0 as u32)
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("invalid input syntax for type pg_lsn: \"{0}\"")]
+pub struct PgLsnParseError(String);
+
+impl FromStr for PgLsn {
+    type Err = PgLsnParseError;
+
+    /// Parse the `XXX/XXX` format Postgres prints
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || PgLsnParseError(s.to_string());
+        let (hi, lo) = s.split_once('/').ok_or_else(error)?;
+        let parse = |half: &str| {
+            if half.is_empty() || half.len() > 8 {
+                return Err(error());
+            }
+            u32::from_str_radix(half, 16).map_err(|_| error())
+        };
+        Ok(PgLsn((parse(hi)? as u64) << 32 | parse(lo)? as u64))
+    }
+}
+
+impl Add<u64> for PgLsn {
+    type Output = PgLsn;
+
+    /// # Panics
+    ///
+    /// On overflow, as Postgres raises an ERROR for `pg_lsn + numeric`
+    fn add(self, bytes: u64) -> PgLsn {
+        self.checked_add(bytes).expect("pg_lsn out of range")
+    }
+}
+
+impl AddAssign<u64> for PgLsn {
+    fn add_assign(&mut self, bytes: u64) {
+        *self = *self + bytes;
+    }
+}
+
+impl Sub<u64> for PgLsn {
+    type Output = PgLsn;
+
+    /// # Panics
+    ///
+    /// If the result is before `0/0`
+    fn sub(self, bytes: u64) -> PgLsn {
+        self.checked_sub(bytes).expect("pg_lsn out of range")
+    }
+}
+
+impl SubAssign<u64> for PgLsn {
+    fn sub_assign(&mut self, bytes: u64) {
+        *self = *self - bytes;
+    }
+}
+
+impl Sub<PgLsn> for PgLsn {
+    type Output = i128;
+
+    /// The number of bytes from `other` to `self`, which is negative if `other` is later
+    fn sub(self, other: PgLsn) -> i128 {
+        self.0 as i128 - other.0 as i128
+    }
+}
+
+impl FromDatum for PgLsn {
+    #[inline]
+    unsafe fn from_polymorphic_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        _typoid: pg_sys::Oid,
+    ) -> Option<PgLsn> {
+        if is_null {
+            None
+        } else {
+            Some(PgLsn(datum.value() as u64))
+        }
+    }
+}
+
+impl IntoDatum for PgLsn {
+    #[inline]
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(pg_sys::Datum::from(self.0))
+    }
+
+    #[inline]
+    fn type_oid() -> pg_sys::Oid {
+        pg_sys::LSNOID
+    }
+}
+
+unsafe impl SqlTranslatable for PgLsn {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        Ok(SqlMapping::literal("pg_lsn"))
+    }
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        Ok(Returns::One(SqlMapping::literal("pg_lsn")))
+    }
+}
@@ -0,0 +1,612 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Lazily detoasted `text` and `bytea` values, borrowed for the duration of a function call
+//!
+//! `&str` and `&[u8]` arguments don't copy values stored inline in their tuple, but a large value
+//! is usually compressed or stored out of line, and those arguments detoast it in full before the
+//! function even starts.  [`PgStr`] and [`PgBytes`] hold on to the Datum as it was passed instead,
+//! so a function can find the value's length, or read only part of it, without detoasting the
+//! rest.  Returning one passes the original Datum back to Postgres, still toasted.
+//!
+//! [`Toasted`] does the same for any other varlena type, such as a `#[derive(PostgresType)]`, and
+//! converts the value to its Rust type only when asked.
+//!
+//! As `#[pg_extern]` arguments they're converted with [`FromDatumBorrowed`], so their lifetime is
+//! that of the call's memory context:  they can be returned, but not stored anywhere which
+//! outlives the call.  Whatever they detoast or transcode later on is allocated in that memory
+//! context too, rather than whichever one is current at the time.
+//!
+//! ```rust,no_run
+//! use pgrx::prelude::*;
+//! use pgrx::PgBytes;
+//!
+//! /// Does a (possibly huge) `bytea` start with the PNG signature?
+//! #[pg_extern]
+//! fn is_png(image: PgBytes<'_>) -> bool {
+//!     image.slice(0, 8) == b"\x89PNG\r\n\x1a\n"
+//! }
+//! ```
+//!
+//! ```rust,compile_fail
+//! use pgrx::prelude::*;
+//! use pgrx::PgStr;
+//! use std::sync::Mutex;
+//!
+//! static LAST: Mutex<Option<PgStr<'static>>> = Mutex::new(None);
+//!
+//! // the argument only lives as long as the call's memory context
+//! #[pg_extern]
+//! fn remember(text: PgStr<'static>) {
+//!     *LAST.lock().unwrap() = Some(text);
+//! }
+//! ```
+//!
+//! Converted with [`FromDatum`] instead, as the arguments of set-returning functions are, the
+//! lifetime is unchecked, and is up to the caller to uphold.
+use crate::datum::from::convert_varlena_to_str_memoized;
+use crate::memcxt::MemCx;
+use crate::{pg_sys, varlena, FromDatum, FromDatumBorrowed, IntoDatum, PgMemoryContexts};
+use core::cell::Cell;
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::Deref;
+use pgrx_sql_entity_graph::metadata::{
+    ArgumentError, Returns, ReturnsError, SqlMapping, SqlTranslatable,
+};
+
+/// A varlena Datum which is detoasted the first time its contents are needed
+struct LazyVarlena<'fcx> {
+    datum: pg_sys::Datum,
+    detoasted: Cell<*mut pg_sys::varlena>,
+    /// Where the value is detoasted or transcoded into, which lives at least as long as `'fcx`
+    context: pg_sys::MemoryContext,
+    _marker: PhantomData<&'fcx [u8]>,
+}
+
+impl<'fcx> LazyVarlena<'fcx> {
+    /// # Safety
+    ///
+    /// `datum` must be a non-null varlena Datum, which lives at least as long as `'fcx`, as must
+    /// the `CurrentMemoryContext`.
+    unsafe fn new(datum: pg_sys::Datum) -> Self {
+        Self::new_in(datum, pg_sys::CurrentMemoryContext)
+    }
+
+    /// # Safety
+    ///
+    /// `datum` must be a non-null varlena Datum, which lives at least as long as `'fcx`, as must
+    /// `context`.
+    unsafe fn new_in(datum: pg_sys::Datum, context: pg_sys::MemoryContext) -> Self {
+        Self { datum, detoasted: Cell::new(std::ptr::null_mut()), context, _marker: PhantomData }
+    }
+
+    /// Run `f` with the value's memory context as the `CurrentMemoryContext`, so whatever it
+    /// allocates lives as long as `'fcx`
+    fn exec_in<R>(&self, f: impl FnOnce() -> R) -> R {
+        unsafe {
+            // SAFETY:  `context` outlives `'fcx`, and so `self`
+            PgMemoryContexts::For(self.context).switch_to(|_| f())
+        }
+    }
+
+    fn len(&self) -> usize {
+        unsafe {
+            // SAFETY:  this only reads the varlena's header, or its TOAST pointer
+            pg_sys::toast_raw_datum_size(self.datum) - pg_sys::VARHDRSZ
+        }
+    }
+
+    fn is_detoasted(&self) -> bool {
+        !self.detoasted.get().is_null()
+    }
+
+    fn bytes(&self) -> &'fcx [u8] {
+        unsafe {
+            if self.detoasted.get().is_null() {
+                // this is a no-op for values which are neither compressed nor stored out of line
+                let detoasted =
+                    self.exec_in(|| pg_sys::pg_detoast_datum_packed(self.datum.cast_mut_ptr()));
+                self.detoasted.set(detoasted);
+            }
+            varlena::varlena_to_byte_slice(self.detoasted.get())
+        }
+    }
+
+    fn slice(&self, offset: usize, len: usize) -> &'fcx [u8] {
+        if self.is_detoasted() {
+            let bytes = self.bytes();
+            let start = offset.min(bytes.len());
+            let end = start.saturating_add(len).min(bytes.len());
+            return &bytes[start..end];
+        }
+
+        unsafe {
+            // only fetches and decompresses as much of the value as is needed for the slice
+            self.exec_in(|| varlena::detoast_slice(self.datum, offset, len))
+        }
+    }
+
+    /// # Safety
+    ///
+    /// The same as for [`FromDatum::from_datum_in_memory_context()`]
+    unsafe fn copied_into(
+        mut memory_context: PgMemoryContexts,
+        datum: pg_sys::Datum,
+    ) -> LazyVarlena<'fcx> {
+        memory_context.switch_to(|context| {
+            // `pg_detoast_datum_copy()` copies the value even if it isn't toasted
+            let copy = pg_sys::pg_detoast_datum_copy(datum.cast_mut_ptr());
+            let varlena = LazyVarlena::new_in(pg_sys::Datum::from(copy), context.value());
+            varlena.detoasted.set(copy);
+            varlena
+        })
+    }
+}
+
+/// A `text` value, borrowed from a function's arguments and detoasted only when its contents are
+/// needed
+///
+/// Derefs to `&str`, which detoasts the whole value.
+pub struct PgStr<'fcx>(LazyVarlena<'fcx>);
+
+impl<'fcx> PgStr<'fcx> {
+    /// The length of the value, in bytes, found without detoasting it
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Is the value zero bytes long?
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The value as a `&str`, detoasting it the first time this is called.
+    ///
+    /// # Panics
+    ///
+    /// If the value isn't valid UTF-8.
+    pub fn as_str(&self) -> &'fcx str {
+        unsafe {
+            // SAFETY:  `bytes()` has detoasted the value
+            let _ = self.0.bytes();
+            self.0.exec_in(|| convert_varlena_to_str_memoized(self.0.detoasted.get()))
+        }
+    }
+
+    /// The value's bytes, detoasting it the first time this is called.
+    pub fn as_bytes(&self) -> &'fcx [u8] {
+        self.0.bytes()
+    }
+
+    /// Up to `len` bytes of the value, starting at byte `offset`, detoasting only as much of the
+    /// value as that needs.  The bytes are in the database's encoding, and may begin or end partway
+    /// through a multibyte character.
+    pub fn slice(&self, offset: usize, len: usize) -> &'fcx [u8] {
+        self.0.slice(offset, len)
+    }
+
+    /// At most the first `len` bytes of the value, shortened to the last whole character, and
+    /// detoasting only as much of the value as that needs.  `len` counts bytes in the database's
+    /// encoding, and in a database whose encoding isn't UTF-8 the prefix is then transcoded.
+    ///
+    /// # Panics
+    ///
+    /// If the prefix isn't valid UTF-8.
+    pub fn prefix(&self, len: usize) -> &'fcx str {
+        let bytes = self.0.slice(0, len);
+        // SAFETY:  a transcoded copy is allocated in the value's memory context, like the slice is
+        match self.0.exec_in(|| unsafe { varlena::server_text_prefix(bytes) }) {
+            Ok(s) => s,
+            Err(e) => panic!("text is not valid UTF-8: {e}"),
+        }
+    }
+}
+
+impl<'fcx> Deref for PgStr<'fcx> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<'fcx> fmt::Debug for PgStr<'fcx> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<'fcx> fmt::Display for PgStr<'fcx> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<'fcx> FromDatum for PgStr<'fcx> {
+    #[inline]
+    unsafe fn from_polymorphic_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        _typoid: pg_sys::Oid,
+    ) -> Option<Self> {
+        if is_null || datum.is_null() {
+            None
+        } else {
+            Some(PgStr(LazyVarlena::new(datum)))
+        }
+    }
+
+    unsafe fn from_datum_in_memory_context(
+        memory_context: PgMemoryContexts,
+        datum: pg_sys::Datum,
+        is_null: bool,
+        _typoid: pg_sys::Oid,
+    ) -> Option<Self> {
+        if is_null || datum.is_null() {
+            None
+        } else {
+            Some(PgStr(LazyVarlena::copied_into(memory_context, datum)))
+        }
+    }
+}
+
+impl<'mcx> FromDatumBorrowed<'mcx> for PgStr<'mcx> {
+    #[inline]
+    unsafe fn from_datum_borrowed(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        _typoid: pg_sys::Oid,
+        memcx: &MemCx<'mcx>,
+    ) -> Option<Self> {
+        if is_null || datum.is_null() {
+            None
+        } else {
+            Some(PgStr(LazyVarlena::new_in(datum, memcx.value())))
+        }
+    }
+}
+
+impl<'fcx> IntoDatum for PgStr<'fcx> {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(self.0.datum)
+    }
+
+    fn type_oid() -> pg_sys::Oid {
+        pg_sys::TEXTOID
+    }
+
+    fn is_compatible_with(other: pg_sys::Oid) -> bool {
+        Self::type_oid() == other || other == pg_sys::VARCHAROID
+    }
+}
+
+unsafe impl<'fcx> SqlTranslatable for PgStr<'fcx> {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        Ok(SqlMapping::literal("TEXT"))
+    }
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        Ok(Returns::One(SqlMapping::literal("TEXT")))
+    }
+}
+
+/// A `bytea` value, borrowed from a function's arguments and detoasted only when its contents are
+/// needed
+///
+/// Derefs to `&[u8]`, which detoasts the whole value.
+pub struct PgBytes<'fcx>(LazyVarlena<'fcx>);
+
+impl<'fcx> PgBytes<'fcx> {
+    /// The length of the value, found without detoasting it
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Is the value zero bytes long?
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The value's bytes, detoasting it the first time this is called.
+    pub fn as_bytes(&self) -> &'fcx [u8] {
+        self.0.bytes()
+    }
+
+    /// Up to `len` bytes of the value, starting at `offset`.
+    ///
+    /// Unless the whole value has already been detoasted, this only fetches the parts of it
+    /// needed for the slice, which for a value stored out of line without compression is just
+    /// the TOAST chunks the slice covers.  Each call returns a new copy of those bytes.
+    pub fn slice(&self, offset: usize, len: usize) -> &'fcx [u8] {
+        self.0.slice(offset, len)
+    }
+}
+
+impl<'fcx> Deref for PgBytes<'fcx> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl<'fcx> fmt::Debug for PgBytes<'fcx> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PgBytes").field("len", &self.len()).finish()
+    }
+}
+
+impl<'fcx> FromDatum for PgBytes<'fcx> {
+    #[inline]
+    unsafe fn from_polymorphic_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        _typoid: pg_sys::Oid,
+    ) -> Option<Self> {
+        if is_null || datum.is_null() {
+            None
+        } else {
+            Some(PgBytes(LazyVarlena::new(datum)))
+        }
+    }
+
+    unsafe fn from_datum_in_memory_context(
+        memory_context: PgMemoryContexts,
+        datum: pg_sys::Datum,
+        is_null: bool,
+        _typoid: pg_sys::Oid,
+    ) -> Option<Self> {
+        if is_null || datum.is_null() {
+            None
+        } else {
+            Some(PgBytes(LazyVarlena::copied_into(memory_context, datum)))
+        }
+    }
+}
+
+impl<'mcx> FromDatumBorrowed<'mcx> for PgBytes<'mcx> {
+    #[inline]
+    unsafe fn from_datum_borrowed(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        _typoid: pg_sys::Oid,
+        memcx: &MemCx<'mcx>,
+    ) -> Option<Self> {
+        if is_null || datum.is_null() {
+            None
+        } else {
+            Some(PgBytes(LazyVarlena::new_in(datum, memcx.value())))
+        }
+    }
+}
+
+impl<'fcx> IntoDatum for PgBytes<'fcx> {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(self.0.datum)
+    }
+
+    fn type_oid() -> pg_sys::Oid {
+        pg_sys::BYTEAOID
+    }
+}
+
+unsafe impl<'fcx> SqlTranslatable for PgBytes<'fcx> {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        Ok(SqlMapping::literal("bytea"))
+    }
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        Ok(Returns::One(SqlMapping::literal("bytea")))
+    }
+}
+
+/// Types whose values are `varlena`s, which a [`Toasted`] can hold
+///
+/// It's implemented for the builtin `text`, `bytea`, `json`, `jsonb`, and `numeric` types, and for
+/// every `#[derive(PostgresType)]` type.  It's sealed, as a [`Toasted`] of a pass-by-value type
+/// would read its value as a pointer.
+///
+/// ```rust,compile_fail
+/// fn len(x: pgrx::Toasted<'_, i32>) -> usize {
+///     x.len()
+/// }
+/// ```
+pub trait VarlenaType: sealed::Sealed {}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+impl<T: crate::PostgresType> sealed::Sealed for T {}
+impl<T: crate::PostgresType> VarlenaType for T {}
+
+macro_rules! impl_varlena_type {
+    ($($(<$lt:lifetime>)? $t:ty),+) => {
+        $(
+            impl$(<$lt>)? sealed::Sealed for $t {}
+            impl$(<$lt>)? VarlenaType for $t {}
+        )+
+    };
+}
+
+impl_varlena_type!(
+    String,
+    <'a> &'a str,
+    Vec<u8>,
+    <'a> &'a [u8],
+    crate::Json,
+    crate::JsonB,
+    crate::AnyNumeric
+);
+
+/// A value of any varlena type `T`, borrowed from a function's arguments and only detoasted and
+/// converted to a `T` when asked for
+///
+/// Useful for custom types whose values may be large, when a function only needs their size or
+/// some of their bytes, or only needs the whole value some of the time.
+///
+/// ```rust,no_run
+/// use pgrx::prelude::*;
+/// use pgrx::Toasted;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(PostgresType, Serialize, Deserialize)]
+/// pub struct Document {
+///     body: String,
+/// }
+///
+/// /// Only deserializes documents small enough to be worth looking at
+/// #[pg_extern]
+/// fn small_body(doc: Toasted<'_, Document>) -> Option<String> {
+///     (doc.len() < 1024).then(|| doc.get().body)
+/// }
+/// ```
+pub struct Toasted<'fcx, T> {
+    varlena: LazyVarlena<'fcx>,
+    typoid: pg_sys::Oid,
+    __marker: PhantomData<T>,
+}
+
+impl<'fcx, T: FromDatum + VarlenaType> Toasted<'fcx, T> {
+    /// The length of the value, in bytes and without its header, found without detoasting it
+    pub fn len(&self) -> usize {
+        self.varlena.len()
+    }
+
+    /// Is the value zero bytes long?
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Has the whole value been detoasted yet?
+    pub fn is_detoasted(&self) -> bool {
+        self.varlena.is_detoasted()
+    }
+
+    /// Up to `len` bytes of the value, starting at byte `offset`, detoasting only as much of the
+    /// value as that needs.
+    pub fn slice(&self, offset: usize, len: usize) -> &'fcx [u8] {
+        self.varlena.slice(offset, len)
+    }
+
+    /// The value's bytes, without its header, detoasting it the first time this is called.
+    pub fn as_bytes(&self) -> &'fcx [u8] {
+        self.varlena.bytes()
+    }
+
+    /// Convert the value to a `T`.  The value is detoasted the first time this is called, and each
+    /// call converts that detoasted copy again.
+    pub fn get(&self) -> T {
+        let _ = self.varlena.bytes();
+        unsafe {
+            // SAFETY:  the Datum was given to us as a non-null value of a type `T` is made from
+            let datum = pg_sys::Datum::from(self.varlena.detoasted.get());
+            self.varlena
+                .exec_in(|| T::from_polymorphic_datum(datum, false, self.typoid))
+                .expect("a detoasted varlena should not be NULL")
+        }
+    }
+}
+
+impl<'fcx, T> fmt::Debug for Toasted<'fcx, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Toasted")
+            .field("type", &core::any::type_name::<T>())
+            .field("len", &self.varlena.len())
+            .finish()
+    }
+}
+
+impl<'fcx, T: FromDatum + IntoDatum + VarlenaType> FromDatum for Toasted<'fcx, T> {
+    const GET_TYPOID: bool = true;
+
+    #[inline]
+    unsafe fn from_polymorphic_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        typoid: pg_sys::Oid,
+    ) -> Option<Self> {
+        if is_null || datum.is_null() {
+            None
+        } else {
+            let typoid = Self::value_type(typoid);
+            Some(Toasted { varlena: LazyVarlena::new(datum), typoid, __marker: PhantomData })
+        }
+    }
+
+    unsafe fn from_datum_in_memory_context(
+        memory_context: PgMemoryContexts,
+        datum: pg_sys::Datum,
+        is_null: bool,
+        typoid: pg_sys::Oid,
+    ) -> Option<Self> {
+        if is_null || datum.is_null() {
+            None
+        } else {
+            Some(Toasted {
+                varlena: LazyVarlena::copied_into(memory_context, datum),
+                typoid: Self::value_type(typoid),
+                __marker: PhantomData,
+            })
+        }
+    }
+}
+
+impl<'mcx, T: FromDatum + IntoDatum + VarlenaType> FromDatumBorrowed<'mcx> for Toasted<'mcx, T> {
+    #[inline]
+    unsafe fn from_datum_borrowed(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        typoid: pg_sys::Oid,
+        memcx: &MemCx<'mcx>,
+    ) -> Option<Self> {
+        if is_null || datum.is_null() {
+            None
+        } else {
+            Some(Toasted {
+                varlena: LazyVarlena::new_in(datum, memcx.value()),
+                typoid: Self::value_type(typoid),
+                __marker: PhantomData,
+            })
+        }
+    }
+}
+
+impl<'fcx, T: FromDatum + IntoDatum + VarlenaType> Toasted<'fcx, T> {
+    /// The type of the value, which is `T`'s own type if Postgres can't tell us
+    fn value_type(typoid: pg_sys::Oid) -> pg_sys::Oid {
+        if typoid == pg_sys::InvalidOid {
+            T::type_oid()
+        } else {
+            typoid
+        }
+    }
+}
+
+impl<'fcx, T: IntoDatum> IntoDatum for Toasted<'fcx, T> {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(self.varlena.datum)
+    }
+
+    fn type_oid() -> pg_sys::Oid {
+        T::type_oid()
+    }
+
+    fn is_compatible_with(other: pg_sys::Oid) -> bool {
+        T::is_compatible_with(other)
+    }
+}
+
+unsafe impl<'fcx, T: SqlTranslatable> SqlTranslatable for Toasted<'fcx, T> {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        T::argument_sql()
+    }
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        T::return_sql()
+    }
+}
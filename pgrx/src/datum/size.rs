@@ -0,0 +1,44 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+use crate::{pg_sys, varlena, IntoDatum};
+use core::ffi::CStr;
+
+/// The number of bytes `value` takes as a Datum of its SQL type, as `pg_column_size()` would
+/// report it.
+///
+/// A fixed-size type's length is its `typlen`, a `cstring` includes its NUL terminator, and a
+/// varlena includes its header and is measured as stored: a value which [`IntoDatum`] compresses,
+/// or which is a TOAST pointer, reports its compressed size.  Use
+/// [`varlena::toast_raw_size`] for the size of a varlena once detoasted.
+///
+/// Returns `None` if `value` converts into a SQL `NULL`.
+///
+/// ```rust,no_run
+/// use pgrx::datum_size;
+///
+/// assert_eq!(datum_size(&42i64), Some(8));
+/// assert_eq!(datum_size(&"hello"), Some(9));
+/// ```
+pub fn datum_size<T: IntoDatum + Clone>(value: &T) -> Option<usize> {
+    let datum = value.clone().into_datum()?;
+    let mut typlen = 0;
+    let mut typbyval = false;
+    unsafe {
+        // SAFETY:  get_typlenbyval() raises an ERROR if the type doesn't exist
+        pg_sys::get_typlenbyval(T::type_oid(), &mut typlen, &mut typbyval);
+    }
+
+    Some(match typlen {
+        // SAFETY:  a type with a typlen of -1 is a varlena, and one of -2 a cstring
+        -1 => unsafe { varlena::toast_size(datum) },
+        -2 => unsafe { CStr::from_ptr(datum.cast_mut_ptr()).to_bytes_with_nul().len() },
+        typlen => typlen as usize,
+    })
+}
@@ -0,0 +1,480 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! `tsvector` and `tsquery`, the types of Postgres' full text search
+use crate::pg_sys::panic::CaughtError;
+use crate::{direct_function_call, direct_function_call_as_datum, pg_sys, FromDatum, IntoDatum};
+use core::ffi::CStr;
+use pgrx_pg_sys::errcodes::PgSqlErrorCode;
+use pgrx_pg_sys::PgTryBuilder;
+use pgrx_sql_entity_graph::metadata::{
+    ArgumentError, Returns, ReturnsError, SqlMapping, SqlTranslatable,
+};
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum TextSearchError {
+    #[error("invalid tsvector: {0}")]
+    TsVector(String),
+    #[error("invalid tsquery: {0}")]
+    TsQuery(String),
+}
+
+/// The weight of a lexeme's position in a [`TsVector`], from the most important, `A`, to the
+/// default, `D`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum TsWeight {
+    #[default]
+    D,
+    C,
+    B,
+    A,
+}
+
+impl TsWeight {
+    fn from_bits(bits: u16) -> Self {
+        match bits & 3 {
+            3 => TsWeight::A,
+            2 => TsWeight::B,
+            1 => TsWeight::C,
+            _ => TsWeight::D,
+        }
+    }
+
+    fn letter(self) -> char {
+        match self {
+            TsWeight::A => 'A',
+            TsWeight::B => 'B',
+            TsWeight::C => 'C',
+            TsWeight::D => 'D',
+        }
+    }
+}
+
+/// Where a lexeme occurs in a document, and how important that occurrence is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TsPosition {
+    /// From 1 to 16383.  Postgres clamps greater positions to 16383.
+    pub position: u16,
+    pub weight: TsWeight,
+}
+
+impl TsPosition {
+    pub fn new(position: u16, weight: TsWeight) -> Self {
+        Self { position, weight }
+    }
+}
+
+impl From<u16> for TsPosition {
+    fn from(position: u16) -> Self {
+        Self { position, weight: TsWeight::D }
+    }
+}
+
+/// A lexeme of a [`TsVector`], with the positions it occurs at, if the vector has them
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TsLexeme {
+    pub lexeme: String,
+    pub positions: Vec<TsPosition>,
+}
+
+/// A `tsvector` type from PostgreSQL
+///
+/// This holds the vector's lexemes in the order Postgres keeps them, each with its positions
+/// sorted and without duplicates.
+///
+/// ```rust,no_run
+/// use pgrx::{TsPosition, TsQuery, TsVector, TsWeight};
+///
+/// let title = TsVector::from_lexemes([
+///     ("fat", vec![TsPosition::new(2, TsWeight::A)]),
+///     ("rat", vec![TsPosition::from(3)]),
+/// ])?;
+/// assert!(title.matches(&TsQuery::new("fat & rat")?));
+/// for lexeme in title.iter() {
+///     println!("{} at {:?}", lexeme.lexeme, lexeme.positions);
+/// }
+/// # Ok::<(), pgrx::TextSearchError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TsVector(Vec<TsLexeme>);
+
+impl TsVector {
+    /// Build a vector from lexemes and their positions, as Postgres would parse it from text.
+    ///
+    /// Lexemes are not normalized, as `to_tsvector()` would, but they are sorted, and the
+    /// positions of repeated lexemes are merged.
+    pub fn from_lexemes<I, S, P>(lexemes: I) -> Result<Self, TextSearchError>
+    where
+        I: IntoIterator<Item = (S, P)>,
+        S: AsRef<str>,
+        P: IntoIterator,
+        P::Item: Into<TsPosition>,
+    {
+        let mut text = String::new();
+        for (lexeme, positions) in lexemes {
+            if !text.is_empty() {
+                text.push(' ');
+            }
+            push_quoted(&mut text, lexeme.as_ref());
+            let mut positions = positions.into_iter().map(Into::into).peekable();
+            if positions.peek().is_some() {
+                text.push(':');
+            }
+            for (i, position) in positions.enumerate() {
+                if i > 0 {
+                    text.push(',');
+                }
+                push_position(&mut text, position);
+            }
+        }
+        Self::new(text)
+    }
+
+    /// Parse a `tsvector` from its text form, such as `'fat':2A 'rat':3`
+    pub fn new<S: AsRef<str>>(text: S) -> Result<Self, TextSearchError> {
+        let datum =
+            text_search_in(pg_sys::tsvectorin, text.as_ref()).map_err(TextSearchError::TsVector)?;
+        Ok(unsafe { TsVector::from_datum(datum, false) }.expect("tsvectorin returned NULL"))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The vector's lexemes, in the order Postgres keeps them
+    pub fn iter(&self) -> impl Iterator<Item = &TsLexeme> {
+        self.0.iter()
+    }
+
+    /// The lexeme, if the vector has it
+    pub fn get(&self, lexeme: &str) -> Option<&TsLexeme> {
+        self.0.iter().find(|entry| entry.lexeme == lexeme)
+    }
+
+    /// Does this vector match `query`, as `tsvector @@ tsquery` does?
+    pub fn matches(&self, query: &TsQuery) -> bool {
+        unsafe {
+            direct_function_call::<bool>(
+                pg_sys::ts_match_vq,
+                &[self.clone().into_datum(), query.clone().into_datum()],
+            )
+            .unwrap_or(false)
+        }
+    }
+}
+
+impl core::fmt::Display for TsVector {
+    /// The vector's text form, as Postgres prints it
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut text = String::new();
+        for (i, entry) in self.0.iter().enumerate() {
+            if i > 0 {
+                text.push(' ');
+            }
+            push_quoted(&mut text, &entry.lexeme);
+            for (i, position) in entry.positions.iter().enumerate() {
+                text.push(if i == 0 { ':' } else { ',' });
+                push_position(&mut text, *position);
+            }
+        }
+        f.write_str(&text)
+    }
+}
+
+impl core::str::FromStr for TsVector {
+    type Err = TextSearchError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TsVector::new(s)
+    }
+}
+
+impl IntoIterator for TsVector {
+    type Item = TsLexeme;
+    type IntoIter = std::vec::IntoIter<TsLexeme>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+fn push_quoted(text: &mut String, lexeme: &str) {
+    text.push('\'');
+    for c in lexeme.chars() {
+        if c == '\'' || c == '\\' {
+            text.push(c);
+        }
+        text.push(c);
+    }
+    text.push('\'');
+}
+
+fn push_position(text: &mut String, position: TsPosition) {
+    text.push_str(&position.position.to_string());
+    if position.weight != TsWeight::D {
+        text.push(position.weight.letter());
+    }
+}
+
+/// for tsvector
+impl FromDatum for TsVector {
+    unsafe fn from_polymorphic_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        _: pg_sys::Oid,
+    ) -> Option<TsVector> {
+        if is_null {
+            return None;
+        }
+
+        let vector = pg_sys::pg_detoast_datum(datum.cast_mut_ptr()) as *const pg_sys::TSVectorData;
+        let size = (*vector).size as usize;
+        let entries = (*vector).entries.as_slice(size);
+        // the lexemes, and their positions, follow the entries
+        let strings = entries.as_ptr().add(size) as *const u8;
+        let lexemes = entries
+            .iter()
+            .map(|entry| {
+                let (pos, len) = (entry.pos() as usize, entry.len() as usize);
+                let lexeme = std::slice::from_raw_parts(strings.add(pos), len);
+                let lexeme = std::str::from_utf8(lexeme).expect("lexeme is not valid UTF8");
+
+                let mut positions = Vec::new();
+                if entry.haspos() != 0 {
+                    // a count of positions, and the positions, start at the next 2-byte boundary
+                    let start = strings.add((pos + len + 1) & !1) as *const u16;
+                    let npos = start.read_unaligned() as usize;
+                    for i in 1..=npos {
+                        let word_entry_pos = start.add(i).read_unaligned();
+                        positions.push(TsPosition {
+                            position: word_entry_pos & 0x3fff,
+                            weight: TsWeight::from_bits(word_entry_pos >> 14),
+                        });
+                    }
+                }
+                TsLexeme { lexeme: lexeme.to_string(), positions }
+            })
+            .collect();
+
+        if vector as *mut pg_sys::varlena != datum.cast_mut_ptr() {
+            pg_sys::pfree(vector as *mut _);
+        }
+        Some(TsVector(lexemes))
+    }
+}
+
+/// for tsvector
+impl IntoDatum for TsVector {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        let text = self.to_string();
+        Some(text_search_in(pg_sys::tsvectorin, &text).expect("a TsVector's text is valid"))
+    }
+
+    fn type_oid() -> pg_sys::Oid {
+        pg_sys::TSVECTOROID
+    }
+}
+
+unsafe impl SqlTranslatable for TsVector {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        Ok(SqlMapping::literal("tsvector"))
+    }
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        Ok(Returns::One(SqlMapping::literal("tsvector")))
+    }
+}
+
+/// An item of a [`TsQuery`], which lists them in prefix order: each operator is followed by its
+/// operands, the right one first
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TsQueryItem {
+    /// A lexeme to match, only at positions with one of `weights` unless that's empty, and as a
+    /// prefix of the vector's lexemes if `prefix` is set, as in `'rat':*AB`
+    Lexeme { lexeme: String, weights: Vec<TsWeight>, prefix: bool },
+    /// `!`
+    Not,
+    /// `&`
+    And,
+    /// `|`
+    Or,
+    /// `<->`, or `<N>` for a `distance` of `N`
+    Phrase { distance: u16 },
+}
+
+/// A `tsquery` type from PostgreSQL
+///
+/// This holds the query's canonical text, as Postgres prints it, and its items.  Use
+/// [`TsQuery::matches`] or [`TsVector::matches`] to evaluate it against a vector.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TsQuery {
+    text: String,
+    items: Vec<TsQueryItem>,
+}
+
+impl TsQuery {
+    /// Parse a `tsquery`, such as `fat & (rat | cat)`
+    ///
+    /// Like a `::tsquery` cast, this doesn't normalize the query's words, as `to_tsquery()`
+    /// would.
+    pub fn new<S: AsRef<str>>(text: S) -> Result<Self, TextSearchError> {
+        let datum =
+            text_search_in(pg_sys::tsqueryin, text.as_ref()).map_err(TextSearchError::TsQuery)?;
+        Ok(unsafe { TsQuery::from_datum(datum, false) }.expect("tsqueryin returned NULL"))
+    }
+
+    /// The query's canonical text
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// The query's items, operators before their operands
+    pub fn items(&self) -> &[TsQueryItem] {
+        &self.items
+    }
+
+    /// The lexemes the query looks for
+    pub fn lexemes(&self) -> impl Iterator<Item = &str> {
+        self.items.iter().filter_map(|item| match item {
+            TsQueryItem::Lexeme { lexeme, .. } => Some(lexeme.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Does `vector` match this query, as `tsvector @@ tsquery` does?
+    pub fn matches(&self, vector: &TsVector) -> bool {
+        vector.matches(self)
+    }
+}
+
+impl core::fmt::Display for TsQuery {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl core::str::FromStr for TsQuery {
+    type Err = TextSearchError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TsQuery::new(s)
+    }
+}
+
+/// for tsquery
+impl FromDatum for TsQuery {
+    unsafe fn from_polymorphic_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        _: pg_sys::Oid,
+    ) -> Option<TsQuery> {
+        if is_null {
+            return None;
+        }
+
+        let query = pg_sys::pg_detoast_datum(datum.cast_mut_ptr()) as *const pg_sys::TSQueryData;
+        let size = (*query).size as usize;
+        let items =
+            std::slice::from_raw_parts((*query).data.as_ptr() as *const pg_sys::QueryItem, size);
+        // the operands' strings follow the items
+        let operands = items.as_ptr().add(size) as *const u8;
+        let items = items
+            .iter()
+            .filter_map(|item| match item.type_ as u32 {
+                pg_sys::QI_VAL => {
+                    let operand = &item.qoperand;
+                    let lexeme = std::slice::from_raw_parts(
+                        operands.add(operand.distance() as usize),
+                        operand.length() as usize,
+                    );
+                    let lexeme = std::str::from_utf8(lexeme).expect("lexeme is not valid UTF8");
+                    // bit 3 is weight A, down to bit 0 for weight D
+                    let weights = [TsWeight::A, TsWeight::B, TsWeight::C, TsWeight::D]
+                        .into_iter()
+                        .filter(|weight| operand.weight & (1 << *weight as u8) != 0)
+                        .collect();
+                    Some(TsQueryItem::Lexeme {
+                        lexeme: lexeme.to_string(),
+                        weights,
+                        prefix: operand.prefix,
+                    })
+                }
+                pg_sys::QI_OPR => match item.qoperator.oper as u32 {
+                    pg_sys::OP_NOT => Some(TsQueryItem::Not),
+                    pg_sys::OP_AND => Some(TsQueryItem::And),
+                    pg_sys::OP_OR => Some(TsQueryItem::Or),
+                    pg_sys::OP_PHRASE => {
+                        Some(TsQueryItem::Phrase { distance: item.qoperator.distance as u16 })
+                    }
+                    other => panic!("unrecognized tsquery operator: {other}"),
+                },
+                // stop words are removed from stored queries
+                _ => None,
+            })
+            .collect();
+
+        let text = direct_function_call::<&CStr>(pg_sys::tsqueryout, &[Some(datum)])
+            .expect("tsqueryout returned NULL");
+        let text = text.to_str().expect("text version of tsquery is not valid UTF8").to_string();
+
+        if query as *mut pg_sys::varlena != datum.cast_mut_ptr() {
+            pg_sys::pfree(query as *mut _);
+        }
+        Some(TsQuery { text, items })
+    }
+}
+
+/// for tsquery
+impl IntoDatum for TsQuery {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(text_search_in(pg_sys::tsqueryin, &self.text).expect("a TsQuery's text is valid"))
+    }
+
+    fn type_oid() -> pg_sys::Oid {
+        pg_sys::TSQUERYOID
+    }
+}
+
+unsafe impl SqlTranslatable for TsQuery {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        Ok(SqlMapping::literal("tsquery"))
+    }
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        Ok(Returns::One(SqlMapping::literal("tsquery")))
+    }
+}
+
+/// Call the input function `func` on `text`, returning the message of any error it raises about
+/// the text
+fn text_search_in(
+    func: unsafe fn(pg_sys::FunctionCallInfo) -> pg_sys::Datum,
+    text: &str,
+) -> Result<pg_sys::Datum, String> {
+    let text = std::ffi::CString::new(text).map_err(|_| "contains a NUL byte".to_string())?;
+    PgTryBuilder::new(|| unsafe {
+        Ok(direct_function_call_as_datum(func, &[Some(text.as_ptr().into())])
+            .expect("text search input function returned NULL"))
+    })
+    .catch_others(|e| match &e {
+        CaughtError::PostgresError(report)
+            if matches!(
+                report.sql_error_code(),
+                PgSqlErrorCode::ERRCODE_SYNTAX_ERROR
+                    | PgSqlErrorCode::ERRCODE_PROGRAM_LIMIT_EXCEEDED
+            ) =>
+        {
+            Err(report.message().to_string())
+        }
+        _ => e.rethrow(),
+    })
+    .execute()
+}
@@ -29,6 +29,11 @@ pub struct Timestamp(pg_sys::Timestamp);
 impl From<(Date, Time)> for Timestamp {
     fn from(value: (Date, Time)) -> Self {
         let (date, time) = value;
+        if date.is_infinity() {
+            return Timestamp::positive_infinity();
+        } else if date.is_neg_infinity() {
+            return Timestamp::negative_infinity();
+        }
         Timestamp::new(
             date.year(),
             date.month(),
@@ -210,49 +215,145 @@ impl Timestamp {
         self.0 == Self::NEG_INFINITY
     }
 
+    /// Does this [`Timestamp`] represent either positive or negative infinity?
+    #[inline]
+    pub fn is_infinite(&self) -> bool {
+        self.is_infinity() || self.is_neg_infinity()
+    }
+
     /// Extract the `month`
+    ///
+    /// # Panics
+    ///
+    /// Panics if this [`Timestamp`] is infinite.  Use [`Timestamp::try_month`] to handle that case.
     pub fn month(&self) -> u8 {
-        self.extract_part(DateTimeParts::Month).unwrap().try_into().unwrap()
+        self.try_month().expect("infinite Timestamp has no month")
+    }
+
+    /// Like [`Timestamp::month`], but returns `None` if this [`Timestamp`] is infinite
+    pub fn try_month(&self) -> Option<u8> {
+        if self.is_infinite() {
+            return None;
+        }
+        self.extract_part(DateTimeParts::Month).map(|part| part.try_into().unwrap())
     }
 
     /// Extract the `day`
+    ///
+    /// # Panics
+    ///
+    /// Panics if this [`Timestamp`] is infinite.  Use [`Timestamp::try_day`] to handle that case.
     pub fn day(&self) -> u8 {
-        self.extract_part(DateTimeParts::Day).unwrap().try_into().unwrap()
+        self.try_day().expect("infinite Timestamp has no day")
+    }
+
+    /// Like [`Timestamp::day`], but returns `None` if this [`Timestamp`] is infinite
+    pub fn try_day(&self) -> Option<u8> {
+        if self.is_infinite() {
+            return None;
+        }
+        self.extract_part(DateTimeParts::Day).map(|part| part.try_into().unwrap())
     }
 
     /// Extract the `year`
+    ///
+    /// # Panics
+    ///
+    /// Panics if this [`Timestamp`] is infinite.  Use [`Timestamp::try_year`] to handle that case.
     pub fn year(&self) -> i32 {
-        self.extract_part(DateTimeParts::Year).unwrap().try_into().unwrap()
+        self.try_year().expect("infinite Timestamp has no year")
+    }
+
+    /// Like [`Timestamp::year`], but returns `None` if this [`Timestamp`] is infinite
+    pub fn try_year(&self) -> Option<i32> {
+        if self.is_infinite() {
+            return None;
+        }
+        self.extract_part(DateTimeParts::Year).map(|part| part.try_into().unwrap())
     }
 
     /// Extract the `hour`
+    ///
+    /// # Panics
+    ///
+    /// Panics if this [`Timestamp`] is infinite.  Use [`Timestamp::try_hour`] to handle that case.
     pub fn hour(&self) -> u8 {
-        self.extract_part(DateTimeParts::Hour).unwrap().try_into().unwrap()
+        self.try_hour().expect("infinite Timestamp has no hour")
+    }
+
+    /// Like [`Timestamp::hour`], but returns `None` if this [`Timestamp`] is infinite
+    pub fn try_hour(&self) -> Option<u8> {
+        if self.is_infinite() {
+            return None;
+        }
+        self.extract_part(DateTimeParts::Hour).map(|part| part.try_into().unwrap())
     }
 
     /// Extract the `minute`
+    ///
+    /// # Panics
+    ///
+    /// Panics if this [`Timestamp`] is infinite.  Use [`Timestamp::try_minute`] to handle that case.
     pub fn minute(&self) -> u8 {
-        self.extract_part(DateTimeParts::Minute).unwrap().try_into().unwrap()
+        self.try_minute().expect("infinite Timestamp has no minute")
+    }
+
+    /// Like [`Timestamp::minute`], but returns `None` if this [`Timestamp`] is infinite
+    pub fn try_minute(&self) -> Option<u8> {
+        if self.is_infinite() {
+            return None;
+        }
+        self.extract_part(DateTimeParts::Minute).map(|part| part.try_into().unwrap())
     }
 
     /// Extract the `second`
+    ///
+    /// # Panics
+    ///
+    /// Panics if this [`Timestamp`] is infinite.  Use [`Timestamp::try_second`] to handle that case.
     pub fn second(&self) -> f64 {
-        self.extract_part(DateTimeParts::Second).unwrap().try_into().unwrap()
+        self.try_second().expect("infinite Timestamp has no second")
+    }
+
+    /// Like [`Timestamp::second`], but returns `None` if this [`Timestamp`] is infinite
+    pub fn try_second(&self) -> Option<f64> {
+        if self.is_infinite() {
+            return None;
+        }
+        self.extract_part(DateTimeParts::Second).map(|part| part.try_into().unwrap())
     }
 
     /// Return the `microseconds` part.  This is not the time counted in microseconds, but the
     /// fractional seconds
+    ///
+    /// # Panics
+    ///
+    /// Panics if this [`Timestamp`] is infinite.  Use [`Timestamp::try_microseconds`] to handle that case.
     pub fn microseconds(&self) -> u32 {
-        self.extract_part(DateTimeParts::Microseconds).unwrap().try_into().unwrap()
+        self.try_microseconds().expect("infinite Timestamp has no microseconds")
+    }
+
+    /// Like [`Timestamp::microseconds`], but returns `None` if this [`Timestamp`] is infinite
+    pub fn try_microseconds(&self) -> Option<u32> {
+        if self.is_infinite() {
+            return None;
+        }
+        self.extract_part(DateTimeParts::Microseconds).map(|part| part.try_into().unwrap())
     }
 
     /// Return the `hour`, `minute`, `second`, and `microseconds` as a Rust tuple
+    ///
+    /// # Panics
+    ///
+    /// Panics if this [`Timestamp`] is infinite.
     pub fn to_hms_micro(&self) -> (u8, u8, u8, u32) {
         (self.hour(), self.minute(), self.second() as u8, self.microseconds())
     }
 
+    /// Is this [`Timestamp`] neither positive nor negative infinity?
+    #[inline]
     pub fn is_finite(&self) -> bool {
-        unsafe { direct_function_call(pg_sys::timestamp_finite, &[self.into_datum()]).unwrap() }
+        !self.is_infinite()
     }
 
     /// Truncate [`Timestamp`] to specified units
@@ -20,7 +20,7 @@ use std::convert::TryFrom;
 use std::panic::{RefUnwindSafe, UnwindSafe};
 
 // taken from /include/datatype/timestamp.h
-const MIN_TIMESTAMP_USEC: i64 = -211_813_488_000_000_000;
+pub(crate) const MIN_TIMESTAMP_USEC: i64 = -211_813_488_000_000_000;
 const END_TIMESTAMP_USEC: i64 = 9_223_371_331_200_000_000 - 1; // dec by 1 to accommodate exclusive range match pattern
 
 /// A safe wrapper around Postgres `TIMESTAMP WITH TIME ZONE` type, backed by a [`pg_sys::Timestamp`] integer value.
@@ -68,6 +68,11 @@ impl<Tz: AsRef<str> + UnwindSafe + RefUnwindSafe> TryFrom<(Timestamp, Tz)>
 
     fn try_from(value: (Timestamp, Tz)) -> Result<Self, Self::Error> {
         let (ts, tz) = value;
+        if ts.is_infinity() {
+            return Ok(TimestampWithTimeZone::positive_infinity());
+        } else if ts.is_neg_infinity() {
+            return Ok(TimestampWithTimeZone::negative_infinity());
+        }
         TimestampWithTimeZone::with_timezone(
             ts.year(),
             ts.month(),
@@ -279,43 +284,137 @@ impl TimestampWithTimeZone {
         self.0 == Self::NEG_INFINITY
     }
 
+    /// Does this [`TimestampWithTimeZone`] represent either positive or negative infinity?
+    #[inline]
+    pub fn is_infinite(&self) -> bool {
+        self.is_infinity() || self.is_neg_infinity()
+    }
+
     /// Extract the `month`
+    ///
+    /// # Panics
+    ///
+    /// Panics if this [`TimestampWithTimeZone`] is infinite.  Use [`TimestampWithTimeZone::try_month`] to handle that case.
     pub fn month(&self) -> u8 {
-        self.extract_part(DateTimeParts::Month).unwrap().try_into().unwrap()
+        self.try_month().expect("infinite TimestampWithTimeZone has no month")
+    }
+
+    /// Like [`TimestampWithTimeZone::month`], but returns `None` if this [`TimestampWithTimeZone`] is infinite
+    pub fn try_month(&self) -> Option<u8> {
+        if self.is_infinite() {
+            return None;
+        }
+        self.extract_part(DateTimeParts::Month).map(|part| part.try_into().unwrap())
     }
 
     /// Extract the `day`
+    ///
+    /// # Panics
+    ///
+    /// Panics if this [`TimestampWithTimeZone`] is infinite.  Use [`TimestampWithTimeZone::try_day`] to handle that case.
     pub fn day(&self) -> u8 {
-        self.extract_part(DateTimeParts::Day).unwrap().try_into().unwrap()
+        self.try_day().expect("infinite TimestampWithTimeZone has no day")
+    }
+
+    /// Like [`TimestampWithTimeZone::day`], but returns `None` if this [`TimestampWithTimeZone`] is infinite
+    pub fn try_day(&self) -> Option<u8> {
+        if self.is_infinite() {
+            return None;
+        }
+        self.extract_part(DateTimeParts::Day).map(|part| part.try_into().unwrap())
     }
 
     /// Extract the `year`
+    ///
+    /// # Panics
+    ///
+    /// Panics if this [`TimestampWithTimeZone`] is infinite.  Use [`TimestampWithTimeZone::try_year`] to handle that case.
     pub fn year(&self) -> i32 {
-        self.extract_part(DateTimeParts::Year).unwrap().try_into().unwrap()
+        self.try_year().expect("infinite TimestampWithTimeZone has no year")
+    }
+
+    /// Like [`TimestampWithTimeZone::year`], but returns `None` if this [`TimestampWithTimeZone`] is infinite
+    pub fn try_year(&self) -> Option<i32> {
+        if self.is_infinite() {
+            return None;
+        }
+        self.extract_part(DateTimeParts::Year).map(|part| part.try_into().unwrap())
     }
 
     /// Extract the `hour`
+    ///
+    /// # Panics
+    ///
+    /// Panics if this [`TimestampWithTimeZone`] is infinite.  Use [`TimestampWithTimeZone::try_hour`] to handle that case.
     pub fn hour(&self) -> u8 {
-        self.extract_part(DateTimeParts::Hour).unwrap().try_into().unwrap()
+        self.try_hour().expect("infinite TimestampWithTimeZone has no hour")
+    }
+
+    /// Like [`TimestampWithTimeZone::hour`], but returns `None` if this [`TimestampWithTimeZone`] is infinite
+    pub fn try_hour(&self) -> Option<u8> {
+        if self.is_infinite() {
+            return None;
+        }
+        self.extract_part(DateTimeParts::Hour).map(|part| part.try_into().unwrap())
     }
 
     /// Extract the `minute`
+    ///
+    /// # Panics
+    ///
+    /// Panics if this [`TimestampWithTimeZone`] is infinite.  Use [`TimestampWithTimeZone::try_minute`] to handle that case.
     pub fn minute(&self) -> u8 {
-        self.extract_part(DateTimeParts::Minute).unwrap().try_into().unwrap()
+        self.try_minute().expect("infinite TimestampWithTimeZone has no minute")
+    }
+
+    /// Like [`TimestampWithTimeZone::minute`], but returns `None` if this [`TimestampWithTimeZone`] is infinite
+    pub fn try_minute(&self) -> Option<u8> {
+        if self.is_infinite() {
+            return None;
+        }
+        self.extract_part(DateTimeParts::Minute).map(|part| part.try_into().unwrap())
     }
 
     /// Extract the `second`
+    ///
+    /// # Panics
+    ///
+    /// Panics if this [`TimestampWithTimeZone`] is infinite.  Use [`TimestampWithTimeZone::try_second`] to handle that case.
     pub fn second(&self) -> f64 {
-        self.extract_part(DateTimeParts::Second).unwrap().try_into().unwrap()
+        self.try_second().expect("infinite TimestampWithTimeZone has no second")
+    }
+
+    /// Like [`TimestampWithTimeZone::second`], but returns `None` if this [`TimestampWithTimeZone`] is infinite
+    pub fn try_second(&self) -> Option<f64> {
+        if self.is_infinite() {
+            return None;
+        }
+        self.extract_part(DateTimeParts::Second).map(|part| part.try_into().unwrap())
     }
 
     /// Return the `microseconds` part.  This is not the time counted in microseconds, but the
     /// fractional seconds
+    ///
+    /// # Panics
+    ///
+    /// Panics if this [`TimestampWithTimeZone`] is infinite.  Use [`TimestampWithTimeZone::try_microseconds`] to handle that case.
     pub fn microseconds(&self) -> u32 {
-        self.extract_part(DateTimeParts::Microseconds).unwrap().try_into().unwrap()
+        self.try_microseconds().expect("infinite TimestampWithTimeZone has no microseconds")
+    }
+
+    /// Like [`TimestampWithTimeZone::microseconds`], but returns `None` if this [`TimestampWithTimeZone`] is infinite
+    pub fn try_microseconds(&self) -> Option<u32> {
+        if self.is_infinite() {
+            return None;
+        }
+        self.extract_part(DateTimeParts::Microseconds).map(|part| part.try_into().unwrap())
     }
 
     /// Return the `hour`, `minute`, `second`, and `microseconds` as a Rust tuple
+    ///
+    /// # Panics
+    ///
+    /// Panics if this [`TimestampWithTimeZone`] is infinite.
     pub fn to_hms_micro(&self) -> (u8, u8, u8, u32) {
         (self.hour(), self.minute(), self.second() as u8, self.microseconds())
     }
@@ -355,9 +454,10 @@ impl TimestampWithTimeZone {
         .execute()
     }
 
+    /// Is this [`TimestampWithTimeZone`] neither positive nor negative infinity?
+    #[inline]
     pub fn is_finite(&self) -> bool {
-        // yes, this is the correct function, despite the seemingly mismatched type name
-        unsafe { direct_function_call(pg_sys::timestamp_finite, &[self.into_datum()]).unwrap() }
+        !self.is_infinite()
     }
 
     /// Truncate [`TimestampWithTimeZone`] to specified units
@@ -401,12 +501,14 @@ impl TimestampWithTimeZone {
 
 #[derive(thiserror::Error, Debug, Clone, Copy)]
 pub enum FromTimeError {
-    #[error("timestamp value is negative infinity and shouldn't map to time::PrimitiveDateTime")]
+    #[error("value is negative infinity, which has no finite equivalent")]
     NegInfinity,
-    #[error("timestamp value is negative infinity and shouldn't map to time::PrimitiveDateTime")]
+    #[error("value is positive infinity, which has no finite equivalent")]
     Infinity,
     #[error("time::PrimitiveDateTime was unable to convert this timestamp")]
     TimeCrate,
+    #[error("chrono was unable to represent this date or timestamp")]
+    ChronoCrate,
     #[error("microseconds outside of target microsecond range")]
     MicrosOutOfBounds,
     #[error("hours outside of target range")]
@@ -0,0 +1,178 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Oids which know which system catalog they refer to
+//!
+//! A bare [`pg_sys::Oid`] could name a relation, a type, a function, or anything else with a row
+//! in a catalog, and passing one kind where another is expected compiles fine and then fails, or
+//! worse, at runtime.  These newtypes keep track of the kind, so that e.g. [`PgRelation::open`]
+//! can't be handed a [`TypeOid`].
+//!
+//! Each converts into a raw `pg_sys::Oid` with `From`, but going the other way is explicit, through
+//! `from_raw_unchecked`, since nothing checks that the oid really names a row of that catalog.
+//! Each also maps to the matching `reg*` SQL type, so a `#[pg_extern]` function can accept
+//! `'my_table'::regclass` as a [`RelationOid`].
+//!
+//! [`PgRelation::open`]: crate::PgRelation::open
+use crate::{pg_sys, FromDatum, IntoDatum};
+use pgrx_sql_entity_graph::metadata::{
+    ArgumentError, Returns, ReturnsError, SqlMapping, SqlTranslatable,
+};
+use std::fmt;
+
+macro_rules! typed_oid {
+    ($(#[$meta:meta])* $name:ident, $catalog:literal, $sql:literal, $typoid:ident) => {
+        $(#[$meta])*
+        #[doc = concat!("\n\nIt maps to the `", $sql, "` SQL type.")]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+        #[derive(serde::Deserialize, serde::Serialize)]
+        #[repr(transparent)]
+        pub struct $name(pg_sys::Oid);
+
+        impl $name {
+            pub const INVALID: $name = $name(pg_sys::Oid::INVALID);
+
+            #[doc = concat!("Treat `oid` as the oid of a row in `", $catalog, "`")]
+            ///
+            /// Nothing checks that it is one.
+            #[inline]
+            pub const fn from_raw_unchecked(oid: pg_sys::Oid) -> Self {
+                $name(oid)
+            }
+
+            /// The raw oid
+            #[inline]
+            pub const fn as_oid(&self) -> pg_sys::Oid {
+                self.0
+            }
+
+            #[inline]
+            pub fn is_valid(&self) -> bool {
+                self.0 != pg_sys::Oid::INVALID
+            }
+        }
+
+        impl From<$name> for pg_sys::Oid {
+            #[inline]
+            fn from(oid: $name) -> Self {
+                oid.0
+            }
+        }
+
+        impl PartialEq<pg_sys::Oid> for $name {
+            #[inline]
+            fn eq(&self, other: &pg_sys::Oid) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl FromDatum for $name {
+            #[inline]
+            unsafe fn from_polymorphic_datum(
+                datum: pg_sys::Datum,
+                is_null: bool,
+                typoid: pg_sys::Oid,
+            ) -> Option<$name> {
+                pg_sys::Oid::from_polymorphic_datum(datum, is_null, typoid).map($name)
+            }
+        }
+
+        impl IntoDatum for $name {
+            #[inline]
+            fn into_datum(self) -> Option<pg_sys::Datum> {
+                self.0.into_datum()
+            }
+
+            #[inline]
+            fn type_oid() -> pg_sys::Oid {
+                pg_sys::$typoid
+            }
+
+            fn is_compatible_with(other: pg_sys::Oid) -> bool {
+                // the reg* types are all binary-coercible with oid
+                other == pg_sys::$typoid || other == pg_sys::OIDOID
+            }
+        }
+
+        unsafe impl SqlTranslatable for $name {
+            fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+                Ok(SqlMapping::literal($sql))
+            }
+            fn return_sql() -> Result<Returns, ReturnsError> {
+                Ok(Returns::One(SqlMapping::literal($sql)))
+            }
+        }
+    };
+}
+
+typed_oid!(
+    /// The oid of a relation (a table, index, view, sequence, etc), from `pg_class`
+    RelationOid,
+    "pg_class",
+    "regclass",
+    REGCLASSOID
+);
+
+typed_oid!(
+    /// The oid of a type, from `pg_type`
+    TypeOid,
+    "pg_type",
+    "regtype",
+    REGTYPEOID
+);
+
+typed_oid!(
+    /// The oid of a function or procedure, from `pg_proc`
+    ProcOid,
+    "pg_proc",
+    "regprocedure",
+    REGPROCEDUREOID
+);
+
+typed_oid!(
+    /// The oid of a schema, from `pg_namespace`
+    NamespaceOid,
+    "pg_namespace",
+    "regnamespace",
+    REGNAMESPACEOID
+);
+
+mod sealed {
+    pub trait Sealed {}
+    impl Sealed for crate::pg_sys::Oid {}
+    impl Sealed for super::RelationOid {}
+}
+
+/// Something [`PgRelation::open`] and friends accept as the relation to open: either a
+/// [`RelationOid`] or, for compatibility, a raw `pg_sys::Oid` that the caller vouches for.
+///
+/// [`PgRelation::open`]: crate::PgRelation::open
+pub trait IntoRelationOid: sealed::Sealed {
+    fn into_relation_oid(self) -> RelationOid;
+}
+
+impl IntoRelationOid for pg_sys::Oid {
+    #[inline]
+    fn into_relation_oid(self) -> RelationOid {
+        RelationOid::from_raw_unchecked(self)
+    }
+}
+
+impl IntoRelationOid for RelationOid {
+    #[inline]
+    fn into_relation_oid(self) -> RelationOid {
+        self
+    }
+}
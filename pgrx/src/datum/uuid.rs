@@ -14,7 +14,7 @@ use pgrx_sql_entity_graph::metadata::{
 };
 use std::ops::{Deref, DerefMut};
 
-const UUID_BYTES_LEN: usize = 16;
+pub(crate) const UUID_BYTES_LEN: usize = 16;
 pub type UuidBytes = [u8; UUID_BYTES_LEN];
 
 /// A Universally Unique Identifier (`UUID`) from PostgreSQL
@@ -0,0 +1,77 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Lets the [`uuid`] crate's [`Uuid`](uuid::Uuid) be used directly as a `#[pg_extern]` argument
+//! and return type, enabled with pgrx's `uuid-crate` feature.
+//!
+//! Postgres' `uuid` is the 16 bytes of the UUID, in the same order as [`uuid::Uuid::as_bytes()`],
+//! so the conversion is a plain copy of those bytes.
+use super::uuid::UUID_BYTES_LEN;
+use crate::{pg_sys, FromDatum, IntoDatum, PgMemoryContexts, Uuid};
+use pgrx_sql_entity_graph::metadata::{
+    ArgumentError, Returns, ReturnsError, SqlMapping, SqlTranslatable,
+};
+
+impl From<uuid::Uuid> for Uuid {
+    #[inline]
+    fn from(uuid: uuid::Uuid) -> Self {
+        Uuid::from_bytes(uuid.into_bytes())
+    }
+}
+
+impl From<Uuid> for uuid::Uuid {
+    #[inline]
+    fn from(uuid: Uuid) -> Self {
+        uuid::Uuid::from_bytes(*uuid.as_bytes())
+    }
+}
+
+impl IntoDatum for uuid::Uuid {
+    #[inline]
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        let ptr = unsafe {
+            // SAFETY:  CurrentMemoryContext is always valid
+            PgMemoryContexts::CurrentMemoryContext.palloc_slice::<u8>(UUID_BYTES_LEN)
+        };
+        ptr.copy_from_slice(self.as_bytes());
+
+        Some(ptr.as_ptr().into())
+    }
+
+    #[inline]
+    fn type_oid() -> pg_sys::Oid {
+        pg_sys::UUIDOID
+    }
+}
+
+impl FromDatum for uuid::Uuid {
+    #[inline]
+    unsafe fn from_polymorphic_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        _typoid: pg_sys::Oid,
+    ) -> Option<uuid::Uuid> {
+        if is_null {
+            None
+        } else {
+            // SAFETY:  a `uuid` datum points to exactly 16 bytes, and [u8; 16] has no alignment
+            let bytes = datum.cast_mut_ptr::<[u8; UUID_BYTES_LEN]>().read();
+            Some(uuid::Uuid::from_bytes(bytes))
+        }
+    }
+}
+
+unsafe impl SqlTranslatable for uuid::Uuid {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        Ok(SqlMapping::literal("uuid"))
+    }
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        Ok(Returns::One(SqlMapping::literal("uuid")))
+    }
+}
@@ -0,0 +1,208 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+use crate::{pg_sys, varlena, FromDatum, IntoDatum};
+use pgrx_sql_entity_graph::metadata::{
+    ArgumentError, Returns, ReturnsError, SqlMapping, SqlTranslatable,
+};
+use std::fmt;
+use std::str::FromStr;
+
+/// The size of a `VarBit`'s header: its varlena header, then its length in bits
+const VARBITHDRSZ: usize = pg_sys::VARHDRSZ + std::mem::size_of::<i32>();
+
+/// A `bit varying` type from PostgreSQL
+///
+/// The bits are numbered from the left, as Postgres prints them, so bit `0` of `B'100'` is set.
+/// Only `varbit` Datums convert to and from a `VarBit`: a `bit(n)` has to match its length, so
+/// cast it explicitly, which pads with zeros or truncates as [`VarBit::resize`] does.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct VarBit {
+    len: usize,
+    /// The bits, most significant first, with any unused bits of the last byte zeroed
+    bytes: Vec<u8>,
+}
+
+impl VarBit {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `len` zero bits
+    pub fn zeros(len: usize) -> Self {
+        VarBit { len, bytes: vec![0; (len + 7) / 8] }
+    }
+
+    /// The first `len` bits of `bytes`, most significant bit first, as Postgres stores them
+    ///
+    /// # Panics
+    ///
+    /// If `bytes` has fewer than `len` bits.
+    pub fn from_bytes(bytes: &[u8], len: usize) -> Self {
+        let nbytes = (len + 7) / 8;
+        assert!(bytes.len() >= nbytes, "{len} bits don't fit in {} bytes", bytes.len());
+        let mut varbit = VarBit { len, bytes: bytes[..nbytes].to_vec() };
+        varbit.clear_padding();
+        varbit
+    }
+
+    /// The number of bits
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The bits, most significant first, with the last byte padded with zeros
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// The bit at `index`, counting from the left
+    pub fn get(&self, index: usize) -> Option<bool> {
+        (index < self.len).then(|| self.bytes[index / 8] & (0x80 >> (index % 8)) != 0)
+    }
+
+    /// Set the bit at `index`, counting from the left
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of bounds.
+    pub fn set(&mut self, index: usize, bit: bool) {
+        assert!(index < self.len, "bit index {index} is out of bounds for length {}", self.len);
+        let mask = 0x80 >> (index % 8);
+        if bit {
+            self.bytes[index / 8] |= mask;
+        } else {
+            self.bytes[index / 8] &= !mask;
+        }
+    }
+
+    /// Append a bit on the right
+    pub fn push(&mut self, bit: bool) {
+        if self.len % 8 == 0 {
+            self.bytes.push(0);
+        }
+        self.len += 1;
+        self.set(self.len - 1, bit);
+    }
+
+    /// Truncate, or pad on the right with zeros, to `len` bits, as a cast to `bit(len)` does
+    pub fn resize(&mut self, len: usize) {
+        self.bytes.resize((len + 7) / 8, 0);
+        self.len = len;
+        self.clear_padding();
+    }
+
+    /// The number of set bits
+    pub fn count_ones(&self) -> usize {
+        self.bytes.iter().map(|byte| byte.count_ones() as usize).sum()
+    }
+
+    /// The bits, from the left
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(|index| self.get(index).unwrap())
+    }
+
+    fn clear_padding(&mut self) {
+        if self.len % 8 != 0 {
+            let last = self.bytes.len() - 1;
+            self.bytes[last] &= 0xff << (8 - self.len % 8);
+        }
+    }
+}
+
+impl FromIterator<bool> for VarBit {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut varbit = VarBit::new();
+        for bit in iter {
+            varbit.push(bit);
+        }
+        varbit
+    }
+}
+
+impl fmt::Display for VarBit {
+    /// The bits as `0`s and `1`s, as Postgres prints them
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for bit in self.iter() {
+            f.write_str(if bit { "1" } else { "0" })?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("\"{0}\" is not a valid binary digit")]
+pub struct VarBitParseError(char);
+
+impl FromStr for VarBit {
+    type Err = VarBitParseError;
+
+    /// Parse `0`s and `1`s, as in `B'0101'`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.chars()
+            .map(|c| match c {
+                '0' => Ok(false),
+                '1' => Ok(true),
+                other => Err(VarBitParseError(other)),
+            })
+            .collect()
+    }
+}
+
+/// for `bit` and `bit varying`, which share a representation
+impl FromDatum for VarBit {
+    unsafe fn from_polymorphic_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        _: pg_sys::Oid,
+    ) -> Option<Self> {
+        if is_null {
+            return None;
+        }
+
+        let varbit = pg_sys::pg_detoast_datum(datum.cast_mut_ptr());
+        let ptr = varbit as *const u8;
+        let len = ptr.add(pg_sys::VARHDRSZ).cast::<i32>().read_unaligned() as usize;
+        let bytes = std::slice::from_raw_parts(ptr.add(VARBITHDRSZ), (len + 7) / 8).to_vec();
+        if varbit != datum.cast_mut_ptr() {
+            pg_sys::pfree(varbit.cast());
+        }
+        Some(VarBit { len, bytes })
+    }
+}
+
+impl IntoDatum for VarBit {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        unsafe {
+            let size = VARBITHDRSZ + self.bytes.len();
+            let ptr = pg_sys::palloc(size) as *mut u8;
+            varlena::set_varsize(ptr.cast(), size as i32);
+            ptr.add(pg_sys::VARHDRSZ).cast::<i32>().write_unaligned(self.len as i32);
+            ptr.add(VARBITHDRSZ).copy_from_nonoverlapping(self.bytes.as_ptr(), self.bytes.len());
+            Some(ptr.into())
+        }
+    }
+
+    fn type_oid() -> pg_sys::Oid {
+        pg_sys::VARBITOID
+    }
+}
+
+unsafe impl SqlTranslatable for VarBit {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        Ok(SqlMapping::literal("varbit"))
+    }
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        Ok(Returns::One(SqlMapping::literal("varbit")))
+    }
+}
@@ -384,7 +384,17 @@ where
     T: PostgresType + Serialize,
 {
     fn into_datum(self) -> Option<pg_sys::Datum> {
-        Some(cbor_encode(&self).into())
+        let varlena = cbor_encode(&self);
+        if let Some(compression) = T::COMPRESSION {
+            // SAFETY: cbor_encode() made a valid, uncompressed varlena
+            if let Some(compressed) =
+                unsafe { crate::varlena::compress_varlena(varlena, compression) }
+            {
+                unsafe { pg_sys::pfree(varlena as *mut _) };
+                return Some(compressed.into_pg().into());
+            }
+        }
+        Some(varlena.into())
     }
 
     fn type_oid() -> pg_sys::Oid {
@@ -451,6 +461,21 @@ where
     serde_cbor::from_slice(slice).expect("failed to decode CBOR")
 }
 
+/// Decode a CBOR-encoded varlena Datum, such as a `#[derive(PostgresType)]` value, detoasting it
+/// `chunk_size` bytes at a time with a [`DetoastReader`](crate::varlena::DetoastReader) instead
+/// of all at once.
+///
+/// # Safety
+///
+/// This function is unsafe because it blindly assumes the provided Datum is a non-null varlena.
+pub unsafe fn cbor_decode_streaming<T>(datum: pg_sys::Datum, chunk_size: usize) -> T
+where
+    T: serde::de::DeserializeOwned,
+{
+    let reader = crate::varlena::DetoastReader::new(datum, chunk_size);
+    serde_cbor::from_reader(reader).expect("failed to decode CBOR")
+}
+
 pub unsafe fn cbor_decode_into_context<'de, T>(
     mut memory_context: PgMemoryContexts,
     input: *mut pg_sys::varlena,
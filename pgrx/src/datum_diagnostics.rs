@@ -0,0 +1,276 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Warnings for Datums which can't be converted into the Rust type asked for
+//!
+//! When a function's SQL signature and its Rust signature disagree, or a query's column isn't
+//! the type the code reading it expects, converting the Datum fails with little more than a
+//! `None`, a panic, or a [`TryFromDatumError`][crate::datum::TryFromDatumError] somewhere far from
+//! the mistake.  With pgrx's `datum-diagnostics` feature enabled, every such failure also raises
+//! a `WARNING` naming the Rust type, the Postgres type and typmod of the Datum, and the call site:
+//!
+//! * a `#[pg_extern]` argument whose SQL type isn't compatible with its Rust type
+//! * [`FromDatum::try_from_datum`][crate::FromDatum::try_from_datum], and so the `get()`s of
+//!   [`spi`][crate::spi] results, given a Datum of an incompatible type
+//! * a `FromDatum` conversion which returns `None` for a Datum that isn't NULL
+//!
+//! The conversions still fail as they always do.  This is all compiled out unless the feature is
+//! enabled, which is meant for debugging.  Enabling it in an extension's `pg_test` feature also
+//! turns every test which expects a conversion to fail into a `WARNING` in the server log, so it's
+//! best enabled only while chasing down a mismatch:
+//!
+//! ```toml
+//! [features]
+//! datum-diagnostics = ["pgrx/datum-diagnostics"]
+//! ```
+//!
+//! `FromDatum::try_from_datum` isn't told the typmod of the Datum it converts, so it's reported as
+//! unknown unless the caller knows it and says so with a [`TypmodScope`], as the SPI getters do
+//! with the typmods in their tuple descriptors.
+use crate::{pg_sys, IntoDatum};
+use core::marker::PhantomData;
+
+/// Report that a Datum of type `datum_oid` can't be converted into a `T`.
+///
+/// This does nothing unless the `datum-diagnostics` feature is enabled.
+#[inline]
+#[track_caller]
+pub fn incompatible_types<T: ?Sized>(rust_oid: pg_sys::Oid, datum_oid: pg_sys::Oid, typmod: i32) {
+    #[cfg(feature = "datum-diagnostics")]
+    enabled::incompatible_types::<T>(rust_oid, datum_oid, typmod);
+
+    #[cfg(not(feature = "datum-diagnostics"))]
+    let _ = (rust_oid, datum_oid, typmod);
+}
+
+/// Report that converting a Datum of type `datum_oid`, which wasn't NULL, into a `T` gave `None`.
+///
+/// This does nothing unless the `datum-diagnostics` feature is enabled.
+#[inline]
+#[track_caller]
+pub fn no_value<T: ?Sized>(datum_oid: pg_sys::Oid, typmod: i32) {
+    #[cfg(feature = "datum-diagnostics")]
+    enabled::no_value::<T>(datum_oid, typmod);
+
+    #[cfg(not(feature = "datum-diagnostics"))]
+    let _ = (datum_oid, typmod);
+}
+
+/// Report that converting argument `num` of a function call, which wasn't NULL, into a `T` gave
+/// `None`.
+///
+/// This does nothing unless the `datum-diagnostics` feature is enabled.
+///
+/// # Safety
+///
+/// `fcinfo` must be a valid `FunctionCallInfo`, with at least `num + 1` arguments
+#[inline]
+#[track_caller]
+pub unsafe fn no_argument_value<T: ?Sized>(fcinfo: pg_sys::FunctionCallInfo, num: usize) {
+    #[cfg(feature = "datum-diagnostics")]
+    enabled::no_argument_value::<T>(fcinfo, num);
+
+    #[cfg(not(feature = "datum-diagnostics"))]
+    let _ = (fcinfo, num);
+}
+
+/// While this is alive, conversions which don't know the typmod of their Datum report it as
+/// `typmod`.
+///
+/// This does nothing unless the `datum-diagnostics` feature is enabled.
+#[must_use = "the typmod is only used while the scope is alive"]
+pub struct TypmodScope {
+    #[cfg(feature = "datum-diagnostics")]
+    previous: i32,
+}
+
+impl TypmodScope {
+    /// Report `typmod` for conversions which don't know their Datum's, until the returned scope
+    /// is dropped
+    #[cfg(feature = "datum-diagnostics")]
+    #[inline]
+    pub fn enter(typmod: i32) -> Self {
+        TypmodScope { previous: enabled::TYPMOD.with(|current| current.replace(typmod)) }
+    }
+
+    /// Report `typmod` for conversions which don't know their Datum's, until the returned scope
+    /// is dropped
+    #[cfg(not(feature = "datum-diagnostics"))]
+    #[inline]
+    pub fn enter(_typmod: i32) -> Self {
+        TypmodScope {}
+    }
+}
+
+impl Drop for TypmodScope {
+    #[inline]
+    fn drop(&mut self) {
+        #[cfg(feature = "datum-diagnostics")]
+        enabled::TYPMOD.with(|current| current.set(self.previous));
+    }
+}
+
+/// Checks that the argument a `#[pg_extern]` function was called with can be converted into
+/// `T`, for the code `#[pg_extern]` generates.
+///
+/// Only types which implement [`IntoDatum`] know their type OID, so only they're checked.
+/// [`ArgumentCheckFallback`] does nothing for every other type.
+///
+/// This uses the fact that inherent implementations are a higher priority than trait
+/// implementations.
+#[doc(hidden)]
+pub struct ArgumentCheck<T: ?Sized>(PhantomData<T>);
+
+impl<T: IntoDatum> ArgumentCheck<T> {
+    /// # Safety
+    ///
+    /// `fcinfo` must be a valid `FunctionCallInfo`, with at least `num + 1` arguments
+    #[inline]
+    #[track_caller]
+    pub unsafe fn check(fcinfo: pg_sys::FunctionCallInfo, num: usize) {
+        #[cfg(feature = "datum-diagnostics")]
+        enabled::check_argument::<T>(fcinfo, num);
+
+        #[cfg(not(feature = "datum-diagnostics"))]
+        let _ = (fcinfo, num);
+    }
+}
+
+#[doc(hidden)]
+pub trait ArgumentCheckFallback {
+    /// # Safety
+    ///
+    /// None, as this does nothing
+    #[inline]
+    unsafe fn check(_fcinfo: pg_sys::FunctionCallInfo, _num: usize) {}
+}
+
+impl<T: ?Sized> ArgumentCheckFallback for ArgumentCheck<T> {}
+
+#[cfg(feature = "datum-diagnostics")]
+mod enabled {
+    use crate::datum::is_binary_coercible;
+    use crate::{ereport, is_a, pg_sys, IntoDatum, PgList, PgLogLevel, PgSqlErrorCode};
+    use core::cell::Cell;
+    use core::ffi::CStr;
+    use core::panic::Location;
+
+    thread_local! {
+        /// The typmod set by the innermost [`TypmodScope`](super::TypmodScope), if any
+        pub(super) static TYPMOD: Cell<i32> = Cell::new(-1);
+    }
+
+    /// `typmod`, or if that's unknown, the one given by the innermost `TypmodScope`
+    fn known_typmod(typmod: i32) -> i32 {
+        if typmod == -1 {
+            TYPMOD.with(Cell::get)
+        } else {
+            typmod
+        }
+    }
+
+    #[track_caller]
+    pub(super) fn incompatible_types<T: ?Sized>(
+        rust_oid: pg_sys::Oid,
+        datum_oid: pg_sys::Oid,
+        typmod: i32,
+    ) {
+        warn(format!(
+            "a Datum of type {} can't be converted into `{}`, whose type is {}",
+            describe(datum_oid, known_typmod(typmod)),
+            core::any::type_name::<T>(),
+            describe(rust_oid, -1),
+        ));
+    }
+
+    #[track_caller]
+    pub(super) fn no_value<T: ?Sized>(datum_oid: pg_sys::Oid, typmod: i32) {
+        warn(format!(
+            "a Datum of type {}, which is not NULL, converted into `{}` as `None`",
+            describe(datum_oid, known_typmod(typmod)),
+            core::any::type_name::<T>(),
+        ));
+    }
+
+    #[track_caller]
+    pub(super) unsafe fn no_argument_value<T: ?Sized>(
+        fcinfo: pg_sys::FunctionCallInfo,
+        num: usize,
+    ) {
+        let flinfo = fcinfo.as_ref().map_or(core::ptr::null_mut(), |fcinfo| fcinfo.flinfo);
+        if flinfo.is_null() {
+            no_value::<T>(pg_sys::InvalidOid, -1);
+        } else {
+            let datum_oid = pg_sys::get_fn_expr_argtype(flinfo, num as _);
+            no_value::<T>(datum_oid, argument_typmod(flinfo, num));
+        }
+    }
+
+    #[track_caller]
+    pub(super) unsafe fn check_argument<T: IntoDatum>(
+        fcinfo: pg_sys::FunctionCallInfo,
+        num: usize,
+    ) {
+        let flinfo = fcinfo.as_ref().map_or(core::ptr::null_mut(), |fcinfo| fcinfo.flinfo);
+        if flinfo.is_null() {
+            return;
+        }
+        let datum_oid = pg_sys::get_fn_expr_argtype(flinfo, num as _);
+        // without a call expression to look at, as for a `DirectFunctionCall`, there's no knowing
+        if datum_oid != pg_sys::InvalidOid && !is_binary_coercible::<T>(datum_oid) {
+            let typmod = argument_typmod(flinfo, num);
+            warn(format!(
+                "argument {} is of type {}, which can't be converted into `{}`, whose type is {}",
+                num + 1,
+                describe(datum_oid, typmod),
+                core::any::type_name::<T>(),
+                describe(T::type_oid(), -1),
+            ));
+        }
+    }
+
+    /// The typmod of the `num`th argument of the call being made through `flinfo`, if known
+    unsafe fn argument_typmod(flinfo: *mut pg_sys::FmgrInfo, num: usize) -> i32 {
+        let expr = (*flinfo).fn_expr;
+        let args = if is_a(expr, pg_sys::NodeTag_T_FuncExpr) {
+            (*expr.cast::<pg_sys::FuncExpr>()).args
+        } else if is_a(expr, pg_sys::NodeTag_T_OpExpr) {
+            (*expr.cast::<pg_sys::OpExpr>()).args
+        } else {
+            return -1;
+        };
+        PgList::<pg_sys::Node>::from_pg(args).get_ptr(num).map_or(-1, |arg| pg_sys::exprTypmod(arg))
+    }
+
+    /// The type's name, with its typmod if it has one, and its OID
+    fn describe(oid: pg_sys::Oid, typmod: i32) -> String {
+        if oid == pg_sys::InvalidOid {
+            return "unknown".to_string();
+        }
+        let name = unsafe {
+            let flags = pg_sys::FORMAT_TYPE_TYPEMOD_GIVEN | pg_sys::FORMAT_TYPE_ALLOW_INVALID;
+            let name = pg_sys::format_type_extended(oid, typmod, flags as _);
+            let owned = CStr::from_ptr(name).to_string_lossy().into_owned();
+            pg_sys::pfree(name.cast());
+            owned
+        };
+        format!("`{name}` (oid {}, typmod {typmod})", oid.as_u32())
+    }
+
+    #[track_caller]
+    fn warn(message: String) {
+        let caller = Location::caller();
+        ereport!(
+            PgLogLevel::WARNING,
+            PgSqlErrorCode::ERRCODE_DATATYPE_MISMATCH,
+            message,
+            format!("converted at {}:{}:{}", caller.file(), caller.line(), caller.column())
+        );
+    }
+}
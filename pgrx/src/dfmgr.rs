@@ -0,0 +1,184 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Cooperating with other loaded libraries, through rendezvous variables and symbol lookup
+//!
+//! Postgres' dynamic loader provides two ways for extensions to integrate with each other without
+//! a link-time dependency.  A [`RendezvousVariable`] is a named, backend-local pointer that any
+//! library can find, whichever of them is loaded first.  An [`ExternalLibrary`] loads another
+//! library, such as `pg_stat_statements` or PostGIS, and looks up symbols in it, so that an
+//! extension can call into the other when it's installed and carry on without it when it's not.
+use crate::pg_sys;
+use crate::prelude::*;
+use std::ffi::{c_void, CString};
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+/// A named pointer shared by every library loaded into a backend, as created by Postgres'
+/// `find_rendezvous_variable()`.
+///
+/// The variable starts out `NULL`, and lives until the backend exits.  The libraries sharing it
+/// must agree on the name and on the type `T` it points to, which is usually a struct of function
+/// pointers, so that one library can publish an API that another can find.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgrx::dfmgr::RendezvousVariable;
+///
+/// #[repr(C)]
+/// struct MyApi {
+///     version: u32,
+///     add: extern "C" fn(i32, i32) -> i32,
+/// }
+///
+/// let api = RendezvousVariable::<MyApi>::find("my_extension_api");
+/// if let Some(api) = api.get() {
+///     let api = unsafe { api.as_ref() };
+///     assert_eq!((api.add)(1, 2), 3);
+/// }
+/// ```
+pub struct RendezvousVariable<T> {
+    slot: NonNull<*mut c_void>,
+    _marker: PhantomData<*mut T>,
+}
+
+impl<T> Clone for RendezvousVariable<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for RendezvousVariable<T> {}
+
+impl<T> RendezvousVariable<T> {
+    /// Find the rendezvous variable named `name`, creating it if no library has yet
+    ///
+    /// # Panics
+    ///
+    /// If `name` contains a NUL byte
+    pub fn find(name: &str) -> Self {
+        let name = CString::new(name).expect("rendezvous variable names may not contain a NUL");
+        unsafe {
+            // SAFETY:  `name` is a valid C string, which Postgres copies into the variable's
+            // hash entry, and the returned slot lives in TopMemoryContext for the life of the
+            // backend
+            let slot = pg_sys::find_rendezvous_variable(name.as_ptr());
+            RendezvousVariable {
+                slot: NonNull::new(slot).expect("find_rendezvous_variable() returned NULL"),
+                _marker: PhantomData,
+            }
+        }
+    }
+
+    /// The variable's current value, or `None` if it's still `NULL`
+    pub fn get(&self) -> Option<NonNull<T>> {
+        // SAFETY:  `slot` is valid for the life of the backend
+        NonNull::new(unsafe { *self.slot.as_ptr() }.cast())
+    }
+
+    /// Set the variable's value, or reset it to `NULL` with `None`
+    ///
+    /// # Safety
+    ///
+    /// Every library that reads the variable will dereference `value` as a `T`, so it must point
+    /// to a valid `T` that lives as long as the variable is set to it, typically by being
+    /// `static` or allocated in `TopMemoryContext`.
+    pub unsafe fn set(&self, value: Option<NonNull<T>>) {
+        *self.slot.as_ptr() = value.map_or(std::ptr::null_mut(), |value| value.as_ptr().cast());
+    }
+}
+
+/// A handle to a shared library loaded with Postgres' dynamic loader.
+///
+/// Libraries are only ever loaded once per backend, so loading a library that's already loaded,
+/// such as one named in `shared_preload_libraries`, just finds it.  Loading a library for the
+/// first time runs its `_PG_init()`.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgrx::dfmgr::ExternalLibrary;
+///
+/// if let Some(postgis) = ExternalLibrary::load("$libdir/postgis-3") {
+///     let version = postgis.lookup_function("postgis_lib_version");
+///     assert!(version.is_some());
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExternalLibrary {
+    name: String,
+    handle: NonNull<c_void>,
+}
+
+impl ExternalLibrary {
+    /// Load the library named `library`, which is resolved like the library of a `CREATE
+    /// FUNCTION ... LANGUAGE c` statement, so may start with `$libdir/` and need not have a file
+    /// extension.
+    ///
+    /// Returns `None` if no such library exists.  Any other problem loading it, such as it being
+    /// built for a different Postgres version, raises an `ERROR`.
+    pub fn load(library: &str) -> Option<ExternalLibrary> {
+        let filename = CString::new(library).expect("library names may not contain a NUL");
+        let handle = PgTryBuilder::new(|| unsafe {
+            // SAFETY:  both names are valid C strings.  Every library Postgres will load has a
+            // `Pg_magic_func`, and asking for it doesn't raise an error if it's missing anyway
+            let mut handle = std::ptr::null_mut();
+            pg_sys::load_external_function(
+                filename.as_ptr(),
+                b"Pg_magic_func\0".as_ptr().cast(),
+                false,
+                &mut handle,
+            );
+            NonNull::new(handle)
+        })
+        .catch_when(PgSqlErrorCode::ERRCODE_UNDEFINED_FILE, |_| None)
+        .execute()?;
+        Some(ExternalLibrary { name: library.to_string(), handle })
+    }
+
+    /// The name this library was loaded by
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Look up the symbol named `symbol`, returning `None` if the library doesn't export it.
+    ///
+    /// It's up to the caller to know what the symbol really is and cast it accordingly.
+    pub fn lookup(&self, symbol: &str) -> Option<NonNull<c_void>> {
+        let symbol = CString::new(symbol).expect("symbol names may not contain a NUL");
+        // SAFETY:  `handle` came from load_external_function() and libraries are never unloaded
+        let found =
+            unsafe { pg_sys::lookup_external_function(self.handle.as_ptr(), symbol.as_ptr()) };
+
+        #[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13"))]
+        let found = found.map_or(std::ptr::null_mut(), |f| f as *mut c_void);
+
+        NonNull::new(found)
+    }
+
+    /// Look up a function that uses the Postgres calling convention (`PG_FUNCTION_INFO_V1`),
+    /// returning `None` if the library doesn't export it.
+    ///
+    /// The function can be called with a `FunctionCallInfo` prepared by the caller, or with
+    /// `pg_sys::DirectFunctionCall1Coll()` and friends.
+    pub fn lookup_function(
+        &self,
+        function: &str,
+    ) -> Option<unsafe extern "C" fn(pg_sys::FunctionCallInfo) -> pg_sys::Datum> {
+        self.lookup(function).map(|f| unsafe {
+            // SAFETY:  a library's PG_FUNCTION_INFO_V1 functions have exactly this signature,
+            // which the caller has asserted by asking for one
+            std::mem::transmute::<
+                *mut c_void,
+                unsafe extern "C" fn(pg_sys::FunctionCallInfo) -> pg_sys::Datum,
+            >(f.as_ptr())
+        })
+    }
+}
@@ -9,8 +9,16 @@
 //LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
 //! Helper functions for working with Postgres `enum` types
 
+use crate as pgrx; // for #[pg_guard] support from within ourself
 use crate::pg_sys::GETSTRUCT;
-use crate::{ereport, pg_sys, PgLogLevel, PgSqlErrorCode};
+use crate::systable::{ScanKeys, SysTableScan};
+use crate::{
+    direct_function_call, ereport, pg_guard, pg_sys, IntoDatum, PgLogLevel, PgRelation,
+    PgSqlErrorCode,
+};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
 
 pub fn lookup_enum_by_oid(enumval: pg_sys::Oid) -> (String, pg_sys::Oid, f32) {
     let tup = unsafe {
@@ -84,6 +92,140 @@ pub fn lookup_enum_by_label(typname: &str, label: &str) -> pg_sys::Datum {
     }
 }
 
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum PgEnumTypeError {
+    #[error("type `{0}` does not exist")]
+    NotFound(String),
+
+    #[error("type `{0}` is not an enum")]
+    NotAnEnum(String),
+}
+
+/// An enum type which already exists in the database, such as one created by an application's
+/// schema rather than by `#[derive(PostgresEnum)]`, along with the Oids of its labels.
+///
+/// Lookups are cached by type name for the rest of the backend's life, and the cache is thrown
+/// away whenever `pg_enum` changes, such as through `ALTER TYPE ... ADD VALUE` or `DROP TYPE`.
+/// Type names are resolved with the `search_path` of the first lookup, so it's best to
+/// schema-qualify them.
+///
+/// ```rust,no_run
+/// use pgrx::prelude::*;
+/// use pgrx::PgEnumType;
+///
+/// let mood = PgEnumType::lookup("app.mood").unwrap();
+/// let happy = mood.label_oid("happy").expect("`app.mood` has no `happy` label");
+/// assert_eq!(mood.label_of(happy), Some("happy"));
+/// ```
+///
+/// `#[derive(PostgresExistingEnum)]` uses this to map a Rust enum onto such a type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgEnumType {
+    oid: pg_sys::Oid,
+    /// in sort order
+    labels: Vec<(String, pg_sys::Oid)>,
+}
+
+thread_local! {
+    static ENUM_TYPES: RefCell<HashMap<String, (u64, Rc<PgEnumType>)>> = RefCell::new(HashMap::new());
+    /// bumped whenever `pg_enum` changes, making every cached [`PgEnumType`] stale
+    static ENUM_TYPES_GENERATION: Cell<u64> = Cell::new(0);
+    static ENUM_TYPES_CALLBACK: Cell<bool> = Cell::new(false);
+}
+
+impl PgEnumType {
+    /// Find the enum type named `typname` and its labels.
+    pub fn lookup(typname: &str) -> Result<Rc<PgEnumType>, PgEnumTypeError> {
+        register_invalidation_callback();
+
+        let generation = ENUM_TYPES_GENERATION.with(Cell::get);
+        let cached = ENUM_TYPES.with(|types| {
+            types
+                .borrow()
+                .get(typname)
+                .filter(|(cached_generation, _)| *cached_generation == generation)
+                .map(|(_, enum_type)| enum_type.clone())
+        });
+        if let Some(enum_type) = cached {
+            return Ok(enum_type);
+        }
+
+        // the catalogs are read outside of any `ENUM_TYPES` borrow, as reading them can run the
+        // invalidation callback
+        let enum_type = Rc::new(Self::load(typname)?);
+        ENUM_TYPES.with(|types| {
+            types.borrow_mut().insert(typname.to_string(), (generation, enum_type.clone()))
+        });
+        Ok(enum_type)
+    }
+
+    fn load(typname: &str) -> Result<PgEnumType, PgEnumTypeError> {
+        let oid = unsafe {
+            direct_function_call::<pg_sys::Oid>(pg_sys::to_regtype, &[typname.into_datum()])
+        }
+        .ok_or_else(|| PgEnumTypeError::NotFound(typname.to_string()))?;
+        if unsafe { pg_sys::get_typtype(oid) } as u8 != pg_sys::TYPTYPE_ENUM {
+            return Err(PgEnumTypeError::NotAnEnum(typname.to_string()));
+        }
+
+        let pg_enum =
+            unsafe { PgRelation::with_lock(pg_sys::EnumRelationId, pg_sys::AccessShareLock as _) };
+        let keys = ScanKeys::new().eq(pg_sys::Anum_pg_enum_enumtypid, oid);
+        let index = unsafe { pg_sys::Oid::from_u32_unchecked(pg_sys::EnumTypIdSortOrderIndexId) };
+        let labels = SysTableScan::new(&pg_enum, Some(index), keys)
+            .map(|tuple| unsafe {
+                // SAFETY:  the tuple is a valid copy of a `pg_enum` row
+                let tuple = tuple.into_pg();
+                let form = &*(GETSTRUCT(tuple) as pg_sys::Form_pg_enum);
+                (form.enumlabel.as_str().to_string(), extract_enum_oid(tuple))
+            })
+            .collect();
+
+        Ok(PgEnumType { oid, labels })
+    }
+
+    /// The Oid of the enum type itself
+    pub fn oid(&self) -> pg_sys::Oid {
+        self.oid
+    }
+
+    /// The type's labels, in sort order
+    pub fn labels(&self) -> impl Iterator<Item = &str> + '_ {
+        self.labels.iter().map(|(label, _)| label.as_str())
+    }
+
+    /// The Oid of `label`, which is the Datum of that enum value
+    pub fn label_oid(&self, label: &str) -> Option<pg_sys::Oid> {
+        self.labels.iter().find(|(l, _)| l == label).map(|(_, oid)| *oid)
+    }
+
+    /// The label whose Oid is `oid`
+    pub fn label_of(&self, oid: pg_sys::Oid) -> Option<&str> {
+        self.labels.iter().find(|(_, o)| *o == oid).map(|(label, _)| label.as_str())
+    }
+}
+
+fn register_invalidation_callback() {
+    #[pg_guard]
+    unsafe extern "C" fn invalidate(
+        _arg: pg_sys::Datum,
+        _cacheid: std::os::raw::c_int,
+        _hashvalue: u32,
+    ) {
+        ENUM_TYPES_GENERATION.with(|generation| generation.set(generation.get() + 1));
+    }
+
+    if !ENUM_TYPES_CALLBACK.with(|registered| registered.replace(true)) {
+        unsafe {
+            pg_sys::CacheRegisterSyscacheCallback(
+                pg_sys::SysCacheIdentifier_ENUMOID as i32,
+                Some(invalidate),
+                pg_sys::Datum::from(0),
+            );
+        }
+    }
+}
+
 #[cfg(feature = "pg11")]
 unsafe fn extract_enum_oid(tup: *mut pg_sys::HeapTupleData) -> pg_sys::Oid {
     // #define HeapTupleHeaderGetOid(tup) \
@@ -0,0 +1,365 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Event triggers, and a journal of the DDL run against an extension's own objects
+//!
+//! An event trigger function is a `#[pg_extern]` which takes the
+//! [`pg_sys::FunctionCallInfo`] and returns an [`EventTrigger`].  [`PgEventTrigger`] makes sense
+//! of what it was called for:
+//!
+//! ```rust,no_run
+//! use pgrx::prelude::*;
+//! use pgrx::event_trigger::{EventTrigger, PgEventTrigger};
+//!
+//! #[pg_extern]
+//! fn log_ddl(fcinfo: pg_sys::FunctionCallInfo) -> EventTrigger {
+//!     let trigger = unsafe { PgEventTrigger::from_fcinfo(fcinfo) }.unwrap();
+//!     notice!("{:?}: {}", trigger.event(), trigger.tag());
+//!     EventTrigger
+//! }
+//!
+//! extension_sql!(
+//!     "CREATE EVENT TRIGGER log_ddl ON ddl_command_end EXECUTE FUNCTION log_ddl();",
+//!     name = "log_ddl_trigger",
+//!     requires = [log_ddl]
+//! );
+//! ```
+//!
+//! An extension whose objects users may alter, say by adding indexes or columns to its tables,
+//! can keep an audit trail of that DDL with a [`DdlJournal`], and use what it records to repair
+//! any state of its own that depends on those objects.
+use crate::prelude::*;
+use crate::{is_a, spi};
+use core::ffi::CStr;
+use pgrx_sql_entity_graph::metadata::{
+    ArgumentError, Returns, ReturnsError, SqlMapping, SqlTranslatable,
+};
+
+// Postgres 14 renamed the `event_trigger` type's oid
+#[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13"))]
+const EVENT_TRIGGEROID: pg_sys::Oid = pg_sys::EVTTRIGGEROID;
+#[cfg(not(any(feature = "pg11", feature = "pg12", feature = "pg13")))]
+const EVENT_TRIGGEROID: pg_sys::Oid = pg_sys::EVENT_TRIGGEROID;
+
+/// The return type of an event trigger function, which is `event_trigger` in SQL.
+///
+/// Postgres ignores what event trigger functions return, so this is always NULL.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EventTrigger;
+
+impl IntoDatum for EventTrigger {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        None
+    }
+
+    fn type_oid() -> pg_sys::Oid {
+        EVENT_TRIGGEROID
+    }
+}
+
+unsafe impl SqlTranslatable for EventTrigger {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        Err(ArgumentError::NotValidAsArgument("EventTrigger"))
+    }
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        Ok(Returns::One(SqlMapping::literal("event_trigger")))
+    }
+}
+
+/// The events an event trigger can fire on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventTriggerEvent {
+    DdlCommandStart,
+    DdlCommandEnd,
+    TableRewrite,
+    SqlDrop,
+}
+
+impl EventTriggerEvent {
+    /// The event's name, as given to `CREATE EVENT TRIGGER`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventTriggerEvent::DdlCommandStart => "ddl_command_start",
+            EventTriggerEvent::DdlCommandEnd => "ddl_command_end",
+            EventTriggerEvent::TableRewrite => "table_rewrite",
+            EventTriggerEvent::SqlDrop => "sql_drop",
+        }
+    }
+}
+
+impl TryFrom<&str> for EventTriggerEvent {
+    type Error = PgEventTriggerError;
+
+    fn try_from(event: &str) -> Result<Self, Self::Error> {
+        match event {
+            "ddl_command_start" => Ok(EventTriggerEvent::DdlCommandStart),
+            "ddl_command_end" => Ok(EventTriggerEvent::DdlCommandEnd),
+            "table_rewrite" => Ok(EventTriggerEvent::TableRewrite),
+            "sql_drop" => Ok(EventTriggerEvent::SqlDrop),
+            other => Err(PgEventTriggerError::UnknownEvent(other.to_string())),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone)]
+pub enum PgEventTriggerError {
+    #[error("`PgEventTrigger`s can only be built from the `FunctionCallInfo` of an event trigger")]
+    NotEventTrigger,
+    #[error("`PgEventTrigger`s cannot be built from `NULL` `pgrx::pg_sys::FunctionCallInfo`s")]
+    NullFunctionCallInfo,
+    #[error("unknown event trigger event `{0}`")]
+    UnknownEvent(String),
+    #[error("core::str::Utf8Error: {0}")]
+    CoreUtf8(#[from] core::str::Utf8Error),
+}
+
+/// Is the function being called as an event trigger?
+///
+/// # Safety
+///
+/// `fcinfo` must be a valid, non-null `FunctionCallInfo`
+pub unsafe fn called_as_event_trigger(fcinfo: pg_sys::FunctionCallInfo) -> bool {
+    let fcinfo = fcinfo.as_ref().expect("fcinfo was null");
+    !fcinfo.context.is_null() && is_a(fcinfo.context, pg_sys::NodeTag_T_EventTriggerData)
+}
+
+/// What an event trigger function was called for
+pub struct PgEventTrigger<'a> {
+    data: &'a pg_sys::EventTriggerData,
+    event: EventTriggerEvent,
+    tag: &'a str,
+}
+
+impl<'a> PgEventTrigger<'a> {
+    /// # Safety
+    ///
+    /// `fcinfo` must be the `FunctionCallInfo` the function was called with, and the
+    /// `PgEventTrigger` must not outlive the call.
+    pub unsafe fn from_fcinfo(
+        fcinfo: pg_sys::FunctionCallInfo,
+    ) -> Result<PgEventTrigger<'a>, PgEventTriggerError> {
+        if fcinfo.is_null() {
+            return Err(PgEventTriggerError::NullFunctionCallInfo);
+        }
+        if !called_as_event_trigger(fcinfo) {
+            return Err(PgEventTriggerError::NotEventTrigger);
+        }
+
+        let data = &*((*fcinfo).context as *const pg_sys::EventTriggerData);
+        let event = EventTriggerEvent::try_from(CStr::from_ptr(data.event).to_str()?)?;
+        #[cfg(any(feature = "pg11", feature = "pg12"))]
+        let tag = CStr::from_ptr(data.tag).to_str()?;
+        #[cfg(not(any(feature = "pg11", feature = "pg12")))]
+        let tag = CStr::from_ptr(pg_sys::GetCommandTagName(data.tag)).to_str()?;
+
+        Ok(PgEventTrigger { data, event, tag })
+    }
+
+    /// The event the trigger fired on
+    pub fn event(&self) -> EventTriggerEvent {
+        self.event
+    }
+
+    /// The tag of the command which fired the trigger, such as `CREATE INDEX`
+    pub fn tag(&self) -> &'a str {
+        self.tag
+    }
+
+    /// The parse tree of the command which fired the trigger
+    pub fn parsetree(&self) -> *mut pg_sys::Node {
+        self.data.parsetree
+    }
+}
+
+/// A row recorded by a [`DdlJournal`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalEntry {
+    pub event: EventTriggerEvent,
+    pub command_tag: String,
+    pub object_type: Option<String>,
+    pub schema_name: Option<String>,
+    pub object_identity: Option<String>,
+}
+
+/// Records the DDL users run against an extension's own objects into a table of the extension's.
+///
+/// Call [`DdlJournal::record()`] from an event trigger function which fires on `ddl_command_end`
+/// and, to also journal objects being dropped, `sql_drop`.  A command is recorded when the object
+/// it creates or alters is a member of the extension, or depends on one the way an index or a
+/// trigger depends on its table.  By the time `sql_drop` fires, the dropped objects' dependencies
+/// are gone, so a dropped object is recorded when a command on that same object, identified by
+/// its OID, was.  The extension's own script, run by `CREATE EXTENSION` or
+/// `ALTER EXTENSION ... UPDATE`, is never recorded.
+///
+/// The journal table needs these columns, though it may have others with defaults:
+///
+/// ```sql
+/// CREATE TABLE ddl_journal (
+///     event text NOT NULL,
+///     command_tag text NOT NULL,
+///     object_type text,
+///     schema_name text,
+///     object_identity text,
+///     classid oid,
+///     objid oid
+/// );
+/// ```
+///
+/// Event triggers run as the user whose command fired them, who usually can't write to the
+/// extension's tables, so make the trigger function `security_definer`.  Since it then runs with
+/// the extension owner's privileges, also pin its `search_path`, and schema-qualify the journal
+/// table or leave it in the extension's schema.
+///
+/// A `DdlJournal` is only a configuration, so it's usually declared as a `static`:
+///
+/// ```rust,no_run
+/// use pgrx::prelude::*;
+/// use pgrx::event_trigger::{DdlJournal, EventTrigger, PgEventTrigger};
+///
+/// static JOURNAL: DdlJournal = DdlJournal::new("my_extension", "ddl_journal");
+///
+/// extension_sql!(
+///     "CREATE TABLE ddl_journal (
+///         happened_at timestamptz NOT NULL DEFAULT now(),
+///         username text NOT NULL DEFAULT current_user,
+///         event text NOT NULL,
+///         command_tag text NOT NULL,
+///         object_type text,
+///         schema_name text,
+///         object_identity text,
+///         classid oid,
+///         objid oid
+///     );",
+///     name = "ddl_journal_table"
+/// );
+///
+/// #[pg_extern(security_definer)]
+/// #[search_path(pg_catalog, @extschema@)]
+/// fn journal_ddl(fcinfo: pg_sys::FunctionCallInfo) -> EventTrigger {
+///     let trigger = unsafe { PgEventTrigger::from_fcinfo(fcinfo) }.unwrap();
+///     for entry in JOURNAL.record(&trigger).unwrap() {
+///         if entry.command_tag == "ALTER TABLE" {
+///             // rebuild whatever depends on the shape of our tables
+///         }
+///     }
+///     EventTrigger
+/// }
+///
+/// extension_sql!(
+///     "CREATE EVENT TRIGGER journal_ddl ON ddl_command_end EXECUTE FUNCTION journal_ddl();
+///      CREATE EVENT TRIGGER journal_drops ON sql_drop EXECUTE FUNCTION journal_ddl();",
+///     name = "ddl_journal_triggers",
+///     requires = ["ddl_journal_table", journal_ddl]
+/// );
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct DdlJournal {
+    extension: &'static str,
+    table: &'static str,
+}
+
+impl DdlJournal {
+    /// Journal the DDL run against the members of `extension` into `table`, which may be
+    /// schema-qualified.  An unqualified `table` is looked up in the `search_path` of each call to
+    /// [`DdlJournal::record()`].
+    pub const fn new(extension: &'static str, table: &'static str) -> Self {
+        DdlJournal { extension, table }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        self.extension
+    }
+
+    pub fn table(&self) -> &'static str {
+        self.table
+    }
+
+    /// Record what the command which fired `trigger` did to the extension's objects, returning the
+    /// entries written to the journal.
+    ///
+    /// Nothing is recorded for events other than `ddl_command_end` and `sql_drop`, or once the
+    /// journal table itself has been dropped, as it is by `DROP EXTENSION`.
+    pub fn record(&self, trigger: &PgEventTrigger) -> Result<Vec<JournalEntry>, spi::Error> {
+        // look the table up once, so the query can't find a different one
+        let table = Spi::get_one_with_args::<String>(
+            "SELECT pg_catalog.format('%I.%I', n.nspname, c.relname)
+               FROM pg_catalog.pg_class c
+               JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace
+              WHERE c.oid = pg_catalog.to_regclass($1)",
+            vec![(PgBuiltInOids::TEXTOID.oid(), self.table.into_datum())],
+        );
+        let table = match table {
+            Ok(Some(table)) => table,
+            Ok(None) | Err(spi::Error::InvalidPosition) => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let query = match trigger.event() {
+            EventTriggerEvent::DdlCommandEnd => format!(
+                "INSERT INTO {table} (event, command_tag, object_type, schema_name, object_identity, classid, objid)
+                 SELECT $2, c.command_tag, c.object_type, c.schema_name, c.object_identity, c.classid, c.objid
+                   FROM pg_catalog.pg_event_trigger_ddl_commands() c
+                  WHERE NOT c.in_extension
+                    AND EXISTS (
+                        SELECT 1
+                          FROM pg_catalog.pg_depend e
+                          JOIN pg_catalog.pg_extension x ON x.oid = e.refobjid
+                         WHERE e.refclassid = 'pg_catalog.pg_extension'::pg_catalog.regclass
+                           AND e.deptype = 'e'
+                           AND x.extname = $1
+                           AND ((e.classid, e.objid) = (c.classid, c.objid)
+                                OR (e.classid, e.objid) IN (
+                                    SELECT d.refclassid, d.refobjid
+                                      FROM pg_catalog.pg_depend d
+                                     WHERE d.classid = c.classid
+                                       AND d.objid = c.objid
+                                       AND d.deptype IN ('n', 'a', 'i'))))
+                 RETURNING command_tag, object_type, schema_name, object_identity"
+            ),
+            EventTriggerEvent::SqlDrop => format!(
+                "INSERT INTO {table} (event, command_tag, object_type, schema_name, object_identity, classid, objid)
+                 SELECT $2, $3, d.object_type, d.schema_name, d.object_identity, d.classid, d.objid
+                   FROM pg_catalog.pg_event_trigger_dropped_objects() d
+                  WHERE EXISTS (
+                        SELECT 1
+                          FROM {table} j
+                         WHERE (j.classid, j.objid) = (d.classid, d.objid)
+                           AND j.object_identity = d.object_identity)
+                 RETURNING command_tag, object_type, schema_name, object_identity"
+            ),
+            EventTriggerEvent::DdlCommandStart | EventTriggerEvent::TableRewrite => {
+                return Ok(Vec::new())
+            }
+        };
+
+        let event = trigger.event();
+        Spi::connect(|mut client| {
+            client
+                .update(
+                    &query,
+                    None,
+                    Some(vec![
+                        (PgBuiltInOids::TEXTOID.oid(), self.extension.into_datum()),
+                        (PgBuiltInOids::TEXTOID.oid(), event.as_str().into_datum()),
+                        (PgBuiltInOids::TEXTOID.oid(), trigger.tag().into_datum()),
+                    ]),
+                )?
+                .map(|row| {
+                    Ok(JournalEntry {
+                        event,
+                        command_tag: row.get(1)?.unwrap_or_default(),
+                        object_type: row.get(2)?,
+                        schema_name: row.get(3)?,
+                        object_identity: row.get(4)?,
+                    })
+                })
+                .collect()
+        })
+    }
+}
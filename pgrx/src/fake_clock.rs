@@ -0,0 +1,103 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! A pinned "current time", for deterministically testing time-dependent extension logic.
+//!
+//! When the `pgrx.fake_clock` setting holds a `timestamptz`, pgrx's
+//! [`now()`][crate::datum::now], [`transaction_timestamp()`][crate::datum::transaction_timestamp],
+//! [`statement_timestamp()`][crate::datum::statement_timestamp] and
+//! [`clock_timestamp()`][crate::datum::clock_timestamp] all return it instead of the real time:
+//!
+//! ```sql
+//! SET pgrx.fake_clock = '2023-01-01 00:00:00+00';
+//! ```
+//!
+//! The clock doesn't move on its own.  Use [`set`] or [`advance`] to move it, and [`clear`] to go
+//! back to the real time.
+//!
+//! `#[pg_test(fake_clock = "...")]` pins the clock for a single test, and also makes the SQL
+//! functions of the same names follow it by putting shadowing versions of them ahead of
+//! `pg_catalog` in the `search_path`.  SQL's `CURRENT_TIMESTAMP` and friends are keywords
+//! rather than functions, so they can't be shadowed and always report the real time.
+//!
+//! This is all compiled out unless pgrx's `fake-clock` feature is enabled, which is meant to be
+//! done only for tests, for example from an extension's `pg_test` feature:
+//!
+//! ```toml
+//! [features]
+//! pg_test = ["pgrx/fake-clock"]
+//! ```
+use crate::{Interval, TimestampWithTimeZone};
+
+/// The setting which pins the clock
+pub const FAKE_CLOCK_GUC: &str = "pgrx.fake_clock";
+
+/// The time the clock is pinned at, if it is.
+///
+/// This is always `None` unless the `fake-clock` feature is enabled.
+#[inline]
+pub fn pinned() -> Option<TimestampWithTimeZone> {
+    #[cfg(feature = "fake-clock")]
+    return enabled::pinned();
+
+    #[cfg(not(feature = "fake-clock"))]
+    None
+}
+
+/// Pin the clock at `now` until the end of the current transaction.
+pub fn set(now: TimestampWithTimeZone) {
+    set_setting(&now.to_string());
+}
+
+/// Move the clock forward by `interval`, pinning it at the real time first if it isn't already.
+pub fn advance(interval: Interval) {
+    set(pinned().unwrap_or_else(crate::datum::clock_timestamp) + interval);
+}
+
+/// Unpin the clock until the end of the current transaction.
+pub fn clear() {
+    set_setting("");
+}
+
+fn set_setting(value: &str) {
+    use crate::{direct_function_call, pg_sys, IntoDatum};
+
+    unsafe {
+        // same as `SELECT set_config('pgrx.fake_clock', value, true)`
+        direct_function_call::<&str>(
+            pg_sys::set_config_by_name,
+            &[FAKE_CLOCK_GUC.into_datum(), value.into_datum(), true.into_datum()],
+        );
+    }
+}
+
+#[cfg(feature = "fake-clock")]
+mod enabled {
+    use crate::{error, pg_sys, TimestampWithTimeZone};
+    use std::ffi::CStr;
+
+    pub(super) fn pinned() -> Option<TimestampWithTimeZone> {
+        const NAME: &[u8] = b"pgrx.fake_clock\0";
+        debug_assert_eq!(&NAME[..NAME.len() - 1], super::FAKE_CLOCK_GUC.as_bytes());
+
+        let setting = unsafe {
+            // an unregistered, unset custom setting doesn't exist at all, hence `missing_ok`
+            let value = pg_sys::GetConfigOption(NAME.as_ptr().cast(), true, false);
+            if value.is_null() || *value == 0 {
+                return None;
+            }
+            CStr::from_ptr(value).to_str().ok()?
+        };
+
+        match setting.parse() {
+            Ok(now) => Some(now),
+            Err(e) => error!("invalid value for `{}`: {e}", super::FAKE_CLOCK_GUC),
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Deterministic `ERROR` injection, for testing how an extension cleans up after failures.
+//!
+//! pgrx calls [`fault_point`] at a few places inside itself:
+//!
+//! * [`SPI`] when connecting to SPI and before running a statement
+//! * [`ALLOC`] when allocating through [`PgMemoryContexts`][crate::PgMemoryContexts] or
+//!   [`PgBox`][crate::PgBox]
+//! * [`DATUM`] when converting a Datum with [`FromDatum`][crate::FromDatum]
+//!
+//! Extensions can add their own named points by calling [`fault_point`] too.
+//!
+//! Points are armed with the `pgrx.inject_fault` setting, a comma-separated list of point names,
+//! each optionally followed by `:N` to fail only on the point's Nth hit rather than on every hit:
+//!
+//! ```sql
+//! SET pgrx.inject_fault = 'spi:2, my_extension.flush';
+//! ```
+//!
+//! Hit counts start over whenever the setting changes, or on [`reset`].
+//!
+//! This is all compiled out unless pgrx's `fault-injection` feature is enabled, which is meant
+//! to be done only for tests, for example from an extension's `pg_test` feature:
+//!
+//! ```toml
+//! [features]
+//! pg_test = ["pgrx/fault-injection"]
+//! ```
+
+/// The setting which arms fault points
+pub const FAULT_INJECTION_GUC: &str = "pgrx.inject_fault";
+
+/// Hit when connecting to SPI and before each statement is run through it
+pub const SPI: &str = "spi";
+
+/// Hit when pgrx allocates memory in a Postgres memory context
+pub const ALLOC: &str = "alloc";
+
+/// Hit when pgrx converts a Datum into a Rust value
+pub const DATUM: &str = "datum";
+
+/// Raise an `ERROR` if the fault point `name` is armed by the `pgrx.inject_fault` setting.
+///
+/// This does nothing unless the `fault-injection` feature is enabled.
+#[inline]
+pub fn fault_point(name: &str) {
+    #[cfg(feature = "fault-injection")]
+    enabled::fault_point(name);
+
+    #[cfg(not(feature = "fault-injection"))]
+    let _ = name;
+}
+
+/// Restart every fault point's hit count.
+pub fn reset() {
+    #[cfg(feature = "fault-injection")]
+    enabled::reset();
+}
+
+#[cfg(feature = "fault-injection")]
+mod enabled {
+    use super::FAULT_INJECTION_GUC;
+    use crate::{ereport, pg_sys, PgLogLevel, PgSqlErrorCode};
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::ffi::CStr;
+
+    #[derive(Default)]
+    struct State {
+        /// the setting the hit counts belong to
+        setting: String,
+        hits: HashMap<String, u64>,
+    }
+
+    thread_local! {
+        static STATE: RefCell<State> = RefCell::new(State::default());
+    }
+
+    pub(super) fn fault_point(name: &str) {
+        let Some(setting) = current_setting() else { return };
+
+        let fire = STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            if state.setting.as_bytes() != setting.to_bytes() {
+                state.setting = setting.to_string_lossy().into_owned();
+                state.hits.clear();
+            }
+
+            let State { setting, hits } = &mut *state;
+            for entry in setting.split(',').map(str::trim) {
+                let (point, nth) = match entry.split_once(':') {
+                    Some((point, nth)) => (point.trim(), nth.trim().parse::<u64>().ok()),
+                    None => (entry, None),
+                };
+                if point == name {
+                    let count = hits.entry(name.to_string()).or_default();
+                    *count += 1;
+                    return nth.map_or(true, |nth| *count == nth);
+                }
+            }
+            false
+        });
+
+        // raised outside of `STATE.with()` so the longjmp can't leave the RefCell borrowed
+        if fire {
+            ereport!(
+                PgLogLevel::ERROR,
+                PgSqlErrorCode::ERRCODE_INTERNAL_ERROR,
+                format!("injected fault at `{name}`")
+            );
+        }
+    }
+
+    pub(super) fn reset() {
+        STATE.with(|state| state.borrow_mut().hits.clear());
+    }
+
+    fn current_setting() -> Option<&'static CStr> {
+        const NAME: &[u8] = b"pgrx.inject_fault\0";
+        debug_assert_eq!(&NAME[..NAME.len() - 1], FAULT_INJECTION_GUC.as_bytes());
+
+        unsafe {
+            // an unregistered, unset custom setting doesn't exist at all, hence `missing_ok`
+            let value = pg_sys::GetConfigOption(NAME.as_ptr().cast(), true, false);
+            if value.is_null() || *value == 0 {
+                None
+            } else {
+                Some(CStr::from_ptr(value))
+            }
+        }
+    }
+}
@@ -11,7 +11,7 @@
 //!
 //! Typically these functions are not necessary to call directly as they're used behind
 //! the scenes by the code generated by the `#[pg_extern]` macro.
-use crate::{pg_sys, void_mut_ptr, FromDatum, PgBox, PgMemoryContexts};
+use crate::{pg_sys, void_mut_ptr, FromDatum, FromDatumBorrowed, MemCx, PgBox, PgMemoryContexts};
 
 /// A macro for specifying default argument values so they get properly translated to SQL in
 /// `CREATE FUNCTION` statements
@@ -79,6 +79,43 @@ macro_rules! name {
     };
 }
 
+/// A macro for declaring that an argument or return value is a SQL domain over the given Rust type,
+/// so the domain's name is used in `CREATE FUNCTION` statements
+///
+/// ## Examples
+///
+/// This example will create a SQL function like so:
+///
+/// ```sql
+/// CREATE FUNCTION halve(value positive_int) RETURNS positive_int ...;
+/// ```
+///
+/// ```rust
+/// use pgrx::prelude::*;
+///
+/// extension_sql!(
+///     "CREATE DOMAIN positive_int AS integer CHECK (VALUE > 0);",
+///     name = "positive_int",
+/// );
+///
+/// #[pg_extern(requires = ["positive_int"])]
+/// fn halve(value: domain!(i32, "positive_int")) -> domain!(i32, "positive_int") {
+///    value / 2
+/// }
+/// ```
+///
+/// Arguments arrive already checked against the domain's constraints and are converted using the
+/// domain's base type.  Returned values are checked against the domain's constraints before
+/// being handed back to Postgres, so the example above raises an error when called with `1`.
+///
+/// The macro may also be wrapped in an `Option`, as in `Option<domain!(i32, "positive_int")>`.
+#[macro_export]
+macro_rules! domain {
+    ($ty:ty, $name:literal) => {
+        $ty
+    };
+}
+
 #[macro_export]
 macro_rules! variadic {
     ($ty:ty) => {
@@ -100,6 +137,7 @@ mod pg_11 {
     /// We also cannot ensure that the specified Rust type `T` is compatible with whatever the
     /// underlying datum is at the argument `num` position.  This too, is your responsibility
     #[inline]
+    #[track_caller]
     pub unsafe fn pg_getarg<T: FromDatum>(
         fcinfo: pg_sys::FunctionCallInfo,
         num: usize,
@@ -111,11 +149,15 @@ mod pg_11 {
         unsafe {
             // SAFETY:  User has asserted that the desired Rust type `T` is compatible with the
             // underlying Datum, and has asserted that `fcinfo` is valid
-            if T::GET_TYPOID {
+            let value = if T::GET_TYPOID {
                 T::from_polymorphic_datum(datum, isnull, super::pg_getarg_type(fcinfo, num))
             } else {
                 T::from_datum(datum, isnull)
+            };
+            if value.is_none() && !isnull {
+                crate::datum_diagnostics::no_argument_value::<T>(fcinfo, num);
             }
+            value
         }
     }
 
@@ -212,13 +254,14 @@ mod pg_12_13_14_15 {
     /// We also cannot ensure that the specified Rust type `T` is compatible with whatever the
     /// underlying datum is at the argument `num` position.  This too, is your responsibility
     #[inline]
+    #[track_caller]
     pub unsafe fn pg_getarg<T: FromDatum>(
         fcinfo: pg_sys::FunctionCallInfo,
         num: usize,
     ) -> Option<T> {
         let datum = get_nullable_datum(fcinfo, num);
         unsafe {
-            if T::GET_TYPOID {
+            let value = if T::GET_TYPOID {
                 T::from_polymorphic_datum(
                     datum.value,
                     datum.isnull,
@@ -226,7 +269,11 @@ mod pg_12_13_14_15 {
                 )
             } else {
                 T::from_datum(datum.value, datum.isnull)
+            };
+            if value.is_none() && !datum.isnull {
+                crate::datum_diagnostics::no_argument_value::<T>(fcinfo, num);
             }
+            value
         }
     }
 
@@ -366,6 +413,54 @@ pub unsafe fn pg_getarg_type(fcinfo: pg_sys::FunctionCallInfo, num: usize) -> pg
     pg_sys::get_fn_expr_argtype(fcinfo.as_ref().unwrap().flinfo, num as std::os::raw::c_int)
 }
 
+/// The concrete element type of a (usually polymorphic) argument: the element type if the
+/// argument resolved to an array, such as for `anyarray` or `anycompatiblearray`, or otherwise the
+/// argument's own type.  Arrays over a domain are looked through to the domain's base array type.
+///
+/// Returns `None` if the argument's type can't be determined.
+///
+/// # Safety
+///
+/// The provided `fcinfo` must be valid otherwise this function results in undefined behavior due
+/// to an out of bounds read.
+#[inline]
+pub unsafe fn pg_getarg_element_type(
+    fcinfo: pg_sys::FunctionCallInfo,
+    num: usize,
+) -> Option<pg_sys::Oid> {
+    let typoid = pg_getarg_type(fcinfo, num);
+    if typoid == pg_sys::InvalidOid {
+        return None;
+    }
+    let element_type = pg_sys::get_base_element_type(typoid);
+    if element_type == pg_sys::InvalidOid {
+        Some(typoid)
+    } else {
+        Some(element_type)
+    }
+}
+
+/// Get a numbered argument for a `PG_FUNCTION_INFO_V1` function as a Rust type `T` which borrows
+/// from `memcx`, and so can't outlive it.
+///
+/// # Safety
+///
+/// This function is unsafe as we cannot ensure the `fcinfo` argument is a valid
+/// [`pg_sys::FunctionCallInfo`] pointer.  This is your responsibility.
+///
+/// It is also your responsibility to ensure the argument lives at least as long as `memcx`, which
+/// is the case for the `CurrentMemoryContext` during the call.
+#[inline]
+pub unsafe fn pg_getarg_borrowed<'mcx, T: FromDatumBorrowed<'mcx>>(
+    fcinfo: pg_sys::FunctionCallInfo,
+    num: usize,
+    memcx: &MemCx<'mcx>,
+) -> Option<T> {
+    let datum = pg_getarg_datum_raw(fcinfo, num);
+    let isnull = pg_arg_is_null(fcinfo, num);
+    T::from_datum_borrowed(datum, isnull, pg_getarg_type(fcinfo, num), memcx)
+}
+
 /// This is intended for Postgres functions that take an actual `cstring` argument, not for getting
 /// a varlena argument type as a CStr.
 ///
@@ -410,6 +505,88 @@ pub unsafe fn pg_return_void() -> pg_sys::Datum {
     pg_sys::Datum::from(0)
 }
 
+/// What [`pg_return_domain_checked`] keeps in `fn_extra` between calls
+struct DomainCheckCache {
+    /// The domain the function returns, or `InvalidOid` if it doesn't return one
+    domain: pg_sys::Oid,
+    /// `domain_check`'s own cache of the domain's constraints
+    extra: *mut std::os::raw::c_void,
+}
+
+/// The function's return type, if it's a domain
+unsafe fn domain_rettype(flinfo: *mut pg_sys::FmgrInfo) -> pg_sys::Oid {
+    let rettype = pg_sys::get_fn_expr_rettype(flinfo);
+    if rettype != pg_sys::InvalidOid && pg_sys::get_typtype(rettype) as u8 == pg_sys::TYPTYPE_DOMAIN
+    {
+        rettype
+    } else {
+        pg_sys::InvalidOid
+    }
+}
+
+/// Checks a function's return value against the constraints of the domain it's declared to return,
+/// raising the usual Postgres error if they're violated.
+///
+/// Does nothing if the function's return type can't be determined or isn't a domain.  Like
+/// Postgres' own `domain_in`, the domain's constraints are looked up once per `flinfo` and kept in
+/// its `fn_extra`, allocated in its `fn_mcxt`.
+///
+/// # Safety
+///
+/// This function is unsafe as we cannot guarantee the provided [`pg_sys::FunctionCallInfo`] pointer
+/// is valid, nor that `datum` is of the domain's base type.
+///
+/// The function must not use `fn_extra` for anything else.  Use
+/// [`pg_return_domain_checked_uncached`] if it does.
+pub unsafe fn pg_return_domain_checked(
+    fcinfo: pg_sys::FunctionCallInfo,
+    datum: pg_sys::Datum,
+) -> pg_sys::Datum {
+    let fcinfo = fcinfo.as_mut().unwrap();
+    let flinfo = fcinfo.flinfo;
+    if (*flinfo).fn_extra.is_null() {
+        let cache = PgMemoryContexts::For((*flinfo).fn_mcxt).palloc0_struct::<DomainCheckCache>();
+        (*cache).domain = domain_rettype(flinfo);
+        (*flinfo).fn_extra = cache.cast();
+    }
+    let cache = (*flinfo).fn_extra.cast::<DomainCheckCache>();
+    if (*cache).domain != pg_sys::InvalidOid {
+        pg_sys::domain_check(
+            datum,
+            fcinfo.isnull,
+            (*cache).domain,
+            &mut (*cache).extra,
+            (*flinfo).fn_mcxt,
+        );
+    }
+    datum
+}
+
+/// Like [`pg_return_domain_checked`], but for functions which use `fn_extra` themselves, and so
+/// look up the domain's constraints on every call.
+///
+/// # Safety
+///
+/// This function is unsafe as we cannot guarantee the provided [`pg_sys::FunctionCallInfo`] pointer
+/// is valid, nor that `datum` is of the domain's base type.
+pub unsafe fn pg_return_domain_checked_uncached(
+    fcinfo: pg_sys::FunctionCallInfo,
+    datum: pg_sys::Datum,
+) -> pg_sys::Datum {
+    let fcinfo = fcinfo.as_mut().unwrap();
+    let domain = domain_rettype(fcinfo.flinfo);
+    if domain != pg_sys::InvalidOid {
+        pg_sys::domain_check(
+            datum,
+            fcinfo.isnull,
+            domain,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        );
+    }
+    datum
+}
+
 /// Retrieve the `.flinfo.fn_extra` pointer (as a PgBox'd type) from [`pg_sys::FunctionCallInfo`].
 ///
 /// This function is unsafe as we cannot guarantee the provided [`pg_sys::FunctionCallInfo`] pointer is valid
@@ -0,0 +1,1108 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Scaffolding for the more involved callbacks of a foreign data wrapper
+//!
+//! Each group of callbacks is described by a trait, and installed into the FDW's `FdwRoutine`
+//! with a `set_*` function from its handler function.
+//!
+//! ## Join pushdown
+//!
+//! When both sides of a join are foreign tables on the same server, the planner offers the join
+//! to the FDW's `GetForeignJoinPaths` callback.  An FDW implementing [`ForeignJoinPaths`] gets a
+//! [`ForeignJoin`] describing the join, and can add a [`ForeignJoin::add_path()`] for it.
+//!
+//! A pushed-down join replaces the scans of its foreign tables, so when a row of one of them has
+//! to be rechecked for `EvalPlanQual` (an `UPDATE`, `DELETE` or `SELECT ... FOR UPDATE` racing a
+//! concurrent update), the join must be recomputed locally.  [`ForeignJoin::add_path()`] keeps
+//! the planner's best local join path for that, and [`recheck_join()`] runs its plan from the
+//! FDW's `RecheckForeignScan` callback.
+//!
+//! ```rust,no_run
+//! use pgrx::cost::ScanCost;
+//! use pgrx::fdw::{ForeignJoin, ForeignJoinPaths};
+//! use pgrx::prelude::*;
+//! use pgrx::PgMemoryContexts;
+//!
+//! struct MyFdw;
+//!
+//! #[derive(Default)]
+//! struct JoinState {
+//!     remote_sql: Option<String>,
+//! }
+//!
+//! impl ForeignJoinPaths for MyFdw {
+//!     fn get_foreign_join_paths(join: ForeignJoin<'_>) {
+//!         if join.already_considered() || join.jointype() != pg_sys::JoinType_JOIN_INNER {
+//!             return;
+//!         }
+//!         unsafe {
+//!             // remember that we've seen this join, and how to send it to the remote server
+//!             let state = PgMemoryContexts::CurrentMemoryContext
+//!                 .leak_and_drop_on_delete(JoinState::default());
+//!             join.set_fdw_private(state);
+//!             let cost = ScanCost::new(join.rows()).startup(100.0).tuples(join.rows()).finish();
+//!             join.add_path(cost, std::ptr::null_mut());
+//!         }
+//!     }
+//! }
+//!
+//! # fn handler(routine: &mut pg_sys::FdwRoutine) {
+//! // in the FDW's handler function
+//! pgrx::fdw::set_foreign_join_paths::<MyFdw>(routine);
+//! # }
+//! ```
+//!
+//! ## Asynchronous execution
+//!
+//! On Postgres 14 and later, an `Append` over several foreign scans, such as a partitioned table
+//! whose partitions live on different servers, can have all of them fetching at once rather than
+//! one after another.  An FDW implementing [`ForeignAsync`] starts fetching in
+//! [`ForeignAsync::request()`], then tells the `Append` which socket to wait on, and is notified
+//! once it's readable.  FDWs whose requests are made by other threads can wait on an
+//! [`AsyncNotifier`], which those threads wake with an [`AsyncWaker`].
+//!
+//! ## Modifications
+//!
+//! An FDW which can write to its foreign tables has the modified rows sent back by the remote
+//! server when the statement has a `RETURNING` clause or `WITH CHECK OPTION`s.  A
+//! [`ForeignModifyPlan`] says which of their columns are needed, and a [`ReturnedRow`] puts
+//! them where Postgres expects them.
+//!
+//! ## Batch inserts
+//!
+//! On Postgres 14 and later, an FDW implementing [`ForeignBatchInsert`] is given the rows of an
+//! `INSERT` or `COPY` into its foreign tables in batches of up to
+//! [`ForeignBatchInsert::batch_size()`] rows, so it can send each batch to the remote server in a
+//! single round trip.
+//!
+//! ## Options
+//!
+//! `CREATE SERVER`, `CREATE FOREIGN TABLE` and friends accept `OPTIONS (...)`, which are checked
+//! by the FDW's validator function.  A struct deriving [`FdwOptions`] describes the options of
+//! one kind of object, with their types, defaults and whether they're required, and
+//! [`validate_options()`] checks them from the validator.  The same struct then reads the
+//! options back with [`FdwOptions::for_table()`] and the like.
+use crate as pgrx; // for #[pg_guard] support from within ourself
+use crate::cost::PathCost;
+use crate::heap_tuple::{attribute_datum, attribute_number};
+use crate::{pg_guard, pg_sys, IntoDatum, PgList, PgRelation, PgTupleDesc, TryFromDatumError};
+use pg_sys::elog::PgLogLevel;
+use pg_sys::errcodes::PgSqlErrorCode;
+use pg_sys::function_name;
+use pg_sys::panic::ErrorReport;
+use std::ffi::CStr;
+use std::marker::PhantomData;
+use std::num::NonZeroUsize;
+#[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+use std::{
+    io::{Read, Write},
+    os::unix::{io::AsRawFd, net::UnixStream},
+};
+
+/// A join between relations of a foreign server, which the FDW may be able to push down
+pub struct ForeignJoin<'a> {
+    root: *mut pg_sys::PlannerInfo,
+    joinrel: *mut pg_sys::RelOptInfo,
+    outerrel: *mut pg_sys::RelOptInfo,
+    innerrel: *mut pg_sys::RelOptInfo,
+    jointype: pg_sys::JoinType,
+    extra: *mut pg_sys::JoinPathExtraData,
+    _marker: PhantomData<&'a pg_sys::PlannerInfo>,
+}
+
+impl<'a> ForeignJoin<'a> {
+    /// The planner's state for the query being planned
+    pub fn root(&self) -> *mut pg_sys::PlannerInfo {
+        self.root
+    }
+
+    /// The relation produced by the join
+    pub fn joinrel(&self) -> *mut pg_sys::RelOptInfo {
+        self.joinrel
+    }
+
+    /// The outer side of the join, which may itself be a join
+    pub fn outerrel(&self) -> *mut pg_sys::RelOptInfo {
+        self.outerrel
+    }
+
+    /// The inner side of the join, which may itself be a join
+    pub fn innerrel(&self) -> *mut pg_sys::RelOptInfo {
+        self.innerrel
+    }
+
+    /// The type of the join, such as `pg_sys::JoinType_JOIN_INNER`
+    pub fn jointype(&self) -> pg_sys::JoinType {
+        self.jointype
+    }
+
+    /// The planner's extra information about this particular pairing of `outerrel` and
+    /// `innerrel`
+    pub fn extra(&self) -> *mut pg_sys::JoinPathExtraData {
+        self.extra
+    }
+
+    /// The join's estimated number of rows
+    pub fn rows(&self) -> f64 {
+        unsafe { (*self.joinrel).rows }
+    }
+
+    /// The join clauses, and any other restrictions which can be evaluated at this join, for this
+    /// pairing of `outerrel` and `innerrel`.
+    pub fn restrictions(&self) -> PgList<pg_sys::RestrictInfo> {
+        unsafe { PgList::from_pg((*self.extra).restrictlist) }
+    }
+
+    /// Has the FDW already seen this join, through another pairing of its relations?
+    ///
+    /// The planner offers a join of three or more relations once for each way of splitting it
+    /// into two sides, but the remote server will plan the join itself, so one path is enough.
+    /// This is `true` once [`set_fdw_private()`](ForeignJoin::set_fdw_private) has been called.
+    pub fn already_considered(&self) -> bool {
+        unsafe { !(*self.joinrel).fdw_private.is_null() }
+    }
+
+    /// Attach the FDW's own state to the join relation, where it can be found by `GetForeignPlan`
+    /// and by joins of this join with further relations.
+    ///
+    /// # Safety
+    ///
+    /// `fdw_private` must be allocated in a memory context which lives as long as the plan.
+    pub unsafe fn set_fdw_private<T>(&self, fdw_private: *mut T) {
+        (*self.joinrel).fdw_private = fdw_private.cast();
+    }
+
+    /// Does this query need to be able to recheck rows with `EvalPlanQual`?
+    pub fn needs_epq(&self) -> bool {
+        unsafe {
+            let parse = (*self.root).parse;
+            (*parse).commandType == pg_sys::CmdType_CMD_UPDATE
+                || (*parse).commandType == pg_sys::CmdType_CMD_DELETE
+                || !(*self.root).rowMarks.is_null()
+        }
+    }
+
+    /// The cheapest local path for this join, to recompute it when rows are rechecked with
+    /// `EvalPlanQual`.
+    ///
+    /// This is null if the query doesn't [`needs_epq()`](ForeignJoin::needs_epq), or if the
+    /// planner hasn't found a local path the FDW can use, in which case the join must not be
+    /// pushed down.
+    pub fn epq_path(&self) -> *mut pg_sys::Path {
+        if !self.needs_epq() {
+            return std::ptr::null_mut();
+        }
+        unsafe { pg_sys::GetExistingLocalJoinPath(self.joinrel) }
+    }
+
+    /// Add a path which performs the whole join on the remote server, with the given `cost`, and
+    /// return it, or null if the join can't be pushed down:  because no [`epq_path()`] was found,
+    /// or because the join is `LATERAL`, needing values from relations outside of it, which a
+    /// remote join can't be parameterized by.
+    ///
+    /// `fdw_private` is passed on to `GetForeignPlan`, through the path's `fdw_private`.
+    ///
+    /// # Safety
+    ///
+    /// `fdw_private` must be a valid `List`, or null, allocated in the planner's memory context.
+    ///
+    /// [`epq_path()`]: ForeignJoin::epq_path
+    pub unsafe fn add_path(
+        &self,
+        cost: PathCost,
+        fdw_private: *mut pg_sys::List,
+    ) -> *mut pg_sys::ForeignPath {
+        #[cfg(not(feature = "pg16"))]
+        let lateral = !pg_sys::bms_is_empty((*self.joinrel).lateral_relids);
+        // an empty Bitmapset is always NULL from Postgres 16 on
+        #[cfg(feature = "pg16")]
+        let lateral = !(*self.joinrel).lateral_relids.is_null();
+        if lateral {
+            return std::ptr::null_mut();
+        }
+
+        let epq_path = self.epq_path();
+        if epq_path.is_null() && self.needs_epq() {
+            return std::ptr::null_mut();
+        }
+
+        #[cfg(feature = "pg11")]
+        let create_path = pg_sys::create_foreignscan_path;
+        #[cfg(not(feature = "pg11"))]
+        let create_path = pg_sys::create_foreign_join_path;
+        let path = create_path(
+            self.root,
+            self.joinrel,
+            std::ptr::null_mut(), // the joinrel's own target list
+            cost.rows,
+            cost.startup,
+            cost.total,
+            std::ptr::null_mut(), // no pathkeys
+            std::ptr::null_mut(), // not parameterized
+            epq_path,
+            fdw_private,
+        );
+        pg_sys::add_path(self.joinrel, path.cast());
+        path
+    }
+}
+
+/// An FDW which can push joins down to its remote server
+pub trait ForeignJoinPaths {
+    /// Add paths for `join` with [`ForeignJoin::add_path()`], if it can be pushed down.
+    fn get_foreign_join_paths(join: ForeignJoin<'_>);
+}
+
+/// Install `T` as `routine`'s `GetForeignJoinPaths` callback.
+pub fn set_foreign_join_paths<T: ForeignJoinPaths>(routine: &mut pg_sys::FdwRoutine) {
+    routine.GetForeignJoinPaths = Some(get_foreign_join_paths::<T>);
+}
+
+#[pg_guard]
+unsafe extern "C" fn get_foreign_join_paths<T: ForeignJoinPaths>(
+    root: *mut pg_sys::PlannerInfo,
+    joinrel: *mut pg_sys::RelOptInfo,
+    outerrel: *mut pg_sys::RelOptInfo,
+    innerrel: *mut pg_sys::RelOptInfo,
+    jointype: pg_sys::JoinType,
+    extra: *mut pg_sys::JoinPathExtraData,
+) {
+    T::get_foreign_join_paths(ForeignJoin {
+        root,
+        joinrel,
+        outerrel,
+        innerrel,
+        jointype,
+        extra,
+        _marker: PhantomData,
+    })
+}
+
+/// Recheck a row of a pushed-down join for `EvalPlanQual`, by running the local join plan built
+/// from the path's `fdw_outerpath` and storing its result in `slot`.
+///
+/// Call this from the FDW's `RecheckForeignScan` callback.  Scans of a single foreign table have
+/// nothing to recompute, and always pass.  Returns `false` if the row no longer satisfies the
+/// join.
+///
+/// # Safety
+///
+/// `node` must be the executing `ForeignScanState`, with the local join plan as its outer plan if
+/// it's a join, and `slot` must be its recheck slot.
+pub unsafe fn recheck_join(
+    node: *mut pg_sys::ForeignScanState,
+    slot: *mut pg_sys::TupleTableSlot,
+) -> bool {
+    let plan = (*node).ss.ps.plan as *mut pg_sys::ForeignScan;
+    if (*plan).scan.scanrelid > 0 {
+        return true;
+    }
+
+    let outer = (*node).ss.ps.lefttree;
+    assert!(!outer.is_null(), "pushed-down join has no local plan to recheck with");
+    if !(*outer).chgParam.is_null() {
+        pg_sys::ExecReScan(outer);
+    }
+    let result = (*outer).ExecProcNode.unwrap()(outer);
+
+    #[cfg(feature = "pg11")]
+    {
+        if result.is_null() || (*result).tts_isempty {
+            return false;
+        }
+        pg_sys::ExecCopySlot(slot, result);
+    }
+    #[cfg(not(feature = "pg11"))]
+    {
+        if result.is_null() || (*result).tts_flags as u32 & pg_sys::TTS_FLAG_EMPTY != 0 {
+            return false;
+        }
+        (*(*slot).tts_ops).copyslot.unwrap()(slot, result);
+    }
+    true
+}
+
+/// An FDW which can fetch rows asynchronously, so an `Append` can overlap the scans of several
+/// foreign tables
+#[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+pub trait ForeignAsync {
+    /// Can the scan `path` be executed asynchronously?
+    fn is_async_capable(path: *mut pg_sys::ForeignPath) -> bool;
+
+    /// Produce the next row, with [`AsyncRequest::complete()`], if one is available right away,
+    /// or start fetching it and mark the request [`pending`](AsyncRequest::pending).
+    fn request(request: AsyncRequest<'_>);
+
+    /// Register what a pending request is waiting on, usually with
+    /// [`AsyncRequest::wait_for_socket()`].
+    fn configure_wait(request: AsyncRequest<'_>);
+
+    /// The socket a pending request was waiting on is ready.  Like [`ForeignAsync::request()`],
+    /// produce the next row or leave the request pending.
+    fn notify(request: AsyncRequest<'_>);
+}
+
+/// Install `T` as `routine`'s `IsForeignPathAsyncCapable`, `ForeignAsyncRequest`,
+/// `ForeignAsyncConfigureWait` and `ForeignAsyncNotify` callbacks.
+#[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+pub fn set_foreign_async<T: ForeignAsync>(routine: &mut pg_sys::FdwRoutine) {
+    routine.IsForeignPathAsyncCapable = Some(is_foreign_path_async_capable::<T>);
+    routine.ForeignAsyncRequest = Some(foreign_async_request::<T>);
+    routine.ForeignAsyncConfigureWait = Some(foreign_async_configure_wait::<T>);
+    routine.ForeignAsyncNotify = Some(foreign_async_notify::<T>);
+}
+
+#[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+#[pg_guard]
+unsafe extern "C" fn is_foreign_path_async_capable<T: ForeignAsync>(
+    path: *mut pg_sys::ForeignPath,
+) -> bool {
+    T::is_async_capable(path)
+}
+
+#[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+#[pg_guard]
+unsafe extern "C" fn foreign_async_request<T: ForeignAsync>(areq: *mut pg_sys::AsyncRequest) {
+    T::request(AsyncRequest { areq, _marker: PhantomData })
+}
+
+#[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+#[pg_guard]
+unsafe extern "C" fn foreign_async_configure_wait<T: ForeignAsync>(
+    areq: *mut pg_sys::AsyncRequest,
+) {
+    T::configure_wait(AsyncRequest { areq, _marker: PhantomData })
+}
+
+#[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+#[pg_guard]
+unsafe extern "C" fn foreign_async_notify<T: ForeignAsync>(areq: *mut pg_sys::AsyncRequest) {
+    T::notify(AsyncRequest { areq, _marker: PhantomData })
+}
+
+/// A request from an `Append` for the next row of an asynchronous foreign scan
+#[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+pub struct AsyncRequest<'a> {
+    areq: *mut pg_sys::AsyncRequest,
+    _marker: PhantomData<&'a pg_sys::AsyncRequest>,
+}
+
+#[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+impl<'a> AsyncRequest<'a> {
+    pub fn as_ptr(&self) -> *mut pg_sys::AsyncRequest {
+        self.areq
+    }
+
+    /// The foreign scan the row is requested from
+    pub fn scan_state(&self) -> *mut pg_sys::ForeignScanState {
+        unsafe { (*self.areq).requestee.cast() }
+    }
+
+    /// Is the request waiting on a socket, from an earlier [`AsyncRequest::pending()`]?
+    pub fn is_pending(&self) -> bool {
+        unsafe { (*self.areq).callback_pending }
+    }
+
+    /// Answer the request with the row in `slot`, or with no row if the scan is finished and
+    /// `slot` is null or empty.
+    ///
+    /// # Safety
+    ///
+    /// `slot` must be valid until the next request, usually by being the scan's own
+    /// `ss_ScanTupleSlot`.
+    pub unsafe fn complete(self, slot: *mut pg_sys::TupleTableSlot) {
+        pg_sys::ExecAsyncRequestDone(self.areq, slot);
+    }
+
+    /// The next row isn't available yet, so wait on the socket registered by
+    /// [`ForeignAsync::configure_wait()`] and ask again with [`ForeignAsync::notify()`].
+    pub fn pending(self) {
+        unsafe { pg_sys::ExecAsyncRequestPending(self.areq) }
+    }
+
+    /// Have the `Append` wake up, and call [`ForeignAsync::notify()`], once `socket` is readable.
+    pub fn wait_for_socket(&self, socket: pg_sys::pgsocket) {
+        unsafe {
+            let append = (*self.areq).requestor as *mut pg_sys::AppendState;
+            pg_sys::AddWaitEventToSet(
+                (*append).as_eventset,
+                pg_sys::WL_SOCKET_READABLE,
+                socket,
+                std::ptr::null_mut(),
+                self.areq.cast(),
+            );
+        }
+    }
+}
+
+/// A socket for an asynchronous foreign scan to wait on while its rows are fetched by other
+/// threads, which wake it with an [`AsyncWaker`].
+///
+/// Wait for its [`socket()`](AsyncNotifier::socket) with [`AsyncRequest::wait_for_socket()`], and
+/// [`drain()`](AsyncNotifier::drain) it once notified.
+#[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+#[derive(Debug)]
+pub struct AsyncNotifier {
+    receiver: UnixStream,
+    sender: UnixStream,
+}
+
+#[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+impl AsyncNotifier {
+    pub fn new() -> std::io::Result<Self> {
+        let (receiver, sender) = UnixStream::pair()?;
+        receiver.set_nonblocking(true)?;
+        sender.set_nonblocking(true)?;
+        Ok(Self { receiver, sender })
+    }
+
+    /// A handle other threads can use to wake this notifier.
+    pub fn waker(&self) -> std::io::Result<AsyncWaker> {
+        Ok(AsyncWaker(self.sender.try_clone()?))
+    }
+
+    /// The socket which becomes readable once woken
+    pub fn socket(&self) -> pg_sys::pgsocket {
+        self.receiver.as_raw_fd()
+    }
+
+    /// Consume any wakeups, so the socket isn't readable again until the next one.
+    pub fn drain(&self) {
+        let mut buf = [0u8; 64];
+        while matches!((&self.receiver).read(&mut buf), Ok(n) if n > 0) {}
+    }
+}
+
+/// Wakes an [`AsyncNotifier`] from any thread
+#[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+#[derive(Debug)]
+pub struct AsyncWaker(UnixStream);
+
+#[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+impl AsyncWaker {
+    /// Make the notifier's socket readable.
+    pub fn wake(&self) {
+        // if the socket's buffer is full, it's already readable
+        let _ = (&self.0).write(&[1]);
+    }
+}
+
+/// An `INSERT`, `UPDATE` or `DELETE` on a foreign table being planned, as passed to the FDW's
+/// `PlanForeignModify` callback
+///
+/// A remote server applies the modification itself, so any of the row's columns referred to by
+/// a `RETURNING` clause or by the `WITH CHECK OPTION`s of an updatable view over the table have to
+/// be sent back by the server.  [`ForeignModifyPlan::retrieved_attrs()`] says which.
+pub struct ForeignModifyPlan<'a> {
+    root: *mut pg_sys::PlannerInfo,
+    plan: *mut pg_sys::ModifyTable,
+    result_relation: pg_sys::Index,
+    subplan_index: usize,
+    _marker: PhantomData<&'a pg_sys::PlannerInfo>,
+}
+
+impl<'a> ForeignModifyPlan<'a> {
+    /// # Safety
+    ///
+    /// The arguments must be those the FDW's `PlanForeignModify` callback was called with.
+    pub unsafe fn new(
+        root: *mut pg_sys::PlannerInfo,
+        plan: *mut pg_sys::ModifyTable,
+        result_relation: pg_sys::Index,
+        subplan_index: i32,
+    ) -> Self {
+        Self {
+            root,
+            plan,
+            result_relation,
+            subplan_index: subplan_index as usize,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The planner's state for the query being planned
+    pub fn root(&self) -> *mut pg_sys::PlannerInfo {
+        self.root
+    }
+
+    /// The `ModifyTable` plan node doing the modification
+    pub fn plan(&self) -> *mut pg_sys::ModifyTable {
+        self.plan
+    }
+
+    /// The kind of modification, such as `pg_sys::CmdType_CMD_UPDATE`
+    pub fn operation(&self) -> pg_sys::CmdType {
+        unsafe { (*self.plan).operation }
+    }
+
+    /// The foreign table's range table index
+    pub fn result_relation(&self) -> pg_sys::Index {
+        self.result_relation
+    }
+
+    /// The OID of the foreign table
+    pub fn relid(&self) -> pg_sys::Oid {
+        unsafe { (**(*self.root).simple_rte_array.add(self.result_relation as usize)).relid }
+    }
+
+    /// The target list of the `RETURNING` clause, which is empty if there isn't one
+    pub fn returning_list(&self) -> PgList<pg_sys::TargetEntry> {
+        unsafe { PgList::from_pg(self.nth_list((*self.plan).returningLists)) }
+    }
+
+    /// The `WITH CHECK OPTION`s the modified rows must pass, from updatable views over the table
+    /// or its row-level security policies
+    pub fn with_check_options(&self) -> PgList<pg_sys::WithCheckOption> {
+        unsafe { PgList::from_pg(self.nth_list((*self.plan).withCheckOptionLists)) }
+    }
+
+    /// The numbers of the columns the remote server must send back for each modified row, in
+    /// ascending order: those the `RETURNING` clause or `WITH CHECK OPTION`s refer to, or all of
+    /// them if either refers to the whole row.
+    pub fn retrieved_attrs(&self) -> Vec<pg_sys::AttrNumber> {
+        unsafe {
+            let mut attrs = std::ptr::null_mut();
+            let returning = self.nth_list((*self.plan).returningLists);
+            pg_sys::pull_varattnos(returning.cast(), self.result_relation, &mut attrs);
+            for wco in self.with_check_options().iter_ptr() {
+                pg_sys::pull_varattnos((*wco).qual, self.result_relation, &mut attrs);
+            }
+
+            let mut retrieved = Vec::new();
+            let mut member = -1;
+            loop {
+                member = pg_sys::bms_next_member(attrs, member);
+                if member < 0 {
+                    break;
+                }
+                // members are offset so that system columns are positive too
+                let attno = member + pg_sys::FirstLowInvalidHeapAttributeNumber;
+                if attno == 0 {
+                    let relation = PgRelation::with_lock(self.relid(), pg_sys::NoLock as _);
+                    return relation
+                        .tuple_desc()
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, att)| !att.is_dropped())
+                        .map(|(i, _)| (i + 1) as pg_sys::AttrNumber)
+                        .collect();
+                } else if attno > 0 {
+                    retrieved.push(attno as pg_sys::AttrNumber);
+                }
+            }
+            retrieved
+        }
+    }
+
+    unsafe fn nth_list(&self, lists: *mut pg_sys::List) -> *mut pg_sys::List {
+        PgList::<pg_sys::List>::from_pg(lists)
+            .get_ptr(self.subplan_index)
+            .unwrap_or(std::ptr::null_mut())
+    }
+}
+
+/// The executor's state for an `INSERT`, `UPDATE` or `DELETE` on a foreign table
+///
+/// When the statement has a `RETURNING` clause or `WITH CHECK OPTION`s, the slot returned by the
+/// FDW's `ExecForeignInsert`, `ExecForeignUpdate` or `ExecForeignDelete` callback must hold the
+/// row as the remote server stored it, at least in the columns given by
+/// [`ForeignModifyPlan::retrieved_attrs()`].  Postgres then projects the `RETURNING` list and
+/// checks the options from it.  [`ReturnedRow`] fills such a slot.
+pub struct ForeignModify<'a> {
+    rinfo: *mut pg_sys::ResultRelInfo,
+    _marker: PhantomData<&'a pg_sys::ResultRelInfo>,
+}
+
+impl<'a> ForeignModify<'a> {
+    /// # Safety
+    ///
+    /// `rinfo` must be the `ResultRelInfo` the FDW's modify callbacks were called with.
+    pub unsafe fn new(rinfo: *mut pg_sys::ResultRelInfo) -> Self {
+        Self { rinfo, _marker: PhantomData }
+    }
+
+    /// The foreign table being modified, whose `ri_FdwState` holds whatever the FDW's
+    /// `BeginForeignModify` callback put there
+    pub fn rinfo(&self) -> *mut pg_sys::ResultRelInfo {
+        self.rinfo
+    }
+
+    /// Does the statement have a `RETURNING` clause?
+    pub fn has_returning(&self) -> bool {
+        unsafe { !(*self.rinfo).ri_projectReturning.is_null() }
+    }
+
+    /// Must the modified rows pass any `WITH CHECK OPTION`s?
+    pub fn has_check_options(&self) -> bool {
+        unsafe { !(*self.rinfo).ri_WithCheckOptions.is_null() }
+    }
+
+    /// Does Postgres need the modified row back from the remote server?
+    pub fn needs_returned_row(&self) -> bool {
+        self.has_returning() || self.has_check_options()
+    }
+}
+
+/// Fills a slot with a row sent back by a remote server, for Postgres to project the `RETURNING`
+/// clause and check `WITH CHECK OPTION`s from
+///
+/// Columns which aren't set are NULL.
+///
+/// ```rust,no_run
+/// use pgrx::fdw::ReturnedRow;
+/// use pgrx::prelude::*;
+/// use std::num::NonZeroUsize;
+///
+/// # unsafe fn exec_foreign_insert(slot: *mut pg_sys::TupleTableSlot) -> Result<(), pgrx::TryFromDatumError> {
+/// // in the FDW's `ExecForeignInsert` callback, once the remote server has assigned an id
+/// let id: i64 = 42;
+/// ReturnedRow::new(slot).set_by_index(NonZeroUsize::new(1).unwrap(), id)?.store();
+/// # Ok(())
+/// # }
+/// ```
+pub struct ReturnedRow<'a> {
+    slot: *mut pg_sys::TupleTableSlot,
+    tupdesc: PgTupleDesc<'a>,
+}
+
+impl<'a> ReturnedRow<'a> {
+    /// Start filling `slot`, clearing whatever it held.
+    ///
+    /// # Safety
+    ///
+    /// `slot` must be a valid slot, such as the one passed to the FDW's `ExecForeignInsert`,
+    /// `ExecForeignUpdate` or `ExecForeignDelete` callback.
+    pub unsafe fn new(slot: *mut pg_sys::TupleTableSlot) -> Self {
+        #[cfg(feature = "pg11")]
+        pg_sys::ExecClearTuple(slot);
+        #[cfg(not(feature = "pg11"))]
+        (*(*slot).tts_ops).clear.unwrap()(slot);
+
+        let tupdesc = PgTupleDesc::from_pg_unchecked((*slot).tts_tupleDescriptor);
+        std::slice::from_raw_parts_mut((*slot).tts_isnull, tupdesc.len()).fill(true);
+        Self { slot, tupdesc }
+    }
+
+    /// Set the value of the column named `attname`.
+    ///
+    /// ## Errors
+    ///
+    /// - [TryFromDatumError::NoSuchAttributeName] if the column does not exist
+    /// - [TryFromDatumError::IncompatibleTypes] if the Rust type of the `value` is not compatible
+    /// with the column's Postgres type
+    pub fn set_by_name<T: IntoDatum>(
+        self,
+        attname: &str,
+        value: T,
+    ) -> Result<Self, TryFromDatumError> {
+        let attno = attribute_number(&self.tupdesc, attname)?;
+        self.set_by_index(attno, value)
+    }
+
+    /// Set the value of column `attno`, numbered from 1 as in
+    /// [`ForeignModifyPlan::retrieved_attrs()`].
+    ///
+    /// ## Errors
+    ///
+    /// - [TryFromDatumError::NoSuchAttributeNumber] if the column does not exist
+    /// - [TryFromDatumError::IncompatibleTypes] if the Rust type of the `value` is not compatible
+    /// with the column's Postgres type
+    pub fn set_by_index<T: IntoDatum>(
+        self,
+        attno: NonZeroUsize,
+        value: T,
+    ) -> Result<Self, TryFromDatumError> {
+        let datum = attribute_datum(&self.tupdesc, attno, value)?;
+        let i = attno.get() - 1;
+        unsafe {
+            // SAFETY:  `attribute_datum()` checked that the slot has this attribute
+            *(*self.slot).tts_isnull.add(i) = datum.is_none();
+            *(*self.slot).tts_values.add(i) = datum.unwrap_or(0.into());
+        }
+        Ok(self)
+    }
+
+    /// Store the row in the slot, returning the slot for the FDW's callback to return.
+    pub fn store(self) -> *mut pg_sys::TupleTableSlot {
+        unsafe { pg_sys::ExecStoreVirtualTuple(self.slot) }
+    }
+}
+
+/// An FDW which can insert rows into its foreign tables in batches, rather than one at a time
+///
+/// Postgres only batches inserts which don't need each row back from the FDW as it's inserted,
+/// so [`set_foreign_batch_insert()`] uses a batch size of 1 for inserts with a `RETURNING` clause,
+/// `WITH CHECK OPTION`s or row-level `INSERT` triggers on the foreign table, whatever
+/// [`ForeignBatchInsert::batch_size()`] says.
+#[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+pub trait ForeignBatchInsert {
+    /// The most rows to insert into the foreign table of `rinfo` at once, often read from a
+    /// `batch_size` option of the table or its server.  `1` disables batching.
+    fn batch_size(rinfo: *mut pg_sys::ResultRelInfo) -> usize;
+
+    /// Insert a batch of rows, returning how many were inserted.
+    fn insert_batch(batch: InsertBatch<'_>) -> usize;
+}
+
+/// Install `T` as `routine`'s `GetForeignModifyBatchSize` and `ExecForeignBatchInsert`
+/// callbacks.
+///
+/// The FDW's `BeginForeignModify` or `BeginForeignInsert` callback is still called before the
+/// first batch, and `EndForeignModify` or `EndForeignInsert` after the last.
+#[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+pub fn set_foreign_batch_insert<T: ForeignBatchInsert>(routine: &mut pg_sys::FdwRoutine) {
+    routine.GetForeignModifyBatchSize = Some(get_foreign_modify_batch_size::<T>);
+    routine.ExecForeignBatchInsert = Some(exec_foreign_batch_insert::<T>);
+}
+
+#[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+#[pg_guard]
+unsafe extern "C" fn get_foreign_modify_batch_size<T: ForeignBatchInsert>(
+    rinfo: *mut pg_sys::ResultRelInfo,
+) -> i32 {
+    // Postgres would insert these rows one at a time anyway, but leaves it to the FDW to say so
+    let trigdesc = (*rinfo).ri_TrigDesc;
+    if !(*rinfo).ri_projectReturning.is_null()
+        || !(*rinfo).ri_WithCheckOptions.is_null()
+        || (!trigdesc.is_null()
+            && ((*trigdesc).trig_insert_before_row || (*trigdesc).trig_insert_after_row))
+    {
+        return 1;
+    }
+    T::batch_size(rinfo).clamp(1, i32::MAX as usize) as i32
+}
+
+#[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+#[pg_guard]
+unsafe extern "C" fn exec_foreign_batch_insert<T: ForeignBatchInsert>(
+    estate: *mut pg_sys::EState,
+    rinfo: *mut pg_sys::ResultRelInfo,
+    slots: *mut *mut pg_sys::TupleTableSlot,
+    plan_slots: *mut *mut pg_sys::TupleTableSlot,
+    num_slots: *mut i32,
+) -> *mut *mut pg_sys::TupleTableSlot {
+    let len = *num_slots as usize;
+    let batch = InsertBatch {
+        estate,
+        rinfo,
+        slots: std::slice::from_raw_parts(slots, len),
+        plan_slots: std::slice::from_raw_parts(plan_slots, len),
+    };
+    *num_slots = T::insert_batch(batch).min(len) as i32;
+    slots
+}
+
+/// A batch of rows to insert into a foreign table
+#[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+pub struct InsertBatch<'a> {
+    estate: *mut pg_sys::EState,
+    rinfo: *mut pg_sys::ResultRelInfo,
+    slots: &'a [*mut pg_sys::TupleTableSlot],
+    plan_slots: &'a [*mut pg_sys::TupleTableSlot],
+}
+
+#[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+impl<'a> InsertBatch<'a> {
+    /// The executor's state for the `INSERT`
+    pub fn estate(&self) -> *mut pg_sys::EState {
+        self.estate
+    }
+
+    /// The foreign table being inserted into, whose `ri_FdwState` holds whatever the FDW's
+    /// `BeginForeignModify` or `BeginForeignInsert` callback put there
+    pub fn rinfo(&self) -> *mut pg_sys::ResultRelInfo {
+        self.rinfo
+    }
+
+    /// The rows to insert, in the foreign table's row type
+    pub fn slots(&self) -> &'a [*mut pg_sys::TupleTableSlot] {
+        self.slots
+    }
+
+    /// The rows produced by the `INSERT`'s plan, which may have junk columns
+    pub fn plan_slots(&self) -> &'a [*mut pg_sys::TupleTableSlot] {
+        self.plan_slots
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+}
+
+/// The kind of object an FDW's options are attached to, known from the catalog OID its validator
+/// function is passed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OptionContext {
+    Wrapper,
+    Server,
+    UserMapping,
+    Table,
+    Column,
+}
+
+impl OptionContext {
+    /// The context of options stored in `catalog`, if it's one which holds FDW options
+    pub fn from_catalog(catalog: pg_sys::Oid) -> Option<Self> {
+        match catalog {
+            pg_sys::ForeignDataWrapperRelationId => Some(OptionContext::Wrapper),
+            pg_sys::ForeignServerRelationId => Some(OptionContext::Server),
+            pg_sys::UserMappingRelationId => Some(OptionContext::UserMapping),
+            pg_sys::ForeignTableRelationId => Some(OptionContext::Table),
+            pg_sys::AttributeRelationId => Some(OptionContext::Column),
+            _ => None,
+        }
+    }
+
+    /// The catalog options of this context are stored in
+    pub fn catalog(self) -> pg_sys::Oid {
+        match self {
+            OptionContext::Wrapper => pg_sys::ForeignDataWrapperRelationId,
+            OptionContext::Server => pg_sys::ForeignServerRelationId,
+            OptionContext::UserMapping => pg_sys::UserMappingRelationId,
+            OptionContext::Table => pg_sys::ForeignTableRelationId,
+            OptionContext::Column => pg_sys::AttributeRelationId,
+        }
+    }
+}
+
+/// A problem with the options given to a foreign data wrapper, server, user mapping, table or
+/// column
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum FdwOptionsError {
+    #[error("invalid option \"{name}\"")]
+    Unknown { name: String, valid: &'static [&'static str] },
+    #[error("option \"{0}\" is required")]
+    Missing(&'static str),
+    #[error("invalid value for option \"{name}\": {reason}")]
+    Invalid { name: &'static str, value: String, reason: String },
+}
+
+impl FdwOptionsError {
+    /// The SQL error code Postgres' own FDWs use for this problem
+    pub fn errcode(&self) -> PgSqlErrorCode {
+        match self {
+            FdwOptionsError::Unknown { .. } => PgSqlErrorCode::ERRCODE_FDW_INVALID_OPTION_NAME,
+            FdwOptionsError::Missing(_) => PgSqlErrorCode::ERRCODE_FDW_OPTION_NAME_NOT_FOUND,
+            FdwOptionsError::Invalid { .. } => PgSqlErrorCode::ERRCODE_FDW_INVALID_ATTRIBUTE_VALUE,
+        }
+    }
+
+    /// Raise this as an `ERROR`, listing the valid options if the option was unknown.
+    pub fn report(self) -> ! {
+        let mut report = ErrorReport::new(self.errcode(), self.to_string(), function_name!());
+        if let FdwOptionsError::Unknown { valid, .. } = self {
+            report = if valid.is_empty() {
+                report.set_hint("There are no valid options in this context.")
+            } else {
+                report.set_hint(format!("Valid options in this context are: {}", valid.join(", ")))
+            };
+        }
+        report.report(PgLogLevel::ERROR);
+        unreachable!()
+    }
+}
+
+/// A struct holding the options of a foreign data wrapper, server, user mapping, table or column
+///
+/// Usually derived with `#[derive(FdwOptions)]`, which maps each option onto the field of the same
+/// name.  Fields whose type is an `Option<T>` are optional, fields with a
+/// `#[fdw_option(default = "...")]` take that value if the option isn't given, and any others are
+/// required.
+///
+/// ```rust,no_run
+/// use pgrx::fdw::{FdwOptions, OptionContext};
+/// use pgrx::prelude::*;
+///
+/// #[derive(FdwOptionValue)]
+/// enum Mode {
+///     ReadOnly,
+///     ReadWrite,
+/// }
+///
+/// #[derive(FdwOptions)]
+/// struct ServerOptions {
+///     host: String,
+///     #[fdw_option(default = "5432")]
+///     port: u16,
+///     dbname: Option<String>,
+/// }
+///
+/// #[derive(FdwOptions)]
+/// struct TableOptions {
+///     #[fdw_option(name = "table_name")]
+///     name: Option<String>,
+///     #[fdw_option(default = "read_only")]
+///     mode: Mode,
+/// }
+///
+/// #[pg_extern]
+/// fn my_fdw_validator(options: Vec<String>, catalog: pg_sys::Oid) {
+///     pgrx::fdw::validate_options(&options, catalog, |context, options| match context {
+///         OptionContext::Server => ServerOptions::validate(options),
+///         OptionContext::Table => TableOptions::validate(options),
+///         _ => <()>::validate(options),
+///     })
+/// }
+///
+/// # fn begin_scan(relid: pg_sys::Oid) {
+/// // and later, when the options are needed
+/// let table = TableOptions::for_table(relid).unwrap_or_else(|e| e.report());
+/// # }
+/// ```
+pub trait FdwOptions: Sized {
+    /// The names of the options, listed in the error for an unknown option
+    const OPTIONS: &'static [&'static str];
+
+    /// Parse the options, given as `(name, value)` pairs.
+    fn from_options(options: &[(&str, &str)]) -> Result<Self, FdwOptionsError>;
+
+    /// Check that the options would parse, as an FDW's validator function needs to.
+    fn validate(options: &[(&str, &str)]) -> Result<(), FdwOptionsError> {
+        Self::from_options(options).map(|_| ())
+    }
+
+    /// Parse the options in a `List` of `DefElem`s, such as the `options` of a
+    /// `pg_sys::ForeignTable`.
+    ///
+    /// # Safety
+    ///
+    /// `options` must be null or a valid `List` of `DefElem`s.
+    unsafe fn from_def_elems(options: *mut pg_sys::List) -> Result<Self, FdwOptionsError> {
+        let options = PgList::<pg_sys::DefElem>::from_pg(options)
+            .iter_ptr()
+            .map(|def| {
+                let name = CStr::from_ptr((*def).defname).to_string_lossy().into_owned();
+                let value =
+                    CStr::from_ptr(pg_sys::defGetString(def)).to_string_lossy().into_owned();
+                (name, value)
+            })
+            .collect::<Vec<_>>();
+        let options =
+            options.iter().map(|(name, value)| (name.as_str(), value.as_str())).collect::<Vec<_>>();
+        Self::from_options(&options)
+    }
+
+    /// The options of the foreign data wrapper `fdwid`
+    fn for_wrapper(fdwid: pg_sys::Oid) -> Result<Self, FdwOptionsError> {
+        unsafe { Self::from_def_elems((*pg_sys::GetForeignDataWrapper(fdwid)).options) }
+    }
+
+    /// The options of the foreign server `serverid`
+    fn for_server(serverid: pg_sys::Oid) -> Result<Self, FdwOptionsError> {
+        unsafe { Self::from_def_elems((*pg_sys::GetForeignServer(serverid)).options) }
+    }
+
+    /// The options of `userid`'s mapping for the foreign server `serverid`, which may be the
+    /// `PUBLIC` mapping
+    fn for_user_mapping(
+        userid: pg_sys::Oid,
+        serverid: pg_sys::Oid,
+    ) -> Result<Self, FdwOptionsError> {
+        unsafe { Self::from_def_elems((*pg_sys::GetUserMapping(userid, serverid)).options) }
+    }
+
+    /// The options of the foreign table `relid`
+    fn for_table(relid: pg_sys::Oid) -> Result<Self, FdwOptionsError> {
+        unsafe { Self::from_def_elems((*pg_sys::GetForeignTable(relid)).options) }
+    }
+
+    /// The options of column `attnum` of the foreign table `relid`
+    fn for_column(relid: pg_sys::Oid, attnum: pg_sys::AttrNumber) -> Result<Self, FdwOptionsError> {
+        unsafe { Self::from_def_elems(pg_sys::GetForeignColumnOptions(relid, attnum)) }
+    }
+}
+
+/// No options at all, for the contexts in which an FDW accepts none
+impl FdwOptions for () {
+    const OPTIONS: &'static [&'static str] = &[];
+
+    fn from_options(options: &[(&str, &str)]) -> Result<Self, FdwOptionsError> {
+        match options.first() {
+            Some((name, _)) => {
+                Err(FdwOptionsError::Unknown { name: name.to_string(), valid: Self::OPTIONS })
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// The value of a single FDW option
+///
+/// Implemented for strings, `bool`, and numbers, and derived with `#[derive(FdwOptionValue)]` for
+/// enums whose variants have no fields.  A derived enum's values are its variants' names in
+/// `snake_case`, or the name given with `#[fdw_option(name = "...")]`.
+pub trait FdwOptionValue: Sized {
+    /// Parse `value`, describing what was expected if it's invalid.
+    fn parse_option(value: &str) -> Result<Self, String>;
+
+    /// Parse the `value` of the option `name`.
+    fn from_option(name: &'static str, value: &str) -> Result<Self, FdwOptionsError> {
+        Self::parse_option(value).map_err(|reason| FdwOptionsError::Invalid {
+            name,
+            value: value.to_string(),
+            reason,
+        })
+    }
+}
+
+impl FdwOptionValue for String {
+    fn parse_option(value: &str) -> Result<Self, String> {
+        Ok(value.to_string())
+    }
+}
+
+/// Accepts the same spellings as Postgres' own boolean options, such as `on` and `off`
+impl FdwOptionValue for bool {
+    fn parse_option(value: &str) -> Result<Self, String> {
+        match value.to_ascii_lowercase().as_str() {
+            "true" | "t" | "yes" | "y" | "on" | "1" => Ok(true),
+            "false" | "f" | "no" | "n" | "off" | "0" => Ok(false),
+            _ => Err(format!("\"{value}\" is not a boolean")),
+        }
+    }
+}
+
+macro_rules! impl_fdw_option_value_from_str {
+    ($($t:ty),*) => {
+        $(
+            impl FdwOptionValue for $t {
+                fn parse_option(value: &str) -> Result<Self, String> {
+                    value.trim().parse().map_err(|e| format!("\"{value}\": {e}"))
+                }
+            }
+        )*
+    };
+}
+
+impl_fdw_option_value_from_str!(i16, i32, i64, u16, u32, u64, usize, f32, f64);
+
+/// Validate an FDW's options, as its validator function is asked to.
+///
+/// `options` and `catalog` are the validator's arguments, the `"name=value"` options being set on
+/// an object and the catalog that object is in.  `validate` is given the options of every context
+/// except for those of unrecognized catalogs, which are accepted, and typically dispatches to
+/// [`FdwOptions::validate()`].  Any error is raised as an `ERROR`.
+pub fn validate_options<F>(options: &[String], catalog: pg_sys::Oid, validate: F)
+where
+    F: FnOnce(OptionContext, &[(&str, &str)]) -> Result<(), FdwOptionsError>,
+{
+    let Some(context) = OptionContext::from_catalog(catalog) else { return };
+    let options = options
+        .iter()
+        .map(|option| option.split_once('=').unwrap_or((option.as_str(), "")))
+        .collect::<Vec<_>>();
+    if let Err(e) = validate(context, &options) {
+        e.report()
+    }
+}
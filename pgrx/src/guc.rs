@@ -8,7 +8,11 @@
 //LICENSE
 //LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
 //! Provides a safe interface into Postgres' Configuration System (GUC)
-use crate::{pg_sys, PgMemoryContexts};
+use crate as pgrx; // for #[pg_guard] support from within ourself
+use crate::pg_sys::panic::{
+    abort_on_double_panic, panic_policy, set_abort_on_double_panic, set_panic_policy, PanicPolicy,
+};
+use crate::{pg_guard, pg_sys, PgMemoryContexts};
 use core::ffi::CStr;
 pub use pgrx_macros::PostgresGucEnum;
 use std::cell::Cell;
@@ -344,4 +348,94 @@ impl GucRegistry {
             );
         }
     }
+
+    /// Define an enum GUC, whose values are `error`, `fatal`, and `panic`, which sets the
+    /// [`PanicPolicy`] for Rust panics, as [`set_panic_policy`] does.  Its default is the policy
+    /// in effect when it's defined.
+    ///
+    /// Since a policy of `panic` lets any panic take down the cluster, the GUC is always
+    /// [`GucContext::Suset`].
+    pub fn define_panic_policy_guc(
+        name: &str,
+        short_description: &str,
+        long_description: &str,
+        flags: GucFlags,
+    ) {
+        #[pg_guard]
+        unsafe extern "C" fn assign(newval: i32, _extra: *mut std::os::raw::c_void) {
+            set_panic_policy(match newval {
+                1 => PanicPolicy::Fatal,
+                2 => PanicPolicy::Panic,
+                _ => PanicPolicy::Error,
+            });
+        }
+
+        unsafe {
+            let options =
+                PgMemoryContexts::TopMemoryContext.palloc0_slice::<pg_sys::config_enum_entry>(4);
+            for (option, (label, policy)) in options.iter_mut().zip([
+                ("error", PanicPolicy::Error),
+                ("fatal", PanicPolicy::Fatal),
+                ("panic", PanicPolicy::Panic),
+            ]) {
+                option.name = PgMemoryContexts::TopMemoryContext.pstrdup(label);
+                option.val = policy as i32;
+                option.hidden = false;
+            }
+
+            // each GUC needs its own storage, which lives as long as the GUC does
+            let value = PgMemoryContexts::TopMemoryContext.palloc0_struct::<i32>();
+            let boot_val = panic_policy() as i32;
+            *value = boot_val;
+            pg_sys::DefineCustomEnumVariable(
+                PgMemoryContexts::TopMemoryContext.pstrdup(name),
+                PgMemoryContexts::TopMemoryContext.pstrdup(short_description),
+                PgMemoryContexts::TopMemoryContext.pstrdup(long_description),
+                value,
+                boot_val,
+                options.as_ptr(),
+                GucContext::Suset as isize as u32,
+                flags.bits(),
+                None,
+                Some(assign),
+                None,
+            );
+        }
+    }
+
+    /// Define a bool GUC which aborts the process if a Rust panic happens while unwinding from
+    /// another, as [`set_abort_on_double_panic`] does.  Its default is the setting in effect when
+    /// it's defined.
+    ///
+    /// Since aborting takes down the cluster, the GUC is always [`GucContext::Suset`].
+    pub fn define_abort_on_double_panic_guc(
+        name: &str,
+        short_description: &str,
+        long_description: &str,
+        flags: GucFlags,
+    ) {
+        #[pg_guard]
+        unsafe extern "C" fn assign(newval: bool, _extra: *mut std::os::raw::c_void) {
+            set_abort_on_double_panic(newval);
+        }
+
+        unsafe {
+            // each GUC needs its own storage, which lives as long as the GUC does
+            let value = PgMemoryContexts::TopMemoryContext.palloc0_struct::<bool>();
+            let boot_val = abort_on_double_panic();
+            *value = boot_val;
+            pg_sys::DefineCustomBoolVariable(
+                PgMemoryContexts::TopMemoryContext.pstrdup(name),
+                PgMemoryContexts::TopMemoryContext.pstrdup(short_description),
+                PgMemoryContexts::TopMemoryContext.pstrdup(long_description),
+                value,
+                boot_val,
+                GucContext::Suset as isize as u32,
+                flags.bits(),
+                None,
+                Some(assign),
+                None,
+            );
+        }
+    }
 }
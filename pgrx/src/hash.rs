@@ -0,0 +1,157 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Checksums and hash functions which compute the same values as Postgres' own
+//!
+//! These are for code which has to agree with Postgres on a value: verifying a page's checksum,
+//! checking a WAL record's or control file's CRC, or routing rows to hash partitions as Postgres
+//! would.
+use crate::{pg_sys, PgMemoryContexts};
+
+/// The seed Postgres' hash partitioning uses with each partition key column's extended hash
+/// function.
+pub const HASH_PARTITION_SEED: u64 = 0x7A5B22367996DCFD;
+
+/// A running CRC-32C, as Postgres' `INIT_CRC32C()`/`COMP_CRC32C()`/`FIN_CRC32C()` compute it
+/// for WAL records, the control file, and two-phase state files
+///
+/// ```rust,no_run
+/// use pgrx::hash::Crc32c;
+///
+/// let mut crc = Crc32c::new();
+/// crc.update(b"1234");
+/// crc.update(b"56789");
+/// assert_eq!(crc.finish(), 0xe3069283);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Crc32c(u32);
+
+impl Crc32c {
+    pub fn new() -> Self {
+        Crc32c(0xFFFFFFFF)
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        for byte in data {
+            self.0 = CRC32C_TABLE[((self.0 ^ *byte as u32) & 0xff) as usize] ^ (self.0 >> 8);
+        }
+    }
+
+    pub fn finish(self) -> u32 {
+        self.0 ^ 0xFFFFFFFF
+    }
+}
+
+impl Default for Crc32c {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The CRC-32C of `data`
+pub fn crc32c(data: &[u8]) -> u32 {
+    let mut crc = Crc32c::new();
+    crc.update(data);
+    crc.finish()
+}
+
+/// The lookup table for the reflected Castagnoli polynomial, 0x82F63B78
+const CRC32C_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0x82F63B78 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// The checksum of a data page, as Postgres stores in its `pd_checksum` when data checksums are
+/// enabled.
+///
+/// `blkno` is the page's block number within its relation fork, which is mixed into the
+/// checksum.  The page's own `pd_checksum` is ignored.
+///
+/// # Panics
+///
+/// If `page` isn't `BLCKSZ` bytes long.
+pub fn page_checksum(page: &[u8], blkno: pg_sys::BlockNumber) -> u16 {
+    assert_eq!(page.len(), pg_sys::BLCKSZ as usize, "a page must be BLCKSZ bytes long");
+    unsafe {
+        // pg_checksum_page() needs an aligned page, which it briefly modifies, so use a copy
+        let copy = PgMemoryContexts::CurrentMemoryContext
+            .copy_ptr_into(page.as_ptr() as *mut u8, page.len());
+        let checksum = pg_sys::pg_checksum_page(copy.cast(), blkno);
+        pg_sys::pfree(copy.cast());
+        checksum
+    }
+}
+
+/// Hash `bytes` as Postgres' `hash_any()` does, which is how `hashtext()` hashes a string with
+/// a deterministic collation, and how many extensions' hash opclasses hash their values.
+pub fn hash_bytes(bytes: &[u8]) -> u32 {
+    let len = i32::try_from(bytes.len()).expect("too many bytes to hash");
+    unsafe {
+        #[cfg(any(feature = "pg11", feature = "pg12"))]
+        {
+            pg_sys::hash_any(bytes.as_ptr(), len).value() as u32
+        }
+        #[cfg(not(any(feature = "pg11", feature = "pg12")))]
+        {
+            pg_sys::hash_bytes(bytes.as_ptr(), len)
+        }
+    }
+}
+
+/// Hash `bytes` with `seed`, as Postgres' `hash_any_extended()` does for the extended hash
+/// functions used by hash partitioning
+pub fn hash_bytes_extended(bytes: &[u8], seed: u64) -> u64 {
+    let len = i32::try_from(bytes.len()).expect("too many bytes to hash");
+    unsafe {
+        #[cfg(any(feature = "pg11", feature = "pg12"))]
+        {
+            pg_sys::hash_any_extended(bytes.as_ptr(), len, seed).value() as u64
+        }
+        #[cfg(not(any(feature = "pg11", feature = "pg12")))]
+        {
+            pg_sys::hash_bytes_extended(bytes.as_ptr(), len, seed)
+        }
+    }
+}
+
+/// Hash a `u32` as Postgres' `hash_uint32()` does, which is how `hashint4()` hashes an `int4`
+pub fn hash_uint32(value: u32) -> u32 {
+    unsafe {
+        #[cfg(any(feature = "pg11", feature = "pg12"))]
+        {
+            pg_sys::hash_uint32(value).value() as u32
+        }
+        #[cfg(not(any(feature = "pg11", feature = "pg12")))]
+        {
+            pg_sys::hash_bytes_uint32(value)
+        }
+    }
+}
+
+/// Combine two 32-bit hashes, as Postgres' `hash_combine()` does
+pub fn hash_combine(a: u32, b: u32) -> u32 {
+    a ^ b.wrapping_add(0x9e3779b9).wrapping_add(a << 6).wrapping_add(a >> 2)
+}
+
+/// Combine two 64-bit hashes, as Postgres' `hash_combine64()` does to hash a multi-column
+/// partition key
+pub fn hash_combine64(a: u64, b: u64) -> u64 {
+    a ^ b.wrapping_add(0x49a0f4dd15e5a8e3).wrapping_add(a << 54).wrapping_add(a >> 7)
+}
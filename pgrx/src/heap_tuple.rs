@@ -11,18 +11,21 @@
 //!
 //! [`PgHeapTuple`]s also describe composite types as defined by [`pgrx::composite_type!()`][crate::composite_type].
 use crate::datum::lookup_type_name;
+use crate::iter::SetOfIterator;
 use crate::pg_sys::{Datum, Oid};
 use crate::{
     heap_getattr_raw, pg_sys, trigger_fired_by_delete, trigger_fired_by_insert,
     trigger_fired_by_update, trigger_fired_for_statement, AllocatedByPostgres, AllocatedByRust,
-    FromDatum, IntoDatum, PgBox, PgMemoryContexts, PgTupleDesc, TriggerTuple, TryFromDatumError,
-    WhoAllocated,
+    Array, FromDatum, IntoDatum, PgBox, PgMemoryContexts, PgTupleDesc, TriggerTuple,
+    TryFromDatumError, WhoAllocated,
 };
 use pgrx_pg_sys::errcodes::PgSqlErrorCode;
+use pgrx_pg_sys::panic::CaughtError;
 use pgrx_pg_sys::PgTryBuilder;
 use pgrx_sql_entity_graph::metadata::{
     ArgumentError, Returns, ReturnsError, SqlMapping, SqlTranslatable,
 };
+use std::ffi::CString;
 use std::num::NonZeroUsize;
 
 /// Describes errors that can occur when trying to create a new [PgHeapTuple].
@@ -39,6 +42,15 @@ pub enum PgHeapTupleError {
 
     #[error("Oid `{0}` is not a composite type")]
     NotACompositeType(pg_sys::Oid),
+
+    #[error("The caller did not specify the columns of the record to return")]
+    NoCallResultType,
+
+    #[error("Attribute `{0}` is NULL, but its Rust field is not an `Option`")]
+    NullAttribute(String),
+
+    #[error(transparent)]
+    Attribute(#[from] TryFromDatumError),
 }
 
 /// A [`PgHeapTuple`] is a lightweight wrapper around Postgres' [`pg_sys::HeapTuple`] object and a [`PgTupleDesc`].
@@ -251,6 +263,29 @@ impl<'a> PgHeapTuple<'a, AllocatedByRust> {
         .execute()
     }
 
+    /// Create a new, all-`NULL`, [PgHeapTuple] shaped like the row the caller of the function
+    /// being called by `fcinfo` expects back.
+    ///
+    /// This is how a function declared as returning `pgrx::composite_type!("record")` builds its
+    /// result when the caller supplies a column definition list, as in
+    /// `SELECT * FROM my_func() AS t(a int, b text)`.  See [`PgTupleDesc::for_call_result`] to
+    /// inspect the requested columns.
+    ///
+    /// ## Errors
+    /// - [PgHeapTupleError::NoCallResultType] if the caller didn't say what columns it expects
+    ///
+    /// # Safety
+    ///
+    /// This function is unsafe as we cannot validate that the provided `fcinfo` is valid
+    pub unsafe fn new_for_call_result(
+        fcinfo: pg_sys::FunctionCallInfo,
+    ) -> Result<PgHeapTuple<'a, AllocatedByRust>, PgHeapTupleError> {
+        let tupdesc =
+            PgTupleDesc::for_call_result(fcinfo).ok_or(PgHeapTupleError::NoCallResultType)?;
+        let natts = tupdesc.len();
+        Self::from_datums(tupdesc, std::iter::repeat(None).take(natts))
+    }
+
     /// Create a new [PgHeapTuple] from a [PgTupleDesc] from an iterator of Datums.
     ///
     /// ## Errors
@@ -348,31 +383,13 @@ impl<'a> PgHeapTuple<'a, AllocatedByRust> {
         attno: NonZeroUsize,
         value: T,
     ) -> Result<(), TryFromDatumError> {
+        let datum = attribute_datum(&self.tupdesc, attno, value)?;
         unsafe {
-            match self.get_attribute_by_index(attno) {
-                None => return Err(TryFromDatumError::NoSuchAttributeNumber(attno)),
-                Some(att) => {
-                    let type_oid = T::type_oid();
-                    let composite_type_oid = value.composite_type_oid();
-                    let is_compatible_composite_types =
-                        type_oid == pg_sys::RECORDOID && composite_type_oid == Some(att.atttypid);
-                    if !is_compatible_composite_types && !T::is_compatible_with(att.atttypid) {
-                        return Err(TryFromDatumError::IncompatibleTypes {
-                            rust_type: std::any::type_name::<T>(),
-                            rust_oid: att.atttypid,
-                            datum_type: lookup_type_name(type_oid),
-                            datum_oid: type_oid,
-                        });
-                    }
-                }
-            }
-
             let mut datums =
                 (0..self.tupdesc.len()).map(|i| pg_sys::Datum::from(i)).collect::<Vec<_>>();
             let mut nulls = (0..self.tupdesc.len()).map(|_| false).collect::<Vec<_>>();
             let mut do_replace = (0..self.tupdesc.len()).map(|_| false).collect::<Vec<_>>();
 
-            let datum = value.into_datum();
             let attno = attno.get() - 1;
 
             nulls[attno] = datum.is_none();
@@ -422,6 +439,18 @@ impl<'a, AllocatedBy: WhoAllocated> IntoDatum for PgHeapTuple<'a, AllocatedBy> {
 }
 
 impl<'a, AllocatedBy: WhoAllocated> PgHeapTuple<'a, AllocatedBy> {
+    /// Consume this [`PgHeapTuple`] to replace any number of its attributes at once.  See
+    /// [`HeapTupleModifier`].
+    pub fn modify(self) -> HeapTupleModifier<'a, AllocatedBy> {
+        let natts = self.tupdesc.len();
+        HeapTupleModifier {
+            tuple: self,
+            datums: vec![pg_sys::Datum::from(0); natts],
+            nulls: vec![false; natts],
+            replace: vec![false; natts],
+        }
+    }
+
     /// Consume this [`PgHeapTuple`] and return a composite Datum representation, containing the tuple
     /// data and the corresponding tuple descriptor information.
     pub fn into_composite_datum(self) -> Option<pg_sys::Datum> {
@@ -512,6 +541,105 @@ impl<'a, AllocatedBy: WhoAllocated> PgHeapTuple<'a, AllocatedBy> {
         Err(TryFromDatumError::NoSuchAttributeName(attname.to_owned()))
     }
 
+    /// Retrieve the value of the specified attribute, which must be of a composite type, by name.
+    ///
+    /// The nested tuple is copied out of this one, so it remains valid if this one is changed.
+    ///
+    /// ## Errors
+    /// - return [`TryFromDatumError::NoSuchAttributeName`] if the attribute does not exist
+    /// - return [`TryFromDatumError::IncompatibleTypes`] if the attribute is not a composite type
+    pub fn get_composite_by_name(
+        &self,
+        attname: &str,
+    ) -> Result<Option<PgHeapTuple<'a, AllocatedByRust>>, TryFromDatumError> {
+        self.get_composite_by_index(self.attno_by_name(attname)?)
+    }
+
+    /// Retrieve the value of the specified attribute, which must be of a composite type, by index.
+    ///
+    /// Attribute numbers start at 1, not 0.  The nested tuple is copied out of this one, so it
+    /// remains valid if this one is changed.
+    ///
+    /// ## Errors
+    /// - return [`TryFromDatumError::NoSuchAttributeNumber`] if the attribute does not exist
+    /// - return [`TryFromDatumError::IncompatibleTypes`] if the attribute is not a composite type
+    pub fn get_composite_by_index(
+        &self,
+        attno: NonZeroUsize,
+    ) -> Result<Option<PgHeapTuple<'a, AllocatedByRust>>, TryFromDatumError> {
+        let att = self
+            .tupdesc
+            .get(attno.get() - 1)
+            .ok_or(TryFromDatumError::NoSuchAttributeNumber(attno))?;
+        if !unsafe { pg_sys::type_is_rowtype(att.atttypid) } {
+            return Err(incompatible_attribute::<PgHeapTuple<AllocatedByRust>>(
+                att,
+                pg_sys::RECORDOID,
+            ));
+        }
+        unsafe {
+            // SAFETY:  the attribute is a composite type, so its datum is a composite datum
+            Ok(heap_getattr_raw(self.tuple.as_ptr(), attno, self.tupdesc.as_ptr()).map(|datum| {
+                let nested = PgHeapTuple::from_composite_datum(datum);
+                PgHeapTuple::from_composite_datum(nested.into_composite_datum().unwrap())
+            }))
+        }
+    }
+
+    /// Retrieve the value of the specified attribute, which must be an array of a composite type,
+    /// by name.
+    ///
+    /// ## Errors
+    /// - return [`TryFromDatumError::NoSuchAttributeName`] if the attribute does not exist
+    /// - return [`TryFromDatumError::IncompatibleTypes`] if the attribute is not an array of a
+    /// composite type
+    pub fn get_composite_array_by_name(
+        &self,
+        attname: &str,
+    ) -> Result<Option<Array<'_, PgHeapTuple<'a, AllocatedByRust>>>, TryFromDatumError> {
+        self.get_composite_array_by_index(self.attno_by_name(attname)?)
+    }
+
+    /// Retrieve the value of the specified attribute, which must be an array of a composite type,
+    /// by index.
+    ///
+    /// Attribute numbers start at 1, not 0.
+    ///
+    /// ## Errors
+    /// - return [`TryFromDatumError::NoSuchAttributeNumber`] if the attribute does not exist
+    /// - return [`TryFromDatumError::IncompatibleTypes`] if the attribute is not an array of a
+    /// composite type
+    pub fn get_composite_array_by_index(
+        &self,
+        attno: NonZeroUsize,
+    ) -> Result<Option<Array<'_, PgHeapTuple<'a, AllocatedByRust>>>, TryFromDatumError> {
+        let att = self
+            .tupdesc
+            .get(attno.get() - 1)
+            .ok_or(TryFromDatumError::NoSuchAttributeNumber(attno))?;
+        let element_oid = unsafe { pg_sys::get_element_type(att.atttypid) };
+        if element_oid == pg_sys::InvalidOid || !unsafe { pg_sys::type_is_rowtype(element_oid) } {
+            return Err(incompatible_attribute::<Array<PgHeapTuple<AllocatedByRust>>>(
+                att,
+                pg_sys::RECORDARRAYOID,
+            ));
+        }
+        unsafe {
+            // SAFETY:  the attribute is an array of a composite type, and the array borrows from
+            // our tuple for no longer than we're borrowed
+            Ok(heap_getattr_raw(self.tuple.as_ptr(), attno, self.tupdesc.as_ptr())
+                .and_then(|datum| Array::from_polymorphic_datum(datum, false, att.atttypid)))
+        }
+    }
+
+    fn attno_by_name(&self, attname: &str) -> Result<NonZeroUsize, TryFromDatumError> {
+        self.tupdesc
+            .iter()
+            .position(|att| att.name() == attname)
+            .map(|i| NonZeroUsize::new(i + 1).unwrap())
+            .ok_or_else(|| TryFromDatumError::NoSuchAttributeName(attname.to_owned()))
+    }
+
     /// Retrieve the value of the specified attribute, by index.
     ///
     /// Attribute numbers start at 1, not 0.
@@ -548,6 +676,480 @@ impl<'a, AllocatedBy: WhoAllocated> PgHeapTuple<'a, AllocatedBy> {
     }
 }
 
+/// Builds a new [`PgHeapTuple`] shaped by a [`PgTupleDesc`], one attribute at a time.
+///
+/// Attributes that are never set are `NULL`.  All the values are formed into a tuple with a single
+/// call to [`pg_sys::heap_form_tuple`] when [`HeapTupleBuilder::build`] is called.
+///
+/// ```rust,no_run
+/// use pgrx::heap_tuple::HeapTupleBuilder;
+/// use pgrx::PgTupleDesc;
+/// # fn build_dog() -> Result<(), pgrx::TryFromDatumError> {
+/// let tupdesc = PgTupleDesc::for_composite_type("dog").unwrap();
+/// let dog = HeapTupleBuilder::new(tupdesc)
+///     .set_by_name("name", "Brandy")?
+///     .set_by_name("age", 42)?
+///     .build();
+/// assert_eq!(dog.get_by_name("age")?, Some(42i32));
+/// # Ok(())
+/// # }
+/// ```
+pub struct HeapTupleBuilder<'a> {
+    tupdesc: PgTupleDesc<'a>,
+    datums: Vec<pg_sys::Datum>,
+    nulls: Vec<bool>,
+}
+
+impl<'a> HeapTupleBuilder<'a> {
+    /// Start building an all-`NULL` tuple described by `tupdesc`
+    pub fn new(tupdesc: PgTupleDesc<'a>) -> Self {
+        let natts = tupdesc.len();
+        Self { tupdesc, datums: vec![pg_sys::Datum::from(0); natts], nulls: vec![true; natts] }
+    }
+
+    /// Given the name for an attribute, set its value.
+    ///
+    /// Attribute names are case sensitive.
+    ///
+    /// ## Errors
+    ///
+    /// - return [TryFromDatumError::NoSuchAttributeName] if the attribute does not exist
+    /// - return [TryFromDatumError::IncompatibleTypes] if the Rust type of the `value` is not
+    /// compatible with the attribute's Postgres type
+    pub fn set_by_name<T: IntoDatum>(
+        self,
+        attname: &str,
+        value: T,
+    ) -> Result<Self, TryFromDatumError> {
+        let attno = attribute_number(&self.tupdesc, attname)?;
+        self.set_by_index(attno, value)
+    }
+
+    /// Given the index for an attribute, set its value.
+    ///
+    /// Attribute numbers start at 1, not 0.
+    ///
+    /// ## Errors
+    /// - return [TryFromDatumError::NoSuchAttributeNumber] if the attribute does not exist
+    /// - return [TryFromDatumError::IncompatibleTypes] if the Rust type of the `value` is not
+    /// compatible with the attribute's Postgres type
+    pub fn set_by_index<T: IntoDatum>(
+        mut self,
+        attno: NonZeroUsize,
+        value: T,
+    ) -> Result<Self, TryFromDatumError> {
+        let datum = attribute_datum(&self.tupdesc, attno, value)?;
+        let i = attno.get() - 1;
+        self.nulls[i] = datum.is_none();
+        self.datums[i] = datum.unwrap_or(0.into());
+        Ok(self)
+    }
+
+    /// Form the tuple
+    pub fn build(mut self) -> PgHeapTuple<'a, AllocatedByRust> {
+        unsafe {
+            // SAFETY:  every Datum was type-checked against the attribute it's for, and the
+            // arrays have exactly as many elements as the tuple descriptor has attributes
+            let formed_tuple = pg_sys::heap_form_tuple(
+                self.tupdesc.as_ptr(),
+                self.datums.as_mut_ptr(),
+                self.nulls.as_mut_ptr(),
+            );
+
+            PgHeapTuple {
+                tuple: PgBox::<pg_sys::HeapTupleData, AllocatedByRust>::from_rust(formed_tuple),
+                tupdesc: self.tupdesc,
+            }
+        }
+    }
+}
+
+/// Replaces any number of attributes of an existing [`PgHeapTuple`], creating a new tuple with a
+/// single call to [`pg_sys::heap_modify_tuple`].
+///
+/// Created by [`PgHeapTuple::modify`].  This is typically how a `BEFORE` trigger changes the row
+/// being written:
+///
+/// ```rust,no_run
+/// use pgrx::prelude::*;
+/// #[pg_trigger]
+/// fn stamp<'a>(
+///     trigger: &'a PgTrigger<'a>,
+/// ) -> Result<Option<PgHeapTuple<'a, AllocatedByRust>>, pgrx::TryFromDatumError> {
+///     let Some(new) = trigger.new() else { return Ok(None) };
+///     let new = new
+///         .modify()
+///         .set_by_name("updated_by", "pgrx")?
+///         .set_by_name("revision", 1i64)?
+///         .build();
+///     Ok(Some(new))
+/// }
+/// ```
+pub struct HeapTupleModifier<'a, AllocatedBy: WhoAllocated> {
+    tuple: PgHeapTuple<'a, AllocatedBy>,
+    datums: Vec<pg_sys::Datum>,
+    nulls: Vec<bool>,
+    replace: Vec<bool>,
+}
+
+impl<'a, AllocatedBy: WhoAllocated> HeapTupleModifier<'a, AllocatedBy> {
+    /// Given the name for an attribute, replace its value.
+    ///
+    /// Attribute names are case sensitive.
+    ///
+    /// ## Errors
+    ///
+    /// - return [TryFromDatumError::NoSuchAttributeName] if the attribute does not exist
+    /// - return [TryFromDatumError::IncompatibleTypes] if the Rust type of the `value` is not
+    /// compatible with the attribute's Postgres type
+    pub fn set_by_name<T: IntoDatum>(
+        self,
+        attname: &str,
+        value: T,
+    ) -> Result<Self, TryFromDatumError> {
+        let attno = attribute_number(&self.tuple.tupdesc, attname)?;
+        self.set_by_index(attno, value)
+    }
+
+    /// Given the index for an attribute, replace its value.
+    ///
+    /// Attribute numbers start at 1, not 0.
+    ///
+    /// ## Errors
+    /// - return [TryFromDatumError::NoSuchAttributeNumber] if the attribute does not exist
+    /// - return [TryFromDatumError::IncompatibleTypes] if the Rust type of the `value` is not
+    /// compatible with the attribute's Postgres type
+    pub fn set_by_index<T: IntoDatum>(
+        mut self,
+        attno: NonZeroUsize,
+        value: T,
+    ) -> Result<Self, TryFromDatumError> {
+        let datum = attribute_datum(&self.tuple.tupdesc, attno, value)?;
+        let i = attno.get() - 1;
+        self.nulls[i] = datum.is_none();
+        self.datums[i] = datum.unwrap_or(0.into());
+        self.replace[i] = true;
+        Ok(self)
+    }
+
+    /// Create the modified tuple.  Attributes that weren't replaced keep their original values.
+    pub fn build(mut self) -> PgHeapTuple<'a, AllocatedByRust> {
+        unsafe {
+            // SAFETY:  every replacement Datum was type-checked against the attribute it's for,
+            // and the arrays have exactly as many elements as the tuple descriptor has attributes
+            let modified_tuple = pg_sys::heap_modify_tuple(
+                self.tuple.tuple.as_ptr(),
+                self.tuple.tupdesc.as_ptr(),
+                self.datums.as_mut_ptr(),
+                self.nulls.as_mut_ptr(),
+                self.replace.as_mut_ptr(),
+            );
+
+            PgHeapTuple {
+                tuple: PgBox::<pg_sys::HeapTupleData, AllocatedByRust>::from_rust(modified_tuple),
+                tupdesc: self.tuple.tupdesc,
+            }
+        }
+    }
+}
+
+/// The rows of a function returning `SETOF record`, shaped by the column definition list its
+/// caller supplies, as in `SELECT * FROM pivot() AS t(name text, q1 int, q2 int)`.
+///
+/// The function learns what columns were asked for from [`RecordSet::columns()`], pushes rows
+/// built with [`RecordSet::row()`], or from text with [`RecordSet::push_text()`] as `crosstab()`
+/// does, and returns [`RecordSet::into_set_of()`].
+///
+/// ```rust,no_run
+/// use pgrx::prelude::*;
+///
+/// /// Numbers each requested column, in each of `rows` rows
+/// #[pg_extern]
+/// fn numbered(
+///     rows: i32,
+///     fcinfo: pg_sys::FunctionCallInfo,
+/// ) -> Result<SetOfIterator<'static, pgrx::composite_type!("record")>, PgHeapTupleError> {
+///     let mut set = unsafe { RecordSet::for_call_result(fcinfo) }?;
+///     let ncolumns = set.columns().len();
+///     for row in 0..rows as usize {
+///         set.push_text((0..ncolumns).map(|column| Some((row * ncolumns + column).to_string())))?;
+///     }
+///     Ok(set.into_set_of())
+/// }
+/// ```
+pub struct RecordSet<'a> {
+    tupdesc: PgTupleDesc<'a>,
+    attinmeta: *mut pg_sys::AttInMetadata,
+    rows: Vec<PgHeapTuple<'a, AllocatedByRust>>,
+}
+
+impl<'a> RecordSet<'a> {
+    /// An empty set of rows shaped like the ones the caller of the function being called by
+    /// `fcinfo` expects back.
+    ///
+    /// The set's tuple descriptor is allocated in the `CurrentMemoryContext`, which for a
+    /// set-returning function lasts for all of its calls, and is freed along with it.
+    ///
+    /// ## Errors
+    /// - [PgHeapTupleError::NoCallResultType] if the caller didn't say what columns it expects
+    ///
+    /// # Safety
+    ///
+    /// This function is unsafe as we cannot validate that the provided `fcinfo` is valid
+    pub unsafe fn for_call_result(
+        fcinfo: pg_sys::FunctionCallInfo,
+    ) -> Result<RecordSet<'a>, PgHeapTupleError> {
+        let tupdesc =
+            PgTupleDesc::for_call_result(fcinfo).ok_or(PgHeapTupleError::NoCallResultType)?;
+        // every row shares this descriptor, so leave it to its memory context to free
+        let ptr = tupdesc.as_ptr();
+        std::mem::forget(tupdesc);
+        Ok(RecordSet {
+            tupdesc: PgTupleDesc::from_pg_unchecked(ptr),
+            attinmeta: std::ptr::null_mut(),
+            rows: Vec::new(),
+        })
+    }
+
+    /// The columns the caller asked for
+    pub fn columns(&self) -> &PgTupleDesc<'a> {
+        &self.tupdesc
+    }
+
+    /// Start building an all-`NULL` row of this set, to [`push()`](RecordSet::push) once built.
+    pub fn row(&self) -> HeapTupleBuilder<'a> {
+        HeapTupleBuilder::new(self.shared_tupdesc())
+    }
+
+    /// Add a row to the set.
+    ///
+    /// ## Errors
+    /// - [PgHeapTupleError::IncorrectAttributeCount] if the row has a different number of
+    /// attributes than the caller asked for
+    pub fn push(&mut self, row: PgHeapTuple<'a, AllocatedByRust>) -> Result<(), PgHeapTupleError> {
+        if row.len() != self.tupdesc.len() {
+            return Err(PgHeapTupleError::IncorrectAttributeCount(row.len(), self.tupdesc.len()));
+        }
+        self.rows.push(row);
+        Ok(())
+    }
+
+    /// Add a row to the set from the text representation of each of its values, which are
+    /// converted by the input functions of their columns' types.  `None`s are NULL.
+    ///
+    /// This suits functions which don't know their columns' types until they're called, such as
+    /// pivots of text produced by a query.  A value which isn't valid for its column's type raises
+    /// an ERROR.
+    ///
+    /// ## Errors
+    /// - [PgHeapTupleError::IncorrectAttributeCount] if there's a different number of values than
+    /// columns
+    ///
+    /// ## Panics
+    ///
+    /// If a value contains a NUL byte.
+    pub fn push_text<I, S>(&mut self, values: I) -> Result<(), PgHeapTupleError>
+    where
+        I: IntoIterator<Item = Option<S>>,
+        S: AsRef<str>,
+    {
+        let values = values
+            .into_iter()
+            .map(|value| {
+                value.map(|value| {
+                    CString::new(value.as_ref()).expect("value should not contain a NUL byte")
+                })
+            })
+            .collect::<Vec<_>>();
+        if values.len() != self.tupdesc.len() {
+            return Err(PgHeapTupleError::IncorrectAttributeCount(
+                values.len(),
+                self.tupdesc.len(),
+            ));
+        }
+
+        unsafe {
+            if self.attinmeta.is_null() {
+                self.attinmeta = pg_sys::TupleDescGetAttInMetadata(self.tupdesc.as_ptr());
+            }
+            let mut cstrs = values
+                .iter()
+                .map(|value| value.as_ref().map_or(std::ptr::null_mut(), |v| v.as_ptr().cast_mut()))
+                .collect::<Vec<_>>();
+            // SAFETY:  there's a value, or a null pointer for NULL, for every attribute
+            let formed_tuple = pg_sys::BuildTupleFromCStrings(self.attinmeta, cstrs.as_mut_ptr());
+            self.rows.push(PgHeapTuple {
+                tuple: PgBox::<pg_sys::HeapTupleData, AllocatedByRust>::from_rust(formed_tuple),
+                tupdesc: self.shared_tupdesc(),
+            });
+        }
+        Ok(())
+    }
+
+    /// How many rows have been pushed?
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// The rows, to return from a `#[pg_extern]` function
+    pub fn into_set_of(self) -> SetOfIterator<'a, PgHeapTuple<'a, AllocatedByRust>> {
+        SetOfIterator::new(self.rows)
+    }
+
+    fn shared_tupdesc(&self) -> PgTupleDesc<'a> {
+        // SAFETY:  the descriptor lives as long as its memory context, see `for_call_result()`
+        unsafe { PgTupleDesc::from_pg_unchecked(self.tupdesc.as_ptr()) }
+    }
+}
+
+/// A Rust struct which maps onto an existing composite type, attribute by attribute.
+///
+/// Usually derived with `#[derive(PostgresComposite)]`, which also implements [`FromDatum`],
+/// [`IntoDatum`] and `SqlTranslatable`, so the struct can be used as the argument or return type
+/// of a `#[pg_extern]` function, including in arrays and `SetOfIterator`s, or with [`Spi`].
+///
+/// ```rust,no_run
+/// use pgrx::prelude::*;
+///
+/// extension_sql!(
+///     "CREATE TYPE Dog AS (name text, scritches int);",
+///     name = "create_dog",
+///     creates = [Type(Dog)]
+/// );
+///
+/// #[derive(PostgresComposite)]
+/// struct Dog {
+///     name: String,
+///     #[column = "scritches"]
+///     pets: Option<i32>,
+/// }
+///
+/// #[pg_extern]
+/// fn pet(mut dog: Dog) -> Dog {
+///     dog.pets = Some(dog.pets.unwrap_or_default() + 1);
+///     dog
+/// }
+/// ```
+///
+/// Fields are matched to the type's attributes by name, so the type may have attributes the
+/// struct doesn't mention, which are left NULL when converting the struct to a Datum.
+///
+/// [`Spi`]: crate::spi::Spi
+pub trait PostgresComposite: Sized {
+    /// The SQL name of the composite type
+    const COMPOSITE_TYPE: &'static str;
+
+    /// Read the struct's fields from the attributes of `tuple`.
+    fn from_heap_tuple<AllocatedBy: WhoAllocated>(
+        tuple: &PgHeapTuple<'_, AllocatedBy>,
+    ) -> Result<Self, PgHeapTupleError>;
+
+    /// Build a tuple of [`PostgresComposite::COMPOSITE_TYPE`] from the struct's fields.
+    fn into_heap_tuple(self) -> Result<PgHeapTuple<'static, AllocatedByRust>, PgHeapTupleError>;
+
+    /// The Oid of [`PostgresComposite::COMPOSITE_TYPE`]
+    fn composite_type_oid() -> pg_sys::Oid {
+        crate::wrappers::regtypein(Self::COMPOSITE_TYPE)
+    }
+}
+
+/// Find the number of the attribute named `attname`
+pub(crate) fn attribute_number(
+    tupdesc: &PgTupleDesc,
+    attname: &str,
+) -> Result<NonZeroUsize, TryFromDatumError> {
+    tupdesc
+        .iter()
+        .position(|att| att.name() == attname)
+        .map(|i| NonZeroUsize::new(i + 1).unwrap())
+        .ok_or_else(|| TryFromDatumError::NoSuchAttributeName(attname.to_string()))
+}
+
+/// Convert `value` into a Datum for the attribute numbered `attno`, ensuring its type is
+/// compatible with the attribute's
+pub(crate) fn attribute_datum<T: IntoDatum>(
+    tupdesc: &PgTupleDesc,
+    attno: NonZeroUsize,
+    value: T,
+) -> Result<Option<pg_sys::Datum>, TryFromDatumError> {
+    let att =
+        tupdesc.get(attno.get() - 1).ok_or(TryFromDatumError::NoSuchAttributeNumber(attno))?;
+    let type_oid = T::type_oid();
+    let composite_type_oid = value.composite_type_oid();
+    let is_compatible_composite_types =
+        type_oid == pg_sys::RECORDOID && composite_type_oid == Some(att.atttypid);
+    if !is_compatible_composite_types && !T::is_compatible_with(att.atttypid) {
+        return Err(TryFromDatumError::IncompatibleTypes {
+            rust_type: std::any::type_name::<T>(),
+            rust_oid: att.atttypid,
+            datum_type: lookup_type_name(type_oid),
+            datum_oid: type_oid,
+        });
+    }
+    value.into_datum().map(|datum| coerce_to_typmod(att, datum)).transpose()
+}
+
+/// Apply the attribute's typmod to `datum`, such as the length of a `varchar(n)` or the precision
+/// of a `numeric(p, s)`, as Postgres does when assigning to a column.
+///
+/// A value which doesn't fit is an error rather than being truncated, but it may be padded or
+/// rounded as the type does, such as for `char(n)` and `numeric(p, s)`.
+fn coerce_to_typmod(
+    att: &pg_sys::FormData_pg_attribute,
+    datum: pg_sys::Datum,
+) -> Result<pg_sys::Datum, TryFromDatumError> {
+    if att.atttypmod < 0 {
+        return Ok(datum);
+    }
+    let mut funcid = pg_sys::InvalidOid;
+    let path = unsafe { pg_sys::find_typmod_coercion_function(att.atttypid, &mut funcid) };
+    if path != pg_sys::CoercionPathType_COERCION_PATH_FUNC {
+        return Ok(datum);
+    }
+
+    let (collation, typmod) = (att.attcollation, att.atttypmod);
+    let attname = att.name().to_string();
+    let violation = |error: CaughtError| {
+        let message = match error {
+            CaughtError::PostgresError(report)
+            | CaughtError::ErrorReport(report)
+            | CaughtError::RustPanic { ereport: report, .. } => report.message().to_string(),
+        };
+        Err(TryFromDatumError::TypmodViolation { attname: attname.clone(), message })
+    };
+    PgTryBuilder::new(|| unsafe {
+        // SAFETY:  typmod coercion functions take the value, the typmod, and whether the cast is
+        // explicit, which an assignment is not
+        Ok(pg_sys::OidFunctionCall3Coll(
+            funcid,
+            collation,
+            datum,
+            pg_sys::Datum::from(typmod),
+            pg_sys::Datum::from(false),
+        ))
+    })
+    .catch_when(PgSqlErrorCode::ERRCODE_STRING_DATA_RIGHT_TRUNCATION, violation.clone())
+    .catch_when(PgSqlErrorCode::ERRCODE_STRING_DATA_LENGTH_MISMATCH, violation.clone())
+    .catch_when(PgSqlErrorCode::ERRCODE_NUMERIC_VALUE_OUT_OF_RANGE, violation)
+    .execute()
+}
+
+fn incompatible_attribute<T>(
+    att: &pg_sys::FormData_pg_attribute,
+    rust_oid: pg_sys::Oid,
+) -> TryFromDatumError {
+    TryFromDatumError::IncompatibleTypes {
+        rust_type: std::any::type_name::<T>(),
+        rust_oid,
+        datum_type: lookup_type_name(att.atttypid),
+        datum_oid: att.atttypid,
+    }
+}
+
 /** Composite type support
 
 Support for working with types defined by SQL statements like:
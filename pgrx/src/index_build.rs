@@ -0,0 +1,553 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Building blocks for the `ambuild` function of an index access method
+//!
+//! [`build_scan()`] feeds every tuple of the heap to a closure, already projected into the
+//! index's columns, so an access method doesn't need its own `extern "C"` build callback.
+//! Access methods which want to load their index in key order can pass those tuples through an
+//! [`IndexBuildSorter`], which sorts them with Postgres' `tuplesort`, spilling to disk once
+//! `maintenance_work_mem` is exhausted.
+//!
+//! ## Examples
+//!
+//! ```rust,no_run
+//! use pgrx::index_build::{build_scan, IndexBuildSorter};
+//! use pgrx::prelude::*;
+//! use pgrx::{PgBox, PgRelation};
+//!
+//! #[pg_guard]
+//! unsafe extern "C" fn ambuild(
+//!     heap: pg_sys::Relation,
+//!     index: pg_sys::Relation,
+//!     index_info: *mut pg_sys::IndexInfo,
+//! ) -> *mut pg_sys::IndexBuildResult {
+//!     let heap = PgRelation::from_pg(heap);
+//!     let index = PgRelation::from_pg(index);
+//!
+//!     let mut sorter = IndexBuildSorter::new(&index);
+//!     let ntuples = build_scan(&heap, &index, index_info, |tuple| sorter.put(&tuple));
+//!     sorter.finish(|tuple| {
+//!         // write `tuple.values` and `tuple.tid` to the index, in key order
+//!     });
+//!
+//!     let mut result = PgBox::<pg_sys::IndexBuildResult>::alloc0();
+//!     result.heap_tuples = ntuples as f64;
+//!     result.index_tuples = ntuples as f64;
+//!     result.into_pg()
+//! }
+//! ```
+//!
+//! ## Options and Progress
+//!
+//! [`gin_options()`] and [`gist_options()`] read the storage options a `gin` or `gist` index was
+//! created with, falling back to the same defaults the core access methods do, so an operator
+//! class with its own build path honors `fastupdate`, `gin_pending_list_limit` and `buffering`.
+//! [`BuildProgress`] fills in the `CREATE INDEX` progress report Postgres has already started, so
+//! `pg_stat_progress_create_index` shows a long build by a Rust operator class like a core one.
+//! [`build_scan()`] already reports the blocks and tuples it has scanned, so `BuildProgress` is
+//! only needed for the access method's own phases, such as loading sorted tuples.
+use crate as pgrx; // for #[pg_guard] support from within ourself
+use crate::datum::lookup_type_name;
+use crate::{pg_guard, pg_sys, PgRelation};
+use std::ffi::CStr;
+use std::os::raw::c_void;
+
+/// A heap tuple, as projected into the columns of the index being built
+#[derive(Debug, Copy, Clone)]
+pub struct BuildTuple<'a> {
+    /// The location of the tuple in the heap
+    pub tid: pg_sys::ItemPointerData,
+    /// The value of each index column, including any `INCLUDE` columns
+    pub values: &'a [pg_sys::Datum],
+    /// Whether the corresponding entry of `values` is NULL
+    pub isnull: &'a [bool],
+    /// `false` if the tuple is dead, but may still be visible to some transaction
+    pub alive: bool,
+}
+
+type BuildCallback<'a> = &'a mut dyn FnMut(BuildTuple<'_>);
+
+/// Scan every tuple of `heap` which belongs in `index`, passing each to `f`, and return how many
+/// there were.
+///
+/// This is `table_index_build_scan()`, or `IndexBuildHeapScan()` on Postgres 11, so the values
+/// are computed from the index's expressions and tuples not satisfying a partial index's
+/// predicate are skipped.
+///
+/// On Postgres 12 and later, the scan keeps `pg_stat_progress_create_index` up to date as it goes:
+/// Postgres itself reports the heap blocks scanned, and each tuple passed to `f` is counted as a
+/// tuple done, out of the heap's estimated number of tuples.
+pub fn build_scan<F>(
+    heap: &PgRelation,
+    index: &PgRelation,
+    index_info: *mut pg_sys::IndexInfo,
+    mut f: F,
+) -> usize
+where
+    F: FnMut(BuildTuple<'_>),
+{
+    #[cfg(not(feature = "pg11"))]
+    {
+        let total = heap.reltuples().filter(|&reltuples| reltuples > 0.0).unwrap_or(0.0);
+        update_params(&[
+            (PROGRESS_CREATEIDX_TUPLES_TOTAL, total as i64),
+            (PROGRESS_CREATEIDX_TUPLES_DONE, 0),
+        ]);
+    }
+
+    let mut ntuples = 0;
+    let mut counting = |tuple: BuildTuple<'_>| {
+        ntuples += 1;
+        #[cfg(not(feature = "pg11"))]
+        update_param(PROGRESS_CREATEIDX_TUPLES_DONE, ntuples as i64);
+        f(tuple)
+    };
+    let mut callback: BuildCallback = &mut counting;
+    unsafe {
+        // SAFETY:  `callback` outlives the scan, and `build_callback` is the only one to see it
+        pg_sys::IndexBuildHeapScan(
+            heap.as_ptr(),
+            index.as_ptr(),
+            index_info,
+            Some(build_callback),
+            &mut callback,
+        );
+    }
+    ntuples
+}
+
+#[cfg(any(feature = "pg11", feature = "pg12"))]
+#[pg_guard]
+unsafe extern "C" fn build_callback(
+    index: pg_sys::Relation,
+    htup: pg_sys::HeapTuple,
+    values: *mut pg_sys::Datum,
+    isnull: *mut bool,
+    alive: bool,
+    state: *mut c_void,
+) {
+    call_build_callback(index, (*htup).t_self, values, isnull, alive, state)
+}
+
+#[cfg(any(feature = "pg13", feature = "pg14", feature = "pg15", feature = "pg16"))]
+#[pg_guard]
+unsafe extern "C" fn build_callback(
+    index: pg_sys::Relation,
+    tid: pg_sys::ItemPointer,
+    values: *mut pg_sys::Datum,
+    isnull: *mut bool,
+    alive: bool,
+    state: *mut c_void,
+) {
+    call_build_callback(index, *tid, values, isnull, alive, state)
+}
+
+unsafe fn call_build_callback(
+    index: pg_sys::Relation,
+    tid: pg_sys::ItemPointerData,
+    values: *mut pg_sys::Datum,
+    isnull: *mut bool,
+    alive: bool,
+    state: *mut c_void,
+) {
+    let natts = (*(*index).rd_att).natts as usize;
+    let callback = &mut *(state as *mut BuildCallback);
+    callback(BuildTuple {
+        tid,
+        values: std::slice::from_raw_parts(values, natts),
+        isnull: std::slice::from_raw_parts(isnull, natts),
+        alive,
+    })
+}
+
+/// Sorts [`BuildTuple`]s by the key columns of an index, using `tuplesort`.
+///
+/// Each key column is ordered by the default btree operator class of its type, with NULLs last,
+/// and ties are left in no particular order.  Column types are taken from the index's tuple
+/// descriptor, so operator classes with a `STORAGE` type different from their input type aren't
+/// supported.
+pub struct IndexBuildSorter {
+    state: *mut pg_sys::Tuplesortstate,
+    tupdesc: pg_sys::TupleDesc,
+    input: *mut pg_sys::TupleTableSlot,
+    output: *mut pg_sys::TupleTableSlot,
+    natts: usize,
+}
+
+impl IndexBuildSorter {
+    /// Create a sorter for tuples of `index`, which may use up to `maintenance_work_mem` before
+    /// spilling to disk.
+    pub fn new(index: &PgRelation) -> Self {
+        Self::with_work_mem(index, unsafe { pg_sys::maintenance_work_mem })
+    }
+
+    /// Create a sorter for tuples of `index`, which may use up to `work_mem` kilobytes before
+    /// spilling to disk.
+    ///
+    /// # Panics
+    ///
+    /// If the type of any of `index`'s key columns has no default btree operator class.
+    pub fn with_work_mem(index: &PgRelation, work_mem: i32) -> Self {
+        unsafe {
+            let index_desc = index.tuple_desc();
+            let natts = index_desc.len();
+            let nkeys = (*index.rd_index).indnkeyatts as usize;
+
+            // the index's columns, followed by the tuple's `tid` and whether it's alive
+            #[cfg(feature = "pg11")]
+            let tupdesc = pg_sys::CreateTemplateTupleDesc(natts as i32 + 2, false);
+            #[cfg(not(feature = "pg11"))]
+            let tupdesc = pg_sys::CreateTemplateTupleDesc(natts as i32 + 2);
+            for i in 0..natts {
+                let attr = index_desc.get(i).unwrap();
+                pg_sys::TupleDescInitEntry(
+                    tupdesc,
+                    (i + 1) as pg_sys::AttrNumber,
+                    attr.attname.data.as_ptr(),
+                    attr.atttypid,
+                    attr.atttypmod,
+                    0,
+                );
+            }
+            pg_sys::TupleDescInitEntry(
+                tupdesc,
+                (natts + 1) as pg_sys::AttrNumber,
+                std::ptr::null(),
+                pg_sys::TIDOID,
+                -1,
+                0,
+            );
+            pg_sys::TupleDescInitEntry(
+                tupdesc,
+                (natts + 2) as pg_sys::AttrNumber,
+                std::ptr::null(),
+                pg_sys::BOOLOID,
+                -1,
+                0,
+            );
+
+            let mut attnums = Vec::with_capacity(nkeys);
+            let mut operators = Vec::with_capacity(nkeys);
+            let mut collations = Vec::with_capacity(nkeys);
+            for i in 0..nkeys {
+                let attr = index_desc.get(i).unwrap();
+                let typcache =
+                    pg_sys::lookup_type_cache(attr.atttypid, pg_sys::TYPECACHE_LT_OPR as _);
+                if (*typcache).lt_opr == pg_sys::InvalidOid {
+                    panic!(
+                        "type `{}` of index column `{}` has no default btree operator class",
+                        lookup_type_name(attr.atttypid),
+                        crate::name_to_string(&attr.attname)
+                    );
+                }
+                attnums.push((i + 1) as pg_sys::AttrNumber);
+                operators.push((*typcache).lt_opr);
+                collations.push(*index.rd_indcollation.add(i));
+            }
+            let mut nulls_first = vec![false; nkeys];
+
+            #[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13", feature = "pg14"))]
+            let sortopt = false;
+            #[cfg(any(feature = "pg15", feature = "pg16"))]
+            let sortopt = pg_sys::TUPLESORT_NONE as _;
+            // SAFETY:  tuplesort copies the sort keys
+            let state = pg_sys::tuplesort_begin_heap(
+                tupdesc,
+                nkeys as _,
+                attnums.as_mut_ptr(),
+                operators.as_mut_ptr(),
+                collations.as_mut_ptr(),
+                nulls_first.as_mut_ptr(),
+                work_mem,
+                std::ptr::null_mut(),
+                sortopt,
+            );
+
+            #[cfg(feature = "pg11")]
+            let (input, output) = (
+                pg_sys::MakeSingleTupleTableSlot(tupdesc),
+                pg_sys::MakeSingleTupleTableSlot(tupdesc),
+            );
+            #[cfg(not(feature = "pg11"))]
+            let (input, output) = (
+                pg_sys::MakeSingleTupleTableSlot(tupdesc, &pg_sys::TTSOpsVirtual),
+                pg_sys::MakeSingleTupleTableSlot(tupdesc, &pg_sys::TTSOpsMinimalTuple),
+            );
+
+            Self { state, tupdesc, input, output, natts }
+        }
+    }
+
+    /// Add `tuple` to the sort.
+    ///
+    /// # Panics
+    ///
+    /// If `tuple` doesn't have a value for each of the index's columns.
+    pub fn put(&mut self, tuple: &BuildTuple<'_>) {
+        assert_eq!(tuple.values.len(), self.natts, "wrong number of index columns");
+        assert_eq!(tuple.isnull.len(), self.natts, "wrong number of index columns");
+        unsafe {
+            #[cfg(feature = "pg11")]
+            pg_sys::ExecClearTuple(self.input);
+            #[cfg(not(feature = "pg11"))]
+            (*(*self.input).tts_ops).clear.unwrap()(self.input);
+
+            let values = std::slice::from_raw_parts_mut((*self.input).tts_values, self.natts + 2);
+            let isnull = std::slice::from_raw_parts_mut((*self.input).tts_isnull, self.natts + 2);
+            values[..self.natts].copy_from_slice(tuple.values);
+            isnull[..self.natts].copy_from_slice(tuple.isnull);
+            // SAFETY:  tuplesort copies the `tid` out of the slot before we return
+            values[self.natts] = pg_sys::Datum::from(&tuple.tid as *const pg_sys::ItemPointerData);
+            values[self.natts + 1] = pg_sys::Datum::from(tuple.alive);
+            isnull[self.natts] = false;
+            isnull[self.natts + 1] = false;
+
+            pg_sys::ExecStoreVirtualTuple(self.input);
+            pg_sys::tuplesort_puttupleslot(self.state, self.input);
+        }
+    }
+
+    /// Sort the tuples, and pass each to `f` in order.
+    pub fn finish<F>(self, mut f: F)
+    where
+        F: FnMut(BuildTuple<'_>),
+    {
+        let natts = self.natts;
+        unsafe {
+            pg_sys::tuplesort_performsort(self.state);
+            while pg_sys::tuplesort_gettupleslot(
+                self.state,
+                true,
+                false,
+                self.output,
+                std::ptr::null_mut(),
+            ) {
+                #[cfg(feature = "pg11")]
+                pg_sys::slot_getallattrs(self.output);
+                #[cfg(not(feature = "pg11"))]
+                pg_sys::slot_getsomeattrs_int(self.output, natts as i32 + 2);
+
+                let values = std::slice::from_raw_parts((*self.output).tts_values, natts + 2);
+                let isnull = std::slice::from_raw_parts((*self.output).tts_isnull, natts + 2);
+                f(BuildTuple {
+                    tid: *values[natts].cast_mut_ptr::<pg_sys::ItemPointerData>(),
+                    values: &values[..natts],
+                    isnull: &isnull[..natts],
+                    alive: values[natts + 1].value() != 0,
+                });
+            }
+        }
+    }
+}
+
+impl Drop for IndexBuildSorter {
+    fn drop(&mut self) {
+        unsafe {
+            pg_sys::tuplesort_end(self.state);
+            pg_sys::ExecDropSingleTupleTableSlot(self.input);
+            pg_sys::ExecDropSingleTupleTableSlot(self.output);
+            pg_sys::FreeTupleDesc(self.tupdesc);
+        }
+    }
+}
+
+/// The storage options of a `gin` index
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct GinIndexOptions {
+    /// Whether new entries go to the pending list first, the `fastupdate` option
+    pub fastupdate: bool,
+    /// How large the pending list may grow, in kilobytes, before it's flushed into the index.  This
+    /// is the index's `gin_pending_list_limit` option if it has one, or else the GUC's value.
+    pub pending_list_limit: i32,
+}
+
+/// `GinOptions` from `access/gin_private.h`
+#[repr(C)]
+struct GinOptions {
+    vl_len_: i32,
+    use_fast_update: bool,
+    pending_list_cleanup_size: i32,
+}
+
+/// Read the storage options of `index`, or `None` if it isn't a `gin` index.
+pub fn gin_options(index: &PgRelation) -> Option<GinIndexOptions> {
+    if !index_uses_am(index, "gin") {
+        return None;
+    }
+    unsafe {
+        // SAFETY:  `rd_options` of a gin index is always null or a `GinOptions`
+        let options = (index.rd_options as *const GinOptions).as_ref();
+        let fastupdate = options.map(|options| options.use_fast_update).unwrap_or(true);
+        let pending_list_limit = options
+            .map(|options| options.pending_list_cleanup_size)
+            .filter(|&size| size != -1)
+            .unwrap_or(pg_sys::gin_pending_list_limit);
+        Some(GinIndexOptions { fastupdate, pending_list_limit })
+    }
+}
+
+/// When a `gist` index should be built using buffers, the `buffering` option
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GistBuffering {
+    /// Switch to buffering once the index no longer fits in `effective_cache_size`
+    Auto,
+    /// Always build with buffers
+    On,
+    /// Never build with buffers
+    Off,
+}
+
+/// The storage options of a `gist` index
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct GistIndexOptions {
+    /// How full, in percent, each page is packed when building the index
+    pub fillfactor: i32,
+    /// When to build the index using buffers
+    pub buffering: GistBuffering,
+}
+
+/// `GiSTOptions` from `access/gist_private.h`
+#[repr(C)]
+struct GistOptions {
+    vl_len_: i32,
+    fillfactor: i32,
+    #[cfg(any(feature = "pg11", feature = "pg12"))]
+    buffering_mode_offset: i32,
+    #[cfg(any(feature = "pg13", feature = "pg14", feature = "pg15", feature = "pg16"))]
+    buffering_mode: u32,
+}
+
+/// `GIST_DEFAULT_FILLFACTOR` from `access/gist_private.h`
+const GIST_DEFAULT_FILLFACTOR: i32 = 90;
+
+/// Read the storage options of `index`, or `None` if it isn't a `gist` index.
+pub fn gist_options(index: &PgRelation) -> Option<GistIndexOptions> {
+    if !index_uses_am(index, "gist") {
+        return None;
+    }
+    unsafe {
+        // SAFETY:  `rd_options` of a gist index is always null or a `GiSTOptions`
+        let Some(options) = (index.rd_options as *const GistOptions).as_ref() else {
+            return Some(GistIndexOptions {
+                fillfactor: GIST_DEFAULT_FILLFACTOR,
+                buffering: GistBuffering::Auto,
+            });
+        };
+
+        #[cfg(any(feature = "pg11", feature = "pg12"))]
+        let buffering = {
+            // the mode is a string stored after the struct, or absent if it wasn't set
+            let mode = (options.buffering_mode_offset > 0).then(|| {
+                let ptr = (options as *const GistOptions as *const std::os::raw::c_char)
+                    .add(options.buffering_mode_offset as usize);
+                CStr::from_ptr(ptr).to_bytes()
+            });
+            match mode {
+                Some(b"on") => GistBuffering::On,
+                Some(b"off") => GistBuffering::Off,
+                _ => GistBuffering::Auto,
+            }
+        };
+        #[cfg(any(feature = "pg13", feature = "pg14", feature = "pg15", feature = "pg16"))]
+        let buffering = match options.buffering_mode {
+            1 => GistBuffering::On,
+            2 => GistBuffering::Off,
+            _ => GistBuffering::Auto,
+        };
+        Some(GistIndexOptions { fillfactor: options.fillfactor, buffering })
+    }
+}
+
+fn index_uses_am(index: &PgRelation, amname: &str) -> bool {
+    unsafe {
+        // SAFETY:  `get_am_name()` returns null for an unknown access method
+        let name = pg_sys::get_am_name((*index.rd_rel).relam);
+        !name.is_null() && CStr::from_ptr(name).to_bytes() == amname.as_bytes()
+    }
+}
+
+// `CREATE INDEX` progress parameters from `commands/progress.h`
+#[cfg(not(feature = "pg11"))]
+const PROGRESS_CREATEIDX_SUBPHASE: i32 = 10;
+#[cfg(not(feature = "pg11"))]
+const PROGRESS_CREATEIDX_TUPLES_TOTAL: i32 = 11;
+#[cfg(not(feature = "pg11"))]
+const PROGRESS_CREATEIDX_TUPLES_DONE: i32 = 12;
+
+/// Reports the progress of an index build to `pg_stat_progress_create_index`.
+///
+/// Postgres starts the `CREATE INDEX` progress report before calling `ambuild` and ends it
+/// afterwards, so this only updates it.  The `phase` column is named by the access method's
+/// `ambuildphasename`, from the subphase number given to [`subphase`](Self::subphase).
+///
+/// Progress reporting for `CREATE INDEX` doesn't exist before Postgres 12.
+#[cfg(not(feature = "pg11"))]
+pub struct BuildProgress {
+    done: i64,
+    // progress is reported for this backend, so it must stay on its thread
+    __marker: std::marker::PhantomData<*mut ()>,
+}
+
+#[cfg(not(feature = "pg11"))]
+impl BuildProgress {
+    pub fn new() -> Self {
+        BuildProgress { done: 0, __marker: std::marker::PhantomData }
+    }
+
+    /// Report that the build has moved on to the access method's phase number `subphase`, and
+    /// that none of its tuples are done yet.
+    pub fn subphase(&mut self, subphase: i64) {
+        self.done = 0;
+        update_params(&[
+            (PROGRESS_CREATEIDX_SUBPHASE, subphase),
+            (PROGRESS_CREATEIDX_TUPLES_DONE, 0),
+        ]);
+    }
+
+    /// Report how many tuples the current phase will process in total
+    pub fn tuples_total(&mut self, total: i64) {
+        update_params(&[(PROGRESS_CREATEIDX_TUPLES_TOTAL, total)]);
+    }
+
+    /// Report that another `n` tuples are done, and check for interrupts
+    pub fn tuples_done(&mut self, n: i64) {
+        self.done += n;
+        update_params(&[(PROGRESS_CREATEIDX_TUPLES_DONE, self.done)]);
+        pg_sys::check_for_interrupts!();
+    }
+}
+
+#[cfg(not(feature = "pg11"))]
+impl Default for BuildProgress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(not(feature = "pg11"))]
+fn update_param(param: i32, value: i64) {
+    unsafe {
+        // SAFETY:  the parameter is in bounds, and this only writes to this backend's entry
+        pg_sys::pgstat_progress_update_param(param, value);
+    }
+}
+
+#[cfg(not(feature = "pg11"))]
+fn update_params(values: &[(i32, i64)]) {
+    let (params, values): (Vec<_>, Vec<_>) = values.iter().copied().unzip();
+    unsafe {
+        // SAFETY:  the parameters are all in bounds, and this only writes to this backend's entry
+        pg_sys::pgstat_progress_update_multi_param(
+            params.len() as _,
+            params.as_ptr(),
+            values.as_ptr(),
+        );
+    }
+}
@@ -12,6 +12,9 @@
 //!
 //! The default implementations use `serde_json` to serialize a custom type to human-readable strings,
 //! and `serde_cbor` to serialize internally as a `varlena *` for storage on disk.
+//!
+//! Types with the `#[sendrecvfuncs]` attribute also get binary send/receive functions, through
+//! [`SendRecvFuncs`].
 
 use crate::*;
 
@@ -72,3 +75,20 @@ pub trait JsonInOutFuncs<'de>: serde::de::Deserialize<'de> + serde::ser::Seriali
     /// error message should be generated?
     const NULL_ERROR_MESSAGE: Option<&'static str> = None;
 }
+
+/// `#[derive(PostgresType)]` types with the `#[sendrecvfuncs]` attribute implement this trait to
+/// provide the binary send/receive functions Postgres uses for the binary wire protocol and
+/// `COPY ... WITH (FORMAT binary)`
+pub trait SendRecvFuncs {
+    /// Convert `Self` into its binary representation
+    fn send(&self) -> Vec<u8>;
+
+    /// Given the binary representation of `Self`, as written by [`SendRecvFuncs::send`], read it
+    /// into `Self`.
+    ///
+    /// The whole buffer is expected to be read.  It is expected that malformed input will raise an
+    /// `error!()` or `panic!()`
+    fn recv(buffer: &mut ReceiveBuffer<'_>) -> Self
+    where
+        Self: Sized;
+}
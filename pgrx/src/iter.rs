@@ -9,6 +9,7 @@
 //LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
 use std::iter::once;
 
+use crate::spi::{self, SpiCursor, SpiHeapTupleData};
 use crate::IntoHeapTuple;
 use pgrx_sql_entity_graph::metadata::{
     ArgumentError, Returns, ReturnsError, SqlMapping, SqlTranslatable,
@@ -55,6 +56,18 @@ impl<'a, T> SetOfIterator<'a, T> {
     {
         Self { iter: Box::new(iter.into_iter()) }
     }
+
+    /// Lazily return the rows of an SPI cursor, fetching `batch_size` rows at a time and
+    /// converting each one with `f`.
+    ///
+    /// See [`TableIterator::from_cursor()`] for details.
+    pub fn from_cursor<F>(cursor: SpiCursor<'_>, batch_size: libc::c_long, f: F) -> Self
+    where
+        T: 'a,
+        F: FnMut(SpiHeapTupleData<'_>) -> spi::Result<T> + 'a,
+    {
+        Self::new(cursor.into_stream(batch_size, f))
+    }
 }
 
 impl<'a, T> Iterator for SetOfIterator<'a, T> {
@@ -143,6 +156,31 @@ where
     pub fn once(value: T) -> Self {
         Self::new(once(value))
     }
+
+    /// Lazily return the rows of an SPI cursor, fetching `batch_size` rows at a time and
+    /// converting each one with `f`.
+    ///
+    /// Unlike collecting the cursor's rows into a `Vec`, at most one batch is held in memory, so
+    /// this is suitable for exposing arbitrarily large queries as a function.
+    ///
+    /// ```rust,no_run
+    /// use pgrx::prelude::*;
+    /// #[pg_extern]
+    /// fn all_the_things() -> TableIterator<'static, (name!(id, Option<i64>), name!(name, Option<String>))> {
+    ///     Spi::connect(|client| {
+    ///         let cursor = client.open_cursor("SELECT id, name FROM things ORDER BY id", None);
+    ///         TableIterator::from_cursor(cursor, 1000, |row| Ok((row.get(1)?, row.get(2)?)))
+    ///     })
+    /// }
+    /// ```
+    ///
+    /// See [`SpiCursor::into_stream()`] for details.
+    pub fn from_cursor<F>(cursor: SpiCursor<'_>, batch_size: libc::c_long, f: F) -> Self
+    where
+        F: FnMut(SpiHeapTupleData<'_>) -> spi::Result<T> + 'a,
+    {
+        Self::new(cursor.into_stream(batch_size, f))
+    }
 }
 
 impl<'a, T> Iterator for TableIterator<'a, T> {
@@ -23,6 +23,8 @@
 //! ```
 #![allow(clippy::missing_safety_doc)]
 #![allow(clippy::cast_ptr_alignment)]
+// `forbid-panics` deprecates functions for extensions, which pgrx itself still uses
+#![cfg_attr(all(feature = "forbid-panics", not(debug_assertions)), allow(deprecated))]
 
 #[macro_use]
 extern crate bitflags;
@@ -42,15 +44,26 @@ pub mod array;
 pub mod atomics;
 pub mod bgworkers;
 pub mod callbacks;
+pub mod catalog;
+pub mod config_file;
+pub mod cost;
 pub mod datum;
+pub mod datum_diagnostics;
+pub mod dfmgr;
 pub mod enum_helper;
+pub mod event_trigger;
+pub mod fake_clock;
+pub mod fault_injection;
 pub mod fcinfo;
+pub mod fdw;
 pub mod ffi;
 pub mod guc;
+pub mod hash;
 pub mod heap_tuple;
 #[cfg(feature = "cshim")]
 pub mod hooks;
 pub mod htup;
+pub mod index_build;
 pub mod inoutfuncs;
 pub mod itemptr;
 pub mod iter;
@@ -58,18 +71,30 @@ pub mod iter;
 pub mod list;
 pub mod lwlock;
 pub mod memcxt;
+pub mod memoize;
 pub mod misc;
 #[cfg(feature = "cshim")]
 pub mod namespace;
 pub mod nodes;
+pub mod notify;
+pub mod panic_free;
+pub mod pg_version;
 pub mod pgbox;
+pub mod pgstat;
+pub mod progress;
 pub mod rel;
+pub mod session;
 pub mod shmem;
+pub mod signal_log;
 pub mod spi;
 #[cfg(feature = "cshim")]
 pub mod spinlock;
 pub mod srf;
 pub mod stringinfo;
+pub mod systable;
+pub mod table_builder;
+pub mod table_writer;
+pub mod thread;
 pub mod trigger_support;
 pub mod tupdesc;
 pub mod varlena;
@@ -121,6 +146,9 @@ pub use pg_sys::elog::PgLogLevel;
 pub use pg_sys::errcodes::PgSqlErrorCode;
 pub use pg_sys::oids::PgOid;
 pub use pg_sys::panic::pgrx_extern_c_guard;
+pub use pg_sys::panic::{
+    abort_on_double_panic, panic_policy, set_abort_on_double_panic, set_panic_policy, PanicPolicy,
+};
 pub use pg_sys::pg_try::PgTryBuilder;
 pub use pg_sys::utils::name_data_to_str;
 pub use pg_sys::PgBuiltInOids;
@@ -167,10 +195,23 @@ const _: () = {
 ///
 /// </pre></div>
 ///
-/// This calls both [`pg_magic_func!()`](pg_magic_func) and [`pg_sql_graph_magic!()`](pg_sql_graph_magic).
+/// This calls both [`pg_magic_func!()`](pg_magic_func) and [`pg_sql_graph_magic!()`](pg_sql_graph_magic),
+/// and [`extension_introspection!()`](extension_introspection) to add the `version()`,
+/// `build_info()` and `functions()` functions to the extension's schema.
+///
+/// An extension with functions of its own by those names, or which doesn't want them, can leave
+/// them out:
+///
+/// ```rust,ignore
+/// pgrx::pg_module_magic!(introspection = false);
+/// ```
 #[macro_export]
 macro_rules! pg_module_magic {
     () => {
+        $crate::pg_module_magic!(introspection = false);
+        $crate::extension_introspection!();
+    };
+    (introspection = false) => {
         $crate::pg_magic_func!();
         $crate::pg_sql_graph_magic!();
     };
@@ -310,6 +351,123 @@ macro_rules! pg_sql_graph_magic {
     };
 }
 
+/// Create SQL-callable functions describing the extension's build.
+///
+/// This lets a deployment check exactly which binary is loaded into a running server:
+///
+/// * `version()` returns the crate version.
+/// * `build_info()` returns the crate version, the git hash and `rustc` version it was built with,
+///   the features it was built with, and the Postgres version it was built against.
+/// * `functions()` lists the functions belonging to the extension, with the symbol and library
+///   each is bound to.
+///
+/// The git hash, `rustc` version, and features are recorded by `cargo pgrx` when it builds the
+/// extension, and are `NULL` when built by other means.
+///
+/// [`pg_module_magic!()`](pg_module_magic) calls this for every extension, creating the functions
+/// in the extension's schema.  As `pg_catalog.version()` is found first, call them qualified:
+///
+/// ```sql
+/// SELECT * FROM myext.build_info();
+/// ```
+///
+/// An extension which opts out of that can instead create them in a schema of its own:
+///
+/// ```rust,ignore
+/// pgrx::pg_module_magic!(introspection = false);
+/// pgrx::extension_introspection!(myext_info);
+/// ```
+#[macro_export]
+macro_rules! extension_introspection {
+    () => {
+        #[doc(hidden)]
+        mod __pgrx_extension_introspection {
+            $crate::extension_introspection!(@functions);
+        }
+    };
+    ($schema:ident) => {
+        #[$crate::pg_schema]
+        mod $schema {
+            $crate::extension_introspection!(@functions);
+        }
+    };
+    (@functions) => {
+        use $crate::prelude::*;
+
+        // named apart from the SQL functions, so their symbols can't clash with the extension's
+        #[pg_extern(immutable, parallel_safe, name = "version")]
+        fn pgrx_extension_version() -> &'static str {
+            env!("CARGO_PKG_VERSION")
+        }
+
+        #[pg_extern(immutable, parallel_safe, name = "build_info")]
+        fn pgrx_extension_build_info() -> TableIterator<
+            'static,
+            (
+                name!(version, &'static str),
+                name!(git_hash, Option<&'static str>),
+                name!(rustc_version, Option<&'static str>),
+                name!(features, Option<&'static str>),
+                name!(pg_version, &'static str),
+            ),
+        > {
+            let pg_version = ::core::ffi::CStr::from_bytes_with_nul($crate::pg_sys::PG_VERSION_STR)
+                .ok()
+                .and_then(|version| version.to_str().ok())
+                .unwrap_or_default();
+            TableIterator::once((
+                env!("CARGO_PKG_VERSION"),
+                option_env!("PGRX_BUILD_GIT_HASH"),
+                option_env!("PGRX_BUILD_RUSTC_VERSION"),
+                option_env!("PGRX_BUILD_FEATURES"),
+                pg_version,
+            ))
+        }
+
+        #[pg_extern(stable, parallel_safe, name = "functions")]
+        fn pgrx_extension_functions(
+            fcinfo: pg_sys::FunctionCallInfo,
+        ) -> Result<
+            TableIterator<
+                'static,
+                (
+                    name!(function, Option<String>),
+                    name!(symbol, Option<String>),
+                    name!(library, Option<String>),
+                ),
+            >,
+            $crate::spi::Error,
+        > {
+            // the extension is whichever one this very function belongs to, as neither the crate
+            // nor the package name has to match the extension's, nor its schema be on the
+            // search_path
+            let this = unsafe { (*(*fcinfo).flinfo).fn_oid };
+            let rows = Spi::connect(|client| {
+                client
+                    .select(
+                        "SELECT p.oid::pg_catalog.regprocedure::text, p.prosrc, p.probin
+                           FROM pg_catalog.pg_depend this
+                           JOIN pg_catalog.pg_depend d ON d.refclassid = this.refclassid
+                                                      AND d.refobjid = this.refobjid
+                           JOIN pg_catalog.pg_proc p ON p.oid = d.objid
+                          WHERE this.classid = 'pg_catalog.pg_proc'::pg_catalog.regclass
+                            AND this.objid = $1
+                            AND this.refclassid = 'pg_catalog.pg_extension'::pg_catalog.regclass
+                            AND this.deptype = 'e'
+                            AND d.classid = 'pg_catalog.pg_proc'::pg_catalog.regclass
+                            AND d.deptype = 'e'
+                          ORDER BY 1",
+                        None,
+                        Some(vec![(PgBuiltInOids::OIDOID.oid(), this.into_datum())]),
+                    )?
+                    .map(|row| Ok((row.get(1)?, row.get(2)?, row.get(3)?)))
+                    .collect::<Result<Vec<_>, $crate::spi::Error>>()
+            })?;
+            Ok(TableIterator::new(rows))
+        }
+    };
+}
+
 pub(crate) static UTF8DATABASE: Lazy<Utf8Compat> = Lazy::new(|| {
     let encoding_int = unsafe { pgrx_pg_sys::GetDatabaseEncoding() };
     match encoding_int as core::ffi::c_uint {
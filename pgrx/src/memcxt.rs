@@ -17,6 +17,7 @@
 //!
 use crate::pg_sys;
 use crate::pg_sys::AsPgCStr;
+use core::marker::PhantomData;
 use core::ptr;
 use std::fmt::Debug;
 use std::ptr::NonNull;
@@ -451,6 +452,8 @@ impl PgMemoryContexts {
             panic!("attempt to copy a null pointer");
         }
 
+        crate::fault_injection::fault_point(crate::fault_injection::ALLOC);
+
         // SAFETY: We alloc new space, it should be non-overlapping!
         unsafe {
             // Make sure we copy bytes.
@@ -471,6 +474,7 @@ impl PgMemoryContexts {
     /// We also cannot ensure that the result of this function will stay allocated as long as Rust's
     /// borrow checker thinks it will.
     pub unsafe fn palloc(&mut self, len: usize) -> *mut std::os::raw::c_void {
+        crate::fault_injection::fault_point(crate::fault_injection::ALLOC);
         unsafe { pg_sys::MemoryContextAlloc(self.value(), len) }
     }
 
@@ -551,6 +555,7 @@ impl PgMemoryContexts {
     /// We also cannot ensure that the result of this function will stay allocated as long as Rust's
     /// borrow checker thinks it will.
     pub unsafe fn palloc0(&mut self, len: usize) -> *mut std::os::raw::c_void {
+        crate::fault_injection::fault_point(crate::fault_injection::ALLOC);
         unsafe { pg_sys::MemoryContextAllocZero(self.value(), len) }
     }
 
@@ -599,3 +604,51 @@ impl PgMemoryContexts {
         result
     }
 }
+
+/// A `MemoryContext` borrowed for the lifetime `'mcx`.
+///
+/// Values borrowed from memory allocated in the context, such as those produced by
+/// [`FromDatumBorrowed`](crate::datum::FromDatumBorrowed), carry `'mcx` and so the compiler
+/// prevents them from outliving it.  A `MemCx` is only handed out by [`current_context`].
+pub struct MemCx<'mcx> {
+    ptr: NonNull<pg_sys::MemoryContextData>,
+    _marker: PhantomData<&'mcx pg_sys::MemoryContextData>,
+}
+
+impl<'mcx> MemCx<'mcx> {
+    /// The raw `MemoryContext`
+    pub fn value(&self) -> pg_sys::MemoryContext {
+        self.ptr.as_ptr()
+    }
+
+    /// Execute `f` with this context as the `CurrentMemoryContext`, so anything Postgres allocates
+    /// in `f` lives as long as `'mcx`
+    pub fn exec_in<T>(&self, f: impl FnOnce() -> T) -> T {
+        PgMemoryContexts::exec_in_context(self.value(), |_| f())
+    }
+}
+
+/// Borrow the `CurrentMemoryContext` for the duration of `f`.
+///
+/// `f` must accept a [`MemCx`] of any lifetime, so nothing borrowed through it can escape `f`.
+///
+/// ```rust,no_run
+/// use pgrx::prelude::*;
+/// use pgrx::{current_context, FromDatumBorrowed};
+///
+/// fn text_len(datum: pg_sys::Datum) -> usize {
+///     current_context(|mcx| unsafe {
+///         <&str>::from_datum_borrowed(datum, false, pg_sys::TEXTOID, mcx).map_or(0, str::len)
+///     })
+/// }
+/// ```
+pub fn current_context<F, T>(f: F) -> T
+where
+    F: for<'mcx> FnOnce(&MemCx<'mcx>) -> T,
+{
+    // SAFETY: Postgres always has a `CurrentMemoryContext`, and it will outlive `f` as we don't
+    // switch away from it or reset it here
+    let ptr = unsafe { NonNull::new(pg_sys::CurrentMemoryContext) }
+        .expect("CurrentMemoryContext should not be null");
+    f(&MemCx { ptr, _marker: PhantomData })
+}
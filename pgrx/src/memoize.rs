@@ -0,0 +1,244 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Result caching for `#[pg_extern(memoize)]` functions
+//!
+//! Typically these functions are not necessary to call directly as they're used behind
+//! the scenes by the code generated by the `#[pg_extern]` macro.
+use crate::{pg_sys, varlena, PgMemoryContexts};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// The most results a memoized function keeps before evicting the least recently used
+pub const MEMOIZE_CAPACITY: usize = 1024;
+
+/// How long a memoized function's results are kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoizeScope {
+    /// For the query the function is called from, in the function's `fn_extra`
+    Query,
+    /// For the rest of the session, which is only correct for `IMMUTABLE` functions
+    Session,
+}
+
+struct Entry {
+    datum: pg_sys::Datum,
+    isnull: bool,
+    last_used: u64,
+}
+
+/// A least-recently-used cache of a function's results, keyed by the images of its arguments
+struct MemoCache {
+    memcxt: pg_sys::MemoryContext,
+    typlen: i16,
+    typbyval: bool,
+    entries: HashMap<Vec<u8>, Entry>,
+    clock: u64,
+}
+
+impl MemoCache {
+    fn new(memcxt: pg_sys::MemoryContext, rettype: pg_sys::Oid) -> Self {
+        let mut typlen = 0;
+        let mut typbyval = false;
+        unsafe {
+            // SAFETY: Postgres raises an ERROR for an invalid type
+            pg_sys::get_typlenbyval(rettype, &mut typlen, &mut typbyval);
+        }
+        MemoCache { memcxt, typlen, typbyval, entries: HashMap::new(), clock: 0 }
+    }
+
+    /// A copy of the cached result for `key`, in the `CurrentMemoryContext`
+    unsafe fn get(&mut self, key: &[u8]) -> Option<(pg_sys::Datum, bool)> {
+        self.clock += 1;
+        let entry = self.entries.get_mut(key)?;
+        entry.last_used = self.clock;
+        if entry.isnull {
+            Some((pg_sys::Datum::from(0), true))
+        } else {
+            Some((copy_datum(entry.datum, self.typlen, self.typbyval), false))
+        }
+    }
+
+    unsafe fn insert(&mut self, key: Vec<u8>, datum: pg_sys::Datum, isnull: bool) {
+        if self.entries.len() >= MEMOIZE_CAPACITY {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(evicted) = oldest.and_then(|key| self.entries.remove(&key)) {
+                if !evicted.isnull && !self.typbyval {
+                    pg_sys::pfree(evicted.datum.cast_mut_ptr());
+                }
+            }
+        }
+
+        let (typlen, typbyval) = (self.typlen, self.typbyval);
+        let datum = if isnull {
+            datum
+        } else {
+            PgMemoryContexts::For(self.memcxt).switch_to(|_| copy_datum(datum, typlen, typbyval))
+        };
+        self.clock += 1;
+        self.entries.insert(key, Entry { datum, isnull, last_used: self.clock });
+    }
+}
+
+thread_local! {
+    static SESSION_CACHES: RefCell<HashMap<(pg_sys::Oid, pg_sys::Oid), MemoCache>> = RefCell::new(HashMap::new());
+}
+
+/// Call `f`, a `#[pg_extern]` wrapper body, through a cache of its results for the same arguments.
+///
+/// The arguments are compared by their binary images, so arguments which are equal but
+/// represented differently, such as `numeric`s with different scales, are cached separately.
+///
+/// # Safety
+///
+/// `fcinfo` must be a valid [`pg_sys::FunctionCallInfo`] pointer, and `f` must be the function's
+/// actual implementation.
+pub unsafe fn memoized<F>(
+    fcinfo: pg_sys::FunctionCallInfo,
+    scope: MemoizeScope,
+    f: F,
+) -> pg_sys::Datum
+where
+    F: FnOnce(pg_sys::FunctionCallInfo) -> pg_sys::Datum,
+{
+    let flinfo = (*fcinfo).flinfo;
+    let rettype =
+        if flinfo.is_null() { pg_sys::InvalidOid } else { pg_sys::get_fn_expr_rettype(flinfo) };
+    let Some(key) = cache_key(fcinfo).filter(|_| rettype != pg_sys::InvalidOid) else {
+        // called without enough type information to build a key, such as directly from Rust
+        return f(fcinfo);
+    };
+
+    let hit = match scope {
+        MemoizeScope::Query => {
+            let cache = query_cache(flinfo, rettype);
+            (*cache).get(&key)
+        }
+        MemoizeScope::Session => SESSION_CACHES.with(|caches| {
+            caches
+                .borrow_mut()
+                .get_mut(&((*flinfo).fn_oid, rettype))
+                .and_then(|cache| cache.get(&key))
+        }),
+    };
+    if let Some((datum, isnull)) = hit {
+        (*fcinfo).isnull = isnull;
+        return datum;
+    }
+
+    // the cache isn't borrowed while `f` runs, in case it calls itself
+    let datum = f(fcinfo);
+    let isnull = (*fcinfo).isnull;
+    match scope {
+        MemoizeScope::Query => {
+            let cache = query_cache(flinfo, rettype);
+            (*cache).insert(key, datum, isnull);
+        }
+        MemoizeScope::Session => SESSION_CACHES.with(|caches| {
+            caches
+                .borrow_mut()
+                .entry(((*flinfo).fn_oid, rettype))
+                .or_insert_with(|| MemoCache::new(session_memcxt(), rettype))
+                .insert(key, datum, isnull)
+        }),
+    }
+    datum
+}
+
+/// The per-query cache, kept in `fn_extra` and freed along with `fn_mcxt`
+unsafe fn query_cache(flinfo: *mut pg_sys::FmgrInfo, rettype: pg_sys::Oid) -> *mut MemoCache {
+    if (*flinfo).fn_extra.is_null() {
+        let memcxt = (*flinfo).fn_mcxt;
+        (*flinfo).fn_extra = PgMemoryContexts::For(memcxt)
+            .leak_and_drop_on_delete(MemoCache::new(memcxt, rettype))
+            .cast();
+    }
+    (*flinfo).fn_extra.cast()
+}
+
+fn session_memcxt() -> pg_sys::MemoryContext {
+    thread_local! {
+        static SESSION_MEMCXT: pg_sys::MemoryContext = unsafe {
+            // SAFETY: `TopMemoryContext` lives as long as the backend, and the name is static
+            pg_sys::AllocSetContextCreateExtended(
+                pg_sys::TopMemoryContext,
+                b"pgrx memoize\0".as_ptr().cast(),
+                pg_sys::ALLOCSET_DEFAULT_MINSIZE as usize,
+                pg_sys::ALLOCSET_DEFAULT_INITSIZE as usize,
+                pg_sys::ALLOCSET_DEFAULT_MAXSIZE as usize,
+            )
+        };
+    }
+    SESSION_MEMCXT.with(|memcxt| *memcxt)
+}
+
+/// The collation and the binary image of each argument, or `None` if an argument's type is unknown
+unsafe fn cache_key(fcinfo: pg_sys::FunctionCallInfo) -> Option<Vec<u8>> {
+    let flinfo = (*fcinfo).flinfo;
+    if flinfo.is_null() {
+        return None;
+    }
+
+    let mut key = Vec::new();
+    key.extend_from_slice(&u32::from((*fcinfo).fncollation).to_ne_bytes());
+    for num in 0..(*fcinfo).nargs as usize {
+        if crate::fcinfo::pg_arg_is_null(fcinfo, num) {
+            key.push(0);
+            continue;
+        }
+        let typoid = pg_sys::get_fn_expr_argtype(flinfo, num as _);
+        if typoid == pg_sys::InvalidOid {
+            return None;
+        }
+        let mut typlen = 0;
+        let mut typbyval = false;
+        pg_sys::get_typlenbyval(typoid, &mut typlen, &mut typbyval);
+
+        let datum = crate::fcinfo::pg_getarg_datum_raw(fcinfo, num);
+        let image: &[u8] = if typbyval {
+            key.push(1);
+            key.extend_from_slice(&datum.value().to_ne_bytes());
+            continue;
+        } else if typlen == -1 {
+            let detoasted = pg_sys::pg_detoast_datum_packed(datum.cast_mut_ptr());
+            varlena::varlena_to_byte_slice(detoasted)
+        } else if typlen == -2 {
+            core::ffi::CStr::from_ptr(datum.cast_mut_ptr()).to_bytes()
+        } else {
+            core::slice::from_raw_parts(datum.cast_mut_ptr::<u8>(), typlen as usize)
+        };
+        key.push(2);
+        key.extend_from_slice(&image.len().to_ne_bytes());
+        key.extend_from_slice(image);
+    }
+    Some(key)
+}
+
+/// Copy a non-NULL Datum of a type with the given `typlen` and `typbyval` into the
+/// `CurrentMemoryContext`
+unsafe fn copy_datum(datum: pg_sys::Datum, typlen: i16, typbyval: bool) -> pg_sys::Datum {
+    if typbyval {
+        datum
+    } else if typlen == -1 {
+        pg_sys::Datum::from(pg_sys::pg_detoast_datum_copy(datum.cast_mut_ptr()))
+    } else {
+        let len = if typlen == -2 {
+            core::ffi::CStr::from_ptr(datum.cast_mut_ptr()).to_bytes_with_nul().len()
+        } else {
+            typlen as usize
+        };
+        let copy = pg_sys::palloc(len);
+        core::ptr::copy_nonoverlapping(datum.cast_mut_ptr::<u8>(), copy.cast::<u8>(), len);
+        pg_sys::Datum::from(copy)
+    }
+}
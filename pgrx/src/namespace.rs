@@ -7,10 +7,36 @@
 //LICENSE All rights reserved.
 //LICENSE
 //LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
-//! A helper struct for creating a Postgres `List` of `String`s to qualify an object name
+//! Helpers for resolving object names in Postgres' namespaces
+//!
+//! Names a user passes to an extension, such as a table name in a `text` argument, should be
+//! resolved the same way Postgres resolves them in SQL: through the `search_path`, which
+//! includes the session's temporary schema, and with quoting and qualification parsed as
+//! identifiers.  [`lookup_relid`] does that, and [`OverrideSearchPath`] temporarily replaces the
+//! `search_path` so that code can't be tricked into resolving names in a schema an untrusted
+//! user controls.
+//!
+//! ```rust,no_run
+//! use pgrx::prelude::*;
+//! use pgrx::{lookup_relid, OverrideSearchPath};
+//!
+//! #[pg_extern]
+//! fn table_exists(name: &str) -> bool {
+//!     lookup_relid(name, pg_sys::NoLock as _, true).is_some()
+//! }
+//!
+//! #[pg_extern]
+//! fn count_rows_safely() -> Option<i64> {
+//!     // only pg_catalog is searched until `_path` is dropped
+//!     let _path = OverrideSearchPath::catalog_only();
+//!     Spi::get_one("SELECT count(*) FROM pg_class").unwrap()
+//! }
+//! ```
 
 use crate::list::PgList;
-use crate::pg_sys;
+use crate::{pg_sys, PgMemoryContexts};
+use core::ffi::CStr;
+use core::marker::PhantomData;
 use pgrx_pg_sys::AsPgCStr;
 
 /// A helper struct for creating a Postgres `List` of `String`s to qualify an object name
@@ -49,3 +75,147 @@ impl PgQualifiedNameBuilder {
         unsafe { pg_sys::OpernameGetOprid(self.list.into_pg(), lhs_type, rhs_type) }
     }
 }
+
+/// Replaces the `search_path` until it is dropped
+///
+/// Overrides nest: the innermost one applies until it's dropped, and they must be dropped in the
+/// reverse of the order they were created.  Postgres discards any left over at the end of the
+/// transaction, so one shouldn't be kept past it.
+#[must_use = "the search_path is restored when this is dropped"]
+pub struct OverrideSearchPath {
+    // pushed onto, and popped from, the backend's stack of overrides
+    _not_send: PhantomData<*mut ()>,
+}
+
+impl OverrideSearchPath {
+    /// Search only `schemas`, in order.  `pg_catalog` is searched first, as it is implicitly
+    /// unless it's listed, and the session's temporary schema isn't searched at all.
+    pub fn new(schemas: impl IntoIterator<Item = pg_sys::Oid>) -> Self {
+        Self::push(schemas, true, false)
+    }
+
+    /// Search only `schemas`, in order, after the session's temporary schema and `pg_catalog`,
+    /// as Postgres does for an ordinary `search_path`
+    pub fn with_temp(schemas: impl IntoIterator<Item = pg_sys::Oid>) -> Self {
+        Self::push(schemas, true, true)
+    }
+
+    /// Search only `pg_catalog`
+    pub fn catalog_only() -> Self {
+        Self::push([], true, false)
+    }
+
+    fn push(
+        schemas: impl IntoIterator<Item = pg_sys::Oid>,
+        add_catalog: bool,
+        add_temp: bool,
+    ) -> Self {
+        unsafe {
+            // SAFETY:  `PushOverrideSearchPath()` copies the path, so it can be freed with the
+            // current memory context
+            let path =
+                pg_sys::GetOverrideSearchPath(PgMemoryContexts::CurrentMemoryContext.value());
+            let mut list = std::ptr::null_mut();
+            for schema in schemas {
+                list = pg_sys::lappend_oid(list, schema);
+            }
+            (*path).schemas = list;
+            (*path).addCatalog = add_catalog;
+            (*path).addTemp = add_temp;
+            pg_sys::PushOverrideSearchPath(path);
+        }
+        OverrideSearchPath { _not_send: PhantomData }
+    }
+}
+
+impl Drop for OverrideSearchPath {
+    fn drop(&mut self) {
+        unsafe {
+            // SAFETY:  this override was pushed when `self` was created, and overrides are
+            // dropped in reverse order
+            pg_sys::PopOverrideSearchPath();
+        }
+    }
+}
+
+/// The schemas in the effective `search_path`, in the order they're searched.  With
+/// `include_implicit`, that includes `pg_catalog` and the session's temporary schema, if it has
+/// one, when the `search_path` doesn't list them.
+pub fn search_path(include_implicit: bool) -> Vec<pg_sys::Oid> {
+    unsafe {
+        // SAFETY:  fetch_search_path() returns a freshly allocated list of Oids
+        let list = PgList::<pg_sys::Oid>::from_pg(pg_sys::fetch_search_path(include_implicit));
+        list.iter_oid().collect()
+    }
+}
+
+/// The Oid of the schema named `name`, or `None` if there isn't one and `missing_ok` is set.
+/// Without `missing_ok`, a missing schema raises an ERROR.
+pub fn schema_oid(name: &str, missing_ok: bool) -> Option<pg_sys::Oid> {
+    let oid = unsafe { pg_sys::get_namespace_oid(name.as_pg_cstr(), missing_ok) };
+    (oid != pg_sys::InvalidOid).then_some(oid)
+}
+
+/// Resolve a relation name, which may be qualified and quoted as in SQL, such as
+/// `"My Schema".orders`, to its Oid, honoring the `search_path` and temporary tables as
+/// `'...'::regclass` does.  The relation is locked with `lockmode` once found.
+///
+/// If the relation doesn't exist, this returns `None` when `missing_ok` is set, and raises an
+/// ERROR otherwise.  A name which isn't a valid identifier always raises an ERROR.
+pub fn lookup_relid(
+    name: &str,
+    lockmode: pg_sys::LOCKMODE,
+    missing_ok: bool,
+) -> Option<pg_sys::Oid> {
+    unsafe {
+        #[cfg(not(feature = "pg16"))]
+        let names = pg_sys::stringToQualifiedNameList(name.as_pg_cstr());
+        #[cfg(feature = "pg16")]
+        let names = pg_sys::stringToQualifiedNameList(name.as_pg_cstr(), std::ptr::null_mut());
+        range_var_get_relid(pg_sys::makeRangeVarFromNameList(names), lockmode, missing_ok)
+    }
+}
+
+/// Like [`lookup_relid`], for a relation name and an optional schema name which are already
+/// separate, and aren't quoted.  With no schema, the `search_path` is used.
+pub fn lookup_qualified_relid(
+    schema: Option<&str>,
+    relname: &str,
+    lockmode: pg_sys::LOCKMODE,
+    missing_ok: bool,
+) -> Option<pg_sys::Oid> {
+    unsafe {
+        let schema = schema.map_or(std::ptr::null_mut(), |schema| schema.as_pg_cstr());
+        let rv = pg_sys::makeRangeVar(schema, relname.as_pg_cstr(), -1);
+        range_var_get_relid(rv, lockmode, missing_ok)
+    }
+}
+
+/// `RangeVarGetRelid()`
+///
+/// # Safety
+///
+/// `rv` must be a valid `RangeVar`
+pub unsafe fn range_var_get_relid(
+    rv: *const pg_sys::RangeVar,
+    lockmode: pg_sys::LOCKMODE,
+    missing_ok: bool,
+) -> Option<pg_sys::Oid> {
+    let flags = if missing_ok { pg_sys::RVROption_RVR_MISSING_OK } else { 0 };
+    let relid = pg_sys::RangeVarGetRelidExtended(rv, lockmode, flags, None, std::ptr::null_mut());
+    (relid != pg_sys::InvalidOid).then_some(relid)
+}
+
+/// The name of the schema whose Oid is `oid`, or `None` if there isn't one.  The session's
+/// temporary schema is named `pg_temp`, as in a `search_path`.
+pub fn schema_name(oid: pg_sys::Oid) -> Option<String> {
+    unsafe {
+        let name = pg_sys::get_namespace_name_or_temp(oid);
+        if name.is_null() {
+            return None;
+        }
+        let owned = CStr::from_ptr(name).to_string_lossy().into_owned();
+        pg_sys::pfree(name.cast());
+        Some(owned)
+    }
+}
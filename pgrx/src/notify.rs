@@ -0,0 +1,284 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Coalesced `NOTIFY`s, sent once per transaction just before it commits
+//!
+//! Issuing a `NOTIFY` for every row a trigger sees can flood the notification queue, and every
+//! listener with it.  A [`NotifyBatcher`] instead collects the payloads queued during a
+//! transaction, drops duplicates, and packs them into as few notifications as will fit within
+//! Postgres' payload size limit.  The notifications are only sent if the transaction commits,
+//! and payloads queued in a subtransaction that rolls back are forgotten, just like `NOTIFY`.
+use crate::callbacks::{
+    register_subxact_callback, register_xact_callback, PgSubXactCallbackEvent, PgXactCallbackEvent,
+};
+use crate::pg_sys;
+use crate::prelude::*;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::ffi::CString;
+
+/// The longest payload Postgres accepts for a single notification, in bytes
+pub const NOTIFY_PAYLOAD_MAX_LENGTH: usize = 7999;
+
+/// Collects `NOTIFY` payloads for a channel and sends them, coalesced, when the transaction commits.
+///
+/// Payloads are deduplicated, then joined with the [`NotifyBatcher::separator()`] into as few
+/// notifications as fit within [`NotifyBatcher::max_payload_len()`].  If that would still need
+/// more than [`NotifyBatcher::max_notifications()`] notifications, the excess is replaced by a single
+/// notification carrying the [`NotifyBatcher::overflow_payload()`], telling listeners that they
+/// missed something and should resynchronize.
+///
+/// A `NotifyBatcher` is only a configuration, so it's usually declared as a `static` and shared
+/// by every function that notifies on its channel.  The payloads themselves are tracked per
+/// backend and per transaction.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgrx::notify::NotifyBatcher;
+/// use pgrx::prelude::*;
+///
+/// static CHANGES: NotifyBatcher =
+///     NotifyBatcher::new("row_changes").separator(",").max_notifications(10);
+///
+/// #[pg_extern]
+/// fn row_changed(id: i64) {
+///     CHANGES.queue(&id.to_string());
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotifyBatcher {
+    channel: &'static str,
+    separator: &'static str,
+    max_payload_len: usize,
+    max_notifications: usize,
+    overflow_payload: &'static str,
+}
+
+impl NotifyBatcher {
+    /// Create a batcher for `channel`
+    pub const fn new(channel: &'static str) -> Self {
+        NotifyBatcher {
+            channel,
+            separator: "\n",
+            max_payload_len: NOTIFY_PAYLOAD_MAX_LENGTH,
+            max_notifications: usize::MAX,
+            overflow_payload: "*",
+        }
+    }
+
+    /// The string placed between payloads coalesced into one notification.  Defaults to `"\n"`.
+    ///
+    /// Listeners split notifications on this, so it shouldn't appear in any payload.
+    pub const fn separator(mut self, separator: &'static str) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    /// The largest notification payload to send, in bytes.  Defaults to, and can't be more than,
+    /// [`NOTIFY_PAYLOAD_MAX_LENGTH`].
+    pub const fn max_payload_len(mut self, max_payload_len: usize) -> Self {
+        self.max_payload_len = if max_payload_len > NOTIFY_PAYLOAD_MAX_LENGTH {
+            NOTIFY_PAYLOAD_MAX_LENGTH
+        } else {
+            max_payload_len
+        };
+        self
+    }
+
+    /// The most notifications to send on this channel per transaction, including the overflow
+    /// notification.  Defaults to unlimited.
+    pub const fn max_notifications(mut self, max_notifications: usize) -> Self {
+        self.max_notifications = if max_notifications == 0 { 1 } else { max_notifications };
+        self
+    }
+
+    /// The payload sent in place of the notifications beyond [`NotifyBatcher::max_notifications()`].
+    /// Defaults to `"*"`.
+    pub const fn overflow_payload(mut self, overflow_payload: &'static str) -> Self {
+        self.overflow_payload = overflow_payload;
+        self
+    }
+
+    /// The channel notifications are sent on
+    pub const fn channel(&self) -> &'static str {
+        self.channel
+    }
+
+    /// Queue `payload` to be sent when the current transaction commits.  Queuing a payload that's
+    /// already queued in this transaction does nothing.
+    ///
+    /// # Panics
+    ///
+    /// Raises an `ERROR` if called outside of a transaction, or if `payload` on its own is longer
+    /// than [`NotifyBatcher::max_payload_len()`].
+    pub fn queue(&self, payload: &str) {
+        if !unsafe { pg_sys::IsTransactionState() } {
+            ereport!(
+                PgLogLevel::ERROR,
+                PgSqlErrorCode::ERRCODE_INVALID_TRANSACTION_STATE,
+                "notifications can only be queued inside a transaction"
+            );
+        }
+        if payload.len() > self.max_payload_len {
+            ereport!(
+                PgLogLevel::ERROR,
+                PgSqlErrorCode::ERRCODE_INVALID_PARAMETER_VALUE,
+                format!(
+                    "payload for channel \"{}\" is {} bytes, but may be at most {}",
+                    self.channel,
+                    payload.len(),
+                    self.max_payload_len
+                )
+            );
+        }
+
+        let level = unsafe { pg_sys::GetCurrentTransactionNestLevel() };
+        PENDING.with(|pending| {
+            let mut pending = pending.borrow_mut();
+            let channels = pending.get_or_insert_with(|| {
+                register_callbacks();
+                Vec::new()
+            });
+            let channel = match channels.iter().position(|c| c.batcher == *self) {
+                Some(idx) => &mut channels[idx],
+                None => {
+                    channels.push(PendingChannel::new(*self));
+                    channels.last_mut().unwrap()
+                }
+            };
+            channel.push(payload, level);
+        })
+    }
+
+    /// The notifications that will be sent on this channel if the current transaction commits
+    /// now, in the order they'll be sent
+    pub fn pending_notifications(&self) -> Vec<String> {
+        PENDING.with(|pending| {
+            pending
+                .borrow()
+                .iter()
+                .flatten()
+                .find(|c| c.batcher == *self)
+                .map(PendingChannel::notifications)
+                .unwrap_or_default()
+        })
+    }
+}
+
+/// The payloads queued for one [`NotifyBatcher`] in the current transaction
+struct PendingChannel {
+    batcher: NotifyBatcher,
+    /// Each payload, in the order first queued, with the nesting level of the (sub)transaction
+    /// that queued it
+    payloads: Vec<(String, i32)>,
+    seen: HashSet<String>,
+}
+
+impl PendingChannel {
+    fn new(batcher: NotifyBatcher) -> Self {
+        PendingChannel { batcher, payloads: Vec::new(), seen: HashSet::new() }
+    }
+
+    fn push(&mut self, payload: &str, level: i32) {
+        // a payload already queued was queued at this level or by an ancestor, so it's at least
+        // as likely to be committed as this one
+        if self.seen.insert(payload.to_string()) {
+            self.payloads.push((payload.to_string(), level));
+        }
+    }
+
+    /// Forget the payloads queued by the aborted subtransaction at `level`, or its children
+    fn abort_sub(&mut self, level: i32) {
+        self.payloads.retain(|(_, queued)| *queued < level);
+        self.seen = self.payloads.iter().map(|(payload, _)| payload.clone()).collect();
+    }
+
+    /// Hand the payloads queued by the committed subtransaction at `level` to its parent
+    fn commit_sub(&mut self, level: i32) {
+        for (_, queued) in self.payloads.iter_mut() {
+            if *queued >= level {
+                *queued = level - 1;
+            }
+        }
+    }
+
+    fn notifications(&self) -> Vec<String> {
+        let NotifyBatcher {
+            separator, max_payload_len, max_notifications, overflow_payload, ..
+        } = self.batcher;
+
+        let mut notifications = Vec::new();
+        let mut current = String::new();
+        let mut is_empty = true;
+        for (payload, _) in &self.payloads {
+            if !is_empty && current.len() + separator.len() + payload.len() > max_payload_len {
+                notifications.push(std::mem::take(&mut current));
+                is_empty = true;
+            }
+            if !is_empty {
+                current.push_str(separator);
+            }
+            current.push_str(payload);
+            is_empty = false;
+        }
+        if !is_empty {
+            notifications.push(current);
+        }
+
+        if notifications.len() > max_notifications {
+            notifications.truncate(max_notifications - 1);
+            notifications.push(overflow_payload.to_string());
+        }
+        notifications
+    }
+}
+
+thread_local! {
+    /// The channels with payloads queued in the current transaction.  `None` until something is
+    /// queued, which is also when our transaction callbacks are registered.
+    static PENDING: RefCell<Option<Vec<PendingChannel>>> = RefCell::new(None);
+}
+
+fn register_callbacks() {
+    register_xact_callback(PgXactCallbackEvent::PreCommit, || {
+        let channels = PENDING.with(|pending| pending.borrow_mut().take()).unwrap_or_default();
+        for channel in channels {
+            let name = CString::new(channel.batcher.channel)
+                .expect("notification channel names may not contain a NUL");
+            for notification in channel.notifications() {
+                let payload = CString::new(notification)
+                    .expect("notification payloads may not contain a NUL");
+                unsafe {
+                    // SAFETY:  we're in a transaction that's about to commit, and both arguments
+                    // are valid, NUL-terminated strings that Async_Notify() copies
+                    pg_sys::Async_Notify(name.as_ptr(), payload.as_ptr());
+                }
+            }
+        }
+    });
+    register_xact_callback(PgXactCallbackEvent::Commit, || {
+        PENDING.with(|pending| pending.borrow_mut().take());
+    });
+    register_xact_callback(PgXactCallbackEvent::Abort, || {
+        PENDING.with(|pending| pending.borrow_mut().take());
+    });
+    register_subxact_callback(PgSubXactCallbackEvent::AbortSub, |_, _| {
+        let level = unsafe { pg_sys::GetCurrentTransactionNestLevel() };
+        PENDING.with(|pending| {
+            pending.borrow_mut().iter_mut().flatten().for_each(|c| c.abort_sub(level))
+        });
+    });
+    register_subxact_callback(PgSubXactCallbackEvent::CommitSub, |_, _| {
+        let level = unsafe { pg_sys::GetCurrentTransactionNestLevel() };
+        PENDING.with(|pending| {
+            pending.borrow_mut().iter_mut().flatten().for_each(|c| c.commit_sub(level))
+        });
+    });
+}
@@ -0,0 +1,256 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Datum, varlena and memory primitives which never panic, and never allocate unless asked to.
+//!
+//! Most of pgrx favors convenience: [`FromDatum`](crate::FromDatum) detoasts and transcodes into
+//! new memory as needed, and a malformed varlena or text that isn't valid UTF-8 is a `panic!()`,
+//! which becomes an `ERROR` at the nearest `#[pg_guard]`.  Code on a latency-critical path may
+//! instead want every way out of it spelled out in its types.  Everything here:
+//!
+//! * returns an [`Error`] rather than panicking.  The module is built with clippy's panicking
+//!   lints denied, so an `unwrap()` or an index which could panic fails the build rather than
+//!   waiting to be found in an audit;
+//! * only uses `core`.  The one function which allocates, [`try_palloc_in`], does so because it
+//!   was asked to, and reports running out of memory as an error rather than raising one;
+//! * only calls into Postgres in ways which can't raise an `ERROR`.
+//!
+//! A value which would first need detoasting or transcoding is reported as
+//! [`Error::Toasted`] or [`Error::NeedsTranscoding`], leaving the caller to decide whether to pay
+//! for that, with the rest of pgrx.
+//!
+//! ## Forbidding the panicking versions
+//!
+//! With pgrx's `forbid-panics` feature, release builds (those without `debug_assertions`) deprecate
+//! the functions in [`varlena`](crate::varlena) which can panic and which this module replaces,
+//! such as [`varsize_any`](crate::varsize_any).  An extension which also denies the `deprecated`
+//! lint then can't call them, while debug builds and tests are left alone:
+//!
+//! ```rust,ignore
+//! #![cfg_attr(not(debug_assertions), deny(deprecated))]
+//! ```
+#![deny(
+    clippy::panic,
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::indexing_slicing,
+    clippy::unreachable,
+    clippy::todo,
+    clippy::unimplemented
+)]
+
+use crate::memcxt::MemCx;
+use crate::pg_sys;
+use crate::varlena::{
+    try_vartag_size, varatt_is_1b, varatt_is_1b_e, varatt_is_b8_c, varsize_1b, varsize_4b,
+    vartag_external,
+};
+use core::ptr::NonNull;
+
+/// Why a [`panic_free`](self) primitive failed
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    #[error("expected a Datum of type oid {expected:?}, but it has type oid {actual:?}")]
+    IncompatibleTypes { expected: pg_sys::Oid, actual: pg_sys::Oid },
+    #[error("the varlena is toasted or compressed, and must be detoasted first")]
+    Toasted,
+    #[error("unrecognized TOAST vartag {0}")]
+    UnknownVartag(u8),
+    #[error("the varlena's size is smaller than its header")]
+    CorruptHeader,
+    #[error("the text is not valid UTF-8: {0}")]
+    InvalidUtf8(#[from] core::str::Utf8Error),
+    #[error("the text must be transcoded to UTF-8 from the database's encoding")]
+    NeedsTranscoding,
+    #[error("{0} bytes is more than Postgres can allocate at once")]
+    InvalidAllocSize(usize),
+    #[error("out of memory allocating {0} bytes")]
+    OutOfMemory(usize),
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// `MaxAllocSize` from `utils/memutils.h`
+const MAX_ALLOC_SIZE: usize = 0x3fff_ffff;
+
+/// The total size of a varlena, including its header, like `VARSIZE_ANY()`.
+///
+/// # Safety
+///
+/// `ptr` must point to a varlena's header
+#[inline]
+pub unsafe fn varsize_any(ptr: *const pg_sys::varlena) -> Result<usize> {
+    if varatt_is_1b_e(ptr) {
+        let tag = vartag_external(ptr);
+        try_vartag_size(tag as pg_sys::vartag_external)
+            .map(|size| pg_sys::VARHDRSZ_EXTERNAL() + size)
+            .ok_or(Error::UnknownVartag(tag))
+    } else if varatt_is_1b(ptr) {
+        Ok(varsize_1b(ptr))
+    } else {
+        Ok(varsize_4b(ptr))
+    }
+}
+
+/// The data of a varlena which is stored in line and uncompressed, like `VARDATA_ANY()` and
+/// `VARSIZE_ANY_EXHDR()` together, or [`Error::Toasted`] for anything else.
+///
+/// # Safety
+///
+/// `ptr` must point to a whole varlena, which must not be freed or changed for as long as `'a`
+#[inline]
+pub unsafe fn varlena_data<'a>(ptr: *const pg_sys::varlena) -> Result<&'a [u8]> {
+    let (header, size) = if varatt_is_1b_e(ptr) || varatt_is_b8_c(ptr) {
+        return Err(Error::Toasted);
+    } else if varatt_is_1b(ptr) {
+        (pg_sys::VARHDRSZ_SHORT(), varsize_1b(ptr))
+    } else {
+        (pg_sys::VARHDRSZ, varsize_4b(ptr))
+    };
+    let len = size.checked_sub(header).ok_or(Error::CorruptHeader)?;
+    Ok(core::slice::from_raw_parts(ptr.cast::<u8>().add(header), len))
+}
+
+/// The text of a varlena which is stored in line and uncompressed.
+///
+/// In a database whose encoding isn't UTF-8, text with any non-ASCII characters is
+/// [`Error::NeedsTranscoding`], as transcoding it would allocate.
+///
+/// # Safety
+///
+/// The same as for [`varlena_data`]
+#[inline]
+pub unsafe fn text_to_str<'a>(ptr: *const pg_sys::varlena) -> Result<&'a str> {
+    let bytes = varlena_data(ptr)?;
+    let encoding = pg_sys::GetDatabaseEncoding() as pg_sys::pg_enc;
+    if encoding == pg_sys::pg_enc_PG_UTF8 || encoding == pg_sys::pg_enc_PG_SQL_ASCII {
+        Ok(core::str::from_utf8(bytes)?)
+    } else if bytes.is_ascii() {
+        // every server encoding other than UTF-8 extends ASCII
+        Ok(core::str::from_utf8_unchecked(bytes))
+    } else {
+        Err(Error::NeedsTranscoding)
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A type Postgres passes by value in a Datum, so reading it from one needs nothing but a check
+/// of the Datum's type
+pub trait ByValue: Copy + sealed::Sealed {
+    /// The Postgres type this is read from
+    const TYPE_OID: pg_sys::Oid;
+
+    #[doc(hidden)]
+    fn from_datum_value(datum: pg_sys::Datum) -> Self;
+}
+
+macro_rules! by_value {
+    ($($ty:ty => $oid:ident, |$datum:ident| $convert:expr;)+) => {
+        $(
+            impl sealed::Sealed for $ty {}
+
+            impl ByValue for $ty {
+                const TYPE_OID: pg_sys::Oid = pg_sys::$oid;
+
+                #[inline]
+                fn from_datum_value($datum: pg_sys::Datum) -> Self {
+                    $convert
+                }
+            }
+        )+
+    };
+}
+
+by_value! {
+    bool => BOOLOID, |datum| datum.value() != 0;
+    i16 => INT2OID, |datum| datum.value() as i16;
+    i32 => INT4OID, |datum| datum.value() as i32;
+    i64 => INT8OID, |datum| datum.value() as i64;
+    f32 => FLOAT4OID, |datum| f32::from_bits(datum.value() as u32);
+    f64 => FLOAT8OID, |datum| f64::from_bits(datum.value() as u64);
+}
+
+/// Read a [`ByValue`] type from a Datum of exactly its Postgres type, or `None` if it's NULL
+#[inline]
+pub fn by_value<T: ByValue>(
+    datum: pg_sys::Datum,
+    is_null: bool,
+    type_oid: pg_sys::Oid,
+) -> Result<Option<T>> {
+    if type_oid != T::TYPE_OID {
+        return Err(Error::IncompatibleTypes { expected: T::TYPE_OID, actual: type_oid });
+    }
+    Ok((!is_null).then(|| T::from_datum_value(datum)))
+}
+
+/// Borrow the text of a `text` or `varchar` Datum, or `None` if it's NULL.  See [`text_to_str`].
+///
+/// # Safety
+///
+/// Unless `is_null`, `datum` must be a varlena, which must not be freed or changed for as long as
+/// `'a`
+#[inline]
+pub unsafe fn text<'a>(
+    datum: pg_sys::Datum,
+    is_null: bool,
+    type_oid: pg_sys::Oid,
+) -> Result<Option<&'a str>> {
+    if type_oid != pg_sys::TEXTOID && type_oid != pg_sys::VARCHAROID {
+        return Err(Error::IncompatibleTypes { expected: pg_sys::TEXTOID, actual: type_oid });
+    }
+    if is_null {
+        return Ok(None);
+    }
+    text_to_str(datum.cast_mut_ptr()).map(Some)
+}
+
+/// Borrow the bytes of a `bytea` Datum, or `None` if it's NULL.  See [`varlena_data`].
+///
+/// # Safety
+///
+/// The same as for [`text`]
+#[inline]
+pub unsafe fn bytea<'a>(
+    datum: pg_sys::Datum,
+    is_null: bool,
+    type_oid: pg_sys::Oid,
+) -> Result<Option<&'a [u8]>> {
+    if type_oid != pg_sys::BYTEAOID {
+        return Err(Error::IncompatibleTypes { expected: pg_sys::BYTEAOID, actual: type_oid });
+    }
+    if is_null {
+        return Ok(None);
+    }
+    varlena_data(datum.cast_mut_ptr()).map(Some)
+}
+
+/// Allocate `size` zeroed bytes in `mcx`, returning an error rather than raising an `ERROR` if
+/// Postgres can't.
+#[inline]
+pub fn try_palloc_in<'mcx>(mcx: &MemCx<'mcx>, size: usize) -> Result<&'mcx mut [u8]> {
+    if size > MAX_ALLOC_SIZE {
+        return Err(Error::InvalidAllocSize(size));
+    }
+    unsafe {
+        // SAFETY:  with `MCXT_ALLOC_NO_OOM`, and `size` within `MaxAllocSize`, this can only fail
+        // by returning null
+        let ptr = pg_sys::MemoryContextAllocExtended(
+            mcx.value(),
+            size,
+            (pg_sys::MCXT_ALLOC_NO_OOM | pg_sys::MCXT_ALLOC_ZERO) as _,
+        );
+        // SAFETY:  the allocation is `size` bytes, all zeroed, and lasts as long as `mcx`
+        NonNull::new(ptr.cast::<u8>())
+            .map(|ptr| core::slice::from_raw_parts_mut(ptr.as_ptr(), size))
+            .ok_or(Error::OutOfMemory(size))
+    }
+}
@@ -0,0 +1,28 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! The version of Postgres pgrx was compiled for.
+//!
+//! To compile different code for different versions, see [`pg_version_at_least!`] and
+//! [`pg_version_below!`].
+//!
+//! [`pg_version_at_least!`]: macro@crate::pg_version_at_least
+//! [`pg_version_below!`]: macro@crate::pg_version_below
+use crate::pg_sys;
+
+/// The Postgres major version, such as `15`
+pub const MAJOR: u16 = (pg_sys::PG_VERSION_NUM / 10000) as u16;
+
+/// The full Postgres version as a number, such as `150003` for 15.3
+pub const VERSION_NUM: u32 = pg_sys::PG_VERSION_NUM;
+
+/// Is the Postgres major version at least `major`?
+pub const fn at_least(major: u16) -> bool {
+    MAJOR >= major
+}
@@ -172,6 +172,7 @@ impl<T, AllocatedBy: WhoAllocated> PgBox<T, AllocatedBy> {
     /// be a valid state for `T`.
     #[inline]
     pub unsafe fn alloc() -> PgBox<T, AllocatedByRust> {
+        crate::fault_injection::fault_point(crate::fault_injection::ALLOC);
         PgBox::<T, AllocatedByRust> {
             ptr: Some(unsafe {
                 NonNull::new_unchecked(pg_sys::palloc(std::mem::size_of::<T>()) as *mut T)
@@ -202,6 +203,7 @@ impl<T, AllocatedBy: WhoAllocated> PgBox<T, AllocatedBy> {
     /// be a valid state for `T`.
     #[inline]
     pub unsafe fn alloc0() -> PgBox<T, AllocatedByRust> {
+        crate::fault_injection::fault_point(crate::fault_injection::ALLOC);
         PgBox::<T, AllocatedByRust> {
             ptr: Some(unsafe {
                 NonNull::new_unchecked(pg_sys::palloc0(std::mem::size_of::<T>()) as *mut T)
@@ -232,6 +234,7 @@ impl<T, AllocatedBy: WhoAllocated> PgBox<T, AllocatedBy> {
     /// be a valid state for `T`.
     #[inline]
     pub unsafe fn alloc_in_context(memory_context: PgMemoryContexts) -> PgBox<T, AllocatedByRust> {
+        crate::fault_injection::fault_point(crate::fault_injection::ALLOC);
         PgBox::<T, AllocatedByRust> {
             ptr: Some(unsafe {
                 NonNull::new_unchecked(pg_sys::MemoryContextAlloc(
@@ -265,6 +268,7 @@ impl<T, AllocatedBy: WhoAllocated> PgBox<T, AllocatedBy> {
     /// be a valid state for `T`.
     #[inline]
     pub unsafe fn alloc0_in_context(memory_context: PgMemoryContexts) -> PgBox<T, AllocatedByRust> {
+        crate::fault_injection::fault_point(crate::fault_injection::ALLOC);
         PgBox::<T, AllocatedByRust> {
             ptr: Some(unsafe {
                 NonNull::new_unchecked(pg_sys::MemoryContextAllocZero(
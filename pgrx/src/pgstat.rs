@@ -0,0 +1,191 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Safe wrappers around Postgres' relation-level cumulative statistics reporting
+//!
+//! Custom table access methods and foreign data wrappers don't go through `heapam`, so unless they
+//! report what they did, `pg_stat_*_tables`, autovacuum, and the planner never learn about it.
+//! These functions mirror the `pgstat_count_*()` functions and macros from `pgstat.h`.
+//!
+//! Counts are accumulated in backend-local memory and are flushed to the cumulative statistics
+//! system by Postgres itself at the end of the transaction.
+use crate::{pg_sys, PgRelation};
+
+/// A `pgstat` counter value
+pub type PgStatCounter = pg_sys::PgStat_Counter;
+
+/// Returns the backend-local counters for the relation, if statistics are being collected for it.
+///
+/// This is `pgstat_should_count_relation()` from `pgstat.h`
+#[inline]
+unsafe fn table_counts<'a>(rel: &'a PgRelation) -> Option<&'a mut pg_sys::PgStat_TableCounts> {
+    let rel = rel.as_ptr();
+
+    #[cfg(any(feature = "pg15", feature = "pg16"))]
+    if (*rel).pgstat_info.is_null() && (*rel).pgstat_enabled {
+        pg_sys::pgstat_assoc_relation(rel);
+    }
+
+    let info = (*rel).pgstat_info.as_mut()?;
+
+    #[cfg(not(feature = "pg16"))]
+    {
+        Some(&mut info.t_counts)
+    }
+
+    #[cfg(feature = "pg16")]
+    {
+        Some(&mut info.counts)
+    }
+}
+
+/// Postgres v16 dropped the `t_` prefix from the `PgStat_TableCounts` fields
+macro_rules! bump_counter {
+    ($rel:expr, $old_field:ident, $new_field:ident, $n:expr) => {
+        // SAFETY:  `$rel` is a valid, open relation
+        if let Some(counts) = unsafe { table_counts($rel) } {
+            #[cfg(not(feature = "pg16"))]
+            {
+                counts.$old_field += $n;
+            }
+            #[cfg(feature = "pg16")]
+            {
+                counts.$new_field += $n;
+            }
+        }
+    };
+}
+
+/// Count a sequential or index scan of `rel`.  This is `pgstat_count_heap_scan()`
+#[inline]
+pub fn count_heap_scan(rel: &PgRelation) {
+    bump_counter!(rel, t_numscans, numscans, 1);
+}
+
+/// Count a tuple returned by a scan of `rel`.  This is `pgstat_count_heap_getnext()`
+#[inline]
+pub fn count_heap_getnext(rel: &PgRelation) {
+    bump_counter!(rel, t_tuples_returned, tuples_returned, 1);
+}
+
+/// Count a live tuple fetched from `rel` by tid.  This is `pgstat_count_heap_fetch()`
+#[inline]
+pub fn count_heap_fetch(rel: &PgRelation) {
+    bump_counter!(rel, t_tuples_fetched, tuples_fetched, 1);
+}
+
+/// Count a scan of the index `rel`.  This is `pgstat_count_index_scan()`
+#[inline]
+pub fn count_index_scan(rel: &PgRelation) {
+    bump_counter!(rel, t_numscans, numscans, 1);
+}
+
+/// Count `n` index entries returned by a scan of the index `rel`.  This is `pgstat_count_index_tuples()`
+#[inline]
+pub fn count_index_tuples(rel: &PgRelation, n: PgStatCounter) {
+    bump_counter!(rel, t_tuples_returned, tuples_returned, n);
+}
+
+/// Count `n` block reads of `rel`.  This is `pgstat_count_buffer_read()`
+#[inline]
+pub fn count_buffer_read(rel: &PgRelation, n: PgStatCounter) {
+    bump_counter!(rel, t_blocks_fetched, blocks_fetched, n);
+}
+
+/// Count `n` buffer hits against `rel`.  This is `pgstat_count_buffer_hit()`
+#[inline]
+pub fn count_buffer_hit(rel: &PgRelation, n: PgStatCounter) {
+    bump_counter!(rel, t_blocks_hit, blocks_hit, n);
+}
+
+/// Count `n` tuples inserted into `rel`
+///
+/// The count is transactional:  if the current (sub)transaction aborts, the inserted tuples are
+/// counted as dead instead.
+#[inline]
+pub fn count_heap_insert(rel: &PgRelation, n: PgStatCounter) {
+    unsafe {
+        // SAFETY:  rel is a valid, open relation
+        pg_sys::pgstat_count_heap_insert(rel.as_ptr(), n)
+    }
+}
+
+/// Count a tuple updated in `rel`
+///
+/// `hot` indicates the update was a heap-only-tuple update.  `newpage` indicates the new tuple
+/// version went to a different page and is only tracked by Postgres v16 and later.
+#[inline]
+#[allow(unused_variables)]
+pub fn count_heap_update(rel: &PgRelation, hot: bool, newpage: bool) {
+    unsafe {
+        // SAFETY:  rel is a valid, open relation
+        #[cfg(not(feature = "pg16"))]
+        pg_sys::pgstat_count_heap_update(rel.as_ptr(), hot);
+
+        #[cfg(feature = "pg16")]
+        pg_sys::pgstat_count_heap_update(rel.as_ptr(), hot, newpage);
+    }
+}
+
+/// Count a tuple deleted from `rel`
+#[inline]
+pub fn count_heap_delete(rel: &PgRelation) {
+    unsafe {
+        // SAFETY:  rel is a valid, open relation
+        pg_sys::pgstat_count_heap_delete(rel.as_ptr())
+    }
+}
+
+/// Record that `rel` was truncated, which resets its live/dead tuple counts at commit
+#[inline]
+pub fn count_truncate(rel: &PgRelation) {
+    unsafe {
+        // SAFETY:  rel is a valid, open relation
+        pg_sys::pgstat_count_truncate(rel.as_ptr())
+    }
+}
+
+/// Adjust the dead tuple count of `rel` by `delta`, such as after pruning dead tuples outside of
+/// `VACUUM`.  This is `pgstat_update_heap_dead_tuples()`
+#[inline]
+pub fn update_heap_dead_tuples(rel: &PgRelation, delta: i32) {
+    unsafe {
+        // SAFETY:  rel is a valid, open relation
+        pg_sys::pgstat_update_heap_dead_tuples(rel.as_ptr(), delta)
+    }
+}
+
+/// Report the result of an `ANALYZE`-like pass over `rel`, with the estimated number of live and
+/// dead tuples.
+///
+/// If `reset_counter` is true, the "changes since last analyze" counter that drives autovacuum's
+/// auto-analyze decisions is reset.
+#[inline]
+pub fn report_analyze(
+    rel: &PgRelation,
+    live_tuples: PgStatCounter,
+    dead_tuples: PgStatCounter,
+    reset_counter: bool,
+) {
+    unsafe {
+        // SAFETY:  rel is a valid, open relation
+        pg_sys::pgstat_report_analyze(rel.as_ptr(), live_tuples, dead_tuples, reset_counter)
+    }
+}
+
+/// Report the result of a `VACUUM`-like pass over `rel`, with the number of live and dead tuples
+/// it left behind
+#[inline]
+pub fn report_vacuum(rel: &PgRelation, live_tuples: PgStatCounter, dead_tuples: PgStatCounter) {
+    let shared = unsafe { rel.rd_rel.as_ref() }.expect("rd_rel is NULL").relisshared;
+    unsafe {
+        // SAFETY:  the arguments come from a valid, open relation
+        pg_sys::pgstat_report_vacuum(rel.oid(), shared, live_tuples, dead_tuples)
+    }
+}
@@ -15,13 +15,13 @@ pub use ::pgrx_pg_sys as pg_sys;
 pub use crate::pg_module_magic;
 
 // Necessary local macros:
-pub use crate::{default, name};
+pub use crate::{default, domain, name};
 
 // Needed for variant RETURNS
 pub use crate::iter::{SetOfIterator, TableIterator};
 
 // Needed for complex returns and Triggers
-pub use crate::heap_tuple::{PgHeapTuple, PgHeapTupleError};
+pub use crate::heap_tuple::{PgHeapTuple, PgHeapTupleError, PostgresComposite, RecordSet};
 pub use crate::pgbox::{AllocatedByPostgres, AllocatedByRust, PgBox, WhoAllocated};
 
 // These could be factored into a temporal type module that could be easily imported for code which works with them.
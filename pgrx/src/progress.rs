@@ -0,0 +1,230 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Progress reporting and cooperative cancellation for long-running functions
+//!
+//! A [`ProgressGuard`] is created at the start of a long, maintenance-style operation and handed
+//! to its loops.  It reports progress through the same machinery as `VACUUM` and `CREATE INDEX`,
+//! so monitoring can see it with `pg_stat_get_progress_info()`, checks for query cancellation and
+//! backend termination on every [`tick`](ProgressGuard::tick) and
+//! [`update`](ProgressGuard::update), and keeps track of how long the operation has been running.
+//!
+//! ```rust,no_run
+//! use pgrx::prelude::*;
+//! use pgrx::progress::{ProgressCommand, ProgressGuard};
+//!
+//! #[pg_extern]
+//! fn rebuild_everything(relid: pg_sys::Oid) {
+//!     let mut progress = ProgressGuard::start(ProgressCommand::Vacuum, relid).elapsed_param(0);
+//!     for block in 0..1000i64 {
+//!         // ... do some work ...
+//!         progress.update(1, block);
+//!     }
+//! }
+//! ```
+//!
+//! The reported values are visible to other sessions as
+//!
+//! ```sql
+//! SELECT pid, relid, param1 AS elapsed_ms, param2 AS blocks_done
+//!   FROM pg_stat_get_progress_info('VACUUM');
+//! ```
+//!
+//! A backend reports progress for one command at a time.  The guard ends the report when it's
+//! dropped, and Postgres ends it if the transaction aborts.
+use crate::pg_sys;
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+/// The command a [`ProgressGuard`] reports progress as, which is what
+/// `pg_stat_get_progress_info()` is asked for.
+///
+/// Postgres has no progress command for extensions, so an extension borrows the one closest to
+/// what it's doing.  Its own `pg_stat_progress_*` view will misread the parameters, so monitor the
+/// operation with `pg_stat_get_progress_info()` instead.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ProgressCommand {
+    /// Reported as `'VACUUM'`
+    Vacuum,
+    /// Reported as `'ANALYZE'`
+    #[cfg(any(feature = "pg13", feature = "pg14", feature = "pg15", feature = "pg16"))]
+    Analyze,
+    /// Reported as `'CLUSTER'`
+    #[cfg(any(
+        feature = "pg12",
+        feature = "pg13",
+        feature = "pg14",
+        feature = "pg15",
+        feature = "pg16"
+    ))]
+    Cluster,
+    /// Reported as `'CREATE INDEX'`
+    #[cfg(any(
+        feature = "pg12",
+        feature = "pg13",
+        feature = "pg14",
+        feature = "pg15",
+        feature = "pg16"
+    ))]
+    CreateIndex,
+    /// Reported as `'COPY'`
+    #[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+    Copy,
+}
+
+impl ProgressCommand {
+    fn command_type(self) -> pg_sys::ProgressCommandType {
+        match self {
+            ProgressCommand::Vacuum => pg_sys::ProgressCommandType_PROGRESS_COMMAND_VACUUM,
+            #[cfg(any(feature = "pg13", feature = "pg14", feature = "pg15", feature = "pg16"))]
+            ProgressCommand::Analyze => pg_sys::ProgressCommandType_PROGRESS_COMMAND_ANALYZE,
+            #[cfg(any(
+                feature = "pg12",
+                feature = "pg13",
+                feature = "pg14",
+                feature = "pg15",
+                feature = "pg16"
+            ))]
+            ProgressCommand::Cluster => pg_sys::ProgressCommandType_PROGRESS_COMMAND_CLUSTER,
+            #[cfg(any(
+                feature = "pg12",
+                feature = "pg13",
+                feature = "pg14",
+                feature = "pg15",
+                feature = "pg16"
+            ))]
+            ProgressCommand::CreateIndex => {
+                pg_sys::ProgressCommandType_PROGRESS_COMMAND_CREATE_INDEX
+            }
+            #[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+            ProgressCommand::Copy => pg_sys::ProgressCommandType_PROGRESS_COMMAND_COPY,
+        }
+    }
+}
+
+/// How many progress parameters a backend can report, numbered from zero.  These are the `param1`
+/// through `paramN` columns of `pg_stat_get_progress_info()`.
+pub const NUM_PROGRESS_PARAMS: usize = pg_sys::PGSTAT_NUM_PROGRESS_PARAM as usize;
+
+/// Reports the progress of a long-running operation and checks for interrupts as it goes.
+///
+/// See the [module documentation](self).
+pub struct ProgressGuard {
+    started: Instant,
+    elapsed_param: Option<usize>,
+    // progress is reported for this backend, so the guard must stay on its thread
+    __marker: PhantomData<*mut ()>,
+}
+
+impl ProgressGuard {
+    /// Start reporting progress as `command`, on the relation `relid`.  `relid` may be
+    /// [`pg_sys::InvalidOid`] if the operation isn't about a single relation.
+    ///
+    /// This replaces any progress report this backend was already making.
+    pub fn start(command: ProgressCommand, relid: pg_sys::Oid) -> Self {
+        unsafe {
+            // SAFETY:  this only writes to this backend's own status entry
+            pg_sys::pgstat_progress_start_command(command.command_type(), relid);
+        }
+        ProgressGuard { started: Instant::now(), elapsed_param: None, __marker: PhantomData }
+    }
+
+    /// Also report the elapsed time, in milliseconds, as parameter `param` whenever the guard
+    /// [`tick`](Self::tick)s or [`update`](Self::update)s.
+    ///
+    /// # Panics
+    ///
+    /// If `param` isn't less than [`NUM_PROGRESS_PARAMS`]
+    pub fn elapsed_param(mut self, param: usize) -> Self {
+        check_param(param);
+        self.elapsed_param = Some(param);
+        self.report_elapsed();
+        self
+    }
+
+    /// How long it's been since the guard was started
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Check for interrupts, and update the elapsed time if it's being reported.
+    ///
+    /// Call this regularly from loops that don't otherwise [`update`](Self::update).  If the query
+    /// has been canceled or the backend is being terminated, this raises the corresponding ERROR.
+    pub fn tick(&mut self) {
+        pg_sys::check_for_interrupts!();
+        self.report_elapsed();
+    }
+
+    /// Set progress parameter `param` to `value`, then [`tick`](Self::tick)
+    ///
+    /// # Panics
+    ///
+    /// If `param` isn't less than [`NUM_PROGRESS_PARAMS`]
+    pub fn update(&mut self, param: usize, value: i64) {
+        check_param(param);
+        unsafe {
+            // SAFETY:  `param` is in bounds, and this only writes to this backend's status entry
+            pg_sys::pgstat_progress_update_param(param as _, value);
+        }
+        self.tick();
+    }
+
+    /// Set several progress parameters at once, so that readers see them change together, then
+    /// [`tick`](Self::tick)
+    ///
+    /// # Panics
+    ///
+    /// If any parameter isn't less than [`NUM_PROGRESS_PARAMS`]
+    pub fn update_many(&mut self, values: &[(usize, i64)]) {
+        let (params, values): (Vec<_>, Vec<_>) = values
+            .iter()
+            .map(|&(param, value)| {
+                check_param(param);
+                (param as std::os::raw::c_int, value)
+            })
+            .unzip();
+        unsafe {
+            // SAFETY:  the parameters are in bounds, and both arrays are the same length
+            pg_sys::pgstat_progress_update_multi_param(
+                params.len() as _,
+                params.as_ptr(),
+                values.as_ptr(),
+            );
+        }
+        self.tick();
+    }
+
+    fn report_elapsed(&self) {
+        if let Some(param) = self.elapsed_param {
+            let millis = self.elapsed().as_millis().try_into().unwrap_or(i64::MAX);
+            unsafe {
+                // SAFETY:  `param` was checked by `elapsed_param()`
+                pg_sys::pgstat_progress_update_param(param as _, millis);
+            }
+        }
+    }
+}
+
+impl Drop for ProgressGuard {
+    fn drop(&mut self) {
+        unsafe {
+            // SAFETY:  ending a progress report that was already ended does nothing
+            pg_sys::pgstat_progress_end_command();
+        }
+    }
+}
+
+#[inline]
+fn check_param(param: usize) {
+    assert!(
+        param < NUM_PROGRESS_PARAMS,
+        "progress parameter {param} is out of range, there are only {NUM_PROGRESS_PARAMS}"
+    );
+}
@@ -9,7 +9,8 @@
 //LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
 //! Provides a safe wrapper around Postgres' `pg_sys::RelationData` struct
 use crate::{
-    direct_function_call, name_data_to_str, pg_sys, FromDatum, IntoDatum, PgBox, PgTupleDesc,
+    direct_function_call, name_data_to_str, pg_sys, FromDatum, IntoDatum, IntoRelationOid, PgBox,
+    PgTupleDesc, RelationOid,
 };
 use pgrx_sql_entity_graph::metadata::{
     ArgumentError, Returns, ReturnsError, SqlMapping, SqlTranslatable,
@@ -56,7 +57,8 @@ impl PgRelation {
     /// nasty race conditions.
     ///
     /// As such, this function is unsafe as we cannot guarantee that this requirement is true.
-    pub unsafe fn open(oid: pg_sys::Oid) -> Self {
+    pub unsafe fn open(oid: impl IntoRelationOid) -> Self {
+        let oid = oid.into_relation_oid().as_oid();
         let rel = pg_sys::RelationIdGetRelation(oid);
         if rel.is_null() {
             // relation was recently deleted
@@ -84,10 +86,13 @@ impl PgRelation {
     /// The opened relation is automatically closed via `pg_sys::relation_close()`
     /// when this instance is dropped
     #[deny(unsafe_op_in_unsafe_fn)]
-    pub unsafe fn with_lock(oid: pg_sys::Oid, lockmode: pg_sys::LOCKMODE) -> Self {
+    pub unsafe fn with_lock(oid: impl IntoRelationOid, lockmode: pg_sys::LOCKMODE) -> Self {
         unsafe {
             PgRelation {
-                boxed: PgBox::from_pg(pg_sys::relation_open(oid, lockmode)),
+                boxed: PgBox::from_pg(pg_sys::relation_open(
+                    oid.into_relation_oid().as_oid(),
+                    lockmode,
+                )),
                 need_close: true,
                 lockmode: Some(lockmode),
             }
@@ -111,7 +116,7 @@ impl PgRelation {
     ///
     /// As such, this function is unsafe as we cannot guarantee that this requirement is true.
     pub unsafe fn open_with_name(relname: &str) -> std::result::Result<Self, &'static str> {
-        match direct_function_call::<pg_sys::Oid>(pg_sys::to_regclass, &[relname.into_datum()]) {
+        match direct_function_call::<RelationOid>(pg_sys::to_regclass, &[relname.into_datum()]) {
             Some(oid) => Ok(PgRelation::open(oid)),
             None => Err("no such relation"),
         }
@@ -128,7 +133,7 @@ impl PgRelation {
     /// dropped.
     pub fn open_with_name_and_share_lock(relname: &str) -> std::result::Result<Self, &'static str> {
         unsafe {
-            match direct_function_call::<pg_sys::Oid>(pg_sys::to_regclass, &[relname.into_datum()])
+            match direct_function_call::<RelationOid>(pg_sys::to_regclass, &[relname.into_datum()])
             {
                 Some(oid) => {
                     Ok(PgRelation::with_lock(oid, pg_sys::AccessShareLock as pg_sys::LOCKMODE))
@@ -155,6 +160,12 @@ impl PgRelation {
         rel.rd_id
     }
 
+    /// The OID of the relation, as a [`RelationOid`]
+    #[inline]
+    pub fn relation_oid(&self) -> RelationOid {
+        RelationOid::from_raw_unchecked(self.oid())
+    }
+
     /// RelationGetNamespace
     ///            Returns the rel's namespace OID.
     pub fn namespace_oid(&self) -> pg_sys::Oid {
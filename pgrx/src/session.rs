@@ -0,0 +1,109 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Typed accessors for information about the current backend and its client session
+//!
+//! These wrap the various Postgres globals (`debug_query_string`, `MyProcPid`, `MyDatabaseId`,
+//! the `application_name` GUC, etc) and the SQL-level functions that read `MyProcPort`, so that
+//! logging and auditing extensions don't need to declare their own externs.
+use crate::{direct_function_call, pg_sys, Inet};
+use core::ffi::CStr;
+
+/// The text of the top-level statement the backend is currently executing, if any.
+///
+/// This is the same value `current_query()` returns in SQL.  It may contain multiple statements
+/// if the client sent them in a single query string.
+pub fn current_query() -> Option<String> {
+    unsafe {
+        // SAFETY:  debug_query_string is either NULL or a valid C string for the duration of the
+        // current statement, and we copy it out before returning
+        let query = pg_sys::debug_query_string;
+        (!query.is_null()).then(|| CStr::from_ptr(query).to_string_lossy().into_owned())
+    }
+}
+
+/// The current value of the `application_name` GUC, if it's set
+pub fn application_name() -> Option<String> {
+    unsafe {
+        // SAFETY:  application_name is either NULL or a valid C string owned by the GUC system
+        let name = pg_sys::application_name;
+        if name.is_null() || *name == 0 {
+            None
+        } else {
+            Some(CStr::from_ptr(name).to_string_lossy().into_owned())
+        }
+    }
+}
+
+/// The IP address of the connected client.
+///
+/// Returns `None` if the client connected through a Unix-domain socket, or if this backend has no
+/// client at all, such as in a background worker.  This is `inet_client_addr()` in SQL.
+pub fn client_addr() -> Option<Inet> {
+    unsafe {
+        // SAFETY:  inet_client_addr() takes no arguments and returns NULL if there's no client
+        direct_function_call::<Inet>(pg_sys::inet_client_addr, &[])
+    }
+}
+
+/// The port number of the connected client.
+///
+/// Returns `None` under the same conditions as [`client_addr()`].  This is `inet_client_port()` in SQL.
+pub fn client_port() -> Option<i32> {
+    unsafe {
+        // SAFETY:  inet_client_port() takes no arguments and returns NULL if there's no client
+        direct_function_call::<i32>(pg_sys::inet_client_port, &[])
+    }
+}
+
+/// The process id of this backend.  This is `pg_backend_pid()` in SQL.
+#[inline]
+pub fn backend_pid() -> i32 {
+    unsafe {
+        // SAFETY:  MyProcPid is set during process startup, well before any extension code runs
+        pg_sys::MyProcPid
+    }
+}
+
+/// The oid of the database this backend is connected to.
+///
+/// This is `InvalidOid` in a background worker that hasn't connected to a database.
+#[inline]
+pub fn database_oid() -> pg_sys::Oid {
+    unsafe {
+        // SAFETY:  MyDatabaseId is a plain value set once the backend connects
+        pg_sys::MyDatabaseId
+    }
+}
+
+/// The name of the database this backend is connected to, if it's connected to one.
+/// This is `current_database()` in SQL.
+pub fn database_name() -> Option<String> {
+    let oid = database_oid();
+    if oid == pg_sys::InvalidOid {
+        return None;
+    }
+
+    unsafe {
+        // SAFETY:  get_database_name() returns NULL if the database doesn't exist, otherwise a
+        // palloc'd C string
+        let name = pg_sys::get_database_name(oid);
+        (!name.is_null()).then(|| CStr::from_ptr(name).to_string_lossy().into_owned())
+    }
+}
+
+/// The name of the user whose privileges are currently in effect.  This is `current_user` in SQL.
+pub fn user_name() -> String {
+    unsafe {
+        // SAFETY:  GetUserId() always returns a valid role and GetUserNameFromId() raises an
+        // ERROR (rather than returning NULL) when `noerr` is false
+        let name = pg_sys::GetUserNameFromId(pg_sys::GetUserId(), false);
+        CStr::from_ptr(name).to_string_lossy().into_owned()
+    }
+}
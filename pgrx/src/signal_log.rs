@@ -0,0 +1,231 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! A log buffer which is safe to write to from signal handlers
+//!
+//! Postgres' `ereport()` allocates memory and may `longjmp()`, so it must not be called from a
+//! signal handler, nor from code which a signal handler might have interrupted.  A
+//! [`SignalSafeLog`] is a fixed-size ring buffer of messages which can be written to from there
+//! without locking or allocating, and which is flushed to the server log later, from a point
+//! where calling `ereport()` is safe.
+//!
+//! ```rust,no_run
+//! use pgrx::prelude::*;
+//! use pgrx::signal_log::SignalSafeLog;
+//!
+//! static TRACE: SignalSafeLog<64> = SignalSafeLog::new();
+//!
+//! unsafe extern "C" fn handle_sigusr2(signal: i32) {
+//!     TRACE.log_fmt(PgLogLevel::LOG, format_args!("received signal {signal}"));
+//! }
+//!
+//! #[pg_extern]
+//! fn do_some_work() {
+//!     // ... do the work, during which `handle_sigusr2` may be called ...
+//!
+//!     // now it's safe to write the trace to the server log
+//!     TRACE.flush();
+//! }
+//! ```
+//!
+//! When the buffer fills, the oldest messages which haven't been flushed yet are overwritten, and
+//! a count of those lost is logged as a `WARNING` by the next flush.  Messages longer than the
+//! buffer's `LEN` are truncated.
+//!
+//! A `SignalSafeLog` may be written to by the backend and its signal handlers, but must only be
+//! flushed by the backend outside of a signal handler.
+use crate::PgLogLevel;
+use core::fmt;
+use core::sync::atomic::{fence, AtomicI32, AtomicU8, AtomicUsize, Ordering};
+
+/// The state of a slot whose message is being written
+const BUSY: usize = usize::MAX;
+
+/// A slot's contents are atomics, even though only one writer ever holds it, because the drain
+/// may read a slot while a signal handler overwrites it.  It notices that from the slot's state
+/// and discards what it read, but the read itself mustn't be a data race.
+struct Slot<const LEN: usize> {
+    /// The position plus one of the message in the slot, zero if it's never been written, or
+    /// [`BUSY`] while a message is being written to it
+    state: AtomicUsize,
+    level: AtomicI32,
+    len: AtomicUsize,
+    data: [AtomicU8; LEN],
+}
+
+impl<const LEN: usize> Slot<LEN> {
+    const ZERO: AtomicU8 = AtomicU8::new(0);
+    const EMPTY: Self = Slot {
+        state: AtomicUsize::new(0),
+        level: AtomicI32::new(PgLogLevel::LOG as i32),
+        len: AtomicUsize::new(0),
+        data: [Self::ZERO; LEN],
+    };
+}
+
+/// A ring buffer of up to `N` log messages of up to `LEN` bytes each, which may be written to
+/// from signal handlers.  See the [module documentation](self).
+pub struct SignalSafeLog<const N: usize, const LEN: usize = 256> {
+    slots: [Slot<LEN>; N],
+    /// The position of the next message to be written
+    head: AtomicUsize,
+    /// The position of the next message to be flushed
+    tail: AtomicUsize,
+}
+
+impl<const N: usize, const LEN: usize> SignalSafeLog<N, LEN> {
+    pub const fn new() -> Self {
+        SignalSafeLog {
+            slots: [Slot::EMPTY; N],
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    /// Add `message` to the buffer, to be logged at `level` by the next [`flush`](Self::flush).
+    ///
+    /// This neither locks nor allocates, so may be called from a signal handler.
+    pub fn log(&self, level: PgLogLevel, message: &str) {
+        self.push(level, truncate(message, LEN).as_bytes());
+    }
+
+    /// Format a message into the buffer, as [`log`](Self::log) does.  Use `format_args!()` to
+    /// build `args`.
+    ///
+    /// The formatting itself doesn't allocate, but the `Display` impls of the arguments might, so
+    /// only format values such as numbers and `&str`s from a signal handler.
+    pub fn log_fmt(&self, level: PgLogLevel, args: fmt::Arguments<'_>) {
+        let mut buffer = FixedBuffer { data: [0; LEN], len: 0 };
+        let _ = fmt::Write::write_fmt(&mut buffer, args);
+        self.push(level, &buffer.data[..buffer.len]);
+    }
+
+    fn push(&self, level: PgLogLevel, message: &[u8]) {
+        let position = self.head.fetch_add(1, Ordering::AcqRel);
+        let slot = &self.slots[position % N];
+        if slot.state.swap(BUSY, Ordering::Acquire) == BUSY {
+            // we've interrupted a write to this slot, a full lap behind us.  Leave it be, and let
+            // the next flush count our message as lost
+            return;
+        }
+        // a drain which sees any of the writes below also sees the slot BUSY
+        fence(Ordering::Release);
+        slot.level.store(level as i32, Ordering::Relaxed);
+        slot.len.store(message.len(), Ordering::Relaxed);
+        for (byte, value) in slot.data.iter().zip(message) {
+            byte.store(*value, Ordering::Relaxed);
+        }
+        slot.state.store(position + 1, Ordering::Release);
+    }
+
+    /// Write the buffered messages to the server log, oldest first, and return how many were
+    /// written.  Messages at `ERROR` or above are logged as `WARNING`s, so this never raises an
+    /// `ERROR` itself.  As with `elog()`, `WARNING`s carry `ERRCODE_WARNING` and everything else
+    /// `ERRCODE_SUCCESSFUL_COMPLETION`.
+    ///
+    /// This must not be called from a signal handler.
+    pub fn flush(&self) -> usize {
+        let (flushed, lost) = self.drain(|level, message| {
+            let level = level.min(PgLogLevel::WARNING);
+            let errcode = if level == PgLogLevel::WARNING {
+                crate::PgSqlErrorCode::ERRCODE_WARNING
+            } else {
+                crate::PgSqlErrorCode::ERRCODE_SUCCESSFUL_COMPLETION
+            };
+            crate::ereport!(level, errcode, message);
+        });
+        if lost > 0 {
+            crate::warning!("{lost} buffered log messages were lost");
+        }
+        flushed
+    }
+
+    /// Remove the buffered messages, oldest first, passing each to `f` rather than logging them.
+    /// Returns how many were passed to `f`, and how many were lost because the buffer filled
+    /// before they could be drained.
+    ///
+    /// Like [`flush`](Self::flush), this must not be called from a signal handler.
+    pub fn drain<F: FnMut(PgLogLevel, &str)>(&self, mut f: F) -> (usize, usize) {
+        let head = self.head.load(Ordering::Acquire);
+        let mut tail = self.tail.load(Ordering::Relaxed);
+        let mut drained = 0;
+        let mut lost = 0;
+
+        if head - tail > N {
+            // these were overwritten by newer messages
+            lost += head - N - tail;
+            tail = head - N;
+        }
+
+        let mut message = [0u8; LEN];
+        while tail < head {
+            let slot = &self.slots[tail % N];
+            let mut copied = None;
+            if slot.state.load(Ordering::Acquire) == tail + 1 {
+                // the slot holds a complete message.  A signal handler could overwrite it while
+                // we copy, which we check for below
+                let level = slot.level.load(Ordering::Relaxed);
+                let len = slot.len.load(Ordering::Relaxed).min(LEN);
+                for (value, byte) in message.iter_mut().zip(&slot.data[..len]) {
+                    *value = byte.load(Ordering::Relaxed);
+                }
+                fence(Ordering::Acquire);
+                if slot.state.load(Ordering::Relaxed) == tail + 1 {
+                    copied = Some((PgLogLevel::from(level), len));
+                }
+            }
+            tail += 1;
+            // update the tail first, so a panic from `f` doesn't see this message again
+            self.tail.store(tail, Ordering::Relaxed);
+
+            match copied {
+                Some((level, len)) => {
+                    // messages are only ever truncated on a char boundary
+                    f(level, core::str::from_utf8(&message[..len]).unwrap_or_default());
+                    drained += 1;
+                }
+                None => lost += 1,
+            }
+        }
+        (drained, lost)
+    }
+}
+
+impl<const N: usize, const LEN: usize> Default for SignalSafeLog<N, LEN> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The longest prefix of `s` that fits in `len` bytes and ends on a char boundary
+fn truncate(s: &str, len: usize) -> &str {
+    if s.len() <= len {
+        return s;
+    }
+    let mut end = len;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+/// A `fmt::Write` which silently truncates what doesn't fit
+struct FixedBuffer<const LEN: usize> {
+    data: [u8; LEN],
+    len: usize,
+}
+
+impl<const LEN: usize> fmt::Write for FixedBuffer<LEN> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let s = truncate(s, LEN - self.len);
+        self.data[self.len..self.len + s.len()].copy_from_slice(s.as_bytes());
+        self.len += s.len();
+        Ok(())
+    }
+}
@@ -12,6 +12,7 @@
 use crate::{pg_sys, FromDatum, IntoDatum, Json, PgMemoryContexts, PgOid, TryFromDatumError};
 use core::fmt::Formatter;
 use pgrx_pg_sys::panic::ErrorReportable;
+use std::collections::VecDeque;
 use std::ffi::{CStr, CString};
 use std::fmt::Debug;
 use std::marker::PhantomData;
@@ -180,6 +181,10 @@ pub enum Error {
     /// The [`pg_sys::SPI_tuptable`] is null
     #[error("The active `SPI_tuptable` is NULL")]
     NoTupleTable,
+
+    /// A query returned a different number of columns than the [`SpiRow`] it's read into
+    #[error("Column count mismatch (expected {expected}, got {got})")]
+    ColumnCountMismatch { expected: usize, got: usize },
 }
 
 pub struct Spi;
@@ -248,6 +253,7 @@ impl SpiConnection {
         // SPI_connect() is documented as being able to return SPI_ERROR_CONNECT, so we have to
         // assume it could.  The truth seems to be that it never actually does.  The one user
         // of SpiConnection::connect() returns `spi::Result` anyways, so it's no big deal
+        crate::fault_injection::fault_point(crate::fault_injection::SPI);
         Spi::check_status(unsafe { pg_sys::SPI_connect() })?;
         Ok(SpiConnection(PhantomData))
     }
@@ -405,10 +411,144 @@ pub struct SpiTupleTable<'conn> {
     current: isize,
 }
 
+/// A tuple of Rust types, one per column, that a row of a query's result can be read into with
+/// [`Spi::get_row`] or [`SpiTupleTable::get_row`].
+///
+/// It's implemented for tuples of 1 to 12 [`FromDatum`] types.  Each column's type is checked
+/// against its Rust type before it's converted, and NULL columns are read as `None`.
+pub trait SpiRow {
+    /// The row's values, in the same order as the types of `Self`
+    type Values;
+
+    /// How many columns a row must have
+    const COLUMNS: usize;
+
+    #[doc(hidden)]
+    fn from_tuple_table(table: &SpiTupleTable<'_>) -> Result<Self::Values>;
+}
+
+macro_rules! impl_spi_row {
+    ($columns:literal: $($T:ident => $ordinal:literal),+) => {
+        impl<$($T: FromDatum + IntoDatum),+> SpiRow for ($($T,)+) {
+            type Values = ($(Option<$T>,)+);
+            const COLUMNS: usize = $columns;
+
+            fn from_tuple_table(table: &SpiTupleTable<'_>) -> Result<Self::Values> {
+                Ok(($(table.get::<$T>($ordinal)?,)+))
+            }
+        }
+    };
+}
+
+impl_spi_row!(1: A => 1);
+impl_spi_row!(2: A => 1, B => 2);
+impl_spi_row!(3: A => 1, B => 2, C => 3);
+impl_spi_row!(4: A => 1, B => 2, C => 3, D => 4);
+impl_spi_row!(5: A => 1, B => 2, C => 3, D => 4, E => 5);
+impl_spi_row!(6: A => 1, B => 2, C => 3, D => 4, E => 5, F => 6);
+impl_spi_row!(7: A => 1, B => 2, C => 3, D => 4, E => 5, F => 6, G => 7);
+impl_spi_row!(8: A => 1, B => 2, C => 3, D => 4, E => 5, F => 6, G => 7, H => 8);
+impl_spi_row!(9: A => 1, B => 2, C => 3, D => 4, E => 5, F => 6, G => 7, H => 8, I => 9);
+impl_spi_row!(10: A => 1, B => 2, C => 3, D => 4, E => 5, F => 6, G => 7, H => 8, I => 9, J => 10);
+impl_spi_row!(
+    11: A => 1, B => 2, C => 3, D => 4, E => 5, F => 6, G => 7, H => 8, I => 9, J => 10, K => 11
+);
+impl_spi_row!(
+    12: A => 1, B => 2, C => 3, D => 4, E => 5, F => 6, G => 7, H => 8, I => 9, J => 10, K => 11,
+    L => 12
+);
+
+/// A tuple of Rust values, one per parameter, to bind to a prepared statement's `$n` parameters
+/// with [`OwnedPreparedStatement::get_one`], [`OwnedPreparedStatement::get_row`], or
+/// [`OwnedPreparedStatement::run`].
+///
+/// It's implemented for `()` and tuples of 1 to 12 [`IntoDatum`] types.  Each value's type is
+/// checked against its parameter's type before the statement is executed, and `None` binds NULL.
+pub trait SpiArgs {
+    /// How many parameters a statement must have
+    const COUNT: usize;
+
+    #[doc(hidden)]
+    fn bind(self, plan: NonNull<pg_sys::_SPI_plan>) -> Result<Vec<Option<pg_sys::Datum>>>;
+}
+
+fn bind_arg<T: IntoDatum>(
+    plan: NonNull<pg_sys::_SPI_plan>,
+    index: usize,
+    value: T,
+) -> Result<Option<pg_sys::Datum>> {
+    // SAFETY: `plan` is a valid plan, and `index` was checked against its argument count
+    let param_oid = unsafe { pg_sys::SPI_getargtypeid(plan.as_ptr(), index as i32) };
+    if !crate::datum::is_binary_coercible::<T>(param_oid) {
+        return Err(Error::DatumError(TryFromDatumError::IncompatibleTypes {
+            rust_type: std::any::type_name::<T>(),
+            rust_oid: T::type_oid(),
+            datum_type: crate::datum::lookup_type_name(param_oid),
+            datum_oid: param_oid,
+        }));
+    }
+    Ok(value.into_datum())
+}
+
+fn check_arg_count<A: SpiArgs>(plan: NonNull<pg_sys::_SPI_plan>) -> Result<()> {
+    check_plan_arg_count(plan, A::COUNT)
+}
+
+/// Fails with [`Error::PreparedStatementArgumentMismatch`] unless `plan` has `got` parameters
+fn check_plan_arg_count(plan: NonNull<pg_sys::_SPI_plan>, got: usize) -> Result<()> {
+    // SAFETY: `plan` is a valid plan
+    let expected = unsafe { pg_sys::SPI_getargcount(plan.as_ptr()) } as usize;
+    if expected != got {
+        return Err(Error::PreparedStatementArgumentMismatch { expected, got });
+    }
+    Ok(())
+}
+
+impl SpiArgs for () {
+    const COUNT: usize = 0;
+
+    fn bind(self, plan: NonNull<pg_sys::_SPI_plan>) -> Result<Vec<Option<pg_sys::Datum>>> {
+        check_arg_count::<Self>(plan)?;
+        Ok(Vec::new())
+    }
+}
+
+macro_rules! impl_spi_args {
+    ($count:literal: $($T:ident => $index:tt),+) => {
+        impl<$($T: IntoDatum),+> SpiArgs for ($($T,)+) {
+            const COUNT: usize = $count;
+
+            fn bind(self, plan: NonNull<pg_sys::_SPI_plan>) -> Result<Vec<Option<pg_sys::Datum>>> {
+                check_arg_count::<Self>(plan)?;
+                Ok(vec![$(bind_arg(plan, $index, self.$index)?,)+])
+            }
+        }
+    };
+}
+
+impl_spi_args!(1: A => 0);
+impl_spi_args!(2: A => 0, B => 1);
+impl_spi_args!(3: A => 0, B => 1, C => 2);
+impl_spi_args!(4: A => 0, B => 1, C => 2, D => 3);
+impl_spi_args!(5: A => 0, B => 1, C => 2, D => 3, E => 4);
+impl_spi_args!(6: A => 0, B => 1, C => 2, D => 3, E => 4, F => 5);
+impl_spi_args!(7: A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6);
+impl_spi_args!(8: A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6, H => 7);
+impl_spi_args!(9: A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6, H => 7, I => 8);
+impl_spi_args!(10: A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6, H => 7, I => 8, J => 9);
+impl_spi_args!(
+    11: A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6, H => 7, I => 8, J => 9, K => 10
+);
+impl_spi_args!(
+    12: A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6, H => 7, I => 8, J => 9, K => 10,
+    L => 11
+);
+
 /// Represents a single `pg_sys::Datum` inside a `SpiHeapTupleData`
 pub struct SpiHeapTupleDataEntry<'conn> {
     datum: Option<pg_sys::Datum>,
     type_oid: pg_sys::Oid,
+    typmod: i32,
     __marker: PhantomData<&'conn ()>,
 }
 
@@ -471,6 +611,72 @@ impl Spi {
         })
     }
 
+    /// Run a query and read the columns of its first row into the [`SpiRow`] tuple `R`.
+    ///
+    /// ```rust,no_run
+    /// use pgrx::prelude::*;
+    /// # fn foo() -> Result<(), pgrx::spi::Error> {
+    /// let (id, name, active) =
+    ///     Spi::get_row::<(i64, String, bool)>("SELECT 42::bigint, 'test', true")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ColumnCountMismatch`] if the query doesn't return exactly as many columns
+    /// as `R` has, and [`Error::DatumError`] if a column's type isn't compatible with the Rust type
+    /// in the same position.
+    pub fn get_row<R: SpiRow>(query: &str) -> Result<R::Values> {
+        Spi::connect(|mut client| client.update(query, Some(1), None)?.first().get_row::<R>())
+    }
+
+    /// Like [`Spi::get_row`], but with arguments for the query's `$n` parameters.
+    pub fn get_row_with_args<R: SpiRow>(
+        query: &str,
+        args: Vec<(PgOid, Option<pg_sys::Datum>)>,
+    ) -> Result<R::Values> {
+        Spi::connect(|mut client| client.update(query, Some(1), Some(args))?.first().get_row::<R>())
+    }
+
+    /// Prepare `query` once, so it can be executed any number of times without being planned
+    /// again, with its parameters bound to [`SpiArgs`].
+    ///
+    /// The statement outlives the current SPI connection and transaction, so it can be cached
+    /// across calls of a function, such as in a `thread_local!` or the function's `fn_extra`.
+    /// Postgres re-plans it if the objects it depends on change.  Dropping the statement frees its
+    /// plan, except when the backend is exiting, which is when a `thread_local!`'s destructor runs.
+    ///
+    /// ```rust,no_run
+    /// use pgrx::prelude::*;
+    /// use pgrx::spi::OwnedPreparedStatement;
+    /// use once_cell::unsync::OnceCell;
+    ///
+    /// thread_local! {
+    ///     static LOOKUP: OnceCell<OwnedPreparedStatement> = OnceCell::new();
+    /// }
+    ///
+    /// #[pg_extern]
+    /// fn lookup(id: i64) -> Result<Option<String>, pgrx::spi::Error> {
+    ///     LOOKUP.with(|lookup| {
+    ///         let stmt = lookup.get_or_try_init(|| {
+    ///             Spi::prepare(
+    ///                 "SELECT name FROM things WHERE id = $1",
+    ///                 Some(vec![PgOid::BuiltIn(PgBuiltInOids::INT8OID)]),
+    ///             )
+    ///         })?;
+    ///         stmt.get_one((id,))
+    ///     })
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the supplied `query` string contained a NULL byte
+    pub fn prepare(query: &str, args: Option<Vec<PgOid>>) -> Result<OwnedPreparedStatement> {
+        Spi::connect(|client| Ok(client.prepare(query, args)?.keep()))
+    }
+
     /// just run an arbitrary SQL statement.
     ///
     /// ## Safety
@@ -609,6 +815,7 @@ impl<'conn> SpiClient<'conn> {
         limit: Option<libc::c_long>,
         args: Q::Arguments,
     ) -> Q::Result {
+        crate::fault_injection::fault_point(crate::fault_injection::SPI);
         query.execute(&self, limit, args)
     }
 
@@ -779,6 +986,121 @@ impl Drop for SpiCursor<'_> {
     }
 }
 
+impl SpiCursor<'_> {
+    /// Consume the cursor, returning an iterator that fetches `batch_size` rows at a time,
+    /// converting each row with `f`
+    ///
+    /// Each batch is fetched in its own Spi session, so at most one batch of rows is held in
+    /// memory at any time.  Because the returned [`SpiCursorStream`] holds onto the cursor by
+    /// name it isn't bound to the current Spi session, which makes it suitable for returning
+    /// from set-returning functions.  See [`TableIterator::from_cursor()`].
+    ///
+    /// The cursor is closed once all of its rows have been fetched, or when the stream is
+    /// dropped.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `batch_size` is not positive.
+    ///
+    /// [`TableIterator::from_cursor()`]: crate::iter::TableIterator::from_cursor
+    pub fn into_stream<T, F>(self, batch_size: libc::c_long, f: F) -> SpiCursorStream<T, F>
+    where
+        F: FnMut(SpiHeapTupleData<'_>) -> Result<T>,
+    {
+        assert!(batch_size > 0, "batch_size must be positive");
+        SpiCursorStream {
+            name: Some(self.detach_into_name()),
+            batch_size,
+            buffer: VecDeque::new(),
+            f,
+        }
+    }
+}
+
+/// An iterator that lazily fetches rows from a detached [`SpiCursor`]
+///
+/// Created by [`SpiCursor::into_stream()`].
+///
+/// # Panics
+///
+/// Iterating will raise a Postgres ERROR if fetching from the cursor, or converting one of its
+/// rows, fails.
+pub struct SpiCursorStream<T, F> {
+    name: Option<CursorName>,
+    batch_size: libc::c_long,
+    buffer: VecDeque<T>,
+    f: F,
+}
+
+impl<T, F> SpiCursorStream<T, F>
+where
+    F: FnMut(SpiHeapTupleData<'_>) -> Result<T>,
+{
+    /// Fetch the next batch of rows into the buffer, closing the cursor if it's exhausted
+    fn fetch_batch(&mut self) -> Result<()> {
+        let Self { name, batch_size, buffer, f } = self;
+        let Some(cursor_name) = name.as_deref() else {
+            return Ok(());
+        };
+
+        let fetched = Spi::connect(|client| {
+            let mut cursor = client.find_cursor(cursor_name)?;
+            let table = cursor.fetch(*batch_size)?;
+            let fetched = table.len();
+            for row in table {
+                buffer.push_back(f(row)?);
+            }
+            cursor.detach_into_name();
+            Ok::<_, Error>(fetched)
+        })?;
+
+        if fetched < *batch_size as usize {
+            self.close();
+        }
+        Ok(())
+    }
+}
+
+impl<T, F> Iterator for SpiCursorStream<T, F>
+where
+    F: FnMut(SpiHeapTupleData<'_>) -> Result<T>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buffer.is_empty() {
+            self.fetch_batch().report();
+        }
+        self.buffer.pop_front()
+    }
+}
+
+impl<T, F> SpiCursorStream<T, F> {
+    /// Close the underlying cursor, if it's still open
+    fn close(&mut self) {
+        use pgrx_pg_sys::AsPgCStr;
+
+        if let Some(name) = self.name.take() {
+            // SAFETY: the portal is looked up by name, so we only ever close one that still
+            // exists.  Outside of a valid transaction, Postgres has already dropped it for us
+            unsafe {
+                if pg_sys::IsTransactionState() {
+                    let portal = pg_sys::SPI_cursor_find(name.as_pg_cstr());
+                    if !portal.is_null() {
+                        pg_sys::SPI_cursor_close(portal);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<T, F> Drop for SpiCursorStream<T, F> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
 /// Client lifetime-bound prepared statement
 pub struct PreparedStatement<'conn> {
     plan: NonNull<pg_sys::_SPI_plan>,
@@ -798,12 +1120,46 @@ impl Deref for OwnedPreparedStatement {
 
 impl Drop for OwnedPreparedStatement {
     fn drop(&mut self) {
+        // a statement cached in a `thread_local!` is dropped by `exit()`, after Postgres has shut
+        // the backend down, and its plan goes away with the process anyway
+        if unsafe { pg_sys::proc_exit_inprogress } {
+            return;
+        }
         unsafe {
             pg_sys::SPI_freeplan(self.0.plan.as_ptr());
         }
     }
 }
 
+impl OwnedPreparedStatement {
+    /// Execute the statement with `args` bound to its parameters, and return the first column of
+    /// its first row.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::PreparedStatementArgumentMismatch`] if the statement doesn't have exactly
+    /// as many parameters as `args` has values, and [`Error::DatumError`] if a value's type isn't
+    /// compatible with its parameter's.
+    pub fn get_one<A: FromDatum + IntoDatum>(&self, args: impl SpiArgs) -> Result<Option<A>> {
+        let args = args.bind(self.plan)?;
+        Spi::connect(|mut client| client.update(self, Some(1), Some(args))?.first().get_one())
+    }
+
+    /// Execute the statement with `args` bound to its parameters, and read the columns of its
+    /// first row into the [`SpiRow`] tuple `R`.
+    pub fn get_row<R: SpiRow>(&self, args: impl SpiArgs) -> Result<R::Values> {
+        let args = args.bind(self.plan)?;
+        Spi::connect(|mut client| client.update(self, Some(1), Some(args))?.first().get_row::<R>())
+    }
+
+    /// Execute the statement with `args` bound to its parameters, discarding any rows it returns.
+    /// The statement runs in read/write mode.
+    pub fn run(&self, args: impl SpiArgs) -> Result<()> {
+        let args = args.bind(self.plan)?;
+        Spi::connect(|mut client| client.update(self, None, Some(args)).map(|_| ()))
+    }
+}
+
 impl<'conn> Query<'conn> for &OwnedPreparedStatement {
     type Arguments = Option<Vec<Option<pg_sys::Datum>>>;
     type Result = Result<SpiTupleTable<'conn>>;
@@ -870,13 +1226,7 @@ impl<'conn: 'stmt, 'stmt> Query<'conn> for &'stmt PreparedStatement<'conn> {
             pg_sys::SPI_tuptable = std::ptr::null_mut();
         }
         let args = arguments.unwrap_or_default();
-        let nargs = args.len();
-
-        let expected = unsafe { pg_sys::SPI_getargcount(self.plan.as_ptr()) } as usize;
-
-        if nargs != expected {
-            return Err(Error::PreparedStatementArgumentMismatch { expected, got: nargs });
-        }
+        check_plan_arg_count(self.plan, args.len())?;
 
         let (mut datums, mut nulls): (Vec<_>, Vec<_>) = args.into_iter().map(prepare_datum).unzip();
 
@@ -996,10 +1346,12 @@ impl<'conn> SpiTupleTable<'conn> {
         self.len() == 0
     }
 
+    #[track_caller]
     pub fn get_one<A: FromDatum + IntoDatum>(&self) -> Result<Option<A>> {
         self.get(1)
     }
 
+    #[track_caller]
     pub fn get_two<A: FromDatum + IntoDatum, B: FromDatum + IntoDatum>(
         &self,
     ) -> Result<(Option<A>, Option<B>)> {
@@ -1008,6 +1360,7 @@ impl<'conn> SpiTupleTable<'conn> {
         Ok((a, b))
     }
 
+    #[track_caller]
     pub fn get_three<
         A: FromDatum + IntoDatum,
         B: FromDatum + IntoDatum,
@@ -1021,6 +1374,21 @@ impl<'conn> SpiTupleTable<'conn> {
         Ok((a, b, c))
     }
 
+    /// Read the columns of the current row into the [`SpiRow`] tuple `R`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ColumnCountMismatch`] if the row doesn't have exactly as many columns as
+    /// `R`, and [`Error::DatumError`] if a column's type isn't compatible with the Rust type in the
+    /// same position.
+    pub fn get_row<R: SpiRow>(&self) -> Result<R::Values> {
+        let columns = self.columns()?;
+        if columns != R::COLUMNS {
+            return Err(Error::ColumnCountMismatch { expected: R::COLUMNS, got: columns });
+        }
+        R::from_tuple_table(self)
+    }
+
     #[inline(always)]
     fn get_spi_tuptable(&self) -> Result<(*mut pg_sys::SPITupleTable, *mut pg_sys::TupleDescData)> {
         let table = self.table.as_deref().ok_or(Error::NoTupleTable)?;
@@ -1063,6 +1431,7 @@ impl<'conn> SpiTupleTable<'conn> {
     ///
     /// This function will panic there is no parent MemoryContext.  This is an incredibly unlikely
     /// situation.
+    #[track_caller]
     pub fn get<T: IntoDatum + FromDatum>(&self, ordinal: usize) -> Result<Option<T>> {
         let (_, tupdesc) = self.get_spi_tuptable()?;
         let datum = self.get_datum_by_ordinal(ordinal)?;
@@ -1070,6 +1439,8 @@ impl<'conn> SpiTupleTable<'conn> {
         let datum = datum.unwrap_or_else(|| pg_sys::Datum::from(0));
 
         unsafe {
+            let _typmod =
+                crate::datum_diagnostics::TypmodScope::enter(attribute_typmod(tupdesc, ordinal));
             // SAFETY:  we know the constraints around `datum` and `is_null` match because we
             // just got them from the underlying heap tuple
             Ok(T::try_from_datum_in_memory_context(
@@ -1091,6 +1462,7 @@ impl<'conn> SpiTupleTable<'conn> {
     ///
     /// If the specified name is invalid a [`Error::SpiError(SpiError::NoAttribute)`] is returned
     /// If we have no backing tuple table a [`Error::NoTupleTable`] is returned
+    #[track_caller]
     pub fn get_by_name<T: IntoDatum + FromDatum, S: AsRef<str>>(
         &self,
         name: S,
@@ -1222,6 +1594,15 @@ impl<'conn> SpiTupleTable<'conn> {
     }
 }
 
+/// The typmod of the attribute at the 1-based `ordinal` of `tupdesc`
+///
+/// # Safety
+///
+/// `tupdesc` must be a valid tuple descriptor with at least `ordinal` attributes
+unsafe fn attribute_typmod(tupdesc: pg_sys::TupleDesc, ordinal: usize) -> i32 {
+    (*tupdesc).attrs.as_slice((*tupdesc).natts as usize)[ordinal - 1].atttypmod
+}
+
 impl<'conn> SpiHeapTupleData<'conn> {
     /// Create a new `SpiHeapTupleData` from its constituent parts
     ///
@@ -1247,6 +1628,7 @@ impl<'conn> SpiHeapTupleData<'conn> {
                 data.entries.push(SpiHeapTupleDataEntry {
                     datum: if is_null { None } else { Some(datum) },
                     type_oid: pg_sys::SPI_gettypeid(tupdesc as _, i),
+                    typmod: attribute_typmod(tupdesc, i as usize),
                     __marker: PhantomData,
                 });
             }
@@ -1263,8 +1645,9 @@ impl<'conn> SpiHeapTupleData<'conn> {
     ///
     /// Returns a [`Error::DatumError`] if the desired Rust type is incompatible
     /// with the underlying Datum
+    #[track_caller]
     pub fn get<T: IntoDatum + FromDatum>(&self, ordinal: usize) -> Result<Option<T>> {
-        self.get_datum_by_ordinal(ordinal).map(|entry| entry.value())?
+        self.get_datum_by_ordinal(ordinal)?.value()
     }
 
     /// Get a typed value from this HeapTuple by its name in the resultset.
@@ -1273,11 +1656,12 @@ impl<'conn> SpiHeapTupleData<'conn> {
     ///
     /// Returns a [`Error::DatumError`] if the desired Rust type is incompatible
     /// with the underlying Datum
+    #[track_caller]
     pub fn get_by_name<T: IntoDatum + FromDatum, S: AsRef<str>>(
         &self,
         name: S,
     ) -> Result<Option<T>> {
-        self.get_datum_by_name(name.as_ref()).map(|entry| entry.value())?
+        self.get_datum_by_name(name.as_ref())?.value()
     }
 
     /// Get a raw Datum from this HeapTuple by its ordinal position.
@@ -1335,6 +1719,7 @@ impl<'conn> SpiHeapTupleData<'conn> {
         self.entries[ordinal - 1] = SpiHeapTupleDataEntry {
             datum: datum.into_datum(),
             type_oid: T::type_oid(),
+            typmod: -1,
             __marker: PhantomData,
         };
         Ok(())
@@ -1385,9 +1770,11 @@ impl<'conn> SpiHeapTupleData<'conn> {
 }
 
 impl<'conn> SpiHeapTupleDataEntry<'conn> {
+    #[track_caller]
     pub fn value<T: IntoDatum + FromDatum>(&self) -> Result<Option<T>> {
         match self.datum.as_ref() {
             Some(datum) => unsafe {
+                let _typmod = crate::datum_diagnostics::TypmodScope::enter(self.typmod);
                 T::try_from_datum_in_memory_context(
                     PgMemoryContexts::CurrentMemoryContext
                         .parent()
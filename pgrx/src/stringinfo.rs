@@ -10,9 +10,14 @@
 //! A safe wrapper around Postgres `StringInfo` structure
 #![allow(dead_code, non_snake_case)]
 
-use crate::{pg_sys, AllocatedByPostgres, AllocatedByRust, PgBox, WhoAllocated};
+use crate::{
+    pg_sys, set_varsize_4b, AllocatedByPostgres, AllocatedByRust, IntoDatum, PgBox, WhoAllocated,
+};
 use core::fmt::{Display, Formatter};
 use core::str::Utf8Error;
+use pgrx_sql_entity_graph::metadata::{
+    ArgumentError, Returns, ReturnsError, SqlMapping, SqlTranslatable,
+};
 use std::io::Error;
 
 /// StringInfoData holds information about an extensible string that is allocated by Postgres'
@@ -302,3 +307,131 @@ impl<AllocatedBy: WhoAllocated> Drop for StringInfo<AllocatedBy> {
         }
     }
 }
+
+/// Builds a Postgres `text` value in place, in a [`StringInfo`] allocated in `CurrentMemoryContext`
+///
+/// Formatting a large result into a Rust [`String`] and returning that copies it into a `text`
+/// at the end.  A `TextBuilder` reserves room for the `text` header up front, so
+/// [`TextBuilder::into_text_datum`], or returning it from a `#[pg_extern]` function, hands over
+/// the buffer as it is.
+///
+/// ```rust,no_run
+/// use pgrx::prelude::*;
+/// use pgrx::TextBuilder;
+/// use std::fmt::Write;
+///
+/// #[pg_extern]
+/// fn squares(n: i32) -> TextBuilder {
+///     let mut text = TextBuilder::new();
+///     for i in 1..=n {
+///         writeln!(text, "{i}: {}", i * i).unwrap();
+///     }
+///     text
+/// }
+/// ```
+///
+/// Anything written through [`std::io::Write`] must be valid in the database encoding.
+pub struct TextBuilder {
+    buf: StringInfo<AllocatedByRust>,
+}
+
+impl TextBuilder {
+    /// Construct an empty `TextBuilder`
+    pub fn new() -> Self {
+        let mut buf = StringInfo::new();
+        buf.push_bytes(&[0; pg_sys::VARHDRSZ]);
+        TextBuilder { buf }
+    }
+
+    /// Construct an empty `TextBuilder` with room for `len` bytes of text
+    ///
+    /// Note that Postgres can only represent up to 1 gigabyte of data in a `text`
+    pub fn with_capacity(len: i32) -> Self {
+        let mut builder = TextBuilder::new();
+        builder.buf.enlarge(len);
+        builder
+    }
+
+    /// The length of the text, in bytes
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.buf.len() - pg_sys::VARHDRSZ
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Push a String reference onto the end
+    #[inline]
+    pub fn push_str(&mut self, s: &str) {
+        self.buf.push_str(s)
+    }
+
+    /// The text written so far
+    ///
+    /// # Errors
+    ///
+    /// If bytes which aren't valid UTF8 were written through [`std::io::Write`], a [`Utf8Error`]
+    /// is returned.
+    #[inline]
+    pub fn as_str(&self) -> Result<&str, Utf8Error> {
+        std::str::from_utf8(&self.buf.as_bytes()[pg_sys::VARHDRSZ..])
+    }
+
+    /// Convert this `TextBuilder` into a `text` datum, which is now managed by Postgres
+    pub fn into_text_datum(self) -> pg_sys::Datum {
+        let len = self.buf.len().try_into().expect("text is too long");
+        let varlena = self.buf.into_char_ptr() as *mut pg_sys::varlena;
+        unsafe {
+            // SAFETY:  the buffer is palloc'd, so suitably aligned, and starts with the room we
+            // reserved for the header
+            set_varsize_4b(varlena, len);
+        }
+        pg_sys::Datum::from(varlena)
+    }
+}
+
+impl Default for TextBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::io::Write for TextBuilder {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        self.buf.push_bytes(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl std::fmt::Write for TextBuilder {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+impl IntoDatum for TextBuilder {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(self.into_text_datum())
+    }
+
+    fn type_oid() -> pg_sys::Oid {
+        pg_sys::TEXTOID
+    }
+}
+
+unsafe impl SqlTranslatable for TextBuilder {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        Ok(SqlMapping::literal("TEXT"))
+    }
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        Ok(Returns::One(SqlMapping::literal("TEXT")))
+    }
+}
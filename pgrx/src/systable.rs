@@ -0,0 +1,217 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Index-assisted scans of system catalogs, or catalog-like tables, with `systable_beginscan()`
+//!
+//! The syscache only covers lookups by the catalogs' unique keys.  A [`SysTableScan`] can find
+//! every row matching any combination of [`ScanKeys`], and when given an index whose leading
+//! columns match the keys, returns them in that index's order.
+//!
+//! The caller is responsible for locking the table being scanned, usually with `AccessShareLock`,
+//! by opening it with [`PgRelation::with_lock()`] or [`PgRelation::open_with_name_and_share_lock()`].
+//!
+//! ## Examples
+//!
+//! ```rust,no_run
+//! use pgrx::prelude::*;
+//! use pgrx::systable::{ScanKeys, ScanStrategy, SysTableScan};
+//! use pgrx::PgRelation;
+//!
+//! // the names of every relation in `pg_catalog` starting with "pg_stat", in name order
+//! let pg_class = unsafe {
+//!     PgRelation::with_lock(pg_sys::RelationRelationId, pg_sys::AccessShareLock as _)
+//! };
+//! let keys = ScanKeys::new()
+//!     .name(pg_sys::Anum_pg_class_relname, ScanStrategy::GreaterEqual, "pg_stat")
+//!     .name(pg_sys::Anum_pg_class_relname, ScanStrategy::Less, "pg_stau");
+//! let index = unsafe { pg_sys::Oid::from_u32_unchecked(pg_sys::ClassNameNspIndexId) };
+//! let names = SysTableScan::new(&pg_class, Some(index), keys)
+//!     .map(|tuple| unsafe {
+//!         let form = &*(pg_sys::GETSTRUCT(tuple.into_pg()) as pg_sys::Form_pg_class);
+//!         pgrx::name_to_string(&form.relname)
+//!     })
+//!     .collect::<Vec<_>>();
+//! ```
+use crate::datum::lookup_type_name;
+use crate::heap_tuple::PgHeapTuple;
+use crate::{pg_sys, AllocatedByRust, IntoDatum, PgRelation};
+use std::ptr::NonNull;
+
+/// How a [`ScanKeys`] entry compares an attribute with its value, using the btree operator of
+/// the same strategy from the value type's default btree operator family
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ScanStrategy {
+    Less,
+    LessEqual,
+    Equal,
+    GreaterEqual,
+    Greater,
+}
+
+impl ScanStrategy {
+    fn number(self) -> pg_sys::StrategyNumber {
+        (match self {
+            ScanStrategy::Less => pg_sys::BTLessStrategyNumber,
+            ScanStrategy::LessEqual => pg_sys::BTLessEqualStrategyNumber,
+            ScanStrategy::Equal => pg_sys::BTEqualStrategyNumber,
+            ScanStrategy::GreaterEqual => pg_sys::BTGreaterEqualStrategyNumber,
+            ScanStrategy::Greater => pg_sys::BTGreaterStrategyNumber,
+        }) as pg_sys::StrategyNumber
+    }
+}
+
+/// The conditions a [`SysTableScan`] returns rows for.  Every key must match.
+///
+/// Attributes are numbered from 1, as with the `pg_sys::Anum_*` catalog constants, and always
+/// refer to the table's attributes, even when the scan uses an index.
+#[derive(Default)]
+pub struct ScanKeys(Vec<pg_sys::ScanKeyData>);
+
+impl ScanKeys {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Match rows where attribute `attno` equals `value`
+    ///
+    /// # Panics
+    ///
+    /// See [`ScanKeys::key()`]
+    pub fn eq<T: IntoDatum>(self, attno: u32, value: T) -> Self {
+        self.key(attno, ScanStrategy::Equal, value)
+    }
+
+    /// Match rows where attribute `attno` compares with `value` according to `strategy`
+    ///
+    /// # Panics
+    ///
+    /// If `value` is `NULL`, or `T`'s SQL type has no default btree operator for `strategy`
+    pub fn key<T: IntoDatum>(self, attno: u32, strategy: ScanStrategy, value: T) -> Self {
+        let datum = value.into_datum().expect("scan key values cannot be NULL");
+        self.push(attno, strategy, T::type_oid(), datum)
+    }
+
+    /// Match rows where the `name` attribute `attno` compares with `name` according to `strategy`
+    ///
+    /// Most catalogs identify objects with `name`s, which have no Rust equivalent that
+    /// [`ScanKeys::key()`] could use.
+    ///
+    /// # Panics
+    ///
+    /// If `name` contains a NUL byte
+    pub fn name(self, attno: u32, strategy: ScanStrategy, name: &str) -> Self {
+        let name = name.parse::<pg_sys::NameData>().expect("invalid name");
+        let datum = unsafe {
+            // SAFETY:  the palloc'd copy lives as long as the current memory context, which is
+            // at least as long as the scan using it
+            let ptr =
+                pg_sys::palloc(std::mem::size_of::<pg_sys::NameData>()) as *mut pg_sys::NameData;
+            ptr.write(name);
+            pg_sys::Datum::from(ptr)
+        };
+        self.push(attno, strategy, pg_sys::NAMEOID, datum)
+    }
+
+    fn push(
+        mut self,
+        attno: u32,
+        strategy: ScanStrategy,
+        typoid: pg_sys::Oid,
+        datum: pg_sys::Datum,
+    ) -> Self {
+        let attno = pg_sys::AttrNumber::try_from(attno).expect("invalid attribute number");
+        let procedure = comparison_proc(typoid, strategy);
+        unsafe {
+            // SAFETY:  ScanKeyInit() initializes every field of the key
+            let mut key = std::mem::zeroed::<pg_sys::ScanKeyData>();
+            pg_sys::ScanKeyInit(&mut key, attno, strategy.number(), procedure, datum);
+            self.0.push(key);
+        }
+        self
+    }
+}
+
+/// Find the function implementing the default btree operator for `strategy` on `typoid`
+fn comparison_proc(typoid: pg_sys::Oid, strategy: ScanStrategy) -> pg_sys::RegProcedure {
+    unsafe {
+        // SAFETY:  lookup_type_cache() raises an ERROR for unknown types rather than returning NULL
+        let typentry = pg_sys::lookup_type_cache(typoid, pg_sys::TYPECACHE_BTREE_OPFAMILY as i32);
+        let opfamily = (*typentry).btree_opf;
+        let opno = if opfamily == pg_sys::InvalidOid {
+            pg_sys::InvalidOid
+        } else {
+            pg_sys::get_opfamily_member(opfamily, typoid, typoid, strategy.number() as i16)
+        };
+        if opno == pg_sys::InvalidOid {
+            panic!("type `{}` has no btree {strategy:?} operator", lookup_type_name(typoid));
+        }
+        pg_sys::get_opcode(opno)
+    }
+}
+
+/// A scan of a table's rows matching some [`ScanKeys`]
+///
+/// Rows are read with the catalog snapshot, and yielded as copies.  The scan ends when this is
+/// dropped.
+pub struct SysTableScan<'a> {
+    rel: &'a PgRelation,
+    scan: NonNull<pg_sys::SysScanDescData>,
+    // the scan may refer to its keys until it's ended
+    _keys: ScanKeys,
+}
+
+impl<'a> SysTableScan<'a> {
+    /// Begin scanning `rel` for rows matching `keys`.
+    ///
+    /// If `index` is given, it's used for the scan unless Postgres is ignoring system indexes,
+    /// and rows are returned in its order.  Otherwise, the whole table is scanned.
+    ///
+    /// The caller must hold a lock on `rel`.
+    pub fn new(rel: &'a PgRelation, index: Option<pg_sys::Oid>, mut keys: ScanKeys) -> Self {
+        let scan = unsafe {
+            // SAFETY:  `rel` is open, and `keys` are initialized and outlive the scan
+            pg_sys::systable_beginscan(
+                rel.as_ptr(),
+                index.unwrap_or(pg_sys::InvalidOid),
+                index.is_some(),
+                std::ptr::null_mut(),
+                keys.0.len() as i32,
+                keys.0.as_mut_ptr(),
+            )
+        };
+        let scan = NonNull::new(scan).expect("systable_beginscan returned NULL");
+        Self { rel, scan, _keys: keys }
+    }
+}
+
+impl<'a> Iterator for SysTableScan<'a> {
+    type Item = PgHeapTuple<'a, AllocatedByRust>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            // SAFETY:  the scan is valid until it's ended in drop, and the tuple it returns is
+            // valid until the next call, so we copy it
+            let tuple = pg_sys::systable_getnext(self.scan.as_ptr());
+            if tuple.is_null() {
+                None
+            } else {
+                Some(PgHeapTuple::from_heap_tuple(self.rel.tuple_desc(), tuple).into_owned())
+            }
+        }
+    }
+}
+
+impl Drop for SysTableScan<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            // SAFETY:  the scan was begun in `new()` and is only ended here
+            pg_sys::systable_endscan(self.scan.as_ptr());
+        }
+    }
+}
@@ -0,0 +1,232 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Helpers for creating temporary and unlogged tables at runtime, and for detecting when the
+//! current backend is working in its temporary schema
+use crate::spi::{self, quote_identifier, quote_qualified_identifier, Spi};
+use crate::{pg_sys, IntoDatum, PgBuiltInOids};
+
+/// The persistence of a relation, as stored in `pg_class.relpersistence`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelPersistence {
+    /// A regular, WAL-logged relation
+    Permanent,
+    /// A relation that isn't WAL-logged and is truncated after a crash
+    Unlogged,
+    /// A relation that is only visible to the current backend and is dropped when it exits
+    Temporary,
+}
+
+impl RelPersistence {
+    /// The `pg_class.relpersistence` character for this persistence
+    pub fn as_char(&self) -> char {
+        match self {
+            RelPersistence::Permanent => pg_sys::RELPERSISTENCE_PERMANENT as char,
+            RelPersistence::Unlogged => pg_sys::RELPERSISTENCE_UNLOGGED as char,
+            RelPersistence::Temporary => pg_sys::RELPERSISTENCE_TEMP as char,
+        }
+    }
+}
+
+/// What happens to a temporary table at the end of each transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnCommit {
+    /// `ON COMMIT PRESERVE ROWS`, the Postgres default
+    PreserveRows,
+    /// `ON COMMIT DELETE ROWS`
+    DeleteRows,
+    /// `ON COMMIT DROP`
+    Drop,
+}
+
+impl OnCommit {
+    fn as_sql(&self) -> &'static str {
+        match self {
+            OnCommit::PreserveRows => "ON COMMIT PRESERVE ROWS",
+            OnCommit::DeleteRows => "ON COMMIT DELETE ROWS",
+            OnCommit::Drop => "ON COMMIT DROP",
+        }
+    }
+}
+
+/// Builds and executes a `CREATE TABLE` statement for a table the extension needs at runtime,
+/// such as a staging table for an ETL job.
+///
+/// Temporary tables are always created in the backend's temporary schema, regardless of any
+/// schema set with [`TableBuilder::schema()`], as that is the only place Postgres allows them.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgrx::table_builder::{OnCommit, TableBuilder};
+///
+/// let oid = TableBuilder::temporary("staging")
+///     .column("id", "bigint")
+///     .column("payload", "jsonb")
+///     .on_commit(OnCommit::Drop)
+///     .create()
+///     .expect("failed to create staging table");
+/// ```
+#[derive(Debug, Clone)]
+pub struct TableBuilder {
+    name: String,
+    schema: Option<String>,
+    persistence: RelPersistence,
+    on_commit: Option<OnCommit>,
+    if_not_exists: bool,
+    columns: Vec<(String, String)>,
+}
+
+impl TableBuilder {
+    /// Start building a regular table named `name`
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        TableBuilder {
+            name: name.into(),
+            schema: None,
+            persistence: RelPersistence::Permanent,
+            on_commit: None,
+            if_not_exists: false,
+            columns: Vec::new(),
+        }
+    }
+
+    /// Start building a temporary table named `name`
+    pub fn temporary<S: Into<String>>(name: S) -> Self {
+        TableBuilder::new(name).persistence(RelPersistence::Temporary)
+    }
+
+    /// Start building an unlogged table named `name`
+    pub fn unlogged<S: Into<String>>(name: S) -> Self {
+        TableBuilder::new(name).persistence(RelPersistence::Unlogged)
+    }
+
+    /// Set the persistence of the table
+    pub fn persistence(mut self, persistence: RelPersistence) -> Self {
+        self.persistence = persistence;
+        self
+    }
+
+    /// Create the table in `schema` rather than the first schema on the `search_path`.  Ignored
+    /// for temporary tables.
+    pub fn schema<S: Into<String>>(mut self, schema: S) -> Self {
+        self.schema = Some(schema.into());
+        self
+    }
+
+    /// Set the `ON COMMIT` behavior.  Postgres only allows this for temporary tables.
+    pub fn on_commit(mut self, on_commit: OnCommit) -> Self {
+        self.on_commit = Some(on_commit);
+        self
+    }
+
+    /// Don't raise an error if the table already exists
+    pub fn if_not_exists(mut self) -> Self {
+        self.if_not_exists = true;
+        self
+    }
+
+    /// Add a column named `name` of the SQL type `sql_type`.  The type is used verbatim.
+    pub fn column<N: Into<String>, T: Into<String>>(mut self, name: N, sql_type: T) -> Self {
+        self.columns.push((name.into(), sql_type.into()));
+        self
+    }
+
+    /// The properly quoted, possibly schema-qualified, name of the table
+    pub fn qualified_name(&self) -> String {
+        match (self.persistence, &self.schema) {
+            (RelPersistence::Temporary, _) => {
+                quote_qualified_identifier("pg_temp", self.name.as_str())
+            }
+            (_, Some(schema)) => quote_qualified_identifier(schema.as_str(), self.name.as_str()),
+            (_, None) => quote_identifier(&self.name),
+        }
+    }
+
+    /// The `CREATE TABLE` statement this builder will execute
+    pub fn to_sql(&self) -> String {
+        let persistence = match self.persistence {
+            RelPersistence::Permanent => "",
+            RelPersistence::Unlogged => "UNLOGGED ",
+            RelPersistence::Temporary => "TEMPORARY ",
+        };
+        let if_not_exists = if self.if_not_exists { "IF NOT EXISTS " } else { "" };
+        let columns = self
+            .columns
+            .iter()
+            .map(|(name, sql_type)| format!("{} {}", quote_identifier(name), sql_type))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let mut sql = format!(
+            "CREATE {persistence}TABLE {if_not_exists}{} ({columns})",
+            self.qualified_name()
+        );
+        if let Some(on_commit) = self.on_commit {
+            sql.push(' ');
+            sql.push_str(on_commit.as_sql());
+        }
+        sql
+    }
+
+    /// Create the table and return its oid
+    pub fn create(self) -> spi::Result<pg_sys::Oid> {
+        Spi::run(&self.to_sql())?;
+        let oid = Spi::get_one_with_args::<pg_sys::Oid>(
+            "SELECT $1::regclass::oid",
+            vec![(PgBuiltInOids::TEXTOID.oid(), self.qualified_name().into_datum())],
+        )?;
+        Ok(oid.expect("newly created table has no oid"))
+    }
+}
+
+/// Returns the oid of this backend's temporary schema, if it has one yet.
+///
+/// Postgres creates the temporary schema lazily, the first time a temporary object is created.
+pub fn my_temp_namespace() -> Option<pg_sys::Oid> {
+    let mut temp_namespace = pg_sys::InvalidOid;
+    let mut temp_toast_namespace = pg_sys::InvalidOid;
+    unsafe {
+        // SAFETY:  both arguments are valid pointers to stack-allocated Oids
+        pg_sys::GetTempNamespaceState(&mut temp_namespace, &mut temp_toast_namespace);
+    }
+    (temp_namespace != pg_sys::InvalidOid).then_some(temp_namespace)
+}
+
+/// Is `namespace` this backend's temporary schema?
+#[inline]
+pub fn is_temp_namespace(namespace: pg_sys::Oid) -> bool {
+    unsafe {
+        // SAFETY:  isTempNamespace() works with any oid
+        pg_sys::isTempNamespace(namespace)
+    }
+}
+
+/// Is `namespace` the temporary schema (or its toast schema) of any backend?
+#[inline]
+pub fn is_any_temp_namespace(namespace: pg_sys::Oid) -> bool {
+    unsafe {
+        // SAFETY:  isAnyTempNamespace() works with any oid
+        pg_sys::isAnyTempNamespace(namespace)
+    }
+}
+
+/// Would an unqualified `CREATE` statement create its object in this backend's temporary schema?
+///
+/// This is the case when `pg_temp` is explicitly placed first on the `search_path`, such as within
+/// `SECURITY DEFINER` functions that follow the Postgres recommendations for a safe `search_path`.
+#[cfg(feature = "cshim")]
+pub fn creation_namespace_is_temp() -> bool {
+    use crate::PgList;
+
+    let search_path = unsafe {
+        // SAFETY:  fetch_search_path() returns a freshly palloc'd list of oids, or NIL
+        PgList::<pg_sys::Oid>::from_pg(pg_sys::fetch_search_path(false))
+    };
+    search_path.iter_oid().next().map(is_temp_namespace).unwrap_or(false)
+}
@@ -0,0 +1,309 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Buffered, batched inserts into a table from a background worker
+//!
+//! A [`BufferedTableWriter`] accumulates rows in memory and writes them out with multi-row
+//! `INSERT` statements.  Each batch is written in its own transaction, so a batch is either
+//! entirely committed or not at all, and a failing batch is retried without losing the rows
+//! already committed by earlier batches.
+//!
+//! Rows are described by the [`TableRow`] trait, which is usually derived with
+//! `#[derive(TableRow)]`.
+//!
+//! SPI has no way to feed `COPY ... FROM STDIN`, so batches are always written as `INSERT`s.
+use crate::bgworkers::BackgroundWorker;
+use crate::pg_sys::panic::CaughtError;
+use crate::spi::{quote_identifier, quote_qualified_identifier, Spi};
+use crate::{pg_sys, PgOid, PgTryBuilder};
+use std::collections::VecDeque;
+use std::panic::AssertUnwindSafe;
+use std::time::Duration;
+
+/// Postgres' limit on the number of parameters a single statement may have
+const MAX_STATEMENT_PARAMS: usize = u16::MAX as usize;
+
+/// A Rust type that maps onto a row of a table.
+///
+/// Usually derived with `#[derive(TableRow)]`, which maps each named field onto a column of the
+/// same name.  A field's column can be renamed with `#[column = "name"]`.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgrx::prelude::*;
+/// use pgrx::table_writer::TableRow;
+///
+/// #[derive(Clone, TableRow)]
+/// struct Event {
+///     id: i64,
+///     #[column = "event_kind"]
+///     kind: String,
+///     note: Option<String>,
+/// }
+///
+/// assert_eq!(Event::COLUMNS, &["id", "event_kind", "note"]);
+/// ```
+pub trait TableRow {
+    /// The names of the columns this row is written to, in the same order as [`TableRow::into_args()`]
+    const COLUMNS: &'static [&'static str];
+
+    /// Convert this row into its column values, with the type oid of each
+    fn into_args(self) -> Vec<(PgOid, Option<pg_sys::Datum>)>;
+}
+
+/// Errors raised while flushing a [`BufferedTableWriter`]
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum TableWriterError {
+    /// A batch still failed after the configured number of attempts.  The batch, and every row
+    /// behind it, remains buffered.
+    #[error(
+        "writing a batch of {rows} rows to {table} failed after {attempts} attempts: {message}"
+    )]
+    RetriesExhausted { table: String, rows: usize, attempts: u32, message: String },
+
+    /// The background worker received a SIGTERM while waiting to retry a batch
+    #[error("interrupted by SIGTERM while retrying a batch for {0}")]
+    Interrupted(String),
+}
+
+/// Accumulates rows of type `R` and writes them to a table in batches from a background worker.
+///
+/// Pushing onto a writer that already holds [`BufferedTableWriter::max_buffered()`] rows fails
+/// and hands the row back, so that a worker producing rows faster than they can be written is
+/// forced to flush (or shed load) rather than grow without bound.
+///
+/// [`BufferedTableWriter::flush()`] must be called from a background worker that has connected to
+/// a database with `BackgroundWorker::connect_worker_to_spi()`, and outside of any transaction,
+/// as it starts and commits a transaction for every batch.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgrx::prelude::*;
+/// use pgrx::bgworkers::BackgroundWorker;
+/// use pgrx::table_writer::{BufferedTableWriter, TableRow};
+/// use std::time::Duration;
+///
+/// #[derive(Clone, TableRow)]
+/// struct Sample {
+///     sensor: i32,
+///     reading: f64,
+/// }
+///
+/// let mut writer = BufferedTableWriter::<Sample>::new("samples")
+///     .batch_size(500)
+///     .max_buffered(10_000)
+///     .max_retries(3);
+///
+/// while BackgroundWorker::wait_latch(Some(Duration::from_millis(100))) {
+///     let sample = Sample { sensor: 1, reading: 42.0 };
+///     if let Err(sample) = writer.push(sample) {
+///         writer.flush().expect("failed to flush samples");
+///         writer.push(sample).ok();
+///     }
+///     if writer.should_flush() {
+///         writer.flush().expect("failed to flush samples");
+///     }
+/// }
+/// ```
+pub struct BufferedTableWriter<R: TableRow + Clone> {
+    table: String,
+    batch_size: usize,
+    max_buffered: usize,
+    max_retries: u32,
+    retry_delay: Duration,
+    buffer: VecDeque<R>,
+}
+
+impl<R: TableRow + Clone> BufferedTableWriter<R> {
+    /// Create a writer for the table named `table`, resolved using the `search_path`
+    pub fn new<S: AsRef<str>>(table: S) -> Self {
+        Self::with_table_sql(quote_identifier(table.as_ref()))
+    }
+
+    /// Create a writer for the table named `table` in `schema`
+    pub fn with_schema<S: AsRef<str>, T: AsRef<str>>(schema: S, table: T) -> Self {
+        Self::with_table_sql(quote_qualified_identifier(schema.as_ref(), table.as_ref()))
+    }
+
+    fn with_table_sql(table: String) -> Self {
+        BufferedTableWriter {
+            table,
+            batch_size: 1000,
+            max_buffered: 100_000,
+            max_retries: 3,
+            retry_delay: Duration::from_millis(100),
+            buffer: VecDeque::new(),
+        }
+    }
+
+    /// The number of rows written by each `INSERT`, and so in each transaction.  Defaults to 1000.
+    ///
+    /// The batch is made smaller if needed to stay within Postgres' limit of 65535 parameters
+    /// per statement.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// The number of rows that can be buffered before [`BufferedTableWriter::push()`] starts
+    /// refusing them.  Defaults to 100,000.
+    pub fn max_buffered(mut self, max_buffered: usize) -> Self {
+        self.max_buffered = max_buffered.max(1);
+        self
+    }
+
+    /// How many times a failing batch is retried before [`BufferedTableWriter::flush()`] gives up.
+    /// Defaults to 3.
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// How long to wait before the first retry of a failing batch.  The delay doubles with each
+    /// subsequent retry.  Defaults to 100ms.
+    pub fn retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// The number of rows waiting to be written
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Are there no rows waiting to be written?
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// Is there at least a full batch waiting to be written?
+    pub fn should_flush(&self) -> bool {
+        self.buffer.len() >= self.effective_batch_size()
+    }
+
+    /// Buffer `row` to be written by a later [`BufferedTableWriter::flush()`].
+    ///
+    /// Returns the row back if the buffer is already full.
+    pub fn push(&mut self, row: R) -> Result<(), R> {
+        if self.buffer.len() >= self.max_buffered {
+            return Err(row);
+        }
+        self.buffer.push_back(row);
+        Ok(())
+    }
+
+    /// Write every buffered row, one batch per transaction, returning the number of rows written.
+    ///
+    /// If a batch fails, its transaction is rolled back and it is retried.  Once the retries are
+    /// exhausted the remaining rows stay buffered and an error is returned, so nothing is lost
+    /// and the caller can decide whether to try again later.  A Rust panic while writing a batch
+    /// is not retried.
+    pub fn flush(&mut self) -> Result<usize, TableWriterError> {
+        let mut written = 0;
+        while !self.buffer.is_empty() {
+            let rows = self.buffer.len().min(self.effective_batch_size());
+            self.write_batch(rows)?;
+            self.buffer.drain(..rows);
+            written += rows;
+        }
+        Ok(written)
+    }
+
+    fn effective_batch_size(&self) -> usize {
+        let max_rows = MAX_STATEMENT_PARAMS / R::COLUMNS.len().max(1);
+        self.batch_size.min(max_rows).max(1)
+    }
+
+    fn write_batch(&self, rows: usize) -> Result<(), TableWriterError> {
+        let sql = self.insert_sql(rows);
+        let mut delay = self.retry_delay;
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let batch = self.buffer.iter().take(rows).cloned().collect::<Vec<_>>();
+            let sql = sql.as_str();
+            let result = BackgroundWorker::transaction(AssertUnwindSafe(move || unsafe {
+                // SAFETY:  we're inside a transaction started by BackgroundWorker::transaction()
+                in_subtransaction(AssertUnwindSafe(move || {
+                    let args = batch.into_iter().flat_map(TableRow::into_args).collect();
+                    Spi::run_with_args(sql, Some(args))
+                }))
+            }));
+
+            let message = match result {
+                Ok(Ok(())) => return Ok(()),
+                Ok(Err(e)) => e.to_string(),
+                Err(CaughtError::PostgresError(e)) | Err(CaughtError::ErrorReport(e)) => {
+                    e.message().to_string()
+                }
+                Err(e @ CaughtError::RustPanic { .. }) => e.rethrow(),
+            };
+
+            if attempt > self.max_retries {
+                return Err(TableWriterError::RetriesExhausted {
+                    table: self.table.clone(),
+                    rows,
+                    attempts: attempt,
+                    message,
+                });
+            }
+            if !BackgroundWorker::wait_latch(Some(delay)) {
+                return Err(TableWriterError::Interrupted(self.table.clone()));
+            }
+            delay = delay.saturating_mul(2);
+        }
+    }
+
+    fn insert_sql(&self, rows: usize) -> String {
+        let ncols = R::COLUMNS.len();
+        let columns = R::COLUMNS.iter().map(|c| quote_identifier(c)).collect::<Vec<_>>().join(", ");
+        let values = (0..rows)
+            .map(|row| {
+                let params = (1..=ncols)
+                    .map(|col| format!("${}", row * ncols + col))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("({params})")
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("INSERT INTO {} ({columns}) VALUES {values}", self.table)
+    }
+}
+
+/// Run `f` in an internal subtransaction, rolling it back if `f` raises an error
+///
+/// # Safety
+///
+/// Must be called from within a transaction
+unsafe fn in_subtransaction<F, T>(f: F) -> Result<T, CaughtError>
+where
+    F: FnOnce() -> T + std::panic::UnwindSafe,
+{
+    let memcxt = pg_sys::CurrentMemoryContext;
+    let owner = pg_sys::CurrentResourceOwner;
+    pg_sys::BeginInternalSubTransaction(std::ptr::null());
+
+    PgTryBuilder::new(|| {
+        let result = f();
+        pg_sys::ReleaseCurrentSubTransaction();
+        pg_sys::CurrentMemoryContext = memcxt;
+        pg_sys::CurrentResourceOwner = owner;
+        Ok(result)
+    })
+    .catch_others(|e| {
+        pg_sys::CurrentMemoryContext = memcxt;
+        pg_sys::RollbackAndReleaseCurrentSubTransaction();
+        pg_sys::CurrentResourceOwner = owner;
+        Err(e)
+    })
+    .execute()
+}
@@ -0,0 +1,245 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! Rust threads bounded to the lifetime of a Postgres function call
+//!
+//! Threads started with [`std::thread::spawn`] from inside a backend can outlive the query that
+//! started them, and crash the backend when it exits underneath them.  They also can't notice when
+//! the query is cancelled, because Postgres only tells the backend's own thread.
+//!
+//! [`scoped_worker`] fixes both: every thread spawned in its scope is joined before it returns, even
+//! when unwinding from an error, and each is handed a [`CancellationToken`] which becomes cancelled
+//! when the backend receives a query cancel (`SIGINT`) or terminate (`SIGTERM`) interrupt.  Once the
+//! threads have stopped, the interrupt is serviced on the backend thread as usual, raising the
+//! `ERROR` or `FATAL` Postgres would have.
+//!
+//! The spawned threads must not call into Postgres.  `pgrx` panics if they try.
+//!
+//! With the `rayon` feature, [`ComputePool`] builds on this to run parallel iterators in a bounded
+//! thread pool.
+//!
+//! ```rust,no_run
+//! use pgrx::thread::scoped_worker;
+//!
+//! let data = vec![1u64; 1_000_000];
+//! let sum: u64 = scoped_worker(|scope| {
+//!     let handles = data
+//!         .chunks(100_000)
+//!         .map(|chunk| {
+//!             scope.spawn(move |token| {
+//!                 let mut sum = 0;
+//!                 for n in chunk {
+//!                     if token.is_cancelled() {
+//!                         break;
+//!                     }
+//!                     sum += n;
+//!                 }
+//!                 sum
+//!             })
+//!         })
+//!         .collect::<Vec<_>>();
+//!     handles.into_iter().map(|handle| handle.join()).sum()
+//! });
+//! ```
+use crate::pg_sys;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::{Scope, ScopedJoinHandle};
+
+#[cfg(feature = "rayon")]
+mod pool;
+#[cfg(feature = "rayon")]
+pub use pool::{ComputeContext, ComputePool};
+
+/// How long the backend thread sleeps between checks on its workers, in milliseconds
+const POLL_INTERVAL_MS: libc::c_long = 10;
+
+/// The error [`CancellationToken::check`] returns once cancelled
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("the worker was cancelled by a Postgres interrupt")]
+pub struct Cancelled;
+
+/// Tells a thread spawned by [`WorkerScope::spawn`] that it should stop early
+///
+/// Long running work should check it often: the backend can't service the interrupt, and so can't
+/// cancel the query, until every thread in the scope has returned.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Has the backend been asked to cancel the query or terminate?
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// `Err(Cancelled)` once cancelled, for use with `?`
+    #[inline]
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed)
+    }
+}
+
+/// Spawns threads which can't outlive the [`scoped_worker`] call that created it
+pub struct WorkerScope<'scope, 'env: 'scope> {
+    scope: &'scope Scope<'scope, 'env>,
+    token: CancellationToken,
+    running: Arc<AtomicUsize>,
+}
+
+impl<'scope, 'env> WorkerScope<'scope, 'env> {
+    /// Spawn a thread running `f`, which is given this scope's [`CancellationToken`]
+    ///
+    /// `f` must not call into Postgres.
+    pub fn spawn<F, T>(&self, f: F) -> WorkerHandle<'scope, T>
+    where
+        F: FnOnce(CancellationToken) -> T + Send + 'scope,
+        T: Send + 'scope,
+    {
+        struct Finished(Arc<AtomicUsize>);
+        impl Drop for Finished {
+            fn drop(&mut self) {
+                self.0.fetch_sub(1, Ordering::Release);
+            }
+        }
+
+        self.running.fetch_add(1, Ordering::Relaxed);
+        let finished = Finished(self.running.clone());
+        let token = self.token.clone();
+        let handle = self.scope.spawn(move || {
+            let _finished = finished;
+            f(token)
+        });
+        WorkerHandle { handle, token: self.token.clone() }
+    }
+
+    /// The token given to every thread in this scope
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+/// A thread spawned by [`WorkerScope::spawn`]
+pub struct WorkerHandle<'scope, T> {
+    handle: ScopedJoinHandle<'scope, T>,
+    token: CancellationToken,
+}
+
+impl<'scope, T> WorkerHandle<'scope, T> {
+    /// Has the thread returned?
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    /// Wait for the thread to return, and return its result
+    ///
+    /// The wait is interruptible: should the backend be interrupted in the meantime, the scope's
+    /// threads are cancelled and, once this one has returned, the interrupt is serviced, which
+    /// usually raises an `ERROR`.  A panic in the thread is propagated to the caller.
+    pub fn join(self) -> T {
+        wait_until(&self.token, || self.handle.is_finished());
+        let result = self.handle.join();
+        if self.token.is_cancelled() {
+            pg_sys::check_for_interrupts!();
+        }
+        match result {
+            Ok(value) => value,
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
+}
+
+/// Run `f` with a [`WorkerScope`] for spawning threads, and wait for them all to return
+///
+/// Threads `f` doesn't [`join`](WorkerHandle::join) are waited for after it returns.  That waiting
+/// is interruptible, as `join` is.  If `f` raises an error or panics, the scope's threads are
+/// cancelled and waited for before the error continues on.
+///
+/// Must be called from the backend's own thread.
+pub fn scoped_worker<'env, F, T>(f: F) -> T
+where
+    F: for<'scope> FnOnce(&WorkerScope<'scope, 'env>) -> T,
+{
+    struct CancelOnUnwind(CancellationToken);
+    impl Drop for CancelOnUnwind {
+        fn drop(&mut self) {
+            if std::thread::panicking() {
+                self.0.cancel();
+            }
+        }
+    }
+
+    let token = CancellationToken::default();
+    let value = std::thread::scope(|scope| {
+        let worker_scope =
+            WorkerScope { scope, token: token.clone(), running: Arc::new(AtomicUsize::new(0)) };
+        // the threads have to stop on their own before `std::thread::scope` can join them, so make
+        // sure they're told to when unwinding
+        let _guard = CancelOnUnwind(token.clone());
+        let value = f(&worker_scope);
+        wait_until(&token, || worker_scope.running.load(Ordering::Acquire) == 0);
+        value
+    });
+    if token.is_cancelled() {
+        pg_sys::check_for_interrupts!();
+    }
+    value
+}
+
+/// Sleep on the backend's latch until `done`, cancelling `token` if interrupted meanwhile
+fn wait_until(token: &CancellationToken, done: impl Fn() -> bool) {
+    let mut postmaster_died = false;
+    while !done() {
+        if interrupt_pending() {
+            token.cancel();
+        }
+        if postmaster_died {
+            // the latch can't wait anymore, it returns immediately
+            std::thread::sleep(std::time::Duration::from_millis(POLL_INTERVAL_MS as u64));
+            continue;
+        }
+        unsafe {
+            let rc = pg_sys::WaitLatch(
+                pg_sys::MyLatch,
+                (pg_sys::WL_LATCH_SET | pg_sys::WL_TIMEOUT | pg_sys::WL_POSTMASTER_DEATH) as i32,
+                POLL_INTERVAL_MS,
+                pg_sys::PG_WAIT_EXTENSION,
+            );
+            pg_sys::ResetLatch(pg_sys::MyLatch);
+            if rc & pg_sys::WL_POSTMASTER_DEATH as i32 != 0 {
+                postmaster_died = true;
+                token.cancel();
+            }
+        }
+    }
+}
+
+/// Is a query cancel or terminate waiting to be serviced?
+fn interrupt_pending() -> bool {
+    unsafe {
+        let cancel = std::ptr::read_volatile(std::ptr::addr_of!(pg_sys::QueryCancelPending));
+        let die = std::ptr::read_volatile(std::ptr::addr_of!(pg_sys::ProcDiePending));
+        #[cfg(feature = "pg11")]
+        {
+            cancel || die
+        }
+        #[cfg(not(feature = "pg11"))]
+        {
+            cancel != 0 || die != 0
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//LICENSE Portions Copyright 2019-2021 ZomboDB, LLC.
+//LICENSE
+//LICENSE Portions Copyright 2021-2023 Technology Concepts & Design, Inc.
+//LICENSE
+//LICENSE Portions Copyright 2023-2023 PgCentral Foundation, Inc. <contact@pgcentral.org>
+//LICENSE
+//LICENSE All rights reserved.
+//LICENSE
+//LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+//! A [`rayon`] thread pool for CPU-bound work inside a backend
+use crate::thread::{scoped_worker, CancellationToken, Cancelled};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+/// A bounded pool of threads for parallelizing pure Rust work, such as compression or inference,
+/// within one backend
+///
+/// Work given to [`ComputePool::run`] executes on the pool's threads while the backend waits
+/// interruptibly, as with [`scoped_worker`].  When the query is cancelled, the work is told through
+/// its [`ComputeContext`], whose helpers check before starting each task.  The work must be `Send`,
+/// which rules out most of Postgres' pointer types, and `pgrx` panics on any call into Postgres from
+/// a pool thread.
+///
+/// Create pools lazily from within the backend that uses them, typically in a `static`, rather than
+/// in `_PG_init`: that can run in the postmaster, whose threads don't survive the fork into
+/// backends.
+///
+/// ```rust,no_run
+/// use pgrx::thread::ComputePool;
+/// use once_cell::sync::Lazy;
+///
+/// static POOL: Lazy<ComputePool> = Lazy::new(|| ComputePool::new(4).expect("no threads"));
+///
+/// fn compress_all(blocks: Vec<Vec<u8>>) -> Vec<Vec<u8>> {
+///     POOL.run(|ctx| ctx.map(blocks, |block| block.into_iter().rev().collect()))
+///         .expect("cancelled")
+/// }
+/// ```
+pub struct ComputePool {
+    pool: rayon::ThreadPool,
+}
+
+impl ComputePool {
+    /// A pool of `num_threads` threads.  Zero picks rayon's default, the number of CPUs
+    pub fn new(num_threads: usize) -> Result<Self, rayon::ThreadPoolBuildError> {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .thread_name(|i| format!("pgrx-compute-{i}"))
+            .build()?;
+        Ok(Self { pool })
+    }
+
+    /// The number of threads in the pool
+    pub fn num_threads(&self) -> usize {
+        self.pool.current_num_threads()
+    }
+
+    /// Run `f` in the pool and wait for its result
+    ///
+    /// Any rayon parallel iterators or `rayon::join`s within `f` also use this pool.  Should the
+    /// backend be interrupted while waiting, `f`'s [`ComputeContext`] is cancelled and, once `f` has
+    /// returned, the interrupt is serviced, which usually raises an `ERROR`.
+    ///
+    /// Must be called from the backend's own thread.
+    pub fn run<F, T>(&self, f: F) -> T
+    where
+        F: FnOnce(&ComputeContext) -> T + Send,
+        T: Send,
+    {
+        scoped_worker(|scope| {
+            scope
+                .spawn(|token| {
+                    let ctx = ComputeContext { token };
+                    self.pool.install(|| f(&ctx))
+                })
+                .join()
+        })
+    }
+}
+
+/// Given to the work run by [`ComputePool::run`], to notice cancellation and split up the work
+///
+/// The helpers check for cancellation before starting each task, and return `Err(Cancelled)`
+/// without running the rest once cancelled.
+pub struct ComputeContext {
+    token: CancellationToken,
+}
+
+impl ComputeContext {
+    /// The token cancelled when the backend is interrupted
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// `Err(Cancelled)` once the backend has been interrupted
+    #[inline]
+    pub fn check(&self) -> Result<(), Cancelled> {
+        self.token.check()
+    }
+
+    /// Apply `f` to each of `items` in parallel, collecting the results in order
+    pub fn map<I, F, R>(&self, items: I, f: F) -> Result<Vec<R>, Cancelled>
+    where
+        I: IntoParallelIterator,
+        F: Fn(I::Item) -> R + Sync + Send,
+        R: Send,
+    {
+        items
+            .into_par_iter()
+            .map(|item| {
+                self.check()?;
+                Ok(f(item))
+            })
+            .collect()
+    }
+
+    /// Apply `f` to each of `items` in parallel
+    pub fn for_each<I, F>(&self, items: I, f: F) -> Result<(), Cancelled>
+    where
+        I: IntoParallelIterator,
+        F: Fn(I::Item) + Sync + Send,
+    {
+        items.into_par_iter().try_for_each(|item| {
+            self.check()?;
+            f(item);
+            Ok(())
+        })
+    }
+
+    /// Run `a` and `b`, potentially in parallel
+    pub fn join<A, B, RA, RB>(&self, a: A, b: B) -> Result<(RA, RB), Cancelled>
+    where
+        A: FnOnce() -> RA + Send,
+        B: FnOnce() -> RB + Send,
+        RA: Send,
+        RB: Send,
+    {
+        let (a, b) = rayon::join(
+            || {
+                self.check()?;
+                Ok(a())
+            },
+            || {
+                self.check()?;
+                Ok(b())
+            },
+        );
+        Ok((a?, b?))
+    }
+}
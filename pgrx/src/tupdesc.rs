@@ -197,6 +197,37 @@ impl<'a> PgTupleDesc<'a> {
         }
     }
 
+    /// The row type the caller expects back from the function being called by `fcinfo`.
+    ///
+    /// For a function declared as `RETURNS record` this is the column definition list supplied by
+    /// the caller, such as `SELECT * FROM my_func() AS t(a int, b text)`, which lets the function
+    /// adapt its output to whatever columns were asked for.  Functions returning a named composite
+    /// type get that type's descriptor.
+    ///
+    /// Returns `None` if the function doesn't return a row type, or if it returns `record` and the
+    /// caller didn't say what columns it expects.
+    ///
+    /// The returned descriptor is "blessed", so tuples built with it can be returned as `record`
+    /// datums.
+    ///
+    /// ## Safety
+    ///
+    /// This method is unsafe as we cannot validate that the provided `fcinfo` is valid
+    pub unsafe fn for_call_result<'b>(fcinfo: pg_sys::FunctionCallInfo) -> Option<PgTupleDesc<'b>> {
+        let mut tupdesc = std::ptr::null_mut();
+        match pg_sys::get_call_result_type(fcinfo, std::ptr::null_mut(), &mut tupdesc) {
+            pg_sys::TypeFuncClass_TYPEFUNC_COMPOSITE
+            | pg_sys::TypeFuncClass_TYPEFUNC_COMPOSITE_DOMAIN => {
+                // the descriptor belongs to the caller, so make our own copy before blessing it,
+                // which assigns a typmod to anonymous record types
+                let tupdesc = PgTupleDesc::from_pg_copy(tupdesc);
+                pg_sys::BlessTupleDesc(tupdesc.as_ptr());
+                Some(tupdesc)
+            }
+            _ => None,
+        }
+    }
+
     /// From which relation was this TupleDesc created, if any?
     pub fn parent(&self) -> Option<&PgRelation> {
         self.parent
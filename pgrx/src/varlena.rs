@@ -9,7 +9,9 @@
 //LICENSE Use of this source code is governed by the MIT license that can be found in the LICENSE file.
 //! Helper functions to work with Postgres `varlena *` structures
 
+use crate::memcxt::MemCx;
 use crate::{pg_sys, PgBox};
+use core::ffi::CStr;
 use core::{slice, str};
 
 /// # Safety
@@ -57,6 +59,10 @@ pub unsafe fn set_varsize_short(ptr: *mut pg_sys::varlena, len: i32) {
 /// ```c
 /// #define VARSIZE_EXTERNAL(PTR)                        (VARHDRSZ_EXTERNAL + VARTAG_SIZE(VARTAG_EXTERNAL(PTR)))
 /// ```
+#[cfg_attr(
+    all(feature = "forbid-panics", not(debug_assertions)),
+    deprecated(note = "panics on an unrecognized tag, use `panic_free::varsize_any()`")
+)]
 #[inline]
 pub unsafe fn varsize_external(ptr: *const pg_sys::varlena) -> usize {
     pg_sys::VARHDRSZ_EXTERNAL() + vartag_size(vartag_external(ptr) as pg_sys::vartag_external)
@@ -86,16 +92,29 @@ pub unsafe fn vartag_is_expanded(tag: pg_sys::vartag_external) -> bool {
 ///       (tag) == VARTAG_ONDISK ? sizeof(varatt_external) : \
 ///       TrapMacro(true, "unrecognized TOAST vartag"))
 /// ```
+#[cfg_attr(
+    all(feature = "forbid-panics", not(debug_assertions)),
+    deprecated(note = "panics on an unrecognized tag, use `try_vartag_size()`")
+)]
 #[inline]
 pub unsafe fn vartag_size(tag: pg_sys::vartag_external) -> usize {
+    match try_vartag_size(tag) {
+        Some(size) => size,
+        None => panic!("unrecognized TOAST vartag"),
+    }
+}
+
+/// Like [`vartag_size`], but returns `None` for an unrecognized tag rather than panicking
+#[inline]
+pub unsafe fn try_vartag_size(tag: pg_sys::vartag_external) -> Option<usize> {
     if tag == pg_sys::vartag_external_VARTAG_INDIRECT {
-        std::mem::size_of::<pg_sys::varatt_indirect>()
+        Some(std::mem::size_of::<pg_sys::varatt_indirect>())
     } else if vartag_is_expanded(tag) {
-        std::mem::size_of::<pg_sys::varatt_expanded>()
+        Some(std::mem::size_of::<pg_sys::varatt_expanded>())
     } else if tag == pg_sys::vartag_external_VARTAG_ONDISK {
-        std::mem::size_of::<pg_sys::varatt_external>()
+        Some(std::mem::size_of::<pg_sys::varatt_external>())
     } else {
-        panic!("unrecognized TOAST vartag")
+        None
     }
 }
 
@@ -203,6 +222,10 @@ pub unsafe fn varatt_not_pad_byte(ptr: *const pg_sys::varlena) -> bool {
 ///       (VARATT_IS_1B(PTR) ? VARSIZE_1B(PTR) : \
 ///        VARSIZE_4B(PTR)))
 /// ```
+#[cfg_attr(
+    all(feature = "forbid-panics", not(debug_assertions)),
+    deprecated(note = "panics on an unrecognized tag, use `panic_free::varsize_any()`")
+)]
 #[inline]
 pub unsafe fn varsize_any(ptr: *const pg_sys::varlena) -> usize {
     if varatt_is_1b_e(ptr) {
@@ -226,6 +249,10 @@ pub unsafe fn varsize_any(ptr: *const pg_sys::varlena) -> usize {
 ///               ) \
 ///         )
 /// ```
+#[cfg_attr(
+    all(feature = "forbid-panics", not(debug_assertions)),
+    deprecated(note = "panics on an unrecognized tag, use `panic_free::varlena_data()`")
+)]
 #[inline]
 pub unsafe fn varsize_any_exhdr(ptr: *const pg_sys::varlena) -> usize {
     if varatt_is_1b_e(ptr) {
@@ -298,6 +325,10 @@ pub unsafe fn vardata_any(ptr: *const pg_sys::varlena) -> *const std::os::raw::c
 /// ## Safety
 ///
 /// This function is unsafe because it blindly dereferences the varlena pointer argument
+#[cfg_attr(
+    all(feature = "forbid-panics", not(debug_assertions)),
+    deprecated(note = "panics on an unrecognized tag, use `panic_free::varsize_any()`")
+)]
 #[inline]
 pub unsafe fn varlena_size(t: *const pg_sys::varlena) -> usize {
     std::mem::size_of_val(&(*t).vl_len_) + varsize_any_exhdr(t)
@@ -305,33 +336,169 @@ pub unsafe fn varlena_size(t: *const pg_sys::varlena) -> usize {
 
 /// Convert a Postgres `varlena *` (or `text *`) into a Rust `&str`.
 ///
+/// In a database whose encoding isn't UTF-8, text with any non-ASCII characters is transcoded into
+/// a UTF-8 copy allocated in `CurrentMemoryContext`.
+///
 /// ## Safety
 ///
 /// This function is unsafe because it blindly assumes the provided varlena pointer is non-null.
 ///
-/// Note also that this function is zero-copy and the underlying Rust &str is backed by Postgres-allocated
-/// memory.  As such, the return value will become invalid the moment Postgres frees the varlena
+/// Note also that this function is otherwise zero-copy and the underlying Rust &str is backed by
+/// Postgres-allocated memory.  As such, the return value will become invalid the moment Postgres
+/// frees the varlena, or resets `CurrentMemoryContext`
 #[inline]
 pub unsafe fn text_to_rust_str<'a>(
     varlena: *const pg_sys::varlena,
 ) -> Result<&'a str, str::Utf8Error> {
-    let len = varsize_any_exhdr(varlena);
-    let data = vardata_any(varlena);
-
-    str::from_utf8(slice::from_raw_parts(data as *mut u8, len))
+    str::from_utf8(server_to_utf8_in_current_context(varlena_to_byte_slice(varlena)))
 }
 
 /// Convert a Postgres `varlena *` (or `text *`) into a Rust `&str`.
 ///
 /// ## Safety
 ///
-/// As `text_to_rust_str` but with the additional safety contract that the data is UTF-8.
+/// As `text_to_rust_str` but with the additional safety contract that, in a UTF-8 or `SQL_ASCII`
+/// database, the data is UTF-8.  Postgres transcodes text in any other encoding into valid UTF-8.
 #[inline]
 pub unsafe fn text_to_rust_str_unchecked<'a>(varlena: *const pg_sys::varlena) -> &'a str {
-    let len = varsize_any_exhdr(varlena);
-    let data = vardata_any(varlena);
+    str::from_utf8_unchecked(server_to_utf8_in_current_context(varlena_to_byte_slice(varlena)))
+}
+
+/// Transcode `bytes` from the Postgres encoding `from` into `to`, such as from
+/// [`pg_sys::pg_enc_PG_LATIN1`] into [`pg_sys::pg_enc_PG_UTF8`].
+///
+/// `bytes` are returned as they are if they need no conversion, and otherwise Postgres converts
+/// them into a copy allocated in `memcx`.
+///
+/// # Panics
+///
+/// Postgres raises an `ERROR` if `bytes` aren't valid in `from`, or have characters with no
+/// equivalent in `to`.
+pub fn transcode<'r>(
+    bytes: &'r [u8],
+    from: pg_sys::pg_enc,
+    to: pg_sys::pg_enc,
+    memcx: &MemCx<'r>,
+) -> &'r [u8] {
+    let len = i32::try_from(bytes.len()).expect("text is too long to transcode");
+    memcx.exec_in(|| unsafe {
+        // SAFETY:  Postgres only reads `len` bytes, and either returns them or a NUL-terminated
+        // copy allocated in `memcx`, which outlives `'r`
+        let converted =
+            pg_sys::pg_do_encoding_conversion(bytes.as_ptr().cast_mut(), len, from as _, to as _);
+        if converted.cast_const() == bytes.as_ptr() {
+            bytes
+        } else {
+            CStr::from_ptr(converted.cast()).to_bytes()
+        }
+    })
+}
+
+/// Transcode `bytes`, text in the database's encoding, into UTF-8.
+///
+/// In a UTF-8 or `SQL_ASCII` database, or if `bytes` are ASCII, they're returned as they are.
+/// Otherwise Postgres converts them into a copy allocated in `memcx`.
+///
+/// # Panics
+///
+/// Postgres raises an `ERROR` if `bytes` aren't valid in the database's encoding, or have
+/// characters with no equivalent in UTF-8.
+pub fn server_to_utf8<'r>(bytes: &'r [u8], memcx: &MemCx<'r>) -> &'r [u8] {
+    if !matches!(*crate::UTF8DATABASE, crate::Utf8Compat::Ascii) || bytes.is_ascii() {
+        return bytes;
+    }
+
+    // SAFETY:  Postgres always has a valid database encoding once we can run
+    let encoding = unsafe { pg_sys::GetDatabaseEncoding() };
+    transcode(bytes, encoding as _, pg_sys::pg_enc_PG_UTF8, memcx)
+}
+
+/// The text of a `name`, transcoded from the database's encoding into UTF-8 as for
+/// [`server_to_utf8`].  The error is that of the first invalid UTF-8 in a `SQL_ASCII` database,
+/// where Postgres doesn't check what a name holds.
+///
+/// # Panics
+///
+/// The same as for [`server_to_utf8`]
+pub fn name_to_str<'r>(
+    name: &'r pg_sys::NameData,
+    memcx: &MemCx<'r>,
+) -> Result<&'r str, str::Utf8Error> {
+    str::from_utf8(server_to_utf8(name.as_bytes(), memcx))
+}
+
+/// Like [`name_to_str`], but copied into a `String`, with any invalid UTF-8 replaced by
+/// `U+FFFD`.
+pub fn name_to_string(name: &pg_sys::NameData) -> String {
+    crate::memcxt::current_context(|memcx| {
+        String::from_utf8_lossy(server_to_utf8(name.as_bytes(), memcx)).into_owned()
+    })
+}
+
+/// Like [`server_to_utf8`], but any copy is allocated in `CurrentMemoryContext`.
+///
+/// # Safety
+///
+/// The returned slice either borrows from `bytes` or is allocated in `CurrentMemoryContext`, so
+/// `'a` must outlive neither of them.
+pub(crate) unsafe fn server_to_utf8_in_current_context<'a>(bytes: &[u8]) -> &'a [u8] {
+    crate::memcxt::current_context(|memcx| {
+        let converted = server_to_utf8(bytes, memcx);
+        // SAFETY:  the caller has asserted `'a` doesn't outlive `bytes` or `CurrentMemoryContext`
+        slice::from_raw_parts(converted.as_ptr(), converted.len())
+    })
+}
 
-    str::from_utf8_unchecked(slice::from_raw_parts(data as *mut u8, len))
+/// The longest prefix of `bytes`, text in the database's encoding, which ends on a whole
+/// character, as UTF-8.  The error is that of the first invalid UTF-8 in a UTF-8 or `SQL_ASCII`
+/// database.
+///
+/// # Safety
+///
+/// The same as for [`server_to_utf8_in_current_context`]
+pub(crate) unsafe fn server_text_prefix<'a>(bytes: &[u8]) -> Result<&'a str, str::Utf8Error> {
+    if matches!(*crate::UTF8DATABASE, crate::Utf8Compat::Ascii) && !bytes.is_ascii() {
+        let len = i32::try_from(bytes.len()).unwrap_or(i32::MAX);
+        let whole = pg_sys::pg_mbcliplen(bytes.as_ptr().cast(), len, len) as usize;
+        // Postgres only transcodes into valid UTF-8
+        return Ok(str::from_utf8_unchecked(server_to_utf8_in_current_context(&bytes[..whole])));
+    }
+
+    let bytes: &'a [u8] = slice::from_raw_parts(bytes.as_ptr(), bytes.len());
+    match str::from_utf8(bytes) {
+        Ok(s) => Ok(s),
+        Err(e) if e.error_len().is_none() => {
+            Ok(str::from_utf8_unchecked(&bytes[..e.valid_up_to()]))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Transcode the UTF-8 `s` into the database's encoding, and call `f` with the result.
+///
+/// In a UTF-8 or `SQL_ASCII` database, or if `s` is ASCII, `f` is given `s` as it is.
+///
+/// # Panics
+///
+/// Postgres raises an `ERROR` if `s` has characters with no equivalent in the database's encoding.
+pub fn utf8_to_server<R>(s: &str, f: impl FnOnce(&[u8]) -> R) -> R {
+    if !matches!(*crate::UTF8DATABASE, crate::Utf8Compat::Ascii) || s.is_ascii() {
+        return f(s.as_bytes());
+    }
+
+    let len = i32::try_from(s.len()).expect("text is too long to transcode");
+    unsafe {
+        // SAFETY:  `s` is valid UTF-8, and Postgres either returns it or a converted copy
+        let converted =
+            pg_sys::pg_any_to_server(s.as_ptr().cast(), len, pg_sys::pg_enc_PG_UTF8 as _);
+        if converted.cast_const() == s.as_ptr().cast() {
+            f(s.as_bytes())
+        } else {
+            let result = f(CStr::from_ptr(converted).to_bytes());
+            pg_sys::pfree(converted.cast());
+            result
+        }
+    }
 }
 
 /// Convert a Postgres `varlena *` (or `byte *`) into a Rust `&[u8]`.
@@ -350,12 +517,167 @@ pub unsafe fn varlena_to_byte_slice<'a>(varlena: *const pg_sys::varlena) -> &'a
     std::slice::from_raw_parts(data as *const u8, len)
 }
 
-/// Convert a Rust `&str` into a Postgres `text *`.
+/// Detoast at most `len` bytes of a varlena Datum, starting at byte `offset`, into a Rust `&[u8]`.
+///
+/// Only as much of the value as the slice needs is fetched from its TOAST table, and a compressed
+/// value is only decompressed up to the end of the slice, so this is much cheaper than detoasting
+/// a large value to read its first few bytes.  The slice is shorter than `len` if the value ends
+/// before `offset + len`.
+///
+/// ## Safety
+///
+/// This function is unsafe because it blindly assumes the provided Datum is a non-null varlena.
+///
+/// The returned slice is usually backed by a copy allocated in `CurrentMemoryContext`, but may
+/// borrow from the Datum itself, so it lives no longer than either.
+pub unsafe fn detoast_slice<'a>(datum: pg_sys::Datum, offset: usize, len: usize) -> &'a [u8] {
+    let offset = i32::try_from(offset).unwrap_or(i32::MAX);
+    let len = i32::try_from(len).unwrap_or(i32::MAX);
+    let slice = pg_sys::pg_detoast_datum_slice(datum.cast_mut_ptr(), offset, len);
+    varlena_to_byte_slice(slice)
+}
+
+/// Detoast at most the first `len` bytes of a varlena Datum into a Rust `&[u8]`.
+///
+/// ## Safety
+///
+/// The same as for [`detoast_slice`]
+#[inline]
+pub unsafe fn detoast_prefix<'a>(datum: pg_sys::Datum, len: usize) -> &'a [u8] {
+    detoast_slice(datum, 0, len)
+}
+
+/// Detoast at most the first `len` bytes of a `text` Datum into a Rust `&str`.
+///
+/// The prefix is shortened to the last whole character, as `len` bytes may end partway through a
+/// multibyte one.  In a database whose encoding isn't UTF-8, it's then transcoded into UTF-8.
+///
+/// ## Panics
+///
+/// If the prefix isn't valid UTF-8, as `text` in a `SQL_ASCII` database needn't be, the same as
+/// [`PgStr::prefix()`](crate::PgStr::prefix).
+///
+/// ## Safety
+///
+/// The same as for [`detoast_slice`], with the additional safety contract that the Datum is
+/// `text`.
+pub unsafe fn detoast_text_prefix<'a>(datum: pg_sys::Datum, len: usize) -> &'a str {
+    let bytes = detoast_prefix(datum, len);
+    match server_text_prefix(bytes) {
+        Ok(s) => s,
+        Err(e) => panic!("text is not valid UTF-8: {e}"),
+    }
+}
+
+/// A [`std::io::Read`]er over the bytes of a varlena Datum, which detoasts the value `chunk_size`
+/// bytes at a time, with [`detoast_slice`], instead of all at once.
+///
+/// This is most worthwhile for values stored out of line and uncompressed, in a column whose
+/// `STORAGE` is `external`, as each chunk is then read straight from the TOAST table.  Reading a
+/// compressed value this way decompresses it again from the start for each chunk.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgrx::prelude::*;
+/// use pgrx::varlena::DetoastReader;
+/// use std::io::Read;
+///
+/// fn count_zeros(datum: pg_sys::Datum) -> usize {
+///     let mut reader = unsafe { DetoastReader::new(datum, 64 * 1024) };
+///     let mut zeros = 0;
+///     let mut buf = [0u8; 8192];
+///     loop {
+///         let n = reader.read(&mut buf).unwrap();
+///         if n == 0 {
+///             break;
+///         }
+///         zeros += buf[..n].iter().filter(|b| **b == 0).count();
+///     }
+///     zeros
+/// }
+/// ```
+pub struct DetoastReader {
+    datum: pg_sys::Datum,
+    len: usize,
+    offset: usize,
+    chunk_size: usize,
+    chunk: Vec<u8>,
+    consumed: usize,
+}
+
+impl DetoastReader {
+    /// Read the varlena `datum` in chunks of `chunk_size` bytes
+    ///
+    /// ## Safety
+    ///
+    /// This function is unsafe because it blindly assumes the provided Datum is a non-null varlena,
+    /// which must remain valid while the reader is used.
+    pub unsafe fn new(datum: pg_sys::Datum, chunk_size: usize) -> Self {
+        let len = toast_raw_size(datum) - pg_sys::VARHDRSZ;
+        DetoastReader {
+            datum,
+            len,
+            offset: 0,
+            chunk_size: chunk_size.max(1),
+            chunk: Vec::new(),
+            consumed: 0,
+        }
+    }
+
+    /// The number of bytes in the value, once fully detoasted and without its header
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Is the value empty?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl std::io::BufRead for DetoastReader {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        if self.consumed == self.chunk.len() && self.offset < self.len {
+            let len = self.chunk_size.min(self.len - self.offset);
+            unsafe {
+                // SAFETY:  the caller of `new()` promised `datum` is a valid varlena
+                let ptr = self.datum.cast_mut_ptr::<pg_sys::varlena>();
+                let slice = pg_sys::pg_detoast_datum_slice(ptr, self.offset as i32, len as i32);
+                self.chunk.clear();
+                self.chunk.extend_from_slice(varlena_to_byte_slice(slice));
+                if slice != ptr {
+                    pg_sys::pfree(slice.cast());
+                }
+            }
+            self.offset += self.chunk.len();
+            self.consumed = 0;
+        }
+        Ok(&self.chunk[self.consumed..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.consumed = (self.consumed + amt).min(self.chunk.len());
+    }
+}
+
+impl std::io::Read for DetoastReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        use std::io::BufRead;
+        let available = self.fill_buf()?;
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+/// Convert a Rust `&str` into a Postgres `text *`, transcoded into the database's encoding.
 ///
 /// This allocates the returned Postgres `text *` in `CurrentMemoryContext`.
 #[inline]
 pub fn rust_str_to_text_p(s: &str) -> PgBox<pg_sys::varlena> {
-    let bytea = rust_byte_slice_to_bytea(s.as_bytes());
+    let bytea = utf8_to_server(s, rust_byte_slice_to_bytea);
 
     // a pg_sys::bytea is a type alias for pg_sys::varlena so this cast is fine
     // SAFETY: bytea will be a valid pointer
@@ -375,3 +697,145 @@ pub fn rust_byte_slice_to_bytea(slice: &[u8]) -> PgBox<pg_sys::bytea> {
         ))
     }
 }
+
+/// A method Postgres can compress a varlena with, for [`compress_varlena`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ToastCompression {
+    /// Postgres' own LZ-family compression, its default
+    #[default]
+    Pglz,
+    /// LZ4, which is faster than `Pglz` but needs Postgres 14+ built `--with-lz4`.  Postgres
+    /// raises an ERROR when asked to use it otherwise.  Before Postgres 14, `Pglz` is used
+    /// instead.
+    Lz4,
+}
+
+impl ToastCompression {
+    /// The method's name, as `ALTER TABLE ... SET COMPRESSION` spells it
+    pub fn name(&self) -> &'static str {
+        match self {
+            ToastCompression::Pglz => "pglz",
+            ToastCompression::Lz4 => "lz4",
+        }
+    }
+}
+
+/// Compress a varlena inline, as Postgres would before storing it in a table.
+///
+/// Postgres keeps a value compressed this way when storing it in a column whose `STORAGE` is
+/// `extended` or `main`, rather than choosing the column's compression method, so this is how a
+/// function can pick a method for the values it returns.
+///
+/// Returns `None` if `varlena` is already compressed or TOASTed, or if compressing it wouldn't
+/// save enough space to be worthwhile.  The compressed copy is allocated in
+/// `CurrentMemoryContext`.
+///
+/// ## Safety
+///
+/// This function is unsafe because it blindly assumes the provided varlena pointer is non-null
+/// and valid.
+pub unsafe fn compress_varlena(
+    varlena: *const pg_sys::varlena,
+    compression: ToastCompression,
+) -> Option<PgBox<pg_sys::varlena>> {
+    if varatt_is_1b_e(varlena) || varatt_is_b8_c(varlena) {
+        return None;
+    }
+
+    #[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13"))]
+    let compressed = {
+        let _ = compression;
+        pg_sys::toast_compress_datum(varlena.into())
+    };
+    #[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+    let compressed = {
+        let cmethod = match compression {
+            ToastCompression::Pglz => pg_sys::TOAST_PGLZ_COMPRESSION,
+            ToastCompression::Lz4 => pg_sys::TOAST_LZ4_COMPRESSION,
+        };
+        pg_sys::toast_compress_datum(varlena.into(), cmethod as std::os::raw::c_char)
+    };
+
+    let compressed = compressed.cast_mut_ptr::<pg_sys::varlena>();
+    (!compressed.is_null()).then(|| PgBox::from_pg(compressed))
+}
+
+/// Convert a Rust `&[u8]` into a Postgres `bytea *`, compressed with `compression` if that saves
+/// space.
+///
+/// This allocates the returned Postgres `bytea *` in `CurrentMemoryContext`.
+pub fn rust_byte_slice_to_compressed_bytea(
+    slice: &[u8],
+    compression: ToastCompression,
+) -> PgBox<pg_sys::bytea> {
+    let bytea = rust_byte_slice_to_bytea(slice);
+    // SAFETY: bytea is a valid, uncompressed varlena
+    match unsafe { compress_varlena(bytea.as_ptr(), compression) } {
+        Some(compressed) => {
+            // SAFETY: bytea was palloc'd by cstring_to_text_with_len() and we're done with it
+            unsafe { pg_sys::pfree(bytea.into_pg().cast()) };
+            compressed
+        }
+        None => bytea,
+    }
+}
+
+/// The size of a varlena Datum as stored, compressed or not, as `pg_column_size()` reports it.
+///
+/// For a value stored out of line, this is the size of its data in the TOAST table, without
+/// the TOAST pointer's own header.
+///
+/// ## Safety
+///
+/// This function is unsafe because it blindly assumes the provided Datum is a non-null varlena.
+#[inline]
+pub unsafe fn toast_size(datum: pg_sys::Datum) -> usize {
+    pg_sys::toast_datum_size(datum)
+}
+
+/// The size of a varlena Datum once fully detoasted, including its 4-byte header, found without
+/// detoasting it.
+///
+/// ## Safety
+///
+/// This function is unsafe because it blindly assumes the provided Datum is a non-null varlena.
+#[inline]
+pub unsafe fn toast_raw_size(datum: pg_sys::Datum) -> usize {
+    pg_sys::toast_raw_datum_size(datum)
+}
+
+/// How a varlena Datum is compressed, whether inline or in its TOAST table, or `None` if it
+/// isn't.
+///
+/// ## Safety
+///
+/// This function is unsafe because it blindly assumes the provided Datum is a non-null varlena.
+pub unsafe fn datum_compression(datum: pg_sys::Datum) -> Option<ToastCompression> {
+    let ptr = datum.cast_mut_ptr::<pg_sys::varlena>();
+
+    #[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13"))]
+    {
+        let compressed = if varatt_is_b8_c(ptr) {
+            true
+        } else if varatt_is_1b_e(ptr)
+            && vartag_external(ptr) as pg_sys::vartag_external
+                == pg_sys::vartag_external_VARTAG_ONDISK
+        {
+            // VARATT_EXTERNAL_IS_COMPRESSED()
+            let external = vardata_1b_e(ptr).cast::<pg_sys::varatt_external>().read_unaligned();
+            external.va_extsize < external.va_rawsize - pg_sys::VARHDRSZ as i32
+        } else {
+            false
+        };
+        compressed.then_some(ToastCompression::Pglz)
+    }
+
+    #[cfg(any(feature = "pg14", feature = "pg15", feature = "pg16"))]
+    {
+        match pg_sys::toast_get_compression_id(ptr) {
+            pg_sys::ToastCompressionId_TOAST_PGLZ_COMPRESSION_ID => Some(ToastCompression::Pglz),
+            pg_sys::ToastCompressionId_TOAST_LZ4_COMPRESSION_ID => Some(ToastCompression::Lz4),
+            _ => None,
+        }
+    }
+}
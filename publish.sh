@@ -16,6 +16,7 @@ set -x
 cd $DIR/pgrx-pg-config && cargo publish
 cd $DIR/pgrx-sql-entity-graph && cargo publish
 cd $DIR/pgrx-macros && cargo publish
+cd $DIR/pgrx-bindgen && cargo publish
 cd $DIR/pgrx-pg-sys && cargo publish --no-verify
 cd $DIR/pgrx && cargo publish --no-verify
 cd $DIR/pgrx-tests && cargo publish --no-verify